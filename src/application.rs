@@ -1,17 +1,147 @@
-use crate::config::{ConfigManager, LoggingConfig};
-use crate::monitor::Monitor;
+use crate::config::{
+    ApplicationConfig, BarOptions, ConfigManager, LayoutConfig, LoggingConfig, ModuleConfig,
+    NiriBarConfig,
+};
+use crate::control::{self, ControlHandler, ControlRequest, ControlServer};
+use crate::logger::NiriBarLogger;
+use crate::modules;
+use crate::modules::wallpaper::WallpaperFollower;
+use crate::monitor::{Monitor, MonitorInfo};
+use crate::niri::{NiriEvent, NiriIpc, niri_bus};
+use crate::theme;
+use crate::wallpaper::{SystemWallpaperExecutor, WallpaperSwitcher};
+use anyhow::{anyhow, bail};
 use gdk4::{Display, Monitor as GdkMonitor};
 use gtk4::Application as GtkApplication;
 use gtk4::prelude::*;
 use notify::{Config as NotifyConfig, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::cell::RefCell;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
 
 use glib::ControlFlow;
 use std::time::Duration;
 
+/// Everything one monitor's bar is built from, compared across reloads to find the bars a
+/// config change actually touches
+#[derive(Debug, PartialEq)]
+struct MonitorView {
+    enabled: bool,
+    hide_when_disabled: bool,
+    theme: String,
+    bar_options: BarOptions,
+    layout: Option<LayoutConfig>,
+    workspace_layouts: Vec<(String, Option<LayoutConfig>)>,
+    module_formats: HashMap<String, String>,
+    module_configs: HashMap<String, ModuleConfig>,
+}
+
+/// Connectors whose bars must be created or torn down to match the connected monitors
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MonitorDelta {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl MonitorDelta {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// What [`Application::shutdown`] tore down
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// The niri IPC event-stream reader was told to stop
+    pub ipc_stopped: bool,
+    /// The file watcher task was aborted
+    pub watcher_aborted: bool,
+    /// Number of bar windows closed
+    pub bars_closed: usize,
+}
+
+/// A monitor's bar going into a config reload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExistingBar {
+    /// No bar on the monitor
+    Missing,
+    /// A bar, shown or hidden by the user
+    Present,
+    /// A bar kept hidden because the monitor was disabled
+    HiddenWhileDisabled,
+}
+
+/// What a config reload does with a monitor's bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarReloadAction {
+    /// Build a bar and show it
+    Create,
+    /// Rebuild the bar in place
+    Update,
+    /// Rebuild the bar kept hidden while the monitor was disabled, then show it
+    Reshow,
+    /// Hide the bar and keep it for when the monitor is enabled again
+    Hide,
+    /// Destroy the bar and forget the monitor
+    Destroy,
+    /// Leave things as they are
+    Keep,
+}
+
+/// Application state that control socket commands act on
+struct ControlTarget<'a> {
+    gtk_app: &'a GtkApplication,
+    monitors: &'a Arc<Mutex<HashMap<String, Monitor>>>,
+    config_manager: &'a ConfigManager,
+}
+
+impl ControlHandler for ControlTarget<'_> {
+    fn reload(&mut self) -> anyhow::Result<()> {
+        Application::reload_configuration_and_update_bars(
+            self.gtk_app,
+            self.monitors,
+            self.config_manager,
+            true,
+        )
+        .map_err(|e| anyhow!("{}", e))
+    }
+
+    fn toggle_bar(&mut self, connector: &str) -> anyhow::Result<()> {
+        let mut monitors = self.monitors.lock().unwrap();
+        let monitor = monitors
+            .get_mut(connector)
+            .ok_or_else(|| anyhow!("no bar on '{}'", connector))?;
+        let visible = monitor.toggle_bar();
+        log::info!(
+            "Application: Bar on '{}' is now {}",
+            connector,
+            if visible { "shown" } else { "hidden" }
+        );
+        Ok(())
+    }
+
+    fn set_theme(&mut self, theme: &str) -> anyhow::Result<()> {
+        if theme::resolve_theme_file(&theme::theme_search_dirs_from_env(), theme).is_none() {
+            bail!("theme '{}' not found", theme);
+        }
+        // The config holds the active theme, so bars built later and the theme file
+        // watcher follow it too
+        self.config_manager.set_theme(theme)?;
+        for monitor in self.monitors.lock().unwrap().values_mut() {
+            monitor.update_theme(theme);
+        }
+        Ok(())
+    }
+}
+
+/// Linux signal numbers that trigger a clean shutdown
+const SIGINT: i32 = 2;
+const SIGTERM: i32 = 15;
+
 /// Main application class that manages the entire niri-bar program
 pub struct Application {
     gtk_app: Option<GtkApplication>,
@@ -19,6 +149,12 @@ pub struct Application {
     config_manager: ConfigManager,
     logging_config: LoggingConfig,
     runtime: Runtime,
+    /// Niri IPC handle once the event stream is attached, so shutdown can stop its reader
+    niri_ipc: Arc<Mutex<Option<NiriIpc>>>,
+    /// Background task running the config/theme file watcher
+    watcher_task: Option<JoinHandle<()>>,
+    /// Listener for runtime commands on `$XDG_RUNTIME_DIR/niri-bar.sock`
+    control_server: Option<ControlServer>,
 }
 
 impl Application {
@@ -38,6 +174,10 @@ impl Application {
     ///     include_file: true,
     ///     include_line: true,
     ///     include_class: true,
+    ///     max_size_bytes: 10 * 1024 * 1024,
+    ///     max_files: 5,
+    ///     module_levels: Default::default(),
+    ///     journald: false,
     /// };
     ///
     /// let app = Application::new(logging_config).unwrap();
@@ -79,6 +219,9 @@ impl Application {
             config_manager,
             logging_config,
             runtime,
+            niri_ipc: Arc::new(Mutex::new(None)),
+            watcher_task: None,
+            control_server: None,
         })
     }
 
@@ -93,17 +236,29 @@ impl Application {
                     let app = gtk_app.clone();
                     let monitors = self.monitors.clone();
                     let config_manager = self.config_manager.clone();
+                    let niri_ipc = self.niri_ipc.clone();
                     move |gtk_app| {
                         log::info!("Application: 🎯 GTK Application activated!");
                         Self::on_application_activate(gtk_app, &app, &monitors, &config_manager);
+                        Self::setup_fullscreen_tracking(&monitors);
+                        Self::setup_workspace_layouts(&monitors, &config_manager);
+                        Self::setup_wallpapers(&monitors, &config_manager);
+                        Self::setup_monitor_hotplug(gtk_app, &monitors, &config_manager);
                         // Start Niri IPC event stream if NIRI_SOCKET is set
                         if std::env::var("NIRI_SOCKET").is_ok() {
-                            match crate::niri::NiriIpc::new() {
+                            match NiriIpc::new() {
                                 Ok(niri) => {
                                     if let Err(e) = niri.start_event_stream() {
                                         log::warn!("Application: Niri IPC event stream failed to start: {}", e);
                                     } else {
-                                        log::info!("Application: 🛰️ Niri IPC event stream attached (dumping to stdout)");
+                                        log::info!("Application: 🛰️ Niri IPC event stream attached");
+                                        let fetcher = niri.clone();
+                                        std::thread::spawn(move || {
+                                            if let Err(e) = fetcher.fetch_initial_state() {
+                                                log::warn!("Application: Niri initial state fetch failed: {}", e);
+                                            }
+                                        });
+                                        *niri_ipc.lock().unwrap() = Some(niri);
                                     }
                                 }
                                 Err(e) => log::warn!("Application: Niri IPC init failed: {}", e),
@@ -117,13 +272,17 @@ impl Application {
 
             // Set up a timer to check for config changes periodically
             self.setup_config_checking();
+            self.setup_signal_handlers();
+            self.setup_control_socket();
 
             log::info!("Application: 🚀 Starting GTK main loop...");
 
-            // Start the GTK main loop (this will trigger activation)
+            // Start the GTK main loop (this will trigger activation); our own flags
+            // (e.g. --config) are parsed in main, so GTK doesn't get to see them
             if let Some(ref gtk_app) = self.gtk_app {
-                gtk_app.run();
+                gtk_app.run_with_args::<&str>(&[]);
             }
+            self.shutdown();
         } else {
             log::info!("Application: Skipping GTK initialization (test mode)");
         }
@@ -132,13 +291,111 @@ impl Application {
         Ok(())
     }
 
-    /// Set up file watching for configuration and CSS changes
-    fn setup_config_checking(&mut self) {
+    /// Quit the GTK main loop on SIGINT/SIGTERM; `run` then shuts down cleanly
+    fn setup_signal_handlers(&self) {
+        let Some(gtk_app) = self.gtk_app.clone() else {
+            return;
+        };
+        for (signal, name) in [(SIGINT, "SIGINT"), (SIGTERM, "SIGTERM")] {
+            let gtk_app = gtk_app.clone();
+            glib::unix_signal_add_local(signal, move || {
+                log::info!("Application: 🛑 {} received, quitting", name);
+                gtk_app.quit();
+                ControlFlow::Break
+            });
+        }
+    }
+
+    /// Listen for control commands and run them on the GTK thread
+    fn setup_control_socket(&mut self) {
+        let Some(gtk_app) = self.gtk_app.clone() else {
+            return;
+        };
+        let Some(path) = control::control_socket_path_from_env() else {
+            log::info!("Application: XDG_RUNTIME_DIR not set; no control socket");
+            return;
+        };
+        // Each command runs here and its outcome goes back to the waiting connection
+        let monitors = self.monitors.clone();
+        let config_manager = self.config_manager.clone();
+        let requests = modules::gtk_receiver(move |requests: Vec<ControlRequest>| {
+            for (command, done) in requests {
+                let mut target = ControlTarget {
+                    gtk_app: &gtk_app,
+                    monitors: &monitors,
+                    config_manager: &config_manager,
+                };
+                let result = control::dispatch(&mut target, &command);
+                if let Err(e) = &result {
+                    log::warn!("Application: Control command {:?} failed: {}", command, e);
+                }
+                let _ = done.send(result);
+            }
+            ControlFlow::Continue
+        });
+        match ControlServer::start(path, requests) {
+            Ok(server) => self.control_server = Some(server),
+            Err(e) => log::warn!("Application: Control socket unavailable: {}", e),
+        }
+    }
+
+    /// Stop background work and close every bar: stops the niri IPC reader, aborts the file
+    /// watcher task and destroys the bar windows (releasing their CSS providers). Safe to
+    /// call more than once; later calls find nothing left to tear down.
+    pub fn shutdown(&mut self) -> ShutdownReport {
+        log::info!("Application: 🧹 Shutting down...");
+        let ipc_stopped = match self.niri_ipc.lock().unwrap().take() {
+            Some(ipc) => {
+                ipc.stop();
+                true
+            }
+            None => false,
+        };
+        let watcher_aborted = match self.watcher_task.take() {
+            Some(task) => {
+                task.abort();
+                true
+            }
+            None => false,
+        };
+        if let Some(server) = self.control_server.take() {
+            server.stop();
+        }
+        let bars_closed = {
+            let mut monitors = self.monitors.lock().unwrap();
+            let count = monitors.values().filter(|m| m.has_bar()).count();
+            for monitor in monitors.values_mut() {
+                monitor.destroy_bar();
+            }
+            monitors.clear();
+            count
+        };
+        let report = ShutdownReport {
+            ipc_stopped,
+            watcher_aborted,
+            bars_closed,
+        };
+        log::info!("Application: Shutdown finished: {:?}", report);
+        report
+    }
+
+    /// Hand the application a running niri IPC handle to stop on shutdown
+    pub fn attach_niri_ipc(&self, ipc: NiriIpc) {
+        *self.niri_ipc.lock().unwrap() = Some(ipc);
+    }
+
+    /// Set up file watching for configuration and CSS changes. The watcher task starts
+    /// even without GTK; only the reload timer needs the GTK main loop.
+    pub fn setup_config_checking(&mut self) {
         // Channel of changed file paths -> GTK thread
         let (tx, rx) = tokio::sync::mpsc::channel::<String>(100);
+        // Theme and included config files to watch -> watcher task; sent whenever that set
+        // changes
+        let (watch_tx, mut watch_rx) = tokio::sync::mpsc::unbounded_channel::<Vec<PathBuf>>();
+        let config_path = self.config_manager.config_path().to_path_buf();
 
         // Spawn file watchers in background
-        self.runtime.spawn(async move {
+        self.watcher_task = Some(self.runtime.spawn(async move {
             let mut watcher = RecommendedWatcher::new(
                 move |res| match res {
                     Ok(Event { paths, .. }) => {
@@ -155,35 +412,35 @@ impl Application {
             .unwrap();
 
             // Watch YAML configuration file
-            if let Err(e) = watcher.watch(
-                PathBuf::from("niri-bar.yaml").as_path(),
-                RecursiveMode::NonRecursive,
-            ) {
-                log::error!("Application: Failed to watch niri-bar.yaml: {}", e);
-            }
-
-            // Watch CSS theme files
-            let css_files = [
-                "themes/wombat.css",
-                "themes/solarized.css",
-                "themes/dracula.css",
-            ];
-            for css_file in &css_files {
-                if let Err(e) = watcher.watch(
-                    PathBuf::from(css_file).as_path(),
-                    RecursiveMode::NonRecursive,
-                ) {
-                    log::error!("Application: Failed to watch {}: {}", css_file, e);
-                }
+            if let Err(e) = watcher.watch(&config_path, RecursiveMode::NonRecursive) {
+                log::error!(
+                    "Application: Failed to watch {}: {}",
+                    config_path.display(),
+                    e
+                );
             }
 
             log::info!("Application: 🔍 File watchers started");
 
-            // Keep the watcher alive
-            loop {
-                tokio::time::sleep(Duration::from_secs(1)).await;
+            // Swap the watched CSS and include files as the theme and includes change; this
+            // also keeps the watcher alive for as long as the GTK side holds the sender
+            let mut watched: Vec<PathBuf> = Vec::new();
+            while let Some(paths) = watch_rx.recv().await {
+                for old in watched.iter().filter(|p| !paths.contains(p)) {
+                    let _ = watcher.unwatch(old);
+                }
+                for file in paths.iter().filter(|p| !watched.contains(p)) {
+                    if let Err(e) = watcher.watch(file, RecursiveMode::NonRecursive) {
+                        log::error!("Application: Failed to watch {}: {}", file.display(), e);
+                    }
+                }
+                log::info!(
+                    "Application: 🎨 Watching theme and include files: {:?}",
+                    paths
+                );
+                watched = paths;
             }
-        });
+        }));
 
         // Handle file change events in GTK main thread (recurring)
         if let Some(ref gtk_app) = self.gtk_app {
@@ -192,8 +449,41 @@ impl Application {
             let config_manager = self.config_manager.clone();
             // Receiver must be mutable across calls; wrap in RefCell
             let rx = std::cell::RefCell::new(rx);
+            let mut watched_theme: Option<String> = None;
+            let mut theme_paths: Vec<PathBuf> = Vec::new();
+            // Recomputed at start and after every reload, since an edit can change them
+            let mut include_paths: Option<Vec<PathBuf>> = None;
 
             glib::timeout_add_local(Duration::from_millis(250), move || {
+                // (Re)register theme watches once a config is loaded and whenever its theme changes
+                let theme_update = config_manager.get_config_ref().and_then(|guard| {
+                    guard
+                        .as_ref()
+                        .filter(|c| watched_theme.as_deref() != Some(c.application.theme.as_str()))
+                        .map(|c| {
+                            let dirs = theme::theme_search_dirs_from_env();
+                            (
+                                c.application.theme.clone(),
+                                theme::theme_watch_paths(c, &dirs),
+                            )
+                        })
+                });
+                let mut rewatch = false;
+                if let Some((theme_name, paths)) = theme_update {
+                    theme_paths = paths;
+                    watched_theme = Some(theme_name);
+                    rewatch = true;
+                }
+                if include_paths.is_none() {
+                    include_paths = Some(Self::config_include_paths(&config_manager));
+                    rewatch = true;
+                }
+                if rewatch {
+                    let mut paths = theme_paths.clone();
+                    paths.extend(include_paths.iter().flatten().cloned());
+                    let _ = watch_tx.send(paths);
+                }
+
                 // Drain pending file-change events
                 let mut changed_paths: Vec<String> = Vec::new();
                 while let Ok(p) = rx.borrow_mut().try_recv() {
@@ -206,10 +496,16 @@ impl Application {
                         changed_paths.join(", ")
                     );
                     log::info!("Application: 🔄 Reloading config because files went glow-up...");
+                    let config_files = include_paths.take().unwrap_or_default();
+                    let theme_files_changed = changed_paths.iter().any(|p| {
+                        Path::new(p) != config_manager.config_path()
+                            && !config_files.iter().any(|f| f == Path::new(p))
+                    });
                     if let Err(e) = Self::reload_configuration_and_update_bars(
                         &gtk_app,
                         &monitors,
                         &config_manager,
+                        theme_files_changed,
                     ) {
                         log::error!("Application: Failed to reload configuration: {}", e);
                     }
@@ -218,133 +514,569 @@ impl Application {
                 ControlFlow::Continue
             });
         } else {
-            log::info!("Application: Skipping file watching (test mode - no GTK)");
+            log::info!("Application: Skipping the reload timer (test mode - no GTK)");
         }
     }
 
-    /// Reload configuration and update all bars
+    /// Files the config file currently includes, for the watcher
+    fn config_include_paths(config_manager: &ConfigManager) -> Vec<PathBuf> {
+        let path = config_manager.config_path();
+        std::fs::read(path)
+            .map(|content| crate::config::config_include_paths(&content, path))
+            .unwrap_or_default()
+    }
+
+    /// Reload the config and bring the bars in line with it. Only bars whose effective
+    /// settings changed are rebuilt; `theme_files_changed` re-applies the CSS on all of them.
     fn reload_configuration_and_update_bars(
         gtk_app: &GtkApplication,
         monitors: &Arc<Mutex<HashMap<String, Monitor>>>,
         config_manager: &ConfigManager,
+        theme_files_changed: bool,
     ) -> Result<(), Box<dyn std::error::Error>> {
         log::info!("Application: 🔄 Reloading configuration...");
 
-        // Load new configuration
-        let config_content = std::fs::read("niri-bar.yaml")?;
-        let config = ConfigManager::parse_config(&config_content)?;
+        // Load new configuration, keeping the last applied one to diff against
+        let previous = config_manager.get_config();
+        let config_content = std::fs::read(config_manager.config_path())?;
+        let config = match config_manager.apply_config(&config_content) {
+            Ok(config) => config,
+            Err(e) => {
+                // Keep the old bars up, but flag the broken config on each of them
+                let error = config_manager.last_error();
+                for monitor in monitors.lock().unwrap().values() {
+                    monitor.set_config_error(error.as_deref());
+                }
+                return Err(e.into());
+            }
+        };
         log::info!(
             "Application: 📋 Loaded configuration with theme: '{}'",
             config.application.theme
         );
+        Self::apply_logging_config(&config.logging);
+        for monitor in monitors.lock().unwrap().values() {
+            monitor.set_config_error(None);
+        }
 
-        // Update config manager
-        {
-            let mut config_guard = config_manager.config.lock().unwrap();
-            *config_guard = Some(config.clone());
+        Self::update_bars(
+            gtk_app,
+            monitors,
+            config_manager,
+            previous.as_ref(),
+            &config,
+            theme_files_changed,
+        );
+        log::info!("Application: ✅ Configuration reload complete");
+        Ok(())
+    }
+
+    /// Hand the config's `logging` section to the installed logger
+    fn apply_logging_config(logging: &LoggingConfig) {
+        match NiriBarLogger::reconfigure(logging.clone()) {
+            Ok(true) => log::info!(
+                "Application: 📝 Logging reconfigured (level {}, format {})",
+                logging.level,
+                logging.format
+            ),
+            Ok(false) => {}
+            Err(e) => log::warn!(
+                "Application: Failed to apply the logging config, keeping the old one: {}",
+                e
+            ),
         }
+    }
 
+    /// Bring the bars in line with the connected monitors and `config` (already applied to
+    /// `config_manager`); only bars whose settings differ from `previous` are rebuilt
+    fn update_bars(
+        gtk_app: &GtkApplication,
+        monitors: &Arc<Mutex<HashMap<String, Monitor>>>,
+        config_manager: &ConfigManager,
+        previous: Option<&NiriBarConfig>,
+        config: &NiriBarConfig,
+        theme_files_changed: bool,
+    ) {
         // Get current monitors from GTK
         if let Some(display) = Display::default() {
-            let gdk_monitors = display.monitors();
-            let n_monitors = gdk_monitors.n_items();
+            let gdk_monitors = Self::connected_monitors(&display.monitors());
+            let connected: Vec<String> = gdk_monitors.iter().map(|(c, _)| c.clone()).collect();
+
+            // Tear down bars for monitors that were unplugged
+            let known: Vec<String> = monitors.lock().unwrap().keys().cloned().collect();
+            let delta = Self::sync_monitors(&known, &connected);
+            if !delta.is_empty() {
+                log::info!(
+                    "Application: 🖥️ Monitor set changed (added: {:?}, removed: {:?})",
+                    delta.added,
+                    delta.removed
+                );
+            }
+            let monitor_infos: Vec<MonitorInfo> = gdk_monitors
+                .iter()
+                .map(|(connector, gdk_monitor)| {
+                    MonitorInfo::from_gdk_monitor(connector, gdk_monitor)
+                })
+                .collect();
+            for info in &monitor_infos {
+                config_manager.set_monitor_info(info.clone());
+            }
+            for connector in &delta.removed {
+                config_manager.remove_monitor_info(connector);
+                if let Some(mut removed_monitor) = monitors.lock().unwrap().remove(connector) {
+                    log::info!("Application: ➖ Removing unplugged monitor '{}'", connector);
+                    removed_monitor.destroy_bar();
+                }
+            }
 
-            for i in 0..n_monitors {
-                if let Some(monitor_obj) = gdk_monitors.item(i)
-                    && let Ok(gdk_monitor) = monitor_obj.downcast::<GdkMonitor>()
+            let affected =
+                Self::affected_monitors_with_infos(previous, config, &connected, &monitor_infos);
+            for (connector, gdk_monitor) in gdk_monitors {
+                if !affected.contains(&connector)
+                    && let Some(monitor) = monitors.lock().unwrap().get_mut(&connector)
                 {
-                    let connector = gdk_monitor
-                        .connector()
-                        .unwrap_or_else(|| "Unknown".into())
-                        .to_string();
-
-                    let logical_size = {
-                        let geometry = gdk_monitor.geometry();
-                        (geometry.width(), geometry.height())
-                    };
+                    log::debug!("Application: Bar on '{}' unchanged", connector);
+                    if theme_files_changed {
+                        monitor.update_theme(&config.application.theme);
+                    }
+                    continue;
+                }
 
-                    let scale_factor = gdk_monitor.scale_factor();
+                let logical_size = {
+                    let geometry = gdk_monitor.geometry();
+                    (geometry.width(), geometry.height())
+                };
 
-                    // Check if monitor should display a bar (renamed from enabled)
-                    let should_enable = config_manager.is_monitor_enabled(&connector);
-                    let new_theme = &config.application.theme;
+                let scale_factor = gdk_monitor.scale_factor();
 
-                    // Update or create monitor
-                    let mut monitors_guard = monitors.lock().unwrap();
+                // Check if monitor should display a bar (renamed from enabled)
+                let should_enable = config_manager.is_monitor_enabled(&connector);
+                let new_theme = &config.application.theme;
+                let bar_options = config_manager.get_bar_options(&connector);
 
-                    if should_enable {
-                        if monitors_guard.contains_key(&connector) {
-                            // Update existing monitor with new theme
-                            log::info!(
-                                "Application: 🔄 Updating existing monitor '{}' with theme '{}'",
-                                connector,
-                                new_theme
-                            );
-                            if let Some(existing_monitor) = monitors_guard.get_mut(&connector) {
-                                existing_monitor.update_theme(new_theme);
-                                // Update columns from layout
-                                let column_specs: Vec<(String, crate::config::ColumnSpec)> =
-                                    config_manager
-                                        .get_monitor_layout(&connector)
-                                        .map(|layout| layout.columns.into_iter().collect())
-                                        .unwrap_or_default();
-                                // Build module format map (single merged format; date_format deprecated)
-                                let module_formats =
-                                    Self::collect_module_formats(config_manager, &connector);
-                                let module_configs =
-                                    Self::collect_module_configs(config_manager, &connector);
-                                existing_monitor.update_columns_with_specs(
-                                    &column_specs,
-                                    &module_formats,
-                                    &module_configs,
-                                );
-                            }
-                        } else {
-                            // Create new monitor
-                            log::info!(
-                                "Application: ➕ Creating new monitor '{}' with theme '{}'",
-                                connector,
-                                new_theme
-                            );
-                            let mut new_monitor = Monitor::new(
-                                connector.clone(),
-                                logical_size,
-                                scale_factor,
-                                gdk_monitor,
-                                gtk_app,
-                                new_theme,
-                            );
-                            // Initialize columns from layout
-                            let column_specs: Vec<(String, crate::config::ColumnSpec)> =
-                                config_manager
-                                    .get_monitor_layout(&connector)
-                                    .map(|layout| layout.columns.into_iter().collect())
-                                    .unwrap_or_default();
+                let hide_when_disabled = config_manager.get_monitor_hide_when_disabled(&connector);
+
+                // Update or create monitor
+                let mut monitors_guard = monitors.lock().unwrap();
+                let existing = match monitors_guard.get(&connector) {
+                    None => ExistingBar::Missing,
+                    Some(m) if m.is_hidden_while_disabled() => ExistingBar::HiddenWhileDisabled,
+                    Some(_) => ExistingBar::Present,
+                };
+
+                match Self::bar_reload_action(should_enable, existing, hide_when_disabled) {
+                    action @ (BarReloadAction::Update | BarReloadAction::Reshow) => {
+                        // Update existing monitor with new theme
+                        log::info!(
+                            "Application: 🔄 Updating existing monitor '{}' with theme '{}'",
+                            connector,
+                            new_theme
+                        );
+                        if let Some(existing_monitor) = monitors_guard.get_mut(&connector) {
+                            existing_monitor.update_theme(new_theme);
+                            // Resize before rebuilding columns so modules lay out at the new height
+                            existing_monitor.apply_bar_options(&bar_options);
+                            existing_monitor.refresh_fullscreen_visibility();
+                            // Update columns from layout
+                            let column_specs =
+                                Self::layout_column_specs(config_manager, &connector);
+                            // Build module format map (single merged format; date_format deprecated)
                             let module_formats =
                                 Self::collect_module_formats(config_manager, &connector);
                             let module_configs =
                                 Self::collect_module_configs(config_manager, &connector);
-                            new_monitor.update_columns_with_specs(
+                            existing_monitor.update_columns_with_specs(
                                 &column_specs,
                                 &module_formats,
                                 &module_configs,
                             );
-                            new_monitor.show_bar();
-                            monitors_guard.insert(connector.clone(), new_monitor);
+                            if action == BarReloadAction::Reshow {
+                                log::info!(
+                                    "Application: 👁️ Showing bar on re-enabled monitor '{}'",
+                                    connector
+                                );
+                                existing_monitor.show_bar();
+                            }
                         }
-                    } else {
-                        // Remove monitor if it exists and should be disabled
+                    }
+                    BarReloadAction::Create => {
+                        // Create new monitor
+                        log::info!(
+                            "Application: ➕ Creating new monitor '{}' with theme '{}'",
+                            connector,
+                            new_theme
+                        );
+                        let mut new_monitor = Monitor::new(
+                            connector.clone(),
+                            logical_size,
+                            scale_factor,
+                            gdk_monitor,
+                            gtk_app,
+                            new_theme,
+                            &bar_options,
+                        );
+                        // Initialize columns from layout
+                        let column_specs = Self::layout_column_specs(config_manager, &connector);
+                        let module_formats =
+                            Self::collect_module_formats(config_manager, &connector);
+                        let module_configs =
+                            Self::collect_module_configs(config_manager, &connector);
+                        new_monitor.update_columns_with_specs(
+                            &column_specs,
+                            &module_formats,
+                            &module_configs,
+                        );
+                        // Don't flash the new bar over a fullscreen window
+                        new_monitor.refresh_fullscreen_visibility();
+                        new_monitor.show_bar();
+                        monitors_guard.insert(connector.clone(), new_monitor);
+                    }
+                    BarReloadAction::Hide => {
+                        // Keep the bar around so enabling the monitor again is just a show
+                        if let Some(monitor) = monitors_guard.get_mut(&connector) {
+                            log::info!(
+                                "Application: 🙈 Hiding bar on disabled monitor '{}'",
+                                connector
+                            );
+                            monitor.hide_while_disabled();
+                        }
+                    }
+                    BarReloadAction::Destroy => {
                         if let Some(mut removed_monitor) = monitors_guard.remove(&connector) {
                             log::info!("Application: ➖ Removing disabled monitor '{}'", connector);
-                            removed_monitor.hide_bar();
+                            removed_monitor.destroy_bar();
                         }
                     }
+                    BarReloadAction::Keep => {}
                 }
             }
         }
+    }
 
-        log::info!("Application: ✅ Configuration reload complete");
-        Ok(())
+    /// Connectors among `connectors` whose bar differs between `old` and `new`: enabled state,
+    /// theme, bar options, layouts or merged module settings. Everything is affected when
+    /// there is no previous config.
+    pub fn affected_monitors(
+        old: Option<&NiriBarConfig>,
+        new: &NiriBarConfig,
+        connectors: &[String],
+    ) -> Vec<String> {
+        Self::affected_monitors_with_infos(old, new, connectors, &[])
+    }
+
+    /// [`Self::affected_monitors`], resolving `match_make`/`match_model`/`match_resolution`
+    /// against `monitor_infos`
+    pub fn affected_monitors_with_infos(
+        old: Option<&NiriBarConfig>,
+        new: &NiriBarConfig,
+        connectors: &[String],
+        monitor_infos: &[MonitorInfo],
+    ) -> Vec<String> {
+        let Some(old) = old else {
+            return connectors.to_vec();
+        };
+        if old == new {
+            return Vec::new();
+        }
+        let old = ConfigManager::from_config(old.clone());
+        let new = ConfigManager::from_config(new.clone());
+        for info in monitor_infos {
+            old.set_monitor_info(info.clone());
+            new.set_monitor_info(info.clone());
+        }
+        connectors
+            .iter()
+            .filter(|c| Self::monitor_view(&old, c) != Self::monitor_view(&new, c))
+            .cloned()
+            .collect()
+    }
+
+    fn monitor_view(config_manager: &ConfigManager, connector: &str) -> MonitorView {
+        let info = config_manager.monitor_info(connector);
+        // by_workspace entries that can apply here, most specific monitor first
+        let workspace_layouts = config_manager
+            .get_config()
+            .map(|config| {
+                let mut monitors: Vec<_> = config
+                    .application
+                    .monitors
+                    .iter()
+                    .filter(|m| m.matches(&info))
+                    .collect();
+                monitors.sort_by_key(|m| std::cmp::Reverse(m.specificity()));
+                monitors
+                    .into_iter()
+                    .map(|m| &m.by_workspace)
+                    .chain(std::iter::once(&config.application.by_workspace))
+                    .flat_map(|by_workspace| {
+                        let mut entries: Vec<_> = by_workspace.iter().collect();
+                        entries.sort();
+                        entries
+                    })
+                    .map(|(key, name)| (key.clone(), config.application.layouts.get(name).cloned()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        MonitorView {
+            enabled: config_manager.is_monitor_enabled(connector),
+            hide_when_disabled: config_manager.get_monitor_hide_when_disabled(connector),
+            theme: config_manager
+                .get_config()
+                .map(|c| c.application.theme)
+                .unwrap_or_default(),
+            bar_options: config_manager.get_bar_options(connector),
+            layout: config_manager.get_monitor_layout(connector),
+            workspace_layouts,
+            module_formats: Self::collect_module_formats(config_manager, connector),
+            module_configs: Self::collect_module_configs(config_manager, connector),
+        }
+    }
+
+    /// Connector names of the monitors GDK currently reports, with their handles. Monitors
+    /// whose connector isn't known yet are left out; hotplug picks them up once it is.
+    fn connected_monitors(list: &gtk4::gio::ListModel) -> Vec<(String, GdkMonitor)> {
+        (0..list.n_items())
+            .filter_map(|i| list.item(i))
+            .filter_map(|obj| obj.downcast::<GdkMonitor>().ok())
+            .filter_map(|m| Some((m.connector()?.to_string(), m)))
+            .collect()
+    }
+
+    /// Decide what a reload does with a monitor's bar: whether the monitor is `enabled` in
+    /// the new config, the bar it has now, and whether disabled monitors keep a hidden bar
+    /// (`hide_when_disabled`) instead of destroying it
+    pub fn bar_reload_action(
+        enabled: bool,
+        existing: ExistingBar,
+        hide_when_disabled: bool,
+    ) -> BarReloadAction {
+        match (enabled, existing) {
+            (true, ExistingBar::Missing) => BarReloadAction::Create,
+            (true, ExistingBar::Present) => BarReloadAction::Update,
+            (true, ExistingBar::HiddenWhileDisabled) => BarReloadAction::Reshow,
+            (false, ExistingBar::Missing) => BarReloadAction::Keep,
+            (false, ExistingBar::HiddenWhileDisabled) if hide_when_disabled => {
+                BarReloadAction::Keep
+            }
+            (false, _) if hide_when_disabled => BarReloadAction::Hide,
+            (false, _) => BarReloadAction::Destroy,
+        }
+    }
+
+    /// Compute which connectors gained or lost a monitor, comparing the connectors we track
+    /// against the connected ones; both lists come back sorted
+    pub fn sync_monitors(known: &[String], connected: &[String]) -> MonitorDelta {
+        let mut added: Vec<String> = connected
+            .iter()
+            .filter(|c| !known.contains(c))
+            .cloned()
+            .collect();
+        let mut removed: Vec<String> = known
+            .iter()
+            .filter(|c| !connected.contains(c))
+            .cloned()
+            .collect();
+        added.sort();
+        added.dedup();
+        removed.sort();
+        MonitorDelta { added, removed }
+    }
+
+    /// Create and destroy bars as monitors are plugged in or removed at runtime, from the
+    /// config already in memory. A monitor can appear before its connector is known; it
+    /// gets its bar once `connector` is set.
+    fn setup_monitor_hotplug(
+        gtk_app: &GtkApplication,
+        monitors: &Arc<Mutex<HashMap<String, Monitor>>>,
+        config_manager: &ConfigManager,
+    ) {
+        let Some(display) = Display::default() else {
+            return;
+        };
+        let sync = Rc::new({
+            let gtk_app = gtk_app.clone();
+            let monitors = monitors.clone();
+            let config_manager = config_manager.clone();
+            move || match config_manager.get_config() {
+                Some(config) => Self::update_bars(
+                    &gtk_app,
+                    &monitors,
+                    &config_manager,
+                    Some(&config),
+                    &config,
+                    false,
+                ),
+                // No config applied yet (the initial load failed): try the file again
+                None => {
+                    if let Err(e) = Self::reload_configuration_and_update_bars(
+                        &gtk_app,
+                        &monitors,
+                        &config_manager,
+                        false,
+                    ) {
+                        log::error!("Application: Failed to update bars after hotplug: {}", e);
+                    }
+                }
+            }
+        });
+        display
+            .monitors()
+            .connect_items_changed(move |list, position, removed, added| {
+                log::info!(
+                    "Application: 🔌 Monitors changed ({} added, {} removed)",
+                    added,
+                    removed
+                );
+                for i in position..position + added {
+                    let Some(monitor) = list.item(i).and_downcast::<GdkMonitor>() else {
+                        continue;
+                    };
+                    if monitor.connector().is_some() {
+                        continue;
+                    }
+                    log::debug!("Application: New monitor has no connector yet, waiting for it");
+                    let handler = Rc::new(RefCell::new(None::<gtk4::glib::SignalHandlerId>));
+                    let id = monitor.connect_notify_local(Some("connector"), {
+                        let (sync, handler) = (sync.clone(), handler.clone());
+                        move |monitor, _| {
+                            if monitor.connector().is_none() {
+                                return;
+                            }
+                            if let Some(id) = handler.borrow_mut().take() {
+                                monitor.disconnect(id);
+                            }
+                            sync();
+                        }
+                    });
+                    handler.replace(Some(id));
+                }
+                sync();
+            });
+    }
+
+    /// Hide/restore bars when the focused window enters or leaves fullscreen
+    fn setup_fullscreen_tracking(monitors: &Arc<Mutex<HashMap<String, Monitor>>>) {
+        let monitors = monitors.clone();
+        modules::on_bus_events(move |events| {
+            let relevant = events.iter().any(|ev| {
+                matches!(
+                    ev,
+                    NiriEvent::WindowsChanged
+                        | NiriEvent::FocusChanged(_)
+                        | NiriEvent::WorkspacesChanged
+                )
+            });
+            if relevant && let Ok(mut guard) = monitors.lock() {
+                for monitor in guard.values_mut() {
+                    monitor.refresh_fullscreen_visibility();
+                }
+            }
+            ControlFlow::Continue
+        });
+    }
+
+    /// Column specs for `connector`: the `by_workspace` layout of its focused workspace if
+    /// focus is on this monitor, else the monitor's layout
+    fn layout_column_specs(
+        config_manager: &ConfigManager,
+        connector: &str,
+    ) -> Vec<(String, crate::config::ColumnSpec)> {
+        // The workspace shown on this output, whether or not the output has focus
+        let active = niri_bus()
+            .workspaces_for_output(connector)
+            .into_iter()
+            .find(|ws| ws.is_active);
+        let layout = match active {
+            Some(ws) => config_manager.get_workspace_layout(connector, &ws),
+            None => config_manager.get_monitor_layout(connector),
+        };
+        layout
+            .map(|layout| layout.columns.into_iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Switch a bar's columns when its monitor shows a workspace with a `by_workspace` layout.
+    /// Every bar is checked on each workspace change: activating a workspace on an unfocused
+    /// output changes that output's layout too.
+    fn setup_workspace_layouts(
+        monitors: &Arc<Mutex<HashMap<String, Monitor>>>,
+        config_manager: &ConfigManager,
+    ) {
+        let monitors = monitors.clone();
+        let config_manager = config_manager.clone();
+        modules::on_bus_events(move |events| {
+            let relevant = events.iter().any(|ev| {
+                matches!(
+                    ev,
+                    NiriEvent::WorkspacesChanged | NiriEvent::FocusChanged(_)
+                )
+            });
+            if !relevant {
+                return ControlFlow::Continue;
+            }
+            let Ok(mut guard) = monitors.lock() else {
+                return ControlFlow::Continue;
+            };
+            for (connector, monitor) in guard.iter_mut() {
+                let column_specs = Self::layout_column_specs(&config_manager, connector);
+                if monitor.column_specs() != column_specs.as_slice() {
+                    log::info!(
+                        "Application: 🧩 Switching layout on '{}' for its active workspace",
+                        connector
+                    );
+                    let module_formats = Self::collect_module_formats(&config_manager, connector);
+                    let module_configs = Self::collect_module_configs(&config_manager, connector);
+                    monitor.update_columns_with_specs(
+                        &column_specs,
+                        &module_formats,
+                        &module_configs,
+                    );
+                }
+            }
+            ControlFlow::Continue
+        });
+    }
+
+    /// Apply `application.wallpapers` (and monitors' `wallpapers`) to every bar's output for
+    /// the workspace it shows. The switcher is rebuilt when a reload changed those settings.
+    fn setup_wallpapers(
+        monitors: &Arc<Mutex<HashMap<String, Monitor>>>,
+        config_manager: &ConfigManager,
+    ) {
+        let monitors = monitors.clone();
+        let config_manager = config_manager.clone();
+        let current: RefCell<Option<(ApplicationConfig, Option<Rc<WallpaperFollower>>)>> =
+            RefCell::new(None);
+        modules::on_bus_events(move |events| {
+            let relevant = events
+                .iter()
+                .any(|ev| matches!(ev, NiriEvent::WorkspacesChanged | NiriEvent::Synchronized));
+            let Some(config) = config_manager.get_config().filter(|_| relevant) else {
+                return ControlFlow::Continue;
+            };
+            let mut current = current.borrow_mut();
+            let stale = current.as_ref().is_none_or(|(applied, _)| {
+                applied.wallpapers != config.application.wallpapers
+                    || applied.monitors != config.application.monitors
+            });
+            if stale {
+                let switcher =
+                    WallpaperSwitcher::from_config(&config.application, SystemWallpaperExecutor);
+                let follower = switcher.has_wallpapers().then(|| {
+                    log::info!("Application: 🖼️ Following workspaces with application.wallpapers");
+                    WallpaperFollower::new(switcher)
+                });
+                *current = Some((config.application, follower));
+            }
+            if let Some((_, Some(follower))) = current.as_ref() {
+                let outputs: Vec<String> = match monitors.lock() {
+                    Ok(guard) => guard.keys().cloned().collect(),
+                    Err(_) => return ControlFlow::Continue,
+                };
+                follower.sync(outputs.iter().map(String::as_str));
+            }
+            ControlFlow::Continue
+        });
     }
 
     /// Collect per-module merged formats for a given monitor.
@@ -354,6 +1086,8 @@ impl Application {
         connector: &str,
     ) -> std::collections::HashMap<String, String> {
         let mut map = std::collections::HashMap::new();
+        // Find best matching monitor config (before taking the config lock it needs)
+        let best = config_manager.best_monitor_config(&config_manager.monitor_info(connector));
         // read current config
         let config_guard = config_manager.config.lock().unwrap();
         if let Some(cfg) = &*config_guard {
@@ -364,19 +1098,7 @@ impl Application {
                 }
             }
             // Overlay per-monitor overrides
-            // Find best matching monitor config
-            let mut best: Option<&crate::config::MonitorConfig> = None;
-            let mut best_spec = 0;
-            for m in &cfg.application.monitors {
-                if ConfigManager::matches_pattern(connector, &m.match_pattern) {
-                    let s = ConfigManager::pattern_specificity(&m.match_pattern);
-                    if s > best_spec {
-                        best = Some(m);
-                        best_spec = s;
-                    }
-                }
-            }
-            if let Some(m) = best
+            if let Some(m) = &best
                 && let Some(mods) = &m.modules
             {
                 for (name, mc) in mods {
@@ -394,6 +1116,8 @@ impl Application {
         connector: &str,
     ) -> std::collections::HashMap<String, crate::config::ModuleConfig> {
         let mut map = std::collections::HashMap::new();
+        // Find best matching monitor config (before taking the config lock it needs)
+        let best = config_manager.best_monitor_config(&config_manager.monitor_info(connector));
         // read current config
         let config_guard = config_manager.config.lock().unwrap();
         if let Some(cfg) = &*config_guard {
@@ -402,84 +1126,15 @@ impl Application {
                 map.insert(name.clone(), mc.clone());
             }
             // Overlay per-monitor overrides
-            // Find best matching monitor config
-            let mut best: Option<&crate::config::MonitorConfig> = None;
-            let mut best_spec = 0;
-            for m in &cfg.application.monitors {
-                if ConfigManager::matches_pattern(connector, &m.match_pattern) {
-                    let s = ConfigManager::pattern_specificity(&m.match_pattern);
-                    if s > best_spec {
-                        best = Some(m);
-                        best_spec = s;
-                    }
-                }
-            }
-            if let Some(m) = best
+            if let Some(m) = &best
                 && let Some(mods) = &m.modules
             {
                 for (name, mc) in mods {
-                    // Merge by replacing entire module config for simplicity
-                    let mut merged = map.get(name).cloned().unwrap_or_default();
-                    // Overlay only explicitly provided fields
-                    if mc.format.is_some() {
-                        merged.format = mc.format.clone();
-                    }
-                    if mc.tooltip.is_some() {
-                        merged.tooltip = mc.tooltip;
-                    }
-                    if mc.highlight_active.is_some() {
-                        merged.highlight_active = mc.highlight_active;
-                    }
-                    if mc.show_numbers.is_some() {
-                        merged.show_numbers = mc.show_numbers;
-                    }
-                    if mc.show_wallpaper.is_some() {
-                        merged.show_wallpaper = mc.show_wallpaper;
-                    }
-                    if mc.max_length.is_some() {
-                        merged.max_length = mc.max_length;
-                    }
-                    if mc.ellipsize.is_some() {
-                        merged.ellipsize = mc.ellipsize.clone();
-                    }
-                    if mc.show_percentage.is_some() {
-                        merged.show_percentage = mc.show_percentage;
-                    }
-                    if mc.warn_threshold.is_some() {
-                        merged.warn_threshold = mc.warn_threshold;
-                    }
-                    if mc.critical_threshold.is_some() {
-                        merged.critical_threshold = mc.critical_threshold;
-                    }
-                    if mc.cpu.is_some() {
-                        merged.cpu = mc.cpu;
-                    }
-                    if mc.mem.is_some() {
-                        merged.mem = mc.mem;
-                    }
-                    if mc.net.is_some() {
-                        merged.net = mc.net;
-                    }
-                    if mc.enabled.is_some() {
-                        merged.enabled = mc.enabled;
-                    }
-                    if mc.display.is_some() {
-                        merged.display = mc.display.clone();
-                    }
-                    // Pass through any additional fields
-                    if !mc.additional.is_empty() {
-                        merged.additional.extend(mc.additional.clone());
-                    }
-                    // Wallpaper-specific per-module overrides if present on monitor
-                    if mc.default_wallpaper.is_some() {
-                        merged.default_wallpaper = mc.default_wallpaper.clone();
-                    }
-                    if mc.wallpapers.is_some() {
-                        merged.wallpapers = mc.wallpapers.clone();
-                    }
-                    if mc.special_cmd.is_some() {
-                        merged.special_cmd = mc.special_cmd.clone();
-                    }
+                    // Overlay only the fields the monitor sets
+                    let merged = match map.get(name) {
+                        Some(global) => global.merged_with(mc),
+                        None => mc.clone(),
+                    };
                     map.insert(name.clone(), merged);
                 }
             }
@@ -520,7 +1175,7 @@ impl Application {
 
         // Initial configuration load and monitor setup
         if let Err(e) =
-            Self::reload_configuration_and_update_bars(gtk_app, monitors, config_manager)
+            Self::reload_configuration_and_update_bars(gtk_app, monitors, config_manager, false)
         {
             log::error!("Application: Failed to load initial configuration: {}", e);
         }
@@ -549,6 +1204,10 @@ impl Application {
     ///     include_file: true,
     ///     include_line: true,
     ///     include_class: true,
+    ///     max_size_bytes: 10 * 1024 * 1024,
+    ///     max_files: 5,
+    ///     module_levels: Default::default(),
+    ///     journald: false,
     /// };
     ///
     /// let app = Application::new(logging_config).unwrap();
@@ -567,4 +1226,9 @@ impl Application {
     pub fn get_config_manager(&self) -> &ConfigManager {
         &self.config_manager
     }
+
+    /// Load and watch the configuration from `config_path`; call before [`Application::run`]
+    pub fn set_config_path(&mut self, config_path: PathBuf) {
+        self.config_manager = ConfigManager::with_config_path(config_path);
+    }
 }
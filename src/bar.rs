@@ -1,14 +1,30 @@
+use crate::bar_model::{BarModel, ColumnExtent, InputRect, input_region, max_width_chars};
+// Layout decisions live in the GTK-free model; re-exported where they were first defined
+pub use crate::bar_model::{
+    ColumnSlot, OverflowDecision, column_budget, column_slots, column_width, decide_overflow,
+};
 // Re-export MonitorInfo for use in tests
-use crate::config::{ColumnOverflowPolicy, ColumnSpec, DisplayMode, ModuleConfig, TextAlign};
+use crate::config::{
+    BarLayer, BarMargins, BarOptions, BarPosition, ColumnOverflowPolicy, ColumnSpec, ModuleConfig,
+    TextAlign,
+};
 use crate::modules::create_module_widget;
 pub use crate::monitor::MonitorInfo;
+use crate::theme::{BASE_THEME, resolve_theme_file, theme_search_dirs_from_env};
 use gdk4::{Display, Monitor as GdkMonitor};
 use gtk4 as gtk;
+use gtk4::cairo::{RectangleInt, Region};
 use gtk4::prelude::*;
 use gtk4::{Application as GtkApplication, ApplicationWindow, CssProvider};
 use gtk4::{ListBox, MenuButton, Popover};
 use gtk4_layer_shell::{Edge, Layer, LayerShell};
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+/// Column boxes of a bar with their `click_through` setting, for the input region
+type InputColumns = Rc<RefCell<Vec<(gtk::Box, bool)>>>;
 
 /// Bar class that manages a single status bar for a monitor
 pub struct Bar {
@@ -17,6 +33,241 @@ pub struct Bar {
     monitor_info: MonitorInfo,
     is_visible: bool,
     css_provider: CssProvider,
+    options: BarOptions,
+    fullscreen_hidden: bool,
+    column_css_provider: Option<CssProvider>,
+    style_css_provider: Option<CssProvider>,
+    error_banner: gtk::Label,
+    input_columns: InputColumns,
+}
+
+/// Something CSS providers are installed on (the GDK display in practice)
+pub trait StyleProviderTarget<P> {
+    fn attach(&self, provider: &P);
+    fn detach(&self, provider: &P);
+}
+
+impl StyleProviderTarget<CssProvider> for Display {
+    fn attach(&self, provider: &CssProvider) {
+        gtk::style_context_add_provider_for_display(
+            self,
+            provider,
+            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+        );
+    }
+
+    fn detach(&self, provider: &CssProvider) {
+        gtk::style_context_remove_provider_for_display(self, provider);
+    }
+}
+
+/// Tracks the single CSS provider each bar has installed, keyed by connector, so theme
+/// reloads replace providers instead of stacking them and destroyed bars leave nothing behind.
+///
+/// GTK4 only supports display-wide providers (per-widget providers are deprecated), so
+/// scoping happens here rather than per window.
+pub struct CssProviderRegistry<P> {
+    providers: HashMap<String, P>,
+}
+
+impl<P> Default for CssProviderRegistry<P> {
+    fn default() -> Self {
+        Self {
+            providers: HashMap::new(),
+        }
+    }
+}
+
+impl<P: PartialEq> CssProviderRegistry<P> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Install `provider` for `key`, detaching whatever that key had before
+    pub fn install(&mut self, key: &str, provider: P, target: &impl StyleProviderTarget<P>) {
+        if let Some(old) = self.providers.remove(key) {
+            target.detach(&old);
+        }
+        target.attach(&provider);
+        self.providers.insert(key.to_string(), provider);
+    }
+
+    /// Detach and forget the provider for `key`, but only if it is still `provider`
+    /// (a replacement bar on the same connector may have installed its own since)
+    pub fn release(
+        &mut self,
+        key: &str,
+        provider: &P,
+        target: &impl StyleProviderTarget<P>,
+    ) -> bool {
+        if self.providers.get(key) != Some(provider) {
+            return false;
+        }
+        if let Some(old) = self.providers.remove(key) {
+            target.detach(&old);
+        }
+        true
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.providers.contains_key(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.providers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+}
+
+thread_local! {
+    /// Providers installed by live bars (GTK objects stay on the GTK thread)
+    static BAR_CSS_PROVIDERS: RefCell<CssProviderRegistry<CssProvider>> =
+        RefCell::new(CssProviderRegistry::new());
+}
+
+/// Number of bar CSS providers currently installed on the display
+pub fn installed_css_provider_count() -> usize {
+    BAR_CSS_PROVIDERS.with_borrow(|r| r.len())
+}
+
+/// CSS for a column's configured background/text colors, scoped to one monitor's column
+pub fn column_color_css(connector: &str, column: &str, spec: &ColumnSpec) -> Option<String> {
+    let background = spec.valid_background();
+    let color = spec.valid_text_color();
+    if background.is_none() && color.is_none() {
+        return None;
+    }
+    let selector = format!(
+        ".bar-columns.monitor-{} > .column.column-{}",
+        connector.replace("-", "_"),
+        column.replace([' ', '-'], "_")
+    );
+    let mut css = String::new();
+    if let Some(bg) = background {
+        css.push_str(&format!("{} {{ background-color: {}; }}\n", selector, bg));
+    }
+    if let Some(fg) = color {
+        css.push_str(&format!(
+            "{} {{ color: {}; }}\n{} label {{ color: {}; }}\n",
+            selector, fg, selector, fg
+        ));
+    }
+    Some(css)
+}
+
+/// CSS for the configured bar opacity and background, scoped to one monitor's window
+pub fn bar_style_css(connector: &str, options: &BarOptions) -> Option<String> {
+    if options.opacity.is_none() && options.background.is_none() {
+        return None;
+    }
+    let mut declarations = String::new();
+    if let Some(background) = &options.background {
+        declarations.push_str(&format!(" background-color: {};", background));
+    }
+    if let Some(opacity) = options.opacity {
+        declarations.push_str(&format!(" opacity: {:.2};", opacity));
+    }
+    Some(format!(
+        "window.monitor-{} {{{} }}\n",
+        connector.replace("-", "_"),
+        declarations
+    ))
+}
+
+/// Popover list for a column's overflowed modules. Nothing is selectable or activated on
+/// click, so the list never claims a press before the module's own controllers see it.
+pub fn kebab_overflow_list() -> ListBox {
+    let list = ListBox::new();
+    list.add_css_class("column-kebab-list");
+    list.set_selection_mode(gtk::SelectionMode::None);
+    list.set_activate_on_single_click(false);
+    list
+}
+
+/// Row wrapping an overflowed module in the kebab list. The module keeps its controllers
+/// (clicks, scrolls, tooltips) and timers across the move; the row itself is inert and
+/// leaves focus to the module.
+pub fn kebab_overflow_row(widget: &gtk::Widget) -> gtk::ListBoxRow {
+    let row = gtk::ListBoxRow::new();
+    row.add_css_class("column-overflow-row");
+    row.set_activatable(false);
+    row.set_selectable(false);
+    row.set_focusable(false);
+    if let Some(parent) = widget.parent() {
+        // Clear any previous placement before the row takes the widget
+        if let Some(parent_box) = parent.downcast_ref::<gtk::Box>() {
+            parent_box.remove(widget);
+        } else {
+            widget.unparent();
+        }
+    }
+    row.set_child(Some(widget));
+    row
+}
+
+/// Layer-shell margin per edge, in the order they are applied
+pub fn margin_edges(margins: &BarMargins) -> [(Edge, i32); 4] {
+    [
+        (Edge::Top, margins.top),
+        (Edge::Left, margins.left),
+        (Edge::Right, margins.right),
+        (Edge::Bottom, margins.bottom),
+    ]
+}
+
+/// Explicit exclusive zone keeping windows clear of the gap on the bar's inner side;
+/// `None` leaves layer-shell's automatic zone (the compositor already counts the
+/// margin on the anchored edge)
+pub fn exclusive_zone(options: &BarOptions) -> Option<i32> {
+    let inner_margin = match options.position {
+        BarPosition::Top => options.margins.bottom,
+        BarPosition::Bottom => options.margins.top,
+    };
+    (inner_margin != 0).then_some(options.height + inner_margin)
+}
+
+/// Whether a bar should be hidden given whether its monitor's focused window is fullscreen
+pub fn hidden_for_fullscreen(options: &BarOptions, focused_window_fullscreen: bool) -> bool {
+    options.auto_hide_on_fullscreen && focused_window_fullscreen
+}
+
+/// Layer-shell layer for a configured bar layer
+pub fn shell_layer(layer: BarLayer) -> Layer {
+    match layer {
+        BarLayer::Background => Layer::Background,
+        BarLayer::Bottom => Layer::Bottom,
+        BarLayer::Top => Layer::Top,
+        BarLayer::Overlay => Layer::Overlay,
+    }
+}
+
+/// Longest config error shown in the banner; the full message goes in its tooltip
+pub const CONFIG_ERROR_BANNER_MAX_CHARS: usize = 120;
+
+/// Single-line banner text for a config error: first line only, cut to `max_chars`
+/// characters with an ellipsis
+pub fn config_error_banner_text(error: &str, max_chars: usize) -> String {
+    let first_line = error.lines().next().unwrap_or("").trim();
+    if first_line.chars().count() <= max_chars {
+        return first_line.to_string();
+    }
+    let mut text: String = first_line
+        .chars()
+        .take(max_chars.saturating_sub(1))
+        .collect();
+    text.push('…');
+    text
+}
+
+/// Layer-shell edge a bar is anchored to for a given position
+pub fn anchor_edge(position: BarPosition) -> Edge {
+    match position {
+        BarPosition::Top => Edge::Top,
+        BarPosition::Bottom => Edge::Bottom,
+    }
 }
 
 impl Bar {
@@ -26,6 +277,7 @@ impl Bar {
         gdk_monitor: &GdkMonitor,
         app: &GtkApplication,
         theme: &str,
+        options: &BarOptions,
     ) -> Self {
         log::info!(
             "Bar: 🎯 Creating bar for monitor: {}",
@@ -43,31 +295,25 @@ impl Bar {
 
         // Initialize layer shell
         window.init_layer_shell();
-        window.set_layer(Layer::Top);
-        window.auto_exclusive_zone_enable();
-        window.set_anchor(Edge::Top, true);
+        window.set_layer(shell_layer(options.layer));
+        Self::apply_anchor(&window, options.position);
         window.set_anchor(Edge::Left, true);
         window.set_anchor(Edge::Right, true);
 
         // Pin to specific monitor
         window.set_monitor(Some(gdk_monitor));
 
-        // Set bar height
-        window.set_default_height(40);
+        // Set bar height, then margins and the exclusive zone that depends on both
+        Self::apply_height(&window, options.height);
+        Self::apply_margins(&window, options);
 
         // Load and apply CSS theme
         let css_provider = CssProvider::new();
         let css_content = Self::load_theme_css(theme);
         css_provider.load_from_data(&css_content);
 
-        // Apply CSS to the display
-        if let Some(display) = Display::default() {
-            gtk::style_context_add_provider_for_display(
-                &display,
-                &css_provider,
-                gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
-            );
-        }
+        // Apply CSS to the display (replacing any provider left for this connector)
+        Self::install_css_provider(&monitor_info.connector, &css_provider);
 
         // Create main container for columns
         let container = gtk::Box::new(gtk::Orientation::Horizontal, 0);
@@ -80,7 +326,22 @@ impl Bar {
             "monitor-{}",
             monitor_info.connector.replace("-", "_")
         ));
-        window.set_child(Some(&container));
+
+        // Config error banner, laid over the bottom of the columns while the YAML is broken
+        let error_banner = gtk::Label::new(None);
+        error_banner.add_css_class("config-error-banner");
+        error_banner.set_halign(gtk::Align::Fill);
+        error_banner.set_valign(gtk::Align::End);
+        error_banner.set_ellipsize(gtk::pango::EllipsizeMode::End);
+        error_banner.set_visible(false);
+
+        let overlay = gtk::Overlay::new();
+        overlay.set_child(Some(&container));
+        overlay.add_overlay(&error_banner);
+        window.set_child(Some(&overlay));
+
+        let input_columns = InputColumns::default();
+        Self::connect_input_region(&window, &input_columns);
 
         log::info!(
             "Bar: ✅ Bar created and pinned to monitor: {} ({}x{}, scale={})",
@@ -90,26 +351,202 @@ impl Bar {
             monitor_info.scale_factor
         );
 
-        Self {
+        let mut bar = Self {
             window,
             container,
             monitor_info: monitor_info.clone(),
             is_visible: false,
             css_provider,
+            options: options.clone(),
+            fullscreen_hidden: false,
+            column_css_provider: None,
+            style_css_provider: None,
+            error_banner,
+            input_columns,
+        };
+        bar.update_style_css();
+        bar
+    }
+
+    /// Follow surface resizes so the input region keeps matching the columns' allocation
+    fn connect_input_region(window: &ApplicationWindow, columns: &InputColumns) {
+        let columns = columns.clone();
+        window.connect_realize(move |window| {
+            let Some(surface) = window.surface() else {
+                return;
+            };
+            let window = window.downgrade();
+            let columns = columns.clone();
+            surface.connect_layout(move |_, _, _| {
+                if let Some(window) = window.upgrade() {
+                    Self::schedule_input_region(&window, &columns);
+                }
+            });
+        });
+    }
+
+    /// Apply the input region once the pending layout pass has allocated the columns
+    fn schedule_input_region(window: &ApplicationWindow, columns: &InputColumns) {
+        let window = window.downgrade();
+        let columns = columns.clone();
+        glib::idle_add_local_once(move || {
+            if let Some(window) = window.upgrade() {
+                Self::apply_input_region(&window, &columns.borrow());
+            }
+        });
+    }
+
+    /// Limit pointer input to the interactive columns (see [`input_region`]); the whole
+    /// surface takes input when no column is click-through
+    fn apply_input_region(window: &ApplicationWindow, columns: &[(gtk::Box, bool)]) {
+        let Some(surface) = window.surface() else {
+            return;
+        };
+        let extents: Vec<ColumnExtent> = columns
+            .iter()
+            .filter_map(|(column, click_through)| {
+                let bounds = column.compute_bounds(window)?;
+                Some(ColumnExtent {
+                    x: bounds.x().round() as i32,
+                    width: bounds.width().round() as i32,
+                    click_through: *click_through,
+                })
+            })
+            .collect();
+        let rects = input_region(&extents, surface.height()).unwrap_or_else(|| {
+            vec![InputRect {
+                x: 0,
+                y: 0,
+                width: surface.width(),
+                height: surface.height(),
+            }]
+        });
+        let rects: Vec<RectangleInt> = rects
+            .iter()
+            .map(|r| RectangleInt::new(r.x, r.y, r.width, r.height))
+            .collect();
+        surface.set_input_region(&Region::create_rectangles(&rects));
+    }
+
+    /// Anchor the window to the edge for `position`; the exclusive zone follows the anchor
+    fn apply_anchor(window: &ApplicationWindow, position: BarPosition) {
+        window.set_anchor(Edge::Top, anchor_edge(position) == Edge::Top);
+        window.set_anchor(Edge::Bottom, anchor_edge(position) == Edge::Bottom);
+    }
+
+    /// Apply margins on every edge and the matching exclusive zone
+    fn apply_margins(window: &ApplicationWindow, options: &BarOptions) {
+        for (edge, margin) in margin_edges(&options.margins) {
+            window.set_margin(edge, margin);
+        }
+        match exclusive_zone(options) {
+            Some(zone) => window.set_exclusive_zone(zone),
+            None => window.auto_exclusive_zone_enable(),
         }
     }
 
+    /// Re-apply position, layer, height, margins, opacity and background (e.g. after a
+    /// config reload)
+    pub fn apply_options(&mut self, options: &BarOptions) {
+        if self.options == *options {
+            return;
+        }
+        log::info!(
+            "Bar: Applying {:?} to bar for monitor {}",
+            options,
+            self.monitor_info.connector
+        );
+        if self.options.position != options.position {
+            Self::apply_anchor(&self.window, options.position);
+        }
+        if self.options.layer != options.layer {
+            self.window.set_layer(shell_layer(options.layer));
+        }
+        if self.options.height != options.height {
+            // Resize first so modules (e.g. viewport) see the new allocated height
+            Self::apply_height(&self.window, options.height);
+        }
+        Self::apply_margins(&self.window, options);
+        let restyle = self.options.opacity != options.opacity
+            || self.options.background != options.background;
+        self.options = options.clone();
+        if restyle {
+            self.update_style_css();
+        }
+    }
+
+    /// Temporarily hide or restore the bar for a fullscreen window. Unlike `hide`, this
+    /// keeps the window (and its theme) alive so the bar can come straight back.
+    pub fn set_fullscreen_hidden(&mut self, hidden: bool) {
+        if self.fullscreen_hidden == hidden {
+            return;
+        }
+        log::info!(
+            "Bar: {} bar for fullscreen window on monitor {}",
+            if hidden { "Hiding" } else { "Restoring" },
+            self.monitor_info.connector
+        );
+        self.fullscreen_hidden = hidden;
+        if self.is_visible {
+            self.window.set_visible(!hidden);
+        }
+    }
+
+    /// Show a config error banner, or clear it with `None` after a successful reload
+    pub fn set_config_error(&self, error: Option<&str>) {
+        match error {
+            Some(error) => {
+                self.error_banner.set_text(&config_error_banner_text(
+                    error,
+                    CONFIG_ERROR_BANNER_MAX_CHARS,
+                ));
+                self.error_banner.set_tooltip_text(Some(error));
+                self.error_banner.set_visible(true);
+            }
+            None => {
+                self.error_banner.set_visible(false);
+                self.error_banner.set_tooltip_text(None);
+            }
+        }
+    }
+
+    /// Whether the bar is currently hidden for a fullscreen window
+    pub fn is_fullscreen_hidden(&self) -> bool {
+        self.fullscreen_hidden
+    }
+
+    /// Get the screen edge the bar is anchored to
+    pub fn position(&self) -> BarPosition {
+        self.options.position
+    }
+
+    /// Get the window settings currently applied to the bar
+    pub fn options(&self) -> &BarOptions {
+        &self.options
+    }
+
     /// Load CSS theme from file
     fn load_theme_css(theme: &str) -> String {
         let mut css_content = String::new();
 
+        let search_dirs = theme_search_dirs_from_env();
+        let read_theme = |name: &str| -> std::io::Result<(PathBuf, String)> {
+            let path = resolve_theme_file(&search_dirs, name).ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("{}.css not found in {:?}", name, search_dirs),
+                )
+            })?;
+            let css = std::fs::read_to_string(&path)?;
+            Ok((path, css))
+        };
+
         // First, load base.css
-        let base_path = "themes/base.css";
-        match std::fs::read_to_string(base_path) {
-            Ok(base_css) => {
+        match read_theme(BASE_THEME) {
+            Ok((path, base_css)) => {
                 css_content.push_str(&base_css);
                 css_content.push('\n');
-                log::info!("Bar: Loaded base CSS");
+                log::info!("Bar: Loaded base CSS from {}", path.display());
             }
             Err(e) => {
                 log::warn!("Bar: Failed to load base.css: {}, using fallback", e);
@@ -135,11 +572,10 @@ impl Bar {
         }
 
         // Then load theme-specific CSS
-        let theme_path = format!("themes/{}.css", theme);
-        match std::fs::read_to_string(&theme_path) {
-            Ok(theme_css) => {
+        match read_theme(theme) {
+            Ok((path, theme_css)) => {
                 css_content.push_str(&theme_css);
-                log::info!("Bar: Loaded CSS theme: {}", theme);
+                log::info!("Bar: Loaded CSS theme: {} ({})", theme, path.display());
             }
             Err(e) => {
                 log::warn!(
@@ -179,27 +615,116 @@ impl Bar {
         css_content
     }
 
-    /// Show the bar
+    fn install_css_provider(connector: &str, provider: &CssProvider) {
+        if let Some(display) = Display::default() {
+            BAR_CSS_PROVIDERS.with_borrow_mut(|r| r.install(connector, provider.clone(), &display));
+        }
+    }
+
+    fn release_css_provider(&self) {
+        if let Some(display) = Display::default() {
+            BAR_CSS_PROVIDERS.with_borrow_mut(|r| {
+                r.release(&self.monitor_info.connector, &self.css_provider, &display);
+                if let Some(provider) = &self.column_css_provider {
+                    r.release(&self.column_css_key(), provider, &display);
+                }
+                if let Some(provider) = &self.style_css_provider {
+                    r.release(&self.style_css_key(), provider, &display);
+                }
+            });
+        }
+    }
+
+    /// Registry key for this bar's per-column color provider
+    fn column_css_key(&self) -> String {
+        format!("{}#columns", self.monitor_info.connector)
+    }
+
+    /// Registry key for this bar's opacity/background provider
+    fn style_css_key(&self) -> String {
+        format!("{}#style", self.monitor_info.connector)
+    }
+
+    /// Install (or drop) the provider carrying the configured opacity and background
+    fn update_style_css(&mut self) {
+        let Some(display) = Display::default() else {
+            return;
+        };
+        let key = self.style_css_key();
+        let Some(css) = bar_style_css(&self.monitor_info.connector, &self.options) else {
+            if let Some(old) = self.style_css_provider.take() {
+                BAR_CSS_PROVIDERS.with_borrow_mut(|r| r.release(&key, &old, &display));
+            }
+            return;
+        };
+        let provider = CssProvider::new();
+        provider.load_from_data(&css);
+        BAR_CSS_PROVIDERS.with_borrow_mut(|r| r.install(&key, provider.clone(), &display));
+        self.style_css_provider = Some(provider);
+    }
+
+    /// Install (or drop) the provider carrying per-column colors from the layout
+    fn update_column_css(&mut self, columns: &[(String, ColumnSpec)]) {
+        let css: String = columns
+            .iter()
+            .filter_map(|(name, spec)| column_color_css(&self.monitor_info.connector, name, spec))
+            .collect();
+        let Some(display) = Display::default() else {
+            return;
+        };
+        let key = self.column_css_key();
+        if css.is_empty() {
+            if let Some(old) = self.column_css_provider.take() {
+                BAR_CSS_PROVIDERS.with_borrow_mut(|r| r.release(&key, &old, &display));
+            }
+            return;
+        }
+        let provider = CssProvider::new();
+        provider.load_from_data(&css);
+        BAR_CSS_PROVIDERS.with_borrow_mut(|r| r.install(&key, provider.clone(), &display));
+        self.column_css_provider = Some(provider);
+    }
+
+    /// Show the bar; while it's hidden for a fullscreen window it stays unmapped and comes
+    /// back once the window leaves fullscreen
     pub fn show(&mut self) {
         if !self.is_visible {
             log::info!(
                 "Bar: Showing bar for monitor: {}",
                 self.monitor_info.connector
             );
-            self.window.present();
+            // Re-install the theme, column colors and style if the bar was hidden before
+            if !BAR_CSS_PROVIDERS.with_borrow(|r| r.contains(&self.monitor_info.connector)) {
+                Self::install_css_provider(&self.monitor_info.connector, &self.css_provider);
+            }
+            if let Some(provider) = &self.column_css_provider
+                && !BAR_CSS_PROVIDERS.with_borrow(|r| r.contains(&self.column_css_key()))
+            {
+                Self::install_css_provider(&self.column_css_key(), provider);
+            }
+            if let Some(provider) = &self.style_css_provider
+                && !BAR_CSS_PROVIDERS.with_borrow(|r| r.contains(&self.style_css_key()))
+            {
+                Self::install_css_provider(&self.style_css_key(), provider);
+            }
+            if !self.fullscreen_hidden {
+                self.window.present();
+            }
             self.is_visible = true;
         }
     }
 
-    /// Hide the bar
+    /// Hide the bar, keeping its window to show again; `destroy` closes it
     pub fn hide(&mut self) {
         if self.is_visible {
             log::info!(
                 "Bar: Hiding bar for monitor: {}",
                 self.monitor_info.connector
             );
-            self.window.close();
+            // Closing would destroy the window and drop it from the application
+            self.window.set_visible(false);
             self.is_visible = false;
+            self.release_css_provider();
         }
     }
 
@@ -211,24 +736,13 @@ impl Bar {
             theme
         );
 
-        // Remove old CSS provider from display
-        if let Some(display) = Display::default() {
-            gtk::style_context_remove_provider_for_display(&display, &self.css_provider);
-        }
-
         // Load new CSS theme
         let new_css_provider = CssProvider::new();
         let css_content = Self::load_theme_css(theme);
         new_css_provider.load_from_data(&css_content);
 
-        // Apply new CSS to the display
-        if let Some(display) = Display::default() {
-            gtk::style_context_add_provider_for_display(
-                &display,
-                &new_css_provider,
-                gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
-            );
-        }
+        // Replace (not stack) this bar's provider on the display
+        Self::install_css_provider(&self.monitor_info.connector, &new_css_provider);
 
         // Update the stored CSS provider
         self.css_provider = new_css_provider;
@@ -276,18 +790,18 @@ impl Bar {
             kebab.add_css_class("column-kebab");
             kebab.add_css_class(&format!("column-kebab-{}", safe));
             let popover = Popover::new();
-            let list = ListBox::new();
-            list.add_css_class("column-kebab-list");
+            let list = kebab_overflow_list();
             popover.set_child(Some(&list));
             kebab.set_popover(Some(&popover));
             kebab.set_visible(false);
             column_box.append(&kebab);
 
-            // Approximate overflow check using monitor logical width divided by number of columns
-            let available_w = (self.monitor_info.logical_size.0 / columns_count).max(1);
+            // Approximate overflow check against an equal share of the monitor's logical width
             let (_min_w, nat_w, _min_h, _nat_h) =
                 column_box.measure(gtk::Orientation::Horizontal, -1);
-            let is_overflowing = nat_w > available_w;
+            let is_overflowing =
+                column_budget(&self.monitor_info, columns_count, &ColumnSpec::default())
+                    .is_some_and(|available_w| nat_w > available_w);
             kebab.set_visible(is_overflowing);
 
             self.container.append(&column_box);
@@ -296,6 +810,21 @@ impl Bar {
         self.container.queue_draw();
     }
 
+    /// Align a module label's text; GTK4 CSS has no text-align
+    fn apply_label_align(label: &gtk::Label, align: &TextAlign) {
+        match align {
+            TextAlign::Center => label.set_xalign(0.5),
+            TextAlign::Left => label.set_xalign(0.0),
+            TextAlign::Right => label.set_xalign(1.0),
+        }
+        // Also set justification to be safe across label modes
+        label.set_justify(match align {
+            TextAlign::Center => gtk::Justification::Center,
+            TextAlign::Left => gtk::Justification::Left,
+            TextAlign::Right => gtk::Justification::Right,
+        });
+    }
+
     /// Update columns from (name, ColumnSpec) pairs; applies overflow policy
     pub fn update_layout_columns(
         &mut self,
@@ -308,90 +837,100 @@ impl Bar {
             self.container.remove(&child);
         }
 
-        let columns_count = columns.len().max(1) as i32;
-        for (name, spec) in columns {
-            let safe = name.replace([' ', '-'], "_");
+        self.update_column_css(columns);
+
+        let model = BarModel::new(columns, &self.monitor_info, module_configs);
+        let mut input_columns = Vec::with_capacity(model.columns.len());
+        for column in &model.columns {
+            let spec = &column.spec;
+            let safe = column.css_name();
             let column_box = gtk::Box::new(gtk::Orientation::Horizontal, spec.gap.unwrap_or(0));
 
-            // Column sizing: equal width by default (container homogeneous=true)
-            // Allow optional fixed pixel width per column
-            if let Some(width) = spec.width {
+            // Column sizing: equal width by default (container homogeneous=true).
+            // A fixed width, or an equal share above max_width, pins the column's width;
+            // min_width keeps modules from collapsing when the share gets small.
+            if column.fixed {
                 column_box.set_hexpand(false);
-                column_box.set_size_request(width, -1);
+                column_box.set_size_request(column.width, -1);
             } else {
+                // Columns otherwise fill their equal-width allocation
                 column_box.set_hexpand(true);
+                column_box.set_size_request(spec.min_width.unwrap_or(-1), -1);
             }
-
-            // Columns themselves always fill their equal-width allocation
             column_box.set_halign(gtk::Align::Fill);
-            column_box.set_hexpand(true);
 
-            // Determine effective text alignment for this column (column-level only)
-            let effective_align: TextAlign = spec.align.clone().unwrap_or(match name.as_str() {
-                "center" => TextAlign::Center,
-                "right" => TextAlign::Right,
-                _ => TextAlign::Left,
-            });
             column_box.add_css_class("column");
             column_box.add_css_class(&format!("column-{}", safe));
             match spec.overflow {
                 ColumnOverflowPolicy::Hide => column_box.add_css_class("overflow-hide"),
                 ColumnOverflowPolicy::Kebab => column_box.add_css_class("overflow-kebab"),
+                ColumnOverflowPolicy::Shrink => column_box.add_css_class("overflow-shrink"),
             }
 
-            // Build module widgets dynamically via registry (collect first, decide overflow later)
-            let mut module_widgets: Vec<gtk::Widget> = Vec::new();
-            for module in &spec.modules {
-                // Get module configuration
-                let module_config = module_configs.get(module);
-
-                // Check display property - skip hidden modules
-                if let Some(config) = module_config
-                    && matches!(
-                        config.display.as_ref().unwrap_or(&DisplayMode::Show),
-                        DisplayMode::Hide
-                    )
-                {
-                    continue;
-                }
-
-                // Merge module settings from provided format map into a minimal settings struct
-                let settings = crate::config::ModuleConfig {
-                    format: module_formats.get(module).cloned(),
-                    tooltip: module_config.and_then(|c| c.tooltip),
-                    highlight_active: module_config.and_then(|c| c.highlight_active),
-                    show_numbers: module_config.and_then(|c| c.show_numbers),
-                    show_wallpaper: module_config.and_then(|c| c.show_wallpaper),
-                    // Pass through wallpaper mapping and defaults so workspaces can prepopulate
-                    default_wallpaper: module_config.and_then(|c| c.default_wallpaper.clone()),
-                    wallpapers: module_config.and_then(|c| c.wallpapers.clone()),
-                    special_cmd: module_config.and_then(|c| c.special_cmd.clone()),
-                    swww_options: module_config.and_then(|c| c.swww_options.clone()),
-                    max_length: module_config.and_then(|c| c.max_length),
-                    ellipsize: module_config.and_then(|c| c.ellipsize.clone()),
-                    show_percentage: module_config.and_then(|c| c.show_percentage),
-                    warn_threshold: module_config.and_then(|c| c.warn_threshold),
-                    critical_threshold: module_config.and_then(|c| c.critical_threshold),
-                    cpu: module_config.and_then(|c| c.cpu),
-                    mem: module_config.and_then(|c| c.mem),
-                    net: module_config.and_then(|c| c.net),
-                    enabled: module_config.and_then(|c| c.enabled),
-                    display: module_config.and_then(|c| c.display.clone()),
-                    width: module_config.and_then(|c| c.width),
-                    show_window_titles: module_config.and_then(|c| c.show_window_titles),
-                    highlight_focused: module_config.and_then(|c| c.highlight_focused),
-                    additional: module_config
-                        .map(|c| c.additional.clone())
-                        .unwrap_or_default(),
-                };
-
-                if let Some(widget) = create_module_widget(module, &settings) {
-                    module_widgets.push(widget);
-                } else {
-                    // Unknown module: skip rendering silently
-                    log::warn!("Bar: unknown module '{}' , skipping", module);
-                }
-            }
+            // Build module widgets dynamically via registry (collect first, arrange later)
+            let module_widgets: Vec<gtk::Widget> = column
+                .modules
+                .iter()
+                .map(|placed| {
+                    let module = &placed.name;
+                    let module_config = module_configs.get(module);
+
+                    // Merge module settings from provided format map into a minimal settings struct
+                    let settings = crate::config::ModuleConfig {
+                        format: module_formats.get(module).cloned(),
+                        tooltip: module_config.and_then(|c| c.tooltip),
+                        tooltip_format: module_config.and_then(|c| c.tooltip_format.clone()),
+                        highlight_active: module_config.and_then(|c| c.highlight_active),
+                        show_numbers: module_config.and_then(|c| c.show_numbers),
+                        show_wallpaper: module_config.and_then(|c| c.show_wallpaper),
+                        // Pass through wallpaper mapping and defaults so workspaces can prepopulate
+                        default_wallpaper: module_config.and_then(|c| c.default_wallpaper.clone()),
+                        wallpapers: module_config.and_then(|c| c.wallpapers.clone()),
+                        wallpaper_patterns: module_config
+                            .and_then(|c| c.wallpaper_patterns.clone()),
+                        special_cmd: module_config.and_then(|c| c.special_cmd.clone()),
+                        swww_options: module_config.and_then(|c| c.swww_options.clone()),
+                        max_length: module_config.and_then(|c| c.max_length),
+                        ellipsize: module_config.and_then(|c| c.ellipsize.clone()),
+                        show_percentage: module_config.and_then(|c| c.show_percentage),
+                        warn_threshold: module_config.and_then(|c| c.warn_threshold),
+                        critical_threshold: module_config.and_then(|c| c.critical_threshold),
+                        cpu: module_config.and_then(|c| c.cpu),
+                        mem: module_config.and_then(|c| c.mem),
+                        net: module_config.and_then(|c| c.net),
+                        enabled: module_config.and_then(|c| c.enabled),
+                        display: module_config.and_then(|c| c.display.clone()),
+                        width: module_config.and_then(|c| c.width),
+                        show_window_titles: module_config.and_then(|c| c.show_window_titles),
+                        highlight_focused: module_config.and_then(|c| c.highlight_focused),
+                        align: module_config.and_then(|c| c.align.clone()),
+                        on_click: module_config.and_then(|c| c.on_click.clone()),
+                        on_click_right: module_config.and_then(|c| c.on_click_right.clone()),
+                        on_click_middle: module_config.and_then(|c| c.on_click_middle.clone()),
+                        on_scroll_up: module_config.and_then(|c| c.on_scroll_up.clone()),
+                        on_scroll_down: module_config.and_then(|c| c.on_scroll_down.clone()),
+                        padding: module_config.and_then(|c| c.padding),
+                        // Let monitor-aware modules (e.g. workspaces) know which output they're
+                        // on, and modules keeping UI state across reloads where they're placed
+                        placement: Some(crate::config::ModulePlacement {
+                            output: self.monitor_info.connector.clone(),
+                            column: column.name.clone(),
+                        }),
+                        additional: module_config
+                            .map(|c| c.additional.clone())
+                            .unwrap_or_default(),
+                    };
+
+                    let widget = create_module_widget(module, &settings).unwrap_or_else(|| {
+                        // The model only keeps registered modules; hold the slot regardless
+                        gtk::Box::new(gtk::Orientation::Horizontal, 0).upcast()
+                    });
+                    // Before measuring, so overflow accounts for the padding
+                    widget.set_margin_start(placed.margin);
+                    widget.set_margin_end(placed.margin);
+                    widget
+                })
+                .collect();
 
             // Kebab menu (three vertical dots). Only show when actual overflow for kebab policy
             let kebab = MenuButton::builder()
@@ -402,8 +941,7 @@ impl Bar {
             kebab.add_css_class(&format!("column-kebab-{}", safe));
             kebab.set_visible(false);
             let popover = Popover::new();
-            let list = ListBox::new();
-            list.add_css_class("column-kebab-list");
+            let list = kebab_overflow_list();
             popover.set_child(Some(&list));
             kebab.set_popover(Some(&popover));
             column_box.append(&kebab);
@@ -411,97 +949,74 @@ impl Bar {
             // CSS border for columns to visualize sections
             column_box.add_css_class("column-outline");
 
-            // Place widgets; overflow extras into kebab popover list
-            let available_w = (self.monitor_info.logical_size.0 / columns_count).max(1);
+            // Measure, then let the model decide overflow and placement. Measured widths and
+            // the budget are both logical pixels.
             let (_k_min_w, kebab_nat_w, _k_min_h, _k_nat_h) =
                 kebab.measure(gtk::Orientation::Horizontal, -1);
-            let mut used_w = 0;
-            let mut overflowed: Vec<gtk::Widget> = Vec::new();
-
-            for w in &module_widgets {
-                let (_m_min_w, m_nat_w, _m_min_h, _m_nat_h) =
-                    w.measure(gtk::Orientation::Horizontal, -1);
-                let budget = if matches!(spec.overflow, ColumnOverflowPolicy::Kebab) {
-                    available_w - kebab_nat_w
-                } else {
-                    available_w
-                };
-                if used_w + m_nat_w <= budget {
-                    used_w += m_nat_w;
-                } else {
-                    overflowed.push(w.clone());
-                }
-            }
-
-            // Optionally insert spacers to realize column-level grouping alignment
-            if matches!(effective_align, TextAlign::Right) {
-                let spacer = gtk::Box::new(gtk::Orientation::Horizontal, 0);
-                spacer.set_hexpand(true);
-                column_box.append(&spacer);
-            }
-            if matches!(effective_align, TextAlign::Center) {
-                let spacer_left = gtk::Box::new(gtk::Orientation::Horizontal, 0);
-                spacer_left.set_hexpand(true);
-                column_box.append(&spacer_left);
-            }
-
-            // Append widgets that fit, setting alignment based on column alignment
-            for w in module_widgets.iter() {
-                if !overflowed.iter().any(|ow| ow == w) {
-                    // GTK4 CSS doesn't support text-align, so set alignment programmatically
-                    if let Some(label) = w.downcast_ref::<gtk::Label>() {
-                        // Use column-level alignment only
-                        let module_align = &effective_align;
-
-                        match module_align {
-                            TextAlign::Center => label.set_xalign(0.5),
-                            TextAlign::Left => label.set_xalign(0.0),
-                            TextAlign::Right => label.set_xalign(1.0),
+            let widths: Vec<i32> = module_widgets
+                .iter()
+                .map(|w| w.measure(gtk::Orientation::Horizontal, -1).1)
+                .collect();
+            let arrangement = column.arrange(kebab_nat_w, &widths);
+            if let OverflowDecision::Shrink { .. } = arrangement.decision {
+                // Ellipsize labels to their pixel share of the column. The cap comes from the
+                // font, not the current text, so it holds as the text changes later.
+                let is_label: Vec<bool> = module_widgets
+                    .iter()
+                    .map(|w| w.is::<gtk::Label>())
+                    .collect();
+                if let Some(share_w) = column.shrink_label_width(&widths, &is_label) {
+                    for w in &module_widgets {
+                        if let Some(label) = w.downcast_ref::<gtk::Label>() {
+                            let metrics = label.pango_context().metrics(None, None);
+                            let char_w =
+                                metrics.approximate_char_width() as f64 / gtk::pango::SCALE as f64;
+                            label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+                            label.set_max_width_chars(max_width_chars(share_w, char_w));
                         }
-                        // Also set justification to be safe across label modes
-                        label.set_justify(match module_align {
-                            TextAlign::Center => gtk::Justification::Center,
-                            TextAlign::Left => gtk::Justification::Left,
-                            TextAlign::Right => gtk::Justification::Right,
-                        });
                     }
-                    // For grouped alignment, don't force children to expand; let spacers handle layout
-                    w.set_hexpand(false);
-                    w.set_halign(gtk::Align::Fill);
-                    column_box.append(w);
                 }
             }
 
-            if matches!(effective_align, TextAlign::Center) {
-                let spacer_right = gtk::Box::new(gtk::Orientation::Horizontal, 0);
-                spacer_right.set_hexpand(true);
-                column_box.append(&spacer_right);
-            }
-
-            // Move overflowed widgets into popover as rows
-            for w in overflowed {
-                if matches!(spec.overflow, ColumnOverflowPolicy::Kebab) {
-                    // Set alignment for overflowed labels too - use column alignment for overflow
-                    if let Some(label) = w.downcast_ref::<gtk::Label>() {
-                        match effective_align {
-                            TextAlign::Center => label.set_xalign(0.5),
-                            TextAlign::Left => label.set_xalign(0.0),
-                            TextAlign::Right => label.set_xalign(1.0),
+            // Place the modules that fit by alignment, with flexible spacers between the
+            // left, centered and right groups
+            for slot in &arrangement.slots {
+                match slot {
+                    ColumnSlot::Spacer => {
+                        let spacer = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+                        spacer.set_hexpand(true);
+                        column_box.append(&spacer);
+                    }
+                    ColumnSlot::Module(i) => {
+                        let w = &module_widgets[*i];
+                        // GTK4 CSS doesn't support text-align, so set alignment programmatically
+                        if let Some(label) = w.downcast_ref::<gtk::Label>() {
+                            Self::apply_label_align(label, &column.modules[*i].align);
                         }
+                        // Don't force children to expand; let spacers handle layout
+                        w.set_hexpand(false);
+                        w.set_halign(gtk::Align::Fill);
+                        column_box.append(w);
                     }
-                    let row = gtk::ListBoxRow::new();
-                    row.add_css_class("column-overflow-row");
-                    row.set_child(Some(&w));
-                    list.append(&row);
                 }
             }
 
-            // Show kebab only if there is at least one overflowed item
-            kebab.set_visible(list.first_child().is_some());
+            // Move overflowed widgets into popover as rows, keeping each module's alignment
+            for &i in &arrangement.kebab {
+                let w = &module_widgets[i];
+                if let Some(label) = w.downcast_ref::<gtk::Label>() {
+                    Self::apply_label_align(label, &column.modules[i].align);
+                }
+                list.append(&kebab_overflow_row(w));
+            }
+            kebab.set_visible(arrangement.show_kebab());
 
             self.container.append(&column_box);
+            input_columns.push((column_box, column.click_through()));
         }
 
+        *self.input_columns.borrow_mut() = input_columns;
+        Self::schedule_input_region(&self.window, &self.input_columns);
         self.container.queue_draw();
     }
 
@@ -513,6 +1028,7 @@ impl Bar {
         );
         self.window.close();
         self.is_visible = false;
+        self.release_css_provider();
     }
 
     /// Get the monitor connector this bar belongs to
@@ -537,7 +1053,15 @@ impl Bar {
             self.monitor_info.connector,
             height
         );
-        self.window.set_default_height(height);
+        Self::apply_height(&self.window, height);
+    }
+
+    /// Size the window and request a relayout so children (e.g. viewport aspect ratio)
+    /// see the new allocated height
+    fn apply_height(window: &ApplicationWindow, height: i32) {
+        window.set_default_height(height);
+        window.set_size_request(-1, height);
+        window.queue_resize();
     }
 
     /// Set the bar width
@@ -591,3 +1115,9 @@ impl Bar {
         &self.monitor_info.connector
     }
 }
+
+impl Drop for Bar {
+    fn drop(&mut self) {
+        self.release_css_provider();
+    }
+}
@@ -0,0 +1,406 @@
+//! Layout model for a bar: which modules go in which column, how wide each column is, and
+//! what happens when they don't fit. Pure data, so the decisions can be tested without GTK;
+//! [`crate::bar::Bar`] builds and measures the widgets and applies the result.
+
+use crate::config::{
+    ColumnOverflowPolicy, ColumnSpec, DisplayMode, LayoutConfig, ModuleConfig, TextAlign,
+};
+use crate::modules::{is_known_module, separator};
+use crate::monitor::MonitorInfo;
+use std::collections::HashMap;
+
+/// How a column fits its modules once their natural widths are measured
+#[derive(Debug, Clone, PartialEq)]
+pub enum OverflowDecision {
+    /// Every module fits at its natural width
+    Fits,
+    /// Modules at these indices don't fit; hidden or moved into the kebab popover
+    Overflow(Vec<usize>),
+    /// Labels shrink (ellipsize) to `ratio` of their natural width so everything fits
+    Shrink { ratio: f64 },
+}
+
+/// Decide how a column handles overflow given its module widths and available space.
+/// `kebab_w` is reserved out of the budget for the kebab policy.
+pub fn decide_overflow(
+    policy: &ColumnOverflowPolicy,
+    available_w: i32,
+    kebab_w: i32,
+    widths: &[i32],
+) -> OverflowDecision {
+    match policy {
+        ColumnOverflowPolicy::Shrink => {
+            let total: i32 = widths.iter().sum();
+            if total <= available_w {
+                OverflowDecision::Fits
+            } else {
+                OverflowDecision::Shrink {
+                    ratio: available_w.max(0) as f64 / total as f64,
+                }
+            }
+        }
+        ColumnOverflowPolicy::Hide | ColumnOverflowPolicy::Kebab => {
+            let budget = if matches!(policy, ColumnOverflowPolicy::Kebab) {
+                available_w - kebab_w
+            } else {
+                available_w
+            };
+            let mut used_w = 0;
+            let mut overflowed = Vec::new();
+            for (i, w) in widths.iter().enumerate() {
+                if used_w + w <= budget {
+                    used_w += w;
+                } else {
+                    overflowed.push(i);
+                }
+            }
+            if overflowed.is_empty() {
+                OverflowDecision::Fits
+            } else {
+                OverflowDecision::Overflow(overflowed)
+            }
+        }
+    }
+}
+
+/// Width a column gets on a monitor `monitor_w` pixels wide: its fixed `width`, otherwise an
+/// equal share, clamped to `min_width`/`max_width` (`min_width` wins if they conflict)
+pub fn column_width(monitor_w: i32, columns_count: i32, spec: &ColumnSpec) -> i32 {
+    let mut width = spec.width.unwrap_or(monitor_w / columns_count.max(1));
+    if let Some(max) = spec.max_width {
+        width = width.min(max);
+    }
+    if let Some(min) = spec.min_width {
+        width = width.max(min);
+    }
+    width.max(1)
+}
+
+/// Overflow budget of one column on `monitor`, in logical pixels like the widths GTK
+/// measures; the scale factor only matters for device pixels and is left out. `None` while
+/// the monitor's size is unknown, so nothing is overflowed against a zero width.
+pub fn column_budget(monitor: &MonitorInfo, columns_count: i32, spec: &ColumnSpec) -> Option<i32> {
+    let monitor_w = monitor.logical_size.0;
+    (monitor_w > 0).then(|| column_width(monitor_w, columns_count, spec))
+}
+
+/// One position in a column's row of widgets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColumnSlot {
+    /// The module at this index (into the alignments passed to [`column_slots`])
+    Module(usize),
+    /// A flexible spacer soaking up free space
+    Spacer,
+}
+
+/// Arrange modules by alignment: the left group, then the centered group between two
+/// spacers, then the right group (after a spacer when nothing is centered). Modules keep
+/// their order within a group. A single alignment gives the plain column layouts: no spacer
+/// for left, a leading spacer for right, one on each side for center.
+pub fn column_slots(aligns: &[TextAlign]) -> Vec<ColumnSlot> {
+    let group = |align: TextAlign| {
+        aligns
+            .iter()
+            .enumerate()
+            .filter(move |(_, a)| **a == align)
+            .map(|(i, _)| ColumnSlot::Module(i))
+    };
+    let has = |align: TextAlign| aligns.contains(&align);
+    let mut slots: Vec<ColumnSlot> = group(TextAlign::Left).collect();
+    if has(TextAlign::Center) {
+        slots.push(ColumnSlot::Spacer);
+        slots.extend(group(TextAlign::Center));
+        slots.push(ColumnSlot::Spacer);
+    } else if has(TextAlign::Right) {
+        slots.push(ColumnSlot::Spacer);
+    }
+    slots.extend(group(TextAlign::Right));
+    slots
+}
+
+/// A module placed in a column
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacedModule {
+    /// Module name as written in the layout, e.g. "clock"
+    pub name: String,
+    /// The module's own `align`, else its column's
+    pub align: TextAlign,
+    /// Start and end margin from the module's `padding`
+    pub margin: i32,
+    /// Width counted for overflow in place of the measured one (separators)
+    pub fixed_width: Option<i32>,
+}
+
+/// Horizontal margin for a module: its `padding`, with negative values treated as none
+pub fn module_margin(config: Option<&ModuleConfig>) -> i32 {
+    config.and_then(|c| c.padding).unwrap_or(0).max(0)
+}
+
+/// One column of a bar before its widgets are measured
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnModel {
+    pub name: String,
+    pub spec: ColumnSpec,
+    /// The column's `align`, else derived from its name (`center`, `right`, otherwise left)
+    pub align: TextAlign,
+    /// Modules to build, in layout order; hidden (`display: hide`) and unknown ones are
+    /// left out
+    pub modules: Vec<PlacedModule>,
+    /// Width from [`column_width`] against the monitor's logical width
+    pub width: i32,
+    /// Whether the column is pinned to `width` (a fixed `width`, or an equal share above
+    /// `max_width`) instead of filling its share
+    pub fixed: bool,
+    /// Overflow budget from [`column_budget`]; `None` while the monitor size is unknown
+    pub budget: Option<i32>,
+}
+
+/// A column's final arrangement once widths are known
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnArrangement {
+    pub decision: OverflowDecision,
+    /// Modules left in the column and the spacers between alignment groups; module
+    /// indices point into [`ColumnModel::modules`]
+    pub slots: Vec<ColumnSlot>,
+    /// Overflowed modules listed in the kebab popover, in layout order (kebab policy only;
+    /// under `hide` they are dropped)
+    pub kebab: Vec<usize>,
+}
+
+impl ColumnArrangement {
+    /// Whether the column's kebab button is shown
+    pub fn show_kebab(&self) -> bool {
+        !self.kebab.is_empty()
+    }
+}
+
+impl ColumnModel {
+    fn new(
+        name: &str,
+        spec: &ColumnSpec,
+        monitor: &MonitorInfo,
+        columns_count: i32,
+        module_configs: &HashMap<String, ModuleConfig>,
+    ) -> Self {
+        let align = spec.align.clone().unwrap_or(match name {
+            "center" => TextAlign::Center,
+            "right" => TextAlign::Right,
+            _ => TextAlign::Left,
+        });
+        let modules = spec
+            .modules
+            .iter()
+            .filter_map(|module| {
+                let config = module_configs.get(module);
+                if config.is_some_and(|c| matches!(c.display, Some(DisplayMode::Hide))) {
+                    return None;
+                }
+                if !is_known_module(module) {
+                    log::warn!("Bar: unknown module '{}', skipping", module);
+                    return None;
+                }
+                Some(PlacedModule {
+                    name: module.clone(),
+                    align: config
+                        .and_then(|c| c.align.clone())
+                        .unwrap_or(align.clone()),
+                    margin: module_margin(config),
+                    fixed_width: (module == separator::NAME)
+                        .then(|| separator::fixed_width(config)),
+                })
+            })
+            .collect();
+        let monitor_w = monitor.logical_size.0;
+        let capped = spec
+            .max_width
+            .is_some_and(|max| monitor_w / columns_count > max);
+        Self {
+            name: name.to_string(),
+            spec: spec.clone(),
+            align,
+            modules,
+            width: column_width(monitor_w, columns_count, spec),
+            fixed: spec.width.is_some() || capped,
+            budget: column_budget(monitor, columns_count, spec),
+        }
+    }
+
+    /// Whether the column passes pointer input through to the windows below
+    pub fn click_through(&self) -> bool {
+        self.spec.click_through.unwrap_or(false)
+    }
+
+    /// Column name as used in CSS classes (`column-<name>`)
+    pub fn css_name(&self) -> String {
+        self.name.replace([' ', '-'], "_")
+    }
+
+    /// Arrange the column given the natural widths of its modules (same order as
+    /// `modules`) and of the kebab button. Modules with a `fixed_width` count that instead,
+    /// and are hidden rather than listed in the kebab when they overflow.
+    pub fn arrange(&self, kebab_w: i32, widths: &[i32]) -> ColumnArrangement {
+        let widths: Vec<i32> = widths
+            .iter()
+            .enumerate()
+            .map(|(i, w)| {
+                self.modules
+                    .get(i)
+                    .and_then(|m| m.fixed_width)
+                    .unwrap_or(*w)
+            })
+            .collect();
+        let decision = match self.budget {
+            Some(available_w) => {
+                decide_overflow(&self.spec.overflow, available_w, kebab_w, &widths)
+            }
+            None => OverflowDecision::Fits,
+        };
+        let overflowed: &[usize] = match &decision {
+            OverflowDecision::Overflow(indices) => indices,
+            _ => &[],
+        };
+        let placed: Vec<usize> = (0..self.modules.len())
+            .filter(|i| !overflowed.contains(i))
+            .collect();
+        let placed_aligns: Vec<TextAlign> = placed
+            .iter()
+            .map(|&i| self.modules[i].align.clone())
+            .collect();
+        let slots = column_slots(&placed_aligns)
+            .into_iter()
+            .map(|slot| match slot {
+                ColumnSlot::Module(j) => ColumnSlot::Module(placed[j]),
+                ColumnSlot::Spacer => ColumnSlot::Spacer,
+            })
+            .collect();
+        let kebab = match self.spec.overflow {
+            ColumnOverflowPolicy::Kebab => overflowed
+                .iter()
+                .copied()
+                .filter(|&i| self.modules[i].fixed_width.is_none())
+                .collect(),
+            _ => Vec::new(),
+        };
+        ColumnArrangement {
+            decision,
+            slots,
+            kebab,
+        }
+    }
+
+    /// Pixel width each shrinking label may take: what's left of the budget after the
+    /// other modules, split evenly between the labels. Split evenly rather than by measured
+    /// width so a label whose text hasn't arrived yet isn't capped to nothing. `None` while
+    /// the budget is unknown or when no module is a label.
+    pub fn shrink_label_width(&self, widths: &[i32], is_label: &[bool]) -> Option<i32> {
+        let available_w = self.budget?;
+        let labels = is_label.iter().filter(|l| **l).count() as i32;
+        if labels == 0 {
+            return None;
+        }
+        let others: i32 = widths
+            .iter()
+            .zip(is_label)
+            .enumerate()
+            .filter(|(_, (_, label))| !**label)
+            .map(|(i, (w, _))| {
+                self.modules
+                    .get(i)
+                    .and_then(|m| m.fixed_width)
+                    .unwrap_or(*w)
+            })
+            .sum();
+        Some(((available_w - others) / labels).max(0))
+    }
+}
+
+/// Character cap for a label limited to `width` logical pixels, given its font's
+/// approximate character width; at least one character
+pub fn max_width_chars(width: i32, char_width: f64) -> i32 {
+    if char_width <= 0.0 {
+        return 1;
+    }
+    (width.max(0) as f64 / char_width).floor().max(1.0) as i32
+}
+
+/// A rectangle of the bar surface, in logical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// Where a column was allocated across the bar, and whether it passes input through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnExtent {
+    pub x: i32,
+    pub width: i32,
+    pub click_through: bool,
+}
+
+/// Input region for a bar `height` pixels tall: the interactive columns in `columns`,
+/// with touching ones merged into one rectangle. `None` when no column is click-through,
+/// i.e. the whole surface keeps taking input; an empty list makes the bar fully
+/// click-through. Gaps between columns only take input while every column does.
+pub fn input_region(columns: &[ColumnExtent], height: i32) -> Option<Vec<InputRect>> {
+    if !columns.iter().any(|c| c.click_through) {
+        return None;
+    }
+    let mut rects: Vec<InputRect> = Vec::new();
+    for column in columns.iter().filter(|c| !c.click_through && c.width > 0) {
+        match rects.last_mut() {
+            Some(last) if last.x + last.width >= column.x => {
+                last.width = last.width.max(column.x + column.width - last.x);
+            }
+            _ => rects.push(InputRect {
+                x: column.x,
+                y: 0,
+                width: column.width,
+                height,
+            }),
+        }
+    }
+    Some(rects)
+}
+
+/// Column layout of one bar, computed from its layout and monitor
+#[derive(Debug, Clone, PartialEq)]
+pub struct BarModel {
+    pub columns: Vec<ColumnModel>,
+}
+
+impl BarModel {
+    /// Model for `columns` in order on `monitor`; `module_configs` supplies each module's
+    /// `display` and `align`
+    pub fn new(
+        columns: &[(String, ColumnSpec)],
+        monitor: &MonitorInfo,
+        module_configs: &HashMap<String, ModuleConfig>,
+    ) -> Self {
+        let columns_count = columns.len().max(1) as i32;
+        Self {
+            columns: columns
+                .iter()
+                .map(|(name, spec)| {
+                    ColumnModel::new(name, spec, monitor, columns_count, module_configs)
+                })
+                .collect(),
+        }
+    }
+
+    /// [`BarModel::new`] for a layout's columns
+    pub fn from_layout(
+        layout: &LayoutConfig,
+        monitor: &MonitorInfo,
+        module_configs: &HashMap<String, ModuleConfig>,
+    ) -> Self {
+        let columns: Vec<(String, ColumnSpec)> = layout
+            .columns
+            .iter()
+            .map(|(name, spec)| (name.clone(), spec.clone()))
+            .collect();
+        Self::new(&columns, monitor, module_configs)
+    }
+}
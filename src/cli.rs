@@ -0,0 +1,57 @@
+//! Command line interface. `niri-bar` (or `niri-bar run`) starts the bar and
+//! `niri-bar schema` prints the config schema; the other subcommands send a command to the
+//! running instance over the control socket.
+
+use crate::control::ControlCommand;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+/// Status bar for the niri compositor
+#[derive(Debug, Parser)]
+#[command(name = "niri-bar", version)]
+pub struct Cli {
+    /// Config file to load (default: $NIRI_BAR_CONFIG, then the XDG config dir)
+    #[arg(short, long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum CliCommand {
+    /// Start the bar (the default)
+    Run,
+    /// Reload the running bar's config and theme
+    Reload,
+    /// Show or hide the running bar on a monitor
+    Toggle {
+        /// Monitor connector, e.g. eDP-1
+        connector: String,
+    },
+    /// Switch the running bar to a theme until the next reload
+    SetTheme {
+        /// Theme name, e.g. dracula
+        name: String,
+    },
+    /// Print the config file's JSON schema, e.g. for editor validation
+    Schema,
+}
+
+impl Cli {
+    /// The subcommand to run, `run` when none was given
+    pub fn command(&self) -> CliCommand {
+        self.command.clone().unwrap_or(CliCommand::Run)
+    }
+}
+
+impl CliCommand {
+    /// Command to send to a running instance; None for `run` and `schema`
+    pub fn control_command(&self) -> Option<ControlCommand> {
+        match self {
+            Self::Run | Self::Schema => None,
+            Self::Reload => Some(ControlCommand::Reload),
+            Self::Toggle { connector } => Some(ControlCommand::ToggleBar(connector.clone())),
+            Self::SetTheme { name } => Some(ControlCommand::SetTheme(name.clone())),
+        }
+    }
+}
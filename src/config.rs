@@ -1,11 +1,389 @@
 use crate::file_watcher::FileWatcher;
-use anyhow::Result;
+use crate::monitor::MonitorInfo;
+use crate::niri::WorkspaceInfo;
+use anyhow::{Context, Result, bail};
 use indexmap::IndexMap;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use regex_syntax::hir::{Hir, HirKind, Look};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use tokio::sync::broadcast;
 
+/// Config file name, used in the XDG config dir and the current directory
+pub const DEFAULT_CONFIG_FILE: &str = "niri-bar.yaml";
+/// Environment variable holding an explicit config file path
+pub const CONFIG_PATH_ENV: &str = "NIRI_BAR_CONFIG";
+
+/// Pick the config file: the `--config` argument, then `$NIRI_BAR_CONFIG`, then
+/// `<xdg_config_home>/niri-bar/niri-bar.yaml` if it exists, then `niri-bar.yaml` in the
+/// current directory. Explicit paths are used as given, even if they don't exist yet.
+pub fn resolve_config_path(
+    cli_path: Option<&Path>,
+    env_path: Option<&OsStr>,
+    xdg_config_home: Option<&Path>,
+) -> PathBuf {
+    if let Some(path) = cli_path {
+        return path.to_path_buf();
+    }
+    if let Some(path) = env_path.filter(|p| !p.is_empty()) {
+        return PathBuf::from(path);
+    }
+    if let Some(xdg) = xdg_config_home {
+        let path = xdg.join("niri-bar").join(DEFAULT_CONFIG_FILE);
+        if path.is_file() {
+            return path;
+        }
+    }
+    PathBuf::from(DEFAULT_CONFIG_FILE)
+}
+
+/// `${...}` placeholders filled in later by the code that uses the value, not from the environment
+const RUNTIME_PLACEHOLDERS: &[&str] = &["current_workspace_image"];
+
+/// Expand `${VAR}` tokens from the environment. `$$` is a literal `$`; unset variables and
+/// runtime placeholders like `${current_workspace_image}` are left as written.
+pub fn expand_env_vars(input: &str) -> String {
+    expand_env_vars_with(input, |name| std::env::var(name).ok())
+}
+
+/// [`expand_env_vars`] with a custom variable lookup
+pub fn expand_env_vars_with(input: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        if let Some(tail) = after.strip_prefix('$') {
+            out.push('$');
+            rest = tail;
+            continue;
+        }
+        let name_len = after
+            .strip_prefix('{')
+            .and_then(|inner| inner.find('}'))
+            .filter(|&len| is_env_var_name(&after[1..1 + len]));
+        let Some(len) = name_len else {
+            out.push('$');
+            rest = after;
+            continue;
+        };
+        let name = &after[1..1 + len];
+        let token = &rest[pos..pos + len + 3];
+        match lookup(name) {
+            Some(value) if !RUNTIME_PLACEHOLDERS.contains(&name) => out.push_str(&value),
+            _ => {
+                if !RUNTIME_PLACEHOLDERS.contains(&name) {
+                    log::warn!("ConfigManager: {} is not set, leaving it as-is", token);
+                }
+                out.push_str(token);
+            }
+        }
+        rest = &after[len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn is_env_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Expand environment variables in every string value (not keys) of a parsed YAML document
+fn expand_env_in_value(value: &mut serde_yaml::Value) {
+    match value {
+        serde_yaml::Value::String(s) if s.contains('$') => *s = expand_env_vars(s),
+        serde_yaml::Value::Sequence(items) => items.iter_mut().for_each(expand_env_in_value),
+        serde_yaml::Value::Mapping(map) => map.values_mut().for_each(expand_env_in_value),
+        serde_yaml::Value::Tagged(tagged) => expand_env_in_value(&mut tagged.value),
+        _ => {}
+    }
+}
+
+/// JSON schema of the config file, embedded so `niri-bar schema` can print it
+pub const CONFIG_SCHEMA: &str = include_str!("niri-bar-yaml.schema.json");
+
+static CONFIG_VALIDATOR: Lazy<jsonschema::Validator> = Lazy::new(|| {
+    let schema: serde_json::Value =
+        serde_json::from_str(CONFIG_SCHEMA).expect("embedded config schema is valid JSON");
+    jsonschema::validator_for(&schema).expect("embedded config schema compiles")
+});
+
+/// Where `config` (a config document as JSON) breaks the schema, one
+/// `<json pointer>: <message>` entry per violation; empty when it conforms
+pub fn schema_errors(config: &serde_json::Value) -> Vec<String> {
+    CONFIG_VALIDATOR
+        .iter_errors(config)
+        .map(|error| {
+            let path = error.instance_path.to_string();
+            let path = if path.is_empty() { "/" } else { &path };
+            format!("{}: {}", path, error)
+        })
+        .collect()
+}
+
+/// Top-level key listing other YAML files to merge into a config
+pub const INCLUDE_KEY: &str = "include";
+
+/// Deep-merge `overlay` into `base`: mappings merge key by key, anything else (lists
+/// included) is replaced by the overlay
+pub fn merge_yaml(base: &mut serde_yaml::Value, overlay: serde_yaml::Value) {
+    match (base, overlay) {
+        (serde_yaml::Value::Mapping(base), serde_yaml::Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_yaml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Replace a document's `include: <path>` / `include: [paths]` with the merged contents of
+/// those files (in order, later ones winning), with the document's own keys applied last.
+/// `stack` holds the canonical paths of the files currently being included, for cycle checks.
+fn resolve_includes(
+    mut value: serde_yaml::Value,
+    base_dir: &Path,
+    stack: &mut Vec<PathBuf>,
+) -> Result<serde_yaml::Value> {
+    let includes = take_includes(&mut value)?;
+    if includes.is_empty() {
+        return Ok(value);
+    }
+
+    let mut merged = serde_yaml::Value::Mapping(serde_yaml::Mapping::new());
+    for include in includes {
+        let path = base_dir.join(expand_env_vars(&include));
+        merge_yaml(&mut merged, load_included_yaml(&path, stack)?);
+    }
+    merge_yaml(&mut merged, value);
+    Ok(merged)
+}
+
+/// Remove a document's `include` key and return the paths it lists, as written
+fn take_includes(value: &mut serde_yaml::Value) -> Result<Vec<String>> {
+    Ok(
+        match value
+            .as_mapping_mut()
+            .and_then(|map| map.remove(INCLUDE_KEY))
+        {
+            None => Vec::new(),
+            Some(serde_yaml::Value::String(path)) => vec![path],
+            Some(serde_yaml::Value::Sequence(items)) => items
+                .into_iter()
+                .map(|item| match item {
+                    serde_yaml::Value::String(path) => Ok(path),
+                    other => bail!("`include` entries must be file paths, got {:?}", other),
+                })
+                .collect::<Result<Vec<_>>>()?,
+            Some(other) => bail!(
+                "`include` must be a path or a list of paths, got {:?}",
+                other
+            ),
+        },
+    )
+}
+
+/// Directory a config's `include`s resolve against: the directory of `source`
+fn include_base_dir(source: &Path) -> &Path {
+    match source.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    }
+}
+
+/// Canonical paths of every existing file a config read from `source` includes, directly or
+/// through other includes, for the file watcher. Best effort: a file that can't be read or
+/// parsed is still listed, but what it would include isn't.
+pub fn config_include_paths(content: &[u8], source: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    if let Ok(value) = serde_yaml::from_slice(content) {
+        collect_include_paths(value, include_base_dir(source), &mut found);
+    }
+    found
+}
+
+fn collect_include_paths(mut value: serde_yaml::Value, base_dir: &Path, found: &mut Vec<PathBuf>) {
+    for include in take_includes(&mut value).unwrap_or_default() {
+        let Ok(canonical) = base_dir.join(expand_env_vars(&include)).canonicalize() else {
+            continue;
+        };
+        // Also stops include cycles
+        if found.contains(&canonical) {
+            continue;
+        }
+        found.push(canonical.clone());
+        let included = std::fs::read(&canonical)
+            .ok()
+            .and_then(|content| serde_yaml::from_slice(&content).ok());
+        if let Some(included) = included {
+            let dir = canonical.parent().unwrap_or(Path::new("."));
+            collect_include_paths(included, dir, found);
+        }
+    }
+}
+
+/// Read an included file and resolve its own includes relative to its directory
+fn load_included_yaml(path: &Path, stack: &mut Vec<PathBuf>) -> Result<serde_yaml::Value> {
+    let canonical = path
+        .canonicalize()
+        .with_context(|| format!("Failed to read included config {}", path.display()))?;
+    if stack.contains(&canonical) {
+        let chain: Vec<String> = stack
+            .iter()
+            .chain(std::iter::once(&canonical))
+            .map(|p| p.display().to_string())
+            .collect();
+        bail!("Config include cycle: {}", chain.join(" -> "));
+    }
+
+    let content = std::fs::read_to_string(&canonical)
+        .with_context(|| format!("Failed to read included config {}", canonical.display()))?;
+    let value: serde_yaml::Value = serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse included config {}", canonical.display()))?;
+    // An empty file is an empty mapping, not a null that would wipe what came before
+    let value = match value {
+        serde_yaml::Value::Null => serde_yaml::Value::Mapping(serde_yaml::Mapping::new()),
+        value => value,
+    };
+
+    stack.push(canonical.clone());
+    let dir = canonical.parent().unwrap_or(Path::new("."));
+    let resolved = resolve_includes(value, dir, stack);
+    stack.pop();
+    resolved
+}
+
+/// Key of the named color palette under `application`
+pub const PALETTE_KEY: &str = "colors";
+
+/// Whether a config key holds a color and may reference the palette: `color`, `background`,
+/// or anything ending in `_color` / `_background`
+fn is_color_key(key: &str) -> bool {
+    key == "color" || key == "background" || key.ends_with("_color") || key.ends_with("_background")
+}
+
+/// Palette name referenced by a `$name` value, if it is one
+fn palette_reference(value: &str) -> Option<&str> {
+    value.strip_prefix('$').filter(|name| {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    })
+}
+
+/// Replace `$name` values of color keys with `application.colors[name]`. Anything else,
+/// including literal colors, is left as written; an undefined name is an error.
+fn resolve_palette(value: &mut serde_yaml::Value) -> Result<()> {
+    let palette: HashMap<String, String> = match value
+        .get("application")
+        .and_then(|app| app.get(PALETTE_KEY))
+    {
+        None | Some(serde_yaml::Value::Null) => HashMap::new(),
+        Some(colors) => serde_yaml::from_value(colors.clone())
+            .context("`application.colors` must map names to color strings")?,
+    };
+    resolve_palette_refs(value, &palette, None)
+}
+
+fn resolve_palette_refs(
+    value: &mut serde_yaml::Value,
+    palette: &HashMap<String, String>,
+    key: Option<&str>,
+) -> Result<()> {
+    match value {
+        serde_yaml::Value::String(s) if key.is_some_and(is_color_key) => {
+            if let Some(name) = palette_reference(s) {
+                let Some(color) = palette.get(name) else {
+                    bail!(
+                        "Undefined palette color '${}' in `{}` (define it under application.colors)",
+                        name,
+                        key.unwrap_or_default()
+                    );
+                };
+                *s = color.clone();
+            }
+        }
+        serde_yaml::Value::Sequence(items) => {
+            for item in items {
+                resolve_palette_refs(item, palette, key)?;
+            }
+        }
+        serde_yaml::Value::Mapping(map) => {
+            for (k, v) in map.iter_mut() {
+                resolve_palette_refs(v, palette, k.as_str())?;
+            }
+        }
+        serde_yaml::Value::Tagged(tagged) => resolve_palette_refs(&mut tagged.value, palette, key)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// [`resolve_config_path`] with `$NIRI_BAR_CONFIG` and `$XDG_CONFIG_HOME` (default
+/// `~/.config`) read from the environment
+pub fn resolve_config_path_from_env(cli_path: Option<&Path>) -> PathBuf {
+    let env_path = std::env::var_os(CONFIG_PATH_ENV);
+    let xdg_config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from)
+        .or_else(dirs::config_dir);
+    resolve_config_path(cli_path, env_path.as_deref(), xdg_config_home.as_deref())
+}
+
+/// `logging` section of the config at `path`, so logging can follow it from startup; None
+/// when the file can't be read or doesn't parse
+pub fn load_logging_config(path: &Path) -> Option<LoggingConfig> {
+    let content = std::fs::read(path).ok()?;
+    ConfigManager::parse_config_from(&content, path)
+        .ok()
+        .map(|config| config.logging)
+}
+
+/// Deserialize a keyword enum from its YAML name. `names` maps every accepted spelling to
+/// its variant, canonical name first; an unknown name fails with an error naming `field`
+/// and the canonical names, e.g. "invalid align `centre`, expected one of: left, center, right".
+fn deserialize_keyword<'de, D, T>(
+    deserializer: D,
+    field: &str,
+    names: &[(&str, T)],
+) -> std::result::Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Clone + PartialEq,
+{
+    let value = String::deserialize(deserializer)?;
+    if let Some((_, variant)) = names.iter().find(|(name, _)| *name == value) {
+        return Ok(variant.clone());
+    }
+    // Legacy aliases are accepted but not advertised
+    let accepted: Vec<&str> = names
+        .iter()
+        .enumerate()
+        .filter(|(i, (_, variant))| names.iter().position(|(_, v)| v == variant) == Some(*i))
+        .map(|(_, (name, _))| *name)
+        .collect();
+    Err(serde::de::Error::custom(format!(
+        "invalid {} `{}`, expected one of: {}",
+        field,
+        value,
+        accepted.join(", ")
+    )))
+}
+
 /// Text alignment options
 ///
 /// # Examples
@@ -16,7 +394,7 @@ use tokio::sync::broadcast;
 /// let align = TextAlign::Center;
 /// assert_eq!(align, TextAlign::Center);
 /// ```
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, PartialEq)]
 pub enum TextAlign {
     #[serde(rename = "left")]
     Left,
@@ -32,8 +410,22 @@ impl Default for TextAlign {
     }
 }
 
+impl<'de> Deserialize<'de> for TextAlign {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> std::result::Result<Self, D::Error> {
+        deserialize_keyword(
+            d,
+            "align",
+            &[
+                ("left", Self::Left),
+                ("center", Self::Center),
+                ("right", Self::Right),
+            ],
+        )
+    }
+}
+
 /// Display visibility options
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, PartialEq)]
 pub enum DisplayMode {
     #[serde(rename = "show")]
     Show,
@@ -47,6 +439,174 @@ impl Default for DisplayMode {
     }
 }
 
+impl<'de> Deserialize<'de> for DisplayMode {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> std::result::Result<Self, D::Error> {
+        deserialize_keyword(d, "display", &[("show", Self::Show), ("hide", Self::Hide)])
+    }
+}
+
+/// Bar height used when neither the application nor the monitor sets one
+pub const DEFAULT_BAR_HEIGHT: i32 = 40;
+
+/// Screen edge the bar is anchored to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BarPosition {
+    #[serde(rename = "top")]
+    Top,
+    #[serde(rename = "bottom")]
+    Bottom,
+}
+
+impl Default for BarPosition {
+    fn default() -> Self {
+        Self::Top
+    }
+}
+
+/// Layer-shell layer the bar is placed on
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum BarLayer {
+    #[serde(rename = "background")]
+    Background,
+    #[serde(rename = "bottom")]
+    Bottom,
+    #[serde(rename = "top")]
+    Top,
+    #[serde(rename = "overlay")]
+    Overlay,
+}
+
+impl Default for BarLayer {
+    fn default() -> Self {
+        Self::Top
+    }
+}
+
+/// How config validation treats layouts that reference modules which don't exist
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum UnknownModulePolicy {
+    /// Reject the config
+    #[serde(rename = "error")]
+    Error,
+    /// Log a warning and skip the module when rendering
+    #[serde(rename = "warn")]
+    Warn,
+}
+
+impl Default for UnknownModulePolicy {
+    fn default() -> Self {
+        Self::Error
+    }
+}
+
+/// Gaps between the bar and the screen edges, as written in YAML (unset = inherit)
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct MarginConfig {
+    #[serde(default)]
+    pub margin_top: Option<i32>,
+    #[serde(default)]
+    pub margin_left: Option<i32>,
+    #[serde(default)]
+    pub margin_right: Option<i32>,
+    #[serde(default)]
+    pub margin_bottom: Option<i32>,
+}
+
+/// Resolved bar margins in pixels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BarMargins {
+    pub top: i32,
+    pub left: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl MarginConfig {
+    /// Overlay the margins set here onto `base`
+    pub fn overlay(&self, base: BarMargins) -> BarMargins {
+        BarMargins {
+            top: self.margin_top.unwrap_or(base.top),
+            left: self.margin_left.unwrap_or(base.left),
+            right: self.margin_right.unwrap_or(base.right),
+            bottom: self.margin_bottom.unwrap_or(base.bottom),
+        }
+    }
+}
+
+/// Resolved per-monitor bar window settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct BarOptions {
+    pub position: BarPosition,
+    pub layer: BarLayer,
+    pub height: i32,
+    pub margins: BarMargins,
+    pub auto_hide_on_fullscreen: bool,
+    /// Window opacity, already clamped to `0.0..=1.0`; None leaves it to the theme
+    pub opacity: Option<f64>,
+    /// Validated background color; None leaves it to the theme
+    pub background: Option<String>,
+}
+
+impl Default for BarOptions {
+    fn default() -> Self {
+        Self {
+            position: BarPosition::default(),
+            layer: BarLayer::default(),
+            height: DEFAULT_BAR_HEIGHT,
+            margins: BarMargins::default(),
+            auto_hide_on_fullscreen: false,
+            opacity: None,
+            background: None,
+        }
+    }
+}
+
+/// Clamp a configured bar opacity to `0.0..=1.0`, logging out-of-range values; NaN is
+/// treated as fully opaque
+pub fn clamp_opacity(opacity: f64) -> f64 {
+    if opacity.is_nan() {
+        log::warn!("Config: ignoring invalid bar opacity NaN");
+        return 1.0;
+    }
+    let clamped = opacity.clamp(0.0, 1.0);
+    if clamped != opacity {
+        log::warn!(
+            "Config: bar opacity {} out of range, using {}",
+            opacity,
+            clamped
+        );
+    }
+    clamped
+}
+
+/// Configured bar background, if set and valid (invalid values are logged and ignored)
+pub fn valid_bar_background(background: Option<&str>) -> Option<&str> {
+    let background = background?;
+    if is_valid_hex_color(background) {
+        Some(background)
+    } else {
+        log::warn!(
+            "Config: ignoring invalid bar background color '{}' (expected #rgb or #rrggbb)",
+            background
+        );
+        None
+    }
+}
+
+/// Wallpaper for workspaces whose name matches `pattern` (a regex, unanchored)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WallpaperPattern {
+    pub pattern: String,
+    pub path: String,
+}
+
+/// Where a bar placed a module instance: its monitor's connector and its column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModulePlacement {
+    pub output: String,
+    pub column: String,
+}
+
 /// Module configuration with YAML anchor support
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct ModuleConfig {
@@ -54,6 +614,9 @@ pub struct ModuleConfig {
     pub format: Option<String>,
     #[serde(default)]
     pub tooltip: Option<bool>,
+    /// Tooltip markup using the same placeholders as `format`
+    #[serde(default)]
+    pub tooltip_format: Option<String>,
     #[serde(default)]
     pub highlight_active: Option<bool>,
     #[serde(default)]
@@ -64,11 +627,14 @@ pub struct ModuleConfig {
     pub default_wallpaper: Option<String>,
     #[serde(default)]
     pub wallpapers: Option<HashMap<String, String>>, // map by index or name → path
+    /// Ordered regex matches on workspace names, tried after `wallpapers`; first match wins
+    #[serde(default)]
+    pub wallpaper_patterns: Option<Vec<WallpaperPattern>>,
     #[serde(default)]
     pub special_cmd: Option<String>, // e.g., "mytool -i ${current_workspace_image}"
     /// Swww-specific options for wallpaper transitions when used by wallpapers module
     #[serde(default)]
-    pub swww_options: Option<SwwwOptions>,
+    pub swww_options: Option<PartialSwwwOptions>,
     #[serde(default)]
     pub max_length: Option<usize>,
     #[serde(default)]
@@ -99,18 +665,125 @@ pub struct ModuleConfig {
     /// Highlight focused window in viewport (default: true)
     #[serde(default)]
     pub highlight_focused: Option<bool>,
+    /// Where the module sits in its column; defaults to the column's alignment
+    #[serde(default)]
+    pub align: Option<TextAlign>,
+    /// Shell command run on left click
+    #[serde(default)]
+    pub on_click: Option<String>,
+    /// Shell command run on right click
+    #[serde(default)]
+    pub on_click_right: Option<String>,
+    /// Shell command run on middle click
+    #[serde(default)]
+    pub on_click_middle: Option<String>,
+    /// Shell command run on scroll up
+    #[serde(default)]
+    pub on_scroll_up: Option<String>,
+    /// Shell command run on scroll down
+    #[serde(default)]
+    pub on_scroll_down: Option<String>,
+    /// Space in pixels kept on each side of the module (default 0)
+    #[serde(default)]
+    pub padding: Option<i32>,
+    /// Set by the bar building the module, never read from the config; `None` outside a bar
+    #[serde(skip)]
+    pub placement: Option<ModulePlacement>,
     // Allow additional fields
     #[serde(flatten)]
     pub additional: HashMap<String, serde_yaml::Value>,
 }
 
+impl ModuleConfig {
+    /// `self` with the fields set in `overlay` applied on top. Works on the YAML form, so every
+    /// field (typed or additional) overrides; unset fields keep `self`'s value and nested
+    /// mappings (e.g. `wallpapers`) merge key by key.
+    pub fn merged_with(&self, overlay: &ModuleConfig) -> ModuleConfig {
+        let merge = || -> Result<ModuleConfig> {
+            let mut merged = serde_yaml::to_value(self)?;
+            let mut overlay = serde_yaml::to_value(overlay)?;
+            // Unset fields serialize as null; drop them so they don't clear the base value
+            if let Some(map) = overlay.as_mapping_mut() {
+                map.retain(|_, v| !v.is_null());
+            }
+            merge_yaml(&mut merged, overlay);
+            Ok(serde_yaml::from_value(merged)?)
+        };
+        merge().unwrap_or_else(|e| {
+            log::warn!(
+                "ConfigManager: failed to merge module overrides ({}), overlaying them field by field",
+                e
+            );
+            self.overlaid_fields(overlay)
+        })
+    }
+
+    /// Fallback for [`Self::merged_with`]: every option `overlay` sets replaces `self`'s,
+    /// `wallpapers` and additional options are merged key by key
+    fn overlaid_fields(&self, overlay: &ModuleConfig) -> ModuleConfig {
+        let mut merged = self.clone();
+        macro_rules! overlay {
+            ($($field:ident),* $(,)?) => {
+                $(
+                    if overlay.$field.is_some() {
+                        merged.$field.clone_from(&overlay.$field);
+                    }
+                )*
+            };
+        }
+        overlay!(
+            format,
+            tooltip,
+            tooltip_format,
+            highlight_active,
+            show_numbers,
+            show_wallpaper,
+            default_wallpaper,
+            wallpaper_patterns,
+            special_cmd,
+            swww_options,
+            max_length,
+            ellipsize,
+            show_percentage,
+            warn_threshold,
+            critical_threshold,
+            cpu,
+            mem,
+            net,
+            enabled,
+            display,
+            width,
+            show_window_titles,
+            highlight_focused,
+            align,
+            on_click,
+            on_click_right,
+            on_click_middle,
+            on_scroll_up,
+            on_scroll_down,
+            padding,
+        );
+        if let Some(wallpapers) = &overlay.wallpapers {
+            merged
+                .wallpapers
+                .get_or_insert_with(HashMap::new)
+                .extend(wallpapers.clone());
+        }
+        merged.additional.extend(overlay.additional.clone());
+        merged
+    }
+}
+
 /// Column overflow behavior
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, PartialEq)]
 pub enum ColumnOverflowPolicy {
-    #[serde(rename = "hide", alias = "crop")] // accept legacy name "crop"
+    #[serde(rename = "hide")]
     Hide,
     #[serde(rename = "kebab")]
     Kebab,
+    /// Ellipsize labels to fit instead of hiding modules
+    #[serde(rename = "shrink")]
+    Shrink,
 }
 
 impl Default for ColumnOverflowPolicy {
@@ -119,6 +792,23 @@ impl Default for ColumnOverflowPolicy {
     }
 }
 
+impl<'de> Deserialize<'de> for ColumnOverflowPolicy {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> std::result::Result<Self, D::Error> {
+        deserialize_keyword(
+            d,
+            "overflow",
+            &[
+                ("hide", Self::Hide),
+                ("kebab", Self::Kebab),
+                ("shrink", Self::Shrink),
+                // Legacy names
+                ("crop", Self::Hide),
+                ("ellipsize", Self::Shrink),
+            ],
+        )
+    }
+}
+
 // Removed ColumnSize in favor of simpler equal-width behavior + optional fixed width per column
 
 /// Per-column spec: modules + overflow policy
@@ -137,6 +827,54 @@ pub struct ColumnSpec {
     /// Fixed width for fixed-size columns (in pixels)
     #[serde(default)]
     pub width: Option<i32>,
+    /// Minimum column width (in pixels)
+    #[serde(default)]
+    pub min_width: Option<i32>,
+    /// Maximum column width (in pixels); also caps the overflow budget
+    #[serde(default)]
+    pub max_width: Option<i32>,
+    /// Column background color (hex, e.g. "#242424")
+    #[serde(default)]
+    pub background: Option<String>,
+    /// Column text color (hex, e.g. "#f6f3e8")
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Let clicks and scrolls over this column reach the windows below the bar
+    #[serde(default)]
+    pub click_through: Option<bool>,
+}
+
+/// Whether `value` is a CSS hex color: `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa`
+pub fn is_valid_hex_color(value: &str) -> bool {
+    value.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+impl ColumnSpec {
+    /// Configured background color, if set and valid (invalid values are logged and ignored)
+    pub fn valid_background(&self) -> Option<&str> {
+        Self::valid_color(self.background.as_deref(), "background")
+    }
+
+    fn valid_color<'a>(value: Option<&'a str>, field: &str) -> Option<&'a str> {
+        let value = value?;
+        if is_valid_hex_color(value) {
+            Some(value)
+        } else {
+            log::warn!(
+                "Config: ignoring invalid column {} color '{}' (expected #rgb or #rrggbb)",
+                field,
+                value
+            );
+            None
+        }
+    }
+
+    /// Configured text color, if set and valid (invalid values are logged and ignored)
+    pub fn valid_text_color(&self) -> Option<&str> {
+        Self::valid_color(self.color.as_deref(), "text")
+    }
 }
 
 /// Layout configuration with column mapping
@@ -149,8 +887,18 @@ pub struct LayoutConfig {
 /// Monitor configuration with layout and module overrides
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct MonitorConfig {
-    #[serde(rename = "match")]
+    /// Connector pattern, e.g. `eDP-1` or `DP-.*` (default: any connector)
+    #[serde(rename = "match", default = "default_match_pattern")]
     pub match_pattern: String,
+    /// Pattern the monitor's manufacturer must match, e.g. `Dell Inc.`
+    #[serde(default)]
+    pub match_make: Option<String>,
+    /// Pattern the monitor's model must match, e.g. `DELL U2720Q`
+    #[serde(default)]
+    pub match_model: Option<String>,
+    /// Resolution the monitor must have, as `WIDTHxHEIGHT` (pixels or logical size)
+    #[serde(default)]
+    pub match_resolution: Option<String>,
     /// Whether to show the bar on this monitor
     #[serde(default = "default_enabled", alias = "enabled")]
     pub show_bar: bool,
@@ -161,10 +909,106 @@ pub struct MonitorConfig {
     /// Monitor-specific wallpaper settings (overrides global)
     #[serde(default)]
     pub wallpapers: Option<WallpaperConfig>,
+    /// Monitor-specific bar position (overrides global)
+    #[serde(default)]
+    pub position: Option<BarPosition>,
+    /// Monitor-specific layer-shell layer (overrides global)
+    #[serde(default)]
+    pub layer: Option<BarLayer>,
+    /// Monitor-specific bar height in pixels (overrides global)
+    #[serde(default)]
+    pub height: Option<i32>,
+    /// Monitor-specific margins (each edge overrides global)
+    #[serde(flatten)]
+    pub margins: MarginConfig,
+    /// Monitor-specific fullscreen auto-hide (overrides global)
+    #[serde(default)]
+    pub auto_hide_on_fullscreen: Option<bool>,
+    /// Monitor-specific choice to hide rather than destroy the bar when disabled
+    /// (overrides global)
+    #[serde(default)]
+    pub hide_when_disabled: Option<bool>,
+    /// Monitor-specific bar opacity (overrides global)
+    #[serde(default)]
+    pub opacity: Option<f64>,
+    /// Monitor-specific bar background color (overrides global)
+    #[serde(default)]
+    pub background: Option<String>,
+    /// Map of workspace index or name to a layout profile name (overrides global)
+    #[serde(default)]
+    pub by_workspace: HashMap<String, String>,
 }
 
+/// Specificity each `match_make`/`match_model`/`match_resolution` criterion adds, enough to
+/// outrank any connector pattern
+const MONITOR_CRITERION_SPECIFICITY: u32 = 10_000;
+
+impl MonitorConfig {
+    /// Whether this entry applies to `info`: the connector must match `match`, and every
+    /// `match_make`/`match_model`/`match_resolution` that is set must match too
+    pub fn matches(&self, info: &MonitorInfo) -> bool {
+        let matches_optional = |pattern: &Option<String>, value: &Option<String>| match pattern {
+            None => true,
+            Some(pattern) => value
+                .as_deref()
+                .is_some_and(|value| ConfigManager::matches_pattern(value, pattern)),
+        };
+        ConfigManager::matches_pattern(&info.connector, &self.match_pattern)
+            && matches_optional(&self.match_make, &info.manufacturer)
+            && matches_optional(&self.match_model, &info.model)
+            && self
+                .match_resolution
+                .as_deref()
+                .is_none_or(|resolution| Self::matches_resolution(resolution, info))
+    }
+
+    /// Specificity of the entry (higher = more specific): the connector pattern's, plus
+    /// [`MONITOR_CRITERION_SPECIFICITY`] and the pattern's own specificity per extra criterion
+    pub fn specificity(&self) -> u32 {
+        let extra: u32 = [&self.match_make, &self.match_model]
+            .into_iter()
+            .flatten()
+            .map(|pattern| {
+                MONITOR_CRITERION_SPECIFICITY + ConfigManager::pattern_specificity(pattern)
+            })
+            .sum();
+        let resolution = if self.match_resolution.is_some() {
+            MONITOR_CRITERION_SPECIFICITY
+        } else {
+            0
+        };
+        ConfigManager::pattern_specificity(&self.match_pattern) + extra + resolution
+    }
+
+    /// Whether `resolution` (`WIDTHxHEIGHT`) is the monitor's pixel size or logical size
+    fn matches_resolution(resolution: &str, info: &MonitorInfo) -> bool {
+        let Some((width, height)) = parse_resolution(resolution) else {
+            return false;
+        };
+        (width, height) == info.pixel_size() || (width, height) == info.logical_size
+    }
+}
+
+/// Parse a `WIDTHxHEIGHT` resolution such as `2560x1440`
+pub fn parse_resolution(resolution: &str) -> Option<(i32, i32)> {
+    let (width, height) = resolution.trim().split_once(['x', 'X'])?;
+    let width: i32 = width.trim().parse().ok()?;
+    let height: i32 = height.trim().parse().ok()?;
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+/// Transition types accepted by `swww img --transition-type`
+pub const SWWW_TRANSITION_TYPES: &[&str] = &[
+    "none", "simple", "fade", "left", "right", "top", "bottom", "wipe", "wave", "grow", "center",
+    "any", "outer", "random",
+];
+/// Scaling filters accepted by `swww img --filter`
+pub const SWWW_FILTERS: &[&str] = &["Nearest", "Bilinear", "CatmullRom", "Mitchell", "Lanczos3"];
+/// Resize modes accepted by `swww img --resize`
+pub const SWWW_RESIZE_MODES: &[&str] = &["no", "crop", "fit", "stretch"];
+
 /// Swww-specific options for wallpaper transitions
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SwwwOptions {
     /// Transition type (none, simple, fade, left, right, top, bottom, wipe, wave, grow, center, any, outer, random)
     #[serde(default = "default_transition_type")]
@@ -211,13 +1055,160 @@ fn default_fill_color() -> String {
     "000000".to_string()
 }
 
+impl Default for SwwwOptions {
+    fn default() -> Self {
+        Self {
+            transition_type: default_transition_type(),
+            transition_duration: default_transition_duration(),
+            transition_step: default_transition_step(),
+            transition_fps: default_transition_fps(),
+            filter: default_filter(),
+            resize: default_resize(),
+            fill_color: default_fill_color(),
+        }
+    }
+}
+
+/// `swww_options` of a module block: only the fields it sets, so a per-monitor module
+/// override changes those and inherits the rest. Unset (or `null`) fields fall back to the
+/// [`SwwwOptions`] defaults once resolved.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PartialSwwwOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transition_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transition_duration: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transition_step: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transition_fps: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resize: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fill_color: Option<String>,
+}
+
+impl PartialSwwwOptions {
+    /// The options with every unset field at its default
+    pub fn resolve(&self) -> SwwwOptions {
+        let defaults = SwwwOptions::default();
+        SwwwOptions {
+            transition_type: self
+                .transition_type
+                .clone()
+                .unwrap_or(defaults.transition_type),
+            transition_duration: self
+                .transition_duration
+                .unwrap_or(defaults.transition_duration),
+            transition_step: self.transition_step.unwrap_or(defaults.transition_step),
+            transition_fps: self.transition_fps.unwrap_or(defaults.transition_fps),
+            filter: self.filter.clone().unwrap_or(defaults.filter),
+            resize: self.resize.clone().unwrap_or(defaults.resize),
+            fill_color: self.fill_color.clone().unwrap_or(defaults.fill_color),
+        }
+    }
+}
+
+impl SwwwOptions {
+    /// Fields whose value swww doesn't know, as `(flag, value)` pairs
+    pub fn unknown_values(&self) -> Vec<(&'static str, &str)> {
+        [
+            (
+                "transition-type",
+                &self.transition_type,
+                SWWW_TRANSITION_TYPES,
+            ),
+            ("filter", &self.filter, SWWW_FILTERS),
+            ("resize", &self.resize, SWWW_RESIZE_MODES),
+        ]
+        .into_iter()
+        .filter(|(_, value, allowed)| !allowed.contains(&value.as_str()))
+        .map(|(flag, value, _)| (flag, value.as_str()))
+        .collect()
+    }
+
+    /// Check the options against what swww accepts: `transition_type`, `filter` and `resize`
+    /// from the documented sets, `fill_color` as six hex digits without `#` (e.g. `1e1e2e`).
+    /// The error lists every bad field with its valid values.
+    pub fn validate(&self) -> Result<()> {
+        let mut problems: Vec<String> = [
+            (
+                "transition_type",
+                &self.transition_type,
+                SWWW_TRANSITION_TYPES,
+            ),
+            ("filter", &self.filter, SWWW_FILTERS),
+            ("resize", &self.resize, SWWW_RESIZE_MODES),
+        ]
+        .into_iter()
+        .filter(|(_, value, allowed)| !allowed.contains(&value.as_str()))
+        .map(|(field, value, allowed)| {
+            format!(
+                "invalid {} `{}`, expected one of: {}",
+                field,
+                value,
+                allowed.join(", ")
+            )
+        })
+        .collect();
+        if self.fill_color.len() != 6 || !self.fill_color.chars().all(|c| c.is_ascii_hexdigit()) {
+            problems.push(format!(
+                "invalid fill_color `{}`, expected 6 hex digits like 1e1e2e",
+                self.fill_color
+            ));
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            bail!("swww_options: {}", problems.join("; "))
+        }
+    }
+
+    /// `swww img` flags for these options. Unknown values are passed through (newer swww
+    /// versions may accept them) but logged, since swww rejects the whole command otherwise.
+    pub fn to_args(&self) -> Vec<String> {
+        for (flag, value) in self.unknown_values() {
+            log::warn!(
+                "ConfigManager: unknown swww --{} '{}', swww may reject the wallpaper command",
+                flag,
+                value
+            );
+        }
+
+        let mut args = vec![
+            "--transition-type".to_string(),
+            self.transition_type.clone(),
+        ];
+        // swww ignores the duration for instant transitions
+        if !matches!(self.transition_type.as_str(), "simple" | "none") {
+            args.push("--transition-duration".to_string());
+            args.push(self.transition_duration.to_string());
+        }
+        args.extend([
+            "--transition-step".to_string(),
+            self.transition_step.to_string(),
+            "--transition-fps".to_string(),
+            self.transition_fps.to_string(),
+            "--filter".to_string(),
+            self.filter.clone(),
+            "--resize".to_string(),
+            self.resize.clone(),
+            "--fill-color".to_string(),
+            self.fill_color.clone(),
+        ]);
+        args
+    }
+}
+
 /// Wallpaper configuration with per-workspace mapping
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct WallpaperConfig {
     /// Default wallpaper path
     #[serde(default)]
     pub default: Option<String>,
-    /// Map of workspace index or name to wallpaper path
+    /// Map of workspace index or name to wallpaper path; a directory runs a slideshow
     #[serde(default)]
     pub by_workspace: HashMap<String, String>,
     /// Override command to set wallpaper; supports ${current_workspace_image} substitution
@@ -226,6 +1217,12 @@ pub struct WallpaperConfig {
     /// Swww-specific options for wallpaper transitions
     #[serde(default)]
     pub swww_options: Option<SwwwOptions>,
+    /// Seconds between slideshow images (default: 300)
+    #[serde(default)]
+    pub slideshow_interval_secs: Option<u64>,
+    /// Show slideshow images in random order instead of by file name
+    #[serde(default)]
+    pub shuffle: Option<bool>,
 }
 
 /// Application-level configuration with YAML anchors
@@ -234,10 +1231,45 @@ pub struct ApplicationConfig {
     /// CSS theme to use for styling the bar
     #[serde(default = "default_theme")]
     pub theme: String,
+    /// Screen edge the bars are anchored to
+    #[serde(default)]
+    pub position: BarPosition,
+    /// Layer-shell layer the bars are placed on
+    #[serde(default)]
+    pub layer: BarLayer,
+    /// Bar height in pixels (default 40)
+    #[serde(default)]
+    pub height: Option<i32>,
+    /// Gaps between the bar and the screen edges (default 0)
+    #[serde(flatten)]
+    pub margins: MarginConfig,
+    /// Hide the bar while the focused window on its monitor is fullscreen
+    #[serde(default)]
+    pub auto_hide_on_fullscreen: Option<bool>,
+    /// Keep a disabled monitor's bar hidden instead of destroying it, so re-enabling it
+    /// is fast and keeps its widget state (default false)
+    #[serde(default)]
+    pub hide_when_disabled: Option<bool>,
+    /// Bar window opacity from 0.0 to 1.0 (default: the theme's)
+    #[serde(default)]
+    pub opacity: Option<f64>,
+    /// Bar background color as `#rgb`, `#rrggbb` or with alpha (default: the theme's)
+    #[serde(default)]
+    pub background: Option<String>,
     /// Global module defaults (YAML anchors)
     pub modules: HashMap<String, ModuleConfig>,
+    /// Named colors that color fields can reference as `$name`
+    #[serde(default)]
+    pub colors: HashMap<String, String>,
+    /// Whether a layout naming an unknown module is an error or a warning
+    #[serde(default)]
+    pub unknown_modules: UnknownModulePolicy,
     /// Reusable layout profiles (YAML anchors)
     pub layouts: HashMap<String, LayoutConfig>,
+    /// Map of workspace index or name to a layout profile name, used while that workspace
+    /// is focused
+    #[serde(default)]
+    pub by_workspace: HashMap<String, String>,
     /// Global wallpaper settings
     #[serde(default)]
     pub wallpapers: WallpaperConfig,
@@ -250,6 +1282,9 @@ fn default_theme() -> String {
     "wombat".to_string()
 }
 
+/// Level names accepted by `logging.level` and `logging.module_levels`
+pub const LOG_LEVELS: &[&str] = &["trace", "debug", "info", "warn", "error"];
+
 /// Logging configuration
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct LoggingConfig {
@@ -264,6 +1299,18 @@ pub struct LoggingConfig {
     pub include_line: bool,
     #[serde(default = "default_true")]
     pub include_class: bool,
+    /// Rotate the log file once it grows past this many bytes (0 = never rotate)
+    #[serde(default = "default_log_max_size_bytes")]
+    pub max_size_bytes: u64,
+    /// Rotated files to keep as `<file>.1` (newest) .. `<file>.N`
+    #[serde(default = "default_log_max_files")]
+    pub max_files: usize,
+    /// Level overrides keyed by the `Class:` prefix of log lines, e.g. `Workspaces: debug`
+    #[serde(default)]
+    pub module_levels: HashMap<String, String>,
+    /// Also send log lines to the systemd journal, when running under systemd
+    #[serde(default)]
+    pub journald: bool,
 }
 
 /// Complete configuration structure
@@ -274,10 +1321,20 @@ pub struct NiriBarConfig {
     pub logging: LoggingConfig,
 }
 
+fn default_match_pattern() -> String {
+    ".*".to_string()
+}
+
 fn default_enabled() -> bool {
     true
 }
 
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        default_logging_config()
+    }
+}
+
 fn default_logging_config() -> LoggingConfig {
     LoggingConfig {
         level: "info".to_string(),
@@ -287,6 +1344,10 @@ fn default_logging_config() -> LoggingConfig {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: default_log_max_size_bytes(),
+        max_files: default_log_max_files(),
+        module_levels: HashMap::new(),
+        journald: false,
     }
 }
 
@@ -294,6 +1355,14 @@ fn default_log_format() -> String {
     "iso8601".to_string()
 }
 
+fn default_log_max_size_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_log_max_files() -> usize {
+    5
+}
+
 fn default_true() -> bool {
     true
 }
@@ -314,6 +1383,12 @@ pub struct ConfigManager {
     pub config: Arc<Mutex<Option<NiriBarConfig>>>,
     event_tx: broadcast::Sender<ConfigEvent>,
     watcher: Option<FileWatcher>,
+    /// Message of the last failed load, cleared by the next successful one
+    last_error: Arc<Mutex<Option<String>>>,
+    config_path: PathBuf,
+    /// Make, model and size of the connected monitors, for `match_make`/`match_model`/
+    /// `match_resolution`; connectors missing here match on their name only
+    monitor_infos: Arc<Mutex<HashMap<String, MonitorInfo>>>,
 }
 
 impl Clone for ConfigManager {
@@ -321,50 +1396,118 @@ impl Clone for ConfigManager {
         Self {
             config: self.config.clone(),
             event_tx: self.event_tx.clone(),
+            last_error: self.last_error.clone(),
+            config_path: self.config_path.clone(),
+            monitor_infos: self.monitor_infos.clone(),
             watcher: None, // Don't clone the watcher
         }
     }
 }
 
 impl ConfigManager {
-    /// Create a new configuration manager
+    /// Create a new configuration manager for the config file found via the environment
+    /// (see [`resolve_config_path_from_env`])
     pub fn new() -> Self {
+        Self::with_config_path(resolve_config_path_from_env(None))
+    }
+
+    /// Create a new configuration manager for an explicit config file
+    pub fn with_config_path(config_path: impl Into<PathBuf>) -> Self {
         let (event_tx, _) = broadcast::channel(100);
 
         Self {
             config: Arc::new(Mutex::new(None)),
             event_tx,
             watcher: None,
+            last_error: Arc::new(Mutex::new(None)),
+            config_path: config_path.into(),
+            monitor_infos: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Manager holding an already-parsed config, for resolving per-monitor settings of a
+    /// config that isn't the live one
+    pub fn from_config(config: NiriBarConfig) -> Self {
+        let manager = Self::with_config_path(DEFAULT_CONFIG_FILE);
+        *manager.config.lock().unwrap() = Some(config);
+        manager
+    }
+
+    /// Path of the YAML file this manager loads and watches
+    pub fn config_path(&self) -> &Path {
+        &self.config_path
+    }
+
+    /// Record a connected monitor's make, model and size so per-connector lookups can
+    /// honour `match_make`/`match_model`/`match_resolution`
+    pub fn set_monitor_info(&self, info: MonitorInfo) {
+        self.monitor_infos
+            .lock()
+            .unwrap()
+            .insert(info.connector.clone(), info);
+    }
+
+    /// Forget an unplugged monitor recorded by [`Self::set_monitor_info`]
+    pub fn remove_monitor_info(&self, connector: &str) {
+        self.monitor_infos.lock().unwrap().remove(connector);
+    }
+
+    /// Recorded info for `connector`, or one carrying only the connector name
+    pub fn monitor_info(&self, connector: &str) -> MonitorInfo {
+        self.monitor_infos
+            .lock()
+            .unwrap()
+            .get(connector)
+            .cloned()
+            .unwrap_or_else(|| MonitorInfo::for_connector(connector))
+    }
+
+    /// Most specific monitor entry matching `info` on connector, make, model and resolution
+    pub fn best_monitor_config(&self, info: &MonitorInfo) -> Option<MonitorConfig> {
+        let config_guard = self.config.lock().unwrap();
+        let config = config_guard.as_ref()?;
+        Self::best_monitor_match(config, info).cloned()
+    }
+
     /// Start monitoring the configuration file
     pub async fn start(&mut self) -> Result<()> {
         log::info!("ConfigManager: Starting configuration file monitoring...");
 
         let config = self.config.clone();
         let event_tx = self.event_tx.clone();
+        let last_error = self.last_error.clone();
 
         // Create file watcher for the configuration file
-        let mut watcher = FileWatcher::new("niri-bar.yaml")
+        let file_name = self
+            .config_path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| DEFAULT_CONFIG_FILE.to_string());
+        let dir = match self.config_path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let mut watcher = FileWatcher::with_search_paths(file_name, vec![dir])
             .on_load({
                 let config = config.clone();
                 let event_tx = event_tx.clone();
+                let last_error = last_error.clone();
                 move |path, content| {
-                    Self::handle_config_load(&config, &event_tx, path, content);
+                    Self::handle_config_load(&config, &event_tx, &last_error, path, content);
                 }
             })
             .on_change({
                 let config = config.clone();
                 let event_tx = event_tx.clone();
+                let last_error = last_error.clone();
                 move |path, content| {
-                    Self::handle_config_change(&config, &event_tx, path, content);
+                    Self::handle_config_change(&config, &event_tx, &last_error, path, content);
                 }
             })
             .on_error({
                 let event_tx = event_tx.clone();
                 move |path, error| {
-                    Self::handle_config_error(&event_tx, path, error);
+                    Self::handle_config_error(&event_tx, &last_error, path, error);
                 }
             });
 
@@ -390,16 +1533,63 @@ impl ConfigManager {
         self.event_tx.subscribe()
     }
 
+    /// Error message of the last failed configuration load, if it hasn't been fixed since
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.lock().unwrap().clone()
+    }
+
+    /// Parse and install new configuration content, emitting `Updated` on success. On failure
+    /// the current configuration is kept and the error is recorded and emitted.
+    pub fn apply_config(&self, content: &[u8]) -> Result<NiriBarConfig> {
+        match Self::parse_config_from(content, &self.config_path) {
+            Ok(new_config) => {
+                *self.config.lock().unwrap() = Some(new_config.clone());
+                Self::set_last_error(&self.last_error, None);
+                let _ = self.event_tx.send(ConfigEvent::Updated(new_config.clone()));
+                Ok(new_config)
+            }
+            Err(e) => {
+                let message = format!("Failed to parse configuration: {:#}", e);
+                log::error!("ConfigManager: {}", message);
+                Self::set_last_error(&self.last_error, Some(message.clone()));
+                let _ = self.event_tx.send(ConfigEvent::Error(message));
+                Err(e)
+            }
+        }
+    }
+
+    /// Switch the loaded configuration to theme `name` in memory, emitting `Updated`. The
+    /// file is left alone, so the next reload brings its theme back. Fails before a
+    /// configuration is loaded.
+    pub fn set_theme(&self, name: &str) -> Result<NiriBarConfig> {
+        let updated = {
+            let mut guard = self.config.lock().unwrap();
+            let config = guard
+                .as_mut()
+                .ok_or_else(|| anyhow::anyhow!("no configuration loaded"))?;
+            config.application.theme = name.to_string();
+            config.clone()
+        };
+        log::info!("ConfigManager: 🎨 theme set to '{}'", name);
+        let _ = self.event_tx.send(ConfigEvent::Updated(updated.clone()));
+        Ok(updated)
+    }
+
+    fn set_last_error(last_error: &Arc<Mutex<Option<String>>>, error: Option<String>) {
+        *last_error.lock().unwrap() = error;
+    }
+
     /// Handle initial configuration load
     fn handle_config_load(
         config: &Arc<Mutex<Option<NiriBarConfig>>>,
         event_tx: &broadcast::Sender<ConfigEvent>,
+        last_error: &Arc<Mutex<Option<String>>>,
         path: std::path::PathBuf,
         content: Vec<u8>,
     ) {
         log::info!("ConfigManager: Loading configuration from {:?}", path);
 
-        match Self::parse_config(&content) {
+        match Self::parse_config_from(&content, &path) {
             Ok(new_config) => {
                 log::info!("ConfigManager: Configuration loaded successfully");
 
@@ -409,6 +1599,8 @@ impl ConfigManager {
                     *config_guard = Some(new_config.clone());
                 }
 
+                Self::set_last_error(last_error, None);
+
                 // Emit loaded event
                 let _ = event_tx.send(ConfigEvent::Loaded(new_config));
             }
@@ -420,10 +1612,9 @@ impl ConfigManager {
                 );
 
                 // Emit error event
-                let _ = event_tx.send(ConfigEvent::Error(format!(
-                    "Failed to parse configuration from {:?}: {}",
-                    path, e
-                )));
+                let message = format!("Failed to parse configuration from {:?}: {}", path, e);
+                Self::set_last_error(last_error, Some(message.clone()));
+                let _ = event_tx.send(ConfigEvent::Error(message));
             }
         }
     }
@@ -432,12 +1623,13 @@ impl ConfigManager {
     fn handle_config_change(
         config: &Arc<Mutex<Option<NiriBarConfig>>>,
         event_tx: &broadcast::Sender<ConfigEvent>,
+        last_error: &Arc<Mutex<Option<String>>>,
         path: std::path::PathBuf,
         content: Vec<u8>,
     ) {
         log::info!("ConfigManager: Configuration file changed, reloading...");
 
-        match Self::parse_config(&content) {
+        match Self::parse_config_from(&content, &path) {
             Ok(new_config) => {
                 log::info!("ConfigManager: Configuration updated successfully");
 
@@ -447,6 +1639,8 @@ impl ConfigManager {
                     *config_guard = Some(new_config.clone());
                 }
 
+                Self::set_last_error(last_error, None);
+
                 // Emit updated event
                 let _ = event_tx.send(ConfigEvent::Updated(new_config));
             }
@@ -458,10 +1652,12 @@ impl ConfigManager {
                 );
 
                 // Emit error event (don't update current config)
-                let _ = event_tx.send(ConfigEvent::Error(format!(
+                let message = format!(
                     "Failed to parse updated configuration from {:?}: {}",
                     path, e
-                )));
+                );
+                Self::set_last_error(last_error, Some(message.clone()));
+                let _ = event_tx.send(ConfigEvent::Error(message));
             }
         }
     }
@@ -469,6 +1665,7 @@ impl ConfigManager {
     /// Handle configuration error
     fn handle_config_error(
         event_tx: &broadcast::Sender<ConfigEvent>,
+        last_error: &Arc<Mutex<Option<String>>>,
         path: std::path::PathBuf,
         error: String,
     ) {
@@ -478,10 +1675,9 @@ impl ConfigManager {
             error
         );
 
-        let _ = event_tx.send(ConfigEvent::Error(format!(
-            "Configuration error for {:?}: {}",
-            path, error
-        )));
+        let message = format!("Configuration error for {:?}: {}", path, error);
+        Self::set_last_error(last_error, Some(message.clone()));
+        let _ = event_tx.send(ConfigEvent::Error(message));
     }
 
     /// Parse YAML content into configuration structure
@@ -506,46 +1702,80 @@ impl ConfigManager {
     /// assert_eq!(config.logging.level, "info");
     /// ```
     pub fn parse_config(content: &[u8]) -> Result<NiriBarConfig> {
-        let content_str = String::from_utf8(content.to_vec())?;
-
-        // Parse YAML
-        let config: NiriBarConfig = serde_yaml::from_str(&content_str)?;
-
-        // Validate against schema
-        Self::validate_config(&config)?;
-
-        Ok(config)
+        Self::parse_yaml_config(content, Path::new("."), Vec::new())
     }
 
-    /// Validate configuration against JSON schema
-    fn validate_config(config: &NiriBarConfig) -> Result<()> {
-        // Load schema
-        let _schema_content = include_str!("niri-bar-yaml.schema.json");
-        let _schema: serde_json::Value = serde_json::from_str(_schema_content)?;
+    /// Parse configuration read from `source`; `include`s resolve relative to its directory
+    pub fn parse_config_from(content: &[u8], source: &Path) -> Result<NiriBarConfig> {
+        let stack = source.canonicalize().into_iter().collect();
+        Self::parse_yaml_config(content, include_base_dir(source), stack)
+    }
 
-        // Convert config to JSON for validation
-        let _config_json = serde_json::to_value(config)?;
+    fn parse_yaml_config(
+        content: &[u8],
+        base_dir: &Path,
+        mut include_stack: Vec<PathBuf>,
+    ) -> Result<NiriBarConfig> {
+        let content_str = String::from_utf8(content.to_vec())?;
 
-        // Validate (using jsonschema crate if available, otherwise skip)
-        // For now, we'll do basic validation manually
-        Self::basic_validation(config)?;
+        // Parse YAML and merge in included files, then resolve `$name` palette colors and
+        // expand ${VAR} in every string value before it's typed
+        let value: serde_yaml::Value = serde_yaml::from_str(&content_str)?;
+        let mut value = resolve_includes(value, base_dir, &mut include_stack)?;
+        resolve_palette(&mut value)?;
+        expand_env_in_value(&mut value);
+
+        // Check the document against the schema, then type and validate it. Schema
+        // violations name the offending fields and lead the error; the typed error (which
+        // lists accepted values) stays attached as its cause.
+        let violations = Self::schema_violations(&value)?;
+        let config = serde_yaml::from_value::<NiriBarConfig>(value)
+            .map_err(anyhow::Error::from)
+            .and_then(|config| Self::basic_validation(&config).map(|()| config));
+        match (config, violations) {
+            (Ok(config), None) => Ok(config),
+            (Ok(_), Some(violations)) => bail!("{}", violations),
+            (Err(e), None) => Err(e),
+            (Err(e), Some(violations)) => Err(e.context(violations)),
+        }
+    }
 
-        Ok(())
+    /// Schema violations of a resolved config document, one `Invalid config at <path>: ...`
+    /// line each; `None` when it conforms
+    fn schema_violations(value: &serde_yaml::Value) -> Result<Option<String>> {
+        let json =
+            serde_json::to_value(value).context("config can't be checked against the schema")?;
+        let lines: Vec<String> = schema_errors(&json)
+            .iter()
+            .map(|error| format!("Invalid config at {}", error))
+            .collect();
+        Ok((!lines.is_empty()).then(|| lines.join("\n")))
     }
 
     /// Basic configuration validation
     pub fn basic_validation(config: &NiriBarConfig) -> Result<()> {
-        // Validate logging level
-        let valid_levels = ["debug", "info", "warn", "error"];
-        if !valid_levels.contains(&config.logging.level.as_str()) {
+        // Validate logging levels, global and per module
+        if !LOG_LEVELS.contains(&config.logging.level.as_str()) {
             return Err(anyhow::anyhow!(
                 "Invalid logging level: {}",
                 config.logging.level
             ));
         }
+        let mut module_levels: Vec<_> = config.logging.module_levels.iter().collect();
+        module_levels.sort();
+        if let Some((module, level)) = module_levels
+            .into_iter()
+            .find(|(_, level)| !LOG_LEVELS.contains(&level.as_str()))
+        {
+            return Err(anyhow::anyhow!(
+                "Invalid logging level for module '{}': {}",
+                module,
+                level
+            ));
+        }
 
         // Validate logging format
-        let valid_formats = ["iso8601", "simple"];
+        let valid_formats = ["iso8601", "simple", "json"];
         if !valid_formats.contains(&config.logging.format.as_str()) {
             return Err(anyhow::anyhow!(
                 "Invalid logging format: {}",
@@ -553,18 +1783,139 @@ impl ConfigManager {
             ));
         }
 
+        let unknown = Self::unknown_module_names(config, crate::modules::is_known_module);
+        if !unknown.is_empty() {
+            let names = unknown.join(", ");
+            match config.application.unknown_modules {
+                UnknownModulePolicy::Error => {
+                    return Err(anyhow::anyhow!("Unknown module(s) in layouts: {}", names));
+                }
+                UnknownModulePolicy::Warn => {
+                    log::warn!(
+                        "Config: unknown module(s) in layouts will be skipped: {}",
+                        names
+                    )
+                }
+            }
+        }
+
+        let too_fast =
+            Self::sub_second_intervals(config, crate::modules::refreshes_on_shared_ticks);
+        if !too_fast.is_empty() {
+            log::warn!(
+                "Config: interval_ms below 1000 runs at 1s for: {}",
+                too_fast.join(", ")
+            );
+        }
+
+        // Validate swww options wherever wallpapers can be configured
+        let app = &config.application;
+        let module_swww_options = |m: &ModuleConfig| m.swww_options.as_ref().map(|o| o.resolve());
+        let swww_options = app
+            .wallpapers
+            .swww_options
+            .clone()
+            .into_iter()
+            .chain(app.modules.values().filter_map(module_swww_options))
+            .chain(app.monitors.iter().flat_map(|monitor| {
+                let wallpapers = monitor.wallpapers.as_ref();
+                let modules = monitor.modules.iter().flat_map(|m| m.values());
+                wallpapers
+                    .and_then(|w| w.swww_options.clone())
+                    .into_iter()
+                    .chain(modules.filter_map(module_swww_options))
+            }));
+        for options in swww_options {
+            options.validate()?;
+        }
+
         // Validate monitor patterns
         for monitor_config in &config.application.monitors {
             if monitor_config.match_pattern.is_empty() {
                 return Err(anyhow::anyhow!("Monitor match pattern cannot be empty"));
             }
+            if let Some(resolution) = &monitor_config.match_resolution
+                && parse_resolution(resolution).is_none()
+            {
+                return Err(anyhow::anyhow!(
+                    "Invalid match_resolution '{}': expected WIDTHxHEIGHT, e.g. 2560x1440",
+                    resolution
+                ));
+            }
+            if let Some(height) = monitor_config.height
+                && height < 1
+            {
+                return Err(anyhow::anyhow!(
+                    "Invalid height {} for monitor '{}': must be at least 1",
+                    height,
+                    monitor_config.match_pattern
+                ));
+            }
+        }
+        if let Some(height) = app.height
+            && height < 1
+        {
+            return Err(anyhow::anyhow!(
+                "Invalid application height {}: must be at least 1",
+                height
+            ));
         }
 
         Ok(())
     }
 
+    /// Module names referenced by any column of the application layouts or the monitor
+    /// layouts that `is_known` rejects, sorted and without duplicates
+    pub fn unknown_module_names(
+        config: &NiriBarConfig,
+        is_known: impl Fn(&str) -> bool,
+    ) -> Vec<String> {
+        let app = &config.application;
+        let layouts = app
+            .layouts
+            .values()
+            .chain(app.monitors.iter().filter_map(|m| m.layout.as_ref()));
+        let mut unknown: Vec<String> = layouts
+            .flat_map(|layout| layout.columns.values())
+            .flat_map(|column| column.modules.iter())
+            .filter(|name| !is_known(name))
+            .cloned()
+            .collect();
+        unknown.sort();
+        unknown.dedup();
+        unknown
+    }
+
+    /// Module settings asking for an `interval_ms` under a second from modules that
+    /// `on_shared_ticks` refresh; the shared ticks never fire faster than 1s
+    pub fn sub_second_intervals(
+        config: &NiriBarConfig,
+        on_shared_ticks: impl Fn(&str) -> bool,
+    ) -> Vec<String> {
+        let app = &config.application;
+        let modules = app
+            .modules
+            .iter()
+            .chain(app.monitors.iter().flat_map(|m| m.modules.iter().flatten()));
+        let mut names: Vec<String> = modules
+            .filter(|(name, _)| on_shared_ticks(name))
+            .filter(|(_, settings)| {
+                settings
+                    .additional
+                    .get("interval_ms")
+                    .and_then(|v| v.as_u64())
+                    .is_some_and(|ms| ms < 1000)
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
     /// Check if a monitor matches any pattern in the application config
     pub fn is_monitor_enabled(&self, monitor_name: &str) -> bool {
+        let info = self.monitor_info(monitor_name);
         let config_guard = self.config.lock().unwrap();
         let config = match config_guard.as_ref() {
             Some(config) => config,
@@ -577,8 +1928,8 @@ impl ConfigManager {
         let mut best_specificity = 0;
 
         for monitor_config in &config.application.monitors {
-            if Self::matches_pattern(monitor_name, &monitor_config.match_pattern) {
-                let specificity = Self::pattern_specificity(&monitor_config.match_pattern);
+            if monitor_config.matches(&info) {
+                let specificity = monitor_config.specificity();
                 if specificity > best_specificity {
                     best_match = Some(monitor_config);
                     best_specificity = specificity;
@@ -590,25 +1941,20 @@ impl ConfigManager {
     }
 
     /// Calculate pattern specificity (higher = more specific)
+    ///
+    /// Derived from the compiled pattern: a pure literal (optionally wrapped in
+    /// `^...$`) ranks above any other pattern. Patterns always match the whole
+    /// name, so explicit anchors don't count. Within a tier, more literal
+    /// characters win. Invalid patterns score 0 and never win.
     pub fn pattern_specificity(pattern: &str) -> u32 {
-        if pattern == ".*" {
-            return 1; // Least specific
-        }
-        if pattern.starts_with("^") && pattern.ends_with("$") {
-            if pattern.contains(".*") {
-                return 10; // Specific pattern with wildcard
-            } else {
-                return 100; // Exact match
-            }
-        }
-        if pattern.contains(".*") {
-            return 5; // Pattern with wildcard
-        }
-        50 // Other patterns
+        Self::compiled_pattern(pattern)
+            .map(|c| c.specificity)
+            .unwrap_or(0)
     }
 
     /// Get layout configuration for a specific monitor
     pub fn get_monitor_layout(&self, monitor_name: &str) -> Option<LayoutConfig> {
+        let info = self.monitor_info(monitor_name);
         let config_guard = self.config.lock().unwrap();
         let config = config_guard.as_ref()?;
 
@@ -617,8 +1963,8 @@ impl ConfigManager {
             .application
             .monitors
             .iter()
-            .filter(|m| Self::matches_pattern(monitor_name, &m.match_pattern))
-            .map(|m| (m, Self::pattern_specificity(&m.match_pattern)))
+            .filter(|m| m.matches(&info))
+            .map(|m| (m, m.specificity()))
             .collect();
         // Sort by specificity descending
         matches.sort_by(|a, b| b.1.cmp(&a.1));
@@ -639,31 +1985,214 @@ impl ConfigManager {
         config.application.layouts.values().next().cloned()
     }
 
-    /// Get module configuration for a specific monitor
-    pub fn get_monitor_modules(&self, monitor_name: &str) -> Option<HashMap<String, ModuleConfig>> {
-        let config_guard = self.config.lock().unwrap();
-        let config = config_guard.as_ref()?;
+    /// Layout for `monitor_name` while it shows `workspace`: a `by_workspace` entry of the
+    /// matching monitors (most specific first), then of the application, each keyed by
+    /// workspace name before index. Falls back to [`Self::get_monitor_layout`].
+    pub fn get_workspace_layout(
+        &self,
+        monitor_name: &str,
+        workspace: &WorkspaceInfo,
+    ) -> Option<LayoutConfig> {
+        let info = self.monitor_info(monitor_name);
+        let profile = {
+            let config_guard = self.config.lock().unwrap();
+            let config = config_guard.as_ref()?;
+            Self::workspace_layout_profile(config, &info, workspace).and_then(|name| {
+                let layout = config.application.layouts.get(name).cloned();
+                if layout.is_none() {
+                    log::warn!(
+                        "ConfigManager: by_workspace layout '{}' for workspace {} is not defined in layouts",
+                        name,
+                        workspace.idx
+                    );
+                }
+                layout
+            })
+        };
+        profile.or_else(|| self.get_monitor_layout(monitor_name))
+    }
 
-        // Start from global module defaults
-        let mut merged: HashMap<String, ModuleConfig> = config.application.modules.clone();
+    /// Name of the layout profile mapped to `workspace` on the monitor `info`, if any
+    fn workspace_layout_profile<'a>(
+        config: &'a NiriBarConfig,
+        info: &MonitorInfo,
+        workspace: &WorkspaceInfo,
+    ) -> Option<&'a str> {
+        let key_name = workspace.name.as_deref().unwrap_or("");
+        let key_idx = workspace.idx.to_string();
+        let lookup = |by_workspace: &'a HashMap<String, String>| {
+            by_workspace
+                .get(key_name)
+                .or_else(|| by_workspace.get(&key_idx))
+                .map(String::as_str)
+        };
 
-        // Overlay the most specific matching monitor's modules if present
+        let mut matches: Vec<&MonitorConfig> = config
+            .application
+            .monitors
+            .iter()
+            .filter(|m| m.matches(info))
+            .collect();
+        matches.sort_by_key(|m| std::cmp::Reverse(m.specificity()));
+
+        matches
+            .into_iter()
+            .find_map(|mc| lookup(&mc.by_workspace))
+            .or_else(|| lookup(&config.application.by_workspace))
+    }
+
+    /// Most specific monitor entry matching `info`
+    fn best_monitor_match<'a>(
+        config: &'a NiriBarConfig,
+        info: &MonitorInfo,
+    ) -> Option<&'a MonitorConfig> {
         let mut best_match: Option<&MonitorConfig> = None;
         let mut best_specificity = 0;
         for monitor_config in &config.application.monitors {
-            if Self::matches_pattern(monitor_name, &monitor_config.match_pattern) {
-                let specificity = Self::pattern_specificity(&monitor_config.match_pattern);
+            if monitor_config.matches(info) {
+                let specificity = monitor_config.specificity();
                 if specificity > best_specificity {
                     best_match = Some(monitor_config);
                     best_specificity = specificity;
                 }
             }
         }
-        if let Some(mc) = best_match
+        best_match
+    }
+
+    /// Get the bar position for a specific monitor (monitor override, else global)
+    pub fn get_monitor_position(&self, monitor_name: &str) -> BarPosition {
+        let info = self.monitor_info(monitor_name);
+        let config_guard = self.config.lock().unwrap();
+        let Some(config) = config_guard.as_ref() else {
+            return BarPosition::default();
+        };
+        Self::best_monitor_match(config, &info)
+            .and_then(|mc| mc.position)
+            .unwrap_or(config.application.position)
+    }
+
+    /// Get the layer-shell layer for a specific monitor (monitor override, else global)
+    pub fn get_monitor_layer(&self, monitor_name: &str) -> BarLayer {
+        let info = self.monitor_info(monitor_name);
+        let config_guard = self.config.lock().unwrap();
+        let Some(config) = config_guard.as_ref() else {
+            return BarLayer::default();
+        };
+        Self::best_monitor_match(config, &info)
+            .and_then(|mc| mc.layer)
+            .unwrap_or(config.application.layer)
+    }
+
+    /// Get the bar height for a specific monitor (monitor override, else global, else 40px)
+    pub fn get_monitor_height(&self, monitor_name: &str) -> i32 {
+        let info = self.monitor_info(monitor_name);
+        let config_guard = self.config.lock().unwrap();
+        let Some(config) = config_guard.as_ref() else {
+            return DEFAULT_BAR_HEIGHT;
+        };
+        Self::best_monitor_match(config, &info)
+            .and_then(|mc| mc.height)
+            .or(config.application.height)
+            .unwrap_or(DEFAULT_BAR_HEIGHT)
+    }
+
+    /// Get the bar margins for a specific monitor (per edge: monitor, else global, else 0)
+    pub fn get_monitor_margins(&self, monitor_name: &str) -> BarMargins {
+        let info = self.monitor_info(monitor_name);
+        let config_guard = self.config.lock().unwrap();
+        let Some(config) = config_guard.as_ref() else {
+            return BarMargins::default();
+        };
+        let global = config.application.margins.overlay(BarMargins::default());
+        match Self::best_monitor_match(config, &info) {
+            Some(mc) => mc.margins.overlay(global),
+            None => global,
+        }
+    }
+
+    /// Whether the bar on a specific monitor hides for fullscreen windows (default false)
+    pub fn get_monitor_auto_hide_on_fullscreen(&self, monitor_name: &str) -> bool {
+        let info = self.monitor_info(monitor_name);
+        let config_guard = self.config.lock().unwrap();
+        let Some(config) = config_guard.as_ref() else {
+            return false;
+        };
+        Self::best_monitor_match(config, &info)
+            .and_then(|mc| mc.auto_hide_on_fullscreen)
+            .or(config.application.auto_hide_on_fullscreen)
+            .unwrap_or(false)
+    }
+
+    /// Whether disabling this monitor hides its bar instead of destroying it (monitor
+    /// override, else global; default false)
+    pub fn get_monitor_hide_when_disabled(&self, monitor_name: &str) -> bool {
+        let info = self.monitor_info(monitor_name);
+        let config_guard = self.config.lock().unwrap();
+        let Some(config) = config_guard.as_ref() else {
+            return false;
+        };
+        Self::best_monitor_match(config, &info)
+            .and_then(|mc| mc.hide_when_disabled)
+            .or(config.application.hide_when_disabled)
+            .unwrap_or(false)
+    }
+
+    /// Get the bar opacity for a specific monitor (monitor override, else global), clamped
+    pub fn get_monitor_opacity(&self, monitor_name: &str) -> Option<f64> {
+        let info = self.monitor_info(monitor_name);
+        let config_guard = self.config.lock().unwrap();
+        let config = config_guard.as_ref()?;
+        Self::best_monitor_match(config, &info)
+            .and_then(|mc| mc.opacity)
+            .or(config.application.opacity)
+            .map(clamp_opacity)
+    }
+
+    /// Get the bar background for a specific monitor (monitor override, else global);
+    /// invalid colors are ignored
+    pub fn get_monitor_background(&self, monitor_name: &str) -> Option<String> {
+        let info = self.monitor_info(monitor_name);
+        let config_guard = self.config.lock().unwrap();
+        let config = config_guard.as_ref()?;
+        let background = Self::best_monitor_match(config, &info)
+            .and_then(|mc| mc.background.as_deref())
+            .or(config.application.background.as_deref());
+        valid_bar_background(background).map(str::to_string)
+    }
+
+    /// Get all bar window settings for a specific monitor
+    pub fn get_bar_options(&self, monitor_name: &str) -> BarOptions {
+        BarOptions {
+            position: self.get_monitor_position(monitor_name),
+            layer: self.get_monitor_layer(monitor_name),
+            height: self.get_monitor_height(monitor_name),
+            margins: self.get_monitor_margins(monitor_name),
+            auto_hide_on_fullscreen: self.get_monitor_auto_hide_on_fullscreen(monitor_name),
+            opacity: self.get_monitor_opacity(monitor_name),
+            background: self.get_monitor_background(monitor_name),
+        }
+    }
+
+    /// Get module configuration for a specific monitor
+    pub fn get_monitor_modules(&self, monitor_name: &str) -> Option<HashMap<String, ModuleConfig>> {
+        let info = self.monitor_info(monitor_name);
+        let config_guard = self.config.lock().unwrap();
+        let config = config_guard.as_ref()?;
+
+        // Start from global module defaults
+        let mut merged: HashMap<String, ModuleConfig> = config.application.modules.clone();
+
+        // Overlay only the fields the most specific matching monitor sets
+        if let Some(mc) = Self::best_monitor_match(config, &info)
             && let Some(overrides) = &mc.modules
         {
             for (k, v) in overrides {
-                merged.insert(k.clone(), v.clone());
+                let module = match merged.get(k) {
+                    Some(global) => global.merged_with(v),
+                    None => v.clone(),
+                };
+                merged.insert(k.clone(), module);
             }
         }
 
@@ -686,35 +2215,103 @@ impl ConfigManager {
         Some(config.application.layouts.clone())
     }
 
-    /// Simple regex pattern matching (supports basic patterns like ".*", "DP-.*", etc.)
+    /// Regex pattern matching against the whole monitor name
+    ///
+    /// Patterns are implicitly anchored, so `DP-.*` matches `DP-1` but not
+    /// `eDP-1`. A pattern spelled exactly like the monitor name always matches.
+    /// Invalid patterns log a warning (once) and match nothing.
     pub fn matches_pattern(monitor_name: &str, pattern: &str) -> bool {
-        // Handle exact match patterns with ^ and $
-        if pattern.starts_with("^") && pattern.ends_with("$") {
-            let inner_pattern = &pattern[1..pattern.len() - 1];
-            // Very small subset: prefix.* pattern => starts_with(prefix)
-            if let Some(pos) = inner_pattern.find(".*") {
-                let prefix = &inner_pattern[..pos];
-                return monitor_name.starts_with(prefix);
-            }
-            return monitor_name == inner_pattern;
-        }
-
-        // Convert simple patterns to regex
-        let regex_pattern = pattern.replace(".", "\\.").replace("*", ".*");
-
-        // Simple matching for now - in production you'd want a proper regex engine
-        if regex_pattern == ".*" {
+        if monitor_name == pattern {
             return true;
         }
+        Self::compiled_pattern(pattern)
+            .map(|c| c.regex.is_match(monitor_name))
+            .unwrap_or(false)
+    }
 
-        if let Some(prefix) = pattern.strip_suffix(".*") {
-            return monitor_name.starts_with(prefix);
+    /// Look up (or compile and cache) a monitor pattern
+    fn compiled_pattern(pattern: &str) -> Option<CompiledPattern> {
+        let mut cache = PATTERN_CACHE.lock().unwrap();
+        if let Some(entry) = cache.get(pattern) {
+            return entry.clone();
         }
+        let compiled = Self::compile_pattern(pattern);
+        cache.insert(pattern.to_string(), compiled.clone());
+        compiled
+    }
 
-        monitor_name == pattern
+    fn compile_pattern(pattern: &str) -> Option<CompiledPattern> {
+        let hir = match regex_syntax::Parser::new().parse(pattern) {
+            Ok(hir) => hir,
+            Err(e) => {
+                log::warn!("Config: invalid monitor pattern '{}': {}", pattern, e);
+                return None;
+            }
+        };
+        let regex = match Regex::new(&format!("^(?:{})$", pattern)) {
+            Ok(regex) => regex,
+            Err(e) => {
+                log::warn!("Config: invalid monitor pattern '{}': {}", pattern, e);
+                return None;
+            }
+        };
+        Some(CompiledPattern {
+            regex,
+            specificity: Self::hir_specificity(&hir),
+        })
     }
+
+    fn hir_specificity(hir: &Hir) -> u32 {
+        // Strip explicit ^/$ anchors; matching is whole-name either way
+        let inner: &[Hir] = match hir.kind() {
+            HirKind::Concat(parts) => {
+                let start = matches!(
+                    parts.first().map(|h| h.kind()),
+                    Some(HirKind::Look(Look::Start))
+                );
+                let end = matches!(
+                    parts.last().map(|h| h.kind()),
+                    Some(HirKind::Look(Look::End))
+                );
+                let lo = usize::from(start);
+                let hi = parts.len() - usize::from(end && parts.len() > lo);
+                &parts[lo..hi]
+            }
+            _ => std::slice::from_ref(hir),
+        };
+
+        let is_literal = inner
+            .iter()
+            .all(|h| matches!(h.kind(), HirKind::Literal(_) | HirKind::Empty));
+        let tier = if is_literal { 2 } else { 1 };
+        let literal_len: u32 = inner.iter().map(Self::hir_literal_len).sum();
+        tier * 1000 + literal_len.min(999)
+    }
+
+    /// Number of literal characters every match of `hir` must contain
+    fn hir_literal_len(hir: &Hir) -> u32 {
+        match hir.kind() {
+            HirKind::Literal(lit) => String::from_utf8_lossy(&lit.0).chars().count() as u32,
+            HirKind::Capture(cap) => Self::hir_literal_len(&cap.sub),
+            HirKind::Concat(parts) => parts.iter().map(Self::hir_literal_len).sum(),
+            HirKind::Alternation(alts) => alts.iter().map(Self::hir_literal_len).min().unwrap_or(0),
+            HirKind::Repetition(rep) => Self::hir_literal_len(&rep.sub).saturating_mul(rep.min),
+            HirKind::Empty | HirKind::Class(_) | HirKind::Look(_) => 0,
+        }
+    }
+}
+
+/// Compiled monitor `match` pattern plus its precomputed specificity
+#[derive(Debug, Clone)]
+struct CompiledPattern {
+    regex: Regex,
+    specificity: u32,
 }
 
+/// Monitor patterns compiled once and reused; `None` marks an invalid pattern
+static PATTERN_CACHE: Lazy<Mutex<HashMap<String, Option<CompiledPattern>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
 impl Default for ConfigManager {
     fn default() -> Self {
         Self::new()
@@ -0,0 +1,242 @@
+//! Control socket for scripting niri-bar at runtime. Clients write one JSON command per line
+//! to `$XDG_RUNTIME_DIR/niri-bar.sock`, e.g. `{"reload":true}`, `{"toggle_bar":"eDP-1"}` or
+//! `{"set_theme":"dracula"}`, and get `{"ok":true}` or `{"ok":false,"error":"..."}` back.
+
+use anyhow::{Result, anyhow, bail};
+use serde_json::Value as JsonValue;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+use tokio::sync::{mpsc::UnboundedSender, oneshot};
+
+/// File name of the control socket inside `$XDG_RUNTIME_DIR`
+pub const CONTROL_SOCKET_NAME: &str = "niri-bar.sock";
+
+/// Control socket path under `runtime_dir`; None without a runtime dir
+pub fn control_socket_path(runtime_dir: Option<&Path>) -> Option<PathBuf> {
+    runtime_dir
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .map(|dir| dir.join(CONTROL_SOCKET_NAME))
+}
+
+/// [`control_socket_path`] for the current `$XDG_RUNTIME_DIR`
+pub fn control_socket_path_from_env() -> Option<PathBuf> {
+    let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR").map(PathBuf::from);
+    control_socket_path(runtime_dir.as_deref())
+}
+
+/// A command received on the control socket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlCommand {
+    /// Reload the config and theme files
+    Reload,
+    /// Show or hide the bar on a connector
+    ToggleBar(String),
+    /// Switch every bar to a theme until the next config reload
+    SetTheme(String),
+}
+
+impl ControlCommand {
+    /// Decode one command line: a JSON object with exactly one known key
+    pub fn parse(line: &str) -> Result<Self> {
+        let value: JsonValue =
+            serde_json::from_str(line.trim()).map_err(|e| anyhow!("invalid JSON: {}", e))?;
+        let Some(object) = value.as_object() else {
+            bail!("expected a JSON object, got {}", value);
+        };
+        if object.len() != 1 {
+            bail!("expected exactly one command, got {} keys", object.len());
+        }
+        let (name, arg) = object.iter().next().unwrap();
+        let string_arg = || {
+            arg.as_str()
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .ok_or_else(|| anyhow!("`{}` takes a non-empty string", name))
+        };
+        match name.as_str() {
+            "reload" => match arg.as_bool() {
+                Some(true) => Ok(Self::Reload),
+                _ => bail!("`reload` takes `true`"),
+            },
+            "toggle_bar" => Ok(Self::ToggleBar(string_arg()?)),
+            "set_theme" => Ok(Self::SetTheme(string_arg()?)),
+            other => bail!("unknown command `{}`", other),
+        }
+    }
+
+    /// The command as sent on the socket (one line, without the newline)
+    pub fn to_json(&self) -> String {
+        let value = match self {
+            Self::Reload => serde_json::json!({ "reload": true }),
+            Self::ToggleBar(connector) => serde_json::json!({ "toggle_bar": connector }),
+            Self::SetTheme(theme) => serde_json::json!({ "set_theme": theme }),
+        };
+        value.to_string()
+    }
+}
+
+/// How long [`send_command`] waits for the bar to answer
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Send one command to the control socket at `path` and return the reply line
+pub fn send_command(path: &Path, command: &ControlCommand) -> Result<String> {
+    let mut stream = UnixStream::connect(path)
+        .map_err(|e| anyhow!("can't reach niri-bar at {}: {}", path.display(), e))?;
+    stream.set_read_timeout(Some(REPLY_TIMEOUT))?;
+    stream.write_all(format!("{}\n", command.to_json()).as_bytes())?;
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    Ok(reply.trim_end().to_string())
+}
+
+/// Whether a reply line reports success
+pub fn reply_is_ok(reply: &str) -> bool {
+    serde_json::from_str::<JsonValue>(reply)
+        .ok()
+        .and_then(|v| v.get("ok").and_then(JsonValue::as_bool))
+        .unwrap_or(false)
+}
+
+/// The CLI side: send `command`, print the reply to `out` and return the exit code, 1 when
+/// the bar rejected the command or running it failed
+pub fn run_client(path: &Path, command: &ControlCommand, out: &mut impl Write) -> Result<i32> {
+    let reply = send_command(path, command)?;
+    writeln!(out, "{}", reply)?;
+    Ok(if reply_is_ok(&reply) { 0 } else { 1 })
+}
+
+/// Runs control commands; implemented by the application on the GTK thread
+pub trait ControlHandler {
+    fn reload(&mut self) -> Result<()>;
+    fn toggle_bar(&mut self, connector: &str) -> Result<()>;
+    fn set_theme(&mut self, theme: &str) -> Result<()>;
+}
+
+/// Route a command to the matching handler method
+pub fn dispatch(handler: &mut impl ControlHandler, command: &ControlCommand) -> Result<()> {
+    match command {
+        ControlCommand::Reload => handler.reload(),
+        ControlCommand::ToggleBar(connector) => handler.toggle_bar(connector),
+        ControlCommand::SetTheme(theme) => handler.set_theme(theme),
+    }
+}
+
+/// A parsed command on its way to the GTK thread, with the channel its outcome goes back on
+pub type ControlRequest = (ControlCommand, oneshot::Sender<Result<()>>);
+
+/// Reply line for a command's outcome: rejected, failed or run
+fn reply(result: &Result<()>) -> String {
+    let value = match result {
+        Ok(()) => serde_json::json!({ "ok": true }),
+        Err(e) => serde_json::json!({ "ok": false, "error": e.to_string() }),
+    };
+    format!("{}\n", value)
+}
+
+/// Listener thread accepting control connections. Parsed commands are sent to `requests`;
+/// each connection waits for the outcome and replies with it.
+pub struct ControlServer {
+    path: PathBuf,
+    stop_requested: Arc<AtomicBool>,
+}
+
+impl ControlServer {
+    /// Bind the socket at `path` and start accepting on a background thread. A stale socket
+    /// file left by a crashed instance is replaced; a live one means another bar is running.
+    pub fn start(path: PathBuf, requests: UnboundedSender<ControlRequest>) -> Result<Self> {
+        if path.exists() {
+            if UnixStream::connect(&path).is_ok() {
+                bail!("control socket {} is already in use", path.display());
+            }
+            std::fs::remove_file(&path)?;
+        }
+        let listener = UnixListener::bind(&path)?;
+        let stop_requested = Arc::new(AtomicBool::new(false));
+
+        let stop = stop_requested.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stop.load(Ordering::SeqCst) {
+                    break;
+                }
+                match stream {
+                    Ok(stream) => {
+                        let requests = requests.clone();
+                        thread::spawn(move || Self::serve(stream, requests));
+                    }
+                    Err(e) => log::warn!("Control: accept error: {}", e),
+                }
+            }
+            log::info!("Control: listener stopped");
+        });
+        log::info!("Control: 🎛️ listening on {}", path.display());
+
+        Ok(Self {
+            path,
+            stop_requested,
+        })
+    }
+
+    /// Answer each command line of one connection until the client hangs up
+    fn serve(stream: UnixStream, requests: UnboundedSender<ControlRequest>) {
+        let Ok(mut writer) = stream.try_clone() else {
+            return;
+        };
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else { break };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let result = match ControlCommand::parse(&line) {
+                Ok(command) => {
+                    log::info!("Control: received {:?}", command);
+                    Self::run(&requests, command)
+                }
+                Err(e) => {
+                    log::warn!("Control: rejected '{}': {}", line, e);
+                    Err(e)
+                }
+            };
+            if writer.write_all(reply(&result).as_bytes()).is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Hand a command to the GTK thread and wait for its outcome
+    fn run(requests: &UnboundedSender<ControlRequest>, command: ControlCommand) -> Result<()> {
+        let (done_tx, done_rx) = oneshot::channel();
+        requests
+            .send((command, done_tx))
+            .map_err(|_| anyhow!("niri-bar is shutting down"))?;
+        done_rx
+            .blocking_recv()
+            .map_err(|_| anyhow!("niri-bar dropped the command"))?
+    }
+
+    /// Path of the bound socket
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Stop accepting connections and remove the socket file
+    pub fn stop(&self) {
+        if self.stop_requested.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        // Wake the blocking accept so the thread sees the flag
+        let _ = UnixStream::connect(&self.path);
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
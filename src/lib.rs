@@ -1,11 +1,16 @@
 pub mod application;
 pub mod bar;
+pub mod bar_model;
+pub mod cli;
 pub mod config;
+pub mod control;
 pub mod event_bus;
 pub mod file_watcher;
 pub mod logger;
 pub mod modules;
 pub mod monitor;
 pub mod niri;
+pub mod theme;
 pub mod ui;
+pub mod ui_state;
 pub mod wallpaper;
@@ -3,13 +3,225 @@ use chrono::Utc;
 use log::{Level, LevelFilter, Log, Metadata, Record};
 use std::fs::{File, OpenOptions};
 use std::io::{self, Write};
-use std::path::Path;
-use std::sync::{Arc, Mutex};
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
 
 /// Custom logger that honors the niri-bar.yaml logging configuration
 pub struct NiriBarLogger {
     pub config: LoggingConfig,
     pub file_handle: Option<Arc<Mutex<File>>>,
+    /// Expanded log file path, reopened after rotation
+    file_path: Option<PathBuf>,
+    /// systemd journal connection when `journald` is on and the journal is reachable
+    journal: Option<JournalSink>,
+}
+
+/// The logger [`NiriBarLogger::init`] installed; [`NiriBarLogger::reconfigure`] swaps it
+static ACTIVE_LOGGER: OnceLock<RwLock<NiriBarLogger>> = OnceLock::new();
+
+/// What the `log` facade holds: forwards to [`ACTIVE_LOGGER`], so reconfiguring doesn't need
+/// a new `'static` logger
+struct ActiveLogger;
+
+impl Log for ActiveLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        ACTIVE_LOGGER
+            .get()
+            .and_then(|logger| logger.read().ok())
+            .is_some_and(|logger| logger.enabled(metadata))
+    }
+
+    fn log(&self, record: &Record) {
+        if let Some(logger) = ACTIVE_LOGGER.get()
+            && let Ok(logger) = logger.read()
+        {
+            logger.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(logger) = ACTIVE_LOGGER.get()
+            && let Ok(logger) = logger.read()
+        {
+            logger.flush();
+        }
+    }
+}
+
+/// Where systemd-journald accepts native protocol datagrams
+pub const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// Which outputs a logger writes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LogSinks {
+    pub console: bool,
+    pub file: bool,
+    pub journal: bool,
+}
+
+impl LogSinks {
+    /// Outputs for `config`; the journal only when asked for and `journal_available`
+    pub fn select(config: &LoggingConfig, journal_available: bool) -> Self {
+        Self {
+            console: config.console,
+            file: !config.file.is_empty(),
+            journal: config.journald && journal_available,
+        }
+    }
+}
+
+/// syslog priority the journal stores for a log level
+pub fn journal_priority(level: Level) -> u8 {
+    match level {
+        Level::Error => 3,
+        Level::Warn => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
+    }
+}
+
+/// Encode journal fields in the native protocol: `KEY=value` lines, or for values containing
+/// a newline the key, a little-endian u64 length and the raw value
+pub fn encode_journal_fields(fields: &[(&str, &str)]) -> Vec<u8> {
+    let mut datagram = Vec::new();
+    for (key, value) in fields {
+        datagram.extend_from_slice(key.as_bytes());
+        if value.contains('\n') {
+            datagram.push(b'\n');
+            datagram.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        } else {
+            datagram.push(b'=');
+        }
+        datagram.extend_from_slice(value.as_bytes());
+        datagram.push(b'\n');
+    }
+    datagram
+}
+
+/// Sends records to systemd-journald as structured entries
+pub struct JournalSink {
+    socket: UnixDatagram,
+    path: PathBuf,
+}
+
+impl JournalSink {
+    /// Connect to the journal socket at `path`
+    pub fn connect(path: &Path) -> Result<Self, io::Error> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(path)?;
+        Ok(Self {
+            socket,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Send one entry with the level as `PRIORITY` and the target as `TARGET`
+    pub fn send(&self, record: &Record, message: &str) -> Result<(), io::Error> {
+        let priority = journal_priority(record.level()).to_string();
+        let line = record.line().map(|line| line.to_string());
+        let mut fields = vec![
+            ("MESSAGE", message),
+            ("PRIORITY", priority.as_str()),
+            ("SYSLOG_IDENTIFIER", "niri-bar"),
+            ("TARGET", record.target()),
+        ];
+        if let Some(file) = record.file() {
+            fields.push(("CODE_FILE", file));
+        }
+        if let Some(line) = &line {
+            fields.push(("CODE_LINE", line));
+        }
+        self.socket
+            .send_to(&encode_journal_fields(&fields), &self.path)
+            .map(|_| ())
+    }
+}
+
+/// Path of the `n`th rotated archive of `path`, e.g. `niri-bar.log.2`
+pub fn rotated_log_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Move `path` to `<path>.1`, shifting existing archives up by one and deleting those
+/// beyond `max_files`. With `max_files` 0 the log is simply removed.
+pub fn rotate_log_files(path: &Path, max_files: usize) -> Result<(), io::Error> {
+    // Prune archives past the limit, including leftovers from a larger old limit
+    let mut n = max_files.max(1);
+    while rotated_log_path(path, n).exists() {
+        if n >= max_files {
+            std::fs::remove_file(rotated_log_path(path, n))?;
+        }
+        n += 1;
+    }
+    if max_files == 0 {
+        return std::fs::remove_file(path);
+    }
+    for n in (1..max_files).rev() {
+        let from = rotated_log_path(path, n);
+        if from.exists() {
+            std::fs::rename(from, rotated_log_path(path, n + 1))?;
+        }
+    }
+    std::fs::rename(path, rotated_log_path(path, 1))
+}
+
+/// Level filter for a configured level name; unknown names mean `info`
+pub fn parse_level(level: &str) -> LevelFilter {
+    match level.to_lowercase().as_str() {
+        "trace" => LevelFilter::Trace,
+        "debug" => LevelFilter::Debug,
+        "info" => LevelFilter::Info,
+        "warn" => LevelFilter::Warn,
+        "error" => LevelFilter::Error,
+        _ => LevelFilter::Info,
+    }
+}
+
+/// The class a log line belongs to: an explicit target (as set by `log_info!` and friends),
+/// else the `Class:` prefix of the message, e.g. `Niri IPC` for "Niri IPC: connected"
+pub fn log_class<'a>(target: &'a str, message: &'a str) -> Option<&'a str> {
+    if !target.is_empty() && !target.starts_with("niri_bar") {
+        return Some(target);
+    }
+    let (prefix, _) = message.split_once(": ")?;
+    let is_class = !prefix.is_empty()
+        && prefix.len() <= 40
+        && prefix
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'));
+    is_class.then_some(prefix)
+}
+
+/// Level for lines of `class`: its `module_levels` override (case-insensitive), else the
+/// global level
+pub fn level_for_class(config: &LoggingConfig, class: Option<&str>) -> LevelFilter {
+    class
+        .and_then(|class| {
+            config
+                .module_levels
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(class))
+        })
+        .map(|(_, level)| parse_level(level))
+        .unwrap_or_else(|| parse_level(&config.level))
+}
+
+/// Most verbose level any line can be logged at, global or per module
+pub fn max_level_filter(config: &LoggingConfig) -> LevelFilter {
+    config
+        .module_levels
+        .values()
+        .map(|level| parse_level(level))
+        .chain(std::iter::once(parse_level(&config.level)))
+        .max()
+        .unwrap_or(LevelFilter::Info)
+}
+
+fn open_log_file(path: &Path) -> Result<File, io::Error> {
+    OpenOptions::new().create(true).append(true).open(path)
 }
 
 impl NiriBarLogger {
@@ -29,24 +241,58 @@ impl NiriBarLogger {
     ///     include_file: true,
     ///     include_line: true,
     ///     include_class: true,
+    ///     max_size_bytes: 10 * 1024 * 1024,
+    ///     max_files: 5,
+    ///     module_levels: Default::default(),
+    ///     journald: false,
     /// };
     ///
     /// let logger = NiriBarLogger::new(config).unwrap();
     /// // Logger is ready to use
     /// ```
     pub fn new(config: LoggingConfig) -> Result<Self, io::Error> {
-        let file_handle = if !config.file.is_empty() {
+        Self::with_journal_socket(config, Path::new(JOURNAL_SOCKET))
+    }
+
+    /// [`NiriBarLogger::new`] sending journal entries to `journal_socket`
+    pub fn with_journal_socket(
+        config: LoggingConfig,
+        journal_socket: &Path,
+    ) -> Result<Self, io::Error> {
+        let sinks = LogSinks::select(&config, journal_socket.exists());
+        let (file_handle, file_path) = if !config.file.is_empty() {
             let expanded_path = shellexpand::tilde(&config.file).to_string();
-            let path = Path::new(&expanded_path);
+            let path = PathBuf::from(expanded_path);
 
             // Create directory if it doesn't exist
             if let Some(parent) = path.parent() {
                 std::fs::create_dir_all(parent)?;
             }
 
-            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            let file = open_log_file(&path)?;
+
+            (Some(Arc::new(Mutex::new(file))), Some(path))
+        } else {
+            (None, None)
+        };
 
-            Some(Arc::new(Mutex::new(file)))
+        // Not running under systemd is fine; the other outputs carry on
+        if config.journald && !sinks.journal {
+            eprintln!(
+                "Logger: journald requested but {} is missing; not logging to the journal",
+                journal_socket.display()
+            );
+        }
+        let journal = if sinks.journal {
+            JournalSink::connect(journal_socket)
+                .inspect_err(|e| {
+                    eprintln!(
+                        "Logger: can't reach the journal at {}: {}; not logging to it",
+                        journal_socket.display(),
+                        e
+                    )
+                })
+                .ok()
         } else {
             None
         };
@@ -54,9 +300,16 @@ impl NiriBarLogger {
         Ok(Self {
             config,
             file_handle,
+            file_path,
+            journal,
         })
     }
 
+    /// Whether log lines also go to the systemd journal
+    pub fn logs_to_journal(&self) -> bool {
+        self.journal.is_some()
+    }
+
     /// Initialize the global logger with the given configuration
     ///
     /// # Examples
@@ -73,6 +326,10 @@ impl NiriBarLogger {
     ///     include_file: true,
     ///     include_line: true,
     ///     include_class: true,
+    ///     max_size_bytes: 10 * 1024 * 1024,
+    ///     max_files: 5,
+    ///     module_levels: Default::default(),
+    ///     journald: false,
     /// };
     ///
     /// // Initialize the global logger
@@ -82,24 +339,52 @@ impl NiriBarLogger {
     /// log::info!("Logger initialized!");
     /// ```
     pub fn init(config: LoggingConfig) -> Result<(), Box<dyn std::error::Error>> {
-        let level_filter = match config.level.to_lowercase().as_str() {
-            "trace" => LevelFilter::Trace,
-            "debug" => LevelFilter::Debug,
-            "info" => LevelFilter::Info,
-            "warn" => LevelFilter::Warn,
-            "error" => LevelFilter::Error,
-            _ => LevelFilter::Info,
-        };
+        // Let through what any module override wants; `log` narrows it per class
+        let level_filter = max_level_filter(&config);
 
         let logger = Self::new(config)?;
-        log::set_boxed_logger(Box::new(logger))?;
+        ACTIVE_LOGGER
+            .set(RwLock::new(logger))
+            .map_err(|_| "logger already initialized")?;
+        log::set_logger(&ActiveLogger)?;
         log::set_max_level(level_filter);
 
         Ok(())
     }
 
+    /// Switch the logger [`NiriBarLogger::init`] installed to `config`, e.g. the `logging`
+    /// section of a reloaded config. Returns whether it changed anything: without an
+    /// installed logger, or with the configuration it already has, this is a no-op. On error
+    /// the current logger stays.
+    pub fn reconfigure(config: LoggingConfig) -> Result<bool, io::Error> {
+        Self::reconfigure_with_journal_socket(config, Path::new(JOURNAL_SOCKET))
+    }
+
+    /// [`NiriBarLogger::reconfigure`] sending journal entries to `journal_socket`
+    pub fn reconfigure_with_journal_socket(
+        config: LoggingConfig,
+        journal_socket: &Path,
+    ) -> Result<bool, io::Error> {
+        let Some(active) = ACTIVE_LOGGER.get() else {
+            return Ok(false);
+        };
+        if active.read().is_ok_and(|logger| logger.config == config) {
+            return Ok(false);
+        }
+        let level_filter = max_level_filter(&config);
+        // Open the new outputs before taking the lock, so logging carries on meanwhile
+        let logger = Self::with_journal_socket(config, journal_socket)?;
+        *active.write().unwrap_or_else(|e| e.into_inner()) = logger;
+        log::set_max_level(level_filter);
+        Ok(true)
+    }
+
     /// Format a log message according to the configuration
     fn format_message(&self, record: &Record) -> String {
+        if self.config.format.eq_ignore_ascii_case("json") {
+            return self.format_json(record);
+        }
+
         let timestamp = Utc::now();
         let timestamp_str = if self.config.format.to_lowercase() == "iso8601" {
             timestamp.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
@@ -146,6 +431,35 @@ impl NiriBarLogger {
         parts.join(" ")
     }
 
+    /// One JSON object per line for `jq` and log shippers; `include_*` pick the optional fields
+    fn format_json(&self, record: &Record) -> String {
+        let mut object = serde_json::Map::new();
+        object.insert(
+            "ts".to_string(),
+            Utc::now()
+                .format("%Y-%m-%dT%H:%M:%S%.3fZ")
+                .to_string()
+                .into(),
+        );
+        object.insert("level".to_string(), record.level().as_str().into());
+        if self.config.include_class {
+            object.insert("target".to_string(), record.target().into());
+        }
+        if self.config.include_file
+            && let Some(file) = record.file()
+        {
+            object.insert("file".to_string(), file.into());
+        }
+        if self.config.include_line
+            && let Some(line) = record.line()
+        {
+            object.insert("line".to_string(), line.into());
+        }
+        object.insert("message".to_string(), record.args().to_string().into());
+
+        serde_json::Value::Object(object).to_string()
+    }
+
     /// Write a log message to the configured outputs
     fn write_log(&self, message: &str) -> Result<(), io::Error> {
         let message_with_newline = format!("{}\n", message);
@@ -161,6 +475,15 @@ impl NiriBarLogger {
         {
             file.write_all(message_with_newline.as_bytes())?;
             file.flush()?;
+
+            // Rotate once the file outgrows the limit and continue in a fresh one
+            if let Some(path) = &self.file_path
+                && self.config.max_size_bytes > 0
+                && file.metadata()?.len() > self.config.max_size_bytes
+            {
+                rotate_log_files(path, self.config.max_files)?;
+                *file = open_log_file(path)?;
+            }
         }
 
         Ok(())
@@ -169,13 +492,13 @@ impl NiriBarLogger {
 
 impl Log for NiriBarLogger {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        let level_filter = match self.config.level.to_lowercase().as_str() {
-            "trace" => LevelFilter::Trace,
-            "debug" => LevelFilter::Debug,
-            "info" => LevelFilter::Info,
-            "warn" => LevelFilter::Warn,
-            "error" => LevelFilter::Error,
-            _ => LevelFilter::Info,
+        // The message isn't known here, so only an explicit target can pick an override
+        let level_filter = if self.config.module_levels.is_empty() {
+            parse_level(&self.config.level)
+        } else if metadata.target().starts_with("niri_bar") {
+            max_level_filter(&self.config)
+        } else {
+            level_for_class(&self.config, Some(metadata.target()))
         };
 
         metadata.level() <= level_filter
@@ -183,6 +506,17 @@ impl Log for NiriBarLogger {
 
     fn log(&self, record: &Record) {
         if self.enabled(record.metadata()) {
+            if !self.config.module_levels.is_empty() {
+                let message = record.args().to_string();
+                let class = log_class(record.target(), &message);
+                if record.level() > level_for_class(&self.config, class) {
+                    return;
+                }
+            }
+            if let Some(journal) = &self.journal {
+                let _ = journal.send(record, &record.args().to_string());
+            }
+
             let message = self.format_message(record);
 
             // Use stderr for errors, stdout for everything else
@@ -1,26 +1,42 @@
+use clap::Parser;
 use niri_bar::application::Application;
-use niri_bar::config::LoggingConfig;
+use niri_bar::cli::{Cli, CliCommand};
+use niri_bar::config::{CONFIG_SCHEMA, load_logging_config, resolve_config_path_from_env};
+use niri_bar::control::{control_socket_path_from_env, run_client};
 use niri_bar::logger::NiriBarLogger;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize logging with default configuration
-    let logging_config = LoggingConfig {
-        level: "debug".to_string(),
-        file: "~/.local/share/niri-bar/niri-bar.log".to_string(),
-        console: true,
-        format: "iso8601".to_string(),
-        include_file: true,
-        include_line: true,
-        include_class: true,
-    };
+    let cli = Cli::parse();
 
+    if cli.command() == CliCommand::Schema {
+        print!("{}", CONFIG_SCHEMA);
+        return Ok(());
+    }
+
+    // Subcommands other than `run` only talk to the running bar
+    if let Some(command) = cli.command().control_command() {
+        let path = control_socket_path_from_env().ok_or("XDG_RUNTIME_DIR is not set")?;
+        let code = run_client(&path, &command, &mut std::io::stdout())?;
+        if code != 0 {
+            std::process::exit(code);
+        }
+        return Ok(());
+    }
+
+    let config_path = resolve_config_path_from_env(cli.config.as_deref());
+
+    // Log as the config asks from the start; reloads re-apply its `logging` section
+    let logging_config = load_logging_config(&config_path).unwrap_or_default();
     NiriBarLogger::init(logging_config.clone())?;
 
     // Initialize GTK
     gtk4::init()?;
 
+    log::info!("Main: Using configuration file {}", config_path.display());
+
     // Create and run the application
     let mut app = Application::new(logging_config)?;
+    app.set_config_path(config_path);
     app.run()?;
 
     Ok(())
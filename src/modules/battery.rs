@@ -2,15 +2,450 @@ use gtk4 as gtk;
 use gtk4::prelude::*;
 
 use crate::config::ModuleConfig;
-use gtk::gio;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use zbus::blocking::Connection;
+use zbus::zvariant::Value;
+
+/// Where the kernel exposes batteries
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+/// Unit of a battery's `now` / `full` / `power` readings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BatteryUnit {
+    /// `energy_*` (µWh) and `power_now` (µW)
+    #[default]
+    Energy,
+    /// `charge_*` (µAh) and `current_now` (µA)
+    Charge,
+}
+
+/// One battery's sysfs state
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BatteryReading {
+    /// `energy_now` (µWh), or `charge_now` (µAh) on batteries without energy files
+    pub now: Option<u64>,
+    /// `energy_full` / `charge_full`, same unit as `now`
+    pub full: Option<u64>,
+    /// `capacity` percent
+    pub capacity: Option<u8>,
+    /// `status`, e.g. "Charging", "Discharging", "Full"
+    pub status: Option<String>,
+    /// `power_now` (µW), or `current_now` (µA) next to `charge_*` files
+    pub power: Option<u64>,
+    /// Which files `now`, `full` and `power` came from
+    pub unit: BatteryUnit,
+    /// `voltage_min_design` (µV), to convert charge readings to energy
+    pub voltage: Option<u64>,
+}
+
+impl BatteryReading {
+    /// The reading in energy units; charge readings are converted at `voltage_min_design`,
+    /// `None` when the battery doesn't report it
+    pub fn to_energy(&self) -> Option<BatteryReading> {
+        match self.unit {
+            BatteryUnit::Energy => Some(self.clone()),
+            BatteryUnit::Charge => {
+                let voltage = self.voltage.filter(|v| *v > 0)?;
+                // µAh × µV = 10⁻¹² Wh, so divide by 10⁶ for µWh (and µA × µV for µW)
+                let convert = |v: u64| (v as u128 * voltage as u128 / 1_000_000) as u64;
+                Some(BatteryReading {
+                    now: self.now.map(convert),
+                    full: self.full.map(convert),
+                    power: self.power.map(convert),
+                    unit: BatteryUnit::Energy,
+                    ..self.clone()
+                })
+            }
+        }
+    }
+}
+
+/// Combined state of all batteries
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatteryAggregate {
+    pub percent: u8,
+    pub status: String,
+    /// Summed `now` / `full` / `power`; `None` unless every battery reports them
+    pub now: Option<u64>,
+    pub full: Option<u64>,
+    pub power: Option<u64>,
+}
+
+impl BatteryAggregate {
+    pub fn charging(&self) -> bool {
+        self.status == "Charging"
+    }
+}
+
+/// All `BAT*` entries under a power_supply directory (normally `/sys/class/power_supply`)
+pub fn list_batteries(base: &Path) -> Vec<PathBuf> {
+    let mut batteries: Vec<PathBuf> = match std::fs::read_dir(base) {
+        Ok(rd) => rd
+            .flatten()
+            .filter(|e| {
+                e.file_name()
+                    .to_str()
+                    .map(|n| n.to_ascii_uppercase().starts_with("BAT"))
+                    .unwrap_or(false)
+            })
+            .map(|e| e.path())
+            .collect(),
+        Err(_) => return Vec::new(),
+    };
+    batteries.sort();
+    batteries
+}
+
+/// Attributes of a power_supply `uevent` file (`POWER_SUPPLY_CAPACITY=87`, ...), keyed by
+/// their sysfs file name (`capacity`). Lines without `=` or with an empty key are skipped;
+/// a repeated key keeps its last value.
+pub fn parse_uevent(contents: &str) -> HashMap<String, String> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            let key = key.trim();
+            let key = key.strip_prefix("POWER_SUPPLY_").unwrap_or(key);
+            (!key.is_empty()).then(|| (key.to_ascii_lowercase(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Read one battery directory; missing attributes are left as `None`. Everything comes
+/// from a single read of `uevent`, falling back to the individual attribute files when
+/// the battery has none.
+pub fn read_battery(dir: &Path) -> BatteryReading {
+    match std::fs::read_to_string(dir.join("uevent")) {
+        Ok(contents) => {
+            let attributes = parse_uevent(&contents);
+            battery_reading(|name| attributes.get(name).cloned())
+        }
+        Err(_) => battery_reading(|name| {
+            std::fs::read_to_string(dir.join(name))
+                .ok()
+                .map(|s| s.trim().to_string())
+        }),
+    }
+}
+
+/// [`BatteryReading`] from a lookup of sysfs attributes by file name
+fn battery_reading(read: impl Fn(&str) -> Option<String>) -> BatteryReading {
+    let read_u64 = |name: &str| read(name).and_then(|s| s.parse::<u64>().ok());
+    let (now, full, power, unit) = match (read_u64("energy_now"), read_u64("energy_full")) {
+        (Some(now), Some(full)) => (
+            Some(now),
+            Some(full),
+            read_u64("power_now"),
+            BatteryUnit::Energy,
+        ),
+        _ => (
+            read_u64("charge_now"),
+            read_u64("charge_full"),
+            read_u64("current_now"),
+            BatteryUnit::Charge,
+        ),
+    };
+    BatteryReading {
+        now,
+        full,
+        capacity: read("capacity").and_then(|s| s.parse::<u8>().ok()),
+        status: read("status").filter(|s| !s.is_empty()),
+        power,
+        unit,
+        voltage: read_u64("voltage_min_design"),
+    }
+}
+
+/// Combine batteries into one reading. The percentage is weighted by capacity
+/// (sum of `now` over sum of `full`) when every battery reports both, otherwise the mean
+/// of their `capacity` files. Batteries mixing `energy_*` and `charge_*` files are summed
+/// in energy, converting charge at `voltage_min_design`; without a voltage to convert
+/// with, only the `capacity` mean is used. Any charging battery makes the aggregate
+/// "Charging", then any discharging one "Discharging"; "Full" only when all are full.
+pub fn aggregate_batteries(readings: &[BatteryReading]) -> Option<BatteryAggregate> {
+    let first = readings.first()?;
+    let converted: Option<Vec<BatteryReading>> = if readings.iter().all(|r| r.unit == first.unit) {
+        Some(readings.to_vec())
+    } else {
+        readings.iter().map(BatteryReading::to_energy).collect()
+    };
+    let comparable = converted.is_some();
+    let readings = converted.as_deref().unwrap_or(readings);
+    let energy: Option<Vec<(u64, u64)>> = readings
+        .iter()
+        .map(|r| r.now.zip(r.full).filter(|(_, full)| *full > 0))
+        .collect::<Option<_>>()
+        .filter(|_| comparable);
+    let totals = energy.as_ref().map(|pairs| {
+        (
+            pairs.iter().map(|(n, _)| n).sum::<u64>(),
+            pairs.iter().map(|(_, f)| f).sum::<u64>(),
+        )
+    });
+    let percent = match energy {
+        Some(pairs) => {
+            let now: u64 = pairs.iter().map(|(n, _)| n).sum();
+            let full: u64 = pairs.iter().map(|(_, f)| f).sum();
+            ((now * 100 + full / 2) / full).min(100) as u8
+        }
+        None => {
+            let caps: Vec<u32> = readings
+                .iter()
+                .filter_map(|r| r.capacity.map(u32::from))
+                .collect();
+            if caps.is_empty() {
+                0
+            } else {
+                (caps.iter().sum::<u32>() / caps.len() as u32).min(100) as u8
+            }
+        }
+    };
+
+    let statuses: Vec<&str> = readings
+        .iter()
+        .filter_map(|r| r.status.as_deref())
+        .collect();
+    let status = if statuses.contains(&"Charging") {
+        "Charging"
+    } else if statuses.contains(&"Discharging") {
+        "Discharging"
+    } else if !statuses.is_empty() && statuses.iter().all(|s| *s == "Full") {
+        "Full"
+    } else {
+        statuses.first().copied().unwrap_or("Unknown")
+    };
+
+    Some(BatteryAggregate {
+        percent,
+        status: status.to_string(),
+        now: totals.map(|(now, _)| now),
+        full: totals.map(|(_, full)| full),
+        power: readings
+            .iter()
+            .map(|r| r.power)
+            .sum::<Option<u64>>()
+            .filter(|_| comparable),
+    })
+}
+
+/// Power samples averaged for `{time}`
+pub const POWER_AVERAGE_SAMPLES: usize = 5;
+
+/// Moving average over the last few power draws, so `{time}` doesn't jump with every
+/// reading. Samples are dropped when the battery switches between charging and discharging.
+#[derive(Debug, Clone, Default)]
+pub struct PowerAverage {
+    samples: VecDeque<u64>,
+    charging: Option<bool>,
+}
+
+impl PowerAverage {
+    /// Add a sample and return the current average; `None` for a zero reading
+    pub fn push(&mut self, power: u64, charging: bool) -> Option<u64> {
+        if self.charging != Some(charging) {
+            self.samples.clear();
+            self.charging = Some(charging);
+        }
+        if power == 0 {
+            return None;
+        }
+        if self.samples.len() == POWER_AVERAGE_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(power);
+        Some(self.samples.iter().sum::<u64>() / self.samples.len() as u64)
+    }
+}
+
+/// Seconds until empty (discharging) or full (charging) at `power`; `None` without a draw
+pub fn time_remaining_secs(now: u64, full: u64, power: u64, charging: bool) -> Option<u64> {
+    if power == 0 {
+        return None;
+    }
+    let remaining = if charging {
+        full.saturating_sub(now)
+    } else {
+        now
+    };
+    Some(remaining * 3600 / power)
+}
+
+/// Feed the aggregate's power draw into `average` and estimate the time left; `None`
+/// unless the pack is charging or discharging with known energy and power
+pub fn estimate_time_secs(agg: &BatteryAggregate, average: &mut PowerAverage) -> Option<u64> {
+    let charging = match agg.status.as_str() {
+        "Charging" => true,
+        "Discharging" => false,
+        _ => return None,
+    };
+    let power = average.push(agg.power?, charging)?;
+    time_remaining_secs(agg.now?, agg.full?, power, charging)
+}
+
+/// Render seconds as `H:MM`
+pub fn format_time_hm(secs: u64) -> String {
+    format!("{}:{:02}", secs / 3600, (secs % 3600) / 60)
+}
+
+/// Expand `{percent}`, `{status}` and `{time}` in a format string. An unknown `{time}`
+/// expands to nothing and surrounding whitespace is trimmed.
+pub fn render_battery_format(fmt: &str, percent: u8, status: &str, time: Option<u64>) -> String {
+    fmt.replace("{percent}", &percent.to_string())
+        .replace("{status}", status)
+        .replace("{time}", &time.map(format_time_hm).unwrap_or_default())
+        .trim()
+        .to_string()
+}
+
+/// A desktop notification due after a battery update
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatteryAlert {
+    /// Dropped to or below the critical threshold while discharging (percent)
+    Low(u8),
+    /// Plugged back in after a low alert
+    Charging,
+}
+
+/// Crossing detection for `notify_low`: one `Low` per drop below the critical threshold and
+/// one `Charging` when plugged in afterwards, however often the battery is read in between.
+/// Re-armed by charging or by climbing back above the threshold.
+#[derive(Debug, Clone, Default)]
+pub struct LowBatteryNotifier {
+    low_sent: bool,
+}
+
+impl LowBatteryNotifier {
+    /// Feed one aggregate reading; returns the alert it triggers, if any
+    pub fn update(&mut self, percent: u8, status: &str, critical: u8) -> Option<BatteryAlert> {
+        match status {
+            "Charging" if self.low_sent => {
+                self.low_sent = false;
+                Some(BatteryAlert::Charging)
+            }
+            "Discharging" if percent <= critical && !self.low_sent => {
+                self.low_sent = true;
+                Some(BatteryAlert::Low(percent))
+            }
+            _ => {
+                if percent > critical {
+                    self.low_sent = false;
+                }
+                None
+            }
+        }
+    }
+}
+
+/// Classes the charging animation cycles through on the icon, one per shared 1s tick
+pub const CHARGING_FRAME_CLASSES: [&str; 4] =
+    ["charging-0", "charging-1", "charging-2", "charging-3"];
+
+/// Static charge-state class for a status that isn't animated: `full` once full, `charging`
+/// while plugged in; nothing while discharging
+pub fn static_charge_class(status: &str) -> Option<&'static str> {
+    match status {
+        "Full" => Some("full"),
+        "Charging" | "Not charging" => Some("charging"),
+        _ => None,
+    }
+}
+
+/// Charge-state class for the icon: with `animate`, a charging battery cycles through
+/// [`CHARGING_FRAME_CLASSES`]; any other status stops the cycle and falls back to
+/// [`static_charge_class`]
+#[derive(Debug, Clone, Default)]
+pub struct ChargingAnimation {
+    animate: bool,
+    frame: Option<usize>,
+}
+
+impl ChargingAnimation {
+    pub fn new(animate: bool) -> Self {
+        Self {
+            animate,
+            frame: None,
+        }
+    }
+
+    /// Feed the aggregate status; returns the class to show now. Charging keeps the
+    /// current frame, so repeated readings don't restart the cycle.
+    pub fn set_status(&mut self, status: &str) -> Option<&'static str> {
+        if self.animate && status == "Charging" {
+            let frame = *self.frame.get_or_insert(0);
+            return Some(CHARGING_FRAME_CLASSES[frame]);
+        }
+        self.frame = None;
+        static_charge_class(status)
+    }
+
+    /// Advance one tick: the next frame's class, or `None` once the animation stopped
+    pub fn tick(&mut self) -> Option<&'static str> {
+        let frame = self.frame.as_mut()?;
+        *frame = (*frame + 1) % CHARGING_FRAME_CLASSES.len();
+        Some(CHARGING_FRAME_CLASSES[*frame])
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.frame.is_some()
+    }
+}
+
+/// Send a desktop notification for `alert` through `org.freedesktop.Notifications`
+fn send_battery_notification(alert: BatteryAlert) -> anyhow::Result<()> {
+    let (icon, summary, body, urgency) = match alert {
+        BatteryAlert::Low(percent) => (
+            "battery-caution-symbolic",
+            "Battery low".to_string(),
+            format!("{}% remaining, plug in the charger", percent),
+            2u8,
+        ),
+        BatteryAlert::Charging => (
+            "battery-good-charging-symbolic",
+            "Battery charging".to_string(),
+            "Charger connected".to_string(),
+            1u8,
+        ),
+    };
+    let mut hints: HashMap<&str, Value> = HashMap::new();
+    hints.insert("urgency", Value::from(urgency));
+    let conn = Connection::session()?;
+    conn.call_method(
+        Some("org.freedesktop.Notifications"),
+        "/org/freedesktop/Notifications",
+        Some("org.freedesktop.Notifications"),
+        "Notify",
+        &(
+            "niri-bar",
+            0u32,
+            icon,
+            summary.as_str(),
+            body.as_str(),
+            Vec::<&str>::new(),
+            hints,
+            -1i32,
+        ),
+    )?;
+    Ok(())
+}
 
 #[derive(Clone)]
 struct BatteryOpts {
     show_icon: bool,
-    show_percentage: bool,
+    format: String,
+    tooltip_format: Option<String>,
     warn: u8,
     crit: u8,
     pulse: bool,
+    /// Shared across clones so every update feeds the same average
+    power_average: Rc<RefCell<PowerAverage>>,
+    /// Present when `notify_low` is on; shared like `power_average`
+    low_notifier: Option<Rc<RefCell<LowBatteryNotifier>>>,
+    /// Shared like `power_average`; `animation_ticking` is set while a tick drives it
+    charge_animation: Rc<RefCell<ChargingAnimation>>,
+    animation_ticking: Rc<Cell<bool>>,
 }
 
 pub struct BatteryModule;
@@ -19,19 +454,31 @@ impl BatteryModule {
     pub const IDENT: &'static str = "bar.module.battery";
 
     pub fn create_widget(settings: &ModuleConfig) -> gtk::Widget {
-        // Read config with safe defaults
+        // Read config with safe defaults; without `device` all batteries are combined
         let device = settings
             .additional
             .get("device")
             .and_then(|v| v.as_str())
-            .map(|s| s.to_string())
-            .unwrap_or_else(|| "bat0".to_string());
+            .map(|s| s.to_string());
         let show_icon = settings
             .additional
             .get("show_icon")
             .and_then(|v| v.as_bool())
             .unwrap_or(true);
         let show_percentage = settings.show_percentage.unwrap_or(true);
+        let format = settings.format.clone().unwrap_or_else(|| {
+            if show_percentage {
+                "{percent}%".to_string()
+            } else {
+                String::new()
+            }
+        });
+        let interval_ms = settings
+            .additional
+            .get("interval_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(30_000)
+            .max(1000);
         let pulse = settings
             .additional
             .get("pulse")
@@ -39,13 +486,28 @@ impl BatteryModule {
             .unwrap_or(true);
         let warn = settings.warn_threshold.unwrap_or(40);
         let crit = settings.critical_threshold.unwrap_or(10);
+        let notify_low = settings
+            .additional
+            .get("notify_low")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let animate = settings
+            .additional
+            .get("animate")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
 
         let opts = BatteryOpts {
             show_icon,
-            show_percentage,
+            format,
+            tooltip_format: super::tooltip_template(settings),
             warn,
             crit,
             pulse,
+            power_average: Rc::new(RefCell::new(PowerAverage::default())),
+            low_notifier: notify_low.then(|| Rc::new(RefCell::new(LowBatteryNotifier::default()))),
+            charge_animation: Rc::new(RefCell::new(ChargingAnimation::new(animate))),
+            animation_ticking: Rc::new(Cell::new(false)),
         };
 
         // Root container: box with label and optional menu button
@@ -148,50 +610,34 @@ impl BatteryModule {
             // no menu shown
         }
 
-        // Determine sysfs paths (case-insensitive bat0/BAT0, and fallback scan)
-        let resolved = resolve_battery_device(&device);
-        log::info!("Battery device resolved to: {:?}", resolved);
-        let cap_path = resolved.join("capacity");
-        let stat_path = resolved.join("status");
+        // Determine sysfs paths: a pinned device (case-insensitive bat0/BAT0, and fallback
+        // scan), or every BAT* entry
+        let devices = match device.as_deref() {
+            Some(device) => vec![resolve_battery_device(device)],
+            None => list_batteries(Path::new(POWER_SUPPLY_DIR)),
+        };
+        log::info!("Battery devices resolved to: {:?}", devices);
 
         // Initial render
-        update_battery_label(&label, Some(&image), &cap_path, &stat_path, &opts);
-
-        // Event-driven updates via gio FileMonitor (no polling, stays on GTK main loop)
-        let mut monitors: Vec<gio::FileMonitor> = Vec::new();
-        for p in [&cap_path, &stat_path] {
-            let file = gio::File::for_path(p);
-            if let Ok(mon) =
-                file.monitor_file(gio::FileMonitorFlags::NONE, None::<&gio::Cancellable>)
-            {
-                log::info!("Monitoring battery file: {:?}", p);
-                let lbl = label.clone();
-                let img = image.clone();
-                let cap_p = cap_path.clone();
-                let stat_p = stat_path.clone();
-                let opts_c = opts.clone();
-                mon.connect_changed(move |_, _file, _other, _event| {
-                    log::info!("Battery file changed: {:?}", _event);
-                    update_battery_label(&lbl, Some(&img), &cap_p, &stat_p, &opts_c);
-                });
-                monitors.push(mon);
-            } else {
-                log::warn!("Failed to monitor battery file: {:?}", p);
-            }
-        }
-        // keep monitors alive by attaching to widget data (unsafe per GTK API contract)
-        unsafe {
-            root.set_data("battery_file_monitors", monitors);
-        }
+        update_battery_label(&label, Some(&image), &devices, &opts);
 
-        // No fallback polling needed - file monitoring is reliable on modern systems
+        // Re-read the batteries on the shared tick; `{time}` needs regular power samples
+        let label_weak = label.downgrade();
+        let image_weak = image.downgrade();
+        super::subscribe_refresh(std::time::Duration::from_millis(interval_ms), move || {
+            let (Some(lbl), Some(img)) = (label_weak.upgrade(), image_weak.upgrade()) else {
+                return glib::ControlFlow::Break;
+            };
+            update_battery_label(&lbl, Some(&img), &devices, &opts);
+            glib::ControlFlow::Continue
+        });
 
         root.upcast()
     }
 }
 
-fn resolve_battery_device(preferred: &str) -> std::path::PathBuf {
-    let base = std::path::Path::new("/sys/class/power_supply");
+fn resolve_battery_device(preferred: &str) -> PathBuf {
+    let base = Path::new(POWER_SUPPLY_DIR);
     let mut candidates = vec![
         preferred.to_string(),
         preferred.to_uppercase(),
@@ -245,21 +691,34 @@ fn find_powerprofilesctl() -> Option<std::path::PathBuf> {
 fn update_battery_label(
     label: &gtk::Label,
     image: Option<&gtk::Image>,
-    capacity_path: &std::path::Path,
-    status_path: &std::path::Path,
+    devices: &[PathBuf],
     opts: &BatteryOpts,
 ) {
-    let mut pct: Option<u8> = None;
-    let mut stat: Option<String> = None;
-    if let Ok(s) = std::fs::read_to_string(capacity_path) {
-        pct = s.trim().parse::<u8>().ok();
-    }
-    if let Ok(s) = std::fs::read_to_string(status_path) {
-        stat = Some(s.trim().to_string());
+    let readings: Vec<BatteryReading> = devices.iter().map(|d| read_battery(d)).collect();
+    let aggregate = aggregate_batteries(&readings);
+    let p = aggregate.as_ref().map(|a| a.percent).unwrap_or(0);
+    let charging = aggregate.as_ref().is_some_and(|a| a.charging());
+    let time = aggregate
+        .as_ref()
+        .and_then(|a| estimate_time_secs(a, &mut opts.power_average.borrow_mut()));
+    let stat = aggregate.map(|a| a.status);
+
+    if let Some(notifier) = &opts.low_notifier
+        && let Some(alert) =
+            notifier
+                .borrow_mut()
+                .update(p, stat.as_deref().unwrap_or("Unknown"), opts.crit)
+    {
+        log::info!("Battery: 🔔 sending {:?} notification", alert);
+        // D-Bus round trip off the GTK thread
+        std::thread::spawn(move || {
+            if let Err(e) = send_battery_notification(alert) {
+                log::warn!("Battery: failed to send notification: {}", e);
+            }
+        });
     }
 
-    let p = pct.unwrap_or(0);
-    let charging = stat.as_deref() == Some("Charging");
+    let status = stat.as_deref().unwrap_or("Unknown");
 
     // Choose icon name (symbolic SVG from theme)
     if let Some(img) = image {
@@ -269,15 +728,26 @@ fn update_battery_label(
         } else {
             img.set_icon_name(None::<&str>);
         }
+        let class = opts.charge_animation.borrow_mut().set_status(status);
+        set_charge_class(img, class);
+        if opts.charge_animation.borrow().is_animating() && !opts.animation_ticking.get() {
+            start_charging_animation(img, opts);
+        }
     }
 
-    let txt = if opts.show_percentage {
-        format!("{}%", p)
-    } else {
-        String::new()
-    };
+    let txt = render_battery_format(&opts.format, p, status, time);
     log::debug!("Battery update: {}%, status: {:?}, text: {}", p, stat, txt);
     label.set_text(&txt);
+    // Same placeholders as `format`
+    super::apply_tooltip(
+        label,
+        opts.tooltip_format.as_deref(),
+        &[
+            ("percent", &p.to_string()),
+            ("status", status),
+            ("time", &time.map(format_time_hm).unwrap_or_default()),
+        ],
+    );
 
     // Set classes for colorization
     label.remove_css_class("battery-ok");
@@ -307,6 +777,38 @@ fn update_battery_label(
     }
 }
 
+/// Show `class` on the icon in place of any other charge-state class
+fn set_charge_class(image: &gtk::Image, class: Option<&str>) {
+    for other in CHARGING_FRAME_CLASSES.iter().chain(&["charging", "full"]) {
+        if class != Some(*other) {
+            image.remove_css_class(other);
+        }
+    }
+    if let Some(class) = class {
+        image.add_css_class(class);
+    }
+}
+
+/// Step the charging animation on the shared 1s tick until it stops or the icon is gone
+fn start_charging_animation(image: &gtk::Image, opts: &BatteryOpts) {
+    opts.animation_ticking.set(true);
+    let image_weak = image.downgrade();
+    let animation = opts.charge_animation.clone();
+    let ticking = opts.animation_ticking.clone();
+    super::subscribe_refresh(std::time::Duration::from_secs(1), move || {
+        let Some(image) = image_weak.upgrade() else {
+            ticking.set(false);
+            return glib::ControlFlow::Break;
+        };
+        let Some(class) = animation.borrow_mut().tick() else {
+            ticking.set(false);
+            return glib::ControlFlow::Break;
+        };
+        set_charge_class(&image, Some(class));
+        glib::ControlFlow::Continue
+    });
+}
+
 fn select_battery_icon_name(percent: u8, charging: bool) -> String {
     // Map to standard Adwaita symbolic icon names
     let bucket = if percent >= 95 {
@@ -0,0 +1,182 @@
+use gtk4 as gtk;
+use gtk4::prelude::*;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use super::{BarModule, ScrollThrottle, scroll_throttle_ms};
+use crate::config::ModuleConfig;
+
+/// Brightness as a whole percentage of `max` (0 when `max` is unknown)
+pub fn brightness_percent(current: u64, max: u64) -> u8 {
+    if max == 0 {
+        return 0;
+    }
+    ((current.min(max) as f64 / max as f64) * 100.0).round() as u8
+}
+
+/// Raw brightness after stepping by `delta_percent` of `max`, clamped to `0..=max`
+pub fn step_brightness(current: u64, delta_percent: i32, max: u64) -> u64 {
+    let step = (max as f64 * delta_percent as f64 / 100.0).round() as i64;
+    (current as i64 + step).clamp(0, max as i64) as u64
+}
+
+/// Resolve the backlight device directory: the configured name, else the first entry
+fn resolve_backlight_device(preferred: Option<&str>) -> Option<PathBuf> {
+    let base = Path::new("/sys/class/backlight");
+    if let Some(name) = preferred {
+        let p = base.join(name);
+        if p.exists() {
+            return Some(p);
+        }
+        log::warn!("Brightness: backlight device '{}' not found", name);
+    }
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(base)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .collect();
+    entries.sort();
+    entries.into_iter().next()
+}
+
+fn read_u64(path: &Path) -> Option<u64> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|s| s.trim().parse::<u64>().ok())
+}
+
+/// Write a raw brightness value; falls back to `brightnessctl` when sysfs isn't writable
+fn write_brightness(device: &Path, value: u64) {
+    if std::fs::write(device.join("brightness"), value.to_string()).is_ok() {
+        return;
+    }
+    let name = device
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    match std::process::Command::new("brightnessctl")
+        .args(["-q", "-d", &name, "set", &value.to_string()])
+        .spawn()
+    {
+        Ok(mut child) => {
+            // Reap the child off the GTK thread
+            std::thread::spawn(move || {
+                let _ = child.wait();
+            });
+            log::debug!("Brightness: set {} via brightnessctl", value);
+        }
+        Err(e) => log::error!("Brightness: failed to set brightness: {}", e),
+    }
+}
+
+pub struct BrightnessModule;
+
+impl BrightnessModule {
+    pub const IDENT: &'static str = "bar.module.brightness";
+
+    pub fn create_widget(settings: &ModuleConfig) -> gtk::Widget {
+        let fmt = settings
+            .format
+            .clone()
+            .unwrap_or_else(|| "{percent}%".to_string());
+        let device_name = settings
+            .additional
+            .get("device")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        let step = settings
+            .additional
+            .get("step")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(5) as i32;
+
+        let label = gtk::Label::new(None);
+        label.add_css_class("module-brightness");
+        label.set_hexpand(true);
+        label.set_halign(gtk::Align::Fill);
+
+        let Some(device) = resolve_backlight_device(device_name.as_deref()) else {
+            log::warn!("Brightness: no backlight device found");
+            return label.upcast();
+        };
+        log::info!("Brightness: using backlight device {:?}", device);
+
+        let update = {
+            let device = device.clone();
+            move |label: &gtk::Label| {
+                let current = read_u64(&device.join("brightness")).unwrap_or(0);
+                let max = read_u64(&device.join("max_brightness")).unwrap_or(0);
+                let pct = brightness_percent(current, max);
+                label.set_text(&fmt.replace("{percent}", &pct.to_string()));
+            }
+        };
+        update(&label);
+
+        // The backlight class notifies `actual_brightness` when keys or firmware change the
+        // level; writes by other tools land in `brightness`
+        let watcher: Rc<RefCell<Option<RecommendedWatcher>>> = Rc::new(RefCell::new(None));
+        let label_weak = label.downgrade();
+        let watch_update = update.clone();
+        let tx = super::gtk_receiver({
+            let watcher = watcher.clone();
+            move |_: Vec<()>| {
+                let Some(label) = label_weak.upgrade() else {
+                    watcher.borrow_mut().take();
+                    return glib::ControlFlow::Break;
+                };
+                watch_update(&label);
+                glib::ControlFlow::Continue
+            }
+        });
+        match notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            if res.is_ok() {
+                let _ = tx.send(());
+            }
+        }) {
+            Ok(mut w) => {
+                for file in ["brightness", "actual_brightness"] {
+                    if let Err(e) = w.watch(&device.join(file), RecursiveMode::NonRecursive) {
+                        log::warn!("Brightness: failed to watch {}: {}", file, e);
+                    }
+                }
+                *watcher.borrow_mut() = Some(w);
+            }
+            Err(e) => log::warn!("Brightness: failed to create watcher: {}", e),
+        }
+
+        // Scroll to adjust brightness
+        let scroll = gtk::EventControllerScroll::new(
+            gtk::EventControllerScrollFlags::VERTICAL | gtk::EventControllerScrollFlags::DISCRETE,
+        );
+        let scroll_label = label.downgrade();
+        let throttle = RefCell::new(ScrollThrottle::new(scroll_throttle_ms(settings)));
+        scroll.connect_scroll(move |_, _dx, dy| {
+            if !throttle.borrow_mut().accept(dy, std::time::Instant::now()) {
+                return gtk::glib::Propagation::Stop;
+            }
+            let current = read_u64(&device.join("brightness")).unwrap_or(0);
+            let max = read_u64(&device.join("max_brightness")).unwrap_or(0);
+            let delta = if dy < 0.0 { step } else { -step };
+            write_brightness(&device, step_brightness(current, delta, max));
+            // Reflect a direct sysfs write now; brightnessctl lands through the watcher
+            if let Some(label) = scroll_label.upgrade() {
+                update(&label);
+            }
+            gtk::glib::Propagation::Stop
+        });
+        label.add_controller(scroll);
+
+        label.upcast()
+    }
+}
+
+impl BarModule for BrightnessModule {
+    fn id(&self) -> &'static str {
+        Self::IDENT
+    }
+    fn create(&self, settings: &ModuleConfig) -> gtk::Widget {
+        Self::create_widget(settings)
+    }
+}
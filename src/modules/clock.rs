@@ -1,10 +1,160 @@
-use chrono::Local;
+use chrono::{DateTime, Local, Locale, Utc};
+use chrono_tz::Tz;
 use gtk4 as gtk;
 use gtk4::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 
-use super::BarModule;
+use super::{BarModule, ScrollThrottle, scroll_throttle_ms, tooltip_template};
 use crate::config::ModuleConfig;
 
+/// Zone a clock renders in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClockZone {
+    Local,
+    Named(Tz),
+}
+
+/// Parse an IANA zone name ("local" selects local time); unknown names fall back to local time
+pub fn parse_timezone(name: &str) -> ClockZone {
+    let name = name.trim();
+    if name.eq_ignore_ascii_case("local") {
+        return ClockZone::Local;
+    }
+    match name.parse::<Tz>() {
+        Ok(tz) => ClockZone::Named(tz),
+        Err(_) => {
+            log::warn!("Clock: unknown timezone '{}', using local time", name);
+            ClockZone::Local
+        }
+    }
+}
+
+/// Zones from `additional["timezones"]` (a list, or a single name); local time when unset
+pub fn parse_timezones(settings: &ModuleConfig) -> Vec<ClockZone> {
+    let zones: Vec<ClockZone> = match settings.additional.get("timezones") {
+        Some(serde_yaml::Value::Sequence(names)) => names
+            .iter()
+            .filter_map(|v| v.as_str())
+            .map(parse_timezone)
+            .collect(),
+        Some(serde_yaml::Value::String(name)) => vec![parse_timezone(name)],
+        _ => Vec::new(),
+    };
+    if zones.is_empty() {
+        vec![ClockZone::Local]
+    } else {
+        zones
+    }
+}
+
+/// Zone index after scrolling: down moves to the next zone, up to the previous, wrapping
+pub fn cycle_zone_index(current: usize, len: usize, dy: f64) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    if dy < 0.0 {
+        (current + len - 1) % len
+    } else {
+        (current + 1) % len
+    }
+}
+
+/// Environment variables naming the time locale, in POSIX precedence order
+const LOCALE_ENV_VARS: [&str; 3] = ["LC_ALL", "LC_TIME", "LANG"];
+
+/// Parse a locale name such as `de_DE`, `de-DE` or `de_DE.UTF-8@euro`; `C` and `POSIX`
+/// select the C locale. `None` for names chrono has no data for.
+pub fn parse_locale(name: &str) -> Option<Locale> {
+    let base = name
+        .trim()
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .replace('-', "_");
+    if base == "C" || base == "POSIX" {
+        return Some(Locale::POSIX);
+    }
+    Locale::try_from(base.as_str()).ok()
+}
+
+/// Locale for weekday and month names: `configured` (`additional["locale"]`), else the
+/// system's time locale looked up through `env`, else the C locale
+pub fn resolve_locale(configured: Option<&str>, env: impl Fn(&str) -> Option<String>) -> Locale {
+    if let Some(name) = configured {
+        return parse_locale(name).unwrap_or_else(|| {
+            log::warn!("Clock: unknown locale '{}', using the C locale", name);
+            Locale::POSIX
+        });
+    }
+    let system = LOCALE_ENV_VARS
+        .iter()
+        .filter_map(|var| env(var))
+        .find(|value| !value.is_empty());
+    match system {
+        Some(name) => parse_locale(&name).unwrap_or_else(|| {
+            log::debug!(
+                "Clock: system locale '{}' unsupported, using the C locale",
+                name
+            );
+            Locale::POSIX
+        }),
+        None => Locale::POSIX,
+    }
+}
+
+/// Render `now` in `zone` as (label text, tooltip) in the C locale; the tooltip carries the
+/// zone abbreviation
+pub fn render_clock(now: DateTime<Utc>, zone: ClockZone, fmt: &str) -> (String, String) {
+    render_clock_localized(now, zone, fmt, Locale::POSIX)
+}
+
+/// [`render_clock`] with weekday and month names from `locale`
+pub fn render_clock_localized(
+    now: DateTime<Utc>,
+    zone: ClockZone,
+    fmt: &str,
+    locale: Locale,
+) -> (String, String) {
+    match zone {
+        ClockZone::Local => {
+            let t = now.with_timezone(&Local);
+            (
+                t.format_localized(fmt, locale).to_string(),
+                format!("Local ({})", t.format("%Z")),
+            )
+        }
+        ClockZone::Named(tz) => {
+            let t = now.with_timezone(&tz);
+            (
+                t.format_localized(fmt, locale).to_string(),
+                format!("{} ({})", tz.name(), t.format("%Z")),
+            )
+        }
+    }
+}
+
+/// Whether `additional["calendar"]` enables the calendar popover (default false)
+pub fn calendar_enabled(settings: &ModuleConfig) -> bool {
+    settings
+        .additional
+        .get("calendar")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// Popover holding a calendar; opens below its parent and closes on outside click or Escape
+pub fn build_calendar_popover() -> (gtk::Popover, gtk::Calendar) {
+    let calendar = gtk::Calendar::new();
+    calendar.add_css_class("clock-calendar");
+    let popover = gtk::Popover::new();
+    popover.add_css_class("clock-calendar-popover");
+    popover.set_position(gtk::PositionType::Bottom);
+    popover.set_autohide(true);
+    popover.set_child(Some(&calendar));
+    (popover, calendar)
+}
+
 pub struct ClockModule;
 
 impl ClockModule {
@@ -13,6 +163,14 @@ impl ClockModule {
     pub fn create_widget(settings: &ModuleConfig) -> gtk::Widget {
         let default_fmt = "%a %b %d, %Y @ %I:%M:%S %p".to_string();
         let fmt = settings.format.clone().unwrap_or(default_fmt);
+        let zones = Rc::new(parse_timezones(settings));
+        let show_tooltip = settings.tooltip.unwrap_or(true);
+        // `tooltip_format` is a strftime format like `format`; without it, the zone name
+        let tooltip_fmt = tooltip_template(settings);
+        let locale = resolve_locale(
+            settings.additional.get("locale").and_then(|v| v.as_str()),
+            |var| std::env::var(var).ok(),
+        );
 
         let label = gtk::Label::new(None);
         label.add_css_class("module-clock");
@@ -20,26 +178,90 @@ impl ClockModule {
         label.set_hexpand(true);
         label.set_halign(gtk::Align::Fill);
 
-        let now_text = Local::now().format(&fmt).to_string();
-        label.set_text(&now_text);
+        // Index into `zones` of the zone currently shown
+        let active = Rc::new(Cell::new(0usize));
+        let update = {
+            let zones = zones.clone();
+            let active = active.clone();
+            move |label: &gtk::Label| {
+                let now = Utc::now();
+                let (text, tooltip) =
+                    render_clock_localized(now, zones[active.get()], &fmt, locale);
+                label.set_text(&text);
+                if let Some(tooltip_fmt) = &tooltip_fmt {
+                    let (markup, _) =
+                        render_clock_localized(now, zones[active.get()], tooltip_fmt, locale);
+                    label.set_tooltip_markup(Some(&markup));
+                } else if show_tooltip {
+                    label.set_tooltip_text(Some(&tooltip));
+                }
+            }
+        };
+        let update = Rc::new(update);
+        update(&label);
 
-        // Event-driven clock updates with efficient timing
         let label_weak = label.downgrade();
-        let fmt_clone = fmt.clone();
-
-        // Use a more efficient update mechanism
-        glib::timeout_add_local(std::time::Duration::from_millis(1000), move || {
+        let update_tick = update.clone();
+        super::subscribe_refresh(std::time::Duration::from_secs(1), move || {
             if let Some(label) = label_weak.upgrade() {
-                let text = Local::now().format(&fmt_clone).to_string();
-                label.set_text(&text);
+                update_tick(&label);
                 glib::ControlFlow::Continue
             } else {
                 glib::ControlFlow::Break
             }
         });
 
+        // Scroll cycles through the configured zones
+        if zones.len() > 1 {
+            let throttle = RefCell::new(ScrollThrottle::new(scroll_throttle_ms(settings)));
+            let scroll = gtk::EventControllerScroll::new(
+                gtk::EventControllerScrollFlags::VERTICAL
+                    | gtk::EventControllerScrollFlags::DISCRETE,
+            );
+            let label_weak = label.downgrade();
+            scroll.connect_scroll(move |_, _dx, dy| {
+                if !throttle.borrow_mut().accept(dy, std::time::Instant::now()) {
+                    return gtk::glib::Propagation::Proceed;
+                }
+                active.set(cycle_zone_index(active.get(), zones.len(), dy));
+                log::debug!("Clock: 🛞 switched to {:?}", zones[active.get()]);
+                if let Some(label) = label_weak.upgrade() {
+                    update(&label);
+                }
+                gtk::glib::Propagation::Stop
+            });
+            label.add_controller(scroll);
+        }
+
+        if calendar_enabled(settings) {
+            Self::attach_calendar(&label);
+        }
+
         label.upcast()
     }
+
+    /// Open a calendar popover under the label on left click
+    fn attach_calendar(label: &gtk::Label) {
+        let (popover, calendar) = build_calendar_popover();
+        popover.set_parent(label);
+        // The popover isn't a regular child; release it with the label
+        label.connect_destroy({
+            let popover = popover.clone();
+            move |_| popover.unparent()
+        });
+
+        let click = gtk::GestureClick::new();
+        click.set_button(gtk::gdk::BUTTON_PRIMARY);
+        click.connect_pressed(move |gesture, _n_press, _x, _y| {
+            // Always open on today's month
+            if let Ok(today) = gtk::glib::DateTime::now_local() {
+                calendar.select_day(&today);
+            }
+            popover.popup();
+            gesture.set_state(gtk::EventSequenceState::Claimed);
+        });
+        label.add_controller(click);
+    }
 }
 
 impl BarModule for ClockModule {
@@ -0,0 +1,223 @@
+use anyhow::Result;
+use gtk4 as gtk;
+use gtk4::prelude::*;
+use once_cell::sync::Lazy;
+use std::sync::Mutex;
+use zbus::blocking::Connection;
+
+use super::{BarModule, StateBroadcast};
+use crate::config::ModuleConfig;
+
+/// CSS class applied while idle is inhibited
+pub const ACTIVE_CLASS: &str = "active";
+
+/// Reason shown by the screensaver/power manager for the inhibit
+const INHIBIT_REASON: &str = "Idle inhibitor toggled in niri-bar";
+
+/// Idle inhibit service; implemented over D-Bus, mockable in tests
+pub trait InhibitBackend {
+    /// Start inhibiting idle; returns the cookie that releases it
+    fn inhibit(&mut self, reason: &str) -> Result<u32>;
+    /// Release the inhibit taken with `cookie`
+    fn uninhibit(&mut self, cookie: u32) -> Result<()>;
+}
+
+/// Inhibitor state: active while it holds a cookie
+pub struct IdleInhibitor<B: InhibitBackend> {
+    backend: B,
+    cookie: Option<u32>,
+}
+
+impl<B: InhibitBackend> IdleInhibitor<B> {
+    pub fn new(backend: B) -> Self {
+        Self {
+            backend,
+            cookie: None,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.cookie.is_some()
+    }
+
+    /// Cookie of the inhibit currently held
+    pub fn cookie(&self) -> Option<u32> {
+        self.cookie
+    }
+
+    /// Take or release the inhibit; no-op when already in the requested state. A failed
+    /// release still drops the cookie, since the service no longer honours it either way.
+    pub fn set_active(&mut self, active: bool) -> Result<()> {
+        match (active, self.cookie) {
+            (true, None) => {
+                self.cookie = Some(self.backend.inhibit(INHIBIT_REASON)?);
+                Ok(())
+            }
+            (false, Some(cookie)) => {
+                self.cookie = None;
+                self.backend.uninhibit(cookie)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Flip the state; returns whether idle is now inhibited
+    pub fn toggle(&mut self) -> Result<bool> {
+        self.set_active(!self.is_active())?;
+        Ok(self.is_active())
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+}
+
+/// `org.freedesktop.ScreenSaver` inhibit on the session bus. The service ties inhibits to the
+/// calling connection, so it stays open for as long as the backend lives.
+#[derive(Default)]
+pub struct ScreenSaverBackend {
+    conn: Option<Connection>,
+}
+
+impl ScreenSaverBackend {
+    fn call(
+        &mut self,
+        method: &str,
+        body: &(impl serde::Serialize + zbus::zvariant::DynamicType),
+    ) -> Result<zbus::Message> {
+        let conn = match &self.conn {
+            Some(conn) => conn,
+            None => self.conn.insert(Connection::session()?),
+        };
+        Ok(conn.call_method(
+            Some("org.freedesktop.ScreenSaver"),
+            "/org/freedesktop/ScreenSaver",
+            Some("org.freedesktop.ScreenSaver"),
+            method,
+            body,
+        )?)
+    }
+}
+
+impl InhibitBackend for ScreenSaverBackend {
+    fn inhibit(&mut self, reason: &str) -> Result<u32> {
+        let reply = self.call("Inhibit", &("niri-bar", reason))?;
+        Ok(reply.body().deserialize::<u32>()?)
+    }
+
+    fn uninhibit(&mut self, cookie: u32) -> Result<()> {
+        self.call("UnInhibit", &(cookie,))?;
+        Ok(())
+    }
+}
+
+/// One inhibitor per process, shared by the module on every bar, so the state survives
+/// config reloads that rebuild the widgets
+static INHIBITOR: Lazy<Mutex<IdleInhibitor<ScreenSaverBackend>>> =
+    Lazy::new(|| Mutex::new(IdleInhibitor::new(ScreenSaverBackend::default())));
+
+/// Inhibitor state as last toggled, sent to the module on every bar
+static IDLE_STATE: Mutex<StateBroadcast<bool>> = Mutex::new(StateBroadcast::new());
+
+/// CSS class for an inhibitor state (`None` when inactive)
+pub fn idle_css_class(active: bool) -> Option<&'static str> {
+    active.then_some(ACTIVE_CLASS)
+}
+
+/// Label text for an inhibitor state
+pub fn idle_text<'a>(active: bool, active_text: &'a str, inactive_text: &'a str) -> &'a str {
+    if active { active_text } else { inactive_text }
+}
+
+pub struct IdleInhibitModule;
+
+impl IdleInhibitModule {
+    pub const IDENT: &'static str = "bar.module.idle";
+
+    pub fn create_widget(settings: &ModuleConfig) -> gtk::Widget {
+        let active_text = settings
+            .additional
+            .get("active_text")
+            .and_then(|v| v.as_str())
+            .unwrap_or("☕")
+            .to_string();
+        let inactive_text = settings
+            .additional
+            .get("inactive_text")
+            .and_then(|v| v.as_str())
+            .unwrap_or("💤")
+            .to_string();
+
+        let label = gtk::Label::new(None);
+        label.add_css_class("module-idle");
+        label.set_hexpand(true);
+        label.set_halign(gtk::Align::Fill);
+
+        let apply = move |label: &gtk::Label, active: bool| {
+            label.set_text(idle_text(active, &active_text, &inactive_text));
+            match idle_css_class(active) {
+                Some(class) => label.add_css_class(class),
+                None => label.remove_css_class(ACTIVE_CLASS),
+            }
+            label.set_tooltip_text(Some(if active {
+                "Idle inhibitor: on"
+            } else {
+                "Idle inhibitor: off"
+            }));
+        };
+        // The inhibitor may be busy on D-Bus; start from the last published state instead
+        apply(
+            &label,
+            IDLE_STATE
+                .lock()
+                .unwrap()
+                .latest()
+                .copied()
+                .unwrap_or(false),
+        );
+
+        // Follow toggles made from the module on any bar
+        let label_weak = label.downgrade();
+        let updates = super::gtk_receiver(move |states: Vec<bool>| {
+            let Some(label) = label_weak.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            if let Some(active) = states.last() {
+                apply(&label, *active);
+            }
+            glib::ControlFlow::Continue
+        });
+        IDLE_STATE.lock().unwrap().subscribe(updates);
+
+        // Click to toggle the inhibitor; the D-Bus call runs off the GTK thread and every
+        // bar's label follows through IDLE_STATE
+        let click = gtk::GestureClick::new();
+        click.set_button(gtk::gdk::BUTTON_PRIMARY);
+        click.connect_pressed(move |gesture, _n_press, _x, _y| {
+            std::thread::spawn(|| {
+                let mut inhibitor = INHIBITOR.lock().unwrap();
+                match inhibitor.toggle() {
+                    Ok(true) => log::info!("Idle: ☕ inhibiting idle"),
+                    Ok(false) => log::info!("Idle: 💤 idle allowed again"),
+                    Err(e) => log::error!("Idle: failed to toggle idle inhibitor: {}", e),
+                }
+                let active = inhibitor.is_active();
+                drop(inhibitor);
+                IDLE_STATE.lock().unwrap().publish(active);
+            });
+            gesture.set_state(gtk::EventSequenceState::Claimed);
+        });
+        label.add_controller(click);
+
+        label.upcast()
+    }
+}
+
+impl BarModule for IdleInhibitModule {
+    fn id(&self) -> &'static str {
+        Self::IDENT
+    }
+    fn create(&self, settings: &ModuleConfig) -> gtk::Widget {
+        Self::create_widget(settings)
+    }
+}
@@ -0,0 +1,54 @@
+use gtk4 as gtk;
+use gtk4::prelude::*;
+
+use super::BarModule;
+use crate::config::ModuleConfig;
+use crate::niri::{NiriEvent, niri_bus};
+
+pub struct KeyboardModule;
+
+impl KeyboardModule {
+    pub const IDENT: &'static str = "bar.module.keyboard";
+
+    pub fn create_widget(_settings: &ModuleConfig) -> gtk::Widget {
+        let label = gtk::Label::new(None);
+        label.add_css_class("module-keyboard");
+        label.set_hexpand(true);
+        label.set_halign(gtk::Align::Fill);
+
+        // Empty until niri reports a layout the index actually points at
+        let apply = |label: &gtk::Label| {
+            let name = niri_bus()
+                .current_keyboard_layout_name()
+                .unwrap_or_default();
+            label.set_text(&name);
+        };
+        apply(&label);
+
+        // Update from KeyboardLayoutChanged events only
+        let label_weak = label.downgrade();
+        super::on_bus_events(move |events| {
+            let Some(label) = label_weak.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            if events
+                .iter()
+                .any(|ev| matches!(ev, NiriEvent::KeyboardLayoutChanged))
+            {
+                apply(&label);
+            }
+            glib::ControlFlow::Continue
+        });
+
+        label.upcast()
+    }
+}
+
+impl BarModule for KeyboardModule {
+    fn id(&self) -> &'static str {
+        Self::IDENT
+    }
+    fn create(&self, settings: &ModuleConfig) -> gtk::Widget {
+        Self::create_widget(settings)
+    }
+}
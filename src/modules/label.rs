@@ -0,0 +1,145 @@
+use gtk4 as gtk;
+use gtk4::prelude::*;
+
+use super::BarModule;
+use crate::config::ModuleConfig;
+use crate::niri::{NiriBus, NiriEvent, WindowInfo, niri_bus};
+
+/// Placeholder values for the label, read from the niri bus
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LabelValues {
+    /// Focused window title
+    pub title: String,
+    /// Focused workspace name, or its idx when unnamed
+    pub workspace: String,
+    /// Windows on the focused workspace
+    pub window_count: usize,
+    /// How the focused window is laid out, see [`window_layout_name`]
+    pub layout: String,
+    /// Current keyboard layout name
+    pub keyboard: String,
+}
+
+impl LabelValues {
+    /// Current values from `bus`; empty (and a zero count) for whatever niri hasn't reported
+    pub fn from_bus(bus: &NiriBus) -> Self {
+        let workspace = bus.workspaces_snapshot().into_iter().find(|w| w.is_focused);
+        Self {
+            title: bus.current_title(),
+            workspace: workspace
+                .as_ref()
+                .map(|w| w.name.clone().unwrap_or_else(|| w.idx.to_string()))
+                .unwrap_or_default(),
+            window_count: workspace
+                .map(|w| bus.window_count_for_workspace(w.id))
+                .unwrap_or(0),
+            layout: bus
+                .focused_window()
+                .map(|w| window_layout_name(&w).to_string())
+                .unwrap_or_default(),
+            keyboard: bus.current_keyboard_layout_name().unwrap_or_default(),
+        }
+    }
+
+    /// `(placeholder, value)` pairs for `format` and `tooltip_format`
+    pub fn placeholders(&self) -> [(&'static str, String); 5] {
+        [
+            ("title", self.title.clone()),
+            ("workspace", self.workspace.clone()),
+            ("window_count", self.window_count.to_string()),
+            ("layout", self.layout.clone()),
+            ("keyboard", self.keyboard.clone()),
+        ]
+    }
+}
+
+/// `fullscreen`, `maximized`, `floating` or `tiled`, first match wins
+pub fn window_layout_name(window: &WindowInfo) -> &'static str {
+    if window.is_fullscreen {
+        "fullscreen"
+    } else if window.is_maximized {
+        "maximized"
+    } else if window.is_floating {
+        "floating"
+    } else {
+        "tiled"
+    }
+}
+
+/// Expand `{title}`, `{workspace}`, `{window_count}`, `{layout}` and `{keyboard}` in `fmt`;
+/// other text is kept as written
+pub fn render_label(fmt: &str, values: &LabelValues) -> String {
+    values
+        .placeholders()
+        .iter()
+        .fold(fmt.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        })
+}
+
+/// Whether a bus event can change a label placeholder
+pub fn label_event_is_relevant(event: &NiriEvent) -> bool {
+    match event {
+        NiriEvent::WindowsChanged
+        | NiriEvent::FocusChanged(_)
+        | NiriEvent::WorkspacesChanged
+        | NiriEvent::KeyboardLayoutChanged
+        | NiriEvent::Synchronized => true,
+        NiriEvent::OverviewToggled(_) | NiriEvent::WindowLayoutsChanged => false,
+    }
+}
+
+pub struct LabelModule;
+
+impl LabelModule {
+    pub const IDENT: &'static str = "bar.module.label";
+
+    pub fn create_widget(settings: &ModuleConfig) -> gtk::Widget {
+        let fmt = settings
+            .format
+            .clone()
+            .unwrap_or_else(|| "{workspace}: {title}".to_string());
+        let tooltip_format = super::tooltip_template(settings);
+
+        let label = gtk::Label::new(None);
+        label.add_css_class("module-label");
+        label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+        label.set_hexpand(true);
+        label.set_halign(gtk::Align::Fill);
+
+        let apply = move |label: &gtk::Label| {
+            let values = LabelValues::from_bus(&niri_bus());
+            label.set_text(&render_label(&fmt, &values));
+            let placeholders = values.placeholders();
+            let pairs: Vec<(&str, &str)> = placeholders
+                .iter()
+                .map(|(name, value)| (*name, value.as_str()))
+                .collect();
+            super::apply_tooltip(label, tooltip_format.as_deref(), &pairs);
+        };
+        apply(&label);
+
+        // Redraw once per batch of bus events that touches a placeholder
+        let label_weak = label.downgrade();
+        super::on_bus_events(move |events| {
+            let Some(label) = label_weak.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            if events.iter().any(label_event_is_relevant) {
+                apply(&label);
+            }
+            glib::ControlFlow::Continue
+        });
+
+        label.upcast()
+    }
+}
+
+impl BarModule for LabelModule {
+    fn id(&self) -> &'static str {
+        Self::IDENT
+    }
+    fn create(&self, settings: &ModuleConfig) -> gtk::Widget {
+        Self::create_widget(settings)
+    }
+}
@@ -0,0 +1,165 @@
+use gtk4 as gtk;
+use gtk4::prelude::*;
+
+use super::BarModule;
+use crate::config::ModuleConfig;
+
+/// Memory figures parsed from `/proc/meminfo` (values in KiB)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemInfo {
+    pub total_kib: u64,
+    pub available_kib: u64,
+}
+
+impl MemInfo {
+    pub fn used_kib(&self) -> u64 {
+        self.total_kib.saturating_sub(self.available_kib)
+    }
+
+    /// Used memory as a whole percentage of total (0 when total is unknown)
+    pub fn percent(&self) -> u8 {
+        if self.total_kib == 0 {
+            return 0;
+        }
+        ((self.used_kib() as f64 / self.total_kib as f64) * 100.0).round() as u8
+    }
+}
+
+/// Parse `/proc/meminfo` text. `MemTotal` is required; `MemAvailable` is preferred,
+/// falling back to `MemFree + Buffers + Cached` on kernels that don't report it.
+pub fn parse_meminfo(text: &str) -> Option<MemInfo> {
+    let mut total = None;
+    let mut available = None;
+    let mut free = None;
+    let mut buffers = 0;
+    let mut cached = 0;
+    for line in text.lines() {
+        let Some((key, rest)) = line.split_once(':') else {
+            continue;
+        };
+        let Some(value) = rest
+            .split_whitespace()
+            .next()
+            .and_then(|v| v.parse::<u64>().ok())
+        else {
+            continue;
+        };
+        match key.trim() {
+            "MemTotal" => total = Some(value),
+            "MemAvailable" => available = Some(value),
+            "MemFree" => free = Some(value),
+            "Buffers" => buffers = value,
+            "Cached" => cached = value,
+            _ => {}
+        }
+    }
+    let total_kib = total?;
+    let available_kib = available.or_else(|| free.map(|f| f + buffers + cached))?;
+    Some(MemInfo {
+        total_kib,
+        available_kib: available_kib.min(total_kib),
+    })
+}
+
+/// Human-readable size from KiB using binary units, e.g. `8388608` -> `"8.0G"`
+pub fn format_kib(kib: u64) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+    let mut value = kib as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{}{}", kib, UNITS[0])
+    } else {
+        format!("{:.1}{}", value, UNITS[unit])
+    }
+}
+
+/// Expand `{used}`, `{total}` and `{percent}` in a format string
+pub fn render_mem_format(fmt: &str, info: &MemInfo, show_percentage: bool) -> String {
+    let mut text = fmt
+        .replace("{used}", &format_kib(info.used_kib()))
+        .replace("{total}", &format_kib(info.total_kib))
+        .replace("{percent}", &info.percent().to_string());
+    if show_percentage && !fmt.contains("{percent}") {
+        text.push_str(&format!(" ({}%)", info.percent()));
+    }
+    text
+}
+
+pub struct MemModule;
+
+impl MemModule {
+    pub const IDENT: &'static str = "bar.module.mem";
+
+    pub fn create_widget(settings: &ModuleConfig) -> gtk::Widget {
+        let fmt = settings
+            .format
+            .clone()
+            .unwrap_or_else(|| "{used}/{total}".to_string());
+        let show_percentage = settings.show_percentage.unwrap_or(false);
+        let warn = settings.warn_threshold.unwrap_or(80);
+        let crit = settings.critical_threshold.unwrap_or(90);
+        let interval_ms = settings
+            .additional
+            .get("interval_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(2000)
+            .max(100);
+
+        let label = gtk::Label::new(None);
+        label.add_css_class("module-mem");
+        label.set_hexpand(true);
+        label.set_halign(gtk::Align::Fill);
+
+        update_mem_label(&label, &fmt, show_percentage, warn, crit);
+
+        let label_weak = label.downgrade();
+        super::subscribe_refresh(std::time::Duration::from_millis(interval_ms), move || {
+            if let Some(label) = label_weak.upgrade() {
+                update_mem_label(&label, &fmt, show_percentage, warn, crit);
+                glib::ControlFlow::Continue
+            } else {
+                glib::ControlFlow::Break
+            }
+        });
+
+        label.upcast()
+    }
+}
+
+impl BarModule for MemModule {
+    fn id(&self) -> &'static str {
+        Self::IDENT
+    }
+    fn create(&self, settings: &ModuleConfig) -> gtk::Widget {
+        Self::create_widget(settings)
+    }
+}
+
+fn update_mem_label(label: &gtk::Label, fmt: &str, show_percentage: bool, warn: u8, crit: u8) {
+    let info = std::fs::read_to_string("/proc/meminfo")
+        .ok()
+        .and_then(|s| parse_meminfo(&s));
+    let Some(info) = info else {
+        log::warn!("Mem: failed to read /proc/meminfo");
+        label.set_text("");
+        return;
+    };
+
+    label.set_text(&render_mem_format(fmt, &info, show_percentage));
+
+    let pct = info.percent();
+    label.remove_css_class("mem-ok");
+    label.remove_css_class("mem-warn");
+    label.remove_css_class("mem-crit");
+    if pct >= crit {
+        label.add_css_class("mem-crit");
+    } else if pct >= warn {
+        label.add_css_class("mem-warn");
+    } else {
+        label.add_css_class("mem-ok");
+    }
+}
@@ -1,11 +1,24 @@
 use gtk4 as gtk;
+use gtk4::prelude::*;
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
 
 pub mod battery;
+pub mod brightness;
 pub mod clock;
+pub mod idle;
+pub mod keyboard;
+pub mod label;
+pub mod mem;
+pub mod notifications;
+pub mod overview;
+pub mod power_profile;
+pub mod script;
+pub mod separator;
+pub mod temp;
 pub mod tray;
 pub mod viewport;
+pub mod volume;
 pub mod wallpaper;
 pub mod window_title;
 pub mod workspaces;
@@ -45,6 +58,45 @@ static REGISTRY: Lazy<HashMap<&'static str, FactoryFn>> = Lazy::new(|| {
         viewport::ViewportModule::IDENT,
         viewport::ViewportModule::create_widget,
     );
+    m.insert(mem::MemModule::IDENT, mem::MemModule::create_widget);
+    m.insert(
+        volume::VolumeModule::IDENT,
+        volume::VolumeModule::create_widget,
+    );
+    m.insert(
+        brightness::BrightnessModule::IDENT,
+        brightness::BrightnessModule::create_widget,
+    );
+    m.insert(temp::TempModule::IDENT, temp::TempModule::create_widget);
+    m.insert(
+        script::ScriptModule::IDENT,
+        script::ScriptModule::create_widget,
+    );
+    m.insert(
+        overview::OverviewModule::IDENT,
+        overview::OverviewModule::create_widget,
+    );
+    m.insert(
+        keyboard::KeyboardModule::IDENT,
+        keyboard::KeyboardModule::create_widget,
+    );
+    m.insert(
+        idle::IdleInhibitModule::IDENT,
+        idle::IdleInhibitModule::create_widget,
+    );
+    m.insert(
+        notifications::NotificationsModule::IDENT,
+        notifications::NotificationsModule::create_widget,
+    );
+    m.insert(
+        power_profile::PowerProfileModule::IDENT,
+        power_profile::PowerProfileModule::create_widget,
+    );
+    m.insert(label::LabelModule::IDENT, label::LabelModule::create_widget);
+    m.insert(
+        separator::SeparatorModule::IDENT,
+        separator::SeparatorModule::create_widget,
+    );
     m
 });
 
@@ -53,6 +105,21 @@ fn resolve_identifier(name: &str) -> String {
     format!("bar.module.{}", name)
 }
 
+/// Whether `name` (as written in a layout, e.g. "clock") is a registered module
+pub fn is_known_module(name: &str) -> bool {
+    REGISTRY.contains_key(resolve_identifier(name).as_str())
+}
+
+/// Whether `name`'s `interval_ms` is served by the shared ticks (see [`tick_schedule`])
+pub fn refreshes_on_shared_ticks(name: &str) -> bool {
+    [
+        battery::BatteryModule::IDENT,
+        mem::MemModule::IDENT,
+        temp::TempModule::IDENT,
+    ]
+    .contains(&resolve_identifier(name).as_str())
+}
+
 /// Create a module widget dynamically based on the YAML module name and merged settings.
 ///
 /// # Examples
@@ -74,7 +141,485 @@ pub fn create_module_widget(
     settings: &crate::config::ModuleConfig,
 ) -> Option<gtk::Widget> {
     let ident = resolve_identifier(module_name);
-    REGISTRY
-        .get(ident.as_str())
-        .map(|factory| factory(settings))
+    REGISTRY.get(ident.as_str()).map(|factory| {
+        let widget = factory(settings);
+        attach_click_actions(&widget, settings);
+        attach_scroll_actions(&widget, settings);
+        widget
+    })
+}
+
+/// Configured `tooltip_format`, unless empty or tooltips are off (`tooltip: false`)
+pub fn tooltip_template(settings: &crate::config::ModuleConfig) -> Option<String> {
+    if !settings.tooltip.unwrap_or(true) {
+        return None;
+    }
+    settings
+        .tooltip_format
+        .clone()
+        .filter(|template| !template.trim().is_empty())
+}
+
+/// Escape text for Pango markup
+pub fn escape_markup(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Replace `{name}` placeholders in a markup template with markup-escaped values; unknown
+/// placeholders are left as written
+pub fn fill_placeholders(template: &str, values: &[(&str, &str)]) -> String {
+    values
+        .iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), &escape_markup(value))
+        })
+}
+
+/// Set `widget`'s tooltip markup from `template` filled with `values`; no-op without a template
+pub fn apply_tooltip(
+    widget: &impl IsA<gtk::Widget>,
+    template: Option<&str>,
+    values: &[(&str, &str)],
+) {
+    if let Some(template) = template {
+        widget.set_tooltip_markup(Some(&fill_placeholders(template, values)));
+    }
+}
+
+/// Mouse button a click action is bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClickButton {
+    Left,
+    Middle,
+    Right,
+}
+
+impl ClickButton {
+    /// Map a GDK button number to a click button
+    pub fn from_gdk(button: u32) -> Option<Self> {
+        match button {
+            gtk::gdk::BUTTON_PRIMARY => Some(Self::Left),
+            gtk::gdk::BUTTON_MIDDLE => Some(Self::Middle),
+            gtk::gdk::BUTTON_SECONDARY => Some(Self::Right),
+            _ => None,
+        }
+    }
+}
+
+/// Command configured for a button (`on_click`, `on_click_middle`, `on_click_right`)
+pub fn click_command(settings: &crate::config::ModuleConfig, button: ClickButton) -> Option<&str> {
+    match button {
+        ClickButton::Left => settings.on_click.as_deref(),
+        ClickButton::Middle => settings.on_click_middle.as_deref(),
+        ClickButton::Right => settings.on_click_right.as_deref(),
+    }
+    .map(str::trim)
+    .filter(|c| !c.is_empty())
+}
+
+/// Starts click commands without waiting for them; swapped for a fake in tests
+pub trait ClickRunner {
+    fn spawn(&self, command: &str);
+}
+
+/// Spawns commands through `sh -c`, reaping them off the GTK thread
+pub struct ShellClickRunner;
+
+impl ClickRunner for ShellClickRunner {
+    fn spawn(&self, command: &str) {
+        match std::process::Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .spawn()
+        {
+            Ok(mut child) => {
+                std::thread::spawn(move || {
+                    let _ = child.wait();
+                });
+            }
+            Err(e) => log::error!("Modules: failed to run click command '{}': {}", command, e),
+        }
+    }
+}
+
+/// Run the command bound to `button`, if any; returns whether one was started
+pub fn dispatch_click<R: ClickRunner>(
+    runner: &R,
+    settings: &crate::config::ModuleConfig,
+    button: ClickButton,
+) -> bool {
+    let Some(command) = click_command(settings, button) else {
+        return false;
+    };
+    log::debug!("Modules: 🖱️ {:?} click → '{}'", button, command);
+    runner.spawn(command);
+    true
+}
+
+/// Default minimum time between handled scroll events
+pub const DEFAULT_SCROLL_THROTTLE_MS: u64 = 50;
+
+/// Configured `scroll_throttle_ms` (default 50)
+pub fn scroll_throttle_ms(settings: &crate::config::ModuleConfig) -> u64 {
+    settings
+        .additional
+        .get("scroll_throttle_ms")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_SCROLL_THROTTLE_MS)
+}
+
+/// Gate for scroll events: ignores negligible movement and anything arriving within
+/// the throttle window after the last accepted event
+#[derive(Debug, Clone)]
+pub struct ScrollThrottle {
+    interval: std::time::Duration,
+    last: Option<std::time::Instant>,
+}
+
+impl ScrollThrottle {
+    pub fn new(throttle_ms: u64) -> Self {
+        Self {
+            interval: std::time::Duration::from_millis(throttle_ms),
+            last: None,
+        }
+    }
+
+    /// Whether a scroll of `dy` at `now` should be handled; accepted events restart the window
+    pub fn accept(&mut self, dy: f64, now: std::time::Instant) -> bool {
+        if dy.abs() <= 0.1 {
+            return false;
+        }
+        if let Some(last) = self.last
+            && now.saturating_duration_since(last) < self.interval
+        {
+            return false;
+        }
+        self.last = Some(now);
+        true
+    }
+}
+
+/// Command configured for a scroll direction (`on_scroll_up` for negative `dy`)
+pub fn scroll_command(settings: &crate::config::ModuleConfig, dy: f64) -> Option<&str> {
+    if dy < 0.0 {
+        settings.on_scroll_up.as_deref()
+    } else {
+        settings.on_scroll_down.as_deref()
+    }
+    .map(str::trim)
+    .filter(|c| !c.is_empty())
+}
+
+/// Run the command bound to the scroll direction, if any; returns whether one was started
+pub fn dispatch_scroll<R: ClickRunner>(
+    runner: &R,
+    settings: &crate::config::ModuleConfig,
+    dy: f64,
+) -> bool {
+    let Some(command) = scroll_command(settings, dy) else {
+        return false;
+    };
+    log::debug!("Modules: 🛞 scroll dy={:.3} → '{}'", dy, command);
+    runner.spawn(command);
+    true
+}
+
+/// Attach a scroll controller running the module's `on_scroll_*` commands; no-op when none are set
+fn attach_scroll_actions(widget: &gtk::Widget, settings: &crate::config::ModuleConfig) {
+    if scroll_command(settings, -1.0).is_none() && scroll_command(settings, 1.0).is_none() {
+        return;
+    }
+    // Replace the module's own scroll handling (volume, clock zones, workspaces) rather than
+    // stacking a second controller that would act on the same scroll
+    let existing: Vec<gtk::EventControllerScroll> = widget
+        .observe_controllers()
+        .iter::<gtk::glib::Object>()
+        .filter_map(|c| c.ok()?.downcast::<gtk::EventControllerScroll>().ok())
+        .collect();
+    for controller in &existing {
+        widget.remove_controller(controller);
+    }
+    let throttle = std::cell::RefCell::new(ScrollThrottle::new(scroll_throttle_ms(settings)));
+    let settings = settings.clone();
+    let scroll = gtk::EventControllerScroll::new(
+        gtk::EventControllerScrollFlags::VERTICAL | gtk::EventControllerScrollFlags::DISCRETE,
+    );
+    scroll.connect_scroll(move |_, _dx, dy| {
+        if throttle.borrow_mut().accept(dy, std::time::Instant::now())
+            && dispatch_scroll(&ShellClickRunner, &settings, dy)
+        {
+            return gtk::glib::Propagation::Stop;
+        }
+        gtk::glib::Propagation::Proceed
+    });
+    widget.add_controller(scroll);
+}
+
+/// Attach a click gesture running the module's `on_click*` commands; no-op when none are set
+fn attach_click_actions(widget: &gtk::Widget, settings: &crate::config::ModuleConfig) {
+    let buttons = [ClickButton::Left, ClickButton::Middle, ClickButton::Right];
+    if !buttons
+        .iter()
+        .any(|b| click_command(settings, *b).is_some())
+    {
+        return;
+    }
+    let settings = settings.clone();
+    let click = gtk::GestureClick::new();
+    // Listen on all buttons and pick the command in the handler. Run in the capture phase so
+    // a button with a command is claimed here and never reaches the module's own click
+    // handling (a `gtk::Button`'s `clicked`, a popover gesture); other buttons pass through.
+    click.set_button(0);
+    click.set_propagation_phase(gtk::PropagationPhase::Capture);
+    click.connect_pressed(move |gesture, _n_press, _x, _y| {
+        let Some(button) = ClickButton::from_gdk(gesture.current_button()) else {
+            return;
+        };
+        if dispatch_click(&ShellClickRunner, &settings, button) {
+            gesture.set_state(gtk::EventSequenceState::Claimed);
+        }
+    });
+    widget.add_controller(click);
+    widget.set_cursor_from_name(Some("pointer"));
+}
+
+/// Ticks shared by every periodic module refresh, fastest first
+pub const SHARED_TICKS: [std::time::Duration; 3] = [
+    std::time::Duration::from_secs(1),
+    std::time::Duration::from_secs(5),
+    std::time::Duration::from_secs(30),
+];
+
+/// Where a requested refresh interval lands: every `every`-th firing of the shared `tick`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TickSchedule {
+    pub tick: std::time::Duration,
+    pub every: u32,
+}
+
+impl TickSchedule {
+    /// Actual refresh interval
+    pub fn interval(&self) -> std::time::Duration {
+        self.tick * self.every
+    }
+}
+
+/// Schedule for a requested `interval`: rounded to whole seconds (at least one), on the
+/// slowest shared tick that divides it evenly
+pub fn tick_schedule(interval: std::time::Duration) -> TickSchedule {
+    let secs = ((interval.as_millis() + 500) / 1000).clamp(1, u32::MAX as u128) as u32;
+    let tick = SHARED_TICKS
+        .iter()
+        .rev()
+        .copied()
+        .find(|tick| secs.is_multiple_of(tick.as_secs() as u32))
+        .unwrap_or(SHARED_TICKS[0]);
+    TickSchedule {
+        tick,
+        every: secs / tick.as_secs() as u32,
+    }
+}
+
+type TickCallback = Box<dyn FnMut() -> glib::ControlFlow>;
+
+struct TickSubscriber {
+    every: u32,
+    elapsed: u32,
+    callback: TickCallback,
+}
+
+/// Run the subscribers due on this firing; those returning `Break` are dropped
+fn run_due(subscribers: &mut Vec<TickSubscriber>) {
+    subscribers.retain_mut(|sub| {
+        sub.elapsed += 1;
+        if sub.elapsed < sub.every {
+            return true;
+        }
+        sub.elapsed = 0;
+        (sub.callback)() == glib::ControlFlow::Continue
+    });
+}
+
+/// Module refreshes multiplexed onto [`SHARED_TICKS`], so modules refreshing at the same
+/// cadence share one timer instead of each spawning their own
+#[derive(Default)]
+pub struct TimerHub {
+    buckets: [Vec<TickSubscriber>; SHARED_TICKS.len()],
+    running: [bool; SHARED_TICKS.len()],
+}
+
+impl TimerHub {
+    /// Add `callback` on the tick serving `interval`; it's dropped once it returns `Break`
+    pub fn subscribe(
+        &mut self,
+        interval: std::time::Duration,
+        callback: impl FnMut() -> glib::ControlFlow + 'static,
+    ) -> TickSchedule {
+        let schedule = tick_schedule(interval);
+        self.buckets[Self::bucket(schedule.tick)].push(TickSubscriber {
+            every: schedule.every,
+            elapsed: 0,
+            callback: Box::new(callback),
+        });
+        schedule
+    }
+
+    /// One firing of `tick`: runs the subscribers that are due, returns how many remain
+    pub fn fire(&mut self, tick: std::time::Duration) -> usize {
+        let bucket = &mut self.buckets[Self::bucket(tick)];
+        run_due(bucket);
+        bucket.len()
+    }
+
+    /// Subscribers currently on `tick`
+    pub fn subscribers(&self, tick: std::time::Duration) -> usize {
+        self.buckets[Self::bucket(tick)].len()
+    }
+
+    fn bucket(tick: std::time::Duration) -> usize {
+        SHARED_TICKS
+            .iter()
+            .position(|t| *t == tick)
+            .expect("not a shared tick")
+    }
+}
+
+thread_local! {
+    /// Module widgets live on the GTK thread, so the shared hub does too
+    static TIMER_HUB: std::cell::RefCell<TimerHub> = std::cell::RefCell::new(TimerHub::default());
+}
+
+/// Refresh with `callback` every `interval` on the shared ticks, until it returns `Break`
+/// (typically once its widget is gone). The tick's GLib timer starts with its first
+/// subscriber and stops when the last one leaves.
+pub fn subscribe_refresh(
+    interval: std::time::Duration,
+    callback: impl FnMut() -> glib::ControlFlow + 'static,
+) -> TickSchedule {
+    TIMER_HUB.with_borrow_mut(|hub| {
+        let schedule = hub.subscribe(interval, callback);
+        let bucket = TimerHub::bucket(schedule.tick);
+        if !hub.running[bucket] {
+            hub.running[bucket] = true;
+            log::debug!("Modules: ⏱️ starting shared {:?} tick", schedule.tick);
+            glib::timeout_add_local(schedule.tick, move || run_shared_tick(bucket));
+        }
+        schedule
+    })
+}
+
+fn run_shared_tick(bucket: usize) -> glib::ControlFlow {
+    // Run outside the borrow so callbacks may subscribe again
+    let mut due = TIMER_HUB.with_borrow_mut(|hub| std::mem::take(&mut hub.buckets[bucket]));
+    run_due(&mut due);
+    TIMER_HUB.with_borrow_mut(|hub| {
+        due.append(&mut hub.buckets[bucket]);
+        hub.buckets[bucket] = due;
+        if hub.buckets[bucket].is_empty() {
+            hub.running[bucket] = false;
+            log::debug!(
+                "Modules: ⏱️ stopping shared {:?} tick",
+                SHARED_TICKS[bucket]
+            );
+            glib::ControlFlow::Break
+        } else {
+            glib::ControlFlow::Continue
+        }
+    })
+}
+
+/// Sender whose values reach `handle` on the GTK thread, one batch per wakeup, until it
+/// returns `Break` (typically once its widget is gone). Nothing runs while nothing is sent,
+/// unlike draining a channel on a timer.
+pub fn gtk_receiver<T: 'static>(
+    mut handle: impl FnMut(Vec<T>) -> glib::ControlFlow + 'static,
+) -> tokio::sync::mpsc::UnboundedSender<T> {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<T>();
+    glib::spawn_future_local(async move {
+        while let Some(first) = rx.recv().await {
+            let mut batch = vec![first];
+            while let Ok(value) = rx.try_recv() {
+                batch.push(value);
+            }
+            if handle(batch) == glib::ControlFlow::Break {
+                break;
+            }
+        }
+    });
+    tx
+}
+
+/// [`gtk_receiver`] fed with niri bus events. The bus talks to std channels, so a thread
+/// relays them; it exits with the first event after `handle` returned `Break`.
+pub fn on_bus_events(
+    handle: impl FnMut(Vec<crate::niri::NiriEvent>) -> glib::ControlFlow + 'static,
+) {
+    let tx = gtk_receiver(handle);
+    let (bus_tx, bus_rx) = std::sync::mpsc::channel();
+    crate::niri::niri_bus().register_typed_listener(bus_tx);
+    std::thread::spawn(move || {
+        for event in bus_rx {
+            if tx.send(event).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Latest value of some process-wide state plus the widgets following it, e.g. through
+/// [`gtk_receiver`] senders. Senders whose widget is gone drop out on the next publish.
+pub struct StateBroadcast<T> {
+    latest: Option<T>,
+    subscribers: Vec<tokio::sync::mpsc::UnboundedSender<T>>,
+}
+
+impl<T: Clone> StateBroadcast<T> {
+    pub const fn new() -> Self {
+        Self {
+            latest: None,
+            subscribers: Vec::new(),
+        }
+    }
+
+    /// Follow the state; the latest value, when there is one, is sent right away
+    pub fn subscribe(&mut self, tx: tokio::sync::mpsc::UnboundedSender<T>) {
+        if let Some(value) = &self.latest
+            && tx.send(value.clone()).is_err()
+        {
+            return;
+        }
+        self.subscribers.push(tx);
+    }
+
+    /// Store `value` and send it to every subscriber still listening
+    pub fn publish(&mut self, value: T) {
+        self.subscribers.retain(|tx| tx.send(value.clone()).is_ok());
+        self.latest = Some(value);
+    }
+
+    pub fn latest(&self) -> Option<&T> {
+        self.latest.as_ref()
+    }
+
+    /// Subscribers as of the last publish
+    pub fn subscribers(&self) -> usize {
+        self.subscribers.len()
+    }
+}
+
+impl<T: Clone> Default for StateBroadcast<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
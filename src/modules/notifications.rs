@@ -0,0 +1,420 @@
+use anyhow::{Result, bail};
+use gtk4 as gtk;
+use gtk4::prelude::*;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedSender;
+use zbus::blocking::{Connection, Proxy};
+use zbus::proxy::CacheProperties;
+use zbus::zvariant::OwnedValue;
+
+use super::BarModule;
+use crate::config::ModuleConfig;
+
+/// CSS class applied while Do-Not-Disturb is on
+pub const DND_CLASS: &str = "dnd";
+/// Mako mode toggled for Do-Not-Disturb unless `dnd_mode` says otherwise
+pub const DEFAULT_MAKO_DND_MODE: &str = "do-not-disturb";
+/// Badge shown instead of counts above this
+const MAX_BADGE_COUNT: u32 = 99;
+
+const NOTIFICATIONS_BUS: &str = "org.freedesktop.Notifications";
+/// First and longest wait between attempts to reach a daemon that isn't running yet
+const DAEMON_RETRY_MIN: Duration = Duration::from_secs(1);
+const DAEMON_RETRY_MAX: Duration = Duration::from_secs(30);
+
+/// Pending notification count and Do-Not-Disturb state of the notification daemon
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NotificationState {
+    pub count: u32,
+    pub dnd: bool,
+}
+
+/// D-Bus call that flips Do-Not-Disturb
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DndAction {
+    /// mako: `fr.emersion.Mako.SetModes` with the full new mode list
+    SetModes(Vec<String>),
+    /// dunst: set the `org.dunstproject.cmd0.paused` property
+    SetPaused(bool),
+}
+
+/// Notification daemon the module talks to; mako and dunst differ in how they expose DND
+pub trait NotificationBackend {
+    fn name(&self) -> &'static str;
+    /// Read the current count and DND state
+    fn refresh(&mut self) -> Result<NotificationState>;
+    /// Call that flips DND, based on the state last read by [`Self::refresh`]
+    fn toggle_action(&self) -> DndAction;
+    /// Run a call built by [`Self::toggle_action`]
+    fn apply(&mut self, action: &DndAction) -> Result<()>;
+}
+
+/// Flip DND on `backend`, re-reading its state first so the action isn't stale
+pub fn toggle_dnd(backend: &mut dyn NotificationBackend) -> Result<NotificationState> {
+    backend.refresh()?;
+    let action = backend.toggle_action();
+    backend.apply(&action)?;
+    backend.refresh()
+}
+
+/// Daemons the module supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DaemonKind {
+    Mako,
+    Dunst,
+}
+
+impl DaemonKind {
+    /// Daemon named by the `backend` option or by `GetServerInformation` (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "mako" => Some(Self::Mako),
+            "dunst" => Some(Self::Dunst),
+            _ => None,
+        }
+    }
+}
+
+/// mako state: every listed notification counts; DND is on while `dnd_mode` is active
+pub fn mako_state(notifications: usize, modes: &[String], dnd_mode: &str) -> NotificationState {
+    NotificationState {
+        count: notifications as u32,
+        dnd: modes.iter().any(|mode| mode == dnd_mode),
+    }
+}
+
+/// mako toggle: drop `dnd_mode` from the active modes, or append it, keeping other modes
+pub fn mako_toggle_action(modes: &[String], dnd_mode: &str) -> DndAction {
+    let mut next: Vec<String> = modes
+        .iter()
+        .filter(|mode| *mode != dnd_mode)
+        .cloned()
+        .collect();
+    if next.len() == modes.len() {
+        next.push(dnd_mode.to_string());
+    }
+    DndAction::SetModes(next)
+}
+
+/// dunst state: displayed plus waiting (held back while paused) notifications
+pub fn dunst_state(displayed: u32, waiting: u32, paused: bool) -> NotificationState {
+    NotificationState {
+        count: displayed.saturating_add(waiting),
+        dnd: paused,
+    }
+}
+
+/// dunst toggle: flip `paused`
+pub fn dunst_toggle_action(paused: bool) -> DndAction {
+    DndAction::SetPaused(!paused)
+}
+
+/// Count badge text; None when there is nothing to show
+pub fn badge_text(count: u32) -> Option<String> {
+    match count {
+        0 => None,
+        n if n > MAX_BADGE_COUNT => Some(format!("{}+", MAX_BADGE_COUNT)),
+        n => Some(n.to_string()),
+    }
+}
+
+fn notifications_proxy<'a>(conn: &Connection, path: &'a str, iface: &'a str) -> Result<Proxy<'a>> {
+    Ok(zbus::blocking::proxy::Builder::new(conn)
+        .destination(NOTIFICATIONS_BUS)?
+        .path(path)?
+        .interface(iface)?
+        .cache_properties(CacheProperties::No)
+        .build()?)
+}
+
+/// mako over `fr.emersion.Mako`
+pub struct MakoBackend {
+    conn: Connection,
+    dnd_mode: String,
+    modes: Vec<String>,
+}
+
+impl MakoBackend {
+    pub fn new(conn: Connection, dnd_mode: String) -> Self {
+        Self {
+            conn,
+            dnd_mode,
+            modes: Vec::new(),
+        }
+    }
+
+    fn proxy(&self) -> Result<Proxy<'_>> {
+        notifications_proxy(&self.conn, "/fr/emersion/Mako", "fr.emersion.Mako")
+    }
+}
+
+impl NotificationBackend for MakoBackend {
+    fn name(&self) -> &'static str {
+        "mako"
+    }
+
+    fn refresh(&mut self) -> Result<NotificationState> {
+        let proxy = self.proxy()?;
+        let notifications: Vec<HashMap<String, OwnedValue>> =
+            proxy.call("ListNotifications", &())?;
+        let modes: Vec<String> = proxy.call("ListModes", &())?;
+        let state = mako_state(notifications.len(), &modes, &self.dnd_mode);
+        self.modes = modes;
+        Ok(state)
+    }
+
+    fn toggle_action(&self) -> DndAction {
+        mako_toggle_action(&self.modes, &self.dnd_mode)
+    }
+
+    fn apply(&mut self, action: &DndAction) -> Result<()> {
+        let DndAction::SetModes(modes) = action else {
+            bail!("mako can't apply {:?}", action);
+        };
+        self.proxy()?.call_method("SetModes", &(modes,))?;
+        Ok(())
+    }
+}
+
+/// dunst over `org.dunstproject.cmd0`
+pub struct DunstBackend {
+    conn: Connection,
+    paused: bool,
+}
+
+impl DunstBackend {
+    pub fn new(conn: Connection) -> Self {
+        Self {
+            conn,
+            paused: false,
+        }
+    }
+
+    fn proxy(&self) -> Result<Proxy<'_>> {
+        notifications_proxy(
+            &self.conn,
+            "/org/freedesktop/Notifications",
+            "org.dunstproject.cmd0",
+        )
+    }
+}
+
+impl NotificationBackend for DunstBackend {
+    fn name(&self) -> &'static str {
+        "dunst"
+    }
+
+    fn refresh(&mut self) -> Result<NotificationState> {
+        let proxy = self.proxy()?;
+        let displayed = proxy.get_property::<u32>("displayedLength")?;
+        let waiting = proxy.get_property::<u32>("waitingLength")?;
+        self.paused = proxy.get_property::<bool>("paused")?;
+        Ok(dunst_state(displayed, waiting, self.paused))
+    }
+
+    fn toggle_action(&self) -> DndAction {
+        dunst_toggle_action(self.paused)
+    }
+
+    fn apply(&mut self, action: &DndAction) -> Result<()> {
+        let DndAction::SetPaused(paused) = action else {
+            bail!("dunst can't apply {:?}", action);
+        };
+        self.proxy()?.set_property("paused", *paused)?;
+        Ok(())
+    }
+}
+
+/// Ask the running daemon for its name via `GetServerInformation`
+fn detect_daemon(conn: &Connection) -> Result<DaemonKind> {
+    let proxy = notifications_proxy(conn, "/org/freedesktop/Notifications", NOTIFICATIONS_BUS)?;
+    let (name, _vendor, _version, _spec): (String, String, String, String) =
+        proxy.call("GetServerInformation", &())?;
+    match DaemonKind::from_name(&name) {
+        Some(kind) => Ok(kind),
+        None => bail!("unsupported notification daemon '{}'", name),
+    }
+}
+
+fn connect_backend(
+    configured: Option<DaemonKind>,
+    dnd_mode: String,
+) -> Result<Box<dyn NotificationBackend>> {
+    let conn = Connection::session()?;
+    let kind = match configured {
+        Some(kind) => kind,
+        None => detect_daemon(&conn)?,
+    };
+    Ok(match kind {
+        DaemonKind::Mako => Box::new(MakoBackend::new(conn, dnd_mode)),
+        DaemonKind::Dunst => Box::new(DunstBackend::new(conn)),
+    })
+}
+
+/// Keep trying [`connect_backend`] with a doubling delay until a daemon shows up; `None`
+/// once the widget is gone. Clicks before then have nothing to toggle and are dropped.
+fn wait_for_backend(
+    configured: Option<DaemonKind>,
+    dnd_mode: &str,
+    toggles: &Receiver<()>,
+) -> Option<Box<dyn NotificationBackend>> {
+    let mut delay = DAEMON_RETRY_MIN;
+    let mut warned = false;
+    loop {
+        match connect_backend(configured, dnd_mode.to_string()) {
+            Ok(backend) => return Some(backend),
+            Err(e) => {
+                if !warned {
+                    log::warn!(
+                        "Notifications: no notification daemon available yet ({}), retrying",
+                        e
+                    );
+                    warned = true;
+                }
+            }
+        }
+        loop {
+            match toggles.try_recv() {
+                Ok(()) => log::debug!("Notifications: no notification daemon to toggle yet"),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return None,
+            }
+        }
+        std::thread::sleep(delay);
+        delay = (delay * 2).min(DAEMON_RETRY_MAX);
+    }
+}
+
+/// Poll the daemon on a background thread, pushing changed states to `updates` and
+/// toggling DND whenever `toggles` receives a request
+fn run_backend_loop(
+    mut backend: Box<dyn NotificationBackend>,
+    updates: UnboundedSender<NotificationState>,
+    toggles: Receiver<()>,
+    interval: Duration,
+) {
+    log::info!("Notifications: 🔔 using {}", backend.name());
+    let mut last: Option<NotificationState> = None;
+    loop {
+        let state = match toggles.recv_timeout(interval) {
+            Ok(()) => toggle_dnd(backend.as_mut()),
+            Err(RecvTimeoutError::Timeout) => backend.refresh(),
+            // Widget is gone
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+        match state {
+            Ok(state) if last != Some(state) => {
+                last = Some(state);
+                if updates.send(state).is_err() {
+                    return;
+                }
+            }
+            Ok(_) => {}
+            Err(e) => log::debug!("Notifications: {} query failed: {}", backend.name(), e),
+        }
+    }
+}
+
+pub struct NotificationsModule;
+
+impl NotificationsModule {
+    pub const IDENT: &'static str = "bar.module.notifications";
+
+    pub fn create_widget(settings: &ModuleConfig) -> gtk::Widget {
+        let string_option = |key: &str, default: &str| {
+            settings
+                .additional
+                .get(key)
+                .and_then(|v| v.as_str())
+                .unwrap_or(default)
+                .to_string()
+        };
+        let icon = string_option("icon", "🔔");
+        let dnd_icon = string_option("dnd_icon", "🔕");
+        let dnd_mode = string_option("dnd_mode", DEFAULT_MAKO_DND_MODE);
+        let backend_name = string_option("backend", "auto");
+        let interval_ms = settings
+            .additional
+            .get("interval_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(1000);
+
+        let container = gtk::Box::new(gtk::Orientation::Horizontal, 2);
+        container.add_css_class("module-notifications");
+        let icon_label = gtk::Label::new(Some(&icon));
+        let badge = gtk::Label::new(None);
+        badge.add_css_class("badge");
+        badge.set_visible(false);
+        container.append(&icon_label);
+        container.append(&badge);
+
+        let configured = match backend_name.as_str() {
+            "auto" => None,
+            name => match DaemonKind::from_name(name) {
+                Some(kind) => Some(kind),
+                None => {
+                    log::warn!(
+                        "Notifications: unknown backend '{}', detecting the daemon instead",
+                        name
+                    );
+                    None
+                }
+            },
+        };
+
+        // Apply daemon updates on the GTK thread as they arrive
+        let container_weak = container.downgrade();
+        let update_tx = super::gtk_receiver(move |states: Vec<NotificationState>| {
+            let Some(container) = container_weak.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            if let Some(state) = states.last() {
+                icon_label.set_text(if state.dnd { &dnd_icon } else { &icon });
+                if state.dnd {
+                    container.add_css_class(DND_CLASS);
+                } else {
+                    container.remove_css_class(DND_CLASS);
+                }
+                let text = badge_text(state.count);
+                badge.set_visible(text.is_some());
+                badge.set_text(text.as_deref().unwrap_or(""));
+            }
+            glib::ControlFlow::Continue
+        });
+        let (toggle_tx, toggle_rx) = mpsc::channel::<()>();
+        std::thread::spawn(move || {
+            if let Some(backend) = wait_for_backend(configured, &dnd_mode, &toggle_rx) {
+                run_backend_loop(
+                    backend,
+                    update_tx,
+                    toggle_rx,
+                    Duration::from_millis(interval_ms.max(100)),
+                );
+            }
+        });
+
+        // Click to toggle Do-Not-Disturb
+        let click = gtk::GestureClick::new();
+        click.set_button(gtk::gdk::BUTTON_PRIMARY);
+        click.connect_pressed(move |gesture, _n_press, _x, _y| {
+            if toggle_tx.send(()).is_err() {
+                log::warn!("Notifications: no notification daemon to toggle");
+            }
+            gesture.set_state(gtk::EventSequenceState::Claimed);
+        });
+        container.add_controller(click);
+
+        container.upcast()
+    }
+}
+
+impl BarModule for NotificationsModule {
+    fn id(&self) -> &'static str {
+        Self::IDENT
+    }
+    fn create(&self, settings: &ModuleConfig) -> gtk::Widget {
+        Self::create_widget(settings)
+    }
+}
@@ -0,0 +1,95 @@
+use gtk4 as gtk;
+use gtk4::prelude::*;
+
+use super::BarModule;
+use crate::config::ModuleConfig;
+use crate::niri::{NiriEvent, niri_bus, toggle_overview};
+
+/// CSS class applied while the overview is open
+pub const OPEN_CLASS: &str = "open";
+
+/// CSS class for an overview state (`None` when closed)
+pub fn overview_css_class(open: bool) -> Option<&'static str> {
+    open.then_some(OPEN_CLASS)
+}
+
+/// Label text for an overview state
+pub fn overview_text<'a>(open: bool, open_text: &'a str, closed_text: &'a str) -> &'a str {
+    if open { open_text } else { closed_text }
+}
+
+pub struct OverviewModule;
+
+impl OverviewModule {
+    pub const IDENT: &'static str = "bar.module.overview";
+
+    pub fn create_widget(settings: &ModuleConfig) -> gtk::Widget {
+        let open_text = settings
+            .additional
+            .get("open_text")
+            .and_then(|v| v.as_str())
+            .unwrap_or("◉")
+            .to_string();
+        let closed_text = settings
+            .additional
+            .get("closed_text")
+            .and_then(|v| v.as_str())
+            .unwrap_or("○")
+            .to_string();
+
+        let label = gtk::Label::new(None);
+        label.add_css_class("module-overview");
+        label.set_hexpand(true);
+        label.set_halign(gtk::Align::Fill);
+
+        let apply = move |label: &gtk::Label, open: bool| {
+            label.set_text(overview_text(open, &open_text, &closed_text));
+            match overview_css_class(open) {
+                Some(class) => label.add_css_class(class),
+                None => label.remove_css_class(OPEN_CLASS),
+            }
+        };
+        apply(&label, niri_bus().is_overview_open());
+
+        // Update from OverviewToggled events only
+        let label_weak = label.downgrade();
+        super::on_bus_events(move |events| {
+            let Some(label) = label_weak.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            let latest = events
+                .into_iter()
+                .filter_map(|ev| match ev {
+                    NiriEvent::OverviewToggled(open) => Some(open),
+                    _ => None,
+                })
+                .last();
+            if let Some(open) = latest {
+                apply(&label, open);
+            }
+            glib::ControlFlow::Continue
+        });
+
+        // Click to toggle the overview
+        let click = gtk::GestureClick::new();
+        click.set_button(gtk::gdk::BUTTON_PRIMARY);
+        click.connect_pressed(|gesture, _n_press, _x, _y| {
+            if let Err(e) = toggle_overview() {
+                log::error!("Overview: failed to toggle overview: {}", e);
+            }
+            gesture.set_state(gtk::EventSequenceState::Claimed);
+        });
+        label.add_controller(click);
+
+        label.upcast()
+    }
+}
+
+impl BarModule for OverviewModule {
+    fn id(&self) -> &'static str {
+        Self::IDENT
+    }
+    fn create(&self, settings: &ModuleConfig) -> gtk::Widget {
+        Self::create_widget(settings)
+    }
+}
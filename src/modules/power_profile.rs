@@ -0,0 +1,246 @@
+use anyhow::Result;
+use gtk4 as gtk;
+use gtk4::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, mpsc};
+use tokio::sync::mpsc::UnboundedSender;
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::OwnedValue;
+
+use super::{BarModule, StateBroadcast};
+use crate::config::ModuleConfig;
+
+/// Profiles in cycling order, lowest power first
+pub const PROFILES: [&str; 3] = ["power-saver", "balanced", "performance"];
+
+/// power-profiles-daemon settings; implemented over D-Bus, mockable in tests
+pub trait PowerProfilesBackend {
+    fn active_profile(&self) -> Result<String>;
+    /// Profiles this machine supports
+    fn profiles(&self) -> Result<Vec<String>>;
+    fn set_active_profile(&self, profile: &str) -> Result<()>;
+}
+
+/// Profile after `current` among `available`, in [`PROFILES`] order (reversed when
+/// `forward` is false), wrapping around. An unknown `current` starts from the first.
+pub fn next_profile(current: &str, available: &[String], forward: bool) -> Option<String> {
+    let mut order: Vec<&str> = PROFILES
+        .iter()
+        .copied()
+        .filter(|p| available.iter().any(|a| a == p))
+        .collect();
+    if !forward {
+        order.reverse();
+    }
+    let next = match order.iter().position(|p| *p == current) {
+        Some(i) => order[(i + 1) % order.len()],
+        None => *order.first()?,
+    };
+    Some(next.to_string())
+}
+
+/// CSS class for a profile: its name when it's one of [`PROFILES`]
+pub fn profile_css_class(profile: &str) -> Option<&'static str> {
+    PROFILES.iter().copied().find(|p| *p == profile)
+}
+
+/// Expand `{profile}` and `{icon}` in a format string
+pub fn render_profile_format(fmt: &str, profile: &str) -> String {
+    let icon = match profile {
+        "power-saver" => "🍃",
+        "performance" => "🚀",
+        _ => "⚖️",
+    };
+    fmt.replace("{profile}", profile).replace("{icon}", icon)
+}
+
+/// Maps widget input (click, scroll) to profile changes
+pub struct PowerProfileController<B: PowerProfilesBackend> {
+    backend: B,
+}
+
+impl<B: PowerProfilesBackend> PowerProfileController<B> {
+    pub fn new(backend: B) -> Self {
+        Self { backend }
+    }
+
+    /// Switch to the next (or previous) profile; returns the profile set
+    pub fn cycle(&self, forward: bool) -> Result<Option<String>> {
+        let current = self.backend.active_profile()?;
+        let available = self.backend.profiles()?;
+        let Some(next) = next_profile(&current, &available, forward) else {
+            return Ok(None);
+        };
+        if next != current {
+            self.backend.set_active_profile(&next)?;
+        }
+        Ok(Some(next))
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+}
+
+/// `net.hadess.PowerProfiles` on the system bus
+pub struct DbusPowerProfiles {
+    proxy: Proxy<'static>,
+}
+
+impl DbusPowerProfiles {
+    pub fn connect() -> Result<Self> {
+        let conn = Connection::system()?;
+        let proxy = Proxy::new(
+            &conn,
+            "net.hadess.PowerProfiles",
+            "/net/hadess/PowerProfiles",
+            "net.hadess.PowerProfiles",
+        )?;
+        Ok(Self { proxy })
+    }
+}
+
+impl PowerProfilesBackend for DbusPowerProfiles {
+    fn active_profile(&self) -> Result<String> {
+        Ok(self.proxy.get_property::<String>("ActiveProfile")?)
+    }
+
+    fn profiles(&self) -> Result<Vec<String>> {
+        let profiles: Vec<std::collections::HashMap<String, OwnedValue>> =
+            self.proxy.get_property("Profiles")?;
+        Ok(profiles
+            .iter()
+            .filter_map(|p| String::try_from(p.get("Profile")?.try_clone().ok()?).ok())
+            .collect())
+    }
+
+    fn set_active_profile(&self, profile: &str) -> Result<()> {
+        self.proxy.set_property("ActiveProfile", profile)?;
+        Ok(())
+    }
+}
+
+/// Active profile for every power profile widget, fed by one watcher for the whole process
+static ACTIVE_PROFILE: Mutex<StateBroadcast<String>> = Mutex::new(StateBroadcast::new());
+
+/// Whether the watcher thread is running; cleared when it gives up, so the next widget
+/// built (e.g. on reload) tries again
+static WATCHING: AtomicBool = AtomicBool::new(false);
+
+/// Send the active profile to `updates` now and on every change, starting the shared
+/// watcher if it isn't running
+fn follow_active_profile(updates: UnboundedSender<String>) {
+    ACTIVE_PROFILE.lock().unwrap().subscribe(updates);
+    if WATCHING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(|| {
+        let result =
+            DbusPowerProfiles::connect().and_then(|backend| watch_active_profile(&backend));
+        WATCHING.store(false, Ordering::SeqCst);
+        if let Err(e) = result {
+            log::warn!("PowerProfile: power-profiles-daemon unavailable: {}", e);
+        }
+    });
+}
+
+/// Publish the active profile now and on every `ActiveProfile` change
+fn watch_active_profile(backend: &DbusPowerProfiles) -> Result<()> {
+    let publish = |profile| ACTIVE_PROFILE.lock().unwrap().publish(profile);
+    publish(backend.active_profile()?);
+    for change in backend
+        .proxy
+        .receive_property_changed::<String>("ActiveProfile")
+    {
+        publish(change.get()?);
+    }
+    Ok(())
+}
+
+pub struct PowerProfileModule;
+
+impl PowerProfileModule {
+    pub const IDENT: &'static str = "bar.module.power_profile";
+
+    pub fn create_widget(settings: &ModuleConfig) -> gtk::Widget {
+        let fmt = settings
+            .format
+            .clone()
+            .unwrap_or_else(|| "{icon} {profile}".to_string());
+
+        let label = gtk::Label::new(None);
+        label.add_css_class("module-power-profile");
+        label.set_hexpand(true);
+        label.set_halign(gtk::Align::Fill);
+
+        // Profile changes are applied on the GTK thread as they arrive
+        let label_weak = label.downgrade();
+        let update_tx = super::gtk_receiver(move |profiles: Vec<String>| {
+            let Some(label) = label_weak.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            if let Some(profile) = profiles.last() {
+                label.set_text(&render_profile_format(&fmt, profile));
+                for class in PROFILES {
+                    label.remove_css_class(class);
+                }
+                if let Some(class) = profile_css_class(profile) {
+                    label.add_css_class(class);
+                }
+            }
+            glib::ControlFlow::Continue
+        });
+
+        // The shared watcher follows property changes; a thread per widget runs its clicks
+        // and scrolls, ending with the widget's senders
+        follow_active_profile(update_tx);
+        let (cycle_tx, cycle_rx) = mpsc::channel::<bool>();
+        std::thread::spawn(move || {
+            let controller = match DbusPowerProfiles::connect() {
+                Ok(backend) => PowerProfileController::new(backend),
+                Err(_) => return,
+            };
+            for forward in cycle_rx {
+                match controller.cycle(forward) {
+                    Ok(Some(profile)) => log::info!("PowerProfile: ⚡ switched to {}", profile),
+                    Ok(None) => {}
+                    Err(e) => log::error!("PowerProfile: failed to switch profile: {}", e),
+                }
+            }
+        });
+
+        // Click cycles forward; scroll up/down steps forward/back
+        let click = gtk::GestureClick::new();
+        click.set_button(gtk::gdk::BUTTON_PRIMARY);
+        {
+            let cycle_tx = cycle_tx.clone();
+            click.connect_pressed(move |gesture, _n_press, _x, _y| {
+                let _ = cycle_tx.send(true);
+                gesture.set_state(gtk::EventSequenceState::Claimed);
+            });
+        }
+        label.add_controller(click);
+
+        let scroll = gtk::EventControllerScroll::new(
+            gtk::EventControllerScrollFlags::VERTICAL | gtk::EventControllerScrollFlags::DISCRETE,
+        );
+        scroll.connect_scroll(move |_, _dx, dy| {
+            if dy.abs() > 0.1 {
+                let _ = cycle_tx.send(dy < 0.0);
+            }
+            gtk::glib::Propagation::Stop
+        });
+        label.add_controller(scroll);
+
+        label.upcast()
+    }
+}
+
+impl BarModule for PowerProfileModule {
+    fn id(&self) -> &'static str {
+        Self::IDENT
+    }
+    fn create(&self, settings: &ModuleConfig) -> gtk::Widget {
+        Self::create_widget(settings)
+    }
+}
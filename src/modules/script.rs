@@ -0,0 +1,154 @@
+use gtk4 as gtk;
+use gtk4::prelude::*;
+
+use super::BarModule;
+use crate::config::ModuleConfig;
+
+/// CSS classes applied according to the command's exit status
+const EXIT_CLASSES: [&str; 3] = ["exit-zero", "exit-nonzero", "exit-error"];
+
+/// What one run of the script produced
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptOutput {
+    /// First stdout line, trailing newline/whitespace trimmed
+    pub text: String,
+    /// Second stdout line, if any
+    pub tooltip: Option<String>,
+    /// Exit code; `None` if the process couldn't run or was killed by a signal
+    pub exit_code: Option<i32>,
+}
+
+/// Runs a command and returns (exit code, stdout); swapped for a fake in tests
+pub trait CommandRunner: Send + 'static {
+    fn run(&self, command: &str) -> std::io::Result<(Option<i32>, String)>;
+}
+
+/// Runs commands through `sh -c`
+pub struct ShellCommandRunner;
+
+impl CommandRunner for ShellCommandRunner {
+    fn run(&self, command: &str) -> std::io::Result<(Option<i32>, String)> {
+        let out = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .output()?;
+        Ok((
+            out.status.code(),
+            String::from_utf8_lossy(&out.stdout).to_string(),
+        ))
+    }
+}
+
+/// Split stdout into label text (first line) and optional tooltip (second line)
+pub fn parse_script_output(stdout: &str) -> (String, Option<String>) {
+    let mut lines = stdout.lines();
+    let text = lines.next().unwrap_or_default().trim_end().to_string();
+    let tooltip = lines
+        .next()
+        .map(|l| l.trim_end().to_string())
+        .filter(|l| !l.is_empty());
+    (text, tooltip)
+}
+
+/// CSS class for an exit status
+pub fn exit_code_class(exit_code: Option<i32>) -> &'static str {
+    match exit_code {
+        Some(0) => EXIT_CLASSES[0],
+        Some(_) => EXIT_CLASSES[1],
+        None => EXIT_CLASSES[2],
+    }
+}
+
+/// Run the command once and parse its output
+pub fn run_script<R: CommandRunner>(runner: &R, command: &str) -> ScriptOutput {
+    match runner.run(command) {
+        Ok((exit_code, stdout)) => {
+            let (text, tooltip) = parse_script_output(&stdout);
+            ScriptOutput {
+                text,
+                tooltip,
+                exit_code,
+            }
+        }
+        Err(e) => {
+            log::warn!("Script: failed to run '{}': {}", command, e);
+            ScriptOutput {
+                text: String::new(),
+                tooltip: None,
+                exit_code: None,
+            }
+        }
+    }
+}
+
+pub struct ScriptModule;
+
+impl ScriptModule {
+    pub const IDENT: &'static str = "bar.module.script";
+
+    pub fn create_widget(settings: &ModuleConfig) -> gtk::Widget {
+        let command = settings
+            .additional
+            .get("command")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        let interval_ms = settings
+            .additional
+            .get("interval_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(5000)
+            .max(100);
+        let show_tooltip = settings.tooltip.unwrap_or(true);
+
+        let label = gtk::Label::new(None);
+        label.add_css_class("module-script");
+        label.set_hexpand(true);
+        label.set_halign(gtk::Align::Fill);
+
+        let Some(command) = command else {
+            log::warn!("Script: no 'command' configured");
+            return label.upcast();
+        };
+
+        // Run the command off the GTK thread; results come back over a channel
+        let label_weak = label.downgrade();
+        let tx = super::gtk_receiver(move |outputs: Vec<ScriptOutput>| {
+            let Some(label) = label_weak.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            if let Some(output) = outputs.last() {
+                label.set_text(&output.text);
+                if show_tooltip {
+                    label.set_tooltip_text(output.tooltip.as_deref());
+                }
+                for class in EXIT_CLASSES {
+                    label.remove_css_class(class);
+                }
+                label.add_css_class(exit_code_class(output.exit_code));
+            }
+            glib::ControlFlow::Continue
+        });
+        std::thread::spawn(move || {
+            let runner = ShellCommandRunner;
+            loop {
+                let output = run_script(&runner, &command);
+                if tx.send(output).is_err() {
+                    // Widget is gone
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(interval_ms));
+            }
+        });
+
+        label.upcast()
+    }
+}
+
+impl BarModule for ScriptModule {
+    fn id(&self) -> &'static str {
+        Self::IDENT
+    }
+    fn create(&self, settings: &ModuleConfig) -> gtk::Widget {
+        Self::create_widget(settings)
+    }
+}
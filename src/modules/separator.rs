@@ -0,0 +1,59 @@
+use gtk4 as gtk;
+use gtk4::prelude::*;
+
+use super::BarModule;
+use crate::config::ModuleConfig;
+
+/// Name of the separator in layouts
+pub const NAME: &str = "separator";
+
+/// Width a separator takes without a configured `width`, in logical pixels
+pub const DEFAULT_WIDTH: i32 = 9;
+
+/// Width a separator always takes in its column: its `width` (negative counts as 0), else
+/// [`DEFAULT_WIDTH`]. Overflow budgets use this instead of a measured width, so a glyph
+/// separator costs the same as a line.
+pub fn fixed_width(settings: Option<&ModuleConfig>) -> i32 {
+    settings
+        .and_then(|s| s.width)
+        .unwrap_or(DEFAULT_WIDTH)
+        .max(0)
+}
+
+/// Thin divider between modules: a vertical line, or the glyph in `format`, centered in a
+/// box of [`fixed_width`]
+pub struct SeparatorModule;
+
+impl SeparatorModule {
+    pub const IDENT: &'static str = "bar.module.separator";
+
+    pub fn create_widget(settings: &ModuleConfig) -> gtk::Widget {
+        // A box rather than a bare label, so shrinking columns never ellipsize the glyph
+        let container = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+        container.add_css_class("module-separator");
+        container.set_size_request(fixed_width(Some(settings)), -1);
+
+        let divider: gtk::Widget = match settings.format.as_deref().filter(|f| !f.is_empty()) {
+            Some(glyph) => {
+                let label = gtk::Label::new(Some(glyph));
+                label.add_css_class("module-separator-glyph");
+                label.upcast()
+            }
+            None => gtk::Separator::new(gtk::Orientation::Vertical).upcast(),
+        };
+        divider.set_hexpand(true);
+        divider.set_halign(gtk::Align::Center);
+        container.append(&divider);
+
+        container.upcast()
+    }
+}
+
+impl BarModule for SeparatorModule {
+    fn id(&self) -> &'static str {
+        Self::IDENT
+    }
+    fn create(&self, settings: &ModuleConfig) -> gtk::Widget {
+        Self::create_widget(settings)
+    }
+}
@@ -0,0 +1,185 @@
+use gtk4 as gtk;
+use gtk4::prelude::*;
+use std::path::{Path, PathBuf};
+
+use super::BarModule;
+use crate::config::ModuleConfig;
+
+/// Sensor labels preferred for the CPU package temperature when no sensor is configured
+const PREFERRED_LABELS: [&str; 3] = ["Package id 0", "Tctl", "Tdie"];
+/// hwmon chip names preferred when no preferred label is present
+const PREFERRED_CHIPS: [&str; 3] = ["coretemp", "k10temp", "zenpower"];
+
+/// One `temp*_input` node under `/sys/class/hwmon`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TempSensor {
+    /// hwmon chip name (contents of `name`), e.g. "coretemp"
+    pub chip: String,
+    /// Sensor label (contents of `temp*_label`), or the input file stem when unlabeled
+    pub label: String,
+    pub input: PathBuf,
+}
+
+/// Convert a sysfs millidegree reading to degrees Celsius
+pub fn millidegrees_to_celsius(raw: i64) -> f64 {
+    raw as f64 / 1000.0
+}
+
+/// List all temperature inputs under a hwmon base directory (normally `/sys/class/hwmon`)
+pub fn discover_sensors(base: &Path) -> Vec<TempSensor> {
+    let mut hwmons: Vec<PathBuf> = match std::fs::read_dir(base) {
+        Ok(rd) => rd.flatten().map(|e| e.path()).collect(),
+        Err(_) => return Vec::new(),
+    };
+    hwmons.sort();
+
+    let mut sensors = Vec::new();
+    for hwmon in hwmons {
+        let chip = std::fs::read_to_string(hwmon.join("name"))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default();
+        let mut inputs: Vec<PathBuf> = match std::fs::read_dir(&hwmon) {
+            Ok(rd) => rd
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| {
+                    p.file_name()
+                        .and_then(|n| n.to_str())
+                        .map(|n| n.starts_with("temp") && n.ends_with("_input"))
+                        .unwrap_or(false)
+                })
+                .collect(),
+            Err(_) => continue,
+        };
+        inputs.sort();
+        for input in inputs {
+            let stem = input
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_suffix("_input"))
+                .unwrap_or_default()
+                .to_string();
+            let label = std::fs::read_to_string(hwmon.join(format!("{}_label", stem)))
+                .map(|s| s.trim().to_string())
+                .unwrap_or(stem);
+            sensors.push(TempSensor {
+                chip: chip.clone(),
+                label,
+                input,
+            });
+        }
+    }
+    sensors
+}
+
+/// Pick a sensor: by `wanted` (label, chip name or "chip/label"), otherwise a CPU package
+/// style sensor, otherwise the first one found
+pub fn select_sensor<'a>(
+    sensors: &'a [TempSensor],
+    wanted: Option<&str>,
+) -> Option<&'a TempSensor> {
+    if let Some(wanted) = wanted {
+        return sensors.iter().find(|s| {
+            s.label == wanted || s.chip == wanted || format!("{}/{}", s.chip, s.label) == wanted
+        });
+    }
+    PREFERRED_LABELS
+        .iter()
+        .find_map(|l| sensors.iter().find(|s| s.label == *l))
+        .or_else(|| {
+            PREFERRED_CHIPS
+                .iter()
+                .find_map(|c| sensors.iter().find(|s| s.chip == *c))
+        })
+        .or_else(|| sensors.first())
+}
+
+/// Read a sensor in degrees Celsius
+pub fn read_celsius(sensor: &TempSensor) -> Option<f64> {
+    std::fs::read_to_string(&sensor.input)
+        .ok()
+        .and_then(|s| s.trim().parse::<i64>().ok())
+        .map(millidegrees_to_celsius)
+}
+
+pub struct TempModule;
+
+impl TempModule {
+    pub const IDENT: &'static str = "bar.module.temp";
+
+    pub fn create_widget(settings: &ModuleConfig) -> gtk::Widget {
+        let fmt = settings
+            .format
+            .clone()
+            .unwrap_or_else(|| "{temp}°C".to_string());
+        let sensor_name = settings
+            .additional
+            .get("sensor")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        let warn = settings.warn_threshold.unwrap_or(80);
+        let crit = settings.critical_threshold.unwrap_or(95);
+        let interval_ms = settings
+            .additional
+            .get("interval_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(2000)
+            .max(100);
+
+        let label = gtk::Label::new(None);
+        label.add_css_class("module-temp");
+        label.set_hexpand(true);
+        label.set_halign(gtk::Align::Fill);
+
+        let sensors = discover_sensors(Path::new("/sys/class/hwmon"));
+        let Some(sensor) = select_sensor(&sensors, sensor_name.as_deref()).cloned() else {
+            log::warn!(
+                "Temp: no temperature sensor found (wanted: {:?})",
+                sensor_name
+            );
+            return label.upcast();
+        };
+        log::info!("Temp: using sensor {}/{}", sensor.chip, sensor.label);
+
+        update_temp_label(&label, &sensor, &fmt, warn, crit);
+        let label_weak = label.downgrade();
+        super::subscribe_refresh(std::time::Duration::from_millis(interval_ms), move || {
+            if let Some(label) = label_weak.upgrade() {
+                update_temp_label(&label, &sensor, &fmt, warn, crit);
+                glib::ControlFlow::Continue
+            } else {
+                glib::ControlFlow::Break
+            }
+        });
+
+        label.upcast()
+    }
+}
+
+impl BarModule for TempModule {
+    fn id(&self) -> &'static str {
+        Self::IDENT
+    }
+    fn create(&self, settings: &ModuleConfig) -> gtk::Widget {
+        Self::create_widget(settings)
+    }
+}
+
+fn update_temp_label(label: &gtk::Label, sensor: &TempSensor, fmt: &str, warn: u8, crit: u8) {
+    let Some(celsius) = read_celsius(sensor) else {
+        label.set_text("");
+        return;
+    };
+    label.set_text(&fmt.replace("{temp}", &format!("{:.0}", celsius)));
+
+    label.remove_css_class("temp-ok");
+    label.remove_css_class("temp-warn");
+    label.remove_css_class("temp-crit");
+    if celsius >= crit as f64 {
+        label.add_css_class("temp-crit");
+    } else if celsius >= warn as f64 {
+        label.add_css_class("temp-warn");
+    } else {
+        label.add_css_class("temp-ok");
+    }
+}
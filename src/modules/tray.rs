@@ -1,23 +1,963 @@
+use anyhow::{Result, anyhow};
 use gtk::prelude::*;
 use gtk4 as gtk;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedSender;
 
+use zbus::blocking::{Connection, MessageIterator, Proxy, fdo::DBusProxy};
+use zbus::message::Type as MessageType;
+use zbus::proxy::CacheProperties;
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+use zbus::{MatchRule, names::BusName};
+
+use super::BarModule;
 use crate::config::ModuleConfig;
 
+const WATCHER_NAME: &str = "org.kde.StatusNotifierWatcher";
+const WATCHER_PATH: &str = "/StatusNotifierWatcher";
+const ITEM_INTERFACE: &str = "org.kde.StatusNotifierItem";
+const MENU_INTERFACE: &str = "com.canonical.dbusmenu";
+/// Object path of items that register with a bus name only
+pub const DEFAULT_ITEM_PATH: &str = "/StatusNotifierItem";
+/// Icon shown when an item has neither a usable icon name nor pixmap
+pub const FALLBACK_ICON: &str = "image-missing";
+/// How often to look for a StatusNotifierWatcher while none is running
+const WATCHER_RETRY: Duration = Duration::from_secs(5);
+
+/// `GetLayout` reply: revision and the root node `(id, properties, children)`
+type MenuLayout = (u32, (i32, HashMap<String, OwnedValue>, Vec<OwnedValue>));
+
+/// Distinguishes hosts when several bars (one per monitor) run in one process
+static HOST_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// `Status` property of an item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrayItemStatus {
+    Passive,
+    #[default]
+    Active,
+    NeedsAttention,
+}
+
+impl TrayItemStatus {
+    pub fn parse(status: &str) -> Self {
+        match status {
+            "Passive" => Self::Passive,
+            "NeedsAttention" => Self::NeedsAttention,
+            _ => Self::Active,
+        }
+    }
+}
+
+/// ARGB32 icon image (network byte order), as carried by `IconPixmap`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TrayPixmap {
+    pub width: i32,
+    pub height: i32,
+    pub data: Vec<u8>,
+}
+
+impl TrayPixmap {
+    fn is_valid(&self) -> bool {
+        self.width > 0
+            && self.height > 0
+            && self.data.len() >= self.width as usize * self.height as usize * 4
+    }
+}
+
+/// Everything the tray renders for one StatusNotifierItem
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TrayItem {
+    /// Registered service string, e.g. `:1.42/StatusNotifierItem`; unique per item
+    pub id: String,
+    pub title: String,
+    pub status: TrayItemStatus,
+    pub icon_name: String,
+    /// Extra icon theme directory the item ships its icons in
+    pub icon_theme_path: String,
+    pub icon_pixmaps: Vec<TrayPixmap>,
+    pub attention_icon_name: String,
+    pub attention_pixmaps: Vec<TrayPixmap>,
+    pub tooltip: String,
+    /// Whether the item exports a DbusMenu
+    pub has_menu: bool,
+    /// Item only supports its menu; left click opens it instead of `Activate`
+    pub item_is_menu: bool,
+}
+
+/// One DbusMenu entry
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TrayMenuItem {
+    pub id: i32,
+    pub label: String,
+    pub enabled: bool,
+    pub visible: bool,
+    pub separator: bool,
+    /// `Some(checked)` for checkmark/radio entries
+    pub toggle: Option<bool>,
+    pub children: Vec<TrayMenuItem>,
+}
+
+/// Updates from the D-Bus host to the GTK thread
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrayEvent {
+    /// Item registered or changed
+    Upsert(TrayItem),
+    /// Item unregistered (by id)
+    Removed(String),
+    /// Menu layout fetched for an item, ready to show
+    Menu {
+        id: String,
+        items: Vec<TrayMenuItem>,
+    },
+}
+
+/// Items currently in the tray, in registration order. Passive items (idle, per the
+/// StatusNotifierItem spec) are left out until they turn active again.
+#[derive(Debug, Clone, Default)]
+pub struct TrayState {
+    items: Vec<TrayItem>,
+}
+
+impl TrayState {
+    /// Apply an event; returns whether the visible set of items changed
+    pub fn apply(&mut self, event: &TrayEvent) -> bool {
+        match event {
+            TrayEvent::Upsert(item) if item.status == TrayItemStatus::Passive => {
+                let before = self.items.len();
+                self.items.retain(|i| i.id != item.id);
+                self.items.len() != before
+            }
+            TrayEvent::Upsert(item) => match self.items.iter_mut().find(|i| i.id == item.id) {
+                Some(existing) if existing == item => false,
+                Some(existing) => {
+                    *existing = item.clone();
+                    true
+                }
+                None => {
+                    self.items.push(item.clone());
+                    true
+                }
+            },
+            TrayEvent::Removed(id) => {
+                let before = self.items.len();
+                self.items.retain(|i| &i.id != id);
+                self.items.len() != before
+            }
+            TrayEvent::Menu { .. } => false,
+        }
+    }
+
+    pub fn items(&self) -> &[TrayItem] {
+        &self.items
+    }
+
+    pub fn get(&self, id: &str) -> Option<&TrayItem> {
+        self.items.iter().find(|i| i.id == id)
+    }
+}
+
+/// What to render for an item
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrayIcon {
+    /// Icon theme name
+    Named(String),
+    /// Absolute path to an image file (some items put one in `IconName`)
+    File(String),
+    Pixmap(TrayPixmap),
+    Fallback,
+}
+
+/// Pixmap closest to `size`: the smallest at least `size` wide, else the largest
+pub fn best_pixmap(pixmaps: &[TrayPixmap], size: i32) -> Option<&TrayPixmap> {
+    let valid = pixmaps.iter().filter(|p| p.is_valid());
+    valid
+        .clone()
+        .filter(|p| p.width >= size)
+        .min_by_key(|p| p.width)
+        .or_else(|| valid.max_by_key(|p| p.width))
+}
+
+/// Pick the icon for an item: the attention icon while it needs attention, a theme icon
+/// when `has_icon` knows the name, a file path, then the best pixmap
+pub fn select_icon(item: &TrayItem, size: i32, has_icon: impl Fn(&str) -> bool) -> TrayIcon {
+    let attention = item.status == TrayItemStatus::NeedsAttention
+        && (!item.attention_icon_name.is_empty() || !item.attention_pixmaps.is_empty());
+    let (name, pixmaps) = if attention {
+        (&item.attention_icon_name, &item.attention_pixmaps)
+    } else {
+        (&item.icon_name, &item.icon_pixmaps)
+    };
+    if !name.is_empty() && has_icon(name) {
+        return TrayIcon::Named(name.clone());
+    }
+    if name.starts_with('/') {
+        return TrayIcon::File(name.clone());
+    }
+    match best_pixmap(pixmaps, size) {
+        Some(pixmap) => TrayIcon::Pixmap(pixmap.clone()),
+        None => TrayIcon::Fallback,
+    }
+}
+
+/// Convert SNI's ARGB32 (network byte order) to RGBA for a GDK memory texture
+pub fn argb_to_rgba(data: &[u8]) -> Vec<u8> {
+    data.chunks_exact(4)
+        .flat_map(|px| [px[1], px[2], px[3], px[0]])
+        .collect()
+}
+
+/// Split a registered item string into (bus name, object path). Items register as a bus
+/// name (`org.kde.StatusNotifierItem-123-1`), a bus name followed by a path
+/// (`:1.42/org/ayatana/NotificationItem/foo`), or only a path, which belongs to `sender`.
+pub fn parse_item_address(service: &str, sender: &str) -> (String, String) {
+    if service.starts_with('/') {
+        return (sender.to_string(), service.to_string());
+    }
+    match service.find('/') {
+        Some(slash) => (service[..slash].to_string(), service[slash..].to_string()),
+        None => (service.to_string(), DEFAULT_ITEM_PATH.to_string()),
+    }
+}
+
+/// Drop DbusMenu mnemonic markers: `_File` → `File`, `__` → `_`
+pub fn strip_mnemonic(label: &str) -> String {
+    let mut out = String::with_capacity(label.len());
+    let mut chars = label.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '_' {
+            if chars.peek() == Some(&'_') {
+                chars.next();
+                out.push('_');
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Requests from the GTK thread to the D-Bus host (by item id)
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrayCommand {
+    Activate(String),
+    SecondaryActivate(String),
+    ContextMenu(String),
+    /// Fetch the item's menu; answered with `TrayEvent::Menu`
+    OpenMenu(String),
+    MenuClicked {
+        id: String,
+        entry: i32,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct ItemAddress {
+    bus: String,
+    path: String,
+    /// Unique bus name signals arrive from
+    owner: String,
+    menu: Option<String>,
+}
+
+/// State shared by the host's threads
+struct TrayHost {
+    conn: Connection,
+    items: Mutex<HashMap<String, ItemAddress>>,
+    updates: UnboundedSender<TrayEvent>,
+}
+
+/// StatusNotifierHost: background threads own the D-Bus connection, follow items
+/// registered with the watcher and run commands sent from the GTK thread.
+pub struct SniTrayBackend {
+    commands: RefCell<Option<Sender<TrayCommand>>>,
+}
+
+impl SniTrayBackend {
+    /// Start the host; item updates are pushed into `updates`
+    pub fn spawn(updates: UnboundedSender<TrayEvent>) -> Self {
+        let (tx, rx) = mpsc::channel::<TrayCommand>();
+        std::thread::spawn(move || {
+            if let Err(e) = run_tray_host(updates, rx) {
+                log::warn!("Tray: {}", e);
+            }
+        });
+        Self {
+            commands: RefCell::new(Some(tx)),
+        }
+    }
+
+    pub fn send(&self, command: TrayCommand) {
+        if let Some(commands) = self.commands.borrow().as_ref() {
+            let _ = commands.send(command);
+        }
+    }
+
+    /// Shut the host down: its command loop ends and it stops its signal listeners
+    pub fn stop(&self) {
+        self.commands.borrow_mut().take();
+    }
+}
+
+/// Signal the host sends itself to wake its listener threads when it stops
+const HOST_STOPPED: &str = "NiriBarHostStopped";
+
+/// Whether a signal is the host's own [`HOST_STOPPED`]
+fn is_host_stopped(conn: &Connection, msg: &zbus::Message) -> bool {
+    let header = msg.header();
+    header.member().is_some_and(|m| m.as_str() == HOST_STOPPED)
+        && header.sender().map(|s| s.as_str()) == conn.unique_name().map(|n| n.as_str())
+}
+
+fn run_tray_host(
+    updates: UnboundedSender<TrayEvent>,
+    commands: Receiver<TrayCommand>,
+) -> Result<()> {
+    let conn = Connection::session()?;
+    let host_name = format!(
+        "org.kde.StatusNotifierHost-{}-{}",
+        std::process::id(),
+        HOST_COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    conn.request_name(host_name.as_str())?;
+
+    // Subscribe before listing items so none registered in between is missed
+    let watcher_rule = MatchRule::builder()
+        .msg_type(MessageType::Signal)
+        .interface(WATCHER_NAME)?
+        .build();
+    let watcher_signals = MessageIterator::for_match_rule(watcher_rule, &conn, None)?;
+    let item_rule = MatchRule::builder()
+        .msg_type(MessageType::Signal)
+        .interface(ITEM_INTERFACE)?
+        .build();
+    let item_signals = MessageIterator::for_match_rule(item_rule, &conn, None)?;
+
+    let host = Arc::new(TrayHost {
+        conn,
+        items: Mutex::new(HashMap::new()),
+        updates,
+    });
+
+    // niri doesn't run a watcher itself; wait for one (e.g. from another tray host)
+    let mut warned = false;
+    let registered = loop {
+        match register_with_watcher(&host.conn, &host_name) {
+            Ok(items) => break items,
+            Err(e) => {
+                if !warned {
+                    log::warn!(
+                        "Tray: no StatusNotifierWatcher on the session bus yet ({}), retrying",
+                        e
+                    );
+                    warned = true;
+                }
+                if let Err(TryRecvError::Disconnected) = commands.try_recv() {
+                    return Ok(());
+                }
+                std::thread::sleep(WATCHER_RETRY);
+            }
+        }
+    };
+    log::info!(
+        "Tray: 🧷 registered {} with {} item(s)",
+        host_name,
+        registered.len()
+    );
+    for service in &registered {
+        host.add_item(service);
+    }
+
+    // Both listeners run until the host stops (or the widget's receiver is gone)
+    let watcher_listener = {
+        let host = host.clone();
+        std::thread::spawn(move || {
+            for msg in watcher_signals.flatten() {
+                if is_host_stopped(&host.conn, &msg) {
+                    break;
+                }
+                let member = msg
+                    .header()
+                    .member()
+                    .map(|m| m.to_string())
+                    .unwrap_or_default();
+                let Ok(service) = msg.body().deserialize::<String>() else {
+                    continue;
+                };
+                let alive = match member.as_str() {
+                    "StatusNotifierItemRegistered" => host.add_item(&service),
+                    "StatusNotifierItemUnregistered" => host.remove_item(&service),
+                    _ => true,
+                };
+                if !alive {
+                    break;
+                }
+            }
+        })
+    };
+    let item_listener = {
+        let host = host.clone();
+        std::thread::spawn(move || {
+            for msg in item_signals.flatten() {
+                if is_host_stopped(&host.conn, &msg) {
+                    break;
+                }
+                let header = msg.header();
+                let sender = header.sender().map(|s| s.to_string()).unwrap_or_default();
+                let path = header.path().map(|p| p.to_string()).unwrap_or_default();
+                if !host.refresh_from_signal(&sender, &path) {
+                    break;
+                }
+            }
+        })
+    };
+
+    // Ends when the widget stops the backend
+    for command in commands {
+        if let Err(e) = host.handle_command(&command) {
+            log::warn!("Tray: {:?} failed: {}", command, e);
+        }
+    }
+
+    // Wake both listeners with a signal only this host emits, then wait for them
+    for interface in [WATCHER_NAME, ITEM_INTERFACE] {
+        if let Err(e) =
+            host.conn
+                .emit_signal(None::<BusName<'_>>, "/", interface, HOST_STOPPED, &())
+        {
+            log::warn!("Tray: couldn't stop the signal listeners: {}", e);
+            return Ok(());
+        }
+    }
+    let _ = watcher_listener.join();
+    let _ = item_listener.join();
+    let _ = host.conn.release_name(host_name.as_str());
+    log::debug!("Tray: host {} stopped", host_name);
+    Ok(())
+}
+
+/// Register as a host; returns the items the watcher already knows
+fn register_with_watcher(conn: &Connection, host_name: &str) -> Result<Vec<String>> {
+    let watcher = Proxy::new(conn, WATCHER_NAME, WATCHER_PATH, WATCHER_NAME)?;
+    watcher.call_method("RegisterStatusNotifierHost", &(host_name,))?;
+    Ok(watcher
+        .get_property::<Vec<String>>("RegisteredStatusNotifierItems")
+        .unwrap_or_default())
+}
+
+fn item_proxy<'a>(
+    conn: &Connection,
+    bus: &'a str,
+    path: &'a str,
+    iface: &'a str,
+) -> Result<Proxy<'a>> {
+    Ok(zbus::blocking::proxy::Builder::new(conn)
+        .destination(bus)?
+        .path(path)?
+        .interface(iface)?
+        .cache_properties(CacheProperties::No)
+        .build()?)
+}
+
+fn pixmaps_from(raw: Vec<(i32, i32, Vec<u8>)>) -> Vec<TrayPixmap> {
+    raw.into_iter()
+        .map(|(width, height, data)| TrayPixmap {
+            width,
+            height,
+            data,
+        })
+        .collect()
+}
+
+impl TrayHost {
+    /// Track a newly registered item and publish it; false once the widget is gone. The
+    /// watcher sends services with their bus name; a bare path can't be resolved here, as
+    /// the signal's sender is the watcher, not the item.
+    fn add_item(&self, service: &str) -> bool {
+        let (bus, path) = parse_item_address(service, "");
+        if bus.is_empty() {
+            log::warn!(
+                "Tray: item {} registered without a bus name, skipping",
+                service
+            );
+            return true;
+        }
+        let owner = if bus.starts_with(':') {
+            bus.clone()
+        } else {
+            BusName::try_from(bus.as_str())
+                .ok()
+                .and_then(|name| DBusProxy::new(&self.conn).ok()?.get_name_owner(name).ok())
+                .map(|owner| owner.to_string())
+                .unwrap_or_else(|| bus.clone())
+        };
+        let mut address = ItemAddress {
+            bus,
+            path,
+            owner,
+            menu: None,
+        };
+        match self.fetch_item(service, &mut address) {
+            Ok(item) => {
+                if let Ok(mut items) = self.items.lock() {
+                    items.insert(service.to_string(), address);
+                }
+                log::debug!("Tray: item added {}", service);
+                self.updates.send(TrayEvent::Upsert(item)).is_ok()
+            }
+            Err(e) => {
+                log::warn!("Tray: couldn't read item {}: {}", service, e);
+                true
+            }
+        }
+    }
+
+    fn remove_item(&self, service: &str) -> bool {
+        if let Ok(mut items) = self.items.lock() {
+            items.remove(service);
+        }
+        log::debug!("Tray: item removed {}", service);
+        self.updates
+            .send(TrayEvent::Removed(service.to_string()))
+            .is_ok()
+    }
+
+    /// An item emitted NewIcon/NewStatus/...: re-read it
+    fn refresh_from_signal(&self, sender: &str, path: &str) -> bool {
+        let found = self.items.lock().ok().and_then(|items| {
+            items
+                .iter()
+                .find(|(_, a)| a.owner == sender && a.path == path)
+                .map(|(id, a)| (id.clone(), a.clone()))
+        });
+        let Some((id, mut address)) = found else {
+            return true;
+        };
+        match self.fetch_item(&id, &mut address) {
+            Ok(item) => {
+                if let Ok(mut items) = self.items.lock() {
+                    items.insert(id, address);
+                }
+                self.updates.send(TrayEvent::Upsert(item)).is_ok()
+            }
+            Err(e) => {
+                log::debug!("Tray: refresh of {} failed: {}", id, e);
+                true
+            }
+        }
+    }
+
+    fn fetch_item(&self, id: &str, address: &mut ItemAddress) -> Result<TrayItem> {
+        let proxy = item_proxy(&self.conn, &address.bus, &address.path, ITEM_INTERFACE)?;
+        let string = |name: &str| proxy.get_property::<String>(name).unwrap_or_default();
+        let pixmaps = |name: &str| {
+            proxy
+                .get_property::<Vec<(i32, i32, Vec<u8>)>>(name)
+                .map(pixmaps_from)
+                .unwrap_or_default()
+        };
+        // Status is mandatory; failing to read it means the item isn't really there
+        let status = proxy.get_property::<String>("Status")?;
+        let tooltip = proxy
+            .get_property::<(String, Vec<(i32, i32, Vec<u8>)>, String, String)>("ToolTip")
+            .map(|(_, _, title, body)| if title.is_empty() { body } else { title })
+            .unwrap_or_default();
+        address.menu = proxy
+            .get_property::<OwnedObjectPath>("Menu")
+            .ok()
+            .map(|p| p.to_string())
+            .filter(|p| p != "/");
+
+        Ok(TrayItem {
+            id: id.to_string(),
+            title: string("Title"),
+            status: TrayItemStatus::parse(&status),
+            icon_name: string("IconName"),
+            icon_theme_path: string("IconThemePath"),
+            icon_pixmaps: pixmaps("IconPixmap"),
+            attention_icon_name: string("AttentionIconName"),
+            attention_pixmaps: pixmaps("AttentionIconPixmap"),
+            tooltip,
+            has_menu: address.menu.is_some(),
+            item_is_menu: proxy.get_property::<bool>("ItemIsMenu").unwrap_or(false),
+        })
+    }
+
+    fn address(&self, id: &str) -> Result<ItemAddress> {
+        self.items
+            .lock()
+            .ok()
+            .and_then(|items| items.get(id).cloned())
+            .ok_or_else(|| anyhow!("unknown item {}", id))
+    }
+
+    fn handle_command(&self, command: &TrayCommand) -> Result<()> {
+        match command {
+            TrayCommand::Activate(id) => self.call_item(id, "Activate"),
+            TrayCommand::SecondaryActivate(id) => self.call_item(id, "SecondaryActivate"),
+            TrayCommand::ContextMenu(id) => self.call_item(id, "ContextMenu"),
+            TrayCommand::OpenMenu(id) => {
+                let address = self.address(id)?;
+                let menu_path = address.menu.ok_or_else(|| anyhow!("item has no menu"))?;
+                let menu = item_proxy(&self.conn, &address.bus, &menu_path, MENU_INTERFACE)?;
+                // Lets lazily built menus fill themselves in; not every item implements it
+                let _ = menu.call_method("AboutToShow", &(0i32,));
+                let (_revision, (_root_id, _props, children)): MenuLayout =
+                    menu.call("GetLayout", &(0i32, -1i32, Vec::<String>::new()))?;
+                let items = children
+                    .iter()
+                    .filter_map(|child| menu_item_from_value(child))
+                    .collect();
+                let _ = self.updates.send(TrayEvent::Menu {
+                    id: id.clone(),
+                    items,
+                });
+                Ok(())
+            }
+            TrayCommand::MenuClicked { id, entry } => {
+                let address = self.address(id)?;
+                let menu_path = address.menu.ok_or_else(|| anyhow!("item has no menu"))?;
+                let menu = item_proxy(&self.conn, &address.bus, &menu_path, MENU_INTERFACE)?;
+                menu.call_method("Event", &(*entry, "clicked", Value::from(0i32), 0u32))?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Activate-style calls take screen coordinates, which aren't known under Wayland
+    fn call_item(&self, id: &str, method: &str) -> Result<()> {
+        let address = self.address(id)?;
+        let proxy = item_proxy(&self.conn, &address.bus, &address.path, ITEM_INTERFACE)?;
+        proxy.call_method(method, &(0i32, 0i32))?;
+        Ok(())
+    }
+}
+
+/// Parse a DbusMenu layout node `(ia{sv}av)`
+fn menu_item_from_value(value: &Value<'_>) -> Option<TrayMenuItem> {
+    let value = match value {
+        Value::Value(inner) => inner.as_ref(),
+        other => other,
+    };
+    let Value::Structure(node) = value else {
+        return None;
+    };
+    let [Value::I32(id), Value::Dict(props), Value::Array(children)] = node.fields() else {
+        return None;
+    };
+    let mut item = TrayMenuItem {
+        id: *id,
+        enabled: true,
+        visible: true,
+        ..Default::default()
+    };
+    let mut toggle_type = false;
+    let mut toggle_state = false;
+    for (key, value) in props.iter() {
+        let Value::Str(key) = key else {
+            continue;
+        };
+        let value = match value {
+            Value::Value(inner) => inner.as_ref(),
+            other => other,
+        };
+        match (key.as_str(), value) {
+            ("label", Value::Str(label)) => item.label = strip_mnemonic(label.as_str()),
+            ("type", Value::Str(kind)) => item.separator = kind.as_str() == "separator",
+            ("enabled", Value::Bool(enabled)) => item.enabled = *enabled,
+            ("visible", Value::Bool(visible)) => item.visible = *visible,
+            ("toggle-type", Value::Str(kind)) => toggle_type = !kind.is_empty(),
+            ("toggle-state", Value::I32(state)) => toggle_state = *state == 1,
+            _ => {}
+        }
+    }
+    if toggle_type {
+        item.toggle = Some(toggle_state);
+    }
+    item.children = children
+        .inner()
+        .iter()
+        .filter_map(menu_item_from_value)
+        .collect();
+    Some(item)
+}
+
+/// Button, icon and menu popover for one item
+struct TrayItemWidget {
+    button: gtk::Button,
+    image: gtk::Image,
+    popover: gtk::Popover,
+    has_menu: Rc<Cell<bool>>,
+    item_is_menu: Rc<Cell<bool>>,
+}
+
+impl TrayItemWidget {
+    fn new(id: &str, backend: &Rc<SniTrayBackend>) -> Self {
+        let button = gtk::Button::new();
+        button.add_css_class("tray-item");
+        button.set_has_frame(false);
+        let image = gtk::Image::new();
+        button.set_child(Some(&image));
+
+        let popover = gtk::Popover::new();
+        popover.add_css_class("tray-menu");
+        popover.set_position(gtk::PositionType::Bottom);
+        popover.set_autohide(true);
+        popover.set_parent(&button);
+        button.connect_destroy({
+            let popover = popover.clone();
+            move |_| popover.unparent()
+        });
+
+        let has_menu = Rc::new(Cell::new(false));
+        let item_is_menu = Rc::new(Cell::new(false));
+
+        // Left click activates (or opens the menu of menu-only items)
+        {
+            let (id, backend) = (id.to_string(), backend.clone());
+            let (has_menu, item_is_menu) = (has_menu.clone(), item_is_menu.clone());
+            button.connect_clicked(move |_| {
+                if item_is_menu.get() && has_menu.get() {
+                    backend.send(TrayCommand::OpenMenu(id.clone()));
+                } else {
+                    backend.send(TrayCommand::Activate(id.clone()));
+                }
+            });
+        }
+
+        // Right click shows the menu; middle click is the secondary action
+        let other_click = gtk::GestureClick::new();
+        other_click.set_button(0);
+        {
+            let (id, backend, has_menu) = (id.to_string(), backend.clone(), has_menu.clone());
+            other_click.connect_pressed(move |gesture, _n_press, _x, _y| {
+                let command = match gesture.current_button() {
+                    gtk::gdk::BUTTON_SECONDARY if has_menu.get() => {
+                        TrayCommand::OpenMenu(id.clone())
+                    }
+                    gtk::gdk::BUTTON_SECONDARY => TrayCommand::ContextMenu(id.clone()),
+                    gtk::gdk::BUTTON_MIDDLE => TrayCommand::SecondaryActivate(id.clone()),
+                    _ => return,
+                };
+                backend.send(command);
+                gesture.set_state(gtk::EventSequenceState::Claimed);
+            });
+        }
+        button.add_controller(other_click);
+
+        Self {
+            button,
+            image,
+            popover,
+            has_menu,
+            item_is_menu,
+        }
+    }
+
+    fn update(&self, item: &TrayItem, icon_size: i32) {
+        apply_tray_icon(&self.image, item, icon_size);
+
+        let tooltip = if item.tooltip.is_empty() {
+            &item.title
+        } else {
+            &item.tooltip
+        };
+        self.button
+            .set_tooltip_text((!tooltip.is_empty()).then_some(tooltip.as_str()));
+
+        if item.status == TrayItemStatus::NeedsAttention {
+            self.button.add_css_class("needs-attention");
+        } else {
+            self.button.remove_css_class("needs-attention");
+        }
+
+        self.has_menu.set(item.has_menu);
+        self.item_is_menu.set(item.item_is_menu);
+    }
+
+    fn show_menu(&self, id: &str, items: &[TrayMenuItem], backend: &Rc<SniTrayBackend>) {
+        if items.iter().all(|i| !i.visible) {
+            log::debug!("Tray: menu of {} is empty", id);
+            return;
+        }
+        let list = build_menu_box(id, items, backend, &self.popover);
+        self.popover.set_child(Some(&list));
+        self.popover.popup();
+    }
+}
+
+fn apply_tray_icon(image: &gtk::Image, item: &TrayItem, size: i32) {
+    image.set_pixel_size(size);
+    let theme = gtk::gdk::Display::default().map(|d| gtk::IconTheme::for_display(&d));
+    if let Some(theme) = &theme
+        && !item.icon_theme_path.is_empty()
+        && !theme
+            .search_path()
+            .iter()
+            .any(|p| p.as_os_str() == item.icon_theme_path.as_str())
+    {
+        theme.add_search_path(&item.icon_theme_path);
+    }
+    let has_icon = |name: &str| theme.as_ref().is_some_and(|t| t.has_icon(name));
+    match select_icon(item, size, has_icon) {
+        TrayIcon::Named(name) => image.set_icon_name(Some(&name)),
+        TrayIcon::File(path) => image.set_from_file(Some(&path)),
+        TrayIcon::Pixmap(pixmap) => {
+            let bytes = gtk::glib::Bytes::from_owned(argb_to_rgba(&pixmap.data));
+            let texture = gtk::gdk::MemoryTexture::new(
+                pixmap.width,
+                pixmap.height,
+                gtk::gdk::MemoryFormat::R8g8b8a8,
+                &bytes,
+                pixmap.width as usize * 4,
+            );
+            image.set_paintable(Some(&texture));
+        }
+        TrayIcon::Fallback => image.set_icon_name(Some(FALLBACK_ICON)),
+    }
+}
+
+fn build_menu_box(
+    id: &str,
+    items: &[TrayMenuItem],
+    backend: &Rc<SniTrayBackend>,
+    popover: &gtk::Popover,
+) -> gtk::Box {
+    let list = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    for entry in items.iter().filter(|i| i.visible) {
+        if entry.separator {
+            list.append(&gtk::Separator::new(gtk::Orientation::Horizontal));
+            continue;
+        }
+        let label = match entry.toggle {
+            Some(true) => format!("✓ {}", entry.label),
+            Some(false) => format!("    {}", entry.label),
+            None => entry.label.clone(),
+        };
+        if !entry.children.is_empty() {
+            let expander = gtk::Expander::new(Some(&label));
+            expander.add_css_class("tray-submenu");
+            expander.set_sensitive(entry.enabled);
+            expander.set_child(Some(&build_menu_box(id, &entry.children, backend, popover)));
+            list.append(&expander);
+            continue;
+        }
+        let button = gtk::Button::with_label(&label);
+        button.set_has_frame(false);
+        button.add_css_class("tray-menu-item");
+        button.set_sensitive(entry.enabled);
+        if let Some(child) = button.child().and_downcast::<gtk::Label>() {
+            child.set_xalign(0.0);
+        }
+        let (id, entry_id) = (id.to_string(), entry.id);
+        let backend = backend.clone();
+        let popover_weak = popover.downgrade();
+        button.connect_clicked(move |_| {
+            backend.send(TrayCommand::MenuClicked {
+                id: id.clone(),
+                entry: entry_id,
+            });
+            if let Some(popover) = popover_weak.upgrade() {
+                popover.popdown();
+            }
+        });
+        list.append(&button);
+    }
+    list
+}
+
 pub struct TrayModule;
 
 impl TrayModule {
     pub const IDENT: &'static str = "bar.module.tray";
 
-    pub fn create_widget(_settings: &ModuleConfig) -> gtk::Widget {
+    pub fn create_widget(settings: &ModuleConfig) -> gtk::Widget {
+        let icon_size = settings
+            .additional
+            .get("icon_size")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(16)
+            .clamp(8, 128) as i32;
+
         let root = gtk::Box::new(gtk::Orientation::Horizontal, 4);
         root.add_css_class("module-tray");
         root.set_hexpand(false);
         root.set_halign(gtk::Align::End);
+        // Hidden until the first item shows up
+        root.set_visible(false);
+
+        // Host updates are applied on the GTK thread as they arrive
+        let mut state = TrayState::default();
+        let mut widgets: HashMap<String, TrayItemWidget> = HashMap::new();
+        let root_weak = root.downgrade();
+        let backend_slot: Rc<RefCell<Option<Rc<SniTrayBackend>>>> = Rc::default();
+        let handler_backend = backend_slot.clone();
+        let tx = super::gtk_receiver(move |events: Vec<TrayEvent>| {
+            let (Some(root), Some(backend)) =
+                (root_weak.upgrade(), handler_backend.borrow().clone())
+            else {
+                return glib::ControlFlow::Break;
+            };
+            for event in events {
+                match &event {
+                    TrayEvent::Upsert(item) => {
+                        if !state.apply(&event) {
+                            continue;
+                        }
+                        if state.get(&item.id).is_none() {
+                            // Went passive
+                            if let Some(widget) = widgets.remove(&item.id) {
+                                root.remove(&widget.button);
+                            }
+                            continue;
+                        }
+                        let widget = widgets.entry(item.id.clone()).or_insert_with(|| {
+                            let widget = TrayItemWidget::new(&item.id, &backend);
+                            root.append(&widget.button);
+                            widget
+                        });
+                        widget.update(item, icon_size);
+                    }
+                    TrayEvent::Removed(id) => {
+                        state.apply(&event);
+                        if let Some(widget) = widgets.remove(id) {
+                            root.remove(&widget.button);
+                        }
+                    }
+                    TrayEvent::Menu { id, items } => {
+                        if let Some(widget) = widgets.get(id) {
+                            widget.show_menu(id, items, &backend);
+                        }
+                    }
+                }
+            }
+            root.set_visible(!state.items().is_empty());
+            glib::ControlFlow::Continue
+        });
+        let backend = Rc::new(SniTrayBackend::spawn(tx));
+        backend_slot.replace(Some(backend.clone()));
 
-        // Placeholder label until SNI host is wired
-        let lbl = gtk::Label::new(Some("tray"));
-        root.append(&lbl);
+        // The host and its listener threads live as long as the widget
+        root.connect_destroy(move |_| {
+            backend.stop();
+            backend_slot.take();
+        });
 
         root.upcast()
     }
 }
+
+impl BarModule for TrayModule {
+    fn id(&self) -> &'static str {
+        Self::IDENT
+    }
+    fn create(&self, settings: &ModuleConfig) -> gtk::Widget {
+        Self::create_widget(settings)
+    }
+}
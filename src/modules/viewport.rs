@@ -1,12 +1,18 @@
 use gtk4 as gtk;
 use gtk4::prelude::*;
 // use gdk_pixbuf::Pixbuf;
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+use ashpd::WindowIdentifier;
+use ashpd::desktop::screencast::{CursorMode, Screencast, SourceType};
+use ashpd::desktop::{PersistMode, ResponseError};
 use glib;
+use pipewire as pw;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::os::fd::OwnedFd;
 use std::rc::Rc;
-use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 // Type aliases for complex types
 type WindowColumnData = (i64, i64, f64, f64, String, bool); // (win_id, y_index, w_px, h_px, title, is_focused)
@@ -14,7 +20,7 @@ type WindowColumnMap = std::collections::BTreeMap<i64, Vec<WindowColumnData>>;
 type OrderedColumns = Vec<(i64, Vec<WindowColumnData>)>;
 
 use crate::config::ModuleConfig;
-use crate::niri::niri_bus;
+use crate::niri::{NiriEvent, focus_window, niri_bus};
 
 /// Represents a window with its layout information for viewport rendering
 #[derive(Debug, Clone)]
@@ -29,9 +35,213 @@ pub struct WindowLayout {
     pub height: f64,
 }
 
-/// Screen capture manager (simplified version without actual PipeWire integration)
+/// Window layouts as last drawn (bottom to top) and the canvas size they were drawn at,
+/// so clicks hit exactly what is visible
+#[derive(Debug, Clone, Default)]
+pub struct DrawnViewport {
+    pub layouts: Vec<WindowLayout>,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl DrawnViewport {
+    /// Window under a click at widget pixel (`px`, `py`), converted back to normalized
+    /// space; the topmost drawn window wins
+    pub fn hit_test(&self, px: f64, py: f64) -> Option<i64> {
+        if self.width <= 0.0 || self.height <= 0.0 {
+            return None;
+        }
+        let (nx, ny) = (px / self.width, py / self.height);
+        self.layouts
+            .iter()
+            .rev()
+            .find(|l| nx >= l.x && nx < l.x + l.width && ny >= l.y && ny < l.y + l.height)
+            .map(|l| l.id)
+    }
+}
+
+/// Captured frames are downscaled to at most this width before they reach the UI
+pub const MAX_FRAME_WIDTH: u32 = 480;
+/// Minimum time between two decoded frames; the minimap doesn't need full frame rate
+const FRAME_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A decoded screen frame: tightly packed RGBA, `width * height * 4` bytes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pub data: Arc<Vec<u8>>,
+}
+
+/// Anything that can hand the viewport its latest frame; lets tests inject fake frames
+pub trait FrameSource {
+    fn current_frame(&self) -> Option<Frame>;
+}
+
+/// Pixel layouts negotiated with the ScreenCast stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapturePixelFormat {
+    Rgba,
+    Rgbx,
+    Bgra,
+    Bgrx,
+}
+
+impl CapturePixelFormat {
+    fn from_video_format(format: pw::spa::param::video::VideoFormat) -> Option<Self> {
+        use pw::spa::param::video::VideoFormat;
+        match format {
+            VideoFormat::RGBA => Some(Self::Rgba),
+            VideoFormat::RGBx => Some(Self::Rgbx),
+            VideoFormat::BGRA => Some(Self::Bgra),
+            VideoFormat::BGRx => Some(Self::Bgrx),
+            _ => None,
+        }
+    }
+
+    /// RGBA of the pixel starting at `px`; the `x` formats are treated as opaque
+    fn to_rgba(self, px: &[u8]) -> [u8; 4] {
+        match self {
+            Self::Rgba => [px[0], px[1], px[2], px[3]],
+            Self::Rgbx => [px[0], px[1], px[2], 255],
+            Self::Bgra => [px[2], px[1], px[0], px[3]],
+            Self::Bgrx => [px[2], px[1], px[0], 255],
+        }
+    }
+}
+
+/// Size of a `width`×`height` frame after downscaling to at most `max_width` pixels wide
+pub fn scaled_frame_size(width: u32, height: u32, max_width: u32) -> (u32, u32) {
+    if width <= max_width || max_width == 0 {
+        return (width, height);
+    }
+    let h = (height as u64 * max_width as u64 + width as u64 / 2) / width as u64;
+    (max_width, (h as u32).max(1))
+}
+
+/// Convert a captured buffer (rows `stride` bytes apart) into a tightly packed RGBA frame,
+/// downscaled with nearest-neighbour sampling; `None` if the buffer is too short
+pub fn decode_frame(
+    src: &[u8],
+    format: CapturePixelFormat,
+    width: u32,
+    height: u32,
+    stride: usize,
+    max_width: u32,
+) -> Option<Frame> {
+    if width == 0 || height == 0 || stride < width as usize * 4 {
+        return None;
+    }
+    let needed = (height as usize - 1) * stride + width as usize * 4;
+    if src.len() < needed {
+        return None;
+    }
+    let (out_w, out_h) = scaled_frame_size(width, height, max_width);
+    let mut data = Vec::with_capacity(out_w as usize * out_h as usize * 4);
+    for y in 0..out_h as usize {
+        let sy = y * height as usize / out_h as usize;
+        for x in 0..out_w as usize {
+            let sx = x * width as usize / out_w as usize;
+            let at = sy * stride + sx * 4;
+            data.extend_from_slice(&format.to_rgba(&src[at..at + 4]));
+        }
+    }
+    Some(Frame {
+        width: out_w,
+        height: out_h,
+        data: Arc::new(data),
+    })
+}
+
+/// Frame pixels in cairo's `ARgb32` layout (native-endian, premultiplied alpha)
+pub fn frame_to_cairo_argb(frame: &Frame) -> Vec<u8> {
+    let mut out = Vec::with_capacity(frame.data.len());
+    for px in frame.data.chunks_exact(4) {
+        let a = px[3] as u32;
+        let premul = |c: u8| (c as u32 * a + 127) / 255;
+        let argb = (a << 24) | (premul(px[0]) << 16) | (premul(px[1]) << 8) | premul(px[2]);
+        out.extend_from_slice(&argb.to_ne_bytes());
+    }
+    out
+}
+
+/// Transform that makes a frame cover the whole canvas, cropping the overflow evenly
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameFit {
+    pub scale: f64,
+    pub offset_x: f64,
+    pub offset_y: f64,
+}
+
+/// Scale and offset to paint a `frame_w`×`frame_h` frame over an `area_w`×`area_h` canvas,
+/// keeping its aspect ratio; `None` when either size is empty
+pub fn fit_frame(frame_w: f64, frame_h: f64, area_w: f64, area_h: f64) -> Option<FrameFit> {
+    if frame_w <= 0.0 || frame_h <= 0.0 || area_w <= 0.0 || area_h <= 0.0 {
+        return None;
+    }
+    let scale = (area_w / frame_w).max(area_h / frame_h);
+    Some(FrameFit {
+        scale,
+        offset_x: (area_w - frame_w * scale) / 2.0,
+        offset_y: (area_h - frame_h * scale) / 2.0,
+    })
+}
+
+/// Narrowest the canvas gets when sized from the workspace aspect ratio
+pub const MIN_VIEWPORT_WIDTH: i32 = 40;
+/// Width changes at or below this many pixels are ignored, so rounding doesn't relayout
+const RESIZE_TOLERANCE: i32 = 2;
+
+/// Canvas width matching the workspace aspect ratio at `area_height`, when it differs from
+/// `current_width` by more than a couple of pixels; `None` when no resize is needed or
+/// either size is unknown
+pub fn viewport_target_width(
+    area_height: f64,
+    workspace_width: f64,
+    workspace_height: f64,
+    current_width: i32,
+) -> Option<i32> {
+    if area_height <= 0.0 || workspace_width <= 0.0 || workspace_height <= 0.0 {
+        return None;
+    }
+    let target =
+        ((area_height * workspace_width / workspace_height).round() as i32).max(MIN_VIEWPORT_WIDTH);
+    ((current_width - target).abs() > RESIZE_TOLERANCE).then_some(target)
+}
+
+/// Whether a bus event can change what the viewport shows: the focused workspace, its
+/// windows, their tiles or the focused window
+pub fn viewport_event_is_relevant(event: &NiriEvent) -> bool {
+    match event {
+        NiriEvent::WindowsChanged
+        | NiriEvent::FocusChanged(_)
+        | NiriEvent::WorkspacesChanged
+        | NiriEvent::WindowLayoutsChanged
+        | NiriEvent::Synchronized => true,
+        NiriEvent::KeyboardLayoutChanged | NiriEvent::OverviewToggled(_) => false,
+    }
+}
+
+/// Lifecycle of the portal session behind [`ScreenCapture`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureState {
+    Idle,
+    Starting,
+    Running,
+    /// The user (or portal policy) refused the screencast; we stay on synthetic rendering
+    Denied,
+    Failed,
+}
+
+/// Restore token from the last granted session, so later starts don't prompt again
+static RESTORE_TOKEN: Mutex<Option<String>> = Mutex::new(None);
+
+/// Screen capture via the xdg-desktop-portal ScreenCast interface and a PipeWire stream.
+/// Frames are decoded on a background thread; the UI only reads the latest one.
 pub struct ScreenCapture {
-    _placeholder: bool,
+    latest: Arc<Mutex<Option<Frame>>>,
+    state: Arc<Mutex<CaptureState>>,
+    stop: Mutex<Option<pw::channel::Sender<()>>>,
 }
 
 impl Default for ScreenCapture {
@@ -42,25 +252,333 @@ impl Default for ScreenCapture {
 
 impl ScreenCapture {
     pub fn new() -> Self {
-        Self { _placeholder: true }
+        Self {
+            latest: Arc::new(Mutex::new(None)),
+            state: Arc::new(Mutex::new(CaptureState::Idle)),
+            stop: Mutex::new(None),
+        }
     }
 
-    /// Start screen capture (placeholder implementation)
+    pub fn state(&self) -> CaptureState {
+        self.state
+            .lock()
+            .map(|s| *s)
+            .unwrap_or(CaptureState::Failed)
+    }
+
+    fn set_state(&self, state: CaptureState) {
+        if let Ok(mut s) = self.state.lock() {
+            *s = state;
+        }
+    }
+
+    /// Ask the portal for a monitor stream and start decoding it. Does nothing while a capture
+    /// is running or after the user denied it; a denial is not an error.
     pub async fn start_capture(&self) -> Result<()> {
-        log::info!("Viewport: Screen capture would start here (placeholder)");
+        if matches!(
+            self.state(),
+            CaptureState::Starting | CaptureState::Running | CaptureState::Denied
+        ) {
+            return Ok(());
+        }
+        self.set_state(CaptureState::Starting);
+
+        let (fd, node_id) = match open_screencast().await {
+            Ok(v) => v,
+            Err(e) if is_permission_denied(&e) => {
+                self.set_state(CaptureState::Denied);
+                log::warn!("Viewport: screen capture was denied, using synthetic rendering");
+                return Ok(());
+            }
+            Err(e) => {
+                self.set_state(CaptureState::Failed);
+                return Err(e);
+            }
+        };
+
+        let (stop_tx, stop_rx) = pw::channel::channel::<()>();
+        if let Ok(mut stop) = self.stop.lock() {
+            *stop = Some(stop_tx);
+        }
+        let latest = Arc::clone(&self.latest);
+        let state = Arc::clone(&self.state);
+        self.set_state(CaptureState::Running);
+        std::thread::spawn(move || {
+            let result = run_capture_stream(fd, node_id, Arc::clone(&latest), stop_rx);
+            if let Ok(mut latest) = latest.lock() {
+                *latest = None;
+            }
+            if let Ok(mut s) = state.lock() {
+                *s = match result {
+                    Ok(()) => CaptureState::Idle,
+                    Err(e) => {
+                        log::error!("Viewport: ❌ PipeWire capture stopped: {}", e);
+                        CaptureState::Failed
+                    }
+                };
+            }
+        });
+        log::info!("Viewport: 📷 screen capture started (node {})", node_id);
         Ok(())
     }
 
     pub fn stop_capture(&self) {
-        log::info!("Viewport: Screen capture would stop here (placeholder)");
+        if let Some(stop) = self.stop.lock().ok().and_then(|mut s| s.take()) {
+            let _ = stop.send(());
+            log::info!("Viewport: screen capture stopped");
+        }
+        if let Ok(mut latest) = self.latest.lock() {
+            *latest = None;
+        }
     }
 
-    pub fn get_current_frame(&self) -> Option<()> {
-        // Placeholder - would return actual frame data
-        None
+    /// Latest decoded frame, if capture is running and has produced one
+    pub fn get_current_frame(&self) -> Option<Frame> {
+        self.latest.lock().ok().and_then(|f| f.clone())
     }
 }
 
+impl FrameSource for ScreenCapture {
+    fn current_frame(&self) -> Option<Frame> {
+        self.get_current_frame()
+    }
+}
+
+impl Drop for ScreenCapture {
+    fn drop(&mut self) {
+        self.stop_capture();
+    }
+}
+
+/// A live frame source the viewports share; [`ScreenCapture`] unless another is installed
+/// with [`set_live_capture`] (tests, or sessions without a portal)
+pub trait LiveCapture: FrameSource {
+    /// Start capturing if it isn't already; repeated calls must not prompt again
+    fn ensure_started(self: Rc<Self>);
+    fn state(&self) -> CaptureState;
+}
+
+impl LiveCapture for ScreenCapture {
+    fn ensure_started(self: Rc<Self>) {
+        if self.state() != CaptureState::Idle {
+            return;
+        }
+        glib::spawn_future_local(async move {
+            if let Err(e) = self.start_capture().await {
+                log::error!("Viewport: Failed to start screen capture: {}", e);
+            }
+        });
+    }
+
+    fn state(&self) -> CaptureState {
+        ScreenCapture::state(self)
+    }
+}
+
+thread_local! {
+    /// One capture for the whole process, so every bar's viewport shares a single portal
+    /// request instead of each asking on its own
+    static LIVE_CAPTURE: RefCell<Option<Rc<dyn LiveCapture>>> = const { RefCell::new(None) };
+}
+
+/// Replace the process-wide capture the viewports draw from
+pub fn set_live_capture(capture: Rc<dyn LiveCapture>) {
+    LIVE_CAPTURE.with(|c| *c.borrow_mut() = Some(capture));
+}
+
+/// The process-wide capture, created on first use
+pub fn live_capture() -> Rc<dyn LiveCapture> {
+    LIVE_CAPTURE.with(|c| {
+        c.borrow_mut()
+            .get_or_insert_with(|| Rc::new(ScreenCapture::new()))
+            .clone()
+    })
+}
+
+fn is_permission_denied(e: &anyhow::Error) -> bool {
+    matches!(
+        e.downcast_ref::<ashpd::Error>(),
+        Some(ashpd::Error::Response(ResponseError::Cancelled))
+            | Some(ashpd::Error::Portal(ashpd::PortalError::NotAllowed(_)))
+    )
+}
+
+/// Run the portal handshake; returns the PipeWire remote and the monitor's node id
+async fn open_screencast() -> Result<(OwnedFd, u32)> {
+    let proxy = Screencast::new().await?;
+    let session = proxy.create_session().await?;
+    let restore_token = RESTORE_TOKEN.lock().ok().and_then(|t| t.clone());
+    proxy
+        .select_sources(
+            &session,
+            CursorMode::Hidden,
+            SourceType::Monitor.into(),
+            false,
+            restore_token.as_deref(),
+            PersistMode::Application,
+        )
+        .await?
+        .response()?;
+    let response = proxy
+        .start(&session, &WindowIdentifier::default())
+        .await?
+        .response()?;
+    if let Some(token) = response.restore_token()
+        && let Ok(mut t) = RESTORE_TOKEN.lock()
+    {
+        *t = Some(token.to_string());
+    }
+    let node_id = response
+        .streams()
+        .first()
+        .map(|s| s.pipe_wire_node_id())
+        .ok_or_else(|| anyhow!("portal returned no screencast stream"))?;
+    let fd = proxy.open_pipe_wire_remote(&session).await?;
+    Ok((fd, node_id))
+}
+
+/// Stream state for the PipeWire callbacks
+struct CaptureStreamData {
+    format: pw::spa::param::video::VideoInfoRaw,
+    pixel_format: Option<CapturePixelFormat>,
+    last_frame: Option<Instant>,
+}
+
+/// Consume the portal's PipeWire node until `stop` fires, publishing frames into `latest`
+fn run_capture_stream(
+    fd: OwnedFd,
+    node_id: u32,
+    latest: Arc<Mutex<Option<Frame>>>,
+    stop: pw::channel::Receiver<()>,
+) -> Result<()> {
+    use pw::spa::param::format::{FormatProperties, MediaSubtype, MediaType};
+    use pw::spa::param::video::VideoFormat;
+
+    pw::init();
+    let mainloop = pw::main_loop::MainLoop::new(None)?;
+    let context = pw::context::Context::new(&mainloop)?;
+    let core = context.connect_fd(fd, None)?;
+    let _stop = stop.attach(mainloop.loop_(), {
+        let mainloop = mainloop.clone();
+        move |_| mainloop.quit()
+    });
+
+    let stream = pw::stream::Stream::new(
+        &core,
+        "niri-bar-viewport",
+        pw::properties::properties! {
+            *pw::keys::MEDIA_TYPE => "Video",
+            *pw::keys::MEDIA_CATEGORY => "Capture",
+            *pw::keys::MEDIA_ROLE => "Screen",
+        },
+    )?;
+
+    let data = CaptureStreamData {
+        format: Default::default(),
+        pixel_format: None,
+        last_frame: None,
+    };
+    let _listener = stream
+        .add_local_listener_with_user_data(data)
+        .param_changed(|_, data, id, param| {
+            let Some(param) = param else { return };
+            if id != pw::spa::param::ParamType::Format.as_raw() {
+                return;
+            }
+            match pw::spa::param::format_utils::parse_format(param) {
+                Ok((MediaType::Video, MediaSubtype::Raw)) => {}
+                _ => return,
+            }
+            if data.format.parse(param).is_ok() {
+                data.pixel_format = CapturePixelFormat::from_video_format(data.format.format());
+                log::debug!(
+                    "Viewport: capture format {:?} {}x{}",
+                    data.format.format(),
+                    data.format.size().width,
+                    data.format.size().height
+                );
+            }
+        })
+        .process(move |stream, data| {
+            let Some(mut buffer) = stream.dequeue_buffer() else {
+                return;
+            };
+            if data
+                .last_frame
+                .is_some_and(|t| t.elapsed() < FRAME_INTERVAL)
+            {
+                return;
+            }
+            let Some(pixel_format) = data.pixel_format else {
+                return;
+            };
+            let size = data.format.size();
+            let Some(plane) = buffer.datas_mut().first_mut() else {
+                return;
+            };
+            let offset = plane.chunk().offset() as usize;
+            let stride = match plane.chunk().stride() {
+                s if s > 0 => s as usize,
+                _ => size.width as usize * 4,
+            };
+            let Some(bytes) = plane.data() else { return };
+            let Some(bytes) = bytes.get(offset..) else {
+                return;
+            };
+            if let Some(frame) = decode_frame(
+                bytes,
+                pixel_format,
+                size.width,
+                size.height,
+                stride,
+                MAX_FRAME_WIDTH,
+            ) {
+                if let Ok(mut latest) = latest.lock() {
+                    *latest = Some(frame);
+                }
+                data.last_frame = Some(Instant::now());
+            }
+        })
+        .register()?;
+
+    let format = pw::spa::pod::object!(
+        pw::spa::utils::SpaTypes::ObjectParamFormat,
+        pw::spa::param::ParamType::EnumFormat,
+        pw::spa::pod::property!(FormatProperties::MediaType, Id, MediaType::Video),
+        pw::spa::pod::property!(FormatProperties::MediaSubtype, Id, MediaSubtype::Raw),
+        pw::spa::pod::property!(
+            FormatProperties::VideoFormat,
+            Choice,
+            Enum,
+            Id,
+            VideoFormat::BGRx,
+            VideoFormat::BGRx,
+            VideoFormat::BGRA,
+            VideoFormat::RGBx,
+            VideoFormat::RGBA,
+        ),
+    );
+    let bytes: Vec<u8> = pw::spa::pod::serialize::PodSerializer::serialize(
+        std::io::Cursor::new(Vec::new()),
+        &pw::spa::pod::Value::Object(format),
+    )
+    .map_err(|e| anyhow!("failed to serialize capture format: {:?}", e))?
+    .0
+    .into_inner();
+    let mut params = [pw::spa::pod::Pod::from_bytes(&bytes)
+        .ok_or_else(|| anyhow!("invalid capture format pod"))?];
+
+    stream.connect(
+        pw::spa::utils::Direction::Input,
+        Some(node_id),
+        pw::stream::StreamFlags::AUTOCONNECT | pw::stream::StreamFlags::MAP_BUFFERS,
+        &mut params,
+    )?;
+
+    mainloop.run();
+    Ok(())
+}
+
 /// Viewport module that displays a live view of the current workspace
 pub struct ViewportModule;
 
@@ -70,6 +588,11 @@ impl ViewportModule {
     pub fn create_widget(settings: &ModuleConfig) -> gtk::Widget {
         let _show_window_titles = settings.show_window_titles.unwrap_or(true);
         let highlight_focused = settings.highlight_focused.unwrap_or(true);
+        let live_preview = settings
+            .additional
+            .get("live_preview")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
         // update_rate_ms removed; event-driven updates via NiriBus notifications
 
         // Create the main container
@@ -93,85 +616,152 @@ impl ViewportModule {
             drawing_area.set_size_request(80, -1); // Minimum width, height will be set by bar
         }
 
-        // Create screen capture manager
-        let screen_capture = Rc::new(RefCell::new(ScreenCapture::new()));
+        // Frames come from the process-wide capture; the portal is only asked when
+        // live_preview is on, once for all bars
+        let screen_capture = live_capture();
+        if live_preview {
+            Rc::clone(&screen_capture).ensure_started();
+        }
 
         // Track current workspace and windows
         let current_workspace_id = Rc::new(RefCell::new(None::<i64>));
         let window_layouts = Rc::new(RefCell::new(HashMap::<i64, WindowLayout>::new()));
         let focused_window_id = Rc::new(RefCell::new(None::<i64>));
+        let drawn = Rc::new(RefCell::new(DrawnViewport::default()));
 
         // Set up drawing function
         {
             let window_layouts_ref = Rc::clone(&window_layouts);
             let focused_window_ref = Rc::clone(&focused_window_id);
             let screen_capture_ref = Rc::clone(&screen_capture);
+            let drawn_ref = Rc::clone(&drawn);
             let _drawing_area_weak = drawing_area.downgrade();
 
             drawing_area.set_draw_func(move |_area, cr, width, height| {
-                Self::draw_viewport(
+                let frame = screen_capture_ref.current_frame();
+                let last = Self::draw_viewport(
                     cr,
                     width,
                     height,
                     &window_layouts_ref,
                     &focused_window_ref,
-                    &screen_capture_ref,
+                    frame.as_ref(),
                     highlight_focused,
                 );
+                if let Ok(mut drawn) = drawn_ref.try_borrow_mut() {
+                    *drawn = last;
+                }
             });
         }
 
-        // Subscribe to NiriBus updates; redraw immediately on events (no polling)
+        // Click a window to focus it
+        {
+            let click = gtk::GestureClick::new();
+            click.set_button(gtk::gdk::BUTTON_PRIMARY);
+            let drawn_ref = Rc::clone(&drawn);
+            click.connect_pressed(move |gesture, _n_press, x, y| {
+                let hit = drawn_ref.try_borrow().ok().and_then(|d| d.hit_test(x, y));
+                if let Some(id) = hit {
+                    log::debug!("Viewport: 🖱️ focus window {}", id);
+                    if let Err(e) = focus_window(id) {
+                        log::error!("Viewport: ❌ failed to focus window {}: {}", id, e);
+                    }
+                    gesture.set_state(gtk::EventSequenceState::Claimed);
+                }
+            });
+            drawing_area.add_controller(click);
+        }
+
+        // Subscribe to NiriBus updates; redraw only when an event arrives or the canvas is
+        // resized (no polling, no idle loop)
         {
-            let drawing_area_weak = drawing_area.downgrade();
-            let current_workspace_ref = Rc::clone(&current_workspace_id);
-            let window_layouts_ref = Rc::clone(&window_layouts);
-            let focused_window_ref = Rc::clone(&focused_window_id);
-            let screen_capture_ref = Rc::clone(&screen_capture);
             let fixed_width_opt = settings.width;
+            let refresh = {
+                let current_workspace_ref = Rc::clone(&current_workspace_id);
+                let window_layouts_ref = Rc::clone(&window_layouts);
+                let focused_window_ref = Rc::clone(&focused_window_id);
+                move |area: &gtk::DrawingArea| {
+                    if let Some((workspace_width, workspace_height)) = Self::update_viewport_state(
+                        &current_workspace_ref,
+                        &window_layouts_ref,
+                        &focused_window_ref,
+                    ) && fixed_width_opt.is_none()
+                    {
+                        let current_height = area.allocated_height() as f64;
+                        if let Some(target_width) = viewport_target_width(
+                            current_height,
+                            workspace_width,
+                            workspace_height,
+                            area.allocated_width(),
+                        ) {
+                            area.set_size_request(target_width, -1);
+                            log::debug!(
+                                "Viewport: Resized to {}x{} (aspect ratio: {:.2})",
+                                target_width,
+                                current_height as i32,
+                                workspace_width / workspace_height
+                            );
+                        }
+                    }
+                    area.queue_draw();
+                }
+            };
 
-            let (tx, rx) = mpsc::channel::<()>();
-            // Only register with niri_bus if not in test environment
-            if !cfg!(test) {
-                crate::niri::niri_bus().register_ui_listener(tx);
+            // Redraw once per batch of bus events that touch the viewport
+            {
+                let drawing_area_weak = drawing_area.downgrade();
+                let refresh = refresh.clone();
+                super::on_bus_events(move |events| {
+                    let Some(area) = drawing_area_weak.upgrade() else {
+                        return glib::ControlFlow::Break;
+                    };
+                    if events.iter().any(viewport_event_is_relevant) {
+                        refresh(&area);
+                    }
+                    glib::ControlFlow::Continue
+                });
             }
 
-            // Only set up event loop if not in test environment
-            if !cfg!(test) {
-                glib::source::idle_add_local(move || {
-                    while rx.try_recv().is_ok() {}
-                    if let Some(area) = drawing_area_weak.upgrade() {
-                        if let Some((workspace_width, workspace_height)) =
-                            Self::update_viewport_state(
-                                &current_workspace_ref,
-                                &window_layouts_ref,
-                                &focused_window_ref,
-                                &screen_capture_ref,
-                            )
-                            && fixed_width_opt.is_none()
-                        {
-                            let current_height = area.allocated_height() as f64;
-                            if current_height > 0.0 {
-                                let workspace_aspect_ratio = workspace_width / workspace_height;
-                                let target_width =
-                                    (current_height * workspace_aspect_ratio).round() as i32;
-                                let target_width = target_width.max(40);
-                                if (area.allocated_width() - target_width).abs() > 2 {
-                                    area.set_size_request(target_width, -1);
-                                    log::debug!(
-                                        "Viewport: Resized to {}x{} (aspect ratio: {:.2})",
-                                        target_width,
-                                        current_height as i32,
-                                        workspace_aspect_ratio
-                                    );
-                                }
-                            }
+            // The bar height decides the aspect-ratio width, so follow size allocations too.
+            // Deferred: the resize signal fires mid-allocation, too late to request a size.
+            drawing_area.connect_resize({
+                let refresh = refresh.clone();
+                move |area, _width, _height| {
+                    let area_weak = area.downgrade();
+                    let refresh = refresh.clone();
+                    glib::idle_add_local_once(move || {
+                        if let Some(area) = area_weak.upgrade() {
+                            refresh(&area);
                         }
-                        area.queue_draw();
+                    });
+                }
+            });
+
+            // Live frames arrive off the bus; repaint at the capture rate while they do
+            if live_preview {
+                let drawing_area_weak = drawing_area.downgrade();
+                let capture = Rc::clone(&screen_capture);
+                glib::timeout_add_local(FRAME_INTERVAL, move || {
+                    let Some(area) = drawing_area_weak.upgrade() else {
+                        return glib::ControlFlow::Break;
+                    };
+                    match capture.state() {
+                        CaptureState::Starting | CaptureState::Running => {
+                            area.queue_draw();
+                            glib::ControlFlow::Continue
+                        }
+                        CaptureState::Idle => glib::ControlFlow::Continue,
+                        CaptureState::Denied | CaptureState::Failed => glib::ControlFlow::Break,
                     }
-                    glib::ControlFlow::Continue
                 });
             }
+
+            let drawing_area_weak = drawing_area.downgrade();
+            glib::idle_add_local_once(move || {
+                if let Some(area) = drawing_area_weak.upgrade() {
+                    refresh(&area);
+                }
+            });
         }
 
         container.append(&drawing_area);
@@ -183,7 +773,6 @@ impl ViewportModule {
         current_workspace_id: &Rc<RefCell<Option<i64>>>,
         window_layouts: &Rc<RefCell<HashMap<i64, WindowLayout>>>,
         focused_window_id: &Rc<RefCell<Option<i64>>>,
-        screen_capture: &Rc<RefCell<ScreenCapture>>,
     ) -> Option<(f64, f64)> {
         // Returns workspace dimensions for aspect ratio calculation
         let bus = niri_bus();
@@ -193,14 +782,11 @@ impl ViewportModule {
         let focused_workspace = workspaces.iter().find(|ws| ws.is_focused);
 
         if let Some(workspace) = focused_workspace {
-            let mut needs_capture_restart = false;
-
             // Check if workspace changed
             if let Ok(mut current_ws) = current_workspace_id.try_borrow_mut()
                 && current_ws.as_ref() != Some(&workspace.id)
             {
                 *current_ws = Some(workspace.id);
-                needs_capture_restart = true;
                 log::info!("Viewport: Workspace changed to {}", workspace.id);
             }
 
@@ -318,39 +904,27 @@ impl ViewportModule {
                 // Return workspace dimensions for aspect ratio calculation
                 return Some((workspace_width, workspace_height));
             }
-
-            // Restart screen capture if workspace changed
-            if needs_capture_restart && let Ok(capture) = screen_capture.try_borrow() {
-                capture.stop_capture();
-
-                // Start new capture asynchronously
-                let capture_clone = screen_capture.clone();
-                #[allow(clippy::await_holding_refcell_ref)]
-                glib::spawn_future_local(async move {
-                    if let Ok(capture) = capture_clone.try_borrow() {
-                        let result = capture.start_capture().await;
-                        if let Err(e) = result {
-                            log::error!("Viewport: Failed to start screen capture: {}", e);
-                        }
-                    }
-                });
-            }
         }
         None
     }
 
-    /// Draw the viewport with windows and highlights
+    /// Draw the viewport with windows and highlights; returns what was drawn for hit-testing
     fn draw_viewport(
         cr: &cairo::Context,
         width: i32,
         height: i32,
         window_layouts: &Rc<RefCell<HashMap<i64, WindowLayout>>>,
         focused_window_id: &Rc<RefCell<Option<i64>>>,
-        _screen_capture: &Rc<RefCell<ScreenCapture>>,
+        frame: Option<&Frame>,
         highlight_focused: bool,
-    ) {
+    ) -> DrawnViewport {
         let width_f = width as f64;
         let height_f = height as f64;
+        let mut drawn = DrawnViewport {
+            layouts: Vec::new(),
+            width: width_f,
+            height: height_f,
+        };
 
         // Set up the drawing context
         cr.set_antialias(cairo::Antialias::Best);
@@ -366,6 +940,28 @@ impl ViewportModule {
         cr.set_source(&pattern).unwrap();
         cr.paint().unwrap();
 
+        // Blit the latest captured frame under the window overlays
+        let mut has_frame = false;
+        if let Some(frame) = frame
+            && let Some(fit) = fit_frame(frame.width as f64, frame.height as f64, width_f, height_f)
+            && let Ok(surface) = cairo::ImageSurface::create_for_data(
+                frame_to_cairo_argb(frame),
+                cairo::Format::ARgb32,
+                frame.width as i32,
+                frame.height as i32,
+                frame.width as i32 * 4,
+            )
+        {
+            cr.save().unwrap();
+            cr.translate(fit.offset_x, fit.offset_y);
+            cr.scale(fit.scale, fit.scale);
+            if cr.set_source_surface(&surface, 0.0, 0.0).is_ok() {
+                cr.paint().unwrap();
+                has_frame = true;
+            }
+            cr.restore().unwrap();
+        }
+
         // Draw window overlays and highlights
         if let Ok(layouts) = window_layouts.try_borrow() {
             let focused_id = focused_window_id.try_borrow().ok().and_then(|f| *f);
@@ -378,6 +974,7 @@ impl ViewportModule {
                 b_focused.cmp(&a_focused) // Focused windows last (drawn on top)
             });
 
+            drawn.layouts = sorted_layouts.iter().copied().cloned().collect();
             for layout in sorted_layouts {
                 let x = (layout.x * width_f).round();
                 let y = (layout.y * height_f).round();
@@ -386,13 +983,14 @@ impl ViewportModule {
 
                 let is_focused = highlight_focused && Some(layout.id) == focused_id;
 
-                // Draw window background
+                // Draw window background (a light tint over a captured frame)
+                let fill_alpha = if has_frame { 0.25 } else { 1.0 };
                 if is_focused {
                     // Focused window background - slightly brighter
-                    cr.set_source_rgba(0.25, 0.35, 0.45, 0.9);
+                    cr.set_source_rgba(0.25, 0.35, 0.45, 0.9 * fill_alpha);
                 } else {
                     // Normal window background
-                    cr.set_source_rgba(0.20, 0.25, 0.30, 0.8);
+                    cr.set_source_rgba(0.20, 0.25, 0.30, 0.8 * fill_alpha);
                 }
                 cr.rectangle(x, y, w, h);
                 cr.fill().unwrap();
@@ -448,5 +1046,6 @@ impl ViewportModule {
                 }
             }
         }
+        drawn
     }
 }
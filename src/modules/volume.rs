@@ -0,0 +1,372 @@
+use anyhow::{Result, anyhow};
+use gtk4 as gtk;
+use gtk4::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::io::{Read, Write};
+use std::os::fd::AsRawFd;
+use std::os::unix::net::UnixStream;
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use tokio::sync::mpsc::UnboundedSender;
+
+use libpulse_binding as pulse;
+use pulse::callbacks::ListResult;
+use pulse::context::subscribe::InterestMaskSet;
+use pulse::context::{Context, FlagSet as ContextFlagSet, State as ContextState};
+use pulse::mainloop::api::Mainloop as _;
+use pulse::mainloop::events::io::FlagSet as IoEventFlagSet;
+use pulse::mainloop::standard::{IterateResult, Mainloop};
+use pulse::volume::{ChannelVolumes, Volume};
+
+use super::{BarModule, ScrollThrottle, scroll_throttle_ms};
+use crate::config::ModuleConfig;
+
+const DEFAULT_SINK: &str = "@DEFAULT_SINK@";
+
+/// Default sink volume (percent of nominal) and mute state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VolumeState {
+    pub volume: u32,
+    pub muted: bool,
+}
+
+/// Audio backend used by the volume module; implemented for PulseAudio, mockable in tests
+pub trait VolumeBackend {
+    /// Change the default sink volume by `delta_percent` (may be negative)
+    fn adjust_volume(&self, delta_percent: i32);
+    /// Flip the default sink mute state
+    fn toggle_mute(&self);
+}
+
+/// Maps widget input (scroll, click) to backend calls
+pub struct VolumeController<B: VolumeBackend> {
+    backend: B,
+    step: i32,
+}
+
+impl<B: VolumeBackend> VolumeController<B> {
+    pub fn new(backend: B, step: u32) -> Self {
+        Self {
+            backend,
+            step: step.max(1) as i32,
+        }
+    }
+
+    /// Scroll up raises, scroll down lowers; returns false for negligible movement
+    pub fn handle_scroll(&self, dy: f64) -> bool {
+        if dy.abs() <= 0.1 {
+            return false;
+        }
+        let delta = if dy < 0.0 { self.step } else { -self.step };
+        self.backend.adjust_volume(delta);
+        true
+    }
+
+    pub fn handle_click(&self) {
+        self.backend.toggle_mute();
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+}
+
+/// Apply a percent delta, clamped to `0..=max`
+pub fn apply_volume_delta(current: u32, delta: i32, max: u32) -> u32 {
+    (current as i64 + delta as i64).clamp(0, max as i64) as u32
+}
+
+/// Expand `{volume}`, `{icon}` and `{muted}` in a format string
+pub fn render_volume_format(fmt: &str, state: &VolumeState, muted_text: &str) -> String {
+    let icon = if state.muted || state.volume == 0 {
+        "🔇"
+    } else if state.volume < 34 {
+        "🔈"
+    } else if state.volume < 67 {
+        "🔉"
+    } else {
+        "🔊"
+    };
+    fmt.replace("{volume}", &state.volume.to_string())
+        .replace("{icon}", icon)
+        .replace("{muted}", if state.muted { muted_text } else { "" })
+}
+
+enum PulseCommand {
+    Adjust(i32),
+    ToggleMute,
+}
+
+/// PulseAudio backend: a dedicated thread owns the mainloop/context, subscribes to
+/// sink/server changes and applies commands sent from the GTK thread. The thread sleeps in
+/// the mainloop until PulseAudio or a command wakes it.
+pub struct PulseVolumeBackend {
+    commands: Sender<PulseCommand>,
+    /// Written after each command so the mainloop wakes up; dropping it stops the thread
+    wake: UnixStream,
+}
+
+impl PulseVolumeBackend {
+    /// Start the PulseAudio thread; state updates are pushed into `updates`
+    pub fn spawn(updates: UnboundedSender<VolumeState>, max_volume: u32) -> Result<Self> {
+        let (tx, rx) = mpsc::channel::<PulseCommand>();
+        let (wake, wake_rx) = UnixStream::pair()?;
+        // A full socket already has a wakeup pending, so never block the GTK thread on it
+        wake.set_nonblocking(true)?;
+        std::thread::spawn(move || {
+            if let Err(e) = run_pulse_loop(updates, rx, wake_rx, max_volume) {
+                log::warn!("Volume: {}", e);
+            }
+        });
+        Ok(Self { commands: tx, wake })
+    }
+
+    fn send(&self, command: PulseCommand) {
+        if self.commands.send(command).is_ok() {
+            let _ = (&self.wake).write(&[1]);
+        }
+    }
+}
+
+impl VolumeBackend for PulseVolumeBackend {
+    fn adjust_volume(&self, delta_percent: i32) {
+        self.send(PulseCommand::Adjust(delta_percent));
+    }
+
+    fn toggle_mute(&self) {
+        self.send(PulseCommand::ToggleMute);
+    }
+}
+
+fn volume_to_percent(v: Volume) -> u32 {
+    ((v.0 as f64 / Volume::NORMAL.0 as f64) * 100.0).round() as u32
+}
+
+fn percent_to_volume(percent: u32) -> Volume {
+    Volume(((percent as f64 / 100.0) * Volume::NORMAL.0 as f64).round() as u32)
+}
+
+fn run_pulse_loop(
+    updates: UnboundedSender<VolumeState>,
+    commands: Receiver<PulseCommand>,
+    mut wake: UnixStream,
+    max_volume: u32,
+) -> Result<()> {
+    let mut mainloop =
+        Mainloop::new().ok_or_else(|| anyhow!("failed to create PulseAudio mainloop"))?;
+    let mut context = Context::new(&mainloop, "niri-bar")
+        .ok_or_else(|| anyhow!("failed to create PulseAudio context"))?;
+    context
+        .connect(None, ContextFlagSet::NOFLAGS, None)
+        .map_err(|e| anyhow!("failed to connect to PulseAudio: {}", e))?;
+
+    // Wait for the context to become ready
+    loop {
+        match mainloop.iterate(true) {
+            IterateResult::Quit(_) | IterateResult::Err(_) => {
+                return Err(anyhow!("PulseAudio mainloop stopped while connecting"));
+            }
+            IterateResult::Success(_) => {}
+        }
+        match context.get_state() {
+            ContextState::Ready => break,
+            ContextState::Failed | ContextState::Terminated => {
+                return Err(anyhow!("PulseAudio connection failed"));
+            }
+            _ => {}
+        }
+    }
+    log::info!("Volume: 🔊 connected to PulseAudio");
+
+    // Subscription callback only marks state dirty; the sink is re-queried below
+    let dirty = Rc::new(Cell::new(true));
+    {
+        let dirty = dirty.clone();
+        context.set_subscribe_callback(Some(Box::new(move |_facility, _op, _idx| {
+            dirty.set(true);
+        })));
+    }
+    context.subscribe(InterestMaskSet::SINK | InterestMaskSet::SERVER, |_| {});
+
+    // Commands wake the mainloop through the socket; EOF means the widget is gone
+    wake.set_nonblocking(true)?;
+    let closed = Rc::new(Cell::new(false));
+    let wake_fd = wake.as_raw_fd();
+    let _wake_event = {
+        let closed = closed.clone();
+        mainloop
+            .new_io_event(
+                wake_fd,
+                IoEventFlagSet::INPUT,
+                Box::new(move |_event, _fd, _flags| {
+                    let mut buf = [0u8; 64];
+                    loop {
+                        match wake.read(&mut buf) {
+                            Ok(0) => {
+                                closed.set(true);
+                                break;
+                            }
+                            Ok(_) => {}
+                            Err(_) => break,
+                        }
+                    }
+                }),
+            )
+            .ok_or_else(|| anyhow!("failed to watch the PulseAudio command socket"))?
+    };
+
+    let current_volumes: Rc<RefCell<Option<ChannelVolumes>>> = Rc::new(RefCell::new(None));
+    let current_muted: Rc<Cell<Option<bool>>> = Rc::new(Cell::new(None));
+    let mut introspect = context.introspect();
+
+    loop {
+        if dirty.replace(false) {
+            let volumes = current_volumes.clone();
+            let muted = current_muted.clone();
+            let updates = updates.clone();
+            introspect.get_sink_info_by_name(DEFAULT_SINK, move |result| {
+                if let ListResult::Item(info) = result {
+                    *volumes.borrow_mut() = Some(info.volume);
+                    muted.set(Some(info.mute));
+                    let _ = updates.send(VolumeState {
+                        volume: volume_to_percent(info.volume.avg()),
+                        muted: info.mute,
+                    });
+                }
+            });
+        }
+
+        loop {
+            match commands.try_recv() {
+                Ok(PulseCommand::Adjust(delta)) => {
+                    let cv = *current_volumes.borrow();
+                    if let Some(mut cv) = cv {
+                        let target =
+                            apply_volume_delta(volume_to_percent(cv.avg()), delta, max_volume);
+                        cv.scale(percent_to_volume(target));
+                        introspect.set_sink_volume_by_name(DEFAULT_SINK, &cv, None);
+                    }
+                }
+                Ok(PulseCommand::ToggleMute) => {
+                    if let Some(muted) = current_muted.get() {
+                        introspect.set_sink_mute_by_name(DEFAULT_SINK, !muted, None);
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    closed.set(true);
+                    break;
+                }
+            }
+        }
+
+        // Widget is gone; shut the connection down
+        if closed.get() {
+            context.disconnect();
+            return Ok(());
+        }
+
+        // Sleep until PulseAudio replies or reports a change, or a command arrives
+        if let IterateResult::Quit(_) | IterateResult::Err(_) = mainloop.iterate(true) {
+            return Err(anyhow!("PulseAudio mainloop stopped"));
+        }
+    }
+}
+
+pub struct VolumeModule;
+
+impl VolumeModule {
+    pub const IDENT: &'static str = "bar.module.volume";
+
+    pub fn create_widget(settings: &ModuleConfig) -> gtk::Widget {
+        let fmt = settings
+            .format
+            .clone()
+            .unwrap_or_else(|| "{icon} {volume}%".to_string());
+        let step = settings
+            .additional
+            .get("step")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(5) as u32;
+        let max_volume = settings
+            .additional
+            .get("max_volume")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(100) as u32;
+        let muted_text = settings
+            .additional
+            .get("muted_text")
+            .and_then(|v| v.as_str())
+            .unwrap_or("muted")
+            .to_string();
+
+        let label = gtk::Label::new(None);
+        label.add_css_class("module-volume");
+        label.set_hexpand(true);
+        label.set_halign(gtk::Align::Fill);
+
+        // Apply backend updates on the GTK thread as they arrive
+        let label_weak = label.downgrade();
+        let updates = super::gtk_receiver(move |states: Vec<VolumeState>| {
+            let Some(label) = label_weak.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            if let Some(state) = states.last() {
+                label.set_text(&render_volume_format(&fmt, state, &muted_text));
+                if state.muted {
+                    label.add_css_class("muted");
+                } else {
+                    label.remove_css_class("muted");
+                }
+            }
+            glib::ControlFlow::Continue
+        });
+        let backend = match PulseVolumeBackend::spawn(updates, max_volume) {
+            Ok(backend) => backend,
+            Err(e) => {
+                log::warn!("Volume: failed to start the PulseAudio thread: {}", e);
+                return label.upcast();
+            }
+        };
+        let controller = Rc::new(VolumeController::new(backend, step));
+
+        // Scroll to adjust volume
+        let scroll = gtk::EventControllerScroll::new(
+            gtk::EventControllerScrollFlags::VERTICAL | gtk::EventControllerScrollFlags::DISCRETE,
+        );
+        {
+            let controller = controller.clone();
+            let throttle = RefCell::new(ScrollThrottle::new(scroll_throttle_ms(settings)));
+            scroll.connect_scroll(move |_, _dx, dy| {
+                if !throttle.borrow_mut().accept(dy, std::time::Instant::now()) {
+                    return gtk::glib::Propagation::Stop;
+                }
+                if controller.handle_scroll(dy) {
+                    log::debug!("Volume: 🛞 scroll dy={:.3}", dy);
+                }
+                gtk::glib::Propagation::Stop
+            });
+        }
+        label.add_controller(scroll);
+
+        // Click to toggle mute
+        let click = gtk::GestureClick::new();
+        click.set_button(gtk::gdk::BUTTON_PRIMARY);
+        click.connect_pressed(move |gesture, _n_press, _x, _y| {
+            controller.handle_click();
+            gesture.set_state(gtk::EventSequenceState::Claimed);
+        });
+        label.add_controller(click);
+
+        label.upcast()
+    }
+}
+
+impl BarModule for VolumeModule {
+    fn id(&self) -> &'static str {
+        Self::IDENT
+    }
+    fn create(&self, settings: &ModuleConfig) -> gtk::Widget {
+        Self::create_widget(settings)
+    }
+}
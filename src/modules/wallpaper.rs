@@ -1,10 +1,70 @@
 use gtk4 as gtk;
 use gtk4::prelude::*;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
+use std::time::Duration;
+
+use super::{on_bus_events, subscribe_refresh};
+use crate::config::{ModuleConfig, WallpaperConfig};
+use crate::niri::{NiriEvent, niri_bus};
+use crate::wallpaper::{
+    SystemWallpaperExecutor, WALLPAPER_DEBOUNCE, WallpaperCommandExecutor, WallpaperSwitcher,
+};
+
+/// Keeps outputs on the wallpaper of the workspace each one shows, through a
+/// [`WallpaperSwitcher`]: debounced switches run from a one-shot timer and slideshows
+/// advance on the shared 1s tick for as long as the follower is alive
+pub struct WallpaperFollower<E: WallpaperCommandExecutor = SystemWallpaperExecutor> {
+    switcher: WallpaperSwitcher<E>,
+    /// Workspace id each output was last switched to
+    shown: RefCell<HashMap<String, i64>>,
+}
+
+impl<E: WallpaperCommandExecutor + 'static> WallpaperFollower<E> {
+    pub fn new(switcher: WallpaperSwitcher<E>) -> Rc<Self> {
+        let follower = Rc::new(Self {
+            switcher,
+            shown: RefCell::new(HashMap::new()),
+        });
+        let weak = Rc::downgrade(&follower);
+        subscribe_refresh(Duration::from_secs(1), move || {
+            let Some(follower) = weak.upgrade() else {
+                return gtk::glib::ControlFlow::Break;
+            };
+            follower.switcher.advance_slideshows();
+            gtk::glib::ControlFlow::Continue
+        });
+        follower
+    }
 
-use crate::config::ModuleConfig;
-use crate::niri::niri_bus;
+    /// Request a switch for every output in `outputs` now showing another workspace
+    pub fn sync<'a>(self: &Rc<Self>, outputs: impl IntoIterator<Item = &'a str>) {
+        let bus = niri_bus();
+        let mut requested = false;
+        for output in outputs {
+            let Some(workspace) = bus.active_workspace_for_output(output) else {
+                continue;
+            };
+            let previous = self
+                .shown
+                .borrow_mut()
+                .insert(output.to_string(), workspace.id);
+            if previous != Some(workspace.id) {
+                self.switcher.switch_wallpaper_for(&workspace, output);
+                requested = true;
+            }
+        }
+        if requested && self.switcher.has_pending() {
+            let weak = Rc::downgrade(self);
+            gtk::glib::timeout_add_local_once(WALLPAPER_DEBOUNCE, move || {
+                if let Some(follower) = weak.upgrade() {
+                    follower.switcher.apply_pending();
+                }
+            });
+        }
+    }
+}
 
 /// Monitor-scoped wallpaper module: applies wallpaper per monitor on workspace focus
 pub struct WallpaperModule;
@@ -13,204 +73,55 @@ impl WallpaperModule {
     pub const IDENT: &'static str = "bar.module.wallpaper";
 
     pub fn create_widget(settings: &ModuleConfig) -> gtk::Widget {
-        // Invisible controller widget; reacts to focus changes and applies wallpaper
+        // Invisible controller widget; reacts to workspace changes and applies wallpaper
         let container = gtk::Box::new(gtk::Orientation::Horizontal, 0);
         container.add_css_class("module-wallpaper-controller");
 
-        let default_wp = settings.default_wallpaper.clone();
-        let map_wp = settings.wallpapers.clone().unwrap_or_default();
-        let special_cmd = settings.special_cmd.clone();
-        let swww_opts = settings.swww_options.clone();
-
-        // Discover monitor connector from ancestor CSS class monitor-<connector_with_underscores>
-        let monitor_conn: Rc<RefCell<Option<String>>> = Rc::new(RefCell::new(None));
-        {
-            let container_weak = container.downgrade();
-            let monitor_conn = monitor_conn.clone();
-            gtk::glib::idle_add_local(move || {
-                if let Some(w) = container_weak.upgrade() {
-                    let mut cur = Some(w.upcast::<gtk::Widget>());
-                    let mut hops = 0;
-                    while let Some(widget) = cur {
-                        // GTK4 doesn't expose listing classes; sniff via widget_name fallback
-                        let name = widget.widget_name();
-                        if let Some(suffix) = name.strip_prefix("monitor-") {
-                            let conn = suffix.replace('_', "-");
-                            *monitor_conn.borrow_mut() = Some(conn);
-                            return gtk::glib::ControlFlow::Break;
-                        }
-                        cur = widget.parent();
-                        hops += 1;
-                        if hops > 8 {
-                            break;
-                        }
-                    }
-                }
-                gtk::glib::ControlFlow::Break
-            });
-        }
+        // The bar passes the monitor it placed us on; without one there's nothing to follow
+        let Some(output) = settings.placement.as_ref().map(|p| p.output.clone()) else {
+            return container.upcast();
+        };
 
-        // Track last applied (workspace id, image path) to avoid re-issuing commands
-        let last_applied: Rc<RefCell<Option<(i64, String)>>> = Rc::new(RefCell::new(None));
+        // The switcher debounces, so scrolling through workspaces only applies the one the
+        // output settles on
+        let switcher =
+            WallpaperSwitcher::new(module_wallpaper_config(settings), SystemWallpaperExecutor)
+                .with_debounce(WALLPAPER_DEBOUNCE);
+        let follower = WallpaperFollower::new(switcher);
 
-        // Periodically check focus changes; apply only on change
+        // Apply the current workspace's wallpaper, then follow workspace changes
+        follower.sync([output.as_str()]);
         let container_weak = container.downgrade();
-        let last_applied_clone = last_applied.clone();
-        gtk::glib::timeout_add_local(std::time::Duration::from_millis(150), move || {
-            if container_weak.upgrade().is_some() {
-                if let Some(focused) = niri_bus()
-                    .workspaces_snapshot()
-                    .into_iter()
-                    .find(|w| w.is_focused)
-                {
-                    let key_idx = focused.idx.to_string();
-                    let key_name = focused.name.clone().unwrap_or_default();
-                    let target = map_wp
-                        .get(&key_idx)
-                        .or_else(|| {
-                            if key_name.is_empty() {
-                                None
-                            } else {
-                                map_wp.get(&key_name)
-                            }
-                        })
-                        .cloned()
-                        .or(default_wp.clone());
-                    if let Some(img) = target {
-                        // Only apply when (workspace id, image) differs
-                        let should_apply = {
-                            let last = last_applied_clone.borrow();
-                            match &*last {
-                                Some((wid, last_img)) => *wid != focused.id || last_img != &img,
-                                None => true,
-                            }
-                        };
-                        if should_apply {
-                            let out = monitor_conn.borrow().clone();
-                            log::info!(
-                                "WallpaperModule: 🎯 Switching to workspace {} -> {}",
-                                focused.idx,
-                                img
-                            );
-                            Self::apply_wallpaper_command(
-                                &special_cmd,
-                                swww_opts.as_ref(),
-                                &img,
-                                out.as_deref(),
-                            );
-                            *last_applied_clone.borrow_mut() = Some((focused.id, img));
-                        }
-                    }
-                }
-                gtk::glib::ControlFlow::Continue
-            } else {
-                gtk::glib::ControlFlow::Break
+        on_bus_events(move |events| {
+            if container_weak.upgrade().is_none() {
+                return gtk::glib::ControlFlow::Break;
             }
-        });
-
-        container.upcast()
-    }
-
-    fn apply_wallpaper_command(
-        special_cmd: &Option<String>,
-        swww_opts: Option<&crate::config::SwwwOptions>,
-        image_path: &str,
-        output: Option<&str>,
-    ) {
-        log::info!(
-            "WallpaperModule: 🚀 Applying wallpaper: {} (output: {:?})",
-            image_path,
-            output
-        );
-        fn expand_tilde(path: &str) -> String {
-            if let Some(stripped) = path.strip_prefix("~/")
-                && let Some(home) = std::env::var_os("HOME")
+            if events
+                .iter()
+                .any(|e| matches!(e, NiriEvent::WorkspacesChanged | NiriEvent::Synchronized))
             {
-                format!("{}/{}", home.to_string_lossy(), stripped)
-            } else {
-                path.to_string()
-            }
-        }
-
-        let img_expanded = expand_tilde(image_path);
-        if !std::path::Path::new(&img_expanded).exists() {
-            log::warn!(
-                "WallpaperModule: path missing: {} (from {})",
-                img_expanded,
-                image_path
-            );
-            return;
-        }
-
-        if let Some(cmd) = special_cmd {
-            let prepared = cmd.replace("${current_workspace_image}", &img_expanded);
-            let _ = std::process::Command::new("sh")
-                .arg("-c")
-                .arg(prepared)
-                .spawn();
-            return;
-        }
-
-        if let Some(swww) = find_in_path("swww") {
-            // Ensure daemon is initialized; if query fails, attempt init (best-effort, non-blocking)
-            let daemon_ok = std::process::Command::new(&swww)
-                .arg("query")
-                .output()
-                .map(|o| o.status.success())
-                .unwrap_or(false);
-            if !daemon_ok {
-                let _ = std::process::Command::new(&swww).arg("init").spawn();
-                // Defer applying until next tick to avoid racing the daemon startup
-                return;
-            }
-
-            // Build argument list instead of shell string
-            let mut cmd = std::process::Command::new(&swww);
-            cmd.arg("img");
-            if let Some(out) = output {
-                cmd.arg("--outputs").arg(out);
-            }
-            if let Some(opts) = swww_opts {
-                cmd.arg("--transition-type").arg(&opts.transition_type);
-                if !matches!(opts.transition_type.as_str(), "simple" | "none") {
-                    cmd.arg("--transition-duration")
-                        .arg(format!("{}", opts.transition_duration));
-                }
-                cmd.arg("--transition-step")
-                    .arg(format!("{}", opts.transition_step));
-                cmd.arg("--transition-fps")
-                    .arg(format!("{}", opts.transition_fps));
-                cmd.arg("--filter").arg(&opts.filter);
-                cmd.arg("--resize").arg(&opts.resize);
-                cmd.arg("--fill-color").arg(&opts.fill_color);
+                follower.sync([output.as_str()]);
             }
-            cmd.arg(&img_expanded);
-            let _ = cmd.spawn();
-            return;
-        }
+            gtk::glib::ControlFlow::Continue
+        });
 
-        if let Some(swaybg) = find_in_path("swaybg") {
-            let _ = std::process::Command::new("pkill").arg("swaybg").spawn();
-            let mut cmd = std::process::Command::new(swaybg);
-            cmd.args(["-m", "fill", "-i", &img_expanded]);
-            if let Some(out) = output {
-                // swaybg uses -o <OUTPUT> for output selection
-                cmd.args(["-o", out]);
-            }
-            let _ = cmd.spawn();
-        }
+        container.upcast()
     }
 }
 
-fn find_in_path(cmd: &str) -> Option<String> {
-    use std::env;
-    if let Some(paths) = env::var_os("PATH") {
-        for p in env::split_paths(&paths) {
-            let cand = p.join(cmd);
-            if cand.exists() {
-                return cand.to_str().map(|s| s.to_string());
-            }
-        }
+/// The module's settings (already merged for its monitor) as switcher settings:
+/// `default_wallpaper`, `wallpapers`, `special_cmd`, `swww_options`, `slideshow_interval_secs`
+/// and `shuffle`
+pub fn module_wallpaper_config(settings: &ModuleConfig) -> WallpaperConfig {
+    WallpaperConfig {
+        default: settings.default_wallpaper.clone(),
+        by_workspace: settings.wallpapers.clone().unwrap_or_default(),
+        special_cmd: settings.special_cmd.clone(),
+        swww_options: settings.swww_options.as_ref().map(|o| o.resolve()),
+        slideshow_interval_secs: settings
+            .additional
+            .get("slideshow_interval_secs")
+            .and_then(|v| v.as_u64()),
+        shuffle: settings.additional.get("shuffle").and_then(|v| v.as_bool()),
     }
-    None
 }
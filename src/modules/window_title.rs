@@ -1,43 +1,357 @@
 use gtk4 as gtk;
 use gtk4::prelude::*;
+use regex::Regex;
+use std::collections::HashMap;
+use std::rc::Rc;
+use unicode_segmentation::UnicodeSegmentation;
 
 use crate::config::ModuleConfig;
-use crate::niri::niri_bus;
+use crate::niri::{NiriEvent, niri_bus};
+
+/// Icon used when no themed icon matches the focused window's `app_id`
+pub const FALLBACK_ICON: &str = "application-x-executable";
+
+/// Icon names to try for an `app_id`, most specific first: as-is, lowercased, and the
+/// last reverse-DNS segment (`org.gnome.Nautilus` → `nautilus`)
+pub fn icon_candidates(app_id: &str) -> Vec<String> {
+    let app_id = app_id.trim();
+    if app_id.is_empty() {
+        return Vec::new();
+    }
+    let lower = app_id.to_lowercase();
+    let last = lower.rsplit('.').next().unwrap_or_default().to_string();
+    let mut candidates = vec![app_id.to_string()];
+    for c in [lower, last] {
+        if !c.is_empty() && !candidates.contains(&c) {
+            candidates.push(c);
+        }
+    }
+    candidates
+}
+
+/// First candidate the icon theme has, otherwise the generic fallback
+pub fn resolve_icon_name(app_id: &str, has_icon: impl Fn(&str) -> bool) -> String {
+    icon_candidates(app_id)
+        .into_iter()
+        .find(|c| has_icon(c))
+        .unwrap_or_else(|| FALLBACK_ICON.to_string())
+}
+
+/// Whether `additional["show_icon"]` is set (default false)
+pub fn show_icon_enabled(settings: &ModuleConfig) -> bool {
+    settings
+        .additional
+        .get("show_icon")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+}
+
+/// `rewrites` key whose rules apply to every app without a matching app-specific rule
+pub const GLOBAL_REWRITE_KEY: &str = "*";
+
+/// One compiled title rewrite: `pattern` matches are replaced with `replacement`
+/// (`$1`-style capture references allowed)
+#[derive(Debug, Clone)]
+pub struct TitleRewriteRule {
+    pub pattern: Regex,
+    pub replacement: String,
+}
+
+/// Title rewrite rules from `additional["rewrites"]`, compiled once per widget:
+///
+/// ```yaml
+/// rewrites:
+///   firefox:
+///     - { pattern: "^(.*) — Mozilla Firefox$", replacement: "$1" }
+///   "*":
+///     - { pattern: "\\s+$", replacement: "" }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TitleRewrites {
+    by_app: HashMap<String, Vec<TitleRewriteRule>>,
+    global: Vec<TitleRewriteRule>,
+}
+
+impl TitleRewrites {
+    /// Compile the `rewrites` option; invalid regexes are logged and skipped
+    pub fn from_settings(settings: &ModuleConfig) -> Self {
+        settings
+            .additional
+            .get("rewrites")
+            .map(Self::from_value)
+            .unwrap_or_default()
+    }
+
+    /// Compile a `rewrites` map (app_id → rule or list of rules)
+    pub fn from_value(value: &serde_yaml::Value) -> Self {
+        let mut rewrites = Self::default();
+        let Some(map) = value.as_mapping() else {
+            log::warn!("WindowTitle: 'rewrites' must be a map of app_id to rules");
+            return rewrites;
+        };
+        for (key, rules) in map {
+            let Some(app_id) = key.as_str() else {
+                continue;
+            };
+            let rules: Vec<&serde_yaml::Value> = match rules {
+                serde_yaml::Value::Sequence(list) => list.iter().collect(),
+                other => vec![other],
+            };
+            let compiled: Vec<TitleRewriteRule> = rules
+                .into_iter()
+                .filter_map(|rule| Self::compile_rule(app_id, rule))
+                .collect();
+            if app_id == GLOBAL_REWRITE_KEY {
+                rewrites.global = compiled;
+            } else {
+                rewrites.by_app.insert(app_id.to_string(), compiled);
+            }
+        }
+        rewrites
+    }
+
+    fn compile_rule(app_id: &str, rule: &serde_yaml::Value) -> Option<TitleRewriteRule> {
+        let Some(pattern) = rule.get("pattern").and_then(|v| v.as_str()) else {
+            log::warn!("WindowTitle: rewrite rule for '{}' has no pattern", app_id);
+            return None;
+        };
+        let replacement = rule
+            .get("replacement")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string();
+        match Regex::new(pattern) {
+            Ok(pattern) => Some(TitleRewriteRule {
+                pattern,
+                replacement,
+            }),
+            Err(e) => {
+                log::warn!(
+                    "WindowTitle: invalid rewrite pattern '{}' for '{}': {}",
+                    pattern,
+                    app_id,
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.global.is_empty() && self.by_app.values().all(|r| r.is_empty())
+    }
+
+    /// Rewrite `title` with the first matching rule for `app_id`, else the first matching
+    /// global rule; the raw title when nothing matches
+    pub fn apply(&self, app_id: &str, title: &str) -> String {
+        let app_rules = self.by_app.get(app_id).map(Vec::as_slice).unwrap_or(&[]);
+        app_rules
+            .iter()
+            .chain(self.global.iter())
+            .find(|rule| rule.pattern.is_match(title))
+            .map(|rule| {
+                rule.pattern
+                    .replace_all(title, rule.replacement.as_str())
+                    .into_owned()
+            })
+            .unwrap_or_else(|| title.to_string())
+    }
+}
+
+/// Text shown when no window is focused, unless `empty_text` is configured
+pub const DEFAULT_EMPTY_TEXT: &str = "Desktop";
+
+/// Configured `additional["empty_text"]`, or the default
+pub fn empty_text(settings: &ModuleConfig) -> String {
+    settings
+        .additional
+        .get("empty_text")
+        .and_then(|v| v.as_str())
+        .unwrap_or(DEFAULT_EMPTY_TEXT)
+        .to_string()
+}
+
+/// Marker put where `max_length` cut a title
+pub const ELLIPSIS: &str = "…";
+
+/// Which part of a long title `ellipsize` drops
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EllipsizePosition {
+    Start,
+    Middle,
+    #[default]
+    End,
+}
+
+impl EllipsizePosition {
+    /// The `ellipsize` option; unset or unknown values mean `end`
+    pub fn from_option(value: Option<&str>) -> Self {
+        match value {
+            Some("start") => Self::Start,
+            Some("middle") => Self::Middle,
+            None | Some("end") => Self::End,
+            Some(other) => {
+                log::warn!("WindowTitle: unknown ellipsize '{}', using 'end'", other);
+                Self::End
+            }
+        }
+    }
+
+    /// Pango mode for labels that still overflow their column after truncation
+    pub fn pango_mode(self) -> gtk::pango::EllipsizeMode {
+        match self {
+            Self::Start => gtk::pango::EllipsizeMode::Start,
+            Self::Middle => gtk::pango::EllipsizeMode::Middle,
+            Self::End => gtk::pango::EllipsizeMode::End,
+        }
+    }
+}
+
+/// `text` cut to at most `max_length` grapheme clusters, ellipsis included, dropping its
+/// start, middle or end. Cuts fall between user-perceived characters, so emoji sequences
+/// and combining marks stay whole. Text that fits is returned unchanged.
+pub fn truncate_graphemes(text: &str, max_length: usize, position: EllipsizePosition) -> String {
+    let graphemes: Vec<&str> = text.graphemes(true).collect();
+    if graphemes.len() <= max_length {
+        return text.to_string();
+    }
+    let keep = max_length.saturating_sub(1);
+    let (head, tail) = match position {
+        EllipsizePosition::Start => (0, keep),
+        EllipsizePosition::Middle => (keep.div_ceil(2), keep / 2),
+        EllipsizePosition::End => (keep, 0),
+    };
+    let mut truncated = graphemes[..head].concat();
+    if max_length > 0 {
+        truncated.push_str(ELLIPSIS);
+    }
+    truncated.push_str(&graphemes[graphemes.len() - tail..].concat());
+    truncated
+}
 
 pub struct WindowTitleModule;
 
 impl WindowTitleModule {
     pub const IDENT: &'static str = "bar.module.window_title";
 
-    pub fn create_widget(_settings: &ModuleConfig) -> gtk::Widget {
+    pub fn create_widget(settings: &ModuleConfig) -> gtk::Widget {
+        let position = EllipsizePosition::from_option(settings.ellipsize.as_deref());
         let label = gtk::Label::new(None);
         label.add_css_class("module-window-title");
-        label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+        label.set_ellipsize(position.pango_mode());
         // Allow the label to fill its column; horizontal alignment will be set by column
         label.set_hexpand(true);
         label.set_halign(gtk::Align::Fill);
 
         // GTK4 CSS doesn't support text-align, so we handle alignment programmatically
 
-        // Set initial title from bus state
-        let bus = niri_bus();
-        let initial = bus.current_title();
-        if !initial.is_empty() {
-            label.set_text(&initial);
+        // Focused window title after rewrite rules; `empty_text` when nothing is focused
+        let rewrites = TitleRewrites::from_settings(settings);
+        let empty_text = empty_text(settings);
+        let current_text = Rc::new(move || {
+            let bus = niri_bus();
+            let title = bus.current_title_or(&empty_text);
+            if rewrites.is_empty() {
+                title
+            } else {
+                rewrites.apply(&bus.current_app_id(), &title)
+            }
+        });
+
+        // `max_length` shortens the label on grapheme boundaries
+        let max_length = settings.max_length;
+        let shown = move |title: &str| match max_length {
+            Some(max) => truncate_graphemes(title, max, position),
+            None => title.to_string(),
+        };
+
+        // `tooltip_format` placeholders: `{title}` (rewritten, but never truncated) and
+        // `{app_id}`
+        let tooltip_format = super::tooltip_template(settings);
+        let set_title = Rc::new(move |label: &gtk::Label, title: &str| {
+            label.set_text(&shown(title));
+            super::apply_tooltip(
+                label,
+                tooltip_format.as_deref(),
+                &[("title", title), ("app_id", &niri_bus().current_app_id())],
+            );
+        });
+
+        // Set initial title from bus state; before the first window list arrives it would
+        // read as "nothing focused", so leave the label blank until the bus is synchronized
+        let initial = current_text();
+        if niri_bus().is_synchronized() && !initial.is_empty() {
+            set_title(&label, &initial);
         }
 
-        // Poll bus state on GTK thread every 50ms (non-blocking)
+        // Re-read the title on the GTK thread whenever the bus reports a change
         let label_weak = label.downgrade();
-        glib::timeout_add_local(std::time::Duration::from_millis(50), move || {
-            let title = niri_bus().current_title();
-            if let Some(label) = label_weak.upgrade() {
-                label.set_text(&title);
-                glib::ControlFlow::Continue
-            } else {
-                glib::ControlFlow::Break
+        let bus_text = current_text.clone();
+        let bus_set_title = set_title.clone();
+        super::on_bus_events(move |_events| {
+            let Some(label) = label_weak.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            if niri_bus().is_synchronized() {
+                let title = bus_text();
+                if label.text() != shown(&title) {
+                    bus_set_title(&label, &title);
+                }
+            }
+            glib::ControlFlow::Continue
+        });
+
+        if !show_icon_enabled(settings) {
+            return label.upcast();
+        }
+
+        let image = gtk::Image::new();
+        image.add_css_class("module-window-title-icon");
+        let container = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+        container.add_css_class("module-window-title-box");
+        container.set_hexpand(true);
+        container.set_halign(gtk::Align::Fill);
+        container.append(&image);
+        container.append(&label);
+
+        let apply_icon = |image: &gtk::Image| {
+            let app_id = niri_bus().current_app_id();
+            // Nothing focused: no icon
+            if app_id.is_empty() {
+                image.set_visible(false);
+                return;
+            }
+            let name = match gtk::gdk::Display::default() {
+                Some(display) => {
+                    let theme = gtk::IconTheme::for_display(&display);
+                    resolve_icon_name(&app_id, |n| theme.has_icon(n))
+                }
+                None => FALLBACK_ICON.to_string(),
+            };
+            image.set_icon_name(Some(&name));
+            image.set_visible(true);
+        };
+        apply_icon(&image);
+
+        // Refresh the icon on focus changes; title and icon change together
+        let image_weak = image.downgrade();
+        let label_weak = label.downgrade();
+        super::on_bus_events(move |events| {
+            let (Some(image), Some(label)) = (image_weak.upgrade(), label_weak.upgrade()) else {
+                return glib::ControlFlow::Break;
+            };
+            // Same guard as the title listener: no blanking before the first window list
+            if niri_bus().is_synchronized()
+                && events
+                    .iter()
+                    .any(|ev| matches!(ev, NiriEvent::FocusChanged(_)))
+            {
+                apply_icon(&image);
+                set_title(&label, &current_text());
             }
+            glib::ControlFlow::Continue
         });
 
-        label.upcast()
+        container.upcast()
     }
 }
@@ -1,13 +1,380 @@
 use gtk4 as gtk;
 use gtk4::prelude::*;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 // no direct glib import; prefer gtk::glib to avoid version mismatches
 
+use super::{ScrollThrottle, scroll_throttle_ms};
 use crate::config::ModuleConfig;
-use crate::niri::{focus_workspace_index, niri_bus};
+use crate::niri::{
+    NiriEvent, NoFocusStep, WorkspaceInfo, WorkspaceRef, close_workspace_windows, focus_workspace,
+    move_focused_window_to_workspace, niri_bus,
+};
+use crate::ui_state::{UiStateKey, restore_state, save_state};
+use crate::wallpaper::slideshow_images;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-// no mpsc needed; thumbnails come from YAML mapping only
+
+/// (id, idx, name, is_focused, is_active) for one workspace, as compared between refreshes
+pub type WorkspaceSnapshot = (i64, i64, Option<String>, bool, bool);
+
+/// What a batch of bus events invalidates in the workspaces module
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WorkspaceRefresh {
+    /// Re-diff the buttons against the bus (list, labels, focus, `hide_empty`)
+    pub buttons: bool,
+    /// Recount the window badges
+    pub counts: bool,
+}
+
+impl WorkspaceRefresh {
+    /// Nothing to redo for this batch
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Fold a batch of bus events into the work they require. Workspace and focus changes
+/// re-diff the buttons; window changes and the initial sync also move counts (and empty
+/// workspaces for `hide_empty`). Keyboard, overview and tile layout events are ignored.
+pub fn workspace_refresh<'a>(events: impl IntoIterator<Item = &'a NiriEvent>) -> WorkspaceRefresh {
+    events
+        .into_iter()
+        .fold(WorkspaceRefresh::default(), |acc, event| match event {
+            NiriEvent::WorkspacesChanged | NiriEvent::FocusChanged(_) => WorkspaceRefresh {
+                buttons: true,
+                ..acc
+            },
+            NiriEvent::WindowsChanged | NiriEvent::Synchronized => WorkspaceRefresh {
+                buttons: true,
+                counts: true,
+            },
+            NiriEvent::KeyboardLayoutChanged
+            | NiriEvent::OverviewToggled(_)
+            | NiriEvent::WindowLayoutsChanged => acc,
+        })
+}
+
+/// What changed between two workspace snapshots
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorkspaceDiff {
+    /// Workspaces no longer present; their buttons are removed
+    pub removed: Vec<i64>,
+    /// Workspaces that need a new button, in snapshot order
+    pub added: Vec<i64>,
+    /// Existing workspaces whose idx or name changed; their labels are updated in place
+    pub relabeled: Vec<i64>,
+    /// Whether workspaces present in both snapshots changed relative order
+    pub reordered: bool,
+    /// Workspace that gained focus (pulsed), if focus moved
+    pub newly_focused: Option<i64>,
+}
+
+impl WorkspaceDiff {
+    /// No buttons to add, remove, relabel, reorder or pulse
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Diff two snapshots keyed by workspace id
+pub fn diff_workspaces(old: &[WorkspaceSnapshot], new: &[WorkspaceSnapshot]) -> WorkspaceDiff {
+    let old_by_id: HashMap<i64, &WorkspaceSnapshot> = old.iter().map(|w| (w.0, w)).collect();
+    let new_ids: Vec<i64> = new.iter().map(|w| w.0).collect();
+
+    let removed = old
+        .iter()
+        .map(|w| w.0)
+        .filter(|id| !new_ids.contains(id))
+        .collect();
+    let added = new
+        .iter()
+        .filter(|w| !old_by_id.contains_key(&w.0))
+        .map(|w| w.0)
+        .collect();
+    let relabeled = new
+        .iter()
+        .filter(|w| {
+            old_by_id
+                .get(&w.0)
+                .is_some_and(|o| o.1 != w.1 || o.2 != w.2)
+        })
+        .map(|w| w.0)
+        .collect();
+
+    let kept_old: Vec<i64> = old
+        .iter()
+        .map(|w| w.0)
+        .filter(|id| new_ids.contains(id))
+        .collect();
+    let kept_new: Vec<i64> = new_ids
+        .iter()
+        .copied()
+        .filter(|id| old_by_id.contains_key(id))
+        .collect();
+
+    let old_focused = old.iter().find(|w| w.3).map(|w| w.0);
+    let new_focused = new.iter().find(|w| w.3).map(|w| w.0);
+
+    WorkspaceDiff {
+        removed,
+        added,
+        relabeled,
+        reordered: kept_old != kept_new,
+        newly_focused: new_focused.filter(|id| Some(*id) != old_focused),
+    }
+}
+
+/// Button text: the index, or the name (falling back to the index) when numbers are hidden
+pub fn workspace_label(show_numbers: bool, idx: i64, name: Option<&str>) -> String {
+    if show_numbers {
+        format!("{}", idx)
+    } else {
+        // If numbers are hidden, prefer the workspace name, fallback to number only if missing
+        name.map(str::to_string)
+            .unwrap_or_else(|| format!("{}", idx))
+    }
+}
+
+/// Drop workspaces without windows, keeping the focused one and those an output shows even
+/// when empty
+pub fn visible_workspaces(
+    snapshot: Vec<WorkspaceSnapshot>,
+    window_count: impl Fn(i64) -> usize,
+) -> Vec<WorkspaceSnapshot> {
+    snapshot
+        .into_iter()
+        .filter(|w| w.3 || w.4 || window_count(w.0) > 0)
+        .collect()
+}
+
+/// Window-count badge text; `None` hides the badge (zero windows with `hide_empty`)
+pub fn count_badge_text(count: usize, hide_empty: bool) -> Option<String> {
+    if count == 0 && hide_empty {
+        None
+    } else {
+        Some(count.to_string())
+    }
+}
+
+/// What a workspace pill shows instead of its number
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorkspaceIcon {
+    /// Literal text, e.g. a Nerd Font glyph
+    Glyph(String),
+    /// Name of an icon in the current icon theme
+    Themed(String),
+}
+
+/// `additional["icons"]`: workspace name or index → glyph or icon name
+pub fn parse_icon_map(settings: &ModuleConfig) -> HashMap<String, String> {
+    let Some(map) = settings
+        .additional
+        .get("icons")
+        .and_then(|v| v.as_mapping())
+    else {
+        return HashMap::new();
+    };
+    map.iter()
+        .filter_map(|(k, v)| {
+            // YAML reads `1: ...` keys as numbers
+            let key = match k {
+                serde_yaml::Value::String(s) => s.clone(),
+                serde_yaml::Value::Number(n) => n.to_string(),
+                _ => return None,
+            };
+            Some((key, v.as_str()?.to_string()))
+        })
+        .collect()
+}
+
+/// Icon for a workspace: a mapping for its name wins over one for its index. Values the
+/// icon theme knows (`has_icon`) render as themed icons, anything else as literal text.
+pub fn resolve_workspace_icon(
+    icons: &HashMap<String, String>,
+    idx: i64,
+    name: Option<&str>,
+    has_icon: impl Fn(&str) -> bool,
+) -> Option<WorkspaceIcon> {
+    let value = name
+        .and_then(|n| icons.get(n))
+        .or_else(|| icons.get(&idx.to_string()))?;
+    Some(if has_icon(value) {
+        WorkspaceIcon::Themed(value.clone())
+    } else {
+        WorkspaceIcon::Glyph(value.clone())
+    })
+}
+
+/// Entry in a workspace pill's right-click menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkspaceMenuAction {
+    /// Focus the workspace (`focus`)
+    Focus,
+    /// Move the focused window onto the workspace (`move_window_here`)
+    MoveWindowHere,
+    /// Close every window on the workspace (`close_all`)
+    CloseAll,
+}
+
+impl WorkspaceMenuAction {
+    /// Menu used when `context_menu` isn't configured; the destructive `close_all` is opt-in
+    pub const DEFAULT_MENU: [Self; 2] = [Self::Focus, Self::MoveWindowHere];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "focus" => Some(Self::Focus),
+            "move_window_here" => Some(Self::MoveWindowHere),
+            "close_all" => Some(Self::CloseAll),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Focus => "Focus workspace",
+            Self::MoveWindowHere => "Move focused window here",
+            Self::CloseAll => "Close all windows",
+        }
+    }
+}
+
+/// `additional["context_menu"]`: list of action names; `false` or `[]` disables the menu.
+/// Unknown names are logged and skipped.
+pub fn parse_context_menu(settings: &ModuleConfig) -> Vec<WorkspaceMenuAction> {
+    match settings.additional.get("context_menu") {
+        None => WorkspaceMenuAction::DEFAULT_MENU.to_vec(),
+        Some(serde_yaml::Value::Bool(false)) => Vec::new(),
+        Some(serde_yaml::Value::Sequence(items)) => items
+            .iter()
+            .filter_map(|v| {
+                let name = v.as_str()?;
+                let action = WorkspaceMenuAction::parse(name);
+                if action.is_none() {
+                    log::warn!("Workspaces: unknown context_menu action '{}'", name);
+                }
+                action
+            })
+            .collect(),
+        Some(other) => {
+            log::warn!(
+                "Workspaces: 'context_menu' must be a list of actions, got {:?}",
+                other
+            );
+            WorkspaceMenuAction::DEFAULT_MENU.to_vec()
+        }
+    }
+}
+
+/// Run a context menu action against a workspace
+fn run_menu_action(action: WorkspaceMenuAction, ws_id: i64) {
+    log::info!("Workspaces: 📋 {:?} on workspace {}", action, ws_id);
+    let result = match action {
+        WorkspaceMenuAction::Focus => focus_workspace(WorkspaceRef::Id(ws_id)),
+        WorkspaceMenuAction::MoveWindowHere => move_focused_window_to_workspace(ws_id),
+        WorkspaceMenuAction::CloseAll => close_workspace_windows(ws_id).map(|n| {
+            log::debug!("Workspaces: closing {} window(s) on workspace {}", n, ws_id);
+        }),
+    };
+    if let Err(e) = result {
+        log::error!(
+            "Workspaces: ❌ {:?} failed on workspace {}: {}",
+            action,
+            ws_id,
+            e
+        );
+    }
+}
+
+/// Default length of the `.pulse` class on a newly focused workspace
+pub const DEFAULT_PULSE_MS: u64 = 260;
+
+/// How long the focus pulse lasts: `pulse_ms` (default 260), or `None` when
+/// `pulse_enabled: false` or `pulse_ms: 0` turn it off
+pub fn pulse_duration(settings: &ModuleConfig) -> Option<std::time::Duration> {
+    let enabled = settings
+        .additional
+        .get("pulse_enabled")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    let ms = settings
+        .additional
+        .get("pulse_ms")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_PULSE_MS);
+    (enabled && ms > 0).then(|| std::time::Duration::from_millis(ms))
+}
+
+/// Workspaces state kept across config reloads
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorkspacesUiState {
+    /// Workspace the focus pulse last played on
+    pub last_pulsed: Option<i64>,
+}
+
+/// Workspace to pulse after a refresh: the newly focused one, unless the pulse already
+/// played for it (e.g. before a reload rebuilt the widget)
+pub fn pulse_target(newly_focused: Option<i64>, last_pulsed: Option<i64>) -> Option<i64> {
+    newly_focused.filter(|id| Some(*id) != last_pulsed)
+}
+
+/// Last pulse target, saved under the widget's placement so a rebuilt widget picks it up
+struct PulseTracker {
+    state: RefCell<WorkspacesUiState>,
+    key: Option<UiStateKey>,
+}
+
+impl PulseTracker {
+    fn new(settings: &ModuleConfig) -> Self {
+        let key = UiStateKey::for_module(WorkspacesModule::IDENT, settings);
+        let state = key.as_ref().and_then(restore_state).unwrap_or_default();
+        Self {
+            state: RefCell::new(state),
+            key,
+        }
+    }
+
+    /// Workspace to pulse for `newly_focused`, remembering it as pulsed
+    fn take(&self, newly_focused: Option<i64>) -> Option<i64> {
+        let mut state = self.state.borrow_mut();
+        let target = pulse_target(newly_focused, state.last_pulsed)?;
+        state.last_pulsed = Some(target);
+        if let Some(key) = &self.key {
+            save_state(key.clone(), &*state);
+        }
+        Some(target)
+    }
+}
+
+/// Display options shared by all buttons of one widget
+struct ButtonStyle {
+    show_numbers: bool,
+    show_wallpaper: bool,
+    map_wp: HashMap<String, String>,
+    pattern_wp: Vec<WorkspaceWallpaperPattern>,
+    default_wp: Option<String>,
+    show_count: bool,
+    hide_empty_count: bool,
+    hide_empty: bool,
+    icons: HashMap<String, String>,
+    context_menu: Vec<WorkspaceMenuAction>,
+    /// Focus pulse length; no pulse when `None`
+    pulse: Option<std::time::Duration>,
+}
+
+/// A live workspace button; `idx` follows the workspace when it moves
+struct WorkspaceButton {
+    button: gtk::Button,
+    label: gtk::Label,
+    /// Themed icon shown in place of the label when `icons` maps to one
+    icon: Option<gtk::Image>,
+    idx: Rc<Cell<i64>>,
+    /// Window-count badge when `show_count` is enabled
+    count: Option<gtk::Label>,
+    /// Holds the wallpaper background; reloaded in place on relabel
+    wallpaper_css: gtk::CssProvider,
+}
 
 pub struct WorkspacesModule;
 
@@ -15,141 +382,174 @@ impl WorkspacesModule {
     pub const IDENT: &'static str = "bar.module.workspaces";
 
     pub fn create_widget(settings: &ModuleConfig) -> gtk::Widget {
-        let show_numbers = settings.show_numbers.unwrap_or(true);
-        let show_wallpaper = settings.show_wallpaper.unwrap_or(false);
-        let default_wp = settings.default_wallpaper.clone();
-        let map_wp = settings.wallpapers.clone().unwrap_or_default();
+        let style = Rc::new(ButtonStyle {
+            show_numbers: settings.show_numbers.unwrap_or(true),
+            show_wallpaper: settings.show_wallpaper.unwrap_or(false),
+            map_wp: settings.wallpapers.clone().unwrap_or_default(),
+            pattern_wp: compile_wallpaper_patterns(settings),
+            default_wp: settings.default_wallpaper.clone(),
+            show_count: settings
+                .additional
+                .get("show_count")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            hide_empty_count: settings
+                .additional
+                .get("hide_empty_count")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            hide_empty: settings
+                .additional
+                .get("hide_empty")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            icons: parse_icon_map(settings),
+            context_menu: parse_context_menu(settings),
+            pulse: pulse_duration(settings),
+        });
         let _special_cmd = settings.special_cmd.clone();
         let scroll_wrap = settings
             .additional
             .get("scroll_wraparound")
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
-        let scroll_throttle_ms = settings
+        // Scroll through this bar's monitor only with `scroll_per_output: true`
+        let scroll_output: Option<String> = settings
+            .additional
+            .get("scroll_per_output")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+            .then(|| settings.placement.as_ref().map(|p| p.output.clone()))
+            .flatten();
+        let scroll_throttle_ms = scroll_throttle_ms(settings);
+        // Only show workspaces on this bar's monitor unless `all_outputs: true`
+        let all_outputs = settings
             .additional
-            .get("scroll_throttle_ms")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(50);
+            .get("all_outputs")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let output: Option<String> = if all_outputs {
+            None
+        } else {
+            settings.placement.as_ref().map(|p| p.output.clone())
+        };
 
         let container = gtk::Box::new(gtk::Orientation::Horizontal, 4);
         container.add_css_class("module-workspaces");
 
-        // Track last focused id to pulse on change
-        let last_focused = std::rc::Rc::new(std::cell::Cell::new(None::<i64>));
-        // Track last snapshot to avoid unnecessary rebuilds (prevents hover flicker)
-        #[allow(clippy::type_complexity)]
-        let last_snapshot: Rc<RefCell<Vec<(i64, i64, Option<String>, bool)>>> =
-            Rc::new(RefCell::new(Vec::new()));
+        // Live buttons by workspace id; updated in place so hover state survives changes
+        let buttons: Rc<RefCell<HashMap<i64, WorkspaceButton>>> =
+            Rc::new(RefCell::new(HashMap::new()));
+        // Last applied snapshot, diffed against on every refresh
+        let last_snapshot: Rc<RefCell<Vec<WorkspaceSnapshot>>> = Rc::new(RefCell::new(Vec::new()));
+        // Restored after a reload so the focused workspace doesn't pulse again
+        let pulses = Rc::new(PulseTracker::new(settings));
         // Thumbnails are resolved directly from YAML mapping; no runtime capture
-        // Build initial buttons
-        Self::rebuild_buttons(
-            &container,
-            show_numbers,
-            show_wallpaper,
-            &last_focused,
-            &map_wp,
-            &default_wp,
-        );
 
-        // Poll Niri bus for changes; animate focus changes via CSS class
-        let container_weak = container.downgrade();
-        let last_focused_clone = last_focused.clone();
-        let last_snapshot_clone = last_snapshot.clone();
-        // show_wallpaper is already defined above, no need to redefine
-        let map_wp = map_wp.clone();
-        let default_wp = default_wp.clone();
-        glib::timeout_add_local(std::time::Duration::from_millis(200), move || {
-            if let Some(container) = container_weak.upgrade() {
-                // Compare current snapshot to last
-                let bus = niri_bus();
-                let list = bus.workspaces_snapshot();
-                let current: Vec<(i64, i64, Option<String>, bool)> = list
-                    .iter()
-                    .map(|w| (w.id, w.idx, w.name.clone(), w.is_focused))
-                    .collect();
-                let changed = {
-                    let mut last = last_snapshot_clone.borrow_mut();
-                    if *last != current {
-                        *last = current;
-                        true
-                    } else {
-                        false
-                    }
+        // Recompute only on workspace, focus and window events; animate focus via CSS class.
+        // Listen before the first sync so nothing that lands in between is missed.
+        {
+            let container_weak = container.downgrade();
+            let buttons = buttons.clone();
+            let last_snapshot = last_snapshot.clone();
+            let style = style.clone();
+            let output = output.clone();
+            let pulses = pulses.clone();
+            super::on_bus_events(move |events| {
+                let Some(container) = container_weak.upgrade() else {
+                    return glib::ControlFlow::Break;
                 };
-                if changed {
-                    Self::rebuild_buttons(
+                let refresh = workspace_refresh(&events);
+                if refresh.buttons {
+                    Self::sync_buttons(
                         &container,
-                        show_numbers,
-                        show_wallpaper,
-                        &last_focused_clone,
-                        &map_wp,
-                        &default_wp,
+                        &buttons,
+                        &last_snapshot,
+                        &style,
+                        &output,
+                        &pulses,
                     );
                 }
+                if refresh.counts && style.show_count {
+                    Self::refresh_counts(&buttons.borrow(), &style);
+                }
                 glib::ControlFlow::Continue
-            } else {
-                glib::ControlFlow::Break
-            }
-        });
+            });
+        }
+
+        // Build initial buttons, then redraw once more when the main loop goes idle in case
+        // the bus filled up while the bar was being built
+        Self::sync_buttons(
+            &container,
+            &buttons,
+            &last_snapshot,
+            &style,
+            &output,
+            &pulses,
+        );
+        {
+            let container_weak = container.downgrade();
+            glib::idle_add_local_once(move || {
+                if let Some(container) = container_weak.upgrade() {
+                    Self::sync_buttons(
+                        &container,
+                        &buttons,
+                        &last_snapshot,
+                        &style,
+                        &output,
+                        &pulses,
+                    );
+                    Self::refresh_counts(&buttons.borrow(), &style);
+                }
+            });
+        }
 
         // Mouse scroll to cycle workspaces (optimized throttling)
         container.add_controller({
-            let busy = Rc::new(RefCell::new(false));
-            let last_scroll_time = Rc::new(RefCell::new(std::time::Instant::now()));
-            let scroll_throttle_ms_clone = scroll_throttle_ms;
-            let scroll_wrap_clone = scroll_wrap;
+            let throttle = RefCell::new(ScrollThrottle::new(scroll_throttle_ms));
             let gesture = gtk::EventControllerScroll::new(
                 gtk::EventControllerScrollFlags::VERTICAL
                     | gtk::EventControllerScrollFlags::DISCRETE,
             );
             gesture.connect_scroll(move |_, _dx, dy| {
-                let now = std::time::Instant::now();
-                let mut last_time = last_scroll_time.borrow_mut();
-                let time_since_last = now.duration_since(*last_time);
-
-                // Use configurable throttle timeout
-                let throttle_duration = std::time::Duration::from_millis(scroll_throttle_ms_clone);
-                if *busy.borrow() || time_since_last < throttle_duration {
+                // Drop small movements and events inside the configurable throttle window
+                if !throttle.borrow_mut().accept(dy, std::time::Instant::now()) {
                     return gtk::glib::Propagation::Proceed;
                 }
 
-                *busy.borrow_mut() = true;
-                *last_time = now;
+                // Only log significant movements to reduce noise
+                if dy.abs() > 0.8 {
+                    log::debug!("Workspaces: 🛞 scroll dy={:.3}", dy);
+                }
 
-                // Only process significant scroll movements
-                if dy.abs() > 0.1 {
-                    // Only log significant movements to reduce noise
-                    if dy.abs() > 0.8 {
-                        log::debug!("Workspaces: 🛞 scroll dy={:.3}", dy);
+                let direction_up = dy < 0.0;
+                // With no focused (or, per output, active) workspace there's nothing to
+                // step from; ignore the scroll rather than guess a starting point
+                let bus = niri_bus();
+                let target = match scroll_output.as_deref() {
+                    Some(connector) => bus.next_prev_workspace_id_for_output(
+                        connector,
+                        direction_up,
+                        scroll_wrap,
+                        NoFocusStep::Stay,
+                    ),
+                    None => {
+                        bus.next_prev_workspace_id(direction_up, scroll_wrap, NoFocusStep::Stay)
                     }
-
-                    let direction_up = dy < 0.0;
-                    if let Some(idx) =
-                        niri_bus().next_prev_workspace_idx(direction_up, scroll_wrap_clone)
-                    {
-                        if dy.abs() > 0.8 {
-                            // Only log significant movements
-                            if direction_up {
-                                log::info!("Workspaces: ➡️ focus idx {}", idx);
-                            } else {
-                                log::info!("Workspaces: ⬅️ focus idx {}", idx);
-                            }
+                };
+                // Focus by id: an Index would resolve on the focused monitor, which isn't
+                // necessarily this bar's
+                if let Some(id) = target {
+                    if dy.abs() > 0.8 {
+                        // Only log significant movements
+                        if direction_up {
+                            log::info!("Workspaces: ➡️ focus workspace {}", id);
+                        } else {
+                            log::info!("Workspaces: ⬅️ focus workspace {}", id);
                         }
-                        let _ = focus_workspace_index(idx);
                     }
-                } else {
-                    // Small movement, just release the busy flag quickly
-                    *busy.borrow_mut() = false;
-                    return gtk::glib::Propagation::Proceed;
+                    let _ = focus_workspace(WorkspaceRef::Id(id));
                 }
-
-                // Configurable throttle release for better responsiveness
-                let busy_reset = busy.clone();
-                let release_duration = std::time::Duration::from_millis(scroll_throttle_ms_clone);
-                glib::timeout_add_local(release_duration, move || {
-                    *busy_reset.borrow_mut() = false;
-                    glib::ControlFlow::Break
-                });
                 gtk::glib::Propagation::Proceed
             });
             gesture
@@ -158,136 +558,334 @@ impl WorkspacesModule {
         container.upcast()
     }
 
-    fn rebuild_buttons(
+    /// Workspaces to display: those on `output` if set, otherwise all of them
+    fn workspaces_for(output: &Option<String>) -> Vec<WorkspaceInfo> {
+        let bus = niri_bus();
+        match output {
+            Some(connector) => bus.workspaces_for_output(connector),
+            None => bus.workspaces_snapshot(),
+        }
+    }
+
+    /// Bring the buttons in line with the bus: add/remove changed workspaces, update
+    /// labels and `active` in place, and pulse only the workspace that gained focus
+    fn sync_buttons(
         container: &gtk::Box,
-        show_numbers: bool,
-        show_wallpaper: bool,
-        last_focused: &std::rc::Rc<std::cell::Cell<Option<i64>>>,
-        map_wp: &HashMap<String, String>,
-        default_wp: &Option<String>,
+        buttons: &RefCell<HashMap<i64, WorkspaceButton>>,
+        last_snapshot: &RefCell<Vec<WorkspaceSnapshot>>,
+        style: &ButtonStyle,
+        output: &Option<String>,
+        pulses: &PulseTracker,
     ) {
-        // Clear and rebuild (simple for now; can be optimized later)
-        while let Some(child) = container.first_child() {
-            container.remove(&child);
+        let mut current: Vec<WorkspaceSnapshot> = Self::workspaces_for(output)
+            .iter()
+            .map(|w| (w.id, w.idx, w.name.clone(), w.is_focused, w.is_active))
+            .collect();
+        // Window counts feed into the snapshot here, so window events that empty or fill a
+        // workspace show up as added/removed buttons on the next refresh
+        if style.hide_empty {
+            let bus = niri_bus();
+            current = visible_workspaces(current, |id| bus.window_count_for_workspace(id));
         }
+        let diff = {
+            let last = last_snapshot.borrow();
+            if *last == current {
+                return;
+            }
+            diff_workspaces(&last, &current)
+        };
 
-        let bus = niri_bus();
-        let list = bus.workspaces_snapshot();
-
-        for ws in list.iter() {
-            let label_text = if show_numbers {
-                format!("{}", ws.idx)
-            } else {
-                // If numbers are hidden, prefer the workspace name, fallback to number only if missing
-                ws.name.clone().unwrap_or_else(|| format!("{}", ws.idx))
-            };
-            let btn = gtk::Button::new();
-            btn.add_css_class("workspace-pill");
-            // Apply wallpaper directly to the button so it fills entire pill including padding
-            btn.add_css_class("workspace-thumb");
-            btn.set_widget_name(&format!("workspace-btn-{}", ws.id));
-
-            // Ensure button can receive events
-            btn.set_can_focus(true);
-            btn.set_focusable(true);
-            btn.set_sensitive(true);
-            // Overlay: background wallpaper + centered label
-            let overlay = gtk::Overlay::new();
-            overlay.set_hexpand(true);
-            overlay.set_vexpand(true);
-            overlay.set_widget_name(&format!("workspace-overlay-{}", ws.id));
-            overlay.add_css_class("workspace-overlay");
-            if show_wallpaper {
-                // Determine and apply wallpaper from YAML mapping only (no runtime capture)
-                let path_opt = resolve_workspace_wallpaper(ws, map_wp, default_wp);
-                if let Some(path) = path_opt {
-                    set_background_image(&btn.clone().upcast::<gtk::Widget>(), &path);
-                }
-                // Ensure overlay stretches fully
-                let filler = gtk::Box::new(gtk::Orientation::Vertical, 0);
-                filler.set_hexpand(true);
-                filler.set_vexpand(true);
-                filler.set_halign(gtk::Align::Fill);
-                filler.set_valign(gtk::Align::Fill);
-                overlay.set_child(Some(&filler));
+        let mut buttons = buttons.borrow_mut();
+        for id in &diff.removed {
+            if let Some(b) = buttons.remove(id) {
+                container.remove(&b.button);
             }
-            let lbl = gtk::Label::new(Some(&label_text));
-            lbl.add_css_class("workspace-label");
-            lbl.set_halign(gtk::Align::Fill);
-            lbl.set_valign(gtk::Align::Center);
-            lbl.set_xalign(0.5);
-            overlay.add_overlay(&lbl);
-            btn.set_child(Some(&overlay));
-            if ws.is_focused {
-                btn.add_css_class("active");
-                if last_focused.get() != Some(ws.id) {
-                    // Pulse on focus change
-                    btn.add_css_class("pulse");
-                    let btn_weak = btn.downgrade();
-                    glib::timeout_add_local(std::time::Duration::from_millis(260), move || {
-                        if let Some(btn) = btn_weak.upgrade() {
-                            btn.remove_css_class("pulse");
-                        }
-                        glib::ControlFlow::Break
-                    });
-                    last_focused.set(Some(ws.id));
+        }
+        for ws in &current {
+            if diff.added.contains(&ws.0) {
+                let b = Self::create_button(ws, style);
+                container.append(&b.button);
+                buttons.insert(ws.0, b);
+            } else if diff.relabeled.contains(&ws.0)
+                && let Some(b) = buttons.get(&ws.0)
+            {
+                b.idx.set(ws.1);
+                Self::apply_label(&b.label, b.icon.as_ref(), ws, style);
+                Self::apply_wallpaper(&b.button, &b.wallpaper_css, ws, style);
+            }
+        }
+
+        // Restore snapshot order (new buttons were appended at the end)
+        if diff.reordered || !diff.added.is_empty() {
+            let mut prev: Option<gtk::Widget> = None;
+            for ws in &current {
+                if let Some(b) = buttons.get(&ws.0) {
+                    container.reorder_child_after(&b.button, prev.as_ref());
+                    prev = Some(b.button.clone().upcast());
                 }
             }
+        }
 
-            let target_idx = ws.idx;
-            let ws_id = ws.id;
-            btn.connect_clicked(move |_| {
-                log::info!(
-                    "Workspaces: 🖱️ clicked workspace {} (idx {})",
-                    ws_id,
-                    target_idx
-                );
-                match focus_workspace_index(target_idx) {
-                    Ok(_) => log::debug!(
-                        "Workspaces: ✅ successfully focused workspace {}",
-                        target_idx
-                    ),
-                    Err(e) => log::error!(
-                        "Workspaces: ❌ failed to focus workspace {}: {}",
-                        target_idx,
-                        e
-                    ),
+        // On its own output a bar marks the workspace that output shows, even while another
+        // monitor has focus; across all outputs only the focused one
+        for ws in &current {
+            if let Some(b) = buttons.get(&ws.0) {
+                let active = if output.is_some() { ws.4 } else { ws.3 };
+                if active {
+                    b.button.add_css_class("active");
+                } else {
+                    b.button.remove_css_class("active");
                 }
+            }
+        }
+        let pulse_target = pulses.take(diff.newly_focused);
+        if let Some(pulse) = style.pulse
+            && let Some(b) = pulse_target.and_then(|id| buttons.get(&id))
+        {
+            // Pulse on focus change
+            b.button.add_css_class("pulse");
+            let btn_weak = b.button.downgrade();
+            glib::timeout_add_local(pulse, move || {
+                if let Some(btn) = btn_weak.upgrade() {
+                    btn.remove_css_class("pulse");
+                }
+                glib::ControlFlow::Break
             });
+        }
 
-            // Add a gesture click controller for better click handling
-            let click_gesture = gtk::GestureClick::new();
-            click_gesture.set_button(gtk::gdk::BUTTON_PRIMARY); // Left click only
-            let target_idx_click = target_idx;
-            let ws_id_click = ws_id;
-            click_gesture.connect_pressed(move |gesture, _n_press, _x, _y| {
-                log::info!(
-                    "Workspaces: 🖱️ clicked workspace {} (idx {})",
-                    ws_id_click,
-                    target_idx_click
-                );
-                match focus_workspace_index(target_idx_click) {
-                    Ok(_) => log::debug!(
-                        "Workspaces: ✅ successfully focused workspace {}",
-                        target_idx_click
-                    ),
-                    Err(e) => log::error!(
-                        "Workspaces: ❌ failed to focus workspace {}: {}",
-                        target_idx_click,
-                        e
-                    ),
+        *last_snapshot.borrow_mut() = current;
+    }
+
+    /// Update every badge from the bus's current window list
+    fn refresh_counts(buttons: &HashMap<i64, WorkspaceButton>, style: &ButtonStyle) {
+        let bus = niri_bus();
+        for (id, b) in buttons {
+            if let Some(badge) = &b.count {
+                Self::apply_count(badge, bus.window_count_for_workspace(*id), style);
+            }
+        }
+    }
+
+    fn apply_count(badge: &gtk::Label, count: usize, style: &ButtonStyle) {
+        match count_badge_text(count, style.hide_empty_count) {
+            Some(text) => {
+                badge.set_text(&text);
+                badge.set_visible(true);
+            }
+            None => badge.set_visible(false),
+        }
+    }
+
+    /// Set the button's wallpaper from the YAML mapping (no runtime capture)
+    fn apply_wallpaper(
+        btn: &gtk::Button,
+        provider: &gtk::CssProvider,
+        ws: &WorkspaceSnapshot,
+        style: &ButtonStyle,
+    ) {
+        if !style.show_wallpaper {
+            return;
+        }
+        let info = WorkspaceInfo {
+            id: ws.0,
+            idx: ws.1,
+            name: ws.2.clone(),
+            is_focused: ws.3,
+            is_active: ws.4,
+            output: None,
+        };
+        let Some(mut path) =
+            resolve_workspace_wallpaper(&info, &style.map_wp, &style.pattern_wp, &style.default_wp)
+        else {
+            return;
+        };
+        // A slideshow directory is represented by its first image
+        let expanded = expand_tilde(&path);
+        if std::path::Path::new(&expanded).is_dir() {
+            match slideshow_images(std::path::Path::new(&expanded)).first() {
+                Some(first) => path = first.to_string_lossy().into_owned(),
+                None => return,
+            }
+        }
+        set_background_image(&btn.clone().upcast::<gtk::Widget>(), provider, &path);
+    }
+
+    /// Right-click popover listing the configured menu actions
+    fn attach_context_menu(btn: &gtk::Button, ws_id: i64, actions: &[WorkspaceMenuAction]) {
+        let popover = gtk::Popover::new();
+        popover.add_css_class("workspace-menu");
+        popover.set_position(gtk::PositionType::Bottom);
+        popover.set_autohide(true);
+        let list = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        for action in actions.iter().copied() {
+            let item = gtk::Button::with_label(action.label());
+            item.set_has_frame(false);
+            item.add_css_class("workspace-menu-item");
+            let popover_weak = popover.downgrade();
+            item.connect_clicked(move |_| {
+                if let Some(popover) = popover_weak.upgrade() {
+                    popover.popdown();
                 }
-                gesture.set_state(gtk::EventSequenceState::Claimed);
+                run_menu_action(action, ws_id);
             });
-            btn.add_controller(click_gesture);
+            list.append(&item);
+        }
+        popover.set_child(Some(&list));
+        popover.set_parent(btn);
+        // The popover isn't a regular child; release it with the button
+        btn.connect_destroy({
+            let popover = popover.clone();
+            move |_| popover.unparent()
+        });
+
+        let right_click = gtk::GestureClick::new();
+        right_click.set_button(gtk::gdk::BUTTON_SECONDARY);
+        right_click.connect_pressed(move |gesture, _n_press, _x, _y| {
+            popover.popup();
+            gesture.set_state(gtk::EventSequenceState::Claimed);
+        });
+        btn.add_controller(right_click);
+    }
 
-            container.append(&btn);
+    /// Show the workspace's mapped glyph/icon, or its number/name
+    fn apply_label(
+        label: &gtk::Label,
+        icon: Option<&gtk::Image>,
+        ws: &WorkspaceSnapshot,
+        style: &ButtonStyle,
+    ) {
+        let theme = gtk::gdk::Display::default().map(|d| gtk::IconTheme::for_display(&d));
+        let has_icon = |n: &str| theme.as_ref().is_some_and(|t| t.has_icon(n));
+        let resolved = resolve_workspace_icon(&style.icons, ws.1, ws.2.as_deref(), has_icon);
+        let text = match (resolved, icon) {
+            (Some(WorkspaceIcon::Themed(name)), Some(icon)) => {
+                icon.set_icon_name(Some(&name));
+                icon.set_visible(true);
+                label.set_visible(false);
+                return;
+            }
+            (Some(WorkspaceIcon::Glyph(glyph)), _) => glyph,
+            _ => workspace_label(style.show_numbers, ws.1, ws.2.as_deref()),
+        };
+        if let Some(icon) = icon {
+            icon.set_visible(false);
+        }
+        label.set_text(&text);
+        label.set_visible(true);
+    }
+
+    fn create_button(ws: &WorkspaceSnapshot, style: &ButtonStyle) -> WorkspaceButton {
+        let (ws_id, idx, _, _, _) = ws;
+        let btn = gtk::Button::new();
+        btn.add_css_class("workspace-pill");
+        // Apply wallpaper directly to the button so it fills entire pill including padding
+        btn.add_css_class("workspace-thumb");
+        btn.set_widget_name(&format!("workspace-btn-{}", ws_id));
+
+        // Ensure button can receive events
+        btn.set_can_focus(true);
+        btn.set_focusable(true);
+        btn.set_sensitive(true);
+        // Overlay: background wallpaper + centered label
+        let overlay = gtk::Overlay::new();
+        overlay.set_hexpand(true);
+        overlay.set_vexpand(true);
+        overlay.set_widget_name(&format!("workspace-overlay-{}", ws_id));
+        overlay.add_css_class("workspace-overlay");
+        let wallpaper_css = gtk::CssProvider::new();
+        if style.show_wallpaper {
+            btn.style_context()
+                .add_provider(&wallpaper_css, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
+            Self::apply_wallpaper(&btn, &wallpaper_css, ws, style);
+            // Ensure overlay stretches fully
+            let filler = gtk::Box::new(gtk::Orientation::Vertical, 0);
+            filler.set_hexpand(true);
+            filler.set_vexpand(true);
+            filler.set_halign(gtk::Align::Fill);
+            filler.set_valign(gtk::Align::Fill);
+            overlay.set_child(Some(&filler));
+        }
+        let lbl = gtk::Label::new(None);
+        lbl.add_css_class("workspace-label");
+        lbl.set_halign(gtk::Align::Fill);
+        lbl.set_valign(gtk::Align::Center);
+        lbl.set_xalign(0.5);
+        overlay.add_overlay(&lbl);
+        let icon = (!style.icons.is_empty()).then(|| {
+            let image = gtk::Image::new();
+            image.add_css_class("workspace-icon");
+            image.set_halign(gtk::Align::Center);
+            image.set_valign(gtk::Align::Center);
+            overlay.add_overlay(&image);
+            image
+        });
+        Self::apply_label(&lbl, icon.as_ref(), ws, style);
+        let count = style.show_count.then(|| {
+            let badge = gtk::Label::new(None);
+            badge.add_css_class("workspace-count");
+            badge.set_halign(gtk::Align::End);
+            badge.set_valign(gtk::Align::Start);
+            Self::apply_count(&badge, niri_bus().window_count_for_workspace(*ws_id), style);
+            overlay.add_overlay(&badge);
+            badge
+        });
+        btn.set_child(Some(&overlay));
+
+        // Current idx; updated in place when the workspace moves
+        let target_idx = Rc::new(Cell::new(*idx));
+        let ws_id = *ws_id;
+        let idx_clicked = target_idx.clone();
+        btn.connect_clicked(move |_| {
+            let target_idx = idx_clicked.get();
+            log::info!(
+                "Workspaces: 🖱️ clicked workspace {} (idx {})",
+                ws_id,
+                target_idx
+            );
+            // By id: niri resolves an Index on the focused monitor, not this pill's
+            match focus_workspace(WorkspaceRef::Id(ws_id)) {
+                Ok(_) => log::debug!("Workspaces: ✅ successfully focused workspace {}", ws_id),
+                Err(e) => log::error!("Workspaces: ❌ failed to focus workspace {}: {}", ws_id, e),
+            }
+        });
+
+        // Add a gesture click controller for better click handling
+        let click_gesture = gtk::GestureClick::new();
+        click_gesture.set_button(gtk::gdk::BUTTON_PRIMARY); // Left click only
+        let idx_pressed = target_idx.clone();
+        click_gesture.connect_pressed(move |gesture, _n_press, _x, _y| {
+            let target_idx_click = idx_pressed.get();
+            log::info!(
+                "Workspaces: 🖱️ clicked workspace {} (idx {})",
+                ws_id,
+                target_idx_click
+            );
+            match focus_workspace(WorkspaceRef::Id(ws_id)) {
+                Ok(_) => log::debug!("Workspaces: ✅ successfully focused workspace {}", ws_id),
+                Err(e) => log::error!("Workspaces: ❌ failed to focus workspace {}: {}", ws_id, e),
+            }
+            gesture.set_state(gtk::EventSequenceState::Claimed);
+        });
+        btn.add_controller(click_gesture);
+
+        if !style.context_menu.is_empty() {
+            Self::attach_context_menu(&btn, ws_id, &style.context_menu);
+        }
+
+        WorkspaceButton {
+            button: btn,
+            label: lbl,
+            icon,
+            idx: target_idx,
+            count,
+            wallpaper_css,
         }
     }
 }
 
-/// Helper to set a CSS background-image on a widget using a file path
-fn set_background_image(widget: &gtk::Widget, path: &str) {
+/// Helper to set a CSS background-image on a widget using a file path; `provider` must
+/// already be attached to the widget and is replaced wholesale
+fn set_background_image(widget: &gtk::Widget, provider: &gtk::CssProvider, path: &str) {
     // Convert to file:// URL and escape quotes for CSS
     let abs = expand_tilde(path);
     let mut uri = if abs.starts_with("file://") {
@@ -302,10 +900,7 @@ fn set_background_image(widget: &gtk::Widget, path: &str) {
         widget.widget_name(),
         uri.replace("\\", "\\\\").replace("\"", "\\\"")
     );
-    let provider = gtk::CssProvider::new();
     provider.load_from_data(&css);
-    let ctx = widget.style_context();
-    ctx.add_provider(&provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
 }
 
 fn expand_tilde(path: &str) -> String {
@@ -418,9 +1013,42 @@ fn apply_wallpaper_command(special_cmd: &Option<String>, image_path: &str) {
     }
 }
 
+/// A `wallpaper_patterns` entry with its regex compiled
+#[derive(Debug, Clone)]
+pub struct WorkspaceWallpaperPattern {
+    pub pattern: Regex,
+    pub path: String,
+}
+
+/// Compile `wallpaper_patterns` in order; invalid regexes are logged and skipped
+pub fn compile_wallpaper_patterns(settings: &ModuleConfig) -> Vec<WorkspaceWallpaperPattern> {
+    settings
+        .wallpaper_patterns
+        .iter()
+        .flatten()
+        .filter_map(|entry| match Regex::new(&entry.pattern) {
+            Ok(pattern) => Some(WorkspaceWallpaperPattern {
+                pattern,
+                path: entry.path.clone(),
+            }),
+            Err(e) => {
+                log::warn!(
+                    "Workspaces: invalid wallpaper pattern '{}': {}",
+                    entry.pattern,
+                    e
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+/// Wallpaper for `workspace`: `wallpapers` by exact index, then by exact name, then the first
+/// of `patterns` matching the name, then `default_wp`
 pub fn resolve_workspace_wallpaper(
     workspace: &crate::niri::WorkspaceInfo,
     wallpapers: &HashMap<String, String>,
+    patterns: &[WorkspaceWallpaperPattern],
     default_wp: &Option<String>,
 ) -> Option<String> {
     let key_idx = workspace.idx.to_string();
@@ -433,6 +1061,12 @@ pub fn resolve_workspace_wallpaper(
     if let Some(path) = wallpapers.get(&key_name) {
         return Some(path.clone());
     }
+    // Unnamed workspaces have nothing for a pattern to match
+    if let Some(name) = workspace.name.as_deref()
+        && let Some(entry) = patterns.iter().find(|p| p.pattern.is_match(name))
+    {
+        return Some(entry.path.clone());
+    }
 
     // Fallback to default if no specific wallpaper found
     default_wp.clone()
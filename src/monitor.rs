@@ -1,5 +1,6 @@
-use crate::bar::Bar;
-use crate::config::{ColumnSpec, ModuleConfig};
+use crate::bar::{Bar, hidden_for_fullscreen};
+use crate::config::{BarOptions, ColumnSpec, ModuleConfig};
+use crate::niri::niri_bus;
 use gdk4::Monitor as GdkMonitor;
 use gtk4::Application as GtkApplication;
 use gtk4::prelude::*;
@@ -11,15 +12,53 @@ pub struct MonitorInfo {
     pub connector: String,
     pub manufacturer: Option<String>,
     pub model: Option<String>,
+    /// Size in logical (application) pixels, as GDK reports monitor geometry. Widget sizes,
+    /// `height`, column widths and overflow budgets are all in these units.
     pub logical_size: (i32, i32),
+    /// Integer scale GDK reports; device pixels are logical pixels times this
     pub scale_factor: i32,
 }
 
+impl MonitorInfo {
+    /// Info carrying only a connector name, for lookups before GDK has reported the monitor
+    pub fn for_connector(connector: &str) -> Self {
+        Self {
+            connector: connector.to_string(),
+            manufacturer: None,
+            model: None,
+            logical_size: (0, 0),
+            scale_factor: 1,
+        }
+    }
+
+    /// Size in device pixels (the mode's resolution at an integer scale)
+    pub fn pixel_size(&self) -> (i32, i32) {
+        let scale = self.scale_factor.max(1);
+        (self.logical_size.0 * scale, self.logical_size.1 * scale)
+    }
+
+    /// Connector, make, model and geometry GDK reports for `gdk_monitor`
+    pub fn from_gdk_monitor(connector: &str, gdk_monitor: &GdkMonitor) -> Self {
+        let geometry = gdk_monitor.geometry();
+        Self {
+            connector: connector.to_string(),
+            manufacturer: gdk_monitor.manufacturer().map(|s| s.to_string()),
+            model: gdk_monitor.model().map(|s| s.to_string()),
+            logical_size: (geometry.width(), geometry.height()),
+            scale_factor: gdk_monitor.scale_factor(),
+        }
+    }
+}
+
 /// Monitor class that manages a single monitor and its associated bar
 pub struct Monitor {
     info: MonitorInfo,
     gdk_monitor: GdkMonitor,
     bar: Option<Bar>,
+    /// Column specs last applied to the bar, to skip rebuilding an unchanged layout
+    column_specs: Vec<(String, ColumnSpec)>,
+    /// The bar is kept hidden because the monitor is disabled (`hide_when_disabled`)
+    hidden_while_disabled: bool,
 }
 
 impl Monitor {
@@ -31,6 +70,7 @@ impl Monitor {
         gdk_monitor: GdkMonitor,
         app: &GtkApplication,
         theme: &str,
+        bar_options: &BarOptions,
     ) -> Self {
         log::info!(
             "Monitor: Creating monitor: {} ({}x{}, scale={})",
@@ -50,7 +90,7 @@ impl Monitor {
         };
 
         // Create the bar for this monitor
-        let bar = Bar::new(&info, &gdk_monitor, app, theme);
+        let bar = Bar::new(&info, &gdk_monitor, app, theme, bar_options);
 
         log::info!("Monitor: ✅ Monitor '{}' created with bar", connector);
 
@@ -58,6 +98,8 @@ impl Monitor {
             info,
             gdk_monitor,
             bar: Some(bar),
+            column_specs: Vec::new(),
+            hidden_while_disabled: false,
         }
     }
 
@@ -117,6 +159,7 @@ impl Monitor {
             log::info!("Monitor: Showing bar for monitor: {}", self.info.connector);
             bar.show();
         }
+        self.hidden_while_disabled = false;
     }
 
     /// Hide the bar for this monitor
@@ -127,6 +170,32 @@ impl Monitor {
         }
     }
 
+    /// Hide the bar because the monitor was disabled, keeping it to show again once enabled
+    pub fn hide_while_disabled(&mut self) {
+        self.hide_bar();
+        self.hidden_while_disabled = true;
+    }
+
+    /// Whether the bar is hidden by [`Monitor::hide_while_disabled`] and not shown since
+    pub fn is_hidden_while_disabled(&self) -> bool {
+        self.hidden_while_disabled
+    }
+
+    /// Show the bar if it is hidden, hide it otherwise; returns whether it is now visible
+    pub fn toggle_bar(&mut self) -> bool {
+        match &self.bar {
+            Some(bar) if bar.is_visible() => {
+                self.hide_bar();
+                false
+            }
+            Some(_) => {
+                self.show_bar();
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Update the theme for this monitor's bar
     pub fn update_theme(&mut self, theme: &str) {
         if let Some(bar) = &mut self.bar {
@@ -139,6 +208,28 @@ impl Monitor {
         }
     }
 
+    /// Re-apply position, layer, height and margins to this monitor's bar
+    pub fn apply_bar_options(&mut self, options: &BarOptions) {
+        if let Some(bar) = &mut self.bar {
+            bar.apply_options(options);
+        }
+    }
+
+    /// Hide or restore the bar depending on whether this monitor's focused window is fullscreen
+    pub fn refresh_fullscreen_visibility(&mut self) {
+        if let Some(bar) = &mut self.bar {
+            let fullscreen = niri_bus().is_focused_window_fullscreen(&self.info.connector);
+            bar.set_fullscreen_hidden(hidden_for_fullscreen(bar.options(), fullscreen));
+        }
+    }
+
+    /// Show or clear the config error banner on this monitor's bar
+    pub fn set_config_error(&self, error: Option<&str>) {
+        if let Some(bar) = &self.bar {
+            bar.set_config_error(error);
+        }
+    }
+
     /// Update layout columns by names (ordered)
     pub fn update_columns(&mut self, column_names: &[String]) {
         if let Some(bar) = &mut self.bar {
@@ -166,6 +257,12 @@ impl Monitor {
             );
             bar.update_layout_columns(columns, module_formats, module_configs);
         }
+        self.column_specs = columns.to_vec();
+    }
+
+    /// Column specs the bar was last built with
+    pub fn column_specs(&self) -> &[(String, ColumnSpec)] {
+        &self.column_specs
     }
 
     /// Update the bar content
@@ -3,61 +3,145 @@ use once_cell::sync::Lazy;
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
 use std::env;
-use std::io::{BufRead, BufReader, Write};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::net::Shutdown;
 use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::Sender;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Condvar, Mutex, Once};
 use std::thread;
+use std::time::Duration;
 
 // use glib::MainContext; // not used currently; keep imports minimal
 
-/// Niri IPC: manages a read (event-stream) connection and a write connection
+/// Backoff bounds for event-stream reconnection
+const RECONNECT_BACKOFF_MIN: Duration = Duration::from_millis(100);
+const RECONNECT_BACKOFF_MAX: Duration = Duration::from_secs(5);
+/// How long `send_request_sync` waits for niri to answer
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+/// Default window for coalescing UI notifications (~one frame)
+const NOTIFY_DEBOUNCE_DEFAULT: Duration = Duration::from_millis(16);
+
+/// Niri IPC: manages a read (event-stream) connection and a write connection.
+/// Clones share the stop flag and the live event stream.
+#[derive(Clone)]
 pub struct NiriIpc {
     socket_path: String,
+    stop_requested: Arc<AtomicBool>,
+    event_stream: Arc<Mutex<Option<UnixStream>>>, // live event-stream socket, for shutdown
 }
 
 impl NiriIpc {
     pub fn new() -> Result<Self> {
         let socket_path = env::var("NIRI_SOCKET").map_err(|_| anyhow!("NIRI_SOCKET not set"))?;
-        Ok(Self { socket_path })
+        Ok(Self::with_socket_path(socket_path))
+    }
+
+    /// Create an IPC handle for an explicit socket path (bypasses `$NIRI_SOCKET`)
+    pub fn with_socket_path(socket_path: impl Into<String>) -> Self {
+        Self {
+            socket_path: socket_path.into(),
+            stop_requested: Arc::new(AtomicBool::new(false)),
+            event_stream: Arc::new(Mutex::new(None)),
+        }
     }
 
     /// Start event-stream reader; feeds lines into the NiriBus for state + UI events.
     /// Non-blocking: spawns a background task; does not touch GTK main thread directly.
+    /// If the socket drops (e.g. niri restarts) the reader reconnects with exponential
+    /// backoff and resets the bus so the replayed state starts clean.
     pub fn start_event_stream(&self) -> Result<()> {
         let path = self.socket_path.clone();
+        let stop = self.stop_requested.clone();
+        let active = self.event_stream.clone();
         thread::spawn(move || {
-            match UnixStream::connect(&path) {
-                Ok(mut stream) => {
-                    if let Err(e) = writeln!(stream, "\"EventStream\"") {
-                        eprintln!("Niri IPC: write error: {}", e);
-                        return;
-                    }
-                    if let Err(e) = stream.flush() {
-                        eprintln!("Niri IPC: flush error: {}", e);
-                        return;
-                    }
-                    let reader = BufReader::new(stream);
-                    for line in reader.lines() {
-                        match line {
-                            Ok(s) => {
-                                // Feed global bus (and also print for debugging)
-                                niri_bus().handle_json_line(&s);
-                                println!("{}", s);
-                            }
-                            Err(e) => {
-                                eprintln!("Niri IPC: read error: {}", e);
-                                break;
+            let mut backoff = RECONNECT_BACKOFF_MIN;
+            let mut attempt: u32 = 0;
+            while !stop.load(Ordering::SeqCst) {
+                match Self::open_event_stream(&path) {
+                    Ok(stream) => {
+                        if attempt > 0 {
+                            log::info!(
+                                "Niri IPC: 🔌 event stream reconnected after {} attempt(s)",
+                                attempt
+                            );
+                        }
+                        attempt = 0;
+                        backoff = RECONNECT_BACKOFF_MIN;
+                        // Drop whatever we knew; niri replays full state on a fresh stream
+                        niri_bus().reset();
+                        if let Ok(mut slot) = active.lock() {
+                            *slot = stream.try_clone().ok();
+                        }
+                        // stop() may have raced with connect before the slot was filled
+                        if stop.load(Ordering::SeqCst) {
+                            break;
+                        }
+                        let reader = BufReader::new(stream);
+                        for line in reader.lines() {
+                            match line {
+                                Ok(s) => {
+                                    niri_bus().handle_json_line(&s);
+                                    log::trace!("Niri IPC: {}", s);
+                                }
+                                Err(e) => {
+                                    if !stop.load(Ordering::SeqCst) {
+                                        log::warn!("Niri IPC: read error: {}", e);
+                                    }
+                                    break;
+                                }
                             }
                         }
+                        if let Ok(mut slot) = active.lock() {
+                            *slot = None;
+                        }
+                        if stop.load(Ordering::SeqCst) {
+                            break;
+                        }
+                        log::warn!("Niri IPC: event stream closed");
                     }
+                    Err(e) => log::warn!("Niri IPC: connect error: {}", e),
                 }
-                Err(e) => eprintln!("Niri IPC: connect error: {}", e),
+                if stop.load(Ordering::SeqCst) {
+                    break;
+                }
+                attempt += 1;
+                log::info!(
+                    "Niri IPC: reconnecting in {:?} (attempt {})",
+                    backoff,
+                    attempt
+                );
+                thread::sleep(backoff);
+                backoff = (backoff * 2).min(RECONNECT_BACKOFF_MAX);
             }
+            log::info!("Niri IPC: event stream stopped");
         });
         Ok(())
     }
 
+    /// Stop the event-stream reader; the background thread exits instead of reconnecting
+    pub fn stop(&self) {
+        self.stop_requested.store(true, Ordering::SeqCst);
+        if let Ok(mut slot) = self.event_stream.lock()
+            && let Some(stream) = slot.take()
+        {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    /// Whether `stop()` has been requested
+    pub fn is_stopped(&self) -> bool {
+        self.stop_requested.load(Ordering::SeqCst)
+    }
+
+    // Connect and issue the "EventStream" handshake
+    fn open_event_stream(path: &str) -> std::io::Result<UnixStream> {
+        let mut stream = UnixStream::connect(path)?;
+        writeln!(stream, "\"EventStream\"")?;
+        stream.flush()?;
+        Ok(stream)
+    }
+
     /// Send a one-shot request (JSON on one line), returns nothing for now
     pub fn send_request(&self, json_line: &str) -> Result<()> {
         let path = self.socket_path.clone();
@@ -68,11 +152,92 @@ impl NiriIpc {
                 let _ = stream.write_all(payload.as_bytes());
                 let _ = stream.flush();
             } else {
-                eprintln!("Niri IPC: connect error while sending");
+                log::warn!("Niri IPC: connect error while sending");
             }
         });
         Ok(())
     }
+
+    /// Send a request and wait for niri's single-line reply, using the default timeout
+    pub fn send_request_sync(&self, json_line: &str) -> Result<JsonValue> {
+        self.send_request_sync_with_timeout(json_line, REQUEST_TIMEOUT)
+    }
+
+    /// Send a request and wait for niri's single-line reply.
+    /// Fails if niri doesn't answer within `timeout` or the reply isn't valid JSON.
+    pub fn send_request_sync_with_timeout(
+        &self,
+        json_line: &str,
+        timeout: Duration,
+    ) -> Result<JsonValue> {
+        log::debug!("Niri IPC: ➡️ sending request (sync): {}", json_line);
+        let mut stream = UnixStream::connect(&self.socket_path)
+            .map_err(|e| anyhow!("Niri IPC: connect error: {}", e))?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+        stream.write_all(format!("{}\n", json_line).as_bytes())?;
+        stream.flush()?;
+
+        let mut reply = String::new();
+        let read = BufReader::new(stream)
+            .read_line(&mut reply)
+            .map_err(|e| match e.kind() {
+                ErrorKind::WouldBlock | ErrorKind::TimedOut => {
+                    anyhow!("Niri IPC: no reply within {:?}", timeout)
+                }
+                _ => anyhow!("Niri IPC: read error: {}", e),
+            })?;
+        if read == 0 {
+            return Err(anyhow!("Niri IPC: connection closed before reply"));
+        }
+        serde_json::from_str(reply.trim_end())
+            .map_err(|e| anyhow!("Niri IPC: invalid reply: {} -> {}", e, reply.trim_end()))
+    }
+
+    /// Ask niri for the current windows and workspaces and feed them into the NiriBus, so a
+    /// bar started after niri isn't empty until the event stream catches up. Blocks for up
+    /// to the request timeout per request; call it off the GTK thread.
+    pub fn fetch_initial_state(&self) -> Result<()> {
+        for request in INITIAL_STATE_REQUESTS {
+            let reply = self.send_request_sync(&format!("\"{}\"", request))?;
+            niri_bus().handle_json(initial_state_event(request, &reply)?);
+        }
+        log::info!("Niri IPC: 📥 initial windows and workspaces loaded");
+        Ok(())
+    }
+}
+
+/// One-shot requests whose replies seed the NiriBus on startup. Workspaces go first:
+/// they seed focus from each workspace's active window, which the windows' own
+/// `is_focused` then overrides.
+const INITIAL_STATE_REQUESTS: [&str; 2] = ["Workspaces", "Windows"];
+
+/// Rewrite niri's reply to a `"Windows"` or `"Workspaces"` request as the matching
+/// `WindowsChanged` / `WorkspacesChanged` event. Accepts `{"Ok":{"Windows":[...]}}`, the
+/// unwrapped `{"Windows":[...]}` and the nested `{"Windows":{"windows":[...]}}`; an
+/// `{"Err":...}` reply is an error.
+pub fn initial_state_event(request: &str, reply: &JsonValue) -> Result<JsonValue> {
+    if let Some(err) = reply.get("Err") {
+        return Err(anyhow!("Niri IPC: {} request failed: {}", request, err));
+    }
+    let field = request.to_lowercase();
+    let payload = reply
+        .get("Ok")
+        .unwrap_or(reply)
+        .get(request)
+        .ok_or_else(|| anyhow!("Niri IPC: unexpected {} reply: {}", request, reply))?;
+    let list = match payload {
+        JsonValue::Array(_) => payload,
+        _ => payload
+            .get(&field)
+            .filter(|v| v.is_array())
+            .ok_or_else(|| anyhow!("Niri IPC: unexpected {} reply: {}", request, reply))?,
+    };
+    let mut inner = serde_json::Map::new();
+    inner.insert(field, list.clone());
+    let mut event = serde_json::Map::new();
+    event.insert(format!("{}Changed", request), JsonValue::Object(inner));
+    Ok(JsonValue::Object(event))
 }
 
 #[derive(Debug, Clone)]
@@ -83,6 +248,8 @@ pub struct WindowInfo {
     pub workspace_id: i64,
     pub is_focused: bool,
     pub is_floating: bool,
+    pub is_fullscreen: bool,
+    pub is_maximized: bool,
     pub layout: Option<WindowLayout>,
 }
 
@@ -94,12 +261,67 @@ pub struct WindowLayout {
     pub window_offset_in_tile: [f64; 2],
 }
 
-#[derive(Debug, Clone)]
+/// A window's `layout` object; `None` when any of its coordinate pairs is missing
+fn parse_window_layout(layout: &JsonValue) -> Option<WindowLayout> {
+    let pair = |key: &str| {
+        let arr = layout.get(key)?.as_array()?;
+        (arr.len() >= 2).then(|| {
+            [
+                arr[0].as_f64().unwrap_or(0.0),
+                arr[1].as_f64().unwrap_or(0.0),
+            ]
+        })
+    };
+    Some(WindowLayout {
+        pos_in_scrolling_layout: pair("pos_in_scrolling_layout")?,
+        tile_size: pair("tile_size")?,
+        window_size: pair("window_size")?,
+        window_offset_in_tile: pair("window_offset_in_tile")?,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct WorkspaceInfo {
     pub id: i64,
     pub idx: i64,
     pub name: Option<String>,
+    /// The globally focused workspace (at most one)
     pub is_focused: bool,
+    /// The workspace shown on its output (one per output, focused or not)
+    pub is_active: bool,
+    pub output: Option<String>, // connector name, e.g. "DP-1"
+}
+
+/// Typed change notification broadcast by the NiriBus
+#[derive(Debug, Clone, PartialEq)]
+pub enum NiriEvent {
+    /// Window list or a window's properties changed (opened, changed, closed)
+    WindowsChanged,
+    /// Focused window changed (None = nothing focused)
+    FocusChanged(Option<i64>),
+    /// Workspace list or the active workspace changed
+    WorkspacesChanged,
+    /// Keyboard layout names or the current layout changed
+    KeyboardLayoutChanged,
+    /// Overview opened (true) or closed (false)
+    OverviewToggled(bool),
+    /// Window tile positions/sizes changed
+    WindowLayoutsChanged,
+    /// The first full window and workspace lists have both arrived; sent once
+    Synchronized,
+}
+
+/// Which initial snapshots the bus has received
+#[derive(Debug, Default)]
+struct SyncProgress {
+    windows: bool,
+    workspaces: bool,
+}
+
+impl SyncProgress {
+    fn is_complete(&self) -> bool {
+        self.windows && self.workspaces
+    }
 }
 
 /// Central bus that caches state and broadcasts UI-friendly updates
@@ -110,7 +332,13 @@ pub struct NiriBus {
     keyboard_layout_names: Mutex<Vec<String>>, // from KeyboardLayoutsChanged
     current_keyboard_layout_index: Mutex<Option<usize>>, // from KeyboardLayoutsChanged
     overview_is_open: Mutex<bool>,             // from OverviewOpenedOrClosed
+    sync_progress: Mutex<SyncProgress>,        // initial snapshots seen so far
     update_listeners: Mutex<Vec<Sender<()>>>,  // UI listeners
+    typed_listeners: Mutex<Vec<Sender<NiriEvent>>>, // typed UI listeners
+    pending_events: Mutex<Vec<NiriEvent>>,     // coalesced, not yet flushed
+    pending_signal: Condvar,                   // wakes the flusher thread
+    notify_debounce: Mutex<Duration>,
+    flusher_started: Once,
 }
 
 impl NiriBus {
@@ -122,7 +350,13 @@ impl NiriBus {
             keyboard_layout_names: Mutex::new(Vec::new()),
             current_keyboard_layout_index: Mutex::new(None),
             overview_is_open: Mutex::new(false),
+            sync_progress: Mutex::new(SyncProgress::default()),
             update_listeners: Mutex::new(Vec::new()),
+            typed_listeners: Mutex::new(Vec::new()),
+            pending_events: Mutex::new(Vec::new()),
+            pending_signal: Condvar::new(),
+            notify_debounce: Mutex::new(NOTIFY_DEBOUNCE_DEFAULT),
+            flusher_started: Once::new(),
         }
     }
 
@@ -137,6 +371,28 @@ impl NiriBus {
         String::new()
     }
 
+    /// Focused window title, or `fallback` when nothing is focused or the title is empty
+    pub fn current_title_or(&self, fallback: &str) -> String {
+        let title = self.current_title();
+        if title.is_empty() {
+            fallback.to_string()
+        } else {
+            title
+        }
+    }
+
+    /// `app_id` of the focused window (empty when nothing is focused)
+    pub fn current_app_id(&self) -> String {
+        let focused_id = self.focused_window_id.lock().ok().and_then(|g| *g);
+        if let Some(fid) = focused_id
+            && let Ok(map) = self.windows_by_id.lock()
+            && let Some(win) = map.get(&fid)
+        {
+            return win.app_id.clone();
+        }
+        String::new()
+    }
+
     // Notify UI listeners (GTK main thread) that state changed
     fn notify_ui(&self) {
         if let Ok(listeners) = self.update_listeners.lock() {
@@ -146,11 +402,97 @@ impl NiriBus {
         }
     }
 
-    // Backward compat for modules calling this
-    fn queue_broadcast_title(&self) {
+    // Queue a typed event; the flusher thread delivers it after the debounce window.
+    // Events of the same kind coalesce, keeping the latest payload.
+    fn broadcast(&self, event: NiriEvent) {
+        self.flusher_started.call_once(|| {
+            thread::spawn(|| {
+                let bus = niri_bus();
+                loop {
+                    bus.flush_pending_events();
+                }
+            });
+        });
+        if let Ok(mut pending) = self.pending_events.lock() {
+            let kind = std::mem::discriminant(&event);
+            if let Some(slot) = pending
+                .iter_mut()
+                .find(|e| std::mem::discriminant(*e) == kind)
+            {
+                *slot = event;
+            } else {
+                pending.push(event);
+            }
+            self.pending_signal.notify_one();
+        }
+    }
+
+    // Block until something is pending, wait out the debounce window, then deliver
+    fn flush_pending_events(&self) {
+        {
+            let mut pending = match self.pending_events.lock() {
+                Ok(p) => p,
+                Err(e) => e.into_inner(),
+            };
+            while pending.is_empty() {
+                pending = match self.pending_signal.wait(pending) {
+                    Ok(p) => p,
+                    Err(e) => e.into_inner(),
+                };
+            }
+        }
+        thread::sleep(self.notify_debounce());
+        let events = self
+            .pending_events
+            .lock()
+            .map(|mut p| std::mem::take(&mut *p))
+            .unwrap_or_default();
+        if let Ok(mut listeners) = self.typed_listeners.lock() {
+            listeners.retain(|tx| events.iter().all(|ev| tx.send(ev.clone()).is_ok()));
+        }
         self.notify_ui();
     }
 
+    /// Set how long notifications are collected before listeners are woken (default 16ms)
+    pub fn set_notify_debounce(&self, interval: Duration) {
+        if let Ok(mut d) = self.notify_debounce.lock() {
+            *d = interval;
+        }
+    }
+
+    /// Current notification debounce interval
+    pub fn notify_debounce(&self) -> Duration {
+        self.notify_debounce
+            .lock()
+            .map(|d| *d)
+            .unwrap_or(NOTIFY_DEBOUNCE_DEFAULT)
+    }
+
+    /// Whether the first full `WindowsChanged` and `WorkspacesChanged` have both arrived;
+    /// until then the caches may hold partial state
+    pub fn is_synchronized(&self) -> bool {
+        self.sync_progress
+            .lock()
+            .map(|p| p.is_complete())
+            .unwrap_or(false)
+    }
+
+    // Record an initial snapshot; broadcasts `Synchronized` when it completes the pair
+    fn mark_snapshot_received(&self, mark: impl FnOnce(&mut SyncProgress)) {
+        let Ok(mut progress) = self.sync_progress.lock() else {
+            return;
+        };
+        if progress.is_complete() {
+            return;
+        }
+        mark(&mut progress);
+        if progress.is_complete() {
+            drop(progress);
+            log::info!("NiriBus: 🔗 synchronized with niri");
+            self.broadcast(NiriEvent::Synchronized);
+        }
+    }
+
     /// Register a UI listener to get state change notifications
     pub fn register_ui_listener(&self, sender: Sender<()>) {
         if let Ok(mut v) = self.update_listeners.lock() {
@@ -158,6 +500,13 @@ impl NiriBus {
         }
     }
 
+    /// Register a listener that receives the specific `NiriEvent` for each change
+    pub fn register_typed_listener(&self, sender: Sender<NiriEvent>) {
+        if let Ok(mut v) = self.typed_listeners.lock() {
+            v.push(sender);
+        }
+    }
+
     pub fn handle_json_line(&self, line: &str) {
         // Parse JSON and update caches
         match serde_json::from_str::<JsonValue>(line) {
@@ -166,7 +515,8 @@ impl NiriBus {
         }
     }
 
-    fn handle_json(&self, json: JsonValue) {
+    /// Apply one event-stream message (already parsed) to the caches
+    pub fn handle_json(&self, json: JsonValue) {
         if let Some(obj) = json.as_object() {
             if obj.contains_key("WindowsChanged") {
                 if let Some(wv) = obj
@@ -175,6 +525,7 @@ impl NiriBus {
                     .and_then(|v| v.as_array())
                 {
                     self.ingest_windows_array(wv);
+                    self.mark_snapshot_received(|p| p.windows = true);
                 }
             } else if obj.contains_key("WindowOpenedOrChanged") {
                 if let Some(win) = obj
@@ -194,12 +545,17 @@ impl NiriBus {
                         map.remove(&id);
                     }
                     // If the closed window was focused, clear focus and broadcast
+                    let mut focus_cleared = false;
                     if let Ok(mut f) = self.focused_window_id.lock()
                         && f.map(|x| x == id).unwrap_or(false)
                     {
                         *f = None;
+                        focus_cleared = true;
+                    }
+                    self.broadcast(NiriEvent::WindowsChanged);
+                    if focus_cleared {
+                        self.broadcast(NiriEvent::FocusChanged(None));
                     }
-                    self.queue_broadcast_title();
                 }
             } else if obj.contains_key("WindowLayoutsChanged") {
                 // {"WindowLayoutsChanged":{"changes":[[id, {layout...}], ...]}}
@@ -292,7 +648,7 @@ impl NiriBus {
                     }
                 }
                 // Notify UI listeners to update immediately
-                self.notify_ui();
+                self.broadcast(NiriEvent::WindowLayoutsChanged);
             } else if obj.contains_key("WindowFocusChanged") {
                 // {"WindowFocusChanged":{"id":<id|null>}}
                 let new_id_opt = obj
@@ -323,7 +679,7 @@ impl NiriBus {
                         }
                     }
                 }
-                self.queue_broadcast_title();
+                self.broadcast(NiriEvent::FocusChanged(new_id_opt));
             } else if obj.contains_key("WorkspaceActiveWindowChanged") {
                 // {"WorkspaceActiveWindowChanged":{"workspace_id":X,"active_window_id":Y|null}}
                 let new_id_opt = obj
@@ -353,19 +709,36 @@ impl NiriBus {
                         }
                     }
                 }
-                self.queue_broadcast_title();
+                self.broadcast(NiriEvent::FocusChanged(new_id_opt));
             } else if obj.contains_key("WorkspaceActivated") {
                 // {"WorkspaceActivated":{"id":<workspace_id>,"focused":true}}
-                if let Some(ws_id) = obj
-                    .get("WorkspaceActivated")
-                    .and_then(|v| v.get("id"))
-                    .and_then(|v| v.as_i64())
+                // The workspace becomes active on its output; it only takes focus with
+                // `focused: true` (activations on an unfocused output leave focus alone)
+                let activated = obj.get("WorkspaceActivated");
+                let focused = activated
+                    .and_then(|v| v.get("focused"))
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(true);
+                if let Some(ws_id) = activated.and_then(|v| v.get("id")).and_then(|v| v.as_i64())
                     && let Ok(mut list) = self.workspaces.lock()
                 {
+                    let output = list
+                        .iter()
+                        .find(|w| w.id == ws_id)
+                        .map(|w| w.output.clone());
                     for w in list.iter_mut() {
-                        w.is_focused = w.id == ws_id;
+                        if let Some(output) = &output
+                            && w.output == *output
+                        {
+                            w.is_active = w.id == ws_id;
+                        }
+                        if focused {
+                            w.is_focused = w.id == ws_id;
+                        }
                     }
-                    // Title will be driven by subsequent WindowFocusChanged; nothing to do here
+                    drop(list);
+                    // Title will be driven by subsequent WindowFocusChanged
+                    self.broadcast(NiriEvent::WorkspacesChanged);
                 }
             } else if obj.contains_key("WorkspacesChanged") {
                 // Update cached workspaces and seed focus
@@ -388,12 +761,23 @@ impl NiriBus {
                                 .get("is_focused")
                                 .and_then(|v| v.as_bool())
                                 .unwrap_or(false);
+                            // Older replies may lack `is_active`; the focused one is active
+                            let is_active = o
+                                .get("is_active")
+                                .and_then(|v| v.as_bool())
+                                .unwrap_or(is_focused);
+                            let output = o
+                                .get("output")
+                                .and_then(|v| v.as_str())
+                                .map(|s| s.to_string());
                             if let (Some(id), Some(idx)) = (id, idx) {
                                 list.push(WorkspaceInfo {
                                     id,
                                     idx,
                                     name,
                                     is_focused,
+                                    is_active,
+                                    output,
                                 });
                             }
                             if is_focused {
@@ -415,12 +799,14 @@ impl NiriBus {
                     if let Ok(mut slot) = self.workspaces.lock() {
                         *slot = list;
                     }
+                    self.broadcast(NiriEvent::WorkspacesChanged);
                     if let Some(new_id_opt) = focused_active {
                         if let Ok(mut f) = self.focused_window_id.lock() {
                             *f = new_id_opt;
                         }
-                        self.queue_broadcast_title();
+                        self.broadcast(NiriEvent::FocusChanged(new_id_opt));
                     }
+                    self.mark_snapshot_received(|p| p.workspaces = true);
                 }
             } else if obj.contains_key("KeyboardLayoutsChanged") {
                 // {"KeyboardLayoutsChanged":{"keyboard_layouts":{"names":[...],"current_idx":0}}}
@@ -442,12 +828,28 @@ impl NiriBus {
                         .get("current_idx")
                         .and_then(|v| v.as_u64())
                         .and_then(|u| usize::try_from(u).ok());
+                    let count = names.len();
                     if let Ok(mut slot) = self.keyboard_layout_names.lock() {
                         *slot = names;
                     }
-                    if let Ok(mut cur) = self.current_keyboard_layout_index.lock() {
-                        *cur = idx_opt;
-                    }
+                    self.store_keyboard_layout_index(idx_opt, count);
+                    self.broadcast(NiriEvent::KeyboardLayoutChanged);
+                }
+            } else if obj.contains_key("KeyboardLayoutSwitched") {
+                // {"KeyboardLayoutSwitched":{"idx":1}}
+                if let Some(idx) = obj
+                    .get("KeyboardLayoutSwitched")
+                    .and_then(|v| v.get("idx"))
+                    .and_then(|v| v.as_u64())
+                    .and_then(|u| usize::try_from(u).ok())
+                {
+                    let count = self
+                        .keyboard_layout_names
+                        .lock()
+                        .map(|v| v.len())
+                        .unwrap_or(0);
+                    self.store_keyboard_layout_index(Some(idx), count);
+                    self.broadcast(NiriEvent::KeyboardLayoutChanged);
                 }
             } else if obj.contains_key("OverviewOpenedOrClosed") {
                 // {"OverviewOpenedOrClosed":{"is_open":true}}
@@ -455,9 +857,11 @@ impl NiriBus {
                     .get("OverviewOpenedOrClosed")
                     .and_then(|v| v.get("is_open"))
                     .and_then(|v| v.as_bool())
-                    && let Ok(mut slot) = self.overview_is_open.lock()
                 {
-                    *slot = is_open;
+                    if let Ok(mut slot) = self.overview_is_open.lock() {
+                        *slot = is_open;
+                    }
+                    self.broadcast(NiriEvent::OverviewToggled(is_open));
                 }
             }
         }
@@ -492,74 +896,17 @@ impl NiriBus {
                         .and_then(|v| v.as_bool())
                         .unwrap_or(false);
 
-                    // Parse layout information
-                    let layout =
-                        o.get("layout")
-                            .and_then(|v| v.as_object())
-                            .and_then(|layout_obj| {
-                                let pos = layout_obj
-                                    .get("pos_in_scrolling_layout")
-                                    .and_then(|v| v.as_array())
-                                    .and_then(|arr| {
-                                        if arr.len() >= 2 {
-                                            Some([
-                                                arr[0].as_f64().unwrap_or(0.0),
-                                                arr[1].as_f64().unwrap_or(0.0),
-                                            ])
-                                        } else {
-                                            None
-                                        }
-                                    })?;
-
-                                let tile_size = layout_obj
-                                    .get("tile_size")
-                                    .and_then(|v| v.as_array())
-                                    .and_then(|arr| {
-                                        if arr.len() >= 2 {
-                                            Some([
-                                                arr[0].as_f64().unwrap_or(0.0),
-                                                arr[1].as_f64().unwrap_or(0.0),
-                                            ])
-                                        } else {
-                                            None
-                                        }
-                                    })?;
-
-                                let window_size = layout_obj
-                                    .get("window_size")
-                                    .and_then(|v| v.as_array())
-                                    .and_then(|arr| {
-                                        if arr.len() >= 2 {
-                                            Some([
-                                                arr[0].as_f64().unwrap_or(0.0),
-                                                arr[1].as_f64().unwrap_or(0.0),
-                                            ])
-                                        } else {
-                                            None
-                                        }
-                                    })?;
+                    let is_fullscreen = o
+                        .get("is_fullscreen")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false);
 
-                                let window_offset = layout_obj
-                                    .get("window_offset_in_tile")
-                                    .and_then(|v| v.as_array())
-                                    .and_then(|arr| {
-                                        if arr.len() >= 2 {
-                                            Some([
-                                                arr[0].as_f64().unwrap_or(0.0),
-                                                arr[1].as_f64().unwrap_or(0.0),
-                                            ])
-                                        } else {
-                                            None
-                                        }
-                                    })?;
+                    let is_maximized = o
+                        .get("is_maximized")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false);
 
-                                Some(WindowLayout {
-                                    pos_in_scrolling_layout: pos,
-                                    tile_size,
-                                    window_size,
-                                    window_offset_in_tile: window_offset,
-                                })
-                            });
+                    let layout = o.get("layout").and_then(parse_window_layout);
 
                     map.insert(
                         id,
@@ -570,6 +917,8 @@ impl NiriBus {
                             workspace_id,
                             is_focused,
                             is_floating,
+                            is_fullscreen,
+                            is_maximized,
                             layout,
                         },
                     );
@@ -589,7 +938,10 @@ impl NiriBus {
             *f = Some(fid);
         }
 
-        self.queue_broadcast_title();
+        self.broadcast(NiriEvent::WindowsChanged);
+        if focused_id.is_some() {
+            self.broadcast(NiriEvent::FocusChanged(focused_id));
+        }
     }
 
     fn ingest_window_object(&self, o: &serde_json::Map<String, JsonValue>) {
@@ -615,74 +967,17 @@ impl NiriBus {
                 .and_then(|v| v.as_bool())
                 .unwrap_or(false);
 
-            // Parse layout information
-            let layout = o
-                .get("layout")
-                .and_then(|v| v.as_object())
-                .and_then(|layout_obj| {
-                    let pos = layout_obj
-                        .get("pos_in_scrolling_layout")
-                        .and_then(|v| v.as_array())
-                        .and_then(|arr| {
-                            if arr.len() >= 2 {
-                                Some([
-                                    arr[0].as_f64().unwrap_or(0.0),
-                                    arr[1].as_f64().unwrap_or(0.0),
-                                ])
-                            } else {
-                                None
-                            }
-                        })?;
-
-                    let tile_size = layout_obj
-                        .get("tile_size")
-                        .and_then(|v| v.as_array())
-                        .and_then(|arr| {
-                            if arr.len() >= 2 {
-                                Some([
-                                    arr[0].as_f64().unwrap_or(0.0),
-                                    arr[1].as_f64().unwrap_or(0.0),
-                                ])
-                            } else {
-                                None
-                            }
-                        })?;
-
-                    let window_size = layout_obj
-                        .get("window_size")
-                        .and_then(|v| v.as_array())
-                        .and_then(|arr| {
-                            if arr.len() >= 2 {
-                                Some([
-                                    arr[0].as_f64().unwrap_or(0.0),
-                                    arr[1].as_f64().unwrap_or(0.0),
-                                ])
-                            } else {
-                                None
-                            }
-                        })?;
+            let is_fullscreen = o
+                .get("is_fullscreen")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
 
-                    let window_offset = layout_obj
-                        .get("window_offset_in_tile")
-                        .and_then(|v| v.as_array())
-                        .and_then(|arr| {
-                            if arr.len() >= 2 {
-                                Some([
-                                    arr[0].as_f64().unwrap_or(0.0),
-                                    arr[1].as_f64().unwrap_or(0.0),
-                                ])
-                            } else {
-                                None
-                            }
-                        })?;
+            let is_maximized = o
+                .get("is_maximized")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
 
-                    Some(WindowLayout {
-                        pos_in_scrolling_layout: pos,
-                        tile_size,
-                        window_size,
-                        window_offset_in_tile: window_offset,
-                    })
-                });
+            let layout = o.get("layout").and_then(parse_window_layout);
 
             if let Ok(mut map) = self.windows_by_id.lock() {
                 map.insert(
@@ -694,12 +989,55 @@ impl NiriBus {
                         workspace_id,
                         is_focused,
                         is_floating,
+                        is_fullscreen,
+                        is_maximized,
                         layout,
                     },
                 );
             }
-            self.queue_broadcast_title();
+            self.broadcast(NiriEvent::WindowsChanged);
+        }
+    }
+}
+
+/// What stepping to the next or previous workspace does when none is current
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoFocusStep {
+    /// Step nowhere
+    Stay,
+    /// Go to the first workspace, whichever the direction
+    First,
+}
+
+/// Neighbour of the `is_current` workspace in `list`; past either end it wraps around with
+/// `wrap`, otherwise there's none. Without a current workspace `no_focus` decides.
+fn step_workspace(
+    list: &[WorkspaceInfo],
+    is_current: impl Fn(&WorkspaceInfo) -> bool,
+    forward: bool,
+    wrap: bool,
+    no_focus: NoFocusStep,
+) -> Option<&WorkspaceInfo> {
+    let Some(cur) = list.iter().position(is_current) else {
+        return match no_focus {
+            NoFocusStep::Stay => None,
+            NoFocusStep::First => list.first(),
+        };
+    };
+    if forward {
+        if cur + 1 < list.len() {
+            Some(&list[cur + 1])
+        } else if wrap {
+            Some(&list[0])
+        } else {
+            None
         }
+    } else if cur > 0 {
+        Some(&list[cur - 1])
+    } else if wrap {
+        Some(&list[list.len() - 1])
+    } else {
+        None
     }
 }
 
@@ -717,6 +1055,28 @@ impl NiriBus {
             .unwrap_or_default()
     }
 
+    /// Workspaces living on the given output (monitor connector), ordered by idx
+    pub fn workspaces_for_output(&self, connector: &str) -> Vec<WorkspaceInfo> {
+        self.workspaces
+            .lock()
+            .map(|v| {
+                v.iter()
+                    .filter(|w| w.output.as_deref() == Some(connector))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Workspace currently shown on the given output, whether or not that output has focus
+    pub fn active_workspace_for_output(&self, connector: &str) -> Option<WorkspaceInfo> {
+        self.workspaces.lock().ok().and_then(|v| {
+            v.iter()
+                .find(|w| w.is_active && w.output.as_deref() == Some(connector))
+                .cloned()
+        })
+    }
+
     pub fn focused_workspace_index(&self) -> Option<usize> {
         let list = self.workspaces.lock().ok()?;
         for (i, ws) in list.iter().enumerate() {
@@ -727,62 +1087,40 @@ impl NiriBus {
         None
     }
 
-    pub fn next_prev_workspace_id(&self, forward: bool, wrap: bool) -> Option<i64> {
+    /// Id of the workspace after (or before) the focused one; `no_focus` decides what
+    /// happens while no workspace is focused
+    pub fn next_prev_workspace_id(
+        &self,
+        forward: bool,
+        wrap: bool,
+        no_focus: NoFocusStep,
+    ) -> Option<i64> {
         let list = self.workspaces.lock().ok()?;
-        if list.is_empty() {
-            return None;
-        }
-        let mut cur = 0usize;
-        for (i, ws) in list.iter().enumerate() {
-            if ws.is_focused {
-                cur = i;
-                break;
-            }
-        }
-        if forward {
-            if cur + 1 < list.len() {
-                Some(list[cur + 1].id)
-            } else if wrap {
-                Some(list[0].id)
-            } else {
-                None
-            }
-        } else if cur > 0 {
-            Some(list[cur - 1].id)
-        } else if wrap {
-            Some(list[list.len() - 1].id)
-        } else {
-            None
-        }
+        step_workspace(&list, |ws| ws.is_focused, forward, wrap, no_focus).map(|ws| ws.id)
     }
 
-    pub fn next_prev_workspace_idx(&self, forward: bool, wrap: bool) -> Option<i64> {
+    /// Like [`Self::next_prev_workspace_id`], returning the workspace's idx
+    pub fn next_prev_workspace_idx(
+        &self,
+        forward: bool,
+        wrap: bool,
+        no_focus: NoFocusStep,
+    ) -> Option<i64> {
         let list = self.workspaces.lock().ok()?;
-        if list.is_empty() {
-            return None;
-        }
-        let mut cur = 0usize;
-        for (i, ws) in list.iter().enumerate() {
-            if ws.is_focused {
-                cur = i;
-                break;
-            }
-        }
-        if forward {
-            if cur + 1 < list.len() {
-                Some(list[cur + 1].idx)
-            } else if wrap {
-                Some(list[0].idx)
-            } else {
-                None
-            }
-        } else if cur > 0 {
-            Some(list[cur - 1].idx)
-        } else if wrap {
-            Some(list[list.len() - 1].idx)
-        } else {
-            None
-        }
+        step_workspace(&list, |ws| ws.is_focused, forward, wrap, no_focus).map(|ws| ws.idx)
+    }
+
+    /// Like [`Self::next_prev_workspace_id`], but only among the workspaces on `connector`,
+    /// stepping from the one active there (focused or not); `no_focus` applies when none is
+    pub fn next_prev_workspace_id_for_output(
+        &self,
+        connector: &str,
+        forward: bool,
+        wrap: bool,
+        no_focus: NoFocusStep,
+    ) -> Option<i64> {
+        let list = self.workspaces_for_output(connector);
+        step_workspace(&list, |ws| ws.is_active, forward, wrap, no_focus).map(|ws| ws.id)
     }
 
     /// Snapshot of keyboard layouts state: list of names and current index (if any)
@@ -800,6 +1138,38 @@ impl NiriBus {
         (names, idx)
     }
 
+    /// Name of the active keyboard layout; None when unknown or the index doesn't match the
+    /// names niri sent
+    pub fn current_keyboard_layout_name(&self) -> Option<String> {
+        let idx = self
+            .current_keyboard_layout_index
+            .lock()
+            .ok()
+            .and_then(|g| *g)?;
+        self.keyboard_layout_names
+            .lock()
+            .ok()
+            .and_then(|names| names.get(idx).cloned())
+    }
+
+    /// Store the active layout index, dropping it when it's past the end of the names list
+    fn store_keyboard_layout_index(&self, idx: Option<usize>, count: usize) {
+        let idx = match idx {
+            Some(i) if i >= count => {
+                log::warn!(
+                    "Niri IPC: keyboard layout index {} out of range for {} layouts",
+                    i,
+                    count
+                );
+                None
+            }
+            other => other,
+        };
+        if let Ok(mut cur) = self.current_keyboard_layout_index.lock() {
+            *cur = idx;
+        }
+    }
+
     /// Whether the overview is currently open
     pub fn is_overview_open(&self) -> bool {
         self.overview_is_open.lock().map(|v| *v).unwrap_or(false)
@@ -823,13 +1193,54 @@ impl NiriBus {
         }
     }
 
+    /// Number of windows on a workspace
+    pub fn window_count_for_workspace(&self, workspace_id: i64) -> usize {
+        self.windows_by_id
+            .lock()
+            .map(|m| {
+                m.values()
+                    .filter(|w| w.workspace_id == workspace_id)
+                    .count()
+            })
+            .unwrap_or(0)
+    }
+
+    /// The focused window, if niri has reported it
+    pub fn focused_window(&self) -> Option<WindowInfo> {
+        let focused = self.focused_window_id_snapshot()?;
+        self.windows_by_id
+            .lock()
+            .ok()
+            .and_then(|m| m.get(&focused).cloned())
+    }
+
+    /// Whether the focused window is fullscreen and lives on the given output
+    pub fn is_focused_window_fullscreen(&self, connector: &str) -> bool {
+        let Some(window) = self.focused_window() else {
+            return false;
+        };
+        if !window.is_fullscreen {
+            return false;
+        }
+        self.workspaces
+            .lock()
+            .map(|list| {
+                list.iter().any(|ws| {
+                    ws.id == window.workspace_id && ws.output.as_deref() == Some(connector)
+                })
+            })
+            .unwrap_or(false)
+    }
+
     /// Get the currently focused workspace ID
     pub fn focused_workspace_id(&self) -> Option<i64> {
         let list = self.workspaces.lock().ok()?;
         list.iter().find(|ws| ws.is_focused).map(|ws| ws.id)
     }
 
-    /// Reset all internal state for testing isolation
+    /// Forget all cached state and mark the bus unsynchronized. The event stream calls this
+    /// on every (re)connect, since niri replays its full state on a fresh stream; tests use
+    /// it for isolation.
     pub fn reset(&self) {
         if let Ok(mut windows) = self.windows_by_id.lock() {
             windows.clear();
@@ -849,6 +1260,9 @@ impl NiriBus {
         if let Ok(mut ov) = self.overview_is_open.lock() {
             *ov = false;
         }
+        if let Ok(mut progress) = self.sync_progress.lock() {
+            *progress = SyncProgress::default();
+        }
     }
 }
 
@@ -858,11 +1272,115 @@ pub fn send_json_request(line: &str) -> Result<()> {
     ipc.send_request(line)
 }
 
-/// Focus workspace by index via Niri IPC Action
-pub fn focus_workspace_index(idx: i64) -> Result<()> {
-    let payload = format!(
-        "{{\"Action\":{{\"FocusWorkspace\":{{\"reference\":{{\"Index\":{}}}}}}}}}",
-        idx
-    );
-    send_json_request(&payload)
+/// Action JSON focusing a workspace
+pub fn focus_workspace_payload(workspace_ref: &WorkspaceRef) -> String {
+    serde_json::json!({
+        "Action": {
+            "FocusWorkspace": {
+                "reference": workspace_ref.to_json(),
+            }
+        }
+    })
+    .to_string()
+}
+
+/// Focus a workspace via Niri IPC Action. Prefer [`WorkspaceRef::Id`]: niri resolves an
+/// Index on the focused monitor, not the one the workspace lives on
+pub fn focus_workspace(workspace_ref: WorkspaceRef) -> Result<()> {
+    send_json_request(&focus_workspace_payload(&workspace_ref))
+}
+
+/// How an Action refers to a workspace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkspaceRef {
+    /// Position on its output (1-based, as shown by niri)
+    Index(i64),
+    /// Stable workspace id
+    Id(i64),
+    /// Named workspace from niri's config
+    Name(String),
+}
+
+impl WorkspaceRef {
+    /// `reference` object as niri expects it, e.g. `{"Index":2}`
+    pub fn to_json(&self) -> JsonValue {
+        match self {
+            WorkspaceRef::Index(idx) => serde_json::json!({ "Index": idx }),
+            WorkspaceRef::Id(id) => serde_json::json!({ "Id": id }),
+            WorkspaceRef::Name(name) => serde_json::json!({ "Name": name }),
+        }
+    }
+}
+
+/// Action JSON moving a window (or the focused one when `window_id` is None) to a workspace
+pub fn move_window_to_workspace_payload(
+    window_id: Option<i64>,
+    workspace_ref: &WorkspaceRef,
+) -> String {
+    serde_json::json!({
+        "Action": {
+            "MoveWindowToWorkspace": {
+                "focus": true,
+                "reference": workspace_ref.to_json(),
+                "window_id": window_id,
+            }
+        }
+    })
+    .to_string()
+}
+
+/// Move a window to a workspace via Niri IPC Action
+pub fn move_window_to_workspace(window_id: i64, workspace_ref: WorkspaceRef) -> Result<()> {
+    send_json_request(&move_window_to_workspace_payload(
+        Some(window_id),
+        &workspace_ref,
+    ))
+}
+
+/// Move the focused window to a workspace (by id) via Niri IPC Action
+pub fn move_focused_window_to_workspace(workspace_id: i64) -> Result<()> {
+    send_json_request(&move_window_to_workspace_payload(
+        None,
+        &WorkspaceRef::Id(workspace_id),
+    ))
+}
+
+/// Action JSON closing a window
+pub fn close_window_payload(window_id: i64) -> String {
+    serde_json::json!({ "Action": { "CloseWindow": { "id": window_id } } }).to_string()
+}
+
+/// Close the focused window via Niri IPC Action; returns false (and sends nothing) when
+/// no window is focused
+pub fn close_focused_window() -> Result<bool> {
+    let Some(id) = niri_bus().focused_window_id_snapshot() else {
+        log::warn!("Niri IPC: close_focused_window ignored, no window is focused");
+        return Ok(false);
+    };
+    send_json_request(&close_window_payload(id))?;
+    Ok(true)
+}
+
+/// Action JSON focusing a window
+pub fn focus_window_payload(window_id: i64) -> String {
+    serde_json::json!({ "Action": { "FocusWindow": { "id": window_id } } }).to_string()
+}
+
+/// Focus a window via Niri IPC Action
+pub fn focus_window(window_id: i64) -> Result<()> {
+    send_json_request(&focus_window_payload(window_id))
+}
+
+/// Close every window on a workspace; returns how many windows were asked to close
+pub fn close_workspace_windows(workspace_id: i64) -> Result<usize> {
+    let windows = niri_bus().windows_for_workspace(workspace_id);
+    for w in &windows {
+        send_json_request(&close_window_payload(w.id))?;
+    }
+    Ok(windows.len())
+}
+
+/// Open or close the overview via Niri IPC Action
+pub fn toggle_overview() -> Result<()> {
+    send_json_request("{\"Action\":{\"ToggleOverview\":{}}}")
 }
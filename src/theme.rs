@@ -0,0 +1,69 @@
+//! Theme file discovery. Themes are looked up by name (`<name>.css`) in the user's config
+//! directory first, then in the bundled `themes/` directory next to the binary's cwd.
+
+use crate::config::NiriBarConfig;
+use std::path::{Path, PathBuf};
+
+/// Bundled themes directory, relative to the current directory
+pub const THEMES_DIR: &str = "themes";
+/// Name of the structural stylesheet loaded under every theme
+pub const BASE_THEME: &str = "base";
+
+/// Directories searched for theme files, highest priority first:
+/// `<xdg_config_home>/niri-bar/themes`, `<home>/.config/niri-bar/themes`, then `themes/`
+pub fn theme_search_dirs(xdg_config_home: Option<&Path>, home: Option<&Path>) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(xdg) = xdg_config_home {
+        dirs.push(xdg.join("niri-bar").join(THEMES_DIR));
+    }
+    if let Some(home) = home {
+        let dir = home.join(".config").join("niri-bar").join(THEMES_DIR);
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs.push(PathBuf::from(THEMES_DIR));
+    dirs
+}
+
+/// [`theme_search_dirs`] for the current `$XDG_CONFIG_HOME` and home directory
+pub fn theme_search_dirs_from_env() -> Vec<PathBuf> {
+    let xdg_config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from);
+    theme_search_dirs(xdg_config_home.as_deref(), dirs::home_dir().as_deref())
+}
+
+/// First existing `<name>.css` across `dirs`
+pub fn resolve_theme_file(dirs: &[PathBuf], name: &str) -> Option<PathBuf> {
+    dirs.iter()
+        .map(|dir| dir.join(format!("{}.css", name)))
+        .find(|path| path.is_file())
+}
+
+/// Names of all themes found in `dirs` (excluding `base`), sorted and deduplicated
+pub fn available_themes(dirs: &[PathBuf]) -> Vec<String> {
+    let mut names: Vec<String> = dirs
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.filter_map(|e| e.ok()))
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "css"))
+        .filter_map(|path| path.file_stem().map(|s| s.to_string_lossy().into_owned()))
+        .filter(|name| name != BASE_THEME)
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Files to watch for hot reload under `config`: the resolved `base.css` and the resolved
+/// file of the active theme
+pub fn theme_watch_paths(config: &NiriBarConfig, dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = [BASE_THEME, config.application.theme.as_str()]
+        .iter()
+        .filter_map(|name| resolve_theme_file(dirs, name))
+        .collect();
+    paths.dedup();
+    paths
+}
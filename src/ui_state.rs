@@ -0,0 +1,89 @@
+//! Volatile UI state that survives config reloads. A reload rebuilds every module widget
+//! from scratch; modules that opt in save small pieces of state (serialized as JSON) under
+//! their identifier and placement, and read them back when they are recreated.
+
+use crate::config::ModuleConfig;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value as JsonValue;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// Where a module instance lives: its identifier, plus the output and column of its bar
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UiStateKey {
+    pub module: String,
+    pub output: String,
+    pub column: String,
+}
+
+impl UiStateKey {
+    pub fn new(module: &str, output: &str, column: &str) -> Self {
+        Self {
+            module: module.to_string(),
+            output: output.to_string(),
+            column: column.to_string(),
+        }
+    }
+
+    /// Key for the module `ident` built from `settings`, from the placement the bar sets;
+    /// `None` for a widget built outside a bar
+    pub fn for_module(ident: &str, settings: &ModuleConfig) -> Option<Self> {
+        let placement = settings.placement.as_ref()?;
+        Some(Self::new(ident, &placement.output, &placement.column))
+    }
+}
+
+/// Saved state by module placement
+#[derive(Debug, Default)]
+pub struct UiStateRegistry {
+    entries: HashMap<UiStateKey, JsonValue>,
+}
+
+impl UiStateRegistry {
+    /// Store `state` under `key`, replacing what was saved before
+    pub fn save<T: Serialize>(&mut self, key: UiStateKey, state: &T) {
+        match serde_json::to_value(state) {
+            Ok(value) => {
+                self.entries.insert(key, value);
+            }
+            Err(e) => log::warn!("UiState: can't save state for {:?}: {}", key, e),
+        }
+    }
+
+    /// State saved under `key`; `None` when nothing was saved or it no longer fits `T`
+    pub fn restore<T: DeserializeOwned>(&self, key: &UiStateKey) -> Option<T> {
+        let value = self.entries.get(key)?;
+        serde_json::from_value(value.clone())
+            .inspect_err(|e| log::debug!("UiState: dropping stale state for {:?}: {}", key, e))
+            .ok()
+    }
+
+    /// Forget the state saved under `key`
+    pub fn remove(&mut self, key: &UiStateKey) -> Option<JsonValue> {
+        self.entries.remove(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+thread_local! {
+    /// Widgets live on the GTK thread, so the shared registry does too
+    static REGISTRY: RefCell<UiStateRegistry> = RefCell::new(UiStateRegistry::default());
+}
+
+/// [`UiStateRegistry::save`] on this thread's shared registry
+pub fn save_state<T: Serialize>(key: UiStateKey, state: &T) {
+    REGISTRY.with(|registry| registry.borrow_mut().save(key, state));
+}
+
+/// [`UiStateRegistry::restore`] from this thread's shared registry
+pub fn restore_state<T: DeserializeOwned>(key: &UiStateKey) -> Option<T> {
+    REGISTRY.with(|registry| registry.borrow().restore(key))
+}
@@ -1,10 +1,180 @@
-use crate::config::WallpaperConfig;
+use crate::config::{ApplicationConfig, ConfigManager, WallpaperConfig};
 use crate::niri::WorkspaceInfo;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How long a workspace must stay focused before its wallpaper is applied
+pub const WALLPAPER_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Seconds between slideshow images when `slideshow_interval_secs` isn't set
+pub const DEFAULT_SLIDESHOW_INTERVAL_SECS: u64 = 300;
+
+/// File extensions picked up from slideshow directories
+const SLIDESHOW_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "webp", "gif", "bmp", "tif", "tiff", "avif",
+];
+
+/// Image files directly inside `dir`, sorted by file name; empty if it can't be read
+pub fn slideshow_images(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut images: Vec<PathBuf> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| {
+                    SLIDESHOW_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str())
+                })
+        })
+        .collect();
+    images.sort();
+    images
+}
+
+/// SplitMix64: tiny seeded generator so a shuffle seed always gives the same order
+#[derive(Debug, Clone)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Fisher-Yates shuffle
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = (self.next_u64() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+/// Cycles through a slideshow's images: in order, or shuffled anew after every full pass
+#[derive(Debug, Clone)]
+pub struct Slideshow {
+    images: Vec<String>,
+    order: Vec<usize>,
+    position: usize,
+    rng: Option<SplitMix64>,
+}
+
+impl Slideshow {
+    /// Slideshow over `images`; `seed` picks the shuffle order when `shuffle` is set
+    pub fn new(images: Vec<String>, shuffle: bool, seed: u64) -> Self {
+        let mut order: Vec<usize> = (0..images.len()).collect();
+        let mut rng = shuffle.then_some(SplitMix64(seed));
+        if let Some(rng) = rng.as_mut() {
+            rng.shuffle(&mut order);
+        }
+        Self {
+            images,
+            order,
+            position: 0,
+            rng,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    /// The image to show next; wraps around after the last one
+    pub fn next_image(&mut self) -> Option<&str> {
+        if self.images.is_empty() {
+            return None;
+        }
+        if self.position == self.order.len() {
+            self.position = 0;
+            if let Some(rng) = self.rng.as_mut() {
+                let last = self.order.last().copied();
+                rng.shuffle(&mut self.order);
+                // Don't show the same image twice in a row across passes
+                if self.order.len() > 1 && self.order.first().copied() == last {
+                    self.order.swap(0, 1);
+                }
+            }
+        }
+        let image = &self.images[self.order[self.position]];
+        self.position += 1;
+        Some(image)
+    }
+}
+
+/// Slideshow running on one output while its workspace stays focused
+#[derive(Debug)]
+struct ActiveSlideshow {
+    workspace_id: i64,
+    dir: String,
+    slideshow: Slideshow,
+    interval: Duration,
+    last_shown: Instant,
+}
+
+/// Holds back a value until it has been requested unchanged for `delay`
+#[derive(Debug)]
+pub struct Debounce<T> {
+    delay: Duration,
+    pending: Option<(T, Instant)>,
+}
+
+impl<T: PartialEq> Debounce<T> {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            pending: None,
+        }
+    }
+
+    /// Request `value`; a different value replaces the pending one and restarts the wait
+    pub fn request(&mut self, value: T, now: Instant) {
+        match &self.pending {
+            Some((pending, _)) if *pending == value => {}
+            _ => self.pending = Some((value, now)),
+        }
+    }
+
+    /// Drop the pending value, if any
+    pub fn cancel(&mut self) {
+        self.pending = None;
+    }
+
+    /// Whether a value is waiting
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Take the pending value once it has waited `delay`
+    pub fn take_due(&mut self, now: Instant) -> Option<T> {
+        match &self.pending {
+            Some((_, since)) if now.saturating_duration_since(*since) >= self.delay => {
+                self.pending.take().map(|(value, _)| value)
+            }
+            _ => None,
+        }
+    }
+}
 
 /// Wallpaper switcher class that handles wallpaper switching logic
 pub struct WallpaperSwitcher<E = DefaultWallpaperExecutor> {
     config: WallpaperConfig,
+    /// Per-monitor overrides as (monitor match pattern, wallpaper settings)
+    monitor_configs: Vec<(String, WallpaperConfig)>,
     executor: E,
+    /// Zero applies every switch immediately
+    debounce: Duration,
+    /// Last requested switch per output (None = all outputs) waiting out the debounce
+    pending: RefCell<BTreeMap<Option<String>, Debounce<WorkspaceInfo>>>,
+    /// Running slideshow per output, for the focused workspace's directory
+    slideshows: RefCell<BTreeMap<Option<String>, ActiveSlideshow>>,
 }
 
 /// Trait for wallpaper command execution to enable testing
@@ -13,13 +183,11 @@ pub trait WallpaperCommandExecutor {
     fn check_path_exists(&self, path: &str) -> bool;
 }
 
-/// Default implementation using system commands
+/// Dry-run implementation: logs commands instead of running them
 pub struct DefaultWallpaperExecutor;
 
 impl WallpaperCommandExecutor for DefaultWallpaperExecutor {
     fn execute_command(&self, command: &str) -> Result<(), std::io::Error> {
-        // For testing, we don't actually execute commands
-        // In production, this would run the command
         log::debug!("Would execute command: {}", command);
         Ok(())
     }
@@ -29,6 +197,27 @@ impl WallpaperCommandExecutor for DefaultWallpaperExecutor {
     }
 }
 
+/// Runs commands through `sh -c` without waiting for them; what the bar uses
+pub struct SystemWallpaperExecutor;
+
+impl WallpaperCommandExecutor for SystemWallpaperExecutor {
+    fn execute_command(&self, command: &str) -> Result<(), std::io::Error> {
+        let mut child = std::process::Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .spawn()?;
+        // Reap it off the GTK thread; swaybg keeps running until it's replaced
+        std::thread::spawn(move || {
+            let _ = child.wait();
+        });
+        Ok(())
+    }
+
+    fn check_path_exists(&self, path: &str) -> bool {
+        std::path::Path::new(path).exists()
+    }
+}
+
 impl<E> WallpaperSwitcher<E> {
     /// Create a new wallpaper switcher with the given configuration and executor
     ///
@@ -44,13 +233,68 @@ impl<E> WallpaperSwitcher<E> {
     ///     by_workspace: HashMap::new(),
     ///     special_cmd: None,
     ///     swww_options: None,
+    ///     slideshow_interval_secs: None,
+    ///     shuffle: None,
     /// };
     ///
     /// let switcher = WallpaperSwitcher::new(config, DefaultWallpaperExecutor);
     /// // Use switcher.switch_wallpaper(&workspace) to change wallpapers
     /// ```
     pub fn new(config: WallpaperConfig, executor: E) -> Self {
-        Self { config, executor }
+        Self {
+            config,
+            monitor_configs: Vec::new(),
+            executor,
+            debounce: Duration::ZERO,
+            pending: RefCell::new(BTreeMap::new()),
+            slideshows: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    /// Switcher for the global `wallpapers` settings plus every monitor's `wallpapers` override,
+    /// debounced by [`WALLPAPER_DEBOUNCE`]. Niri only reports output names, so entries that
+    /// also match on make, model or resolution are left out.
+    pub fn from_config(config: &ApplicationConfig, executor: E) -> Self {
+        let monitor_configs = config
+            .monitors
+            .iter()
+            .filter(|m| {
+                m.match_make.is_none() && m.match_model.is_none() && m.match_resolution.is_none()
+            })
+            .filter_map(|m| Some((m.match_pattern.clone(), m.wallpapers.clone()?)))
+            .collect();
+        Self::new(config.wallpapers.clone(), executor)
+            .with_monitor_configs(monitor_configs)
+            .with_debounce(WALLPAPER_DEBOUNCE)
+    }
+
+    /// Only apply a switch once its workspace has stayed requested for `debounce`; switches
+    /// superseded earlier are dropped. Pending switches run from [`Self::apply_pending`].
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Add per-monitor wallpaper settings, keyed by monitor match pattern
+    pub fn with_monitor_configs(mut self, monitor_configs: Vec<(String, WallpaperConfig)>) -> Self {
+        self.monitor_configs = monitor_configs;
+        self
+    }
+
+    /// Whether any wallpaper is set, globally or for a monitor
+    pub fn has_wallpapers(&self) -> bool {
+        std::iter::once(&self.config)
+            .chain(self.monitor_configs.iter().map(|(_, config)| config))
+            .any(|config| config.default.is_some() || !config.by_workspace.is_empty())
+    }
+
+    /// Wallpaper settings of the most specific monitor entry matching `connector`
+    fn monitor_config(&self, connector: &str) -> Option<&WallpaperConfig> {
+        self.monitor_configs
+            .iter()
+            .filter(|(pattern, _)| ConfigManager::matches_pattern(connector, pattern))
+            .max_by_key(|(pattern, _)| ConfigManager::pattern_specificity(pattern))
+            .map(|(_, config)| config)
     }
 }
 
@@ -69,6 +313,8 @@ impl WallpaperSwitcher {
     ///     by_workspace: HashMap::new(),
     ///     special_cmd: None,
     ///     swww_options: None,
+    ///     slideshow_interval_secs: None,
+    ///     shuffle: None,
     /// };
     ///
     /// let switcher = WallpaperSwitcher::new_default(config);
@@ -107,6 +353,8 @@ impl<E: WallpaperCommandExecutor> WallpaperSwitcher<E> {
     ///     by_workspace: HashMap::new(),
     ///     special_cmd: None,
     ///     swww_options: None,
+    ///     slideshow_interval_secs: None,
+    ///     shuffle: None,
     /// };
     ///
     /// let switcher = WallpaperSwitcher::new_default(config);
@@ -117,18 +365,81 @@ impl<E: WallpaperCommandExecutor> WallpaperSwitcher<E> {
     ///     idx: 1,
     ///     name: Some("workspace1".to_string()),
     ///     is_focused: true,
+    ///     is_active: true,
+    ///     output: Some("eDP-1".to_string()),
     /// };
     ///
     /// // Switch wallpaper for the workspace
     /// switcher.switch_wallpaper(&workspace);
     /// ```
     pub fn switch_wallpaper(&self, workspace: &WorkspaceInfo) {
+        match workspace.output.as_deref() {
+            Some(connector) => self.switch_wallpaper_for(workspace, connector),
+            None => self.switch(workspace, None),
+        }
+    }
+
+    /// Switch the wallpaper of monitor `connector` for `workspace`, using that monitor's
+    /// `wallpapers` override where it sets something and the global settings otherwise
+    pub fn switch_wallpaper_for(&self, workspace: &WorkspaceInfo, connector: &str) {
+        self.switch(workspace, Some(connector));
+    }
+
+    fn switch(&self, workspace: &WorkspaceInfo, connector: Option<&str>) {
+        if !self.debounce.is_zero() {
+            log::debug!(
+                "WallpaperSwitcher: ⏳ holding switch to workspace {} for {:?}",
+                workspace.idx,
+                self.debounce
+            );
+            self.pending
+                .borrow_mut()
+                .entry(connector.map(str::to_string))
+                .or_insert_with(|| Debounce::new(self.debounce))
+                .request(workspace.clone(), Instant::now());
+            return;
+        }
+        self.apply_switch(workspace, connector);
+    }
+
+    /// Apply the pending switches that have waited out the debounce; returns how many ran.
+    /// Call this periodically (e.g. from a timer) when debouncing.
+    pub fn apply_pending(&self) -> usize {
+        self.apply_pending_at(Instant::now())
+    }
+
+    /// [`Self::apply_pending`] as of `now`
+    pub fn apply_pending_at(&self, now: Instant) -> usize {
+        let due: Vec<(Option<String>, WorkspaceInfo)> = self
+            .pending
+            .borrow_mut()
+            .iter_mut()
+            .filter_map(|(connector, debounce)| Some((connector.clone(), debounce.take_due(now)?)))
+            .collect();
+        for (connector, workspace) in &due {
+            self.apply_switch(workspace, connector.as_deref());
+        }
+        due.len()
+    }
+
+    /// Whether a debounced switch is still waiting
+    pub fn has_pending(&self) -> bool {
+        self.pending.borrow().values().any(Debounce::is_pending)
+    }
+
+    fn apply_switch(&self, workspace: &WorkspaceInfo, connector: Option<&str>) {
         log::info!(
-            "WallpaperSwitcher: 📸 switch requested for workspace: {} ({:?})",
+            "WallpaperSwitcher: 📸 switch requested for workspace: {} ({:?}) on {}",
             workspace.idx,
-            workspace.name
+            workspace.name,
+            connector.unwrap_or("all outputs")
         );
-        if let Some(image_path) = self.resolve_wallpaper_path(workspace) {
+        let monitor = connector.and_then(|c| self.monitor_config(c));
+        // This output's slideshow stops rotating once its workspace is no longer shown
+        self.slideshows.borrow_mut().retain(|output, active| {
+            output.as_deref() != connector || active.workspace_id == workspace.id
+        });
+        if let Some(image_path) = self.resolve_wallpaper_path(workspace, monitor) {
             // Expand tilde to home directory
             let expanded_path = self.expand_tilde(&image_path);
             log::info!(
@@ -136,7 +447,14 @@ impl<E: WallpaperCommandExecutor> WallpaperSwitcher<E> {
                 image_path,
                 expanded_path
             );
-            self.apply_wallpaper_command(&expanded_path);
+            if Path::new(&expanded_path).is_dir() {
+                self.start_slideshow(workspace, connector, monitor, expanded_path);
+                return;
+            }
+            self.slideshows
+                .borrow_mut()
+                .remove(&connector.map(str::to_string));
+            self.apply_wallpaper_command(&expanded_path, connector, monitor);
         } else {
             log::info!(
                 "WallpaperSwitcher: No wallpaper path resolved for workspace: {} ({:?})",
@@ -146,25 +464,145 @@ impl<E: WallpaperCommandExecutor> WallpaperSwitcher<E> {
         }
     }
 
-    /// Resolve the wallpaper path for a given workspace
-    fn resolve_wallpaper_path(&self, workspace: &WorkspaceInfo) -> Option<String> {
-        // Try by name first, then by index, then default
+    /// Show the first image of `dir` and keep rotating it until the workspace loses focus. A
+    /// slideshow already running for this workspace and directory carries on where it was.
+    fn start_slideshow(
+        &self,
+        workspace: &WorkspaceInfo,
+        connector: Option<&str>,
+        monitor: Option<&WallpaperConfig>,
+        dir: String,
+    ) {
+        let key = connector.map(str::to_string);
+        if let Some(active) = self.slideshows.borrow().get(&key)
+            && active.workspace_id == workspace.id
+            && active.dir == dir
+        {
+            return;
+        }
+
+        let images: Vec<String> = slideshow_images(Path::new(&dir))
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        if images.is_empty() {
+            log::warn!(
+                "WallpaperSwitcher: 🤷 no images in slideshow directory {}",
+                dir
+            );
+            self.slideshows.borrow_mut().remove(&key);
+            return;
+        }
+        let interval_secs = monitor
+            .and_then(|m| m.slideshow_interval_secs)
+            .or(self.config.slideshow_interval_secs)
+            .unwrap_or(DEFAULT_SLIDESHOW_INTERVAL_SECS)
+            .max(1);
+        let shuffle = monitor
+            .and_then(|m| m.shuffle)
+            .or(self.config.shuffle)
+            .unwrap_or(false);
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+        log::info!(
+            "WallpaperSwitcher: 🎞️ slideshow of {} images from {} every {}s{}",
+            images.len(),
+            dir,
+            interval_secs,
+            if shuffle { " (shuffled)" } else { "" }
+        );
+
+        let mut slideshow = Slideshow::new(images, shuffle, seed);
+        let first = slideshow.next_image().map(str::to_string);
+        self.slideshows.borrow_mut().insert(
+            key,
+            ActiveSlideshow {
+                workspace_id: workspace.id,
+                dir,
+                slideshow,
+                interval: Duration::from_secs(interval_secs),
+                last_shown: Instant::now(),
+            },
+        );
+        if let Some(image) = first {
+            self.apply_wallpaper_command(&image, connector, monitor);
+        }
+    }
+
+    /// Show the next image of every slideshow whose interval has passed; returns how many
+    /// changed. Call this periodically (e.g. from a timer) while slideshows may run.
+    pub fn advance_slideshows(&self) -> usize {
+        self.advance_slideshows_at(Instant::now())
+    }
+
+    /// [`Self::advance_slideshows`] as of `now`
+    pub fn advance_slideshows_at(&self, now: Instant) -> usize {
+        let due: Vec<(Option<String>, String)> = self
+            .slideshows
+            .borrow_mut()
+            .iter_mut()
+            .filter(|(_, active)| {
+                now.saturating_duration_since(active.last_shown) >= active.interval
+            })
+            .filter_map(|(connector, active)| {
+                active.last_shown = now;
+                Some((
+                    connector.clone(),
+                    active.slideshow.next_image()?.to_string(),
+                ))
+            })
+            .collect();
+        for (connector, image) in &due {
+            let monitor = connector.as_deref().and_then(|c| self.monitor_config(c));
+            self.apply_wallpaper_command(image, connector.as_deref(), monitor);
+        }
+        due.len()
+    }
+
+    /// Whether a slideshow is rotating on any output
+    pub fn has_slideshow(&self) -> bool {
+        !self.slideshows.borrow().is_empty()
+    }
+
+    /// Resolve the wallpaper path for a given workspace: the monitor's `by_workspace` (name,
+    /// then index) and `default`, then the same from the global settings
+    fn resolve_wallpaper_path(
+        &self,
+        workspace: &WorkspaceInfo,
+        monitor: Option<&WallpaperConfig>,
+    ) -> Option<String> {
         let key_name = workspace.name.as_deref().unwrap_or("");
         let key_idx = workspace.idx.to_string();
+        let lookup = |config: &WallpaperConfig| {
+            config
+                .by_workspace
+                .get(key_name)
+                .or_else(|| config.by_workspace.get(&key_idx))
+                .or(config.default.as_ref())
+                .cloned()
+        };
 
-        self.config
-            .by_workspace
-            .get(key_name)
-            .or_else(|| self.config.by_workspace.get(&key_idx))
-            .or(self.config.default.as_ref())
-            .cloned()
+        monitor.and_then(lookup).or_else(|| lookup(&self.config))
     }
 
     /// Apply wallpaper using available providers
-    fn apply_wallpaper_command(&self, image_path: &str)
-    where
+    fn apply_wallpaper_command(
+        &self,
+        image_path: &str,
+        connector: Option<&str>,
+        monitor: Option<&WallpaperConfig>,
+    ) where
         E: WallpaperCommandExecutor,
     {
+        let special_cmd = monitor
+            .and_then(|m| m.special_cmd.as_ref())
+            .or(self.config.special_cmd.as_ref());
+        let swww_options = monitor
+            .and_then(|m| m.swww_options.as_ref())
+            .or(self.config.swww_options.as_ref());
+
         // Order: special_cmd -> swww (daemon) -> swaybg -> noop
         log::info!(
             "WallpaperSwitcher: 📸 switch requested for image: {}",
@@ -172,7 +610,7 @@ impl<E: WallpaperCommandExecutor> WallpaperSwitcher<E> {
         );
 
         // 1) special_cmd
-        if let Some(cmd) = &self.config.special_cmd {
+        if let Some(cmd) = special_cmd {
             log::info!("WallpaperSwitcher: 🎯 taking special_cmd path: {}", cmd);
             let prepared = cmd.replace("${current_workspace_image}", image_path);
             // best-effort split; users can wrap their command to handle complex args
@@ -211,23 +649,17 @@ impl<E: WallpaperCommandExecutor> WallpaperSwitcher<E> {
             // Build swww command with options
             let mut cmd_string = String::from("swww img");
 
+            // Only touch the monitor the workspace is on
+            if let Some(connector) = connector {
+                cmd_string.push_str(&format!(" --outputs {}", connector));
+            }
+
             // Add swww options if configured
-            if let Some(swww_opts) = &self.config.swww_options {
-                cmd_string.push_str(&format!(" --transition-type {}", swww_opts.transition_type));
-
-                // Transition duration (only if not 'simple' or 'none')
-                if !matches!(swww_opts.transition_type.as_str(), "simple" | "none") {
-                    cmd_string.push_str(&format!(
-                        " --transition-duration {}",
-                        swww_opts.transition_duration
-                    ));
+            if let Some(swww_opts) = swww_options {
+                for arg in swww_opts.to_args() {
+                    cmd_string.push(' ');
+                    cmd_string.push_str(&arg);
                 }
-
-                cmd_string.push_str(&format!(" --transition-step {}", swww_opts.transition_step));
-                cmd_string.push_str(&format!(" --transition-fps {}", swww_opts.transition_fps));
-                cmd_string.push_str(&format!(" --filter {}", swww_opts.filter));
-                cmd_string.push_str(&format!(" --resize {}", swww_opts.resize));
-                cmd_string.push_str(&format!(" --fill-color {}", swww_opts.fill_color));
             }
 
             // Add the image path
@@ -266,9 +698,15 @@ impl<E: WallpaperCommandExecutor> WallpaperSwitcher<E> {
         );
         if let Some(swaybg) = swaybg_path {
             log::info!("WallpaperSwitcher: 🧪 using swaybg → fill {}", image_path);
-            // kill existing (best-effort), then spawn
-            let _ = self.executor.execute_command("pkill swaybg");
-            let swaybg_cmd = format!("{} -m fill -i {}", swaybg, image_path);
+            // Replace the swaybg drawing this output (or every swaybg), in one command so
+            // the kill can't race the new instance. The anchored pattern never matches the
+            // shell running the command.
+            let swaybg_cmd = match connector {
+                Some(connector) => format!(
+                    "pkill -f '^{swaybg} -o {connector} '; {swaybg} -o {connector} -m fill -i {image_path}"
+                ),
+                None => format!("pkill swaybg; {swaybg} -m fill -i {image_path}"),
+            };
             match self.executor.execute_command(&swaybg_cmd) {
                 Ok(_) => log::info!("WallpaperSwitcher: ✅ applied via swaybg"),
                 Err(e) => log::error!("WallpaperSwitcher: 💥 failed to execute swaybg: {}", e),
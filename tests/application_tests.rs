@@ -1,5 +1,8 @@
-use niri_bar::application::Application;
+use niri_bar::application::{
+    Application, BarReloadAction, ExistingBar, MonitorDelta, ShutdownReport,
+};
 use niri_bar::config::{ConfigManager, LoggingConfig};
+use niri_bar::niri::NiriIpc;
 use pretty_assertions::assert_eq;
 use std::sync::Arc;
 use std::time::Duration;
@@ -14,6 +17,10 @@ fn test_application_creation() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let app = Application::new_with_gtk(logging_config, false);
@@ -30,6 +37,10 @@ fn test_application_config_manager() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let app = Application::new(logging_config).unwrap();
@@ -49,6 +60,10 @@ fn test_application_monitor_management() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let app = Application::new(logging_config).unwrap();
@@ -80,6 +95,10 @@ fn test_application_creation_with_invalid_logging_config() {
             include_file: true,
             include_line: false,
             include_class: true,
+            max_size_bytes: 10 * 1024 * 1024,
+            max_files: 5,
+            module_levels: Default::default(),
+            journald: false,
         };
 
         let app = Application::new_with_gtk(logging_config, false);
@@ -101,6 +120,10 @@ fn test_application_creation_with_empty_file_path() {
         include_file: false,
         include_line: false,
         include_class: false,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let app = Application::new_with_gtk(logging_config, false);
@@ -117,6 +140,10 @@ fn test_application_monitor_count() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let app = Application::new(logging_config).unwrap();
@@ -142,6 +169,10 @@ fn test_application_getters() {
         include_file: true,
         include_line: true,
         include_class: false,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let app = Application::new(logging_config.clone()).unwrap();
@@ -167,6 +198,10 @@ fn test_application_data_integrity() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let app = Application::new(logging_config.clone()).unwrap();
@@ -197,6 +232,10 @@ fn test_application_module_format_collection() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let app = Application::new(logging_config).unwrap();
@@ -217,6 +256,10 @@ fn test_application_config_reloading_simulation() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let app = Application::new(logging_config).unwrap();
@@ -243,6 +286,10 @@ fn test_application_monitor_pattern_matching() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let app = Application::new(logging_config).unwrap();
@@ -267,6 +314,10 @@ fn test_application_performance() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let start = Instant::now();
@@ -307,6 +358,10 @@ fn test_application_config_validation() {
             include_file: true,
             include_line: true,
             include_class: true,
+            max_size_bytes: 10 * 1024 * 1024,
+            max_files: 5,
+            module_levels: Default::default(),
+            journald: false,
         },
         LoggingConfig {
             level: "debug".to_string(),
@@ -316,6 +371,10 @@ fn test_application_config_validation() {
             include_file: true,
             include_line: true,
             include_class: true,
+            max_size_bytes: 10 * 1024 * 1024,
+            max_files: 5,
+            module_levels: Default::default(),
+            journald: false,
         },
     ];
 
@@ -340,6 +399,10 @@ fn test_application_resource_management() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let app = Application::new(logging_config).unwrap();
@@ -366,6 +429,45 @@ fn test_application_resource_management() {
     assert!(config_manager.get_config().is_none());
 }
 
+#[test]
+fn test_sync_monitors_delta() {
+    let names = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    // Nothing changed
+    let delta = Application::sync_monitors(&names(&["eDP-1", "DP-1"]), &names(&["DP-1", "eDP-1"]));
+    assert!(delta.is_empty());
+
+    // External monitor plugged in
+    assert_eq!(
+        Application::sync_monitors(&names(&["eDP-1"]), &names(&["eDP-1", "DP-2"])),
+        MonitorDelta {
+            added: names(&["DP-2"]),
+            removed: vec![],
+        }
+    );
+
+    // Docked: laptop panel off, two externals swapped in
+    assert_eq!(
+        Application::sync_monitors(
+            &names(&["eDP-1", "DP-1"]),
+            &names(&["HDMI-A-1", "DP-1", "DP-3"])
+        ),
+        MonitorDelta {
+            added: names(&["DP-3", "HDMI-A-1"]),
+            removed: names(&["eDP-1"]),
+        }
+    );
+
+    // Everything unplugged
+    assert_eq!(
+        Application::sync_monitors(&names(&["DP-1", "eDP-1"]), &[]),
+        MonitorDelta {
+            added: vec![],
+            removed: names(&["DP-1", "eDP-1"]),
+        }
+    );
+}
+
 // ===== PROPERTY-BASED TESTS =====
 
 #[cfg(test)]
@@ -390,6 +492,10 @@ mod property_tests {
                 include_file,
                 include_line,
                 include_class,
+                max_size_bytes: 10 * 1024 * 1024,
+                max_files: 5,
+                module_levels: Default::default(),
+                journald: false,
             };
 
             let app = Application::new_with_gtk(logging_config, false);
@@ -397,3 +503,208 @@ mod property_tests {
         }
     }
 }
+
+const RELOAD_BASE: &str = r#"
+application:
+  theme: wombat
+  modules:
+    clock: { format: "%H:%M" }
+    battery: { warn_threshold: 30 }
+  layouts:
+    three_column: { columns: { left: { modules: [clock] }, right: { modules: [battery] } } }
+    laptop: { columns: { center: { modules: [clock, battery] } } }
+  monitors:
+    - match: "^eDP-1$"
+      layout: { columns: { center: { modules: [clock, battery] } } }
+      modules:
+        battery: { warn_threshold: 20 }
+    - match: ".*"
+"#;
+
+fn reload_config(yaml: &str) -> niri_bar::config::NiriBarConfig {
+    ConfigManager::parse_config(yaml.as_bytes()).unwrap()
+}
+
+#[test]
+fn test_affected_monitors_on_reload() {
+    let connectors: Vec<String> = ["eDP-1", "DP-1", "HDMI-A-1"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let old = reload_config(RELOAD_BASE);
+
+    // First load touches every bar; an identical reload touches none
+    assert_eq!(
+        Application::affected_monitors(None, &old, &connectors),
+        connectors
+    );
+    assert!(Application::affected_monitors(Some(&old), &old.clone(), &connectors).is_empty());
+
+    // A monitor-only module override affects just that monitor
+    let new = reload_config(&RELOAD_BASE.replace("warn_threshold: 20", "warn_threshold: 15"));
+    assert_eq!(
+        Application::affected_monitors(Some(&old), &new, &connectors),
+        vec!["eDP-1"]
+    );
+
+    // A global module change shadowed on eDP-1 only reaches the others
+    let new = reload_config(&RELOAD_BASE.replace("warn_threshold: 30", "warn_threshold: 35"));
+    assert_eq!(
+        Application::affected_monitors(Some(&old), &new, &connectors),
+        vec!["DP-1", "HDMI-A-1"]
+    );
+
+    // Logging changes don't touch any bar
+    let mut new = old.clone();
+    new.logging.level = "debug".to_string();
+    assert!(Application::affected_monitors(Some(&old), &new, &connectors).is_empty());
+
+    // Theme and bar options are global unless overridden
+    let new = reload_config(&RELOAD_BASE.replace("theme: wombat", "theme: dracula"));
+    assert_eq!(
+        Application::affected_monitors(Some(&old), &new, &connectors),
+        connectors
+    );
+    let new = reload_config(&RELOAD_BASE.replace(
+        "    - match: \".*\"\n",
+        "    - match: \".*\"\n      height: 32\n",
+    ));
+    assert_eq!(
+        Application::affected_monitors(Some(&old), &new, &connectors),
+        vec!["DP-1", "HDMI-A-1"]
+    );
+
+    // Disabling a monitor is a change for that monitor only
+    let new = reload_config(&RELOAD_BASE.replace(
+        "    - match: \"^eDP-1$\"\n",
+        "    - match: \"^eDP-1$\"\n      show_bar: false\n",
+    ));
+    assert_eq!(
+        Application::affected_monitors(Some(&old), &new, &connectors),
+        vec!["eDP-1"]
+    );
+
+    // A per-workspace layout on one monitor affects that monitor
+    let new = reload_config(&RELOAD_BASE.replace(
+        "    - match: \".*\"\n",
+        "    - match: \"^DP-1$\"\n      by_workspace: { \"2\": laptop }\n    - match: \".*\"\n",
+    ));
+    assert_eq!(
+        Application::affected_monitors(Some(&old), &new, &connectors),
+        vec!["DP-1"]
+    );
+}
+
+#[test]
+fn test_bar_reload_action_for_disabled_monitors() {
+    use BarReloadAction::*;
+    use ExistingBar::*;
+
+    // Enabled monitors get a bar whatever the flag says
+    for hide in [false, true] {
+        assert_eq!(Application::bar_reload_action(true, Missing, hide), Create);
+        assert_eq!(Application::bar_reload_action(true, Present, hide), Update);
+        assert_eq!(
+            Application::bar_reload_action(true, HiddenWhileDisabled, hide),
+            Reshow
+        );
+        assert_eq!(Application::bar_reload_action(false, Missing, hide), Keep);
+    }
+    // Disabling destroys by default, hides when asked to
+    assert_eq!(
+        Application::bar_reload_action(false, Present, false),
+        Destroy
+    );
+    assert_eq!(Application::bar_reload_action(false, Present, true), Hide);
+    assert_eq!(
+        Application::bar_reload_action(false, HiddenWhileDisabled, true),
+        Keep
+    );
+    // Turning the flag off while disabled drops the kept bar
+    assert_eq!(
+        Application::bar_reload_action(false, HiddenWhileDisabled, false),
+        Destroy
+    );
+
+    // The flag resolves per monitor, and a monitor toggling enabled keeps one bar throughout
+    let manager = ConfigManager::from_config(reload_config(&RELOAD_BASE.replace(
+        "    - match: \"^eDP-1$\"\n",
+        "    - match: \"^eDP-1$\"\n      hide_when_disabled: true\n",
+    )));
+    assert!(manager.get_monitor_hide_when_disabled("eDP-1"));
+    assert!(!manager.get_monitor_hide_when_disabled("DP-1"));
+
+    let mut bar = Missing;
+    let mut created = 0;
+    for enabled in [true, false, false, true, false, true] {
+        match Application::bar_reload_action(enabled, bar, true) {
+            Create => {
+                created += 1;
+                bar = Present;
+            }
+            Reshow | Update => bar = Present,
+            Hide => bar = HiddenWhileDisabled,
+            Keep => {}
+            Destroy => bar = Missing,
+        }
+        let expected = if enabled {
+            Present
+        } else {
+            HiddenWhileDisabled
+        };
+        assert_eq!(bar, expected, "enabled: {}", enabled);
+    }
+    assert_eq!(created, 1);
+}
+
+fn headless_app() -> Application {
+    let logging_config = LoggingConfig {
+        level: "debug".to_string(),
+        file: "".to_string(),
+        console: true,
+        format: "iso8601".to_string(),
+        include_file: true,
+        include_line: true,
+        include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
+    };
+    Application::new_with_gtk(logging_config, false).unwrap()
+}
+
+#[test]
+fn test_shutdown_stops_ipc_and_watcher() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let config_path = dir.path().join("niri-bar.yaml");
+    std::fs::write(&config_path, "application: {}\n").unwrap();
+
+    let mut app = headless_app();
+    app.set_config_path(config_path);
+    app.setup_config_checking();
+    let ipc = NiriIpc::with_socket_path(dir.path().join("niri.sock").display().to_string());
+    app.attach_niri_ipc(ipc.clone());
+    assert!(!ipc.is_stopped());
+
+    assert_eq!(
+        app.shutdown(),
+        ShutdownReport {
+            ipc_stopped: true,
+            watcher_aborted: true,
+            bars_closed: 0,
+        }
+    );
+    assert!(ipc.is_stopped());
+    assert_eq!(app.monitor_count(), 0);
+
+    // Nothing left to tear down the second time
+    assert_eq!(app.shutdown(), ShutdownReport::default());
+}
+
+#[test]
+fn test_shutdown_without_background_work() {
+    // Nothing was started in headless mode
+    let mut app = headless_app();
+    assert_eq!(app.shutdown(), ShutdownReport::default());
+}
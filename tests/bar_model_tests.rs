@@ -0,0 +1,455 @@
+use niri_bar::bar_model::{
+    BarModel, ColumnExtent, ColumnSlot, InputRect, OverflowDecision, PlacedModule, input_region,
+    max_width_chars, module_margin,
+};
+use niri_bar::config::{
+    ColumnOverflowPolicy, ColumnSpec, ConfigManager, DisplayMode, ModuleConfig, TextAlign,
+};
+use niri_bar::modules::separator;
+use niri_bar::monitor::MonitorInfo;
+use pretty_assertions::assert_eq;
+use std::collections::HashMap;
+
+fn monitor(width: i32) -> MonitorInfo {
+    MonitorInfo {
+        connector: "eDP-1".to_string(),
+        manufacturer: None,
+        model: None,
+        logical_size: (width, 1080),
+        scale_factor: 1,
+    }
+}
+
+fn column(modules: &[&str], overflow: ColumnOverflowPolicy) -> ColumnSpec {
+    ColumnSpec {
+        modules: modules.iter().map(|m| m.to_string()).collect(),
+        overflow,
+        ..ColumnSpec::default()
+    }
+}
+
+fn placed(name: &str, align: TextAlign) -> PlacedModule {
+    PlacedModule {
+        name: name.to_string(),
+        align,
+        margin: 0,
+        fixed_width: None,
+    }
+}
+
+#[test]
+fn test_bar_model_places_modules_by_column() {
+    let mut configs = HashMap::new();
+    configs.insert(
+        "battery".to_string(),
+        ModuleConfig {
+            display: Some(DisplayMode::Hide),
+            ..ModuleConfig::default()
+        },
+    );
+    let columns = vec![
+        (
+            "left".to_string(),
+            column(&["workspaces", "window_title"], ColumnOverflowPolicy::Hide),
+        ),
+        (
+            "right".to_string(),
+            // Hidden and unknown modules never get a slot
+            column(&["battery", "nope", "clock"], ColumnOverflowPolicy::Hide),
+        ),
+        (
+            "status bits".to_string(),
+            column(&[], ColumnOverflowPolicy::Hide),
+        ),
+    ];
+    let model = BarModel::new(&columns, &monitor(1800), &configs);
+
+    assert_eq!(model.columns.len(), 3);
+    let [left, right, empty] = &model.columns[..] else {
+        unreachable!()
+    };
+    assert_eq!(
+        left.modules,
+        vec![
+            placed("workspaces", TextAlign::Left),
+            placed("window_title", TextAlign::Left)
+        ]
+    );
+    assert_eq!(right.align, TextAlign::Right);
+    assert_eq!(right.modules, vec![placed("clock", TextAlign::Right)]);
+    assert!(empty.modules.is_empty());
+    assert_eq!(empty.css_name(), "status_bits");
+
+    // Equal shares of the logical width
+    for c in &model.columns {
+        assert_eq!((c.width, c.fixed, c.budget), (600, false, Some(600)));
+    }
+}
+
+#[test]
+fn test_bar_model_column_sizing() {
+    let fixed = ColumnSpec {
+        width: Some(200),
+        ..ColumnSpec::default()
+    };
+    let capped = ColumnSpec {
+        max_width: Some(300),
+        ..ColumnSpec::default()
+    };
+    let columns = vec![
+        ("a".to_string(), fixed),
+        ("b".to_string(), capped),
+        ("c".to_string(), ColumnSpec::default()),
+    ];
+    let model = BarModel::new(&columns, &monitor(1200), &HashMap::new());
+    let sizes: Vec<(i32, bool, Option<i32>)> = model
+        .columns
+        .iter()
+        .map(|c| (c.width, c.fixed, c.budget))
+        .collect();
+    assert_eq!(
+        sizes,
+        vec![
+            (200, true, Some(200)),
+            (300, true, Some(300)),
+            (400, false, Some(400))
+        ]
+    );
+
+    // Before GDK reports the monitor size nothing overflows
+    let model = BarModel::new(&columns[2..], &monitor(0), &HashMap::new());
+    assert_eq!(model.columns[0].budget, None);
+    let arrangement = model.columns[0].arrange(20, &[5000]);
+    assert_eq!(arrangement.decision, OverflowDecision::Fits);
+}
+
+#[test]
+fn test_bar_model_hide_overflow() {
+    let columns = vec![(
+        "left".to_string(),
+        column(&["clock", "battery", "mem"], ColumnOverflowPolicy::Hide),
+    )];
+    let model = BarModel::new(&columns, &monitor(300), &HashMap::new());
+    let arrangement = model.columns[0].arrange(24, &[120, 200, 100]);
+
+    // The battery doesn't fit after the clock; the smaller mem module still does
+    assert_eq!(arrangement.decision, OverflowDecision::Overflow(vec![1]));
+    assert_eq!(
+        arrangement.slots,
+        vec![ColumnSlot::Module(0), ColumnSlot::Module(2)]
+    );
+    // Hidden, not listed anywhere
+    assert!(arrangement.kebab.is_empty());
+    assert!(!arrangement.show_kebab());
+
+    let arrangement = model.columns[0].arrange(24, &[100, 100, 100]);
+    assert_eq!(arrangement.decision, OverflowDecision::Fits);
+    assert_eq!(arrangement.slots.len(), 3);
+}
+
+#[test]
+fn test_bar_model_kebab_overflow() {
+    let columns = vec![(
+        "right".to_string(),
+        column(&["tray", "clock", "battery"], ColumnOverflowPolicy::Kebab),
+    )];
+    let model = BarModel::new(&columns, &monitor(300), &HashMap::new());
+
+    // 300 fits all three under hide, but the kebab button's 30px comes out of the budget
+    let arrangement = model.columns[0].arrange(30, &[100, 100, 100]);
+    assert_eq!(arrangement.decision, OverflowDecision::Overflow(vec![2]));
+    assert_eq!(arrangement.kebab, vec![2]);
+    assert!(arrangement.show_kebab());
+    // Right-aligned column: leading spacer, then what fits
+    assert_eq!(
+        arrangement.slots,
+        vec![
+            ColumnSlot::Spacer,
+            ColumnSlot::Module(0),
+            ColumnSlot::Module(1)
+        ]
+    );
+
+    let arrangement = model.columns[0].arrange(30, &[50, 50, 50]);
+    assert!(!arrangement.show_kebab());
+}
+
+#[test]
+fn test_bar_model_shrink_label_width_is_an_even_pixel_share() {
+    let columns = vec![(
+        "center".to_string(),
+        column(
+            &["window_title", "clock", "battery"],
+            ColumnOverflowPolicy::Shrink,
+        ),
+    )];
+    let model = BarModel::new(&columns, &monitor(300), &HashMap::new());
+    // The still-empty clock label gets the same share as the long title
+    let share = model.columns[0].shrink_label_width(&[400, 0, 60], &[true, true, false]);
+    assert_eq!(share, Some(120));
+    assert_eq!(max_width_chars(120, 8.0), 15);
+    assert_eq!(max_width_chars(0, 8.0), 1);
+    assert_eq!(
+        model.columns[0].shrink_label_width(&[400, 0, 60], &[false, false, false]),
+        None
+    );
+}
+
+#[test]
+fn test_bar_model_shrink_keeps_everything_placed() {
+    let columns = vec![(
+        "center".to_string(),
+        column(&["window_title", "clock"], ColumnOverflowPolicy::Shrink),
+    )];
+    let model = BarModel::new(&columns, &monitor(300), &HashMap::new());
+    let arrangement = model.columns[0].arrange(30, &[400, 200]);
+    assert_eq!(
+        arrangement.decision,
+        OverflowDecision::Shrink { ratio: 0.5 }
+    );
+    assert!(arrangement.kebab.is_empty());
+    assert_eq!(
+        arrangement.slots,
+        vec![
+            ColumnSlot::Spacer,
+            ColumnSlot::Module(0),
+            ColumnSlot::Module(1),
+            ColumnSlot::Spacer
+        ]
+    );
+}
+
+#[test]
+fn test_bar_model_alignment_from_config() {
+    let yaml = r#"
+application:
+  modules:
+    battery: { align: right }
+    clock: { align: right }
+  layouts:
+    main:
+      columns:
+        left:
+          modules: [window_title, battery, clock]
+          overflow: kebab
+        middle:
+          modules: [workspaces]
+          align: center
+  monitors: []
+"#;
+    let config = ConfigManager::parse_config(yaml.as_bytes()).unwrap();
+    let layout = &config.application.layouts["main"];
+    let model = BarModel::from_layout(layout, &monitor(1000), &config.application.modules);
+
+    let left = &model.columns[0];
+    assert_eq!(
+        left.modules,
+        vec![
+            placed("window_title", TextAlign::Left),
+            placed("battery", TextAlign::Right),
+            placed("clock", TextAlign::Right)
+        ]
+    );
+    // The title stays on the left edge and the status on the right
+    let arrangement = left.arrange(20, &[100, 50, 50]);
+    assert_eq!(
+        arrangement.slots,
+        vec![
+            ColumnSlot::Module(0),
+            ColumnSlot::Spacer,
+            ColumnSlot::Module(1),
+            ColumnSlot::Module(2)
+        ]
+    );
+    // Overflow keeps the remaining modules' groups; the overflowed one goes to the kebab
+    let arrangement = left.arrange(20, &[300, 200, 100]);
+    assert_eq!(arrangement.kebab, vec![1]);
+    assert_eq!(
+        arrangement.slots,
+        vec![
+            ColumnSlot::Module(0),
+            ColumnSlot::Spacer,
+            ColumnSlot::Module(2)
+        ]
+    );
+
+    let middle = &model.columns[1];
+    assert_eq!(middle.align, TextAlign::Center);
+    assert_eq!(
+        middle.arrange(20, &[80]).slots,
+        vec![
+            ColumnSlot::Spacer,
+            ColumnSlot::Module(0),
+            ColumnSlot::Spacer
+        ]
+    );
+}
+
+#[test]
+fn test_module_padding_sets_margins() {
+    let yaml = r#"
+application:
+  modules:
+    clock: { padding: 8 }
+    battery: { padding: -4 }
+  layouts:
+    main:
+      columns:
+        right:
+          modules: [clock, battery, mem]
+  monitors: []
+"#;
+    let config = ConfigManager::parse_config(yaml.as_bytes()).unwrap();
+    let modules = &config.application.modules;
+    assert_eq!(modules["clock"].padding, Some(8));
+    assert_eq!(modules["battery"].padding, Some(-4));
+
+    // Same margin on both sides; unset or negative padding leaves none
+    assert_eq!(module_margin(modules.get("clock")), 8);
+    assert_eq!(module_margin(modules.get("battery")), 0);
+    assert_eq!(module_margin(modules.get("mem")), 0);
+    assert_eq!(module_margin(None), 0);
+
+    let layout = &config.application.layouts["main"];
+    let model = BarModel::from_layout(layout, &monitor(1000), modules);
+    let margins: Vec<(&str, i32)> = model.columns[0]
+        .modules
+        .iter()
+        .map(|m| (m.name.as_str(), m.margin))
+        .collect();
+    assert_eq!(margins, vec![("clock", 8), ("battery", 0), ("mem", 0)]);
+
+    // A monitor override replaces the global padding
+    let merged = modules["clock"].merged_with(&ModuleConfig {
+        padding: Some(2),
+        ..ModuleConfig::default()
+    });
+    assert_eq!(module_margin(Some(&merged)), 2);
+}
+
+#[test]
+fn test_input_region_from_click_through_columns() {
+    let yaml = r#"
+application:
+  modules: {}
+  layouts:
+    main:
+      columns:
+        left:
+          modules: [workspaces]
+        center:
+          modules: [window_title]
+          click_through: true
+        right:
+          modules: [clock]
+  monitors: []
+"#;
+    let config = ConfigManager::parse_config(yaml.as_bytes()).unwrap();
+    let model = BarModel::from_layout(
+        &config.application.layouts["main"],
+        &monitor(1800),
+        &HashMap::new(),
+    );
+    let flags: Vec<bool> = model.columns.iter().map(|c| c.click_through()).collect();
+    assert_eq!(flags, vec![false, true, false]);
+
+    let extent = |x: i32, width: i32, click_through: bool| ColumnExtent {
+        x,
+        width,
+        click_through,
+    };
+    let rect = |x: i32, width: i32| InputRect {
+        x,
+        y: 0,
+        width,
+        height: 30,
+    };
+
+    // Default: nothing is click-through, so the full surface keeps its input
+    let interactive = [extent(0, 600, false), extent(600, 600, false)];
+    assert_eq!(input_region(&interactive, 30), None);
+
+    // The click-through middle column leaves a hole between the other two
+    let columns = [
+        extent(0, 600, false),
+        extent(600, 600, true),
+        extent(1200, 600, false),
+    ];
+    assert_eq!(
+        input_region(&columns, 30),
+        Some(vec![rect(0, 600), rect(1200, 600)])
+    );
+
+    // Touching interactive columns merge; a gap between them stays click-through
+    let columns = [
+        extent(0, 400, false),
+        extent(400, 400, false),
+        extent(810, 390, false),
+        extent(1200, 600, true),
+    ];
+    assert_eq!(
+        input_region(&columns, 30),
+        Some(vec![rect(0, 800), rect(810, 390)])
+    );
+
+    // Every column click-through: an empty region, the bar takes no input at all
+    let columns = [extent(0, 900, true), extent(900, 900, true)];
+    assert_eq!(input_region(&columns, 30), Some(vec![]));
+}
+
+#[test]
+fn test_separator_fixed_width_in_overflow() {
+    assert_eq!(separator::fixed_width(None), separator::DEFAULT_WIDTH);
+    let sized = |width: i32| ModuleConfig {
+        width: Some(width),
+        ..ModuleConfig::default()
+    };
+    assert_eq!(separator::fixed_width(Some(&sized(20))), 20);
+    assert_eq!(separator::fixed_width(Some(&sized(-3))), 0);
+    assert_eq!(
+        separator::fixed_width(Some(&ModuleConfig::default())),
+        separator::DEFAULT_WIDTH
+    );
+
+    let columns = vec![(
+        "left".to_string(),
+        column(
+            &["clock", "separator", "battery", "separator", "mem"],
+            ColumnOverflowPolicy::Kebab,
+        ),
+    )];
+    let model = BarModel::new(&columns, &monitor(300), &HashMap::new());
+    let fixed: Vec<Option<i32>> = model.columns[0]
+        .modules
+        .iter()
+        .map(|m| m.fixed_width)
+        .collect();
+    assert_eq!(fixed, vec![None, Some(9), None, Some(9), None]);
+
+    // Separators count 9px each whatever was measured: 100 + 9 + 100 + 9 fits the 270px
+    // left after the kebab, mem doesn't
+    let arrangement = model.columns[0].arrange(30, &[100, 500, 100, 500, 100]);
+    assert_eq!(arrangement.decision, OverflowDecision::Overflow(vec![4]));
+    assert_eq!(arrangement.kebab, vec![4]);
+
+    // An overflowing separator is dropped, never listed in the kebab
+    let arrangement = model.columns[0].arrange(30, &[120, 0, 135, 0, 5]);
+    assert_eq!(arrangement.decision, OverflowDecision::Overflow(vec![3]));
+    assert!(arrangement.kebab.is_empty());
+    assert!(!arrangement.show_kebab());
+    assert_eq!(
+        arrangement.slots,
+        vec![
+            ColumnSlot::Module(0),
+            ColumnSlot::Module(1),
+            ColumnSlot::Module(2),
+            ColumnSlot::Module(4)
+        ]
+    );
+
+    // A configured width replaces the default
+    let mut configs = HashMap::new();
+    configs.insert("separator".to_string(), sized(20));
+    let model = BarModel::new(&columns, &monitor(300), &configs);
+    assert_eq!(model.columns[0].modules[1].fixed_width, Some(20));
+}
@@ -53,3 +53,457 @@ fn test_bar_monitor_matching() {
     assert_eq!(monitor_info.connector, "DP-1");
     assert_ne!(monitor_info.connector, "eDP-1");
 }
+
+#[test]
+fn test_bar_position_anchor_edge() {
+    use gtk4_layer_shell::Edge;
+    use niri_bar::bar::anchor_edge;
+    use niri_bar::config::BarPosition;
+
+    assert_eq!(anchor_edge(BarPosition::Top), Edge::Top);
+    assert_eq!(anchor_edge(BarPosition::Bottom), Edge::Bottom);
+    assert_eq!(anchor_edge(BarPosition::default()), Edge::Top);
+}
+
+#[derive(Default)]
+struct MockDisplay {
+    attached: std::cell::RefCell<Vec<u32>>,
+}
+
+impl niri_bar::bar::StyleProviderTarget<u32> for MockDisplay {
+    fn attach(&self, provider: &u32) {
+        self.attached.borrow_mut().push(*provider);
+    }
+
+    fn detach(&self, provider: &u32) {
+        self.attached.borrow_mut().retain(|p| p != provider);
+    }
+}
+
+#[test]
+fn test_css_provider_registry_no_unbounded_growth() {
+    use niri_bar::bar::CssProviderRegistry;
+
+    let display = MockDisplay::default();
+    let mut registry = CssProviderRegistry::new();
+    let mut next_id = 0u32;
+    let mut new_provider = || {
+        next_id += 1;
+        next_id
+    };
+
+    for round in 0..100 {
+        // Create bars on three monitors
+        let mut live = Vec::new();
+        for connector in ["eDP-1", "DP-1", "HDMI-A-1"] {
+            let p = new_provider();
+            registry.install(connector, p, &display);
+            live.push((connector, p));
+        }
+        // Hot-reload the theme a few times: providers are replaced, not stacked
+        for _ in 0..5 {
+            for (connector, p) in live.iter_mut() {
+                *p = new_provider();
+                registry.install(connector, *p, &display);
+            }
+        }
+        assert_eq!(registry.len(), 3, "round {}", round);
+        assert_eq!(display.attached.borrow().len(), 3, "round {}", round);
+
+        // Destroy every bar
+        for (connector, p) in &live {
+            assert!(registry.release(connector, p, &display));
+        }
+        assert!(registry.is_empty());
+        assert!(display.attached.borrow().is_empty());
+    }
+}
+
+#[test]
+fn test_css_provider_registry_stale_release_is_ignored() {
+    use niri_bar::bar::CssProviderRegistry;
+
+    let display = MockDisplay::default();
+    let mut registry = CssProviderRegistry::new();
+
+    // A replacement bar on the same connector installs before the old one is dropped
+    registry.install("DP-1", 1, &display);
+    registry.install("DP-1", 2, &display);
+    assert!(!registry.release("DP-1", &1, &display));
+    assert!(registry.contains("DP-1"));
+    assert_eq!(*display.attached.borrow(), vec![2]);
+
+    assert!(registry.release("DP-1", &2, &display));
+    assert!(!registry.contains("DP-1"));
+    assert!(display.attached.borrow().is_empty());
+}
+
+#[test]
+fn test_bar_margins_per_edge() {
+    use gtk4_layer_shell::Edge;
+    use niri_bar::bar::margin_edges;
+    use niri_bar::config::BarMargins;
+
+    let margins = BarMargins {
+        top: 8,
+        left: 12,
+        right: 16,
+        bottom: 4,
+    };
+    assert_eq!(
+        margin_edges(&margins),
+        [
+            (Edge::Top, 8),
+            (Edge::Left, 12),
+            (Edge::Right, 16),
+            (Edge::Bottom, 4),
+        ]
+    );
+    assert!(
+        margin_edges(&BarMargins::default())
+            .iter()
+            .all(|(_, m)| *m == 0)
+    );
+}
+
+#[test]
+fn test_bar_exclusive_zone_covers_inner_gap() {
+    use niri_bar::bar::exclusive_zone;
+    use niri_bar::config::{BarMargins, BarOptions, BarPosition};
+
+    // No inner gap: keep the automatic zone
+    let mut options = BarOptions {
+        margins: BarMargins {
+            top: 8,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert_eq!(exclusive_zone(&options), None);
+
+    // Gap below a top bar is reserved too
+    options.margins.bottom = 6;
+    assert_eq!(exclusive_zone(&options), Some(46));
+
+    // For a bottom bar the inner side is the top margin
+    options.position = BarPosition::Bottom;
+    options.height = 30;
+    assert_eq!(exclusive_zone(&options), Some(38));
+}
+
+#[test]
+fn test_bar_layer_mapping() {
+    use gtk4_layer_shell::Layer;
+    use niri_bar::bar::shell_layer;
+    use niri_bar::config::BarLayer;
+
+    assert_eq!(shell_layer(BarLayer::Background), Layer::Background);
+    assert_eq!(shell_layer(BarLayer::Bottom), Layer::Bottom);
+    assert_eq!(shell_layer(BarLayer::Top), Layer::Top);
+    assert_eq!(shell_layer(BarLayer::Overlay), Layer::Overlay);
+    assert_eq!(shell_layer(BarLayer::default()), Layer::Top);
+}
+
+#[test]
+fn test_overflow_decision_by_policy() {
+    use niri_bar::bar::{OverflowDecision, decide_overflow};
+    use niri_bar::config::ColumnOverflowPolicy;
+
+    let widths = [100, 80, 60];
+
+    // Everything fits regardless of policy
+    for policy in [
+        ColumnOverflowPolicy::Hide,
+        ColumnOverflowPolicy::Kebab,
+        ColumnOverflowPolicy::Shrink,
+    ] {
+        assert_eq!(
+            decide_overflow(&policy, 300, 20, &widths),
+            OverflowDecision::Fits
+        );
+    }
+
+    // Hide drops what doesn't fit, greedily keeping later modules that still fit
+    assert_eq!(
+        decide_overflow(&ColumnOverflowPolicy::Hide, 170, 20, &widths),
+        OverflowDecision::Overflow(vec![1])
+    );
+    // Kebab reserves room for the menu button
+    assert_eq!(
+        decide_overflow(&ColumnOverflowPolicy::Kebab, 170, 20, &widths),
+        OverflowDecision::Overflow(vec![1, 2])
+    );
+    // Shrink keeps every module and scales labels down
+    match decide_overflow(&ColumnOverflowPolicy::Shrink, 120, 20, &widths) {
+        OverflowDecision::Shrink { ratio } => assert!((ratio - 0.5).abs() < f64::EPSILON),
+        other => panic!("expected shrink, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_column_color_css() {
+    use niri_bar::bar::column_color_css;
+    use niri_bar::config::ColumnSpec;
+
+    let spec = ColumnSpec {
+        background: Some("#242424".to_string()),
+        color: Some("#cae682".to_string()),
+        ..Default::default()
+    };
+    let css = column_color_css("eDP-1", "left-of-center", &spec).unwrap();
+    assert!(css.contains(
+        ".bar-columns.monitor-eDP_1 > .column.column-left_of_center { background-color: #242424; }"
+    ));
+    assert!(css.contains("column-left_of_center label { color: #cae682; }"));
+
+    // Invalid colors produce no CSS at all
+    let invalid = ColumnSpec {
+        background: Some("not-a-color".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(column_color_css("eDP-1", "left", &invalid), None);
+    assert_eq!(
+        column_color_css("eDP-1", "left", &ColumnSpec::default()),
+        None
+    );
+}
+
+#[test]
+fn test_bar_style_css() {
+    use niri_bar::bar::bar_style_css;
+    use niri_bar::config::BarOptions;
+
+    assert_eq!(bar_style_css("eDP-1", &BarOptions::default()), None);
+
+    let options = BarOptions {
+        opacity: Some(0.85),
+        background: Some("#1e1e2e".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        bar_style_css("eDP-1", &options).unwrap(),
+        "window.monitor-eDP_1 { background-color: #1e1e2e; opacity: 0.85; }\n"
+    );
+    let options = BarOptions {
+        opacity: Some(0.5),
+        ..Default::default()
+    };
+    assert_eq!(
+        bar_style_css("DP-2", &options).unwrap(),
+        "window.monitor-DP_2 { opacity: 0.50; }\n"
+    );
+}
+
+#[test]
+fn test_column_width_clamping() {
+    use niri_bar::bar::column_width;
+    use niri_bar::config::ColumnSpec;
+
+    let unconstrained = ColumnSpec::default();
+    assert_eq!(column_width(1920, 3, &unconstrained), 640);
+
+    // Ultrawide: equal share is capped by max_width
+    let capped = ColumnSpec {
+        max_width: Some(800),
+        ..Default::default()
+    };
+    assert_eq!(column_width(5120, 3, &capped), 800);
+    assert_eq!(column_width(1920, 3, &capped), 640);
+
+    // Narrow monitor: min_width keeps the column from collapsing
+    let floored = ColumnSpec {
+        min_width: Some(300),
+        ..Default::default()
+    };
+    assert_eq!(column_width(1280, 5, &floored), 300);
+    assert_eq!(column_width(1920, 3, &floored), 640);
+
+    // Fixed widths are clamped too; min_width wins over a conflicting max_width
+    let fixed = ColumnSpec {
+        width: Some(1000),
+        max_width: Some(600),
+        ..Default::default()
+    };
+    assert_eq!(column_width(1920, 3, &fixed), 600);
+    let conflicting = ColumnSpec {
+        min_width: Some(500),
+        max_width: Some(400),
+        ..Default::default()
+    };
+    assert_eq!(column_width(1920, 3, &conflicting), 500);
+
+    // Zero columns doesn't divide by zero
+    assert_eq!(column_width(1920, 0, &unconstrained), 1920);
+}
+
+#[test]
+fn test_column_budget_uses_logical_pixels() {
+    use niri_bar::bar::{OverflowDecision, column_budget, decide_overflow};
+    use niri_bar::config::{ColumnOverflowPolicy, ColumnSpec};
+
+    let monitor = |logical_size: (i32, i32), scale_factor: i32| MonitorInfo {
+        connector: "eDP-1".to_string(),
+        manufacturer: None,
+        model: None,
+        logical_size,
+        scale_factor,
+    };
+    let spec = ColumnSpec::default();
+
+    // 1920x1080 panel at scale 1 and a 3840x2160 panel at scale 2 share the same logical
+    // width, so they get the same budget
+    let plain = monitor((1920, 1080), 1);
+    let hidpi = monitor((1920, 1080), 2);
+    assert_eq!(hidpi.pixel_size(), (3840, 2160));
+    assert_eq!(column_budget(&plain, 3, &spec), Some(640));
+    assert_eq!(column_budget(&hidpi, 3, &spec), Some(640));
+
+    // 2560x1600 at scale 2 is 1280 logical pixels wide: half the room of a scale 1 panel
+    let laptop = monitor((1280, 800), 2);
+    assert_eq!(laptop.pixel_size(), (2560, 1600));
+    assert_eq!(column_budget(&laptop, 3, &spec), Some(426));
+    assert_eq!(
+        column_budget(&monitor((2560, 1600), 1), 3, &spec),
+        Some(853)
+    );
+
+    // Module widths GTK measures are logical too: 500px of modules fit at scale 1 but
+    // overflow the same panel at scale 2
+    let widths = [200, 150, 150];
+    let decide = |info: &MonitorInfo| {
+        decide_overflow(
+            &ColumnOverflowPolicy::Hide,
+            column_budget(info, 3, &spec).unwrap(),
+            0,
+            &widths,
+        )
+    };
+    assert_eq!(decide(&monitor((2560, 1600), 1)), OverflowDecision::Fits);
+    assert_eq!(decide(&laptop), OverflowDecision::Overflow(vec![2]));
+
+    // Width limits are logical pixels as well
+    let capped = ColumnSpec {
+        max_width: Some(500),
+        ..Default::default()
+    };
+    assert_eq!(
+        column_budget(&monitor((3840, 2160), 1), 3, &capped),
+        Some(500)
+    );
+    assert_eq!(
+        column_budget(&monitor((1920, 1080), 2), 3, &capped),
+        Some(500)
+    );
+
+    // Size not reported yet: no budget rather than overflowing everything
+    assert_eq!(
+        column_budget(&MonitorInfo::for_connector("DP-1"), 3, &spec),
+        None
+    );
+}
+
+#[test]
+fn test_overflowed_widgets_stay_interactive() {
+    use gtk4 as gtk;
+    use gtk4::prelude::*;
+    use niri_bar::bar::{kebab_overflow_list, kebab_overflow_row};
+
+    // Needs a display; headless runs skip
+    if gtk::init().is_err() {
+        return;
+    }
+
+    // A module laid out in its column first, with a click action and a scroll handler
+    let module = gtk::Label::new(Some("12:00"));
+    let click = gtk::GestureClick::new();
+    module.add_controller(click.clone());
+    module.add_controller(gtk::EventControllerScroll::new(
+        gtk::EventControllerScrollFlags::VERTICAL,
+    ));
+    let controllers_before = module.observe_controllers().n_items();
+    let column = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+    column.append(&module);
+
+    let list = kebab_overflow_list();
+    let row = kebab_overflow_row(module.upcast_ref());
+    list.append(&row);
+
+    // Re-parented into the row with every controller still attached
+    assert_eq!(
+        row.child().as_ref(),
+        Some(module.upcast_ref::<gtk::Widget>())
+    );
+    assert!(column.first_child().is_none());
+    assert_eq!(module.observe_controllers().n_items(), controllers_before);
+    assert_eq!(
+        click.widget().as_ref(),
+        Some(module.upcast_ref::<gtk::Widget>())
+    );
+
+    // Neither the list nor the row claims presses for selection or activation
+    assert_eq!(list.selection_mode(), gtk::SelectionMode::None);
+    assert!(!list.activates_on_single_click());
+    assert!(!row.is_activatable());
+    assert!(!row.is_selectable());
+    assert!(row.has_css_class("column-overflow-row"));
+    assert!(list.has_css_class("column-kebab-list"));
+
+    // Timers of live modules (e.g. the clock) tick while their weak ref upgrades; the row
+    // keeps the widget alive once the column lets go
+    let weak = module.downgrade();
+    drop(module);
+    let module = weak.upgrade().expect("overflowed module was dropped");
+    module.set_text("12:01");
+    assert_eq!(module.text(), "12:01");
+}
+
+#[test]
+fn test_column_slots_per_module_alignment() {
+    use ColumnSlot::{Module as M, Spacer as S};
+    use niri_bar::bar::{ColumnSlot, column_slots};
+    use niri_bar::config::TextAlign::{Center, Left, Right};
+
+    // One alignment for the whole column: the plain column layouts
+    assert_eq!(column_slots(&[Left, Left]), vec![M(0), M(1)]);
+    assert_eq!(column_slots(&[Right, Right]), vec![S, M(0), M(1)]);
+    assert_eq!(column_slots(&[Center]), vec![S, M(0), S]);
+    assert_eq!(column_slots(&[]), vec![]);
+
+    // Title left, status right: one spacer between the groups
+    assert_eq!(
+        column_slots(&[Left, Right, Right]),
+        vec![M(0), S, M(1), M(2)]
+    );
+    // Groups are formed by alignment, keeping config order inside each group
+    assert_eq!(
+        column_slots(&[Right, Left, Right, Left]),
+        vec![M(1), M(3), S, M(0), M(2)]
+    );
+
+    // A centered group sits between spacers
+    assert_eq!(
+        column_slots(&[Left, Center, Right]),
+        vec![M(0), S, M(1), S, M(2)]
+    );
+    assert_eq!(column_slots(&[Left, Center]), vec![M(0), S, M(1), S]);
+    assert_eq!(column_slots(&[Right, Center]), vec![S, M(1), S, M(0)]);
+}
+
+#[test]
+fn test_config_error_banner_text() {
+    use niri_bar::bar::config_error_banner_text;
+
+    assert_eq!(
+        config_error_banner_text("Failed to parse configuration: bad indent", 120),
+        "Failed to parse configuration: bad indent"
+    );
+    // Only the first line, trimmed
+    assert_eq!(
+        config_error_banner_text("  line one \nline two", 120),
+        "line one"
+    );
+    // Long messages are cut on char boundaries with an ellipsis
+    assert_eq!(config_error_banner_text("ääääää", 4), "äää…");
+    assert_eq!(config_error_banner_text("abcd", 4), "abcd");
+    assert_eq!(config_error_banner_text("", 4), "");
+}
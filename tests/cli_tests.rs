@@ -0,0 +1,117 @@
+use clap::Parser;
+use niri_bar::cli::{Cli, CliCommand};
+use niri_bar::config::CONFIG_SCHEMA;
+use niri_bar::control::{ControlCommand, reply_is_ok, send_command};
+use pretty_assertions::assert_eq;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixListener;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+fn parse(args: &[&str]) -> Cli {
+    Cli::try_parse_from(std::iter::once("niri-bar").chain(args.iter().copied())).unwrap()
+}
+
+#[test]
+fn test_cli_defaults_to_run() {
+    let cli = parse(&[]);
+    assert_eq!(cli.command(), CliCommand::Run);
+    assert_eq!(cli.config, None);
+
+    // --config still works without a subcommand, in every spelling
+    for args in [
+        &["--config", "/tmp/bar.yaml"][..],
+        &["-c", "/tmp/bar.yaml"],
+        &["--config=/tmp/bar.yaml"],
+        &["run", "--config", "/tmp/bar.yaml"],
+    ] {
+        let cli = parse(args);
+        assert_eq!(cli.command(), CliCommand::Run, "{:?}", args);
+        assert_eq!(
+            cli.config,
+            Some(PathBuf::from("/tmp/bar.yaml")),
+            "{:?}",
+            args
+        );
+    }
+}
+
+#[test]
+fn test_cli_subcommands() {
+    assert_eq!(parse(&["reload"]).command(), CliCommand::Reload);
+    assert_eq!(
+        parse(&["toggle", "eDP-1"]).command(),
+        CliCommand::Toggle {
+            connector: "eDP-1".to_string()
+        }
+    );
+    assert_eq!(
+        parse(&["set-theme", "dracula"]).command(),
+        CliCommand::SetTheme {
+            name: "dracula".to_string()
+        }
+    );
+
+    // Missing arguments and unknown subcommands are usage errors
+    for args in [&["toggle"][..], &["set-theme"], &["restart"]] {
+        assert!(
+            Cli::try_parse_from(std::iter::once("niri-bar").chain(args.iter().copied())).is_err(),
+            "{:?}",
+            args
+        );
+    }
+}
+
+#[test]
+fn test_cli_command_to_socket_message() {
+    assert_eq!(CliCommand::Run.control_command(), None);
+    for (args, message) in [
+        (&["reload"][..], r#"{"reload":true}"#),
+        (&["toggle", "HDMI-A-1"], r#"{"toggle_bar":"HDMI-A-1"}"#),
+        (&["set-theme", "solarized"], r#"{"set_theme":"solarized"}"#),
+    ] {
+        let command = parse(args).command().control_command().unwrap();
+        assert_eq!(command.to_json(), message);
+        // What the CLI sends is what the server accepts
+        assert_eq!(ControlCommand::parse(message).unwrap(), command);
+    }
+}
+
+#[test]
+fn test_cli_schema_command() {
+    assert_eq!(parse(&["schema"]).command(), CliCommand::Schema);
+    // Printed locally, never sent to a running bar
+    assert_eq!(CliCommand::Schema.control_command(), None);
+
+    // What gets printed is the schema configs are validated against
+    let schema: serde_json::Value = serde_json::from_str(CONFIG_SCHEMA).unwrap();
+    assert_eq!(schema["title"], "Niri Bar Configuration Schema");
+    assert_eq!(
+        CONFIG_SCHEMA,
+        std::fs::read_to_string("src/niri-bar-yaml.schema.json").unwrap()
+    );
+}
+
+#[test]
+fn test_send_command_round_trip() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("niri-bar.sock");
+    let listener = UnixListener::bind(&path).unwrap();
+    let server = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line).unwrap();
+        (&stream).write_all(b"{\"ok\":true}\n").unwrap();
+        line
+    });
+
+    let reply = send_command(&path, &ControlCommand::ToggleBar("eDP-1".to_string())).unwrap();
+    assert_eq!(reply, r#"{"ok":true}"#);
+    assert!(reply_is_ok(&reply));
+    assert_eq!(server.join().unwrap(), "{\"toggle_bar\":\"eDP-1\"}\n");
+
+    assert!(!reply_is_ok(r#"{"ok":false,"error":"no bar on 'DP-9'"}"#));
+    assert!(!reply_is_ok("garbage"));
+    // Nobody listening
+    assert!(send_command(&dir.path().join("missing.sock"), &ControlCommand::Reload).is_err());
+}
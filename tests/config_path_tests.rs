@@ -0,0 +1,102 @@
+// Config path resolution. These tests change process environment variables, so they live in
+// their own test binary instead of racing the other config tests that call ConfigManager::new().
+use niri_bar::config::{
+    CONFIG_PATH_ENV, ConfigManager, DEFAULT_CONFIG_FILE, resolve_config_path,
+    resolve_config_path_from_env,
+};
+use pretty_assertions::assert_eq;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tempfile::TempDir;
+
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+fn xdg_with_config() -> (TempDir, PathBuf) {
+    let xdg = TempDir::new().unwrap();
+    let dir = xdg.path().join("niri-bar");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(DEFAULT_CONFIG_FILE);
+    std::fs::write(&path, "application: {}\n").unwrap();
+    (xdg, path)
+}
+
+#[test]
+fn test_resolve_config_path_precedence() {
+    let (xdg, xdg_config) = xdg_with_config();
+    let cli = Path::new("/etc/niri-bar/cli.yaml");
+    let env = OsStr::new("/tmp/env.yaml");
+
+    // --config wins over everything
+    assert_eq!(
+        resolve_config_path(Some(cli), Some(env), Some(xdg.path())),
+        cli
+    );
+    // then $NIRI_BAR_CONFIG
+    assert_eq!(
+        resolve_config_path(None, Some(env), Some(xdg.path())),
+        PathBuf::from("/tmp/env.yaml")
+    );
+    // then the XDG config dir, when the file exists there
+    assert_eq!(
+        resolve_config_path(None, None, Some(xdg.path())),
+        xdg_config
+    );
+    // an empty env var counts as unset
+    assert_eq!(
+        resolve_config_path(None, Some(OsStr::new("")), Some(xdg.path())),
+        xdg_config
+    );
+    // finally the current directory
+    let empty_xdg = TempDir::new().unwrap();
+    assert_eq!(
+        resolve_config_path(None, None, Some(empty_xdg.path())),
+        PathBuf::from(DEFAULT_CONFIG_FILE)
+    );
+    assert_eq!(
+        resolve_config_path(None, None, None),
+        PathBuf::from(DEFAULT_CONFIG_FILE)
+    );
+}
+
+#[test]
+fn test_resolve_config_path_from_env() {
+    let _lock = ENV_LOCK.lock().unwrap();
+    let (xdg, xdg_config) = xdg_with_config();
+
+    // SAFETY: env access in this binary is serialized by ENV_LOCK
+    unsafe {
+        std::env::set_var("XDG_CONFIG_HOME", xdg.path());
+        std::env::set_var(CONFIG_PATH_ENV, "/tmp/from-env.yaml");
+    }
+    assert_eq!(
+        resolve_config_path_from_env(None),
+        PathBuf::from("/tmp/from-env.yaml")
+    );
+    assert_eq!(
+        resolve_config_path_from_env(Some(Path::new("cli.yaml"))),
+        PathBuf::from("cli.yaml")
+    );
+    assert_eq!(
+        ConfigManager::new().config_path(),
+        Path::new("/tmp/from-env.yaml")
+    );
+
+    unsafe {
+        std::env::remove_var(CONFIG_PATH_ENV);
+    }
+    assert_eq!(resolve_config_path_from_env(None), xdg_config);
+    assert_eq!(ConfigManager::new().config_path(), xdg_config);
+
+    unsafe {
+        std::env::remove_var("XDG_CONFIG_HOME");
+    }
+}
+
+#[test]
+fn test_config_manager_with_config_path() {
+    let manager = ConfigManager::with_config_path("/srv/bar.yaml");
+    assert_eq!(manager.config_path(), Path::new("/srv/bar.yaml"));
+    // Clones keep the path
+    assert_eq!(manager.clone().config_path(), Path::new("/srv/bar.yaml"));
+}
@@ -1,8 +1,12 @@
 use indexmap::IndexMap;
 use niri_bar::config::{
-    ApplicationConfig, ColumnOverflowPolicy, ColumnSpec, ConfigManager, DisplayMode, LayoutConfig,
-    LoggingConfig, ModuleConfig, MonitorConfig, NiriBarConfig, TextAlign, WallpaperConfig,
+    ApplicationConfig, BarLayer, BarMargins, BarPosition, ColumnOverflowPolicy, ColumnSpec,
+    ConfigEvent, ConfigManager, DEFAULT_BAR_HEIGHT, DisplayMode, LayoutConfig, LoggingConfig,
+    MarginConfig, ModuleConfig, MonitorConfig, NiriBarConfig, TextAlign, WallpaperConfig,
+    expand_env_vars, expand_env_vars_with, is_valid_hex_color, load_logging_config, schema_errors,
 };
+use niri_bar::monitor::MonitorInfo;
+use niri_bar::niri::WorkspaceInfo;
 use pretty_assertions::assert_eq;
 use proptest::prelude::*;
 use tempfile::TempDir;
@@ -57,6 +61,64 @@ fn test_schema_validation() {
     );
 }
 
+#[test]
+fn test_schema_violations_point_at_fields() {
+    let yaml = r#"
+application:
+  height: tall
+  modules: {}
+  layouts: {}
+  monitors:
+    - match: eDP-1
+      height: 0
+"#;
+    let err = ConfigManager::parse_config(yaml.as_bytes()).unwrap_err();
+    // Every violation is listed, each with the path of the offending field
+    let text = err.to_string();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", text);
+    assert!(
+        lines[0].starts_with("Invalid config at /application/height: "),
+        "{}",
+        text
+    );
+    assert!(
+        lines[1].starts_with("Invalid config at /application/monitors/0/height: "),
+        "{}",
+        text
+    );
+    // The typed error is kept as the cause
+    assert!(format!("{:#}", err).contains("invalid type"), "{:#}", err);
+
+    // Fields that type fine but break the schema are rejected too
+    let yaml = "application:\n  modules: {}\n  layouts: {}\n  monitors:\n    - match: eDP-1\n      height: 0\n";
+    let err = ConfigManager::parse_config(yaml.as_bytes()).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("Invalid config at /application/monitors/0/height: "),
+        "{}",
+        err
+    );
+
+    let valid = serde_json::json!({
+        "application": { "modules": {}, "layouts": {}, "monitors": [] }
+    });
+    assert!(schema_errors(&valid).is_empty());
+    let errors = schema_errors(&serde_json::json!({
+        "application": { "modules": {}, "layouts": {}, "monitors": [], "position": "left" }
+    }));
+    assert_eq!(errors.len(), 1);
+    assert!(
+        errors[0].starts_with("/application/position: "),
+        "{:?}",
+        errors
+    );
+    // Problems with the document itself point at the root
+    let errors = schema_errors(&serde_json::json!({}));
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].starts_with("/: "), "{:?}", errors);
+}
+
 #[test]
 fn test_monitor_pattern_matching() {
     let _config_manager = ConfigManager::new();
@@ -172,6 +234,405 @@ fn test_config_manager_creation() {
     assert!(layouts.is_none());
 }
 
+#[test]
+fn test_config_manager_records_last_error() {
+    let config_manager = ConfigManager::new();
+    let mut events = config_manager.subscribe();
+    assert!(config_manager.last_error().is_none());
+
+    let good = std::fs::read("niri-bar.yaml").expect("read niri-bar.yaml");
+    config_manager
+        .apply_config(&good)
+        .expect("real config parses");
+    let theme = config_manager.get_config().unwrap().application.theme;
+
+    // A broken reload keeps the old config and records the error
+    let result = config_manager.apply_config(b"application: [unclosed");
+    assert!(result.is_err());
+    let error = config_manager.last_error().expect("error recorded");
+    assert!(
+        error.starts_with("Failed to parse configuration"),
+        "{}",
+        error
+    );
+    assert_eq!(
+        config_manager.get_config().unwrap().application.theme,
+        theme
+    );
+
+    // Clones share the error state
+    assert_eq!(config_manager.clone().last_error(), Some(error.clone()));
+
+    // The next good reload clears it
+    config_manager
+        .apply_config(&good)
+        .expect("real config parses");
+    assert!(config_manager.last_error().is_none());
+
+    // Updated, Error, Updated were emitted in order
+    assert!(matches!(events.try_recv(), Ok(ConfigEvent::Updated(_))));
+    assert!(matches!(events.try_recv(), Ok(ConfigEvent::Error(e)) if e == error));
+    assert!(matches!(events.try_recv(), Ok(ConfigEvent::Updated(_))));
+}
+
+#[test]
+fn test_config_manager_set_theme_in_memory() {
+    let config_manager = ConfigManager::new();
+    let mut events = config_manager.subscribe();
+
+    // Nothing to switch before a config is loaded
+    assert!(config_manager.set_theme("solarized").is_err());
+    assert!(events.try_recv().is_err());
+
+    let good = std::fs::read("niri-bar.yaml").expect("read niri-bar.yaml");
+    let loaded = config_manager
+        .apply_config(&good)
+        .expect("real config parses");
+    assert!(matches!(events.try_recv(), Ok(ConfigEvent::Updated(_))));
+    assert_ne!(loaded.application.theme, "solarized");
+
+    let updated = config_manager.set_theme("solarized").unwrap();
+    assert_eq!(updated.application.theme, "solarized");
+    assert_eq!(
+        config_manager.get_config().unwrap().application.theme,
+        "solarized"
+    );
+    // Only the theme changed
+    assert_eq!(
+        updated.application.layouts.len(),
+        loaded.application.layouts.len()
+    );
+    assert_eq!(
+        updated.application.monitors.len(),
+        loaded.application.monitors.len()
+    );
+    assert!(matches!(
+        events.try_recv(),
+        Ok(ConfigEvent::Updated(c)) if c.application.theme == "solarized"
+    ));
+
+    // Clones share the config; reloading the file brings its theme back
+    assert_eq!(
+        config_manager
+            .clone()
+            .get_config()
+            .unwrap()
+            .application
+            .theme,
+        "solarized"
+    );
+    config_manager.apply_config(&good).unwrap();
+    assert_eq!(
+        config_manager.get_config().unwrap().application.theme,
+        loaded.application.theme
+    );
+}
+
+#[test]
+fn test_expand_env_vars() {
+    let lookup = |name: &str| match name {
+        "HOME" => Some("/home/me".to_string()),
+        "XDG_CONFIG_HOME" => Some("/home/me/.config".to_string()),
+        "current_workspace_image" => Some("/should/not/be/used".to_string()),
+        _ => None,
+    };
+
+    assert_eq!(
+        expand_env_vars_with("${HOME}/Pictures/bg.png", lookup),
+        "/home/me/Pictures/bg.png"
+    );
+    assert_eq!(
+        expand_env_vars_with("${XDG_CONFIG_HOME}/niri-bar/${HOME}", lookup),
+        "/home/me/.config/niri-bar//home/me"
+    );
+    // Unknown variables pass through untouched
+    assert_eq!(
+        expand_env_vars_with("${NOPE}/x and ${HOME}", lookup),
+        "${NOPE}/x and /home/me"
+    );
+    // Runtime placeholders are never taken from the environment
+    assert_eq!(
+        expand_env_vars_with("mytool -i ${current_workspace_image}", lookup),
+        "mytool -i ${current_workspace_image}"
+    );
+    // $$ escapes a literal $, lone $ and malformed tokens are kept
+    assert_eq!(expand_env_vars_with("$${HOME}", lookup), "${HOME}");
+    assert_eq!(expand_env_vars_with("cost: 5$ $$", lookup), "cost: 5$ $");
+    assert_eq!(expand_env_vars_with("$HOME ${HOME", lookup), "$HOME ${HOME");
+    assert_eq!(expand_env_vars_with("${1X} ${}", lookup), "${1X} ${}");
+    assert_eq!(expand_env_vars_with("no vars", lookup), "no vars");
+}
+
+#[test]
+fn test_parse_config_expands_env_vars() {
+    let home = std::env::var("HOME").expect("HOME is set in tests");
+    let yaml = r#"
+application:
+  modules:
+    script: { command: "echo ${HOME}" }
+  layouts: {}
+  wallpapers:
+    default: "${HOME}/Pictures/default.png"
+    by_workspace:
+      "1": "${HOME}/Pictures/one.png"
+    special_cmd: "mytool -i ${current_workspace_image}"
+  monitors:
+    - match: ".*"
+      wallpapers:
+        default: "${NIRI_BAR_TEST_SURELY_UNSET}/x.png"
+logging:
+  level: "info"
+  file: "${HOME}/.local/share/niri-bar/niri-bar.log"
+  console: true
+"#;
+    let config = ConfigManager::parse_config(yaml.as_bytes()).unwrap();
+    let wallpapers = &config.application.wallpapers;
+    assert_eq!(
+        wallpapers.default.as_deref(),
+        Some(format!("{}/Pictures/default.png", home).as_str())
+    );
+    assert_eq!(
+        wallpapers.by_workspace["1"],
+        format!("{}/Pictures/one.png", home)
+    );
+    assert_eq!(
+        wallpapers.special_cmd.as_deref(),
+        Some("mytool -i ${current_workspace_image}")
+    );
+    assert_eq!(
+        config.logging.file,
+        format!("{}/.local/share/niri-bar/niri-bar.log", home)
+    );
+    // Free-form module options are expanded too
+    assert_eq!(
+        config.application.modules["script"].additional["command"].as_str(),
+        Some(format!("echo {}", home).as_str())
+    );
+    // Unset variables survive untouched
+    assert_eq!(
+        config.application.monitors[0]
+            .wallpapers
+            .as_ref()
+            .and_then(|w| w.default.as_deref()),
+        Some("${NIRI_BAR_TEST_SURELY_UNSET}/x.png")
+    );
+    assert_eq!(expand_env_vars("plain"), "plain");
+}
+
+fn write_yaml(dir: &std::path::Path, name: &str, content: &str) -> std::path::PathBuf {
+    let path = dir.join(name);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).unwrap();
+    }
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn test_load_logging_config_from_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let main = write_yaml(
+        temp_dir.path(),
+        "niri-bar.yaml",
+        r#"
+application:
+  modules: {}
+  layouts: {}
+  monitors: []
+logging:
+  level: warn
+  file: "/tmp/niri-bar-test.log"
+  console: false
+  max_size_bytes: 4096
+  max_files: 2
+"#,
+    );
+
+    let logging = load_logging_config(&main).unwrap();
+    assert_eq!(logging.level, "warn");
+    assert!(!logging.console);
+    assert_eq!(logging.max_size_bytes, 4096);
+    assert_eq!(logging.max_files, 2);
+
+    // Missing or broken configs leave it to the caller's default
+    assert_eq!(
+        load_logging_config(&temp_dir.path().join("missing.yaml")),
+        None
+    );
+    std::fs::write(&main, "application: [").unwrap();
+    assert_eq!(load_logging_config(&main), None);
+    assert_eq!(LoggingConfig::default().level, "info");
+}
+
+#[test]
+fn test_config_include_merge_precedence() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    write_yaml(
+        dir,
+        "parts/modules.yaml",
+        r#"
+application:
+  theme: "solarized"
+  modules:
+    clock: { format: "%H:%M", tooltip: true }
+    battery: { show_percentage: true }
+"#,
+    );
+    write_yaml(
+        dir,
+        "parts/overrides.yaml",
+        r#"
+application:
+  modules:
+    clock: { format: "%H:%M:%S" }
+"#,
+    );
+    let main = write_yaml(
+        dir,
+        "niri-bar.yaml",
+        r#"
+include: [parts/modules.yaml, parts/overrides.yaml]
+application:
+  theme: "dracula"
+  layouts: {}
+  monitors: []
+logging: { level: "info", file: "/tmp/test.log", console: true }
+"#,
+    );
+
+    let content = std::fs::read(&main).unwrap();
+    let config = ConfigManager::parse_config_from(&content, &main).unwrap();
+    let modules = &config.application.modules;
+    // The including file wins over its includes
+    assert_eq!(config.application.theme, "dracula");
+    // Later includes override earlier keys, while sibling keys deep-merge
+    assert_eq!(modules["clock"].format.as_deref(), Some("%H:%M:%S"));
+    assert_eq!(modules["clock"].tooltip, Some(true));
+    assert_eq!(modules["battery"].show_percentage, Some(true));
+
+    // A single path works too
+    let single = write_yaml(
+        dir,
+        "single.yaml",
+        r#"
+include: parts/modules.yaml
+application: { layouts: {}, monitors: [] }
+"#,
+    );
+    let content = std::fs::read(&single).unwrap();
+    let config = ConfigManager::parse_config_from(&content, &single).unwrap();
+    assert_eq!(config.application.theme, "solarized");
+    assert_eq!(
+        config.application.modules["clock"].format.as_deref(),
+        Some("%H:%M")
+    );
+}
+
+#[test]
+fn test_config_include_replaces_lists() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    write_yaml(
+        dir,
+        "monitors.yaml",
+        r#"
+application:
+  modules: {}
+  layouts: {}
+  monitors:
+    - match: "eDP-1"
+    - match: "DP-1"
+"#,
+    );
+    let main = write_yaml(
+        dir,
+        "main.yaml",
+        r#"
+include: [monitors.yaml]
+application:
+  monitors:
+    - match: "HDMI-A-1"
+"#,
+    );
+    let content = std::fs::read(&main).unwrap();
+    let config = ConfigManager::parse_config_from(&content, &main).unwrap();
+    let matches: Vec<&str> = config
+        .application
+        .monitors
+        .iter()
+        .map(|m| m.match_pattern.as_str())
+        .collect();
+    assert_eq!(matches, vec!["HDMI-A-1"]);
+}
+
+#[test]
+fn test_config_include_cycle_is_an_error() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    write_yaml(dir, "a.yaml", "include: [b.yaml]\n");
+    write_yaml(dir, "b.yaml", "include: [a.yaml]\n");
+    let main = write_yaml(
+        dir,
+        "main.yaml",
+        "include: [a.yaml]\napplication: { modules: {}, layouts: {}, monitors: [] }\n",
+    );
+    let content = std::fs::read(&main).unwrap();
+    let err = ConfigManager::parse_config_from(&content, &main)
+        .unwrap_err()
+        .to_string();
+    assert!(err.starts_with("Config include cycle:"), "{}", err);
+    assert!(
+        err.contains("a.yaml -> ") && err.contains("b.yaml"),
+        "{}",
+        err
+    );
+
+    // Including yourself is a cycle too
+    let own = write_yaml(dir, "self.yaml", "include: [self.yaml]\n");
+    let content = std::fs::read(&own).unwrap();
+    let err = ConfigManager::parse_config_from(&content, &own).unwrap_err();
+    assert!(
+        err.to_string().starts_with("Config include cycle:"),
+        "{}",
+        err
+    );
+
+    // Missing includes name the file
+    let content = b"include: [nowhere.yaml]\n";
+    let err = ConfigManager::parse_config_from(content, &main).unwrap_err();
+    assert!(err.to_string().contains("nowhere.yaml"), "{}", err);
+}
+
+#[test]
+fn test_config_include_paths_for_watching() {
+    use niri_bar::config::config_include_paths;
+
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path().canonicalize().unwrap();
+    write_yaml(&dir, "parts/modules.yaml", "include: [../colors.yaml]\n");
+    write_yaml(&dir, "colors.yaml", "include: [parts/modules.yaml]\n");
+    write_yaml(&dir, "broken.yaml", "include: [\n");
+    let main = write_yaml(
+        &dir,
+        "niri-bar.yaml",
+        "include: [parts/modules.yaml, broken.yaml, missing.yaml]\n",
+    );
+
+    // Nested includes are followed (the cycle only once); a broken file is still
+    // listed and a missing one can't be
+    let content = std::fs::read(&main).unwrap();
+    assert_eq!(
+        config_include_paths(&content, &main),
+        vec![
+            dir.join("parts/modules.yaml"),
+            dir.join("colors.yaml"),
+            dir.join("broken.yaml"),
+        ]
+    );
+    assert!(config_include_paths(b"application: {}\n", &main).is_empty());
+}
+
 #[test]
 fn test_monitor_enabled_check() {
     let config_manager = ConfigManager::new();
@@ -416,6 +877,85 @@ fn test_swww_options_from_real_yaml() {
     }
 }
 
+#[test]
+fn test_swww_options_validation() {
+    use niri_bar::config::SwwwOptions;
+
+    assert!(SwwwOptions::default().validate().is_ok());
+    let happy = SwwwOptions {
+        transition_type: "wave".to_string(),
+        filter: "Nearest".to_string(),
+        resize: "fit".to_string(),
+        fill_color: "1E1e2e".to_string(),
+        ..SwwwOptions::default()
+    };
+    assert!(happy.validate().is_ok());
+
+    let message = |options: SwwwOptions| format!("{:#}", options.validate().unwrap_err());
+    assert_eq!(
+        message(SwwwOptions {
+            transition_type: "fdae".to_string(),
+            ..SwwwOptions::default()
+        }),
+        "swww_options: invalid transition_type `fdae`, expected one of: none, simple, fade, left, right, top, bottom, wipe, wave, grow, center, any, outer, random"
+    );
+    assert_eq!(
+        message(SwwwOptions {
+            filter: "lanczos3".to_string(),
+            ..SwwwOptions::default()
+        }),
+        "swww_options: invalid filter `lanczos3`, expected one of: Nearest, Bilinear, CatmullRom, Mitchell, Lanczos3"
+    );
+    assert_eq!(
+        message(SwwwOptions {
+            resize: "fill".to_string(),
+            ..SwwwOptions::default()
+        }),
+        "swww_options: invalid resize `fill`, expected one of: no, crop, fit, stretch"
+    );
+    for bad in ["#000000", "000", "00000g", "0000000"] {
+        let text = message(SwwwOptions {
+            fill_color: bad.to_string(),
+            ..SwwwOptions::default()
+        });
+        assert!(
+            text.contains(&format!("invalid fill_color `{}`", bad)),
+            "{}",
+            text
+        );
+    }
+    // Every bad field is reported at once
+    let text = message(SwwwOptions {
+        filter: "x".to_string(),
+        resize: "y".to_string(),
+        ..SwwwOptions::default()
+    });
+    assert!(text.contains("invalid filter `x`") && text.contains("invalid resize `y`"));
+
+    // parse_config checks global and per-monitor options
+    let yaml = |global: &str, monitor: &str| {
+        format!(
+            "application:\n  modules: {{}}\n  layouts: {{}}\n  wallpapers:\n    swww_options: {{ {} }}\n  monitors:\n    - match: eDP-1\n      wallpapers:\n        swww_options: {{ {} }}\n",
+            global, monitor
+        )
+    };
+    assert!(
+        ConfigManager::parse_config(yaml("resize: fit", "filter: Mitchell").as_bytes()).is_ok()
+    );
+    for (global, monitor, expected) in [
+        ("resize: fill", "", "invalid resize `fill`"),
+        (
+            "",
+            "transition_type: zoom",
+            "invalid transition_type `zoom`",
+        ),
+        ("", "fill_color: \"#fff\"", "invalid fill_color `#fff`"),
+    ] {
+        let err = ConfigManager::parse_config(yaml(global, monitor).as_bytes()).unwrap_err();
+        assert!(format!("{:#}", err).contains(expected), "{:#}", err);
+    }
+}
+
 // ===== COMPREHENSIVE CONFIG TESTS =====
 
 #[test]
@@ -478,6 +1018,51 @@ logging:
     );
 }
 
+#[test]
+fn test_logging_level_validation() {
+    let yaml = |level: &str, module_level: &str| {
+        format!(
+            "application:\n  modules: {{}}\n  layouts: {{}}\n  monitors: []\nlogging:\n  level: {}\n  file: \"\"\n  console: true\n  module_levels:\n    Workspaces: {}\n",
+            level, module_level
+        )
+    };
+    for level in ["trace", "debug", "info", "warn", "error"] {
+        let config: NiriBarConfig = serde_yaml::from_str(&yaml(level, level)).unwrap();
+        assert!(
+            ConfigManager::basic_validation(&config).is_ok(),
+            "{}",
+            level
+        );
+    }
+    let config: NiriBarConfig = serde_yaml::from_str(&yaml("info", "verbose")).unwrap();
+    let err = ConfigManager::basic_validation(&config).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Invalid logging level for module 'Workspaces': verbose")
+    );
+}
+
+#[test]
+fn test_logging_format_validation() {
+    let yaml = |format: &str| {
+        format!(
+            "application:\n  modules: {{}}\n  layouts: {{}}\n  monitors: []\nlogging:\n  level: info\n  file: \"\"\n  console: true\n  format: {}\n",
+            format
+        )
+    };
+    for format in ["iso8601", "simple", "json"] {
+        let config: NiriBarConfig = serde_yaml::from_str(&yaml(format)).unwrap();
+        assert!(
+            ConfigManager::basic_validation(&config).is_ok(),
+            "{}",
+            format
+        );
+    }
+    let config: NiriBarConfig = serde_yaml::from_str(&yaml("xml")).unwrap();
+    let err = ConfigManager::basic_validation(&config).unwrap_err();
+    assert!(err.to_string().contains("Invalid logging format: xml"));
+}
+
 #[test]
 fn test_monitor_pattern_matching_edge_cases() {
     // Test empty pattern
@@ -510,12 +1095,70 @@ fn test_pattern_specificity_calculation() {
     );
 
     // Test exact match has highest specificity
-    assert_eq!(ConfigManager::pattern_specificity("^exact$"), 100);
+    assert!(
+        ConfigManager::pattern_specificity("^exact$")
+            > ConfigManager::pattern_specificity("^exact.*$")
+    );
+    assert_eq!(
+        ConfigManager::pattern_specificity("^exact$"),
+        ConfigManager::pattern_specificity("exact")
+    );
+
+    // Anchors change nothing since matching is whole-name; longer literals beat shorter ones
+    assert_eq!(
+        ConfigManager::pattern_specificity("^DP-.*$"),
+        ConfigManager::pattern_specificity("DP-.*")
+    );
+    assert!(ConfigManager::matches_pattern("DP-1", "DP-.*"));
+    assert!(!ConfigManager::matches_pattern("eDP-1", "DP-.*"));
+    assert!(
+        ConfigManager::pattern_specificity("HDMI-.*") > ConfigManager::pattern_specificity("DP-.*")
+    );
 
     // Test wildcard patterns
-    assert_eq!(ConfigManager::pattern_specificity(".*"), 1);
-    assert_eq!(ConfigManager::pattern_specificity("DP-.*"), 5); // Pattern with wildcard, no ^$
-    assert_eq!(ConfigManager::pattern_specificity("monitor.*"), 5);
+    assert!(ConfigManager::pattern_specificity(".*") > 0);
+    assert_eq!(
+        ConfigManager::pattern_specificity("DP-.*"),
+        ConfigManager::pattern_specificity("DP-.+")
+    );
+
+    // Invalid patterns never win
+    assert_eq!(ConfigManager::pattern_specificity("DP-("), 0);
+}
+
+#[test]
+fn test_monitor_pattern_matching_regex() {
+    // Alternations
+    let alt = "^(DP|HDMI)-[0-9]+$";
+    assert!(ConfigManager::matches_pattern("DP-1", alt));
+    assert!(ConfigManager::matches_pattern("HDMI-12", alt));
+    assert!(!ConfigManager::matches_pattern("eDP-1", alt));
+    assert!(!ConfigManager::matches_pattern("HDMI-A-1", alt));
+
+    // Character classes and quantifiers; patterns must match the whole name
+    assert!(ConfigManager::matches_pattern("eDP-1", "eDP-\\d"));
+    assert!(!ConfigManager::matches_pattern("eDP-10", "eDP-\\d"));
+    assert!(ConfigManager::matches_pattern("eDP-10", "eDP-\\d+"));
+    assert!(ConfigManager::matches_pattern(
+        "HDMI-A-1",
+        "HDMI-[A-C]-\\d{1,2}"
+    ));
+    assert!(!ConfigManager::matches_pattern(
+        "HDMI-D-1",
+        "HDMI-[A-C]-\\d{1,2}"
+    ));
+    assert!(ConfigManager::matches_pattern("DP-3", "DP-[^12]"));
+    assert!(!ConfigManager::matches_pattern("DP-2", "DP-[^12]"));
+
+    // Invalid patterns match nothing
+    assert!(!ConfigManager::matches_pattern("DP-1", "DP-("));
+    assert!(!ConfigManager::matches_pattern("DP-1", "[DP-1"));
+
+    // More specific regex wins over a broad one
+    assert!(
+        ConfigManager::pattern_specificity("^eDP-\\d$")
+            > ConfigManager::pattern_specificity("^.*DP-.*$")
+    );
 }
 
 #[test]
@@ -678,6 +1321,128 @@ width: 200
     assert_eq!(spec.width, Some(200));
 }
 
+#[test]
+fn test_column_min_max_width() {
+    let spec: ColumnSpec =
+        serde_yaml::from_str("modules: [clock]\nmin_width: 120\nmax_width: 400\n").unwrap();
+    assert_eq!(spec.min_width, Some(120));
+    assert_eq!(spec.max_width, Some(400));
+
+    let spec: ColumnSpec = serde_yaml::from_str("modules: [clock]").unwrap();
+    assert_eq!(spec.min_width, None);
+    assert_eq!(spec.max_width, None);
+}
+
+#[test]
+fn test_column_colors() {
+    let spec: ColumnSpec =
+        serde_yaml::from_str("modules: [clock]\nbackground: \"#242424\"\ncolor: \"#fff\"\n")
+            .unwrap();
+    assert_eq!(spec.background.as_deref(), Some("#242424"));
+    assert_eq!(spec.color.as_deref(), Some("#fff"));
+    assert_eq!(spec.valid_background(), Some("#242424"));
+    assert_eq!(spec.valid_text_color(), Some("#fff"));
+
+    // Unset by default
+    let spec: ColumnSpec = serde_yaml::from_str("modules: [clock]").unwrap();
+    assert_eq!(spec.background, None);
+    assert_eq!(spec.valid_text_color(), None);
+
+    // Invalid values are ignored rather than failing the config
+    let spec: ColumnSpec = serde_yaml::from_str("background: red\ncolor: \"#12345\"\n").unwrap();
+    assert_eq!(spec.valid_background(), None);
+    assert_eq!(spec.valid_text_color(), None);
+
+    for ok in ["#abc", "#ABCD", "#a1b2c3", "#a1b2c3d4"] {
+        assert!(is_valid_hex_color(ok), "{}", ok);
+    }
+    for bad in [
+        "abc",
+        "#",
+        "#ab",
+        "#abcde",
+        "#ggg",
+        "#a1b2c3d4e5",
+        "rgb(0,0,0)",
+    ] {
+        assert!(!is_valid_hex_color(bad), "{}", bad);
+    }
+}
+
+#[test]
+fn test_misspelled_enums_list_accepted_values() {
+    let err = serde_yaml::from_str::<ColumnOverflowPolicy>("kebob").unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("invalid overflow `kebob`, expected one of: hide, kebab, shrink"),
+        "{}",
+        err
+    );
+    let err = serde_yaml::from_str::<TextAlign>("centre").unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("invalid align `centre`, expected one of: left, center, right"),
+        "{}",
+        err
+    );
+    let err = serde_yaml::from_str::<DisplayMode>("hidden").unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("invalid display `hidden`, expected one of: show, hide"),
+        "{}",
+        err
+    );
+
+    // The same text surfaces from a full config load, for module and column fields alike
+    let yaml = |column: &str, module: &str| {
+        format!(
+            "application:\n  modules:\n    clock: {{ {} }}\n  layouts:\n    main:\n      columns:\n        left: {{ modules: [clock], {} }}\n  monitors: []\n",
+            module, column
+        )
+    };
+    for (column, module, expected) in [
+        ("overflow: kebob", "", "invalid overflow `kebob`"),
+        ("align: centre", "", "invalid align `centre`"),
+        ("", "align: Right", "invalid align `Right`"),
+        ("", "display: none", "invalid display `none`"),
+    ] {
+        let err = ConfigManager::parse_config(yaml(column, module).as_bytes()).unwrap_err();
+        assert!(
+            format!("{:#}", err).contains(expected),
+            "{}: {:#}",
+            expected,
+            err
+        );
+    }
+    assert!(
+        ConfigManager::parse_config(yaml("overflow: crop", "display: hide").as_bytes()).is_ok()
+    );
+}
+
+#[test]
+fn test_column_overflow_policy_parsing() {
+    for (text, policy) in [
+        ("hide", ColumnOverflowPolicy::Hide),
+        ("crop", ColumnOverflowPolicy::Hide),
+        ("kebab", ColumnOverflowPolicy::Kebab),
+        ("shrink", ColumnOverflowPolicy::Shrink),
+        ("ellipsize", ColumnOverflowPolicy::Shrink),
+    ] {
+        assert_eq!(
+            serde_yaml::from_str::<ColumnOverflowPolicy>(text).unwrap(),
+            policy
+        );
+    }
+    assert!(serde_yaml::from_str::<ColumnOverflowPolicy>("wrap").is_err());
+    // Serializes under the canonical name
+    assert_eq!(
+        serde_yaml::to_string(&ColumnOverflowPolicy::Shrink)
+            .unwrap()
+            .trim(),
+        "shrink"
+    );
+}
+
 #[test]
 fn test_wallpaper_config_with_swww() {
     let yaml_config = r#"
@@ -744,6 +1509,764 @@ wallpapers:
     assert!(config.wallpapers.is_some());
 }
 
+fn monitor_info(connector: &str, make: &str, model: &str, size: (i32, i32)) -> MonitorInfo {
+    MonitorInfo {
+        connector: connector.to_string(),
+        manufacturer: Some(make.to_string()),
+        model: Some(model.to_string()),
+        logical_size: size,
+        scale_factor: 2,
+    }
+}
+
+#[test]
+fn test_monitor_match_by_make_model_resolution() {
+    let yaml = r#"
+application:
+  modules: {}
+  layouts: {}
+  monitors:
+    - match: ".*"
+      height: 30
+    - match: "DP-.*"
+      height: 32
+    - match_model: "U2720Q"
+      height: 40
+    - match_make: "Dell Inc\\."
+      match_model: "U2720Q"
+      height: 44
+    - match: "DP-2"
+      match_make: "Dell Inc\\."
+      match_model: "U2720Q"
+      height: 48
+    - match_resolution: "3840x2160"
+      show_bar: false
+"#;
+    let config = ConfigManager::parse_config(yaml.as_bytes()).unwrap();
+    // `match` defaults to any connector
+    assert_eq!(config.application.monitors[2].match_pattern, ".*");
+    let manager = ConfigManager::from_config(config);
+
+    let best_height =
+        |info: &MonitorInfo| manager.best_monitor_config(info).and_then(|mc| mc.height);
+    let dell = monitor_info("DP-1", "Dell Inc.", "U2720Q", (1920, 1080));
+    // Make + model outrank model alone, which outranks any connector pattern
+    assert_eq!(best_height(&dell), Some(44));
+    // Connector + make + model is the most specific
+    assert_eq!(
+        best_height(&MonitorInfo {
+            connector: "DP-2".to_string(),
+            ..dell.clone()
+        }),
+        Some(48)
+    );
+    // Same model from another make: the model-only entry
+    assert_eq!(
+        best_height(&monitor_info("HDMI-A-1", "Other", "U2720Q", (1920, 1080))),
+        Some(40)
+    );
+    // No make/model reported: only connector entries apply
+    assert_eq!(best_height(&MonitorInfo::for_connector("DP-3")), Some(32));
+    assert_eq!(best_height(&MonitorInfo::for_connector("eDP-1")), Some(30));
+
+    // Resolution matches the pixel size (logical size x scale) or the logical size
+    let lg = monitor_info("HDMI-A-1", "LG", "27UL850", (1920, 1080));
+    assert!(!manager.best_monitor_config(&lg).unwrap().show_bar);
+    let lg_logical = monitor_info("HDMI-A-1", "LG", "27UL850", (3840, 2160));
+    assert!(!manager.best_monitor_config(&lg_logical).unwrap().show_bar);
+
+    // Connector lookups use the recorded monitor info
+    assert_eq!(manager.get_monitor_height("DP-1"), 32);
+    manager.set_monitor_info(dell);
+    assert_eq!(manager.get_monitor_height("DP-1"), 44);
+    manager.set_monitor_info(lg);
+    assert!(!manager.is_monitor_enabled("HDMI-A-1"));
+    manager.remove_monitor_info("DP-1");
+    assert_eq!(manager.get_monitor_height("DP-1"), 32);
+}
+
+#[test]
+fn test_monitor_match_resolution_validation() {
+    let yaml = |resolution: &str| {
+        format!(
+            "application:\n  modules: {{}}\n  layouts: {{}}\n  monitors:\n    - match_resolution: \"{}\"\n",
+            resolution
+        )
+    };
+    assert!(ConfigManager::parse_config(yaml("2560x1440").as_bytes()).is_ok());
+    assert!(ConfigManager::parse_config(yaml(" 1920 X 1080 ").as_bytes()).is_ok());
+    for bad in ["2560", "wide", "0x1080", "2560x-1"] {
+        let err = ConfigManager::parse_config(yaml(bad).as_bytes()).unwrap_err();
+        assert!(format!("{:#}", err).contains("match_resolution"), "{}", bad);
+    }
+}
+
+#[test]
+fn test_unknown_module_names_are_rejected() {
+    let yaml = |policy: &str| {
+        format!(
+            r#"
+application:
+  {}
+  modules: {{}}
+  layouts:
+    main:
+      columns:
+        left: {{ modules: [workspaces, clok] }}
+        right: {{ modules: [clock, battery] }}
+  monitors:
+    - match: "eDP-1"
+      layout:
+        columns:
+          only: {{ modules: [batery, clok] }}
+"#,
+            policy
+        )
+    };
+
+    // Every typo is reported once, from the shared layouts and monitor layouts alike
+    let err = ConfigManager::parse_config(yaml("").as_bytes()).unwrap_err();
+    assert_eq!(
+        format!("{:#}", err),
+        "Unknown module(s) in layouts: batery, clok"
+    );
+    let err = ConfigManager::parse_config(yaml("unknown_modules: error").as_bytes()).unwrap_err();
+    assert!(format!("{:#}", err).contains("clok"));
+
+    // `warn` keeps the config; the bar skips the modules at render time
+    let config = ConfigManager::parse_config(yaml("unknown_modules: warn").as_bytes()).unwrap();
+    assert_eq!(
+        ConfigManager::unknown_module_names(&config, |name| name != "clok" && name != "batery"),
+        vec!["batery".to_string(), "clok".to_string()]
+    );
+    assert!(ConfigManager::unknown_module_names(&config, |_| true).is_empty());
+    assert!(
+        ConfigManager::parse_config(yaml("unknown_modules: ignore").as_bytes()).is_err(),
+        "only error and warn are policies"
+    );
+}
+
+#[test]
+fn test_bar_position_config() {
+    let yaml = r#"
+application:
+  position: bottom
+  modules: {}
+  layouts: {}
+  monitors:
+    - match: "eDP-1"
+      position: top
+    - match: ".*"
+"#;
+    let config = ConfigManager::parse_config(yaml.as_bytes()).unwrap();
+    assert_eq!(config.application.position, BarPosition::Bottom);
+    assert_eq!(
+        config.application.monitors[0].position,
+        Some(BarPosition::Top)
+    );
+    assert_eq!(config.application.monitors[1].position, None);
+
+    let manager = ConfigManager::new();
+    *manager.config.lock().unwrap() = Some(config);
+    // Monitor override wins; others inherit the global position
+    assert_eq!(manager.get_monitor_position("eDP-1"), BarPosition::Top);
+    assert_eq!(manager.get_monitor_position("DP-1"), BarPosition::Bottom);
+
+    // Defaults to top when unset
+    let config: ApplicationConfig =
+        serde_yaml::from_str("modules: {}\nlayouts: {}\nmonitors: []\n").unwrap();
+    assert_eq!(config.position, BarPosition::Top);
+    assert!(serde_yaml::from_str::<BarPosition>("left").is_err());
+}
+
+#[test]
+fn test_bar_opacity_and_background_config() {
+    let yaml = r##"
+application:
+  opacity: 0.85
+  background: "#1e1e2e"
+  modules: {}
+  layouts: {}
+  monitors:
+    - match: "eDP-1"
+      opacity: 1.7
+    - match: "DP-1"
+      background: "transparent-ish"
+    - match: ".*"
+"##;
+    let config = ConfigManager::parse_config(yaml.as_bytes()).unwrap();
+    assert_eq!(config.application.opacity, Some(0.85));
+    assert_eq!(config.application.background.as_deref(), Some("#1e1e2e"));
+    assert_eq!(config.application.monitors[0].opacity, Some(1.7));
+
+    let manager = ConfigManager::from_config(config);
+    // Global settings apply; out-of-range opacity is clamped, invalid colors are ignored
+    let options = manager.get_bar_options("HDMI-A-1");
+    assert_eq!(options.opacity, Some(0.85));
+    assert_eq!(options.background.as_deref(), Some("#1e1e2e"));
+    assert_eq!(manager.get_monitor_opacity("eDP-1"), Some(1.0));
+    assert_eq!(manager.get_monitor_background("DP-1"), None);
+
+    // Unset leaves both to the theme
+    let yaml = "application:\n  modules: {}\n  layouts: {}\n  monitors: [{match: \".*\"}]\n";
+    let manager = ConfigManager::from_config(ConfigManager::parse_config(yaml.as_bytes()).unwrap());
+    let options = manager.get_bar_options("eDP-1");
+    assert_eq!((options.opacity, options.background), (None, None));
+}
+
+#[test]
+fn test_bar_opacity_clamp_and_background_validation() {
+    use niri_bar::config::{clamp_opacity, valid_bar_background};
+
+    assert_eq!(clamp_opacity(0.5), 0.5);
+    assert_eq!(clamp_opacity(0.0), 0.0);
+    assert_eq!(clamp_opacity(-0.3), 0.0);
+    assert_eq!(clamp_opacity(2.0), 1.0);
+    assert_eq!(clamp_opacity(f64::NAN), 1.0);
+
+    assert_eq!(valid_bar_background(Some("#000")), Some("#000"));
+    assert_eq!(valid_bar_background(Some("#1e1e2ecc")), Some("#1e1e2ecc"));
+    assert_eq!(valid_bar_background(Some("black")), None);
+    assert_eq!(valid_bar_background(Some("#12345")), None);
+    assert_eq!(valid_bar_background(None), None);
+}
+
+#[test]
+fn test_bar_height_config() {
+    let yaml = r#"
+application:
+  height: 32
+  modules: {}
+  layouts: {}
+  monitors:
+    - match: "eDP-1"
+      height: 48
+    - match: ".*"
+"#;
+    let config = ConfigManager::parse_config(yaml.as_bytes()).unwrap();
+    assert_eq!(config.application.height, Some(32));
+    assert_eq!(config.application.monitors[0].height, Some(48));
+
+    let manager = ConfigManager::new();
+    // No config loaded yet
+    assert_eq!(manager.get_monitor_height("eDP-1"), DEFAULT_BAR_HEIGHT);
+    *manager.config.lock().unwrap() = Some(config);
+    assert_eq!(manager.get_monitor_height("eDP-1"), 48);
+    assert_eq!(manager.get_monitor_height("DP-1"), 32);
+
+    // Unset everywhere falls back to 40px
+    let yaml = "application:\n  modules: {}\n  layouts: {}\n  monitors: [{match: \".*\"}]\n";
+    *manager.config.lock().unwrap() = Some(ConfigManager::parse_config(yaml.as_bytes()).unwrap());
+    assert_eq!(manager.get_monitor_height("DP-1"), 40);
+
+    // Heights below 1px are rejected, also on configs that never went through the schema
+    let mut config = ConfigManager::parse_config(yaml.as_bytes()).unwrap();
+    config.application.height = Some(0);
+    let err = ConfigManager::basic_validation(&config).unwrap_err();
+    assert!(err.to_string().contains("height 0"), "{}", err);
+    config.application.height = Some(24);
+    config.application.monitors[0].height = Some(-5);
+    let err = ConfigManager::basic_validation(&config).unwrap_err();
+    assert!(err.to_string().contains("height -5"), "{}", err);
+    config.application.monitors[0].height = Some(1);
+    assert!(ConfigManager::basic_validation(&config).is_ok());
+}
+
+fn workspace(idx: i64, name: Option<&str>) -> WorkspaceInfo {
+    WorkspaceInfo {
+        id: idx + 100,
+        idx,
+        name: name.map(str::to_string),
+        is_focused: true,
+        is_active: true,
+        output: None,
+    }
+}
+
+fn layout_column_names(layout: Option<LayoutConfig>) -> Vec<String> {
+    layout.unwrap().columns.keys().cloned().collect()
+}
+
+#[test]
+fn test_workspace_layout_precedence() {
+    let yaml = r#"
+application:
+  modules: {}
+  layouts:
+    three_column: { columns: { left: { modules: [] }, center: { modules: [] }, right: { modules: [] } } }
+    minimal: { columns: { center: { modules: [] } } }
+    focus: { columns: { left: { modules: [] } } }
+    wide: { columns: { left: { modules: [] }, right: { modules: [] } } }
+  by_workspace:
+    "2": minimal
+    "9": missing
+    chat: focus
+  monitors:
+    - match: "eDP-1"
+      by_workspace:
+        "2": wide
+    - match: "^DP-1$"
+      by_workspace:
+        "3": focus
+    - match: "DP-.*"
+      by_workspace:
+        "3": wide
+"#;
+    let manager = ConfigManager::new();
+    // No config loaded yet
+    assert_eq!(
+        manager.get_workspace_layout("DP-1", &workspace(1, None)),
+        None
+    );
+    *manager.config.lock().unwrap() = Some(ConfigManager::parse_config(yaml.as_bytes()).unwrap());
+
+    // No override: same as the monitor layout
+    assert_eq!(
+        manager.get_workspace_layout("DP-1", &workspace(1, None)),
+        manager.get_monitor_layout("DP-1")
+    );
+    // Application-wide override by index
+    assert_eq!(
+        layout_column_names(manager.get_workspace_layout("DP-2", &workspace(2, None))),
+        vec!["center"]
+    );
+    // Monitor override beats the application one
+    assert_eq!(
+        layout_column_names(manager.get_workspace_layout("eDP-1", &workspace(2, None))),
+        vec!["left", "right"]
+    );
+    // The most specific matching monitor wins
+    assert_eq!(
+        layout_column_names(manager.get_workspace_layout("DP-1", &workspace(3, None))),
+        vec!["left"]
+    );
+    assert_eq!(
+        layout_column_names(manager.get_workspace_layout("DP-2", &workspace(3, None))),
+        vec!["left", "right"]
+    );
+    // Name is looked up before index
+    assert_eq!(
+        layout_column_names(manager.get_workspace_layout("DP-2", &workspace(2, Some("chat")))),
+        vec!["left"]
+    );
+    // A profile that doesn't exist falls back to the monitor layout
+    assert_eq!(
+        manager.get_workspace_layout("DP-2", &workspace(9, None)),
+        manager.get_monitor_layout("DP-2")
+    );
+}
+
+#[test]
+fn test_color_palette_references() {
+    let yaml = r##"
+application:
+  colors:
+    accent: "#e5786d"
+    bg-dark: "#242424"
+  modules:
+    battery:
+      warn_color: $accent
+  layouts:
+    main:
+      columns:
+        left: { modules: [], background: $bg-dark, color: "#f6f3e8" }
+        right: { modules: [], color: $accent }
+  monitors: []
+"##;
+    let config = ConfigManager::parse_config(yaml.as_bytes()).unwrap();
+    let columns = &config.application.layouts["main"].columns;
+    assert_eq!(columns["left"].background.as_deref(), Some("#242424"));
+    assert_eq!(columns["right"].color.as_deref(), Some("#e5786d"));
+    assert_eq!(
+        config.application.modules["battery"].additional["warn_color"],
+        serde_yaml::Value::String("#e5786d".into())
+    );
+    // Literal colors pass through unchanged
+    assert_eq!(columns["left"].color.as_deref(), Some("#f6f3e8"));
+    assert_eq!(config.application.colors.len(), 2);
+
+    // `$name` outside color fields is not a palette reference
+    let yaml = "application:\n  colors: {}\n  modules: { clock: { format: $accent } }\n  layouts: {}\n  monitors: []\n";
+    let config = ConfigManager::parse_config(yaml.as_bytes()).unwrap();
+    assert_eq!(
+        config.application.modules["clock"].format.as_deref(),
+        Some("$accent")
+    );
+}
+
+#[test]
+fn test_color_palette_undefined_name_is_an_error() {
+    let yaml = r##"
+application:
+  colors: { accent: "#e5786d" }
+  modules: {}
+  layouts:
+    main: { columns: { left: { modules: [], background: $missing } } }
+  monitors: []
+"##;
+    let err = ConfigManager::parse_config(yaml.as_bytes()).unwrap_err();
+    let message = format!("{:#}", err);
+    assert!(message.contains("$missing"), "{}", message);
+    assert!(message.contains("background"), "{}", message);
+
+    // Without any palette every reference is undefined
+    let yaml = "application:\n  modules: {}\n  layouts: { main: { columns: { c: { modules: [], color: $accent } } } }\n  monitors: []\n";
+    assert!(ConfigManager::parse_config(yaml.as_bytes()).is_err());
+}
+
+/// The per-monitor module merge as it was before `ModuleConfig::merged_with`: a fixed list
+/// of fields, kept here to check the YAML-based merge agrees on all of them
+fn legacy_module_merge(global: &ModuleConfig, mc: &ModuleConfig) -> ModuleConfig {
+    let mut merged = global.clone();
+    if mc.format.is_some() {
+        merged.format = mc.format.clone();
+    }
+    if mc.tooltip.is_some() {
+        merged.tooltip = mc.tooltip;
+    }
+    if mc.highlight_active.is_some() {
+        merged.highlight_active = mc.highlight_active;
+    }
+    if mc.show_numbers.is_some() {
+        merged.show_numbers = mc.show_numbers;
+    }
+    if mc.show_wallpaper.is_some() {
+        merged.show_wallpaper = mc.show_wallpaper;
+    }
+    if mc.max_length.is_some() {
+        merged.max_length = mc.max_length;
+    }
+    if mc.ellipsize.is_some() {
+        merged.ellipsize = mc.ellipsize.clone();
+    }
+    if mc.show_percentage.is_some() {
+        merged.show_percentage = mc.show_percentage;
+    }
+    if mc.warn_threshold.is_some() {
+        merged.warn_threshold = mc.warn_threshold;
+    }
+    if mc.critical_threshold.is_some() {
+        merged.critical_threshold = mc.critical_threshold;
+    }
+    if mc.cpu.is_some() {
+        merged.cpu = mc.cpu;
+    }
+    if mc.mem.is_some() {
+        merged.mem = mc.mem;
+    }
+    if mc.net.is_some() {
+        merged.net = mc.net;
+    }
+    if mc.enabled.is_some() {
+        merged.enabled = mc.enabled;
+    }
+    if mc.display.is_some() {
+        merged.display = mc.display.clone();
+    }
+    merged.additional.extend(mc.additional.clone());
+    if mc.default_wallpaper.is_some() {
+        merged.default_wallpaper = mc.default_wallpaper.clone();
+    }
+    if mc.wallpapers.is_some() {
+        merged.wallpapers = mc.wallpapers.clone();
+    }
+    if mc.special_cmd.is_some() {
+        merged.special_cmd = mc.special_cmd.clone();
+    }
+    merged
+}
+
+fn module(yaml: &str) -> ModuleConfig {
+    serde_yaml::from_str(yaml).unwrap()
+}
+
+#[test]
+fn test_module_merge_matches_legacy_fields() {
+    let global = module(
+        r#"
+format: "%H:%M"
+tooltip: true
+show_percentage: true
+warn_threshold: 30
+critical_threshold: 10
+display: show
+cpu: true
+mem: true
+default_wallpaper: /bg.png
+wallpapers: { "1": /one.png }
+special_cmd: "set ${current_workspace_image}"
+icon: clock
+"#,
+    );
+    let overrides = [
+        "{}",
+        "format: \"%H:%M:%S\"",
+        "tooltip: false\nwarn_threshold: 50\ndisplay: hide",
+        "cpu: false\nnet: true\nenabled: false\nmax_length: 20\nellipsize: middle",
+        "highlight_active: true\nshow_numbers: false\nshow_wallpaper: true",
+        "default_wallpaper: /other.png\nspecial_cmd: other\nicon: calendar\nextra: 3",
+    ];
+    for overlay in overrides {
+        let overlay = module(overlay);
+        assert_eq!(
+            global.merged_with(&overlay),
+            legacy_module_merge(&global, &overlay),
+            "override: {:?}",
+            overlay
+        );
+    }
+}
+
+#[test]
+fn test_module_merge_overrides_every_field() {
+    let global = module(
+        r#"
+format: "{percent}%"
+width: 200
+on_click: pavucontrol
+swww_options: { transition_type: fade }
+wallpapers: { "1": /one.png, "2": /two.png }
+"#,
+    );
+    // Fields the old hand-written list dropped now override too
+    let merged = global.merged_with(&module(
+        r#"
+width: 320
+on_click_right: "notify-send hi"
+show_window_titles: false
+swww_options: { transition_type: wipe, transition_fps: 60 }
+"#,
+    ));
+    assert_eq!(merged.format.as_deref(), Some("{percent}%"));
+    assert_eq!(merged.width, Some(320));
+    assert_eq!(merged.on_click.as_deref(), Some("pavucontrol"));
+    assert_eq!(merged.on_click_right.as_deref(), Some("notify-send hi"));
+    assert_eq!(merged.show_window_titles, Some(false));
+    let swww = merged.swww_options.unwrap().resolve();
+    assert_eq!(swww.transition_type, "wipe");
+    assert_eq!(swww.transition_fps, 60);
+
+    // Nested maps merge key by key
+    let merged = global.merged_with(&module("wallpapers: { \"2\": /mine.png }"));
+    let wallpapers = merged.wallpapers.unwrap();
+    assert_eq!(wallpapers["1"], "/one.png");
+    assert_eq!(wallpapers["2"], "/mine.png");
+
+    // Per-module alignment is a typed field, overridable per monitor
+    let aligned = module("align: right");
+    assert_eq!(aligned.align, Some(TextAlign::Right));
+    assert!(!aligned.additional.contains_key("align"));
+    assert_eq!(
+        aligned.merged_with(&module("align: left")).align,
+        Some(TextAlign::Left)
+    );
+    assert!(serde_yaml::from_str::<ModuleConfig>("align: middle").is_err());
+
+    // An empty override changes nothing
+    assert_eq!(global.merged_with(&ModuleConfig::default()), global);
+}
+
+#[test]
+fn test_module_merge_swww_options_per_field() {
+    use niri_bar::config::SwwwOptions;
+
+    let global = module(
+        "swww_options: { transition_type: fade, transition_fps: 60, resize: fit, filter: Nearest }",
+    );
+
+    // Only the fields the override sets change; an explicit null inherits
+    let merged = global.merged_with(&module(
+        "swww_options: { transition_type: wipe, transition_fps: null }",
+    ));
+    let swww = merged.swww_options.unwrap().resolve();
+    assert_eq!(swww.transition_type, "wipe");
+    assert_eq!(swww.transition_fps, 60);
+    assert_eq!(swww.resize, "fit");
+    assert_eq!(swww.filter, "Nearest");
+    // Fields set nowhere keep the swww defaults
+    assert_eq!(swww.transition_step, SwwwOptions::default().transition_step);
+
+    // A null or missing block keeps the global options whole
+    for overlay in ["swww_options: null", "{}"] {
+        assert_eq!(
+            global.merged_with(&module(overlay)).swww_options,
+            global.swww_options,
+            "override: {}",
+            overlay
+        );
+    }
+}
+
+#[test]
+fn test_module_merge_falls_back_to_field_by_field() {
+    // An additional option shadowing a typed field can't go through the YAML round trip
+    let mut global = module("format: \"{percent}%\"\nwallpapers: { \"1\": /one.png }\nstep: 5");
+    global
+        .additional
+        .insert("padding".to_string(), "wide".into());
+    let overlay = module("tooltip: false\nwallpapers: { \"2\": /two.png }\nstep: 10");
+
+    let merged = global.merged_with(&overlay);
+    // The global settings survive instead of being replaced by the override
+    assert_eq!(merged.format.as_deref(), Some("{percent}%"));
+    assert_eq!(merged.tooltip, Some(false));
+    let wallpapers = merged.wallpapers.unwrap();
+    assert_eq!(wallpapers["1"], "/one.png");
+    assert_eq!(wallpapers["2"], "/two.png");
+    assert_eq!(merged.additional["step"], serde_yaml::Value::from(10));
+    assert_eq!(
+        merged.additional["padding"],
+        serde_yaml::Value::from("wide")
+    );
+}
+
+#[test]
+fn test_get_monitor_modules_merges_partial_override() {
+    let yaml = r#"
+application:
+  modules:
+    wallpaper:
+      default_wallpaper: /global.png
+      swww_options: { transition_type: fade, transition_fps: 60, resize: fit }
+  layouts: {}
+  monitors:
+    - match: "eDP-1"
+      modules:
+        wallpaper:
+          swww_options: { transition_type: wipe }
+        clock:
+          format: "%H:%M"
+"#;
+    let manager = ConfigManager::new();
+    *manager.config.lock().unwrap() = Some(ConfigManager::parse_config(yaml.as_bytes()).unwrap());
+
+    let modules = manager.get_monitor_modules("eDP-1").unwrap();
+    let wallpaper = &modules["wallpaper"];
+    // The monitor's single field lands on top of the global module, as bars build it
+    assert_eq!(wallpaper.default_wallpaper.as_deref(), Some("/global.png"));
+    let swww = wallpaper.swww_options.clone().unwrap().resolve();
+    assert_eq!(swww.transition_type, "wipe");
+    assert_eq!(swww.transition_fps, 60);
+    assert_eq!(swww.resize, "fit");
+    // Modules only the monitor defines are taken as-is
+    assert_eq!(modules["clock"].format.as_deref(), Some("%H:%M"));
+
+    // Unmatched monitors keep the global module
+    let other = manager.get_monitor_modules("DP-1").unwrap();
+    assert_eq!(
+        other["wallpaper"]
+            .swww_options
+            .clone()
+            .unwrap()
+            .resolve()
+            .transition_type,
+        "fade"
+    );
+}
+
+#[test]
+fn test_bar_margins_config() {
+    let yaml = r#"
+application:
+  margin_top: 6
+  margin_left: 12
+  margin_right: 12
+  modules: {}
+  layouts: {}
+  monitors:
+    - match: "eDP-1"
+      margin_top: 0
+      margin_bottom: 4
+    - match: ".*"
+"#;
+    let config = ConfigManager::parse_config(yaml.as_bytes()).unwrap();
+    assert_eq!(config.application.margins.margin_top, Some(6));
+    assert_eq!(config.application.margins.margin_bottom, None);
+    assert_eq!(
+        config.application.monitors[0].margins.margin_bottom,
+        Some(4)
+    );
+
+    let manager = ConfigManager::new();
+    assert_eq!(manager.get_monitor_margins("DP-1"), BarMargins::default());
+    *manager.config.lock().unwrap() = Some(config);
+
+    // Monitor overrides individual edges, inheriting the rest
+    assert_eq!(
+        manager.get_monitor_margins("eDP-1"),
+        BarMargins {
+            top: 0,
+            left: 12,
+            right: 12,
+            bottom: 4,
+        }
+    );
+    assert_eq!(
+        manager.get_monitor_margins("DP-1"),
+        BarMargins {
+            top: 6,
+            left: 12,
+            right: 12,
+            bottom: 0,
+        }
+    );
+
+    let options = manager.get_bar_options("DP-1");
+    assert_eq!(options.position, BarPosition::Top);
+    assert_eq!(options.height, DEFAULT_BAR_HEIGHT);
+    assert_eq!(options.margins.top, 6);
+}
+
+#[test]
+fn test_bar_layer_config() {
+    for (text, layer) in [
+        ("background", BarLayer::Background),
+        ("bottom", BarLayer::Bottom),
+        ("top", BarLayer::Top),
+        ("overlay", BarLayer::Overlay),
+    ] {
+        assert_eq!(serde_yaml::from_str::<BarLayer>(text).unwrap(), layer);
+    }
+    assert!(serde_yaml::from_str::<BarLayer>("Top").is_err());
+
+    let yaml = r#"
+application:
+  layer: overlay
+  modules: {}
+  layouts: {}
+  monitors:
+    - match: "eDP-1"
+      layer: bottom
+    - match: ".*"
+"#;
+    let config = ConfigManager::parse_config(yaml.as_bytes()).unwrap();
+    let manager = ConfigManager::new();
+    assert_eq!(manager.get_monitor_layer("eDP-1"), BarLayer::Top);
+    *manager.config.lock().unwrap() = Some(config);
+    assert_eq!(manager.get_monitor_layer("eDP-1"), BarLayer::Bottom);
+    assert_eq!(manager.get_bar_options("DP-1").layer, BarLayer::Overlay);
+}
+
+#[test]
+fn test_auto_hide_on_fullscreen_config() {
+    let yaml = r#"
+application:
+  auto_hide_on_fullscreen: true
+  modules: {}
+  layouts: {}
+  monitors:
+    - match: "eDP-1"
+      auto_hide_on_fullscreen: false
+    - match: ".*"
+"#;
+    let config = ConfigManager::parse_config(yaml.as_bytes()).unwrap();
+    let manager = ConfigManager::new();
+    assert!(!manager.get_bar_options("DP-1").auto_hide_on_fullscreen);
+    *manager.config.lock().unwrap() = Some(config);
+    assert!(!manager.get_monitor_auto_hide_on_fullscreen("eDP-1"));
+    assert!(manager.get_bar_options("DP-1").auto_hide_on_fullscreen);
+}
+
 // ===== PROPERTY-BASED TESTS =====
 
 proptest! {
@@ -798,9 +2321,20 @@ fn arbitrary_config() -> impl Strategy<Value = NiriBarConfig> {
             |(theme, modules, layouts, monitors, logging)| NiriBarConfig {
                 application: ApplicationConfig {
                     theme,
+                    position: BarPosition::default(),
+                    layer: BarLayer::default(),
+                    height: None,
+                    margins: MarginConfig::default(),
+                    auto_hide_on_fullscreen: None,
+                    hide_when_disabled: None,
+                    opacity: None,
+                    background: None,
                     modules,
+                    colors: std::collections::HashMap::new(),
+                    unknown_modules: Default::default(),
                     layouts,
                     monitors,
+                    by_workspace: std::collections::HashMap::new(),
                     wallpapers: WallpaperConfig::default(),
                 },
                 logging,
@@ -823,6 +2357,11 @@ fn arbitrary_layout() -> impl Strategy<Value = LayoutConfig> {
                     gap: gap.map(|g| g as i32),
                     align: Some(TextAlign::Left),
                     width: width.map(|w| w as i32),
+                    min_width: None,
+                    max_width: None,
+                    background: None,
+                    color: None,
+                    click_through: None,
                 }),
         ),
         1..5,
@@ -843,10 +2382,22 @@ fn arbitrary_monitor_config() -> impl Strategy<Value = MonitorConfig> {
     )
         .prop_map(|(match_pattern, show_bar)| MonitorConfig {
             match_pattern,
+            match_make: None,
+            match_model: None,
+            match_resolution: None,
             show_bar,
             layout: None,
             modules: None,
             wallpapers: None,
+            position: None,
+            layer: None,
+            height: None,
+            margins: MarginConfig::default(),
+            auto_hide_on_fullscreen: None,
+            hide_when_disabled: None,
+            opacity: None,
+            background: None,
+            by_workspace: std::collections::HashMap::new(),
         })
 }
 
@@ -870,6 +2421,10 @@ fn arbitrary_logging_config() -> impl Strategy<Value = LoggingConfig> {
                     include_file,
                     include_line,
                     include_class,
+                    max_size_bytes: 10 * 1024 * 1024,
+                    max_files: 5,
+                    module_levels: Default::default(),
+                    journald: false,
                 }
             },
         )
@@ -1061,15 +2616,38 @@ mod timeout_tests {
         let config = NiriBarConfig {
             application: ApplicationConfig {
                 theme: "test".to_string(),
+                position: BarPosition::default(),
+                layer: BarLayer::default(),
+                height: None,
+                margins: MarginConfig::default(),
+                auto_hide_on_fullscreen: None,
+                hide_when_disabled: None,
+                opacity: None,
+                background: None,
                 modules: std::collections::HashMap::new(),
+                colors: std::collections::HashMap::new(),
+                unknown_modules: Default::default(),
                 layouts: std::collections::HashMap::new(),
                 monitors: vec![MonitorConfig {
                     match_pattern: ".*".to_string(),
+                    match_make: None,
+                    match_model: None,
+                    match_resolution: None,
                     show_bar: true,
                     layout: None,
                     modules: None,
                     wallpapers: None,
+                    position: None,
+                    layer: None,
+                    height: None,
+                    margins: MarginConfig::default(),
+                    auto_hide_on_fullscreen: None,
+                    hide_when_disabled: None,
+                    opacity: None,
+                    background: None,
+                    by_workspace: std::collections::HashMap::new(),
                 }],
+                by_workspace: std::collections::HashMap::new(),
                 wallpapers: WallpaperConfig::default(),
             },
             logging: LoggingConfig {
@@ -1080,6 +2658,10 @@ mod timeout_tests {
                 include_file: true,
                 include_line: true,
                 include_class: true,
+                max_size_bytes: 10 * 1024 * 1024,
+                max_files: 5,
+                module_levels: Default::default(),
+                journald: false,
             },
         };
 
@@ -1170,3 +2752,26 @@ logging:
         });
     }
 }
+
+#[test]
+fn test_sub_second_intervals_are_reported() {
+    let yaml = r#"
+application:
+  modules:
+    mem: { interval_ms: 250 }
+    temp: { interval_ms: 2000 }
+    notifications: { interval_ms: 250 }
+  layouts: {}
+  monitors:
+    - match: "eDP-1"
+      modules:
+        battery: { interval_ms: 500 }
+        mem: { interval_ms: 100 }
+"#;
+    // Only modules on the shared ticks are capped; notifications polls on its own thread
+    let config = ConfigManager::parse_config(yaml.as_bytes()).unwrap();
+    assert_eq!(
+        ConfigManager::sub_second_intervals(&config, |name| name != "notifications"),
+        vec!["battery".to_string(), "mem".to_string()]
+    );
+}
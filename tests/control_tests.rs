@@ -0,0 +1,145 @@
+use anyhow::{Result, bail};
+use niri_bar::control::{
+    CONTROL_SOCKET_NAME, ControlCommand, ControlHandler, ControlRequest, ControlServer,
+    control_socket_path, dispatch, run_client,
+};
+use pretty_assertions::assert_eq;
+use std::path::{Path, PathBuf};
+
+#[test]
+fn test_parse_control_commands() {
+    assert_eq!(
+        ControlCommand::parse(r#"{"reload":true}"#).unwrap(),
+        ControlCommand::Reload
+    );
+    assert_eq!(
+        ControlCommand::parse(" {\"toggle_bar\": \"eDP-1\"}\n").unwrap(),
+        ControlCommand::ToggleBar("eDP-1".to_string())
+    );
+    assert_eq!(
+        ControlCommand::parse(r#"{"set_theme":"dracula"}"#).unwrap(),
+        ControlCommand::SetTheme("dracula".to_string())
+    );
+}
+
+#[test]
+fn test_parse_rejects_malformed_commands() {
+    for (line, expected) in [
+        ("reload", "invalid JSON"),
+        (r#"["reload"]"#, "expected a JSON object"),
+        ("{}", "exactly one command"),
+        (r#"{"reload":true,"set_theme":"x"}"#, "exactly one command"),
+        (r#"{"reload":false}"#, "`reload` takes `true`"),
+        (
+            r#"{"toggle_bar":1}"#,
+            "`toggle_bar` takes a non-empty string",
+        ),
+        (
+            r#"{"set_theme":""}"#,
+            "`set_theme` takes a non-empty string",
+        ),
+        (r#"{"quit":true}"#, "unknown command `quit`"),
+    ] {
+        let err = ControlCommand::parse(line).unwrap_err().to_string();
+        assert!(err.contains(expected), "{}: {}", line, err);
+    }
+}
+
+/// Records which handler ran, failing for one connector
+#[derive(Default)]
+struct RecordingHandler {
+    calls: Vec<String>,
+}
+
+impl ControlHandler for RecordingHandler {
+    fn reload(&mut self) -> Result<()> {
+        self.calls.push("reload".to_string());
+        Ok(())
+    }
+
+    fn toggle_bar(&mut self, connector: &str) -> Result<()> {
+        if connector == "HDMI-A-9" {
+            bail!("no bar on '{}'", connector);
+        }
+        self.calls.push(format!("toggle_bar {}", connector));
+        Ok(())
+    }
+
+    fn set_theme(&mut self, theme: &str) -> Result<()> {
+        self.calls.push(format!("set_theme {}", theme));
+        Ok(())
+    }
+}
+
+#[test]
+fn test_dispatch_routes_to_handler() {
+    let mut handler = RecordingHandler::default();
+    for line in [
+        r#"{"toggle_bar":"eDP-1"}"#,
+        r#"{"reload":true}"#,
+        r#"{"set_theme":"solarized"}"#,
+    ] {
+        dispatch(&mut handler, &ControlCommand::parse(line).unwrap()).unwrap();
+    }
+    assert_eq!(
+        handler.calls,
+        vec!["toggle_bar eDP-1", "reload", "set_theme solarized"]
+    );
+
+    // Handler errors come back to the caller
+    let err = dispatch(
+        &mut handler,
+        &ControlCommand::ToggleBar("HDMI-A-9".to_string()),
+    )
+    .unwrap_err();
+    assert_eq!(err.to_string(), "no bar on 'HDMI-A-9'");
+    assert_eq!(handler.calls.len(), 3);
+}
+
+#[test]
+fn test_socket_replies_with_the_handler_outcome() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join(CONTROL_SOCKET_NAME);
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<ControlRequest>();
+    let server = ControlServer::start(path.clone(), tx).unwrap();
+    // Stands in for the GTK thread
+    let handler = std::thread::spawn(move || {
+        let mut handler = RecordingHandler::default();
+        while let Some((command, done)) = rx.blocking_recv() {
+            let _ = done.send(dispatch(&mut handler, &command));
+        }
+        handler.calls
+    });
+
+    let mut out = Vec::new();
+    let code = run_client(&path, &ControlCommand::Reload, &mut out).unwrap();
+    assert_eq!(code, 0);
+    assert_eq!(String::from_utf8(out).unwrap(), "{\"ok\":true}\n");
+
+    // A failing handler is reported, and the client exits non-zero
+    let mut out = Vec::new();
+    let code = run_client(
+        &path,
+        &ControlCommand::ToggleBar("HDMI-A-9".to_string()),
+        &mut out,
+    )
+    .unwrap();
+    assert_eq!(code, 1);
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "{\"error\":\"no bar on 'HDMI-A-9'\",\"ok\":false}\n"
+    );
+
+    drop(server);
+    assert_eq!(handler.join().unwrap(), vec!["reload"]);
+}
+
+#[test]
+fn test_control_socket_path() {
+    assert_eq!(
+        control_socket_path(Some(Path::new("/run/user/1000"))),
+        Some(PathBuf::from("/run/user/1000").join(CONTROL_SOCKET_NAME))
+    );
+    assert_eq!(control_socket_path(Some(Path::new(""))), None);
+    assert_eq!(control_socket_path(None), None);
+}
@@ -1,6 +1,12 @@
-use niri_bar::config::LoggingConfig;
-use niri_bar::logger::NiriBarLogger;
-use tempfile::NamedTempFile;
+use log::LevelFilter;
+use log::Log;
+use niri_bar::config::{LoggingConfig, load_logging_config};
+use niri_bar::logger::{
+    LogSinks, NiriBarLogger, encode_journal_fields, journal_priority, level_for_class, log_class,
+    max_level_filter, rotate_log_files, rotated_log_path,
+};
+use std::sync::{Mutex, MutexGuard, Once};
+use tempfile::{NamedTempFile, TempDir};
 
 #[test]
 fn test_logger_initialization() {
@@ -12,6 +18,10 @@ fn test_logger_initialization() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let result = NiriBarLogger::new(config);
@@ -29,6 +39,10 @@ fn test_logger_with_file_output() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let _logger = NiriBarLogger::new(config).unwrap();
@@ -47,6 +61,10 @@ fn test_logger_iso8601_format() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let logger = NiriBarLogger::new(config).unwrap();
@@ -65,6 +83,10 @@ fn test_logger_simple_format() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let logger = NiriBarLogger::new(config).unwrap();
@@ -83,6 +105,10 @@ fn test_logger_level_filtering() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let logger = NiriBarLogger::new(config).unwrap();
@@ -101,6 +127,10 @@ fn test_logger_console_output() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let logger = NiriBarLogger::new(config).unwrap();
@@ -119,6 +149,10 @@ fn test_logger_file_output_disabled() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let _logger = NiriBarLogger::new(config).unwrap();
@@ -137,6 +171,10 @@ fn test_logger_include_file_option() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let logger = NiriBarLogger::new(config).unwrap();
@@ -155,6 +193,10 @@ fn test_logger_include_line_option() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let logger = NiriBarLogger::new(config).unwrap();
@@ -173,6 +215,10 @@ fn test_logger_include_class_option() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let logger = NiriBarLogger::new(config).unwrap();
@@ -194,6 +240,10 @@ fn test_logger_file_creation() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let _logger = NiriBarLogger::new(config).unwrap();
@@ -216,6 +266,10 @@ fn test_logger_directory_creation() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let _logger = NiriBarLogger::new(config).unwrap();
@@ -235,6 +289,10 @@ fn test_logger_default_values() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let logger = NiriBarLogger::new(config).unwrap();
@@ -258,6 +316,10 @@ fn test_logger_error_level() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let logger = NiriBarLogger::new(config).unwrap();
@@ -276,6 +338,10 @@ fn test_logger_trace_level() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let logger = NiriBarLogger::new(config).unwrap();
@@ -294,6 +360,10 @@ fn test_logger_invalid_level_handling() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let logger = NiriBarLogger::new(config).unwrap();
@@ -313,6 +383,10 @@ fn test_logger_file_path_expansion() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let logger = NiriBarLogger::new(config).unwrap();
@@ -331,6 +405,10 @@ fn test_logger_configuration_validation() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let logger = NiriBarLogger::new(config).unwrap();
@@ -354,6 +432,10 @@ fn test_logger_performance() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let _logger = NiriBarLogger::new(config.clone()).unwrap();
@@ -378,6 +460,10 @@ fn test_logger_creation_from_src() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let logger = NiriBarLogger::new(config).unwrap();
@@ -396,6 +482,10 @@ fn test_logger_with_file_from_src() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let logger = NiriBarLogger::new(config).unwrap();
@@ -416,6 +506,10 @@ fn test_logger_creation_edge_cases() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let logger = NiriBarLogger::new(config).unwrap();
@@ -430,6 +524,10 @@ fn test_logger_creation_edge_cases() {
         include_file: false,
         include_line: false,
         include_class: false,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let logger = NiriBarLogger::new(config_with_tilde).unwrap();
@@ -447,6 +545,10 @@ fn test_logger_file_creation_error_handling() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     // This should fail because we can't create the directory
@@ -501,6 +603,10 @@ fn test_logger_format_variations() {
             include_file: true,
             include_line: true,
             include_class: true,
+            max_size_bytes: 10 * 1024 * 1024,
+            max_files: 5,
+            module_levels: Default::default(),
+            journald: false,
         };
 
         let logger = NiriBarLogger::new(config).unwrap();
@@ -526,6 +632,10 @@ fn test_logger_configuration_flags() {
             include_file,
             include_line,
             include_class,
+            max_size_bytes: 10 * 1024 * 1024,
+            max_files: 5,
+            module_levels: Default::default(),
+            journald: false,
         };
 
         let logger = NiriBarLogger::new(config.clone()).unwrap();
@@ -553,6 +663,10 @@ fn test_logger_thread_safety() {
             include_file: true,
             include_line: true,
             include_class: true,
+            max_size_bytes: 10 * 1024 * 1024,
+            max_files: 5,
+            module_levels: Default::default(),
+            journald: false,
         })
         .unwrap(),
     ));
@@ -595,6 +709,10 @@ fn test_logger_file_handle_sharing() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let config2 = LoggingConfig {
@@ -605,6 +723,10 @@ fn test_logger_file_handle_sharing() {
         include_file: false,
         include_line: false,
         include_class: false,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     let logger1 = NiriBarLogger::new(config1).unwrap();
@@ -630,6 +752,10 @@ fn test_logger_resource_cleanup() {
         include_file: true,
         include_line: true,
         include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
     };
 
     {
@@ -644,6 +770,437 @@ fn test_logger_resource_cleanup() {
     assert!(temp_file.path().exists());
 }
 
+fn log_info(logger: &NiriBarLogger, message: &str) {
+    logger.log(
+        &log::Record::builder()
+            .level(log::Level::Info)
+            .args(format_args!("{}", message))
+            .build(),
+    );
+}
+
+#[test]
+fn test_logger_rotates_past_max_size() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("niri-bar.log");
+    let config = LoggingConfig {
+        level: "info".to_string(),
+        file: path.to_string_lossy().to_string(),
+        console: false,
+        format: "simple".to_string(),
+        include_file: false,
+        include_line: false,
+        include_class: false,
+        max_size_bytes: 200,
+        max_files: 2,
+        module_levels: Default::default(),
+        journald: false,
+    };
+    let logger = NiriBarLogger::new(config).unwrap();
+
+    // Each line is ~80 bytes, so every third line pushes the file past 200 bytes
+    for i in 0..12 {
+        log_info(&logger, &format!("message {:02} {}", i, "x".repeat(40)));
+    }
+
+    let read = |p: &std::path::Path| std::fs::read_to_string(p).unwrap();
+    // The live file restarted after the last rotation and stays under the limit
+    assert!(std::fs::metadata(&path).unwrap().len() <= 200);
+    // Newest archive in .1, older in .2, anything older pruned
+    assert!(read(&rotated_log_path(&path, 1)).contains("message 11"));
+    assert!(read(&rotated_log_path(&path, 2)).contains("message 08"));
+    assert!(!rotated_log_path(&path, 3).exists());
+    assert!(!read(&rotated_log_path(&path, 2)).contains("message 00"));
+}
+
+#[test]
+fn test_logger_rotation_disabled() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("niri-bar.log");
+    let config = LoggingConfig {
+        level: "info".to_string(),
+        file: path.to_string_lossy().to_string(),
+        console: false,
+        format: "simple".to_string(),
+        include_file: false,
+        include_line: false,
+        include_class: false,
+        max_size_bytes: 0,
+        max_files: 2,
+        module_levels: Default::default(),
+        journald: false,
+    };
+    let logger = NiriBarLogger::new(config).unwrap();
+    for i in 0..20 {
+        log_info(&logger, &format!("message {}", i));
+    }
+    assert!(!rotated_log_path(&path, 1).exists());
+    assert!(
+        std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("message 19")
+    );
+}
+
+#[test]
+fn test_rotate_log_files_shifts_and_prunes() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("bar.log");
+    assert_eq!(rotated_log_path(&path, 3), dir.path().join("bar.log.3"));
+
+    // Archives beyond the limit, e.g. from an older larger max_files, go too
+    for (p, content) in [
+        (path.clone(), "live"),
+        (rotated_log_path(&path, 1), "one"),
+        (rotated_log_path(&path, 2), "two"),
+        (rotated_log_path(&path, 3), "three"),
+        (rotated_log_path(&path, 4), "four"),
+    ] {
+        std::fs::write(p, content).unwrap();
+    }
+    rotate_log_files(&path, 3).unwrap();
+
+    let read = |n| std::fs::read_to_string(rotated_log_path(&path, n)).unwrap();
+    assert!(!path.exists());
+    assert_eq!(
+        (read(1), read(2), read(3)),
+        ("live".into(), "one".into(), "two".into())
+    );
+    assert!(!rotated_log_path(&path, 4).exists());
+
+    // Keeping no archives just drops the log
+    std::fs::write(&path, "live").unwrap();
+    rotate_log_files(&path, 0).unwrap();
+    assert!(!path.exists());
+    assert!(!rotated_log_path(&path, 1).exists());
+}
+
+fn module_levels_config(file: &str) -> LoggingConfig {
+    LoggingConfig {
+        level: "info".to_string(),
+        file: file.to_string(),
+        console: false,
+        format: "simple".to_string(),
+        include_file: false,
+        include_line: false,
+        include_class: false,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: [
+            ("Workspaces", "debug"),
+            ("Bar", "warn"),
+            ("Niri IPC", "trace"),
+            ("zbus", "error"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect(),
+        journald: false,
+    }
+}
+
+#[test]
+fn test_log_class_extraction() {
+    // Message prefix for the crate's own lines
+    assert_eq!(
+        log_class("niri_bar::modules::workspaces", "Workspaces: 🔄 refresh"),
+        Some("Workspaces")
+    );
+    assert_eq!(
+        log_class("niri_bar::niri", "Niri IPC: connected"),
+        Some("Niri IPC")
+    );
+    // An explicit target wins, including other crates
+    assert_eq!(log_class("Bar", "Workspaces: x"), Some("Bar"));
+    assert_eq!(log_class("zbus", "connection: closed"), Some("zbus"));
+    // Not a class prefix
+    assert_eq!(log_class("niri_bar", "no prefix here"), None);
+    assert_eq!(log_class("niri_bar", "path /tmp/a: missing"), None);
+    assert_eq!(log_class("niri_bar", ": empty"), None);
+}
+
+#[test]
+fn test_module_level_resolution() {
+    let config = module_levels_config("");
+    for (class, expected) in [
+        (Some("Workspaces"), LevelFilter::Debug),
+        (Some("workspaces"), LevelFilter::Debug),
+        (Some("Bar"), LevelFilter::Warn),
+        (Some("Niri IPC"), LevelFilter::Trace),
+        (Some("zbus"), LevelFilter::Error),
+        // No override: the global level
+        (Some("Clock"), LevelFilter::Info),
+        (Some("Bar Extra"), LevelFilter::Info),
+        (None, LevelFilter::Info),
+    ] {
+        assert_eq!(level_for_class(&config, class), expected, "{:?}", class);
+    }
+    assert_eq!(max_level_filter(&config), LevelFilter::Trace);
+
+    let mut global_only = module_levels_config("");
+    global_only.module_levels.clear();
+    global_only.level = "warn".to_string();
+    assert_eq!(max_level_filter(&global_only), LevelFilter::Warn);
+    assert_eq!(
+        level_for_class(&global_only, Some("Workspaces")),
+        LevelFilter::Warn
+    );
+}
+
+#[test]
+fn test_logger_applies_module_levels() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("niri-bar.log");
+    let logger = NiriBarLogger::new(module_levels_config(&path.to_string_lossy())).unwrap();
+
+    let write = |level: log::Level, target: &str, message: &str| {
+        logger.log(
+            &log::Record::builder()
+                .level(level)
+                .target(target)
+                .args(format_args!("{}", message))
+                .build(),
+        );
+    };
+    write(
+        log::Level::Debug,
+        "niri_bar::modules::workspaces",
+        "Workspaces: kept debug",
+    );
+    write(log::Level::Info, "niri_bar::bar", "Bar: dropped info");
+    write(log::Level::Warn, "niri_bar::bar", "Bar: kept warn");
+    write(
+        log::Level::Debug,
+        "niri_bar::modules::clock",
+        "Clock: dropped debug",
+    );
+    write(
+        log::Level::Info,
+        "niri_bar::modules::clock",
+        "Clock: kept info",
+    );
+    write(log::Level::Warn, "zbus", "dropped warn");
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let kept: Vec<&str> = contents
+        .lines()
+        .filter_map(|line| line.split("] ").last())
+        .collect();
+    assert_eq!(
+        kept,
+        vec![
+            "Workspaces: kept debug",
+            "Bar: kept warn",
+            "Clock: kept info"
+        ]
+    );
+}
+
+#[test]
+fn test_logger_json_format_fields() {
+    for include_file in [false, true] {
+        for include_line in [false, true] {
+            for include_class in [false, true] {
+                let dir = TempDir::new().unwrap();
+                let path = dir.path().join("niri-bar.log");
+                let config = LoggingConfig {
+                    level: "info".to_string(),
+                    file: path.to_string_lossy().to_string(),
+                    console: false,
+                    format: "json".to_string(),
+                    include_file,
+                    include_line,
+                    include_class,
+                    max_size_bytes: 10 * 1024 * 1024,
+                    max_files: 5,
+                    module_levels: Default::default(),
+                    journald: false,
+                };
+                let logger = NiriBarLogger::new(config).unwrap();
+                logger.log(
+                    &log::Record::builder()
+                        .level(log::Level::Warn)
+                        .target("niri_bar::bar")
+                        .file(Some("src/bar.rs"))
+                        .line(Some(42))
+                        .args(format_args!("Bar: \"quoted\" message"))
+                        .build(),
+                );
+
+                let contents = std::fs::read_to_string(&path).unwrap();
+                assert_eq!(contents.lines().count(), 1);
+                let line: serde_json::Value = serde_json::from_str(contents.trim()).unwrap();
+                let mut keys: Vec<&str> = line
+                    .as_object()
+                    .unwrap()
+                    .keys()
+                    .map(String::as_str)
+                    .collect();
+                keys.sort();
+
+                let mut expected = vec!["level", "message", "ts"];
+                if include_class {
+                    expected.push("target");
+                    assert_eq!(line["target"], "niri_bar::bar");
+                }
+                if include_file {
+                    expected.push("file");
+                    assert_eq!(line["file"], "src/bar.rs");
+                }
+                if include_line {
+                    expected.push("line");
+                    assert_eq!(line["line"], 42);
+                }
+                expected.sort();
+                assert_eq!(
+                    keys, expected,
+                    "file={} line={} class={}",
+                    include_file, include_line, include_class
+                );
+                assert_eq!(line["level"], "WARN");
+                assert_eq!(line["message"], "Bar: \"quoted\" message");
+                assert!(line["ts"].as_str().unwrap().ends_with('Z'));
+            }
+        }
+    }
+}
+
+fn journald_config(file: &str, console: bool, journald: bool) -> LoggingConfig {
+    LoggingConfig {
+        level: "debug".to_string(),
+        file: file.to_string(),
+        console,
+        format: "simple".to_string(),
+        include_file: true,
+        include_line: true,
+        include_class: true,
+        max_size_bytes: 10 * 1024 * 1024,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald,
+    }
+}
+
+#[test]
+fn test_journal_priority_mapping() {
+    assert_eq!(journal_priority(log::Level::Error), 3);
+    assert_eq!(journal_priority(log::Level::Warn), 4);
+    assert_eq!(journal_priority(log::Level::Info), 6);
+    assert_eq!(journal_priority(log::Level::Debug), 7);
+    assert_eq!(journal_priority(log::Level::Trace), 7);
+}
+
+#[test]
+fn test_log_sink_selection() {
+    let sinks = |file: &str, console, journald, available| {
+        LogSinks::select(&journald_config(file, console, journald), available)
+    };
+    // The journal joins the other outputs instead of replacing them
+    assert_eq!(
+        sinks("/tmp/bar.log", true, true, true),
+        LogSinks {
+            console: true,
+            file: true,
+            journal: true
+        }
+    );
+    // Not under systemd: everything else still works
+    assert_eq!(
+        sinks("/tmp/bar.log", true, true, false),
+        LogSinks {
+            console: true,
+            file: true,
+            journal: false
+        }
+    );
+    // Off unless asked for
+    assert!(!sinks("", false, false, true).journal);
+    assert_eq!(
+        sinks("", false, true, true),
+        LogSinks {
+            console: false,
+            file: false,
+            journal: true
+        }
+    );
+}
+
+#[test]
+fn test_encode_journal_fields() {
+    assert_eq!(
+        encode_journal_fields(&[("MESSAGE", "hello"), ("PRIORITY", "6")]),
+        b"MESSAGE=hello\nPRIORITY=6\n".to_vec()
+    );
+    // Multi-line values use the length-prefixed form
+    let mut expected = b"MESSAGE\n".to_vec();
+    expected.extend_from_slice(&5u64.to_le_bytes());
+    expected.extend_from_slice(b"a\nb c\n");
+    assert_eq!(encode_journal_fields(&[("MESSAGE", "a\nb c")]), expected);
+}
+
+#[test]
+fn test_logger_sends_to_journal() {
+    let dir = TempDir::new().unwrap();
+    let socket_path = dir.path().join("journal.socket");
+    let journal = std::os::unix::net::UnixDatagram::bind(&socket_path).unwrap();
+    let log_path = dir.path().join("niri-bar.log");
+
+    let logger = NiriBarLogger::with_journal_socket(
+        journald_config(&log_path.to_string_lossy(), false, true),
+        &socket_path,
+    )
+    .unwrap();
+    assert!(logger.logs_to_journal());
+    logger.log(
+        &log::Record::builder()
+            .level(log::Level::Warn)
+            .target("niri_bar::bar")
+            .file(Some("src/bar.rs"))
+            .line(Some(7))
+            .args(format_args!("Bar: hidden"))
+            .build(),
+    );
+
+    let mut buf = [0u8; 1024];
+    let len = journal.recv(&mut buf).unwrap();
+    let entry = String::from_utf8_lossy(&buf[..len]).into_owned();
+    assert_eq!(
+        entry.lines().collect::<Vec<_>>(),
+        vec![
+            "MESSAGE=Bar: hidden",
+            "PRIORITY=4",
+            "SYSLOG_IDENTIFIER=niri-bar",
+            "TARGET=niri_bar::bar",
+            "CODE_FILE=src/bar.rs",
+            "CODE_LINE=7",
+        ]
+    );
+    // The file output keeps working alongside
+    assert!(
+        std::fs::read_to_string(&log_path)
+            .unwrap()
+            .contains("Bar: hidden")
+    );
+}
+
+#[test]
+fn test_logger_without_journal_degrades() {
+    let dir = TempDir::new().unwrap();
+    let log_path = dir.path().join("niri-bar.log");
+    let logger = NiriBarLogger::with_journal_socket(
+        journald_config(&log_path.to_string_lossy(), false, true),
+        &dir.path().join("no-journal.socket"),
+    )
+    .unwrap();
+    assert!(!logger.logs_to_journal());
+    log_info(&logger, "still logged");
+    assert!(
+        std::fs::read_to_string(&log_path)
+            .unwrap()
+            .contains("still logged")
+    );
+}
+
 // ===== PROPERTY-BASED TESTS =====
 
 #[cfg(test)]
@@ -669,6 +1226,10 @@ mod property_tests {
                 include_file,
                 include_line,
                 include_class,
+                max_size_bytes: 10 * 1024 * 1024,
+                max_files: 5,
+                module_levels: Default::default(),
+                journald: false,
             };
 
             let logger = NiriBarLogger::new(config);
@@ -690,6 +1251,10 @@ mod property_tests {
                 include_file,
                 include_line,
                 include_class,
+                max_size_bytes: 10 * 1024 * 1024,
+                max_files: 5,
+                module_levels: Default::default(),
+                journald: false,
             };
 
             let logger = NiriBarLogger::new(config.clone()).unwrap();
@@ -700,3 +1265,139 @@ mod property_tests {
         }
     }
 }
+
+fn quiet_config(file: &std::path::Path) -> LoggingConfig {
+    LoggingConfig {
+        level: "info".to_string(),
+        file: file.to_string_lossy().to_string(),
+        console: false,
+        format: "simple".to_string(),
+        include_file: false,
+        include_line: false,
+        include_class: false,
+        max_size_bytes: 0,
+        max_files: 5,
+        module_levels: Default::default(),
+        journald: false,
+    }
+}
+
+/// Tests driving the global logger take turns; it's installed once per test binary
+fn global_logger_guard() -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    static INIT: Once = Once::new();
+    let guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    INIT.call_once(|| {
+        NiriBarLogger::init(LoggingConfig {
+            file: String::new(),
+            ..quiet_config(std::path::Path::new(""))
+        })
+        .unwrap()
+    });
+    guard
+}
+
+#[test]
+fn test_reconfigure_applies_rotation_limits() {
+    let _guard = global_logger_guard();
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("niri-bar.log");
+
+    let mut config = quiet_config(&path);
+    assert!(NiriBarLogger::reconfigure(config.clone()).unwrap());
+    // The same config again has nothing to change
+    assert!(!NiriBarLogger::reconfigure(config.clone()).unwrap());
+
+    config.max_size_bytes = 200;
+    config.max_files = 1;
+    assert!(NiriBarLogger::reconfigure(config).unwrap());
+    for i in 0..12 {
+        log::info!("Reconfigure: message {:02} {}", i, "x".repeat(40));
+    }
+
+    assert!(std::fs::metadata(&path).unwrap().len() <= 200);
+    assert!(rotated_log_path(&path, 1).exists());
+    assert!(!rotated_log_path(&path, 2).exists());
+}
+
+#[test]
+fn test_reconfigure_applies_module_levels() {
+    let _guard = global_logger_guard();
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("niri-bar.log");
+
+    let mut config = quiet_config(&path);
+    config.module_levels = [("Workspaces".to_string(), "debug".to_string())].into();
+    NiriBarLogger::reconfigure(config).unwrap();
+    // The crate's own lines are classed by their message prefix
+    log::debug!(target: "niri_bar::modules::workspaces", "Workspaces: kept debug");
+    log::debug!(target: "niri_bar::modules::clock", "Clock: dropped debug");
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    assert!(contents.contains("Workspaces: kept debug"));
+    assert!(!contents.contains("Clock: dropped debug"));
+}
+
+/// A config file at `dir/niri-bar.yaml` with the given `logging` section
+fn write_config(dir: &std::path::Path, logging: &str) -> std::path::PathBuf {
+    let path = dir.join("niri-bar.yaml");
+    std::fs::write(
+        &path,
+        format!(
+            "application:\n  modules: {{}}\n  layouts: {{}}\n  monitors: []\nlogging:\n{}",
+            logging
+        ),
+    )
+    .unwrap();
+    path
+}
+
+#[test]
+fn test_loaded_json_format_reaches_the_logger() {
+    let _guard = global_logger_guard();
+    let dir = TempDir::new().unwrap();
+    let log_path = dir.path().join("niri-bar.log");
+    let config_path = write_config(
+        dir.path(),
+        &format!(
+            "  level: info\n  file: {:?}\n  console: false\n  format: json\n",
+            log_path.to_string_lossy()
+        ),
+    );
+
+    let logging = load_logging_config(&config_path).unwrap();
+    assert_eq!(logging.format, "json");
+    NiriBarLogger::reconfigure(logging).unwrap();
+    log::info!("Reconfigure: as json");
+
+    let contents = std::fs::read_to_string(&log_path).unwrap();
+    let line: serde_json::Value = serde_json::from_str(contents.lines().last().unwrap()).unwrap();
+    assert_eq!(line["message"], "Reconfigure: as json");
+    assert_eq!(line["level"], "INFO");
+}
+
+#[test]
+fn test_loaded_journald_reaches_the_logger() {
+    let _guard = global_logger_guard();
+    let dir = TempDir::new().unwrap();
+    let socket_path = dir.path().join("journal.socket");
+    let journal = std::os::unix::net::UnixDatagram::bind(&socket_path).unwrap();
+    let config_path = write_config(
+        dir.path(),
+        "  level: info\n  file: \"\"\n  console: false\n  journald: true\n",
+    );
+
+    let logging = load_logging_config(&config_path).unwrap();
+    assert!(logging.journald);
+    assert!(NiriBarLogger::reconfigure_with_journal_socket(logging, &socket_path).unwrap());
+    log::warn!(target: "niri_bar::bar", "Bar: to the journal");
+
+    let mut buf = [0u8; 1024];
+    let len = journal.recv(&mut buf).unwrap();
+    let entry = String::from_utf8_lossy(&buf[..len]).into_owned();
+    assert!(
+        entry
+            .lines()
+            .any(|line| line == "MESSAGE=Bar: to the journal")
+    );
+}
@@ -1,5 +1,8 @@
 use niri_bar::config::{DisplayMode, ModuleConfig};
-use niri_bar::modules::{self, battery, clock, tray, window_title, workspaces};
+use niri_bar::modules::{
+    self, battery, brightness, clock, idle, keyboard, label, mem, notifications, overview,
+    power_profile, script, temp, tray, volume, wallpaper, window_title, workspaces,
+};
 use pretty_assertions::assert_eq;
 
 // ===== MODULE REGISTRY TESTS =====
@@ -64,6 +67,175 @@ fn test_module_registry_case_sensitivity() {
     );
 }
 
+// ===== CLICK ACTION TESTS =====
+
+#[derive(Default)]
+struct FakeClickRunner {
+    spawned: std::cell::RefCell<Vec<String>>,
+}
+
+impl modules::ClickRunner for FakeClickRunner {
+    fn spawn(&self, command: &str) {
+        self.spawned.borrow_mut().push(command.to_string());
+    }
+}
+
+#[test]
+fn test_click_actions_dispatch_per_button() {
+    use modules::{ClickButton, dispatch_click};
+
+    let settings: ModuleConfig =
+        serde_yaml::from_str("on_click: gnome-calendar\non_click_right: \"notify-send hi\"\n")
+            .unwrap();
+    let runner = FakeClickRunner::default();
+
+    assert!(dispatch_click(&runner, &settings, ClickButton::Left));
+    assert!(dispatch_click(&runner, &settings, ClickButton::Right));
+    // No middle command configured
+    assert!(!dispatch_click(&runner, &settings, ClickButton::Middle));
+    assert_eq!(
+        *runner.spawned.borrow(),
+        vec!["gnome-calendar".to_string(), "notify-send hi".to_string()]
+    );
+
+    // Blank commands are treated as unset
+    let blank = ModuleConfig {
+        on_click: Some("   ".to_string()),
+        ..Default::default()
+    };
+    assert!(!dispatch_click(&runner, &blank, ClickButton::Left));
+    assert_eq!(runner.spawned.borrow().len(), 2);
+}
+
+#[test]
+fn test_click_button_from_gdk() {
+    use modules::ClickButton;
+
+    assert_eq!(ClickButton::from_gdk(1), Some(ClickButton::Left));
+    assert_eq!(ClickButton::from_gdk(2), Some(ClickButton::Middle));
+    assert_eq!(ClickButton::from_gdk(3), Some(ClickButton::Right));
+    assert_eq!(ClickButton::from_gdk(8), None);
+}
+
+#[test]
+fn test_scroll_throttle_gate() {
+    use modules::ScrollThrottle;
+    use std::time::{Duration, Instant};
+
+    let t0 = Instant::now();
+    let mut throttle = ScrollThrottle::new(50);
+    // Negligible movement never passes and doesn't start the window
+    assert!(!throttle.accept(0.05, t0));
+    assert!(throttle.accept(1.0, t0));
+    // Inside the window
+    assert!(!throttle.accept(-1.0, t0 + Duration::from_millis(20)));
+    assert!(!throttle.accept(1.0, t0 + Duration::from_millis(49)));
+    // Window elapsed; accepted event restarts it
+    assert!(throttle.accept(1.0, t0 + Duration::from_millis(50)));
+    assert!(!throttle.accept(1.0, t0 + Duration::from_millis(80)));
+    assert!(throttle.accept(-1.0, t0 + Duration::from_millis(100)));
+
+    // Zero throttle only filters small movements
+    let mut unthrottled = ScrollThrottle::new(0);
+    assert!(unthrottled.accept(1.0, t0));
+    assert!(unthrottled.accept(1.0, t0));
+    assert!(!unthrottled.accept(0.1, t0));
+}
+
+#[test]
+fn test_timer_hub_tick_bucketing() {
+    use modules::{TickSchedule, tick_schedule};
+    use std::time::Duration;
+
+    let schedule = |ms| tick_schedule(Duration::from_millis(ms));
+    let on = |tick, every| TickSchedule {
+        tick: Duration::from_secs(tick),
+        every,
+    };
+    // Exact ticks
+    assert_eq!(schedule(1000), on(1, 1));
+    assert_eq!(schedule(5000), on(5, 1));
+    assert_eq!(schedule(30000), on(30, 1));
+    // Multiples ride the slowest tick that divides them
+    assert_eq!(schedule(2000), on(1, 2));
+    assert_eq!(schedule(10000), on(5, 2));
+    assert_eq!(schedule(60000), on(30, 2));
+    assert_eq!(schedule(45000), on(5, 9));
+    assert_eq!(schedule(7000), on(1, 7));
+    // Rounded to whole seconds, never faster than the 1s tick
+    assert_eq!(schedule(100), on(1, 1));
+    assert_eq!(schedule(0), on(1, 1));
+    assert_eq!(schedule(1499), on(1, 1));
+    assert_eq!(schedule(29600), on(30, 1));
+    assert_eq!(schedule(60000).interval(), Duration::from_secs(60));
+}
+
+#[test]
+fn test_timer_hub_runs_due_subscribers() {
+    use modules::TimerHub;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    let second = Duration::from_secs(1);
+    let five = Duration::from_secs(5);
+    let fired = Rc::new(RefCell::new(Vec::new()));
+    let mut hub = TimerHub::default();
+    for (name, ms) in [("clock", 1000), ("mem", 2000), ("battery", 10000)] {
+        let fired = fired.clone();
+        hub.subscribe(Duration::from_millis(ms), move || {
+            fired.borrow_mut().push(name);
+            glib::ControlFlow::Continue
+        });
+    }
+    // Stops itself after its second refresh, like a module whose widget went away
+    let mut runs = 0;
+    hub.subscribe(second, move || {
+        runs += 1;
+        if runs < 2 {
+            glib::ControlFlow::Continue
+        } else {
+            glib::ControlFlow::Break
+        }
+    });
+    assert_eq!(hub.subscribers(second), 3);
+    assert_eq!(hub.subscribers(five), 1);
+
+    assert_eq!(hub.fire(second), 3);
+    assert_eq!(*fired.borrow(), vec!["clock"]);
+    assert_eq!(hub.fire(second), 2);
+    assert_eq!(*fired.borrow(), vec!["clock", "clock", "mem"]);
+
+    // The 5s tick serves the 10s refresh on every other firing
+    fired.borrow_mut().clear();
+    hub.fire(five);
+    assert!(fired.borrow().is_empty());
+    hub.fire(five);
+    assert_eq!(*fired.borrow(), vec!["battery"]);
+    assert_eq!(hub.subscribers(Duration::from_secs(30)), 0);
+}
+
+#[test]
+fn test_scroll_actions_dispatch_per_direction() {
+    use modules::{dispatch_scroll, scroll_throttle_ms};
+
+    let settings: ModuleConfig = serde_yaml::from_str(
+        "on_scroll_up: \"pactl set-sink-volume @DEFAULT_SINK@ +5%\"\nscroll_throttle_ms: 120\n",
+    )
+    .unwrap();
+    let runner = FakeClickRunner::default();
+
+    assert!(dispatch_scroll(&runner, &settings, -1.0));
+    // No scroll-down command configured
+    assert!(!dispatch_scroll(&runner, &settings, 1.0));
+    assert_eq!(
+        *runner.spawned.borrow(),
+        vec!["pactl set-sink-volume @DEFAULT_SINK@ +5%".to_string()]
+    );
+    assert_eq!(scroll_throttle_ms(&settings), 120);
+    assert_eq!(scroll_throttle_ms(&ModuleConfig::default()), 50);
+}
+
 // ===== CLOCK MODULE TESTS =====
 
 #[test]
@@ -122,101 +294,1768 @@ fn test_clock_module_config_variations() {
     }
 }
 
+#[test]
+fn test_clock_timezone_parsing() {
+    use clock::{ClockZone, parse_timezone, parse_timezones};
+
+    let settings: ModuleConfig =
+        serde_yaml::from_str("timezones: [America/New_York, Not/AZone, Asia/Tokyo, local]")
+            .unwrap();
+    assert_eq!(
+        parse_timezones(&settings),
+        vec![
+            ClockZone::Named(chrono_tz::America::New_York),
+            // Invalid names fall back to local time
+            ClockZone::Local,
+            ClockZone::Named(chrono_tz::Asia::Tokyo),
+            ClockZone::Local,
+        ]
+    );
+
+    // Single name and unset
+    let single: ModuleConfig = serde_yaml::from_str("timezones: Europe/Berlin").unwrap();
+    assert_eq!(
+        parse_timezones(&single),
+        vec![ClockZone::Named(chrono_tz::Europe::Berlin)]
+    );
+    assert_eq!(
+        parse_timezones(&ModuleConfig::default()),
+        vec![ClockZone::Local]
+    );
+    assert_eq!(parse_timezone("LOCAL"), ClockZone::Local);
+}
+
+#[test]
+fn test_clock_timezone_cycling_order() {
+    use clock::cycle_zone_index;
+
+    // Scroll down walks forward and wraps
+    assert_eq!(cycle_zone_index(0, 3, 1.0), 1);
+    assert_eq!(cycle_zone_index(1, 3, 1.0), 2);
+    assert_eq!(cycle_zone_index(2, 3, 1.0), 0);
+    // Scroll up walks backward and wraps
+    assert_eq!(cycle_zone_index(0, 3, -1.0), 2);
+    assert_eq!(cycle_zone_index(2, 3, -1.0), 1);
+    // Degenerate lists stay put
+    assert_eq!(cycle_zone_index(0, 1, 1.0), 0);
+    assert_eq!(cycle_zone_index(0, 0, 1.0), 0);
+}
+
+#[test]
+fn test_clock_render_in_zone() {
+    use chrono::TimeZone;
+    use clock::{ClockZone, render_clock};
+
+    let now = chrono::Utc.with_ymd_and_hms(2025, 1, 15, 12, 0, 0).unwrap();
+    let (text, tooltip) = render_clock(now, ClockZone::Named(chrono_tz::Asia::Tokyo), "%H:%M");
+    assert_eq!(text, "21:00");
+    assert_eq!(tooltip, "Asia/Tokyo (JST)");
+
+    let (text, tooltip) =
+        render_clock(now, ClockZone::Named(chrono_tz::America::New_York), "%H:%M");
+    assert_eq!(text, "07:00");
+    assert_eq!(tooltip, "America/New_York (EST)");
+
+    let (_, tooltip) = render_clock(now, ClockZone::Local, "%H:%M");
+    assert!(tooltip.starts_with("Local ("));
+}
+
+#[test]
+fn test_clock_localized_names() {
+    use chrono::TimeZone;
+    use clock::{ClockZone, parse_locale, render_clock_localized};
+
+    // A Monday in March
+    let now = chrono::Utc.with_ymd_and_hms(2024, 3, 4, 12, 0, 0).unwrap();
+    let zone = ClockZone::Named(chrono_tz::UTC);
+    let render = |name: &str| {
+        let locale = parse_locale(name).unwrap();
+        render_clock_localized(now, zone, "%A %d %B", locale).0
+    };
+    assert_eq!(render("de_DE"), "Montag 04 März");
+    assert_eq!(render("fr_FR"), "lundi 04 mars");
+    // Encoding and modifier suffixes, and dashes, are accepted
+    assert_eq!(render("de_DE.UTF-8@euro"), "Montag 04 März");
+    assert_eq!(render("fr-FR"), "lundi 04 mars");
+    assert_eq!(render("C"), "Monday 04 March");
+    assert_eq!(render("POSIX.UTF-8"), "Monday 04 March");
+    assert!(parse_locale("xx_YY").is_none());
+}
+
+#[test]
+fn test_clock_locale_fallbacks() {
+    use chrono::TimeZone;
+    use clock::{ClockZone, render_clock_localized, resolve_locale};
+
+    let now = chrono::Utc.with_ymd_and_hms(2024, 3, 4, 12, 0, 0).unwrap();
+    let zone = ClockZone::Named(chrono_tz::UTC);
+    let render = |locale| render_clock_localized(now, zone, "%a %b", locale).0;
+    let env = |vars: &'static [(&'static str, &'static str)]| {
+        move |name: &str| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        }
+    };
+
+    // The configured locale wins over the environment
+    let locale = resolve_locale(Some("fr_FR"), env(&[("LC_TIME", "de_DE.UTF-8")]));
+    assert_eq!(render(locale), "lun. mars");
+    // Unset: the system time locale, honouring LC_ALL over LC_TIME over LANG
+    let locale = resolve_locale(None, env(&[("LC_TIME", "de_DE.UTF-8")]));
+    assert_eq!(render(locale), "Mo Mär");
+    let locale = resolve_locale(
+        None,
+        env(&[("LC_ALL", "fr_FR.UTF-8"), ("LC_TIME", "de_DE.UTF-8")]),
+    );
+    assert_eq!(render(locale), "lun. mars");
+    let locale = resolve_locale(None, env(&[("LC_ALL", ""), ("LANG", "de_DE.UTF-8")]));
+    assert_eq!(render(locale), "Mo Mär");
+    // Unknown or missing locales use the C locale
+    assert_eq!(render(resolve_locale(Some("xx_YY"), env(&[]))), "Mon Mar");
+    assert_eq!(
+        render(resolve_locale(None, env(&[("LC_TIME", "tlh_KL")]))),
+        "Mon Mar"
+    );
+    assert_eq!(render(resolve_locale(None, env(&[]))), "Mon Mar");
+}
+
+#[test]
+fn test_tooltip_placeholder_substitution() {
+    use modules::{escape_markup, fill_placeholders};
+
+    // Battery-style placeholders, as in its `format`
+    assert_eq!(
+        fill_placeholders(
+            "<b>{percent}%</b> {status} ({time} left)",
+            &[
+                ("percent", "42"),
+                ("status", "Discharging"),
+                ("time", "1:05")
+            ]
+        ),
+        "<b>42%</b> Discharging (1:05 left)"
+    );
+    // Values are escaped so titles can't break the markup; the template's own tags stay
+    assert_eq!(
+        fill_placeholders(
+            "<i>{app_id}</i>: {title}",
+            &[("title", "Q&A <draft> \"notes\""), ("app_id", "firefox")]
+        ),
+        "<i>firefox</i>: Q&amp;A &lt;draft&gt; &quot;notes&quot;"
+    );
+    // Repeated placeholders all expand; unknown ones are left alone
+    assert_eq!(
+        fill_placeholders("{title} / {title} {nope}", &[("title", "x")]),
+        "x / x {nope}"
+    );
+    assert_eq!(escape_markup("it's"), "it&apos;s");
+}
+
+#[test]
+fn test_tooltip_template_option() {
+    use modules::tooltip_template;
+
+    assert_eq!(tooltip_template(&ModuleConfig::default()), None);
+    let set: ModuleConfig = serde_yaml::from_str("tooltip_format: \"{title}\"").unwrap();
+    assert_eq!(tooltip_template(&set).as_deref(), Some("{title}"));
+    // `tooltip: false` wins over a format
+    let off: ModuleConfig =
+        serde_yaml::from_str("tooltip: false\ntooltip_format: \"{title}\"").unwrap();
+    assert_eq!(tooltip_template(&off), None);
+    let blank: ModuleConfig = serde_yaml::from_str("tooltip_format: \"  \"").unwrap();
+    assert_eq!(tooltip_template(&blank), None);
+}
+
+#[test]
+fn test_clock_tooltip_format_uses_clock_placeholders() {
+    use chrono::TimeZone;
+    use clock::{ClockZone, render_clock};
+
+    // The clock's tooltip_format is strftime, like its format
+    let now = chrono::Utc.with_ymd_and_hms(2025, 1, 15, 12, 0, 0).unwrap();
+    let (markup, _) = render_clock(
+        now,
+        ClockZone::Named(chrono_tz::Europe::Berlin),
+        "<b>%A</b> %d %B %Y, %Z",
+    );
+    assert_eq!(markup, "<b>Wednesday</b> 15 January 2025, CET");
+}
+
+#[test]
+fn test_clock_calendar_option() {
+    use clock::calendar_enabled;
+
+    assert!(!calendar_enabled(&ModuleConfig::default()));
+    let on: ModuleConfig = serde_yaml::from_str("calendar: true").unwrap();
+    assert!(calendar_enabled(&on));
+    let off: ModuleConfig = serde_yaml::from_str("calendar: false").unwrap();
+    assert!(!calendar_enabled(&off));
+    // Non-boolean values are ignored
+    let bad: ModuleConfig = serde_yaml::from_str("calendar: sometimes").unwrap();
+    assert!(!calendar_enabled(&bad));
+}
+
+#[test]
+fn test_clock_calendar_popover_smoke() {
+    use gtk4::prelude::*;
+
+    // Needs a display; skip when running headless
+    if gtk4::init().is_err() {
+        return;
+    }
+    let (popover, calendar) = clock::build_calendar_popover();
+    assert!(popover.autohides());
+    assert_eq!(popover.position(), gtk4::PositionType::Bottom);
+    assert_eq!(
+        popover.child().as_ref(),
+        Some(calendar.upcast_ref::<gtk4::Widget>())
+    );
+}
+
 #[test]
 fn test_clock_module_identity() {
     // Test that ClockModule has correct identifier
     assert_eq!(clock::ClockModule::IDENT, "bar.module.clock");
 }
 
-// ===== BATTERY MODULE TESTS =====
+// ===== BATTERY MODULE TESTS =====
+
+#[test]
+fn test_battery_module_config_parsing() {
+    // Test battery-specific configuration options
+    let yaml_config = r#"
+device: "BAT1"
+show_icon: true
+show_percentage: true
+pulse: false
+warn_threshold: 25
+critical_threshold: 15
+"#;
+
+    let config: ModuleConfig = serde_yaml::from_str(yaml_config).unwrap();
+
+    // Verify battery-specific settings are parsed
+    assert_eq!(
+        config.additional.get("device"),
+        Some(&serde_yaml::Value::String("BAT1".to_string()))
+    );
+    assert_eq!(
+        config.additional.get("show_icon"),
+        Some(&serde_yaml::Value::Bool(true))
+    );
+    assert_eq!(
+        config.additional.get("pulse"),
+        Some(&serde_yaml::Value::Bool(false))
+    );
+    assert_eq!(config.warn_threshold, Some(25));
+    assert_eq!(config.critical_threshold, Some(15));
+    assert_eq!(config.show_percentage, Some(true));
+}
+
+#[test]
+fn test_battery_module_defaults() {
+    let config = ModuleConfig::default();
+
+    // Test that battery module has reasonable defaults
+    assert!(
+        config.show_percentage.unwrap_or(true),
+        "Should show percentage by default"
+    );
+    assert!(config.warn_threshold.is_none() || config.warn_threshold.unwrap() > 0);
+    assert!(config.critical_threshold.is_none() || config.critical_threshold.unwrap() > 0);
+}
+
+#[test]
+fn test_battery_module_threshold_validation() {
+    let test_cases = vec![
+        (Some(10), Some(20), "warn < critical should be valid"),
+        (
+            Some(50),
+            Some(20),
+            "warn > critical should be valid (implementation dependent)",
+        ),
+        (Some(0), Some(10), "zero warn threshold should be valid"),
+        (Some(100), Some(10), "high warn threshold should be valid"),
+    ];
+
+    for (warn, critical, description) in test_cases {
+        let config = ModuleConfig {
+            warn_threshold: warn,
+            critical_threshold: critical,
+            ..Default::default()
+        };
+
+        // Test that config is valid
+        // Thresholds are u8, so always >= 0 by type definition
+        assert!(
+            config.warn_threshold.is_some() || config.warn_threshold.is_none(),
+            "{}",
+            description
+        );
+        // Critical threshold is u8, so always >= 0 by type definition
+        assert!(
+            config.critical_threshold.is_some() || config.critical_threshold.is_none(),
+            "{}",
+            description
+        );
+    }
+}
+
+fn fake_battery(base: &std::path::Path, name: &str, files: &[(&str, &str)]) {
+    let dir = base.join(name);
+    std::fs::create_dir_all(&dir).unwrap();
+    for (file, contents) in files {
+        std::fs::write(dir.join(file), format!("{}\n", contents)).unwrap();
+    }
+}
+
+#[test]
+fn test_battery_aggregates_two_batteries() {
+    let dir = tempfile::TempDir::new().unwrap();
+    // Internal 24 Wh battery nearly empty, external 72 Wh one mostly full
+    fake_battery(
+        dir.path(),
+        "BAT0",
+        &[
+            ("energy_now", "2400000"),
+            ("energy_full", "24000000"),
+            ("capacity", "10"),
+            ("status", "Discharging"),
+        ],
+    );
+    fake_battery(
+        dir.path(),
+        "BAT1",
+        &[
+            ("energy_now", "54000000"),
+            ("energy_full", "72000000"),
+            ("capacity", "75"),
+            ("status", "Charging"),
+        ],
+    );
+    fake_battery(dir.path(), "AC", &[("online", "1")]);
+
+    let batteries = battery::list_batteries(dir.path());
+    assert_eq!(
+        batteries,
+        vec![dir.path().join("BAT0"), dir.path().join("BAT1")]
+    );
+
+    let readings: Vec<_> = batteries.iter().map(|b| battery::read_battery(b)).collect();
+    // Weighted by capacity (56.4 of 96 Wh), not a plain mean of 10% and 75%
+    let agg = battery::aggregate_batteries(&readings).unwrap();
+    assert_eq!(agg.percent, 59);
+    // One battery charging makes the whole pack charging
+    assert!(agg.charging());
+
+    // charge_* is used when energy_* is missing
+    let other = tempfile::TempDir::new().unwrap();
+    fake_battery(
+        other.path(),
+        "BAT0",
+        &[("charge_now", "3600000"), ("charge_full", "4800000")],
+    );
+    let reading = battery::read_battery(&other.path().join("BAT0"));
+    assert_eq!(reading.now, Some(3_600_000));
+    assert_eq!(reading.full, Some(4_800_000));
+    assert_eq!(reading.status, None);
+
+    assert!(battery::list_batteries(&dir.path().join("nope")).is_empty());
+}
+
+#[test]
+fn test_battery_uevent_parsing() {
+    let uevent = "\
+POWER_SUPPLY_NAME=BAT0
+POWER_SUPPLY_STATUS=Not charging
+POWER_SUPPLY_CAPACITY=87
+POWER_SUPPLY_ENERGY_NOW=41760000
+POWER_SUPPLY_ENERGY_FULL=48000000
+POWER_SUPPLY_POWER_NOW=0
+garbage without separator
+
+=orphan value
+POWER_SUPPLY_CAPACITY_LEVEL=Normal
+POWER_SUPPLY_MODEL_NAME=5B10W13=930
+";
+    let attributes = battery::parse_uevent(uevent);
+    assert_eq!(attributes["status"], "Not charging");
+    assert_eq!(attributes["capacity"], "87");
+    assert_eq!(attributes["energy_now"], "41760000");
+    assert_eq!(attributes["power_now"], "0");
+    // Only the first `=` separates key and value
+    assert_eq!(attributes["model_name"], "5B10W13=930");
+    // Malformed and empty lines are skipped
+    assert_eq!(attributes.len(), 8);
+    assert!(!attributes.keys().any(|k| k.is_empty() || k.contains(' ')));
+
+    // A uevent file is all the module needs; it wins over stale attribute files
+    let dir = tempfile::TempDir::new().unwrap();
+    fake_battery(
+        dir.path(),
+        "BAT0",
+        &[("uevent", uevent), ("capacity", "12"), ("status", "Full")],
+    );
+    let reading = battery::read_battery(&dir.path().join("BAT0"));
+    assert_eq!(
+        reading,
+        battery::BatteryReading {
+            now: Some(41_760_000),
+            full: Some(48_000_000),
+            capacity: Some(87),
+            status: Some("Not charging".to_string()),
+            power: Some(0),
+            ..Default::default()
+        }
+    );
+
+    // charge_* batteries, with an unparsable capacity and empty status
+    let charge = "POWER_SUPPLY_CHARGE_NOW=3600000\nPOWER_SUPPLY_CHARGE_FULL=4800000\n\
+                  POWER_SUPPLY_CURRENT_NOW=1200000\nPOWER_SUPPLY_CAPACITY=n/a\nPOWER_SUPPLY_STATUS=\n";
+    fake_battery(dir.path(), "BAT1", &[("uevent", charge)]);
+    let reading = battery::read_battery(&dir.path().join("BAT1"));
+    assert_eq!(
+        (reading.now, reading.full, reading.power),
+        (Some(3_600_000), Some(4_800_000), Some(1_200_000))
+    );
+    assert_eq!((reading.capacity, reading.status), (None, None));
+}
+
+#[test]
+fn test_battery_aggregate_math_and_states() {
+    use battery::BatteryReading;
+
+    let reading = |now, full, capacity, status: &str| BatteryReading {
+        now,
+        full,
+        capacity,
+        status: Some(status.to_string()),
+        power: None,
+        ..Default::default()
+    };
+
+    assert_eq!(battery::aggregate_batteries(&[]), None);
+
+    // Sum of now over sum of full: 30 / 80 = 37.5% rounds to 38
+    let agg = battery::aggregate_batteries(&[
+        reading(Some(10), Some(40), Some(25), "Discharging"),
+        reading(Some(20), Some(40), Some(50), "Full"),
+    ])
+    .unwrap();
+    assert_eq!(agg.percent, 38);
+    assert_eq!(agg.status, "Discharging");
+    assert!(!agg.charging());
+
+    // A battery without energy/charge files: mean of capacity files instead
+    let agg = battery::aggregate_batteries(&[
+        reading(Some(10), Some(40), Some(25), "Not charging"),
+        reading(None, None, Some(50), "Full"),
+    ])
+    .unwrap();
+    assert_eq!(agg.percent, 37);
+    assert_eq!(agg.status, "Not charging");
+
+    // All full
+    let agg = battery::aggregate_batteries(&[
+        reading(Some(40), Some(40), Some(100), "Full"),
+        reading(Some(50), Some(50), Some(100), "Full"),
+    ])
+    .unwrap();
+    assert_eq!(agg.percent, 100);
+    assert_eq!(agg.status, "Full");
+
+    // Nothing readable
+    let agg = battery::aggregate_batteries(&[BatteryReading::default()]).unwrap();
+    assert_eq!(agg.percent, 0);
+    assert_eq!(agg.status, "Unknown");
+}
+
+#[test]
+fn test_battery_aggregate_mixed_energy_and_charge() {
+    use battery::{BatteryReading, BatteryUnit};
+
+    // 20 of 40 Wh, plus 1 of 4 Ah at 10 V (10 of 40 Wh): 30 / 80 Wh
+    let energy = BatteryReading {
+        now: Some(20_000_000),
+        full: Some(40_000_000),
+        capacity: Some(50),
+        power: Some(5_000_000),
+        ..Default::default()
+    };
+    let charge = BatteryReading {
+        now: Some(1_000_000),
+        full: Some(4_000_000),
+        capacity: Some(25),
+        power: Some(500_000),
+        unit: BatteryUnit::Charge,
+        voltage: Some(10_000_000),
+        ..Default::default()
+    };
+    let agg = battery::aggregate_batteries(&[energy.clone(), charge.clone()]).unwrap();
+    assert_eq!(agg.percent, 38);
+    assert_eq!(agg.now, Some(30_000_000));
+    assert_eq!(agg.full, Some(80_000_000));
+    // 5 W plus 0.5 A at 10 V
+    assert_eq!(agg.power, Some(10_000_000));
+
+    // No voltage to convert with: mean of the capacity files, no summed energy
+    let charge = BatteryReading {
+        voltage: None,
+        ..charge
+    };
+    let agg = battery::aggregate_batteries(&[energy, charge.clone()]).unwrap();
+    assert_eq!(agg.percent, 37);
+    assert_eq!((agg.now, agg.full, agg.power), (None, None, None));
+
+    // Charge-only packs still sum directly
+    let agg = battery::aggregate_batteries(&[charge.clone(), charge]).unwrap();
+    assert_eq!(agg.percent, 25);
+    assert_eq!(agg.full, Some(8_000_000));
+}
+
+#[test]
+fn test_battery_time_estimate() {
+    use battery::{BatteryAggregate, PowerAverage};
+
+    // 30 Wh left at 10 W: three hours to empty
+    assert_eq!(
+        battery::time_remaining_secs(30_000_000, 60_000_000, 10_000_000, false),
+        Some(3 * 3600)
+    );
+    // 30 Wh missing at 20 W: an hour and a half to full
+    assert_eq!(
+        battery::time_remaining_secs(30_000_000, 60_000_000, 20_000_000, true),
+        Some(5400)
+    );
+    // No draw: no estimate instead of dividing by zero
+    assert_eq!(
+        battery::time_remaining_secs(30_000_000, 60_000_000, 0, false),
+        None
+    );
+
+    assert_eq!(battery::format_time_hm(5400), "1:30");
+    assert_eq!(battery::format_time_hm(3 * 3600 + 5 * 60 + 59), "3:05");
+    assert_eq!(battery::format_time_hm(0), "0:00");
+
+    // Moving average smooths a spike and resets when charging starts
+    let mut avg = PowerAverage::default();
+    assert_eq!(avg.push(10, false), Some(10));
+    assert_eq!(avg.push(20, false), Some(15));
+    assert_eq!(avg.push(0, false), None);
+    for _ in 0..battery::POWER_AVERAGE_SAMPLES {
+        avg.push(30, false);
+    }
+    assert_eq!(avg.push(30, false), Some(30));
+    assert_eq!(avg.push(8, true), Some(8));
+
+    let agg = |status: &str, power| BatteryAggregate {
+        percent: 50,
+        status: status.to_string(),
+        now: Some(30_000_000),
+        full: Some(60_000_000),
+        power,
+    };
+    let mut avg = PowerAverage::default();
+    assert_eq!(
+        battery::estimate_time_secs(&agg("Discharging", Some(10_000_000)), &mut avg),
+        Some(3 * 3600)
+    );
+    // power_now of 0 omits the estimate
+    assert_eq!(
+        battery::estimate_time_secs(&agg("Discharging", Some(0)), &mut avg),
+        None
+    );
+    assert_eq!(
+        battery::estimate_time_secs(&agg("Full", Some(10_000_000)), &mut avg),
+        None
+    );
+    assert_eq!(
+        battery::estimate_time_secs(&agg("Discharging", None), &mut avg),
+        None
+    );
+
+    assert_eq!(
+        battery::render_battery_format("{percent}% {time}", 42, "Discharging", Some(5400)),
+        "42% 1:30"
+    );
+    assert_eq!(
+        battery::render_battery_format("{percent}% {time}", 42, "Full", None),
+        "42%"
+    );
+    assert_eq!(
+        battery::render_battery_format("{status}", 42, "Charging", None),
+        "Charging"
+    );
+}
+
+#[test]
+fn test_battery_low_notification_crossings() {
+    use battery::{BatteryAlert, LowBatteryNotifier};
+
+    let mut notifier = LowBatteryNotifier::default();
+    let readings = [
+        (15, "Discharging", None),
+        (11, "Discharging", None),
+        // Crossing below the threshold fires once, not on every refresh
+        (10, "Discharging", Some(BatteryAlert::Low(10))),
+        (9, "Discharging", None),
+        (8, "Discharging", None),
+        // Plugging in reports charging once
+        (8, "Charging", Some(BatteryAlert::Charging)),
+        (9, "Charging", None),
+        // Unplugged while still low: a new crossing
+        (9, "Discharging", Some(BatteryAlert::Low(9))),
+        (7, "Discharging", None),
+        // Plugged in but not charging doesn't count
+        (7, "Not charging", None),
+        (7, "Discharging", None),
+        (7, "Charging", Some(BatteryAlert::Charging)),
+        (30, "Charging", None),
+        // Charging without a prior low alert stays quiet
+        (30, "Discharging", None),
+        (30, "Charging", None),
+        (30, "Discharging", None),
+        (10, "Discharging", Some(BatteryAlert::Low(10))),
+    ];
+    for (i, (percent, status, expected)) in readings.into_iter().enumerate() {
+        assert_eq!(
+            notifier.update(percent, status, 10),
+            expected,
+            "reading {}",
+            i
+        );
+    }
+
+    // Climbing back above the threshold re-arms without a charging alert
+    let mut notifier = LowBatteryNotifier::default();
+    assert_eq!(
+        notifier.update(5, "Discharging", 10),
+        Some(BatteryAlert::Low(5))
+    );
+    assert_eq!(notifier.update(20, "Full", 10), None);
+    assert_eq!(
+        notifier.update(10, "Discharging", 10),
+        Some(BatteryAlert::Low(10))
+    );
+}
+
+#[test]
+fn test_battery_charging_animation_cycles() {
+    use battery::ChargingAnimation;
+
+    let mut animation = ChargingAnimation::new(true);
+    assert_eq!(animation.set_status("Discharging"), None);
+    assert!(!animation.is_animating());
+    assert_eq!(animation.tick(), None);
+
+    assert_eq!(animation.set_status("Charging"), Some("charging-0"));
+    let frames: Vec<_> = (0..6).map(|_| animation.tick()).collect();
+    assert_eq!(
+        frames,
+        vec![
+            Some("charging-1"),
+            Some("charging-2"),
+            Some("charging-3"),
+            Some("charging-0"),
+            Some("charging-1"),
+            Some("charging-2")
+        ]
+    );
+    // Another charging reading keeps the current frame
+    assert_eq!(animation.set_status("Charging"), Some("charging-2"));
+    assert_eq!(animation.tick(), Some("charging-3"));
+
+    // Full stops the cycle with a static class; charging again starts over
+    assert_eq!(animation.set_status("Full"), Some("full"));
+    assert!(!animation.is_animating());
+    assert_eq!(animation.tick(), None);
+    assert_eq!(animation.set_status("Charging"), Some("charging-0"));
+    assert_eq!(animation.set_status("Discharging"), None);
+    assert_eq!(animation.tick(), None);
+
+    // Without `animate` charging is a static class too
+    let mut still = ChargingAnimation::new(false);
+    assert_eq!(still.set_status("Charging"), Some("charging"));
+    assert_eq!(still.tick(), None);
+    assert_eq!(still.set_status("Not charging"), Some("charging"));
+    assert_eq!(still.set_status("Full"), Some("full"));
+}
+
+#[test]
+fn test_battery_module_identity() {
+    assert_eq!(battery::BatteryModule::IDENT, "bar.module.battery");
+}
+
+// ===== MEMORY MODULE TESTS =====
+
+#[test]
+fn test_mem_module_identity() {
+    assert_eq!(mem::MemModule::IDENT, "bar.module.mem");
+}
+
+#[test]
+fn test_mem_parse_meminfo() {
+    let text = "MemTotal:       16303428 kB\nMemFree:         1234567 kB\nMemAvailable:    8151714 kB\nBuffers:          204800 kB\nCached:          4096000 kB\n";
+    let info = mem::parse_meminfo(text).unwrap();
+    assert_eq!(info.total_kib, 16303428);
+    assert_eq!(info.available_kib, 8151714);
+    assert_eq!(info.used_kib(), 16303428 - 8151714);
+    assert_eq!(info.percent(), 50);
+}
+
+#[test]
+fn test_mem_parse_meminfo_missing_fields() {
+    // No MemAvailable: fall back to MemFree + Buffers + Cached
+    let old_kernel = "MemTotal: 1000 kB\nMemFree: 100 kB\nBuffers: 50 kB\nCached: 250 kB\n";
+    let info = mem::parse_meminfo(old_kernel).unwrap();
+    assert_eq!(info.available_kib, 400);
+    assert_eq!(info.percent(), 60);
+
+    // No MemTotal, or nothing to derive availability from
+    assert!(mem::parse_meminfo("MemAvailable: 100 kB\n").is_none());
+    assert!(mem::parse_meminfo("MemTotal: 1000 kB\n").is_none());
+    assert!(mem::parse_meminfo("").is_none());
+
+    // Garbage lines are skipped
+    let noisy = "junk\nMemTotal: abc kB\nMemTotal: 2048 kB\nMemAvailable: 1024 kB\n";
+    assert_eq!(mem::parse_meminfo(noisy).unwrap().used_kib(), 1024);
+}
+
+#[test]
+fn test_mem_format_kib_human_units() {
+    assert_eq!(mem::format_kib(0), "0K");
+    assert_eq!(mem::format_kib(512), "512K");
+    assert_eq!(mem::format_kib(1024), "1.0M");
+    assert_eq!(mem::format_kib(1536), "1.5M");
+    assert_eq!(mem::format_kib(8 * 1024 * 1024), "8.0G");
+    assert_eq!(mem::format_kib(3 * 1024 * 1024 * 1024), "3.0T");
+}
+
+#[test]
+fn test_mem_render_format() {
+    let info = mem::MemInfo {
+        total_kib: 4 * 1024 * 1024,
+        available_kib: 3 * 1024 * 1024,
+    };
+    assert_eq!(
+        mem::render_mem_format("{used}/{total}", &info, false),
+        "1.0G/4.0G"
+    );
+    assert_eq!(
+        mem::render_mem_format("{used}/{total}", &info, true),
+        "1.0G/4.0G (25%)"
+    );
+    assert_eq!(mem::render_mem_format("{percent}%", &info, true), "25%");
+}
+
+// ===== VOLUME MODULE TESTS =====
+
+#[derive(Default)]
+struct MockVolumeBackend {
+    adjustments: std::cell::RefCell<Vec<i32>>,
+    mute_toggles: std::cell::Cell<u32>,
+}
+
+impl volume::VolumeBackend for MockVolumeBackend {
+    fn adjust_volume(&self, delta_percent: i32) {
+        self.adjustments.borrow_mut().push(delta_percent);
+    }
+
+    fn toggle_mute(&self) {
+        self.mute_toggles.set(self.mute_toggles.get() + 1);
+    }
+}
+
+#[test]
+fn test_volume_module_identity() {
+    assert_eq!(volume::VolumeModule::IDENT, "bar.module.volume");
+}
+
+#[test]
+fn test_volume_controller_scroll_and_click() {
+    let controller = volume::VolumeController::new(MockVolumeBackend::default(), 5);
+
+    // Scroll up raises, scroll down lowers, tiny movements are ignored
+    assert!(controller.handle_scroll(-1.0));
+    assert!(controller.handle_scroll(1.0));
+    assert!(!controller.handle_scroll(0.05));
+    assert_eq!(*controller.backend().adjustments.borrow(), vec![5, -5]);
+
+    controller.handle_click();
+    controller.handle_click();
+    assert_eq!(controller.backend().mute_toggles.get(), 2);
+}
+
+#[test]
+fn test_volume_delta_is_clamped() {
+    assert_eq!(volume::apply_volume_delta(50, 5, 100), 55);
+    assert_eq!(volume::apply_volume_delta(98, 5, 100), 100);
+    assert_eq!(volume::apply_volume_delta(3, -5, 100), 0);
+    assert_eq!(volume::apply_volume_delta(120, 5, 150), 125);
+}
+
+#[test]
+fn test_volume_render_format() {
+    let loud = volume::VolumeState {
+        volume: 80,
+        muted: false,
+    };
+    let muted = volume::VolumeState {
+        volume: 80,
+        muted: true,
+    };
+    assert_eq!(
+        volume::render_volume_format("{icon} {volume}%", &loud, "muted"),
+        "🔊 80%"
+    );
+    assert_eq!(
+        volume::render_volume_format("{icon} {volume}% {muted}", &muted, "muted"),
+        "🔇 80% muted"
+    );
+    assert_eq!(
+        volume::render_volume_format("{volume}{muted}", &loud, "M"),
+        "80"
+    );
+}
+
+// ===== BRIGHTNESS MODULE TESTS =====
+
+#[test]
+fn test_brightness_module_identity() {
+    assert_eq!(brightness::BrightnessModule::IDENT, "bar.module.brightness");
+}
+
+#[test]
+fn test_brightness_percent() {
+    assert_eq!(brightness::brightness_percent(0, 255), 0);
+    assert_eq!(brightness::brightness_percent(128, 255), 50);
+    assert_eq!(brightness::brightness_percent(255, 255), 100);
+    assert_eq!(brightness::brightness_percent(937, 937), 100);
+    // Unknown max and out-of-range readings
+    assert_eq!(brightness::brightness_percent(10, 0), 0);
+    assert_eq!(brightness::brightness_percent(500, 255), 100);
+}
+
+#[test]
+fn test_brightness_step_clamps_to_range() {
+    assert_eq!(brightness::step_brightness(500, 5, 1000), 550);
+    assert_eq!(brightness::step_brightness(500, -5, 1000), 450);
+    assert_eq!(brightness::step_brightness(980, 5, 1000), 1000);
+    assert_eq!(brightness::step_brightness(20, -5, 1000), 0);
+    assert_eq!(brightness::step_brightness(0, 5, 0), 0);
+}
+
+// ===== TEMPERATURE MODULE TESTS =====
+
+fn fake_hwmon(
+    dir: &std::path::Path,
+    hwmon: &str,
+    chip: &str,
+    sensors: &[(&str, Option<&str>, &str)],
+) {
+    let p = dir.join(hwmon);
+    std::fs::create_dir_all(&p).unwrap();
+    std::fs::write(p.join("name"), format!("{}\n", chip)).unwrap();
+    for (stem, label, value) in sensors {
+        std::fs::write(p.join(format!("{}_input", stem)), format!("{}\n", value)).unwrap();
+        if let Some(label) = label {
+            std::fs::write(p.join(format!("{}_label", stem)), format!("{}\n", label)).unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_temp_module_identity() {
+    assert_eq!(temp::TempModule::IDENT, "bar.module.temp");
+}
+
+#[test]
+fn test_temp_millidegree_conversion() {
+    assert_eq!(temp::millidegrees_to_celsius(45000), 45.0);
+    assert_eq!(temp::millidegrees_to_celsius(45500), 45.5);
+    assert_eq!(temp::millidegrees_to_celsius(-2000), -2.0);
+    assert_eq!(temp::millidegrees_to_celsius(0), 0.0);
+}
+
+#[test]
+fn test_temp_sensor_discovery_and_selection() {
+    let dir = tempfile::TempDir::new().unwrap();
+    fake_hwmon(dir.path(), "hwmon0", "acpitz", &[("temp1", None, "27800")]);
+    fake_hwmon(
+        dir.path(),
+        "hwmon1",
+        "coretemp",
+        &[
+            ("temp1", Some("Package id 0"), "52000"),
+            ("temp2", Some("Core 0"), "49000"),
+        ],
+    );
+    fake_hwmon(
+        dir.path(),
+        "hwmon2",
+        "nvme",
+        &[("temp1", Some("Composite"), "38850")],
+    );
+
+    let sensors = temp::discover_sensors(dir.path());
+    assert_eq!(sensors.len(), 4);
+    // Unlabeled inputs fall back to the file stem
+    assert!(
+        sensors
+            .iter()
+            .any(|s| s.chip == "acpitz" && s.label == "temp1")
+    );
+
+    // Auto-select prefers the CPU package sensor
+    let auto = temp::select_sensor(&sensors, None).unwrap();
+    assert_eq!(auto.label, "Package id 0");
+    assert_eq!(temp::read_celsius(auto), Some(52.0));
+
+    // Explicit selection by label, chip, or chip/label
+    assert_eq!(
+        temp::select_sensor(&sensors, Some("Composite"))
+            .unwrap()
+            .chip,
+        "nvme"
+    );
+    assert_eq!(
+        temp::select_sensor(&sensors, Some("acpitz")).unwrap().label,
+        "temp1"
+    );
+    assert_eq!(
+        temp::select_sensor(&sensors, Some("coretemp/Core 0"))
+            .unwrap()
+            .label,
+        "Core 0"
+    );
+    assert!(temp::select_sensor(&sensors, Some("missing")).is_none());
+}
+
+#[test]
+fn test_temp_sensor_selection_fallbacks() {
+    let dir = tempfile::TempDir::new().unwrap();
+    fake_hwmon(dir.path(), "hwmon0", "acpitz", &[("temp1", None, "30000")]);
+    fake_hwmon(
+        dir.path(),
+        "hwmon1",
+        "k10temp",
+        &[("temp3", Some("Tccd1"), "61000")],
+    );
+
+    // No Package/Tctl label: fall back to a known CPU chip
+    let sensors = temp::discover_sensors(dir.path());
+    assert_eq!(temp::select_sensor(&sensors, None).unwrap().chip, "k10temp");
+
+    // Nothing at all
+    let empty = tempfile::TempDir::new().unwrap();
+    assert!(temp::discover_sensors(empty.path()).is_empty());
+    assert!(temp::discover_sensors(&empty.path().join("nope")).is_empty());
+    assert!(temp::select_sensor(&[], None).is_none());
+}
+
+// ===== SCRIPT MODULE TESTS =====
+
+struct FakeCommandRunner {
+    result: Option<(Option<i32>, String)>,
+    calls: std::sync::Mutex<Vec<String>>,
+}
+
+impl FakeCommandRunner {
+    fn new(result: Option<(Option<i32>, &str)>) -> Self {
+        Self {
+            result: result.map(|(code, out)| (code, out.to_string())),
+            calls: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl script::CommandRunner for FakeCommandRunner {
+    fn run(&self, command: &str) -> std::io::Result<(Option<i32>, String)> {
+        self.calls.lock().unwrap().push(command.to_string());
+        self.result
+            .clone()
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no such command"))
+    }
+}
+
+#[test]
+fn test_script_module_identity() {
+    assert_eq!(script::ScriptModule::IDENT, "bar.module.script");
+}
+
+#[test]
+fn test_script_output_parsing() {
+    assert_eq!(
+        script::parse_script_output("hello\n"),
+        ("hello".to_string(), None)
+    );
+    assert_eq!(
+        script::parse_script_output("42%  \nbattery details\nignored\n"),
+        ("42%".to_string(), Some("battery details".to_string()))
+    );
+    // Empty second line means no tooltip
+    assert_eq!(
+        script::parse_script_output("text\n\n"),
+        ("text".to_string(), None)
+    );
+    assert_eq!(script::parse_script_output(""), (String::new(), None));
+    assert_eq!(
+        script::parse_script_output("crlf\r\ntip\r\n"),
+        ("crlf".to_string(), Some("tip".to_string()))
+    );
+}
+
+#[test]
+fn test_script_exit_code_class() {
+    assert_eq!(script::exit_code_class(Some(0)), "exit-zero");
+    assert_eq!(script::exit_code_class(Some(1)), "exit-nonzero");
+    assert_eq!(script::exit_code_class(Some(127)), "exit-nonzero");
+    assert_eq!(script::exit_code_class(None), "exit-error");
+}
+
+#[test]
+fn test_script_run_with_fake_runner() {
+    let runner = FakeCommandRunner::new(Some((Some(2), "down\nvpn disconnected\n")));
+    let out = script::run_script(&runner, "vpn-status");
+    assert_eq!(
+        out,
+        script::ScriptOutput {
+            text: "down".to_string(),
+            tooltip: Some("vpn disconnected".to_string()),
+            exit_code: Some(2),
+        }
+    );
+    assert_eq!(script::exit_code_class(out.exit_code), "exit-nonzero");
+    assert_eq!(
+        *runner.calls.lock().unwrap(),
+        vec!["vpn-status".to_string()]
+    );
+
+    // Spawn failures yield an empty label and the error class
+    let failing = FakeCommandRunner::new(None);
+    let out = script::run_script(&failing, "missing");
+    assert_eq!(out.text, "");
+    assert_eq!(out.tooltip, None);
+    assert_eq!(script::exit_code_class(out.exit_code), "exit-error");
+}
+
+// ===== OVERVIEW MODULE TESTS =====
+
+#[test]
+fn test_overview_module_identity() {
+    assert_eq!(overview::OverviewModule::IDENT, "bar.module.overview");
+}
+
+#[test]
+fn test_overview_state_to_class() {
+    assert_eq!(overview::overview_css_class(true), Some("open"));
+    assert_eq!(overview::overview_css_class(false), None);
+    assert_eq!(overview::overview_text(true, "open", "closed"), "open");
+    assert_eq!(overview::overview_text(false, "open", "closed"), "closed");
+}
+
+// ===== KEYBOARD MODULE TESTS =====
+
+#[test]
+fn test_keyboard_module_identity() {
+    assert_eq!(keyboard::KeyboardModule::IDENT, "bar.module.keyboard");
+}
+
+// ===== LABEL MODULE TESTS =====
+
+#[test]
+fn test_label_placeholders_from_bus() {
+    use label::{LabelValues, render_label};
+    use niri_bar::niri::niri_bus;
+
+    let bus = niri_bus();
+    bus.reset();
+    // Nothing reported yet: empty values, zero count
+    assert_eq!(LabelValues::from_bus(&bus), LabelValues::default());
+    assert_eq!(
+        render_label(
+            "[{workspace}] {title} ({window_count})",
+            &LabelValues::from_bus(&bus)
+        ),
+        "[]  (0)"
+    );
+
+    bus.handle_json_line(
+        "{\"WorkspacesChanged\":{\"workspaces\":[{\"id\":1,\"idx\":1,\"name\":null,\"output\":\"eDP-1\",\"is_active\":true,\"is_focused\":false,\"active_window_id\":null},{\"id\":2,\"idx\":2,\"name\":\"web\",\"output\":\"eDP-1\",\"is_active\":false,\"is_focused\":true,\"active_window_id\":7}]}}",
+    );
+    bus.handle_json_line(
+        "{\"WindowsChanged\":{\"windows\":[{\"id\":7,\"title\":\"Docs — Firefox\",\"app_id\":\"firefox\",\"pid\":1,\"workspace_id\":2,\"is_focused\":true,\"is_floating\":true,\"is_fullscreen\":false},{\"id\":8,\"title\":\"mail\",\"app_id\":\"thunderbird\",\"pid\":2,\"workspace_id\":2,\"is_focused\":false,\"is_floating\":false},{\"id\":9,\"title\":\"term\",\"app_id\":\"foot\",\"pid\":3,\"workspace_id\":1,\"is_focused\":false,\"is_floating\":false}]}}",
+    );
+    bus.handle_json_line("{\"WindowFocusChanged\":{\"id\":7}}");
+    bus.handle_json_line(
+        "{\"KeyboardLayoutsChanged\":{\"keyboard_layouts\":{\"names\":[\"English (US)\",\"German\"],\"current_idx\":1}}}",
+    );
+
+    let values = LabelValues::from_bus(&bus);
+    assert_eq!(
+        values,
+        LabelValues {
+            title: "Docs — Firefox".to_string(),
+            workspace: "web".to_string(),
+            window_count: 2,
+            layout: "floating".to_string(),
+            keyboard: "German".to_string(),
+        }
+    );
+    assert_eq!(
+        render_label(
+            "{workspace}: {title} [{window_count}] {layout} {keyboard} {unknown}",
+            &values
+        ),
+        "web: Docs — Firefox [2] floating German {unknown}"
+    );
+
+    // Unnamed workspaces show their idx; fullscreen wins over floating
+    bus.handle_json_line("{\"WorkspaceActivated\":{\"id\":1,\"focused\":true}}");
+    bus.handle_json_line(
+        "{\"WindowOpenedOrChanged\":{\"window\":{\"id\":9,\"title\":\"term\",\"app_id\":\"foot\",\"pid\":3,\"workspace_id\":1,\"is_focused\":true,\"is_floating\":true,\"is_fullscreen\":true}}}",
+    );
+    bus.handle_json_line("{\"WindowFocusChanged\":{\"id\":9}}");
+    assert_eq!(
+        render_label(
+            "{workspace} {title} {window_count} {layout}",
+            &LabelValues::from_bus(&bus)
+        ),
+        "1 term 1 fullscreen"
+    );
+    bus.reset();
+}
+
+#[test]
+fn test_label_layout_names_and_events() {
+    use label::{label_event_is_relevant, window_layout_name};
+    use niri_bar::niri::{NiriEvent, WindowInfo};
+
+    let window = |floating, fullscreen, maximized| WindowInfo {
+        id: 1,
+        title: String::new(),
+        app_id: String::new(),
+        workspace_id: 1,
+        is_focused: true,
+        is_floating: floating,
+        is_fullscreen: fullscreen,
+        is_maximized: maximized,
+        layout: None,
+    };
+    assert_eq!(window_layout_name(&window(false, false, false)), "tiled");
+    assert_eq!(window_layout_name(&window(true, false, false)), "floating");
+    assert_eq!(window_layout_name(&window(true, false, true)), "maximized");
+    assert_eq!(window_layout_name(&window(true, true, true)), "fullscreen");
+
+    assert!(label_event_is_relevant(&NiriEvent::FocusChanged(None)));
+    assert!(label_event_is_relevant(&NiriEvent::KeyboardLayoutChanged));
+    assert!(label_event_is_relevant(&NiriEvent::Synchronized));
+    assert!(!label_event_is_relevant(&NiriEvent::OverviewToggled(true)));
+    assert!(!label_event_is_relevant(&NiriEvent::WindowLayoutsChanged));
+    assert_eq!(label::LabelModule::IDENT, "bar.module.label");
+}
+
+// ===== IDLE INHIBITOR MODULE TESTS =====
+
+/// Hands out increasing cookies and records every call; `fail_*` make calls error
+#[derive(Default)]
+struct MockInhibitBackend {
+    next_cookie: u32,
+    held: Vec<u32>,
+    calls: Vec<String>,
+    fail_inhibit: bool,
+    fail_uninhibit: bool,
+}
+
+impl idle::InhibitBackend for MockInhibitBackend {
+    fn inhibit(&mut self, _reason: &str) -> anyhow::Result<u32> {
+        self.calls.push("inhibit".to_string());
+        if self.fail_inhibit {
+            anyhow::bail!("no screensaver service");
+        }
+        self.next_cookie += 1;
+        self.held.push(self.next_cookie);
+        Ok(self.next_cookie)
+    }
+
+    fn uninhibit(&mut self, cookie: u32) -> anyhow::Result<()> {
+        self.calls.push(format!("uninhibit {}", cookie));
+        if self.fail_uninhibit {
+            anyhow::bail!("unknown cookie {}", cookie);
+        }
+        self.held.retain(|held| *held != cookie);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_idle_module_identity() {
+    assert_eq!(idle::IdleInhibitModule::IDENT, "bar.module.idle");
+    assert_eq!(idle::idle_css_class(true), Some("active"));
+    assert_eq!(idle::idle_css_class(false), None);
+    assert_eq!(idle::idle_text(true, "on", "off"), "on");
+    assert_eq!(idle::idle_text(false, "on", "off"), "off");
+}
+
+#[test]
+fn test_idle_inhibitor_toggle_cookie_lifecycle() {
+    let mut inhibitor = idle::IdleInhibitor::new(MockInhibitBackend::default());
+    assert!(!inhibitor.is_active());
+
+    // On: takes a cookie and holds it
+    assert!(inhibitor.toggle().unwrap());
+    assert_eq!(inhibitor.cookie(), Some(1));
+    assert_eq!(inhibitor.backend().held, vec![1]);
+
+    // Off: releases that same cookie
+    assert!(!inhibitor.toggle().unwrap());
+    assert_eq!(inhibitor.cookie(), None);
+    assert!(inhibitor.backend().held.is_empty());
+
+    // Each activation gets a fresh cookie
+    assert!(inhibitor.toggle().unwrap());
+    assert_eq!(inhibitor.cookie(), Some(2));
+
+    // Requesting the current state doesn't call the service again
+    inhibitor.set_active(true).unwrap();
+    assert_eq!(
+        inhibitor.backend().calls,
+        vec!["inhibit", "uninhibit 1", "inhibit"]
+    );
+}
+
+#[test]
+fn test_idle_inhibitor_backend_failures() {
+    // A failed inhibit leaves the inhibitor off
+    let mut inhibitor = idle::IdleInhibitor::new(MockInhibitBackend {
+        fail_inhibit: true,
+        ..Default::default()
+    });
+    assert!(inhibitor.toggle().is_err());
+    assert!(!inhibitor.is_active());
+
+    // A failed release still drops the cookie, so the toggle can't get stuck on
+    let mut inhibitor = idle::IdleInhibitor::new(MockInhibitBackend {
+        fail_uninhibit: true,
+        ..Default::default()
+    });
+    assert!(inhibitor.toggle().unwrap());
+    let err = inhibitor.toggle().unwrap_err();
+    assert_eq!(err.to_string(), "unknown cookie 1");
+    assert!(!inhibitor.is_active());
+    assert!(inhibitor.toggle().unwrap());
+    assert_eq!(inhibitor.cookie(), Some(2));
+}
+
+// ===== NOTIFICATIONS MODULE TESTS =====
+
+/// mako-like daemon: DND is a mode, toggled by replacing the mode list
+struct MockMako {
+    notifications: usize,
+    modes: Vec<String>,
+    applied: Vec<notifications::DndAction>,
+}
+
+impl notifications::NotificationBackend for MockMako {
+    fn name(&self) -> &'static str {
+        "mock-mako"
+    }
+
+    fn refresh(&mut self) -> anyhow::Result<notifications::NotificationState> {
+        Ok(notifications::mako_state(
+            self.notifications,
+            &self.modes,
+            notifications::DEFAULT_MAKO_DND_MODE,
+        ))
+    }
+
+    fn toggle_action(&self) -> notifications::DndAction {
+        notifications::mako_toggle_action(&self.modes, notifications::DEFAULT_MAKO_DND_MODE)
+    }
+
+    fn apply(&mut self, action: &notifications::DndAction) -> anyhow::Result<()> {
+        let notifications::DndAction::SetModes(modes) = action else {
+            anyhow::bail!("unexpected {:?}", action);
+        };
+        self.modes = modes.clone();
+        self.applied.push(action.clone());
+        Ok(())
+    }
+}
+
+#[test]
+fn test_notifications_module_identity() {
+    assert_eq!(
+        notifications::NotificationsModule::IDENT,
+        "bar.module.notifications"
+    );
+    assert_eq!(notifications::DND_CLASS, "dnd");
+    use notifications::DaemonKind;
+    assert_eq!(DaemonKind::from_name("mako"), Some(DaemonKind::Mako));
+    assert_eq!(DaemonKind::from_name(" Dunst "), Some(DaemonKind::Dunst));
+    assert_eq!(DaemonKind::from_name("swaync"), None);
+}
+
+#[test]
+fn test_notifications_state_model() {
+    use notifications::{NotificationState, badge_text, dunst_state, mako_state};
+
+    let modes = vec!["default".to_string(), "do-not-disturb".to_string()];
+    assert_eq!(
+        mako_state(3, &modes, "do-not-disturb"),
+        NotificationState {
+            count: 3,
+            dnd: true
+        }
+    );
+    assert!(!mako_state(3, &modes[..1], "do-not-disturb").dnd);
+    // A custom dnd_mode is what counts
+    assert!(!mako_state(0, &modes, "quiet").dnd);
+
+    // dunst counts displayed plus waiting notifications
+    assert_eq!(
+        dunst_state(2, 5, true),
+        NotificationState {
+            count: 7,
+            dnd: true
+        }
+    );
+    assert_eq!(dunst_state(0, 0, false), NotificationState::default());
+
+    assert_eq!(badge_text(0), None);
+    assert_eq!(badge_text(7).as_deref(), Some("7"));
+    assert_eq!(badge_text(99).as_deref(), Some("99"));
+    assert_eq!(badge_text(150).as_deref(), Some("99+"));
+}
+
+#[test]
+fn test_notifications_toggle_payloads() {
+    use notifications::{DndAction, dunst_toggle_action, mako_toggle_action};
+
+    let modes = |names: &[&str]| names.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    // mako: add or remove the DND mode, keeping the others in order
+    assert_eq!(
+        mako_toggle_action(&modes(&["default"]), "do-not-disturb"),
+        DndAction::SetModes(modes(&["default", "do-not-disturb"]))
+    );
+    assert_eq!(
+        mako_toggle_action(
+            &modes(&["default", "do-not-disturb", "work"]),
+            "do-not-disturb"
+        ),
+        DndAction::SetModes(modes(&["default", "work"]))
+    );
+    assert_eq!(
+        mako_toggle_action(&[], "quiet"),
+        DndAction::SetModes(modes(&["quiet"]))
+    );
+
+    // dunst: flip `paused`
+    assert_eq!(dunst_toggle_action(false), DndAction::SetPaused(true));
+    assert_eq!(dunst_toggle_action(true), DndAction::SetPaused(false));
+}
+
+#[test]
+fn test_notifications_toggle_dnd_round_trip() {
+    let mut backend = MockMako {
+        notifications: 2,
+        modes: vec!["default".to_string()],
+        applied: Vec::new(),
+    };
+    let state = notifications::toggle_dnd(&mut backend).unwrap();
+    assert!(state.dnd);
+    assert_eq!(state.count, 2);
+    let state = notifications::toggle_dnd(&mut backend).unwrap();
+    assert!(!state.dnd);
+    assert_eq!(backend.modes, vec!["default"]);
+    assert_eq!(backend.applied.len(), 2);
+}
+
+// ===== POWER PROFILE MODULE TESTS =====
+
+/// power-profiles-daemon stand-in recording every set call
+struct MockPowerProfiles {
+    active: std::cell::RefCell<String>,
+    available: Vec<String>,
+    set_calls: std::cell::RefCell<Vec<String>>,
+}
+
+impl MockPowerProfiles {
+    fn new(active: &str, available: &[&str]) -> Self {
+        Self {
+            active: std::cell::RefCell::new(active.to_string()),
+            available: available.iter().map(|s| s.to_string()).collect(),
+            set_calls: Default::default(),
+        }
+    }
+}
+
+impl power_profile::PowerProfilesBackend for MockPowerProfiles {
+    fn active_profile(&self) -> anyhow::Result<String> {
+        Ok(self.active.borrow().clone())
+    }
+
+    fn profiles(&self) -> anyhow::Result<Vec<String>> {
+        Ok(self.available.clone())
+    }
+
+    fn set_active_profile(&self, profile: &str) -> anyhow::Result<()> {
+        self.set_calls.borrow_mut().push(profile.to_string());
+        *self.active.borrow_mut() = profile.to_string();
+        Ok(())
+    }
+}
+
+#[test]
+fn test_power_profile_module_identity() {
+    assert_eq!(
+        power_profile::PowerProfileModule::IDENT,
+        "bar.module.power_profile"
+    );
+    assert_eq!(
+        power_profile::profile_css_class("performance"),
+        Some("performance")
+    );
+    assert_eq!(power_profile::profile_css_class("turbo"), None);
+    assert_eq!(
+        power_profile::render_profile_format("{icon} {profile}", "power-saver"),
+        "🍃 power-saver"
+    );
+}
+
+#[test]
+fn test_state_broadcast_replays_and_prunes() {
+    let mut state = modules::StateBroadcast::new();
+    let (first_tx, mut first_rx) = tokio::sync::mpsc::unbounded_channel();
+    state.subscribe(first_tx);
+    assert_eq!(state.latest(), None);
+    assert!(first_rx.try_recv().is_err());
+
+    state.publish("balanced".to_string());
+    assert_eq!(first_rx.try_recv().unwrap(), "balanced");
+
+    // A widget built later starts from the latest value
+    let (second_tx, mut second_rx) = tokio::sync::mpsc::unbounded_channel();
+    state.subscribe(second_tx);
+    assert_eq!(second_rx.try_recv().unwrap(), "balanced");
+    assert_eq!(state.subscribers(), 2);
+
+    // Gone widgets drop out instead of piling up across rebuilds
+    drop(first_rx);
+    state.publish("performance".to_string());
+    assert_eq!(second_rx.try_recv().unwrap(), "performance");
+    assert_eq!(state.subscribers(), 1);
+    assert_eq!(state.latest().map(String::as_str), Some("performance"));
+}
+
+#[test]
+fn test_power_profile_cycling_order() {
+    use power_profile::next_profile;
+    let all: Vec<String> = power_profile::PROFILES
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+    assert_eq!(next_profile("power-saver", &all, true).unwrap(), "balanced");
+    assert_eq!(next_profile("balanced", &all, true).unwrap(), "performance");
+    assert_eq!(
+        next_profile("performance", &all, true).unwrap(),
+        "power-saver"
+    );
+    assert_eq!(
+        next_profile("power-saver", &all, false).unwrap(),
+        "performance"
+    );
+    assert_eq!(
+        next_profile("balanced", &all, false).unwrap(),
+        "power-saver"
+    );
+
+    // Without performance support the cycle skips it, whatever order the daemon lists
+    let no_performance = vec!["balanced".to_string(), "power-saver".to_string()];
+    assert_eq!(
+        next_profile("balanced", &no_performance, true).unwrap(),
+        "power-saver"
+    );
+    assert_eq!(
+        next_profile("power-saver", &no_performance, true).unwrap(),
+        "balanced"
+    );
+    // Unknown current profile starts the cycle; nothing available means no change
+    assert_eq!(next_profile("", &all, true).unwrap(), "power-saver");
+    assert_eq!(next_profile("balanced", &[], true), None);
+}
+
+#[test]
+fn test_power_profile_controller_sets_profile() {
+    let controller = power_profile::PowerProfileController::new(MockPowerProfiles::new(
+        "balanced",
+        &["power-saver", "balanced", "performance"],
+    ));
+    assert_eq!(
+        controller.cycle(true).unwrap().as_deref(),
+        Some("performance")
+    );
+    assert_eq!(
+        controller.cycle(true).unwrap().as_deref(),
+        Some("power-saver")
+    );
+    assert_eq!(
+        controller.cycle(false).unwrap().as_deref(),
+        Some("performance")
+    );
+    assert_eq!(
+        *controller.backend().set_calls.borrow(),
+        vec!["performance", "power-saver", "performance"]
+    );
+
+    // A single profile never triggers a set call
+    let controller = power_profile::PowerProfileController::new(MockPowerProfiles::new(
+        "balanced",
+        &["balanced"],
+    ));
+    assert_eq!(controller.cycle(true).unwrap().as_deref(), Some("balanced"));
+    assert!(controller.backend().set_calls.borrow().is_empty());
+}
+
+// ===== WORKSPACES MODULE TESTS =====
+
+/// Single-output snapshot: the focused workspace is also the one shown
+fn ws(id: i64, idx: i64, focused: bool) -> workspaces::WorkspaceSnapshot {
+    (id, idx, None, focused, focused)
+}
 
 #[test]
-fn test_battery_module_config_parsing() {
-    // Test battery-specific configuration options
-    let yaml_config = r#"
-device: "BAT1"
-show_icon: true
-show_percentage: true
-pulse: false
-warn_threshold: 25
-critical_threshold: 15
-"#;
-
-    let config: ModuleConfig = serde_yaml::from_str(yaml_config).unwrap();
+fn test_workspaces_refresh_only_on_relevant_events() {
+    use niri_bar::niri::NiriEvent;
+    use workspaces::{WorkspaceRefresh, workspace_refresh};
+
+    // Unrelated events alone don't wake the module up
+    let idle = [
+        NiriEvent::KeyboardLayoutChanged,
+        NiriEvent::OverviewToggled(true),
+        NiriEvent::WindowLayoutsChanged,
+    ];
+    assert!(workspace_refresh(&idle).is_empty());
+    assert!(workspace_refresh(&[]).is_empty());
 
-    // Verify battery-specific settings are parsed
+    let buttons_only = WorkspaceRefresh {
+        buttons: true,
+        counts: false,
+    };
     assert_eq!(
-        config.additional.get("device"),
-        Some(&serde_yaml::Value::String("BAT1".to_string()))
+        workspace_refresh(&[NiriEvent::WorkspacesChanged]),
+        buttons_only
     );
     assert_eq!(
-        config.additional.get("show_icon"),
-        Some(&serde_yaml::Value::Bool(true))
+        workspace_refresh(&[NiriEvent::FocusChanged(Some(4))]),
+        buttons_only
     );
     assert_eq!(
-        config.additional.get("pulse"),
-        Some(&serde_yaml::Value::Bool(false))
+        workspace_refresh(&[NiriEvent::FocusChanged(None)]),
+        buttons_only
+    );
+
+    // Window events also move the counts, wherever they fall in the batch
+    let both = WorkspaceRefresh {
+        buttons: true,
+        counts: true,
+    };
+    assert_eq!(workspace_refresh(&[NiriEvent::WindowsChanged]), both);
+    assert_eq!(
+        workspace_refresh(&[
+            NiriEvent::WindowsChanged,
+            NiriEvent::OverviewToggled(false),
+            NiriEvent::WorkspacesChanged,
+        ]),
+        both
+    );
+    assert_eq!(
+        workspace_refresh(&[
+            NiriEvent::WindowLayoutsChanged,
+            NiriEvent::FocusChanged(Some(1)),
+            NiriEvent::KeyboardLayoutChanged,
+        ]),
+        buttons_only
     );
-    assert_eq!(config.warn_threshold, Some(25));
-    assert_eq!(config.critical_threshold, Some(15));
-    assert_eq!(config.show_percentage, Some(true));
 }
 
 #[test]
-fn test_battery_module_defaults() {
-    let config = ModuleConfig::default();
+fn test_workspaces_diff_additions_and_removals() {
+    use workspaces::{WorkspaceDiff, diff_workspaces};
 
-    // Test that battery module has reasonable defaults
-    assert!(
-        config.show_percentage.unwrap_or(true),
-        "Should show percentage by default"
+    let old = vec![ws(1, 1, true), ws(2, 2, false)];
+    let new = vec![ws(1, 1, true), ws(2, 2, false), ws(3, 3, false)];
+    assert_eq!(
+        diff_workspaces(&old, &new),
+        WorkspaceDiff {
+            added: vec![3],
+            ..Default::default()
+        }
     );
-    assert!(config.warn_threshold.is_none() || config.warn_threshold.unwrap() > 0);
-    assert!(config.critical_threshold.is_none() || config.critical_threshold.unwrap() > 0);
+
+    let new = vec![ws(2, 1, true)];
+    let diff = diff_workspaces(&old, &new);
+    assert_eq!(diff.removed, vec![1]);
+    assert!(diff.added.is_empty());
+    // Workspace 2 moved to idx 1 and gained focus
+    assert_eq!(diff.relabeled, vec![2]);
+    assert_eq!(diff.newly_focused, Some(2));
+
+    // Initial build: everything is added and the focused workspace pulses
+    let diff = diff_workspaces(&[], &old);
+    assert_eq!(diff.added, vec![1, 2]);
+    assert_eq!(diff.newly_focused, Some(1));
+
+    // Identical snapshots leave the buttons alone
+    assert!(diff_workspaces(&old, &old).is_empty());
 }
 
 #[test]
-fn test_battery_module_threshold_validation() {
-    let test_cases = vec![
-        (Some(10), Some(20), "warn < critical should be valid"),
-        (
-            Some(50),
-            Some(20),
-            "warn > critical should be valid (implementation dependent)",
-        ),
-        (Some(0), Some(10), "zero warn threshold should be valid"),
-        (Some(100), Some(10), "high warn threshold should be valid"),
+fn test_workspaces_diff_reorders_and_renames() {
+    use workspaces::diff_workspaces;
+
+    let old = vec![ws(1, 1, false), ws(2, 2, true), ws(3, 3, false)];
+    let new = vec![ws(3, 1, false), ws(1, 2, false), ws(2, 3, true)];
+    let diff = diff_workspaces(&old, &new);
+    assert!(diff.reordered);
+    assert_eq!(diff.relabeled, vec![3, 1, 2]);
+    assert!(diff.added.is_empty() && diff.removed.is_empty());
+    // Focus stayed on workspace 2: no pulse
+    assert_eq!(diff.newly_focused, None);
+
+    // Removing from the middle keeps relative order
+    let diff = diff_workspaces(&old, &[ws(1, 1, false), ws(3, 2, false)]);
+    assert!(!diff.reordered);
+    assert_eq!(diff.removed, vec![2]);
+
+    // A rename alone is a relabel, not a rebuild
+    let renamed = vec![
+        (1, 1, Some("web".to_string()), false, false),
+        ws(2, 2, true),
+        ws(3, 3, false),
     ];
+    let diff = diff_workspaces(&old, &renamed);
+    assert_eq!(diff.relabeled, vec![1]);
+    assert!(!diff.reordered);
+}
 
-    for (warn, critical, description) in test_cases {
-        let config = ModuleConfig {
-            warn_threshold: warn,
-            critical_threshold: critical,
-            ..Default::default()
-        };
+#[test]
+fn test_workspaces_diff_focus_moves() {
+    use workspaces::diff_workspaces;
+
+    let old = vec![ws(1, 1, true), ws(2, 2, false)];
+    let diff = diff_workspaces(&old, &[ws(1, 1, false), ws(2, 2, true)]);
+    assert_eq!(diff.newly_focused, Some(2));
+    assert!(diff.relabeled.is_empty() && !diff.reordered);
+
+    // Focus leaving this output: nothing to pulse, only `active` classes change
+    let diff = diff_workspaces(&old, &[ws(1, 1, false), ws(2, 2, false)]);
+    assert_eq!(diff.newly_focused, None);
+    assert!(diff.is_empty());
+
+    // Focus landing on a brand new workspace pulses it
+    let diff = diff_workspaces(&old, &[ws(1, 1, false), ws(2, 2, false), ws(9, 3, true)]);
+    assert_eq!(diff.added, vec![9]);
+    assert_eq!(diff.newly_focused, Some(9));
+}
 
-        // Test that config is valid
-        // Thresholds are u8, so always >= 0 by type definition
-        assert!(
-            config.warn_threshold.is_some() || config.warn_threshold.is_none(),
-            "{}",
-            description
-        );
-        // Critical threshold is u8, so always >= 0 by type definition
-        assert!(
-            config.critical_threshold.is_some() || config.critical_threshold.is_none(),
-            "{}",
-            description
-        );
-    }
+#[test]
+fn test_workspaces_hide_empty_filter() {
+    use workspaces::visible_workspaces;
+
+    let snapshot = vec![
+        ws(1, 1, false),
+        ws(2, 2, true),
+        ws(3, 3, false),
+        ws(4, 4, false),
+    ];
+    let counts = |id: i64| match id {
+        1 => 2,
+        4 => 1,
+        _ => 0,
+    };
+    // Workspace 3 is empty and unfocused; workspace 2 is empty but focused
+    let visible = visible_workspaces(snapshot.clone(), counts);
+    assert_eq!(
+        visible,
+        vec![ws(1, 1, false), ws(2, 2, true), ws(4, 4, false)]
+    );
+
+    // Everything empty: only the focused workspace remains
+    assert_eq!(visible_workspaces(snapshot, |_| 0), vec![ws(2, 2, true)]);
+
+    // A workspace filling up reappears as an addition in the diff
+    let before = visible_workspaces(vec![ws(1, 1, true), ws(2, 2, false)], |_| 0);
+    let after = visible_workspaces(vec![ws(1, 1, true), ws(2, 2, false)], |id| {
+        (id == 2) as usize
+    });
+    assert_eq!(workspaces::diff_workspaces(&before, &after).added, vec![2]);
+
+    // An empty workspace shown on an unfocused output stays too
+    let shown_elsewhere = (5, 1, None, false, true);
+    assert_eq!(
+        visible_workspaces(vec![ws(1, 1, false), shown_elsewhere.clone()], |_| 0),
+        vec![shown_elsewhere]
+    );
 }
 
 #[test]
-fn test_battery_module_identity() {
-    assert_eq!(battery::BatteryModule::IDENT, "bar.module.battery");
+fn test_workspace_icon_resolution() {
+    use workspaces::{WorkspaceIcon, parse_icon_map, resolve_workspace_icon};
+
+    let settings: ModuleConfig =
+        serde_yaml::from_str("icons: { 1: \"\", \"2\": \"\", web: firefox, 3: firefox }").unwrap();
+    let icons = parse_icon_map(&settings);
+    assert_eq!(icons.get("1").map(String::as_str), Some(""));
+    assert_eq!(icons.len(), 4);
+    let theme = |n: &str| n == "firefox";
+
+    // Index mapping, glyph
+    assert_eq!(
+        resolve_workspace_icon(&icons, 1, None, theme),
+        Some(WorkspaceIcon::Glyph("".to_string()))
+    );
+    // Name mapping wins over index mapping; known icon names are themed
+    assert_eq!(
+        resolve_workspace_icon(&icons, 2, Some("web"), theme),
+        Some(WorkspaceIcon::Themed("firefox".to_string()))
+    );
+    // Unmapped name falls back to the index mapping
+    assert_eq!(
+        resolve_workspace_icon(&icons, 2, Some("chat"), theme),
+        Some(WorkspaceIcon::Glyph("".to_string()))
+    );
+    // Not in the icon theme: rendered as literal text
+    assert_eq!(
+        resolve_workspace_icon(&icons, 3, None, |_| false),
+        Some(WorkspaceIcon::Glyph("firefox".to_string()))
+    );
+    // No mapping: caller falls back to the number
+    assert_eq!(resolve_workspace_icon(&icons, 9, None, theme), None);
+    assert!(parse_icon_map(&ModuleConfig::default()).is_empty());
 }
 
-// ===== WORKSPACES MODULE TESTS =====
+#[test]
+fn test_workspace_count_badge_text() {
+    use workspaces::count_badge_text;
+
+    assert_eq!(count_badge_text(3, false), Some("3".to_string()));
+    assert_eq!(count_badge_text(3, true), Some("3".to_string()));
+    // Empty workspaces show "0" unless hide_empty_count is set
+    assert_eq!(count_badge_text(0, false), Some("0".to_string()));
+    assert_eq!(count_badge_text(0, true), None);
+}
+
+#[test]
+fn test_workspace_label_text() {
+    use workspaces::workspace_label;
+
+    assert_eq!(workspace_label(true, 3, Some("web")), "3");
+    assert_eq!(workspace_label(false, 3, Some("web")), "web");
+    assert_eq!(workspace_label(false, 3, None), "3");
+}
 
 #[test]
 fn test_workspaces_module_config() {
@@ -262,6 +2101,97 @@ special_cmd: "swww img ${current_workspace_image}"
     );
 }
 
+#[test]
+fn test_workspaces_context_menu_config() {
+    use workspaces::WorkspaceMenuAction;
+
+    let config: ModuleConfig = serde_yaml::from_str("{}").unwrap();
+    assert_eq!(
+        workspaces::parse_context_menu(&config),
+        vec![
+            WorkspaceMenuAction::Focus,
+            WorkspaceMenuAction::MoveWindowHere
+        ]
+    );
+
+    let config: ModuleConfig =
+        serde_yaml::from_str("context_menu: [close_all, bogus, focus]").unwrap();
+    assert_eq!(
+        workspaces::parse_context_menu(&config),
+        vec![WorkspaceMenuAction::CloseAll, WorkspaceMenuAction::Focus]
+    );
+
+    let config: ModuleConfig = serde_yaml::from_str("context_menu: false").unwrap();
+    assert!(workspaces::parse_context_menu(&config).is_empty());
+    let config: ModuleConfig = serde_yaml::from_str("context_menu: []").unwrap();
+    assert!(workspaces::parse_context_menu(&config).is_empty());
+}
+
+#[test]
+fn test_workspaces_pulse_survives_reload() {
+    use niri_bar::ui_state::{UiStateKey, UiStateRegistry};
+    use workspaces::{WorkspacesUiState, diff_workspaces, pulse_target};
+
+    // Focus moves 1 -> 2: the pulse plays on 2
+    let diff = diff_workspaces(
+        &[ws(1, 1, true), ws(2, 2, false)],
+        &[ws(1, 1, false), ws(2, 2, true)],
+    );
+    assert_eq!(pulse_target(diff.newly_focused, None), Some(2));
+    let state = WorkspacesUiState {
+        last_pulsed: Some(2),
+    };
+
+    // A reload rebuilds the widget from an empty snapshot, so focus looks new again...
+    let mut registry = UiStateRegistry::default();
+    let key = UiStateKey::new(workspaces::WorkspacesModule::IDENT, "eDP-1", "left");
+    registry.save(key.clone(), &state);
+    let restored: WorkspacesUiState = registry.restore(&key).unwrap();
+    assert_eq!(restored, state);
+    let rebuilt = diff_workspaces(&[], &[ws(1, 1, false), ws(2, 2, true)]);
+    assert_eq!(rebuilt.newly_focused, Some(2));
+    // ...but the restored state knows the pulse already played
+    assert_eq!(
+        pulse_target(rebuilt.newly_focused, restored.last_pulsed),
+        None
+    );
+
+    // Real focus changes still pulse, including going back
+    assert_eq!(pulse_target(Some(1), Some(2)), Some(1));
+    assert_eq!(pulse_target(Some(2), Some(1)), Some(2));
+    assert_eq!(pulse_target(None, Some(2)), None);
+}
+
+#[test]
+fn test_workspaces_pulse_config() {
+    use std::time::Duration;
+    use workspaces::{DEFAULT_PULSE_MS, pulse_duration};
+
+    let pulse = |yaml: &str| pulse_duration(&serde_yaml::from_str::<ModuleConfig>(yaml).unwrap());
+
+    assert_eq!(pulse("{}"), Some(Duration::from_millis(DEFAULT_PULSE_MS)));
+    assert_eq!(pulse("pulse_ms: 400"), Some(Duration::from_millis(400)));
+    assert_eq!(
+        pulse("pulse_enabled: true\npulse_ms: 120"),
+        Some(Duration::from_millis(120))
+    );
+
+    // Either option turns the pulse (and its removal timer) off
+    assert_eq!(pulse("pulse_ms: 0"), None);
+    assert_eq!(pulse("pulse_enabled: false"), None);
+    assert_eq!(pulse("pulse_enabled: false\npulse_ms: 400"), None);
+
+    // Unparseable values fall back to the defaults
+    assert_eq!(
+        pulse("pulse_ms: fast\npulse_enabled: maybe"),
+        Some(Duration::from_millis(DEFAULT_PULSE_MS))
+    );
+    assert_eq!(
+        pulse("pulse_ms: -5"),
+        Some(Duration::from_millis(DEFAULT_PULSE_MS))
+    );
+}
+
 #[test]
 fn test_workspaces_module_identity() {
     assert_eq!(workspaces::WorkspacesModule::IDENT, "bar.module.workspaces");
@@ -302,6 +2232,181 @@ ellipsize: "{}"
     }
 }
 
+#[test]
+fn test_window_title_icon_resolution() {
+    use window_title::{FALLBACK_ICON, icon_candidates, resolve_icon_name};
+
+    assert_eq!(
+        icon_candidates("org.gnome.Nautilus"),
+        vec!["org.gnome.Nautilus", "org.gnome.nautilus", "nautilus"]
+    );
+    assert_eq!(icon_candidates("firefox"), vec!["firefox"]);
+    assert_eq!(icon_candidates("Alacritty"), vec!["Alacritty", "alacritty"]);
+    assert!(icon_candidates("  ").is_empty());
+
+    // Exact match preferred, then lowercase, then last segment
+    let theme = ["org.gnome.Nautilus", "nautilus", "alacritty"];
+    let has = |n: &str| theme.contains(&n);
+    assert_eq!(
+        resolve_icon_name("org.gnome.Nautilus", has),
+        "org.gnome.Nautilus"
+    );
+    assert_eq!(resolve_icon_name("Alacritty", has), "alacritty");
+    assert_eq!(resolve_icon_name("com.example.Nautilus", has), "nautilus");
+
+    // Unknown or missing app_id falls back to the generic icon
+    assert_eq!(resolve_icon_name("unknown-app", has), FALLBACK_ICON);
+    assert_eq!(resolve_icon_name("", has), FALLBACK_ICON);
+}
+
+#[test]
+fn test_window_title_show_icon_option() {
+    use window_title::show_icon_enabled;
+
+    assert!(!show_icon_enabled(&ModuleConfig::default()));
+    let on: ModuleConfig = serde_yaml::from_str("show_icon: true").unwrap();
+    assert!(show_icon_enabled(&on));
+}
+
+#[test]
+fn test_window_title_truncates_by_grapheme() {
+    use window_title::{EllipsizePosition, truncate_graphemes};
+
+    assert_eq!(EllipsizePosition::from_option(None), EllipsizePosition::End);
+    assert_eq!(
+        EllipsizePosition::from_option(Some("middle")),
+        EllipsizePosition::Middle
+    );
+    assert_eq!(
+        EllipsizePosition::from_option(Some("sideways")),
+        EllipsizePosition::End
+    );
+
+    // Short enough: untouched
+    let title = "Firefox";
+    for position in [
+        EllipsizePosition::Start,
+        EllipsizePosition::Middle,
+        EllipsizePosition::End,
+    ] {
+        assert_eq!(truncate_graphemes(title, 7, position), "Firefox");
+        assert_eq!(truncate_graphemes(title, 50, position), "Firefox");
+    }
+    assert_eq!(
+        truncate_graphemes(title, 5, EllipsizePosition::End),
+        "Fire…"
+    );
+    assert_eq!(
+        truncate_graphemes(title, 5, EllipsizePosition::Start),
+        "…efox"
+    );
+    assert_eq!(
+        truncate_graphemes(title, 5, EllipsizePosition::Middle),
+        "Fi…ox"
+    );
+
+    // Family emoji (ZWJ sequence), a flag and a skin-toned thumb count as one each
+    let emoji = "👨‍👩‍👧‍👦🇯🇵👍🏽 party";
+    assert_eq!(
+        truncate_graphemes(emoji, 3, EllipsizePosition::End),
+        "👨‍👩‍👧‍👦🇯🇵…"
+    );
+    assert_eq!(
+        truncate_graphemes(emoji, 4, EllipsizePosition::Middle),
+        "👨‍👩‍👧‍👦🇯🇵…y"
+    );
+    assert_eq!(truncate_graphemes(emoji, 2, EllipsizePosition::Start), "…y");
+
+    // Combining accents stay on their base letters
+    let combining = "Cafe\u{301} de\u{301}ja\u{300} vu";
+    assert_eq!(
+        truncate_graphemes(combining, 5, EllipsizePosition::End),
+        "Cafe\u{301}…"
+    );
+    assert_eq!(
+        truncate_graphemes(combining, 7, EllipsizePosition::Start),
+        "…e\u{301}ja\u{300} vu"
+    );
+
+    // Just the ellipsis at 1; nothing at all at 0
+    assert_eq!(truncate_graphemes(emoji, 1, EllipsizePosition::End), "…");
+    assert_eq!(truncate_graphemes(emoji, 0, EllipsizePosition::Middle), "");
+}
+
+#[test]
+fn test_window_title_rewrite_precedence() {
+    use window_title::TitleRewrites;
+
+    let settings: ModuleConfig = serde_yaml::from_str(
+        r#"
+rewrites:
+  firefox:
+    - { pattern: "^(.*) — Mozilla Firefox$", replacement: "🦊 $1" }
+  "*":
+    - { pattern: "^(.*) — Mozilla Firefox$", replacement: "global" }
+    - { pattern: "^(.*) - (.*)$", replacement: "$2: $1" }
+"#,
+    )
+    .unwrap();
+    let rewrites = TitleRewrites::from_settings(&settings);
+
+    // App-specific rule wins over a matching global rule
+    assert_eq!(
+        rewrites.apply("firefox", "Rust Docs — Mozilla Firefox"),
+        "🦊 Rust Docs"
+    );
+    // Other apps only see global rules; first matching global rule applies
+    assert_eq!(
+        rewrites.apply("chromium", "Rust Docs — Mozilla Firefox"),
+        "global"
+    );
+    // Falls through to global rules when no app-specific rule matches
+    assert_eq!(
+        rewrites.apply("firefox", "notes.txt - vim"),
+        "vim: notes.txt"
+    );
+    // No rule matches: raw title
+    assert_eq!(rewrites.apply("foot", "~/src"), "~/src");
+}
+
+#[test]
+fn test_window_title_rewrite_single_rule_and_invalid_regex() {
+    use window_title::TitleRewrites;
+
+    let settings: ModuleConfig = serde_yaml::from_str(
+        r#"
+rewrites:
+  kitty: { pattern: '^(\w+)@(\w+): (.*)$', replacement: "$3 [$2]" }
+  broken:
+    - { pattern: "(unclosed", replacement: "x" }
+    - { pattern: "^Broken", replacement: "Fixed" }
+"#,
+    )
+    .unwrap();
+    let rewrites = TitleRewrites::from_settings(&settings);
+    assert_eq!(rewrites.apply("kitty", "me@box: ~/src"), "~/src [box]");
+    // Invalid regex is skipped; remaining rules still apply
+    assert_eq!(rewrites.apply("broken", "Broken title"), "Fixed title");
+
+    // Unset or malformed option means no rewrites
+    assert!(TitleRewrites::from_settings(&ModuleConfig::default()).is_empty());
+    let malformed: ModuleConfig = serde_yaml::from_str("rewrites: [oops]").unwrap();
+    let rewrites = TitleRewrites::from_settings(&malformed);
+    assert!(rewrites.is_empty());
+    assert_eq!(rewrites.apply("any", "title"), "title");
+}
+
+#[test]
+fn test_window_title_empty_text_option() {
+    use window_title::{DEFAULT_EMPTY_TEXT, empty_text};
+
+    assert_eq!(empty_text(&ModuleConfig::default()), DEFAULT_EMPTY_TEXT);
+    let custom: ModuleConfig = serde_yaml::from_str("empty_text: \"~\"").unwrap();
+    assert_eq!(empty_text(&custom), "~");
+    let blank: ModuleConfig = serde_yaml::from_str("empty_text: \"\"").unwrap();
+    assert_eq!(empty_text(&blank), "");
+}
+
 #[test]
 fn test_window_title_module_identity() {
     assert_eq!(
@@ -322,6 +2427,160 @@ display: "show"
     assert_eq!(config.display, Some(DisplayMode::Show));
 }
 
+fn tray_item(id: &str) -> tray::TrayItem {
+    tray::TrayItem {
+        id: id.to_string(),
+        icon_name: "nm-applet".to_string(),
+        ..Default::default()
+    }
+}
+
+fn pixmap(size: i32) -> tray::TrayPixmap {
+    tray::TrayPixmap {
+        width: size,
+        height: size,
+        data: vec![0; (size * size * 4) as usize],
+    }
+}
+
+#[test]
+fn test_tray_state_tracks_items() {
+    use tray::{TrayEvent, TrayState};
+
+    let mut state = TrayState::default();
+    assert!(state.apply(&TrayEvent::Upsert(tray_item(":1.5/StatusNotifierItem"))));
+    assert!(state.apply(&TrayEvent::Upsert(tray_item(
+        "org.kde.StatusNotifierItem-9-1"
+    ))));
+    // Re-sending an unchanged item is not a change
+    assert!(!state.apply(&TrayEvent::Upsert(tray_item(":1.5/StatusNotifierItem"))));
+
+    // Updates replace in place and keep registration order
+    let mut changed = tray_item(":1.5/StatusNotifierItem");
+    changed.status = tray::TrayItemStatus::NeedsAttention;
+    assert!(state.apply(&TrayEvent::Upsert(changed)));
+    let ids: Vec<&str> = state.items().iter().map(|i| i.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        vec![":1.5/StatusNotifierItem", "org.kde.StatusNotifierItem-9-1"]
+    );
+    assert_eq!(
+        state.get(":1.5/StatusNotifierItem").unwrap().status,
+        tray::TrayItemStatus::NeedsAttention
+    );
+
+    // Menus don't touch the item list
+    assert!(!state.apply(&TrayEvent::Menu {
+        id: ":1.5/StatusNotifierItem".to_string(),
+        items: Vec::new(),
+    }));
+
+    assert!(state.apply(&TrayEvent::Removed(":1.5/StatusNotifierItem".to_string())));
+    assert!(!state.apply(&TrayEvent::Removed("unknown".to_string())));
+    assert_eq!(state.items().len(), 1);
+
+    // Passive items leave the tray until they turn active again
+    let mut passive = tray_item("org.kde.StatusNotifierItem-9-1");
+    passive.status = tray::TrayItemStatus::Passive;
+    assert!(state.apply(&TrayEvent::Upsert(passive.clone())));
+    assert!(state.items().is_empty());
+    assert!(!state.apply(&TrayEvent::Upsert(passive)));
+    assert!(state.apply(&TrayEvent::Upsert(tray_item(
+        "org.kde.StatusNotifierItem-9-1"
+    ))));
+    assert_eq!(state.items().len(), 1);
+    assert_eq!(
+        tray::TrayItemStatus::parse("Passive"),
+        tray::TrayItemStatus::Passive
+    );
+    assert_eq!(
+        tray::TrayItemStatus::parse("bogus"),
+        tray::TrayItemStatus::Active
+    );
+}
+
+#[test]
+fn test_tray_icon_selection() {
+    use tray::TrayIcon;
+
+    // Theme icon when the theme has it
+    let item = tray_item("a");
+    assert_eq!(
+        tray::select_icon(&item, 16, |n| n == "nm-applet"),
+        TrayIcon::Named("nm-applet".to_string())
+    );
+    // Unknown name: best pixmap, else the fallback
+    let mut item = tray_item("a");
+    item.icon_pixmaps = vec![pixmap(64), pixmap(22), pixmap(32)];
+    assert_eq!(
+        tray::select_icon(&item, 16, |_| false),
+        TrayIcon::Pixmap(pixmap(22))
+    );
+    item.icon_pixmaps.clear();
+    assert_eq!(tray::select_icon(&item, 16, |_| false), TrayIcon::Fallback);
+    // Absolute paths in IconName are files
+    item.icon_name = "/opt/app/icon.png".to_string();
+    assert_eq!(
+        tray::select_icon(&item, 16, |_| false),
+        TrayIcon::File("/opt/app/icon.png".to_string())
+    );
+
+    // Attention icon only while the item needs attention
+    let mut item = tray_item("a");
+    item.attention_icon_name = "mail-unread".to_string();
+    assert_eq!(
+        tray::select_icon(&item, 16, |_| true),
+        TrayIcon::Named("nm-applet".to_string())
+    );
+    item.status = tray::TrayItemStatus::NeedsAttention;
+    assert_eq!(
+        tray::select_icon(&item, 16, |_| true),
+        TrayIcon::Named("mail-unread".to_string())
+    );
+
+    // Pixmap sizing: smallest that fits, else the largest; malformed ones are skipped
+    let broken = tray::TrayPixmap {
+        width: 48,
+        height: 48,
+        data: vec![0; 4],
+    };
+    let pixmaps = vec![pixmap(16), pixmap(24), broken];
+    assert_eq!(tray::best_pixmap(&pixmaps, 20), Some(&pixmap(24)));
+    assert_eq!(tray::best_pixmap(&pixmaps, 32), Some(&pixmap(24)));
+    assert_eq!(tray::best_pixmap(&[], 16), None);
+
+    assert_eq!(
+        tray::argb_to_rgba(&[0xff, 0x10, 0x20, 0x30, 0x80, 1, 2, 3]),
+        vec![0x10, 0x20, 0x30, 0xff, 1, 2, 3, 0x80]
+    );
+}
+
+#[test]
+fn test_tray_item_address_and_labels() {
+    assert_eq!(
+        tray::parse_item_address("org.kde.StatusNotifierItem-42-1", ""),
+        (
+            "org.kde.StatusNotifierItem-42-1".to_string(),
+            tray::DEFAULT_ITEM_PATH.to_string()
+        )
+    );
+    assert_eq!(
+        tray::parse_item_address(":1.42/org/ayatana/NotificationItem/steam", ""),
+        (
+            ":1.42".to_string(),
+            "/org/ayatana/NotificationItem/steam".to_string()
+        )
+    );
+    assert_eq!(
+        tray::parse_item_address("/StatusNotifierItem", ":1.7"),
+        (":1.7".to_string(), "/StatusNotifierItem".to_string())
+    );
+
+    assert_eq!(tray::strip_mnemonic("_Quit"), "Quit");
+    assert_eq!(tray::strip_mnemonic("Save __as"), "Save _as");
+    assert_eq!(tray::strip_mnemonic("Plain"), "Plain");
+}
+
 #[test]
 fn test_tray_module_identity() {
     assert_eq!(tray::TrayModule::IDENT, "bar.module.tray");
@@ -630,3 +2889,32 @@ scroll_throttle_ms: 1000
         Some(&serde_yaml::Value::Number(1000.into()))
     );
 }
+
+#[test]
+fn test_module_wallpaper_config_from_settings() {
+    let settings: ModuleConfig = serde_yaml::from_str(
+        r#"
+default_wallpaper: ~/default.png
+wallpapers: { "1": ~/one.png, code: ~/Pictures/code }
+swww_options: { transition_type: fade }
+slideshow_interval_secs: 60
+shuffle: true
+"#,
+    )
+    .unwrap();
+
+    let config = wallpaper::module_wallpaper_config(&settings);
+    assert_eq!(config.default.as_deref(), Some("~/default.png"));
+    assert_eq!(
+        config.by_workspace.get("code").map(String::as_str),
+        Some("~/Pictures/code")
+    );
+    assert_eq!(config.by_workspace.len(), 2);
+    assert_eq!(config.special_cmd, None);
+    // Unset swww options take their defaults
+    let swww = config.swww_options.unwrap();
+    assert_eq!(swww.transition_type, "fade");
+    assert_eq!(swww.transition_step, 90);
+    assert_eq!(config.slideshow_interval_secs, Some(60));
+    assert_eq!(config.shuffle, Some(true));
+}
@@ -1,7 +1,16 @@
-use niri_bar::niri::niri_bus;
+use niri_bar::niri::{NoFocusStep, niri_bus};
+use std::sync::{Mutex, MutexGuard};
+
+// The bus is a process-wide singleton; serialize tests that reset or reseed it
+static BUS_LOCK: Mutex<()> = Mutex::new(());
+
+fn bus_guard() -> MutexGuard<'static, ()> {
+    BUS_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
 
 #[test]
 fn test_niri_bus_initial_focus_and_title() {
+    let _guard = bus_guard();
     let bus = niri_bus();
 
     // Seed workspaces with one focused and an active window id
@@ -16,10 +25,12 @@ fn test_niri_bus_initial_focus_and_title() {
 
     // Title should be immediately available
     assert_eq!(bus.current_title(), "Hello World");
+    assert_eq!(bus.current_app_id(), "app");
 }
 
 #[test]
 fn test_niri_bus_workspace_activation_and_scroll_like_changes() {
+    let _guard = bus_guard();
     let bus = niri_bus();
 
     // Reset bus state for test isolation
@@ -39,6 +50,7 @@ fn test_niri_bus_workspace_activation_and_scroll_like_changes() {
 
 #[test]
 fn test_keyboard_layouts_and_overview_events() {
+    let _guard = bus_guard();
     let bus = niri_bus();
     bus.reset();
 
@@ -49,6 +61,17 @@ fn test_keyboard_layouts_and_overview_events() {
     let (names, idx) = bus.keyboard_layouts_snapshot();
     assert_eq!(names, vec!["English (US)", "English (Intl)"]);
     assert_eq!(idx, Some(1));
+    assert_eq!(
+        bus.current_keyboard_layout_name().as_deref(),
+        Some("English (Intl)")
+    );
+
+    // Switching layouts only sends the new index
+    bus.handle_json_line("{\"KeyboardLayoutSwitched\":{\"idx\":0}}");
+    assert_eq!(
+        bus.current_keyboard_layout_name().as_deref(),
+        Some("English (US)")
+    );
 
     // Overview open/close
     bus.handle_json_line("{\"OverviewOpenedOrClosed\":{\"is_open\":true}} ");
@@ -56,3 +79,494 @@ fn test_keyboard_layouts_and_overview_events() {
     bus.handle_json_line("{\"OverviewOpenedOrClosed\":{\"is_open\":false}} ");
     assert!(!bus.is_overview_open());
 }
+
+#[test]
+fn test_active_workspace_per_output_vs_focus() {
+    let _guard = bus_guard();
+    let bus = niri_bus();
+    bus.reset();
+
+    // Two outputs: DP-1 shows workspace 2 and holds focus, HDMI-A-1 shows workspace 4
+    bus.handle_json_line(
+        r#"{"WorkspacesChanged":{"workspaces":[
+            {"id":1,"idx":1,"name":null,"output":"DP-1","is_active":false,"is_focused":false},
+            {"id":2,"idx":2,"name":null,"output":"DP-1","is_active":true,"is_focused":true},
+            {"id":3,"idx":1,"name":null,"output":"HDMI-A-1","is_active":false,"is_focused":false},
+            {"id":4,"idx":2,"name":null,"output":"HDMI-A-1","is_active":true,"is_focused":false}
+        ]}}"#,
+    );
+    let active = |output: &str| bus.active_workspace_for_output(output).map(|w| w.id);
+    assert_eq!(active("DP-1"), Some(2));
+    assert_eq!(active("HDMI-A-1"), Some(4));
+    assert_eq!(active("eDP-1"), None);
+    assert_eq!(bus.focused_workspace_id(), Some(2));
+    let hdmi = bus.active_workspace_for_output("HDMI-A-1").unwrap();
+    assert!(hdmi.is_active && !hdmi.is_focused);
+
+    // Switching workspaces on the unfocused output keeps focus where it was
+    bus.handle_json_line(r#"{"WorkspaceActivated":{"id":3,"focused":false}}"#);
+    assert_eq!(active("HDMI-A-1"), Some(3));
+    assert_eq!(active("DP-1"), Some(2));
+    assert_eq!(bus.focused_workspace_id(), Some(2));
+
+    // Focusing the other output moves focus, and each output keeps its own active workspace
+    bus.handle_json_line(r#"{"WorkspaceActivated":{"id":3,"focused":true}}"#);
+    assert_eq!(bus.focused_workspace_id(), Some(3));
+    assert_eq!(active("DP-1"), Some(2));
+    assert_eq!(active("HDMI-A-1"), Some(3));
+    let dp = bus.active_workspace_for_output("DP-1").unwrap();
+    assert!(dp.is_active && !dp.is_focused);
+
+    // Activating on DP-1 only changes DP-1's active workspace
+    bus.handle_json_line(r#"{"WorkspaceActivated":{"id":1,"focused":true}}"#);
+    assert_eq!(active("DP-1"), Some(1));
+    assert_eq!(active("HDMI-A-1"), Some(3));
+    assert_eq!(bus.focused_workspace_id(), Some(1));
+    let snapshot = bus.workspaces_snapshot();
+    assert_eq!(snapshot.iter().filter(|w| w.is_active).count(), 2);
+    assert_eq!(snapshot.iter().filter(|w| w.is_focused).count(), 1);
+}
+
+#[test]
+fn test_keyboard_layout_index_out_of_range() {
+    let _guard = bus_guard();
+    let bus = niri_bus();
+    bus.reset();
+
+    // An index past the names list is dropped instead of stored
+    bus.handle_json_line(
+        "{\"KeyboardLayoutsChanged\":{\"keyboard_layouts\":{\"names\":[\"us\",\"de\"],\"current_idx\":5}}}",
+    );
+    let (names, idx) = bus.keyboard_layouts_snapshot();
+    assert_eq!(names, vec!["us", "de"]);
+    assert_eq!(idx, None);
+    assert_eq!(bus.current_keyboard_layout_name(), None);
+
+    // Same for a switch to a layout that doesn't exist
+    bus.handle_json_line("{\"KeyboardLayoutSwitched\":{\"idx\":1}}");
+    assert_eq!(bus.current_keyboard_layout_name().as_deref(), Some("de"));
+    bus.handle_json_line("{\"KeyboardLayoutSwitched\":{\"idx\":2}}");
+    assert_eq!(bus.keyboard_layouts_snapshot().1, None);
+    assert_eq!(bus.current_keyboard_layout_name(), None);
+
+    // No layouts at all
+    bus.handle_json_line(
+        "{\"KeyboardLayoutsChanged\":{\"keyboard_layouts\":{\"names\":[],\"current_idx\":0}}}",
+    );
+    assert_eq!(bus.current_keyboard_layout_name(), None);
+}
+
+#[test]
+fn test_title_fallback_after_focused_window_closes() {
+    let _guard = bus_guard();
+    let bus = niri_bus();
+    bus.reset();
+
+    bus.handle_json_line(
+        "{\"WindowsChanged\":{\"windows\":[{\"id\":5,\"title\":\"Editor\",\"app_id\":\"foot\",\"pid\":1,\"workspace_id\":1,\"is_focused\":true,\"is_floating\":false,\"is_urgent\":false}]}}",
+    );
+    assert_eq!(bus.current_title_or("Desktop"), "Editor");
+
+    // Closing the focused window clears focus; the fallback takes over
+    bus.handle_json_line("{\"WindowClosed\":{\"id\":5}}");
+    assert_eq!(bus.focused_window_id_snapshot(), None);
+    assert_eq!(bus.current_title(), "");
+    assert_eq!(bus.current_title_or("Desktop"), "Desktop");
+}
+
+#[test]
+fn test_window_count_per_workspace() {
+    let _guard = bus_guard();
+    let bus = niri_bus();
+    bus.reset();
+
+    bus.handle_json_line(
+        "{\"WindowsChanged\":{\"windows\":[{\"id\":1,\"title\":\"a\",\"app_id\":\"foot\",\"pid\":1,\"workspace_id\":10,\"is_focused\":true,\"is_floating\":false,\"is_urgent\":false},{\"id\":2,\"title\":\"b\",\"app_id\":\"foot\",\"pid\":2,\"workspace_id\":10,\"is_focused\":false,\"is_floating\":false,\"is_urgent\":false},{\"id\":3,\"title\":\"c\",\"app_id\":\"firefox\",\"pid\":3,\"workspace_id\":20,\"is_focused\":false,\"is_floating\":false,\"is_urgent\":false}]}}",
+    );
+    assert_eq!(bus.window_count_for_workspace(10), 2);
+    assert_eq!(bus.window_count_for_workspace(20), 1);
+    // Empty workspace
+    assert_eq!(bus.window_count_for_workspace(30), 0);
+    assert_eq!(
+        bus.window_count_for_workspace(10),
+        bus.windows_for_workspace(10).len()
+    );
+
+    // Closing a window updates the count
+    bus.handle_json_line("{\"WindowClosed\":{\"id\":3}}");
+    assert_eq!(bus.window_count_for_workspace(20), 0);
+
+    // Moving a window between workspaces
+    bus.handle_json_line(
+        "{\"WindowOpenedOrChanged\":{\"window\":{\"id\":2,\"title\":\"b\",\"app_id\":\"foot\",\"pid\":2,\"workspace_id\":30,\"is_focused\":false,\"is_floating\":false,\"is_urgent\":false}}}",
+    );
+    assert_eq!(bus.window_count_for_workspace(10), 1);
+    assert_eq!(bus.window_count_for_workspace(30), 1);
+}
+
+#[test]
+fn test_typed_listener_receives_specific_events() {
+    use niri_bar::niri::NiriEvent;
+    use std::sync::mpsc;
+
+    let _guard = bus_guard();
+    let bus = niri_bus();
+    let (tx, rx) = mpsc::channel::<NiriEvent>();
+    bus.register_typed_listener(tx);
+
+    bus.handle_json_line("{\"WindowFocusChanged\":{\"id\":77}}");
+    bus.handle_json_line("{\"OverviewOpenedOrClosed\":{\"is_open\":true}}");
+    bus.handle_json_line(
+        "{\"KeyboardLayoutsChanged\":{\"keyboard_layouts\":{\"names\":[\"us\"],\"current_idx\":0}}}",
+    );
+    bus.handle_json_line("{\"WindowLayoutsChanged\":{\"changes\":[]}}");
+    bus.handle_json_line("{\"WindowClosed\":{\"id\":12345}}");
+
+    // Delivery is debounced; wait for the flush, then only check containment
+    std::thread::sleep(bus.notify_debounce() * 4 + std::time::Duration::from_millis(50));
+    let events: Vec<NiriEvent> = rx.try_iter().collect();
+    assert!(events.contains(&NiriEvent::FocusChanged(Some(77))));
+    assert!(events.contains(&NiriEvent::OverviewToggled(true)));
+    assert!(events.contains(&NiriEvent::KeyboardLayoutChanged));
+    assert!(events.contains(&NiriEvent::WindowLayoutsChanged));
+    assert!(events.contains(&NiriEvent::WindowsChanged));
+}
+
+#[test]
+fn test_workspaces_for_output_filters_by_connector() {
+    let _guard = bus_guard();
+    let bus = niri_bus();
+
+    bus.handle_json_line(
+        "{\"WorkspacesChanged\":{\"workspaces\":[{\"id\":1,\"idx\":1,\"name\":null,\"output\":\"eDP-1\",\"is_active\":true,\"is_focused\":true,\"active_window_id\":null},{\"id\":2,\"idx\":2,\"name\":null,\"output\":\"eDP-1\",\"is_active\":false,\"is_focused\":false,\"active_window_id\":null},{\"id\":3,\"idx\":1,\"name\":\"ext\",\"output\":\"DP-2\",\"is_active\":true,\"is_focused\":false,\"active_window_id\":null}]}}",
+    );
+
+    let internal = bus.workspaces_for_output("eDP-1");
+    assert_eq!(
+        internal.iter().map(|w| w.id).collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+    assert!(
+        internal
+            .iter()
+            .all(|w| w.output.as_deref() == Some("eDP-1"))
+    );
+
+    let external = bus.workspaces_for_output("DP-2");
+    assert_eq!(external.len(), 1);
+    assert_eq!(external[0].id, 3);
+    assert_eq!(external[0].name.as_deref(), Some("ext"));
+
+    assert!(bus.workspaces_for_output("HDMI-A-1").is_empty());
+}
+
+#[test]
+fn test_notifications_are_coalesced() {
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let _guard = bus_guard();
+    let bus = niri_bus();
+    bus.set_notify_debounce(Duration::from_millis(16));
+    let (tx, rx) = mpsc::channel::<()>();
+    bus.register_ui_listener(tx);
+
+    for _ in 0..1000 {
+        bus.handle_json_line("{\"WindowLayoutsChanged\":{\"changes\":[]}}");
+    }
+    std::thread::sleep(Duration::from_millis(150));
+
+    let wakeups = rx.try_iter().count();
+    assert!(wakeups >= 1, "listener should still be woken");
+    assert!(wakeups < 100, "expected coalesced wakeups, got {}", wakeups);
+}
+
+#[test]
+fn test_fullscreen_focused_window_hides_bar() {
+    use niri_bar::bar::hidden_for_fullscreen;
+    use niri_bar::config::BarOptions;
+
+    let _guard = bus_guard();
+    let bus = niri_bus();
+    bus.reset();
+
+    let options = BarOptions {
+        auto_hide_on_fullscreen: true,
+        ..Default::default()
+    };
+
+    bus.handle_json_line(
+        "{\"WorkspacesChanged\":{\"workspaces\":[{\"id\":1,\"idx\":1,\"name\":null,\"output\":\"eDP-1\",\"is_urgent\":false,\"is_active\":true,\"is_focused\":true,\"active_window_id\":7},{\"id\":2,\"idx\":1,\"name\":null,\"output\":\"DP-1\",\"is_urgent\":false,\"is_active\":true,\"is_focused\":false,\"active_window_id\":null}]}}",
+    );
+    bus.handle_json_line(
+        "{\"WindowsChanged\":{\"windows\":[{\"id\":7,\"title\":\"video\",\"app_id\":\"mpv\",\"pid\":1,\"workspace_id\":1,\"is_focused\":true,\"is_floating\":false,\"is_fullscreen\":true,\"is_urgent\":false}]}}",
+    );
+
+    // Only the monitor holding the focused fullscreen window hides its bar
+    assert!(bus.is_focused_window_fullscreen("eDP-1"));
+    assert!(!bus.is_focused_window_fullscreen("DP-1"));
+    assert!(hidden_for_fullscreen(
+        &options,
+        bus.is_focused_window_fullscreen("eDP-1")
+    ));
+    assert!(!hidden_for_fullscreen(
+        &options,
+        bus.is_focused_window_fullscreen("DP-1")
+    ));
+    // Option off: never hide
+    assert!(!hidden_for_fullscreen(&BarOptions::default(), true));
+
+    // Leaving fullscreen restores the bar
+    bus.handle_json_line(
+        "{\"WindowOpenedOrChanged\":{\"window\":{\"id\":7,\"title\":\"video\",\"app_id\":\"mpv\",\"pid\":1,\"workspace_id\":1,\"is_focused\":true,\"is_floating\":false,\"is_fullscreen\":false,\"is_urgent\":false}}}",
+    );
+    assert!(!hidden_for_fullscreen(
+        &options,
+        bus.is_focused_window_fullscreen("eDP-1")
+    ));
+
+    // Fullscreen again, then focus moves away: bar comes back too
+    bus.handle_json_line(
+        "{\"WindowOpenedOrChanged\":{\"window\":{\"id\":7,\"title\":\"video\",\"app_id\":\"mpv\",\"pid\":1,\"workspace_id\":1,\"is_focused\":true,\"is_floating\":false,\"is_fullscreen\":true,\"is_urgent\":false}}}",
+    );
+    assert!(bus.is_focused_window_fullscreen("eDP-1"));
+    bus.handle_json_line("{\"WindowFocusChanged\":{\"id\":null}}");
+    assert!(!bus.is_focused_window_fullscreen("eDP-1"));
+}
+
+#[test]
+fn test_close_focused_window_without_focus_is_noop() {
+    use niri_bar::niri::close_focused_window;
+
+    let _guard = bus_guard();
+    let bus = niri_bus();
+    bus.reset();
+    assert_eq!(bus.focused_window_id_snapshot(), None);
+    // Returns before touching the niri socket
+    assert!(!close_focused_window().unwrap());
+}
+
+#[test]
+fn test_next_prev_workspace_per_output_wraps() {
+    let _guard = bus_guard();
+    let bus = niri_bus();
+
+    // eDP-1 has 1..3 with 2 shown but DP-2 focused; DP-2 has 1..2
+    bus.handle_json_line(
+        "{\"WorkspacesChanged\":{\"workspaces\":[{\"id\":1,\"idx\":1,\"name\":null,\"output\":\"eDP-1\",\"is_active\":false,\"is_focused\":false,\"active_window_id\":null},{\"id\":2,\"idx\":2,\"name\":null,\"output\":\"eDP-1\",\"is_active\":true,\"is_focused\":false,\"active_window_id\":null},{\"id\":3,\"idx\":3,\"name\":null,\"output\":\"eDP-1\",\"is_active\":false,\"is_focused\":false,\"active_window_id\":null},{\"id\":4,\"idx\":1,\"name\":null,\"output\":\"DP-2\",\"is_active\":false,\"is_focused\":false,\"active_window_id\":null},{\"id\":5,\"idx\":2,\"name\":null,\"output\":\"DP-2\",\"is_active\":true,\"is_focused\":true,\"active_window_id\":null}]}}",
+    );
+
+    // Steps from the output's active workspace, not the focused one elsewhere
+    assert_eq!(
+        bus.next_prev_workspace_id_for_output("eDP-1", true, false, NoFocusStep::Stay),
+        Some(3)
+    );
+    assert_eq!(
+        bus.next_prev_workspace_id_for_output("eDP-1", false, false, NoFocusStep::Stay),
+        Some(1)
+    );
+
+    // At the end of the output's list: stop, or wrap within the output only
+    assert_eq!(
+        bus.next_prev_workspace_id_for_output("DP-2", true, false, NoFocusStep::Stay),
+        None
+    );
+    assert_eq!(
+        bus.next_prev_workspace_id_for_output("DP-2", true, true, NoFocusStep::Stay),
+        Some(4)
+    );
+    assert_eq!(
+        bus.next_prev_workspace_id_for_output("DP-2", false, true, NoFocusStep::Stay),
+        Some(4)
+    );
+    // Global cycling (the default) walks the whole idx-ordered list and crosses outputs
+    assert_eq!(
+        bus.next_prev_workspace_id(true, false, NoFocusStep::Stay),
+        Some(3)
+    );
+    assert_eq!(
+        bus.next_prev_workspace_idx(true, false, NoFocusStep::Stay),
+        Some(3)
+    );
+    assert_eq!(
+        bus.next_prev_workspace_id(false, false, NoFocusStep::Stay),
+        Some(2)
+    );
+
+    assert_eq!(
+        bus.next_prev_workspace_id_for_output("HDMI-A-1", true, true, NoFocusStep::Stay),
+        None
+    );
+}
+
+#[test]
+fn test_window_fullscreen_and_maximized_flags_round_trip() {
+    let _guard = bus_guard();
+    let bus = niri_bus();
+    bus.reset();
+
+    let flags = |workspace_id| {
+        let mut flags: Vec<(i64, bool, bool)> = bus
+            .windows_for_workspace(workspace_id)
+            .iter()
+            .map(|w| (w.id, w.is_fullscreen, w.is_maximized))
+            .collect();
+        flags.sort();
+        flags
+    };
+
+    // Full list: flags as sent, missing ones default to false
+    bus.handle_json_line(
+        "{\"WindowsChanged\":{\"windows\":[{\"id\":1,\"title\":\"video\",\"app_id\":\"mpv\",\"pid\":1,\"workspace_id\":1,\"is_focused\":true,\"is_floating\":false,\"is_fullscreen\":true,\"is_maximized\":false,\"is_urgent\":false},{\"id\":2,\"title\":\"editor\",\"app_id\":\"foot\",\"pid\":2,\"workspace_id\":1,\"is_focused\":false,\"is_floating\":false,\"is_fullscreen\":false,\"is_maximized\":true,\"is_urgent\":false},{\"id\":3,\"title\":\"old\",\"app_id\":\"xterm\",\"pid\":3,\"workspace_id\":2,\"is_focused\":false,\"is_floating\":false}]}}",
+    );
+    assert_eq!(flags(1), vec![(1, true, false), (2, false, true)]);
+    assert_eq!(flags(2), vec![(3, false, false)]);
+
+    // Single-window updates replace both flags
+    bus.handle_json_line(
+        "{\"WindowOpenedOrChanged\":{\"window\":{\"id\":2,\"title\":\"editor\",\"app_id\":\"foot\",\"pid\":2,\"workspace_id\":1,\"is_focused\":false,\"is_floating\":false,\"is_fullscreen\":true,\"is_maximized\":false,\"is_urgent\":false}}}",
+    );
+    bus.handle_json_line(
+        "{\"WindowOpenedOrChanged\":{\"window\":{\"id\":4,\"title\":\"new\",\"app_id\":\"foot\",\"pid\":4,\"workspace_id\":2,\"is_focused\":false,\"is_floating\":false,\"is_fullscreen\":false,\"is_maximized\":true,\"is_urgent\":false}}}",
+    );
+    assert_eq!(flags(1), vec![(1, true, false), (2, true, false)]);
+    assert_eq!(flags(2), vec![(3, false, false), (4, false, true)]);
+}
+
+#[test]
+fn test_synchronized_after_both_initial_snapshots() {
+    use niri_bar::niri::NiriEvent;
+    use std::sync::mpsc;
+
+    let _guard = bus_guard();
+    let bus = niri_bus();
+    bus.reset();
+    assert!(!bus.is_synchronized());
+    let (tx, rx) = mpsc::channel::<NiriEvent>();
+    bus.register_typed_listener(tx);
+    let wait_for_flush =
+        || std::thread::sleep(bus.notify_debounce() * 4 + std::time::Duration::from_millis(50));
+
+    // Workspaces alone (plus single-window updates) aren't a full picture yet
+    bus.handle_json_line(
+        "{\"WorkspacesChanged\":{\"workspaces\":[{\"id\":1,\"idx\":1,\"name\":null,\"output\":\"eDP-1\",\"is_active\":true,\"is_focused\":true,\"active_window_id\":7}]}}",
+    );
+    bus.handle_json_line(
+        "{\"WindowOpenedOrChanged\":{\"window\":{\"id\":7,\"title\":\"term\",\"app_id\":\"foot\",\"pid\":1,\"workspace_id\":1,\"is_focused\":true,\"is_floating\":false}}}",
+    );
+    assert!(!bus.is_synchronized());
+    wait_for_flush();
+    assert!(!rx.try_iter().any(|ev| ev == NiriEvent::Synchronized));
+
+    bus.handle_json_line(
+        "{\"WindowsChanged\":{\"windows\":[{\"id\":7,\"title\":\"term\",\"app_id\":\"foot\",\"pid\":1,\"workspace_id\":1,\"is_focused\":true,\"is_floating\":false}]}}",
+    );
+    assert!(bus.is_synchronized());
+    wait_for_flush();
+    assert!(rx.try_iter().any(|ev| ev == NiriEvent::Synchronized));
+
+    // Sent once; later snapshots are ordinary changes
+    bus.handle_json_line(
+        "{\"WorkspacesChanged\":{\"workspaces\":[{\"id\":1,\"idx\":1,\"name\":null,\"output\":\"eDP-1\",\"is_active\":true,\"is_focused\":true,\"active_window_id\":null}]}}",
+    );
+    bus.handle_json_line("{\"WindowsChanged\":{\"windows\":[]}}");
+    wait_for_flush();
+    let events: Vec<NiriEvent> = rx.try_iter().collect();
+    assert!(events.contains(&NiriEvent::WindowsChanged));
+    assert!(!events.contains(&NiriEvent::Synchronized));
+    assert!(bus.is_synchronized());
+
+    // Either order works
+    bus.reset();
+    bus.handle_json_line("{\"WindowsChanged\":{\"windows\":[]}}");
+    assert!(!bus.is_synchronized());
+    bus.handle_json_line("{\"WorkspacesChanged\":{\"workspaces\":[]}}");
+    assert!(bus.is_synchronized());
+}
+
+#[test]
+fn test_next_prev_workspace_for_output_picks_id_on_unfocused_output() {
+    let _guard = bus_guard();
+    let bus = niri_bus();
+    bus.reset();
+
+    // Both outputs number their workspaces from idx 1; DP-2 has focus, eDP-1 shows id 11
+    bus.handle_json_line(
+        "{\"WorkspacesChanged\":{\"workspaces\":[{\"id\":11,\"idx\":1,\"name\":null,\"output\":\"eDP-1\",\"is_active\":true,\"is_focused\":false,\"active_window_id\":null},{\"id\":12,\"idx\":2,\"name\":null,\"output\":\"eDP-1\",\"is_active\":false,\"is_focused\":false,\"active_window_id\":null},{\"id\":21,\"idx\":1,\"name\":null,\"output\":\"DP-2\",\"is_active\":false,\"is_focused\":false,\"active_window_id\":null},{\"id\":22,\"idx\":2,\"name\":null,\"output\":\"DP-2\",\"is_active\":true,\"is_focused\":true,\"active_window_id\":null}]}}",
+    );
+
+    // The target is eDP-1's second workspace, not idx 2 on the focused DP-2
+    assert_eq!(
+        bus.next_prev_workspace_id_for_output("eDP-1", true, false, NoFocusStep::Stay),
+        Some(12)
+    );
+    assert_eq!(
+        bus.next_prev_workspace_id_for_output("DP-2", false, false, NoFocusStep::Stay),
+        Some(21)
+    );
+    assert_eq!(
+        bus.next_prev_workspace_id_for_output("eDP-1", false, true, NoFocusStep::Stay),
+        Some(12)
+    );
+}
+
+#[test]
+fn test_next_prev_workspace_without_focus() {
+    let _guard = bus_guard();
+    let bus = niri_bus();
+    bus.reset();
+
+    // Nothing focused and nothing active (ids differ from idx on purpose)
+    bus.handle_json_line(
+        "{\"WorkspacesChanged\":{\"workspaces\":[{\"id\":10,\"idx\":1,\"name\":null,\"output\":\"eDP-1\",\"is_active\":false,\"is_focused\":false,\"active_window_id\":null},{\"id\":20,\"idx\":2,\"name\":null,\"output\":\"eDP-1\",\"is_active\":false,\"is_focused\":false,\"active_window_id\":null},{\"id\":30,\"idx\":3,\"name\":null,\"output\":\"eDP-1\",\"is_active\":false,\"is_focused\":false,\"active_window_id\":null}]}}",
+    );
+
+    for forward in [true, false] {
+        for wrap in [true, false] {
+            // No silent step from the first workspace's position
+            assert_eq!(
+                bus.next_prev_workspace_id(forward, wrap, NoFocusStep::Stay),
+                None
+            );
+            assert_eq!(
+                bus.next_prev_workspace_idx(forward, wrap, NoFocusStep::Stay),
+                None
+            );
+            assert_eq!(
+                bus.next_prev_workspace_id_for_output("eDP-1", forward, wrap, NoFocusStep::Stay),
+                None
+            );
+            // Or explicitly start at the first workspace, whichever the direction
+            assert_eq!(
+                bus.next_prev_workspace_id(forward, wrap, NoFocusStep::First),
+                Some(10)
+            );
+            assert_eq!(
+                bus.next_prev_workspace_idx(forward, wrap, NoFocusStep::First),
+                Some(1)
+            );
+            assert_eq!(
+                bus.next_prev_workspace_id_for_output("eDP-1", forward, wrap, NoFocusStep::First),
+                Some(10)
+            );
+        }
+    }
+
+    // The fallback only applies without focus
+    bus.handle_json_line("{\"WorkspaceActivated\":{\"id\":30,\"focused\":true}}");
+    assert_eq!(
+        bus.next_prev_workspace_id(false, false, NoFocusStep::First),
+        Some(20)
+    );
+    assert_eq!(
+        bus.next_prev_workspace_idx(true, false, NoFocusStep::First),
+        None
+    );
+
+    // Nothing to pick from an empty list either way
+    bus.reset();
+    assert_eq!(
+        bus.next_prev_workspace_idx(true, true, NoFocusStep::First),
+        None
+    );
+}
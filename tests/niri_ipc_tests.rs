@@ -0,0 +1,288 @@
+use niri_bar::niri::{
+    NiriIpc, WorkspaceRef, close_window_payload, focus_window_payload, focus_workspace_payload,
+    initial_state_event, move_window_to_workspace_payload, niri_bus,
+};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::net::UnixListener;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+// The bus is a process-wide singleton; serialize tests that reset or reseed it
+static BUS_LOCK: Mutex<()> = Mutex::new(());
+
+fn bus_guard() -> MutexGuard<'static, ()> {
+    BUS_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn read_line(stream: &std::os::unix::net::UnixStream) -> String {
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line).unwrap();
+    line
+}
+
+#[test]
+fn test_event_stream_reconnects_and_stops() {
+    let _guard = bus_guard();
+    let dir = TempDir::new().unwrap();
+    let socket = dir.path().join("niri.sock");
+    let listener = UnixListener::bind(&socket).unwrap();
+
+    let ipc = NiriIpc::with_socket_path(socket.to_string_lossy());
+    ipc.start_event_stream().unwrap();
+
+    // First connection: handshake, some state, then drop the socket
+    let (mut first, _) = listener.accept().unwrap();
+    first
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    assert_eq!(read_line(&first), "\"EventStream\"\n");
+    first
+        .write_all(
+            b"{\"WorkspacesChanged\":{\"workspaces\":[{\"id\":7,\"idx\":1,\"name\":null,\"is_focused\":true}]}}\n",
+        )
+        .unwrap();
+    first.flush().unwrap();
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while niri_bus().workspaces_snapshot().is_empty() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(niri_bus().workspaces_snapshot().len(), 1);
+    drop(first);
+
+    // Reader must come back and redo the handshake
+    let (second, _) = listener.accept().unwrap();
+    second
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    assert_eq!(read_line(&second), "\"EventStream\"\n");
+
+    // Stale state from the dropped stream is cleared on reconnect
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while !niri_bus().workspaces_snapshot().is_empty() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(niri_bus().workspaces_snapshot().is_empty());
+
+    // stop() shuts the live stream down instead of reconnecting
+    ipc.stop();
+    assert!(ipc.is_stopped());
+    let mut buf = [0u8; 1];
+    let mut second = second;
+    assert_eq!(second.read(&mut buf).unwrap(), 0);
+}
+
+#[test]
+fn test_send_request_sync_reads_reply() {
+    let dir = TempDir::new().unwrap();
+    let socket = dir.path().join("niri.sock");
+    let listener = UnixListener::bind(&socket).unwrap();
+
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let request = read_line(&stream);
+        stream
+            .write_all(b"{\"Ok\":{\"Workspaces\":[{\"id\":1,\"idx\":1}]}}\n")
+            .unwrap();
+        request
+    });
+
+    let ipc = NiriIpc::with_socket_path(socket.to_string_lossy());
+    let reply = ipc.send_request_sync("\"Workspaces\"").unwrap();
+    assert_eq!(server.join().unwrap(), "\"Workspaces\"\n");
+    assert_eq!(reply["Ok"]["Workspaces"][0]["id"], 1);
+}
+
+#[test]
+fn test_send_request_sync_times_out() {
+    let dir = TempDir::new().unwrap();
+    let socket = dir.path().join("niri.sock");
+    let listener = UnixListener::bind(&socket).unwrap();
+
+    // Accept and read the request but never answer
+    let server = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let _ = read_line(&stream);
+        std::thread::sleep(Duration::from_millis(500));
+    });
+
+    let ipc = NiriIpc::with_socket_path(socket.to_string_lossy());
+    let started = Instant::now();
+    let result = ipc.send_request_sync_with_timeout("\"Windows\"", Duration::from_millis(100));
+    assert!(result.is_err());
+    assert!(started.elapsed() < Duration::from_millis(450));
+    server.join().unwrap();
+}
+
+#[test]
+fn test_send_request_sync_rejects_bad_reply() {
+    let dir = TempDir::new().unwrap();
+    let socket = dir.path().join("niri.sock");
+    let listener = UnixListener::bind(&socket).unwrap();
+
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let _ = read_line(&stream);
+        stream.write_all(b"not json\n").unwrap();
+    });
+
+    let ipc = NiriIpc::with_socket_path(socket.to_string_lossy());
+    assert!(ipc.send_request_sync("\"Windows\"").is_err());
+    server.join().unwrap();
+
+    // Nothing listening at all
+    let missing = NiriIpc::with_socket_path(dir.path().join("missing.sock").to_string_lossy());
+    assert!(missing.send_request_sync("\"Windows\"").is_err());
+}
+
+#[test]
+fn test_initial_state_event_envelopes() {
+    let expected = serde_json::json!({ "WorkspacesChanged": { "workspaces": [{ "id": 1 }] } });
+    for reply in [
+        serde_json::json!({ "Ok": { "Workspaces": [{ "id": 1 }] } }),
+        serde_json::json!({ "Workspaces": [{ "id": 1 }] }),
+        serde_json::json!({ "Ok": { "Workspaces": { "workspaces": [{ "id": 1 }] } } }),
+    ] {
+        assert_eq!(
+            initial_state_event("Workspaces", &reply).unwrap(),
+            expected,
+            "{}",
+            reply
+        );
+    }
+    assert_eq!(
+        initial_state_event("Windows", &serde_json::json!({ "Ok": { "Windows": [] } })).unwrap(),
+        serde_json::json!({ "WindowsChanged": { "windows": [] } })
+    );
+
+    // Errors and replies for something else are rejected
+    for reply in [
+        serde_json::json!({ "Err": "unknown request" }),
+        serde_json::json!({ "Ok": { "Outputs": {} } }),
+        serde_json::json!({ "Ok": { "Workspaces": { "workspaces": 3 } } }),
+        serde_json::json!("Handled"),
+    ] {
+        assert!(
+            initial_state_event("Workspaces", &reply).is_err(),
+            "{}",
+            reply
+        );
+    }
+}
+
+#[test]
+fn test_fetch_initial_state_populates_bus() {
+    let _guard = bus_guard();
+    niri_bus().reset();
+    let dir = TempDir::new().unwrap();
+    let socket = dir.path().join("niri.sock");
+    let listener = UnixListener::bind(&socket).unwrap();
+
+    // One connection per request, answered with the two envelope shapes niri has used
+    let server = std::thread::spawn(move || {
+        let mut requests = Vec::new();
+        for _ in 0..2 {
+            let (mut stream, _) = listener.accept().unwrap();
+            let request = read_line(&stream);
+            let reply: &[u8] = match request.trim_end() {
+                "\"Windows\"" => b"{\"Ok\":{\"Windows\":[{\"id\":3,\"title\":\"editor\",\"app_id\":\"foot\",\"workspace_id\":7,\"is_focused\":true}]}}\n",
+                _ => b"{\"Ok\":{\"Workspaces\":{\"workspaces\":[{\"id\":7,\"idx\":1,\"name\":null,\"is_focused\":true,\"output\":\"eDP-1\"}]}}}\n",
+            };
+            stream.write_all(reply).unwrap();
+            requests.push(request);
+        }
+        requests
+    });
+
+    let ipc = NiriIpc::with_socket_path(socket.to_string_lossy());
+    ipc.fetch_initial_state().unwrap();
+    assert_eq!(
+        server.join().unwrap(),
+        vec!["\"Workspaces\"\n", "\"Windows\"\n"]
+    );
+
+    let bus = niri_bus();
+    assert_eq!(bus.current_title(), "editor");
+    assert_eq!(bus.windows_for_workspace(7).len(), 1);
+    let workspaces = bus.workspaces_snapshot();
+    assert_eq!(workspaces.len(), 1);
+    assert_eq!(workspaces[0].id, 7);
+    assert!(workspaces[0].is_focused);
+    assert_eq!(workspaces[0].output.as_deref(), Some("eDP-1"));
+    bus.reset();
+}
+
+#[test]
+fn test_fetch_initial_state_reports_errors() {
+    let dir = TempDir::new().unwrap();
+    let socket = dir.path().join("niri.sock");
+    let listener = UnixListener::bind(&socket).unwrap();
+
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let _ = read_line(&stream);
+        stream.write_all(b"{\"Err\":\"not allowed\"}\n").unwrap();
+    });
+
+    let ipc = NiriIpc::with_socket_path(socket.to_string_lossy());
+    assert!(ipc.fetch_initial_state().is_err());
+    server.join().unwrap();
+}
+
+#[test]
+fn test_workspace_menu_action_payloads() {
+    let v: serde_json::Value = serde_json::from_str(&move_window_to_workspace_payload(
+        None,
+        &WorkspaceRef::Id(42),
+    ))
+    .unwrap();
+    assert_eq!(
+        v,
+        serde_json::json!({"Action":{"MoveWindowToWorkspace":{
+            "window_id": null, "reference": {"Id": 42}, "focus": true
+        }}})
+    );
+
+    let v: serde_json::Value = serde_json::from_str(&close_window_payload(7)).unwrap();
+    assert_eq!(v, serde_json::json!({"Action":{"CloseWindow":{"id":7}}}));
+}
+
+#[test]
+fn test_move_window_to_workspace_payload_per_reference() {
+    assert_eq!(
+        move_window_to_workspace_payload(Some(5), &WorkspaceRef::Index(2)),
+        r#"{"Action":{"MoveWindowToWorkspace":{"focus":true,"reference":{"Index":2},"window_id":5}}}"#
+    );
+    assert_eq!(
+        move_window_to_workspace_payload(Some(5), &WorkspaceRef::Id(17)),
+        r#"{"Action":{"MoveWindowToWorkspace":{"focus":true,"reference":{"Id":17},"window_id":5}}}"#
+    );
+    assert_eq!(
+        move_window_to_workspace_payload(Some(5), &WorkspaceRef::Name("chat \"main\"".into())),
+        r#"{"Action":{"MoveWindowToWorkspace":{"focus":true,"reference":{"Name":"chat \"main\""},"window_id":5}}}"#
+    );
+}
+
+#[test]
+fn test_focus_workspace_payload_per_reference() {
+    assert_eq!(
+        focus_workspace_payload(&WorkspaceRef::Id(17)),
+        r#"{"Action":{"FocusWorkspace":{"reference":{"Id":17}}}}"#
+    );
+    assert_eq!(
+        focus_workspace_payload(&WorkspaceRef::Index(2)),
+        r#"{"Action":{"FocusWorkspace":{"reference":{"Index":2}}}}"#
+    );
+}
+
+#[test]
+fn test_window_action_payloads() {
+    assert_eq!(
+        focus_window_payload(12),
+        r#"{"Action":{"FocusWindow":{"id":12}}}"#
+    );
+    assert_eq!(
+        close_window_payload(12),
+        r#"{"Action":{"CloseWindow":{"id":12}}}"#
+    );
+}
@@ -324,7 +324,22 @@ mod viewport_rendering_tests {
 
 #[cfg(test)]
 mod screen_capture_tests {
-    use niri_bar::modules::viewport::ScreenCapture;
+    use niri_bar::modules::viewport::{
+        CapturePixelFormat, CaptureState, Frame, FrameFit, FrameSource, LiveCapture, ScreenCapture,
+        decode_frame, fit_frame, frame_to_cairo_argb, live_capture, scaled_frame_size,
+        set_live_capture,
+    };
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    struct FakeFrames(Option<Frame>);
+
+    impl FrameSource for FakeFrames {
+        fn current_frame(&self) -> Option<Frame> {
+            self.0.clone()
+        }
+    }
 
     #[test]
     fn test_screen_capture_creation() {
@@ -343,6 +358,139 @@ mod screen_capture_tests {
 
         // Should still have no frame
         assert!(capture.get_current_frame().is_none());
+        assert_eq!(capture.state(), CaptureState::Idle);
+        assert!(capture.current_frame().is_none());
+    }
+
+    #[derive(Default)]
+    struct FakeCapture {
+        starts: Cell<u32>,
+    }
+
+    impl FrameSource for FakeCapture {
+        fn current_frame(&self) -> Option<Frame> {
+            None
+        }
+    }
+
+    impl LiveCapture for FakeCapture {
+        fn ensure_started(self: Rc<Self>) {
+            self.starts.set(self.starts.get() + 1);
+        }
+        fn state(&self) -> CaptureState {
+            CaptureState::Running
+        }
+    }
+
+    #[test]
+    fn test_live_capture_is_shared_and_injectable() {
+        // Without an injected capture, every caller gets the same ScreenCapture
+        let first = live_capture();
+        assert!(Rc::ptr_eq(&first, &live_capture()));
+        assert_eq!(first.state(), CaptureState::Idle);
+
+        let fake = Rc::new(FakeCapture::default());
+        set_live_capture(fake.clone());
+        for _ in 0..3 {
+            live_capture().ensure_started();
+        }
+        assert_eq!(fake.starts.get(), 3);
+        assert_eq!(live_capture().state(), CaptureState::Running);
+    }
+
+    #[test]
+    fn test_fake_frame_source() {
+        let frame = Frame {
+            width: 1,
+            height: 1,
+            data: Arc::new(vec![10, 20, 30, 255]),
+        };
+        assert_eq!(FakeFrames(Some(frame.clone())).current_frame(), Some(frame));
+        assert!(FakeFrames(None).current_frame().is_none());
+    }
+
+    #[test]
+    fn test_fit_frame_covers_canvas() {
+        // 16:9 frame on a wider canvas: width fits, height overflows evenly
+        assert_eq!(
+            fit_frame(160.0, 90.0, 320.0, 90.0),
+            Some(FrameFit {
+                scale: 2.0,
+                offset_x: 0.0,
+                offset_y: -45.0,
+            })
+        );
+        // Same aspect ratio: exact fit, no offsets
+        assert_eq!(
+            fit_frame(1920.0, 1080.0, 96.0, 54.0),
+            Some(FrameFit {
+                scale: 0.05,
+                offset_x: 0.0,
+                offset_y: 0.0,
+            })
+        );
+        // Taller canvas: height fits, width is cropped on both sides
+        let fit = fit_frame(200.0, 100.0, 100.0, 100.0).unwrap();
+        assert_eq!(fit.scale, 1.0);
+        assert_eq!(fit.offset_x, -50.0);
+        assert_eq!(fit.offset_y, 0.0);
+
+        assert_eq!(fit_frame(0.0, 100.0, 100.0, 100.0), None);
+        assert_eq!(fit_frame(100.0, 100.0, 100.0, 0.0), None);
+    }
+
+    #[test]
+    fn test_scaled_frame_size() {
+        assert_eq!(scaled_frame_size(320, 180, 480), (320, 180));
+        assert_eq!(scaled_frame_size(1920, 1080, 480), (480, 270));
+        assert_eq!(scaled_frame_size(2560, 1440, 480), (480, 270));
+        assert_eq!(scaled_frame_size(10000, 1, 480), (480, 1));
+    }
+
+    #[test]
+    fn test_decode_frame_formats_and_stride() {
+        // 2x1 BGRx with 4 bytes of row padding
+        let src = [1, 2, 3, 0, 4, 5, 6, 0, 9, 9, 9, 9];
+        let frame = decode_frame(&src, CapturePixelFormat::Bgrx, 2, 1, 12, 480).unwrap();
+        assert_eq!((frame.width, frame.height), (2, 1));
+        assert_eq!(*frame.data, vec![3, 2, 1, 255, 6, 5, 4, 255]);
+
+        let frame = decode_frame(&[1, 2, 3, 128], CapturePixelFormat::Bgra, 1, 1, 4, 480).unwrap();
+        assert_eq!(*frame.data, vec![3, 2, 1, 128]);
+        let frame = decode_frame(&[1, 2, 3, 0], CapturePixelFormat::Rgbx, 1, 1, 4, 480).unwrap();
+        assert_eq!(*frame.data, vec![1, 2, 3, 255]);
+
+        // Too short for the declared size
+        assert!(decode_frame(&src, CapturePixelFormat::Rgba, 2, 2, 12, 480).is_none());
+        assert!(decode_frame(&src, CapturePixelFormat::Rgba, 0, 1, 12, 480).is_none());
+    }
+
+    #[test]
+    fn test_decode_frame_downscales() {
+        // 4x2 RGBA where each pixel's red channel is its x coordinate
+        let src: Vec<u8> = (0..2)
+            .flat_map(|y| (0..4u8).flat_map(move |x| [x, y, 0, 255]))
+            .collect();
+        let frame = decode_frame(&src, CapturePixelFormat::Rgba, 4, 2, 16, 2).unwrap();
+        assert_eq!((frame.width, frame.height), (2, 1));
+        assert_eq!(*frame.data, vec![0, 0, 0, 255, 2, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_frame_to_cairo_argb() {
+        let frame = Frame {
+            width: 2,
+            height: 1,
+            data: Arc::new(vec![255, 128, 0, 255, 200, 100, 50, 0]),
+        };
+        let argb = frame_to_cairo_argb(&frame);
+        assert_eq!(argb.len(), 8);
+        assert_eq!(
+            u32::from_ne_bytes(argb[0..4].try_into().unwrap()),
+            0xFFFF_8000
+        );
+        // Fully transparent pixels premultiply to zero
+        assert_eq!(u32::from_ne_bytes(argb[4..8].try_into().unwrap()), 0);
     }
 }
 
@@ -630,3 +778,144 @@ mod performance_tests {
         );
     }
 }
+
+#[cfg(test)]
+mod hit_test_tests {
+    use niri_bar::modules::viewport::{DrawnViewport, WindowLayout};
+
+    fn layout(id: i64, x: f64, y: f64, width: f64, height: f64) -> WindowLayout {
+        WindowLayout {
+            id,
+            title: format!("window {}", id),
+            workspace_id: 1,
+            is_focused: false,
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Two columns: window 1 fills the left 60%, windows 2 and 3 stack on the right
+    fn drawn() -> DrawnViewport {
+        DrawnViewport {
+            layouts: vec![
+                layout(1, 0.0, 0.0, 0.6, 1.0),
+                layout(2, 0.6, 0.0, 0.4, 0.5),
+                layout(3, 0.6, 0.5, 0.4, 0.5),
+            ],
+            width: 100.0,
+            height: 20.0,
+        }
+    }
+
+    #[test]
+    fn test_hit_test_maps_pixels_to_windows() {
+        let drawn = drawn();
+        assert_eq!(drawn.hit_test(10.0, 10.0), Some(1));
+        assert_eq!(drawn.hit_test(59.9, 19.9), Some(1));
+        // Column boundary belongs to the right-hand column
+        assert_eq!(drawn.hit_test(60.0, 5.0), Some(2));
+        assert_eq!(drawn.hit_test(80.0, 9.9), Some(2));
+        assert_eq!(drawn.hit_test(80.0, 10.0), Some(3));
+        assert_eq!(drawn.hit_test(99.0, 19.0), Some(3));
+    }
+
+    #[test]
+    fn test_hit_test_misses() {
+        let drawn = drawn();
+        // Outside the canvas
+        assert_eq!(drawn.hit_test(-1.0, 5.0), None);
+        assert_eq!(drawn.hit_test(100.0, 5.0), None);
+        assert_eq!(drawn.hit_test(50.0, 20.0), None);
+
+        // Empty space next to a partial-height window
+        let partial = DrawnViewport {
+            layouts: vec![layout(7, 0.0, 0.0, 1.0, 0.5)],
+            width: 100.0,
+            height: 20.0,
+        };
+        assert_eq!(partial.hit_test(50.0, 15.0), None);
+
+        // Nothing drawn yet
+        assert_eq!(DrawnViewport::default().hit_test(1.0, 1.0), None);
+    }
+
+    #[test]
+    fn test_hit_test_prefers_topmost() {
+        // Overlapping rects: the one drawn last is on top
+        let drawn = DrawnViewport {
+            layouts: vec![
+                layout(1, 0.0, 0.0, 1.0, 1.0),
+                layout(2, 0.25, 0.25, 0.5, 0.5),
+            ],
+            width: 40.0,
+            height: 40.0,
+        };
+        assert_eq!(drawn.hit_test(20.0, 20.0), Some(2));
+        assert_eq!(drawn.hit_test(2.0, 2.0), Some(1));
+    }
+}
+
+#[cfg(test)]
+mod redraw_tests {
+    use niri_bar::modules::viewport::{
+        MIN_VIEWPORT_WIDTH, viewport_event_is_relevant, viewport_target_width,
+    };
+    use niri_bar::niri::NiriEvent;
+
+    #[test]
+    fn test_target_width_follows_aspect_ratio() {
+        // 16:9 workspace on a 27px tall bar
+        assert_eq!(viewport_target_width(27.0, 1920.0, 1080.0, 0), Some(48));
+        // Wide scrolling workspace: three screens across
+        assert_eq!(viewport_target_width(30.0, 5760.0, 1080.0, 80), Some(160));
+        // Rounds to the nearest pixel
+        assert_eq!(viewport_target_width(25.0, 1920.0, 1080.0, 0), Some(44));
+    }
+
+    #[test]
+    fn test_target_width_ignores_small_differences() {
+        assert_eq!(viewport_target_width(27.0, 1920.0, 1080.0, 48), None);
+        assert_eq!(viewport_target_width(27.0, 1920.0, 1080.0, 50), None);
+        assert_eq!(viewport_target_width(27.0, 1920.0, 1080.0, 46), None);
+        assert_eq!(viewport_target_width(27.0, 1920.0, 1080.0, 51), Some(48));
+        assert_eq!(viewport_target_width(27.0, 1920.0, 1080.0, 45), Some(48));
+    }
+
+    #[test]
+    fn test_target_width_minimum_and_unknown_sizes() {
+        // Tall, narrow workspace clamps to the minimum width
+        assert_eq!(
+            viewport_target_width(20.0, 500.0, 2000.0, 0),
+            Some(MIN_VIEWPORT_WIDTH)
+        );
+        assert_eq!(
+            viewport_target_width(20.0, 500.0, 2000.0, MIN_VIEWPORT_WIDTH),
+            None
+        );
+        // Not allocated yet, or no workspace size
+        assert_eq!(viewport_target_width(0.0, 1920.0, 1080.0, 0), None);
+        assert_eq!(viewport_target_width(27.0, 0.0, 1080.0, 0), None);
+        assert_eq!(viewport_target_width(27.0, 1920.0, 0.0, 0), None);
+    }
+
+    #[test]
+    fn test_redraw_only_on_relevant_events() {
+        for event in [
+            NiriEvent::WindowsChanged,
+            NiriEvent::FocusChanged(Some(3)),
+            NiriEvent::FocusChanged(None),
+            NiriEvent::WorkspacesChanged,
+            NiriEvent::WindowLayoutsChanged,
+        ] {
+            assert!(viewport_event_is_relevant(&event), "{:?}", event);
+        }
+        for event in [
+            NiriEvent::KeyboardLayoutChanged,
+            NiriEvent::OverviewToggled(true),
+        ] {
+            assert!(!viewport_event_is_relevant(&event), "{:?}", event);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use niri_bar::config::{ConfigManager, NiriBarConfig};
+use niri_bar::theme::{
+    BASE_THEME, THEMES_DIR, available_themes, resolve_theme_file, theme_search_dirs,
+    theme_watch_paths,
+};
+use pretty_assertions::assert_eq;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn write_css(dir: &Path, name: &str) -> PathBuf {
+    std::fs::create_dir_all(dir).unwrap();
+    let path = dir.join(format!("{}.css", name));
+    std::fs::write(&path, format!("/* {} */", name)).unwrap();
+    path
+}
+
+#[test]
+fn test_theme_search_dir_order() {
+    let dirs = theme_search_dirs(Some(Path::new("/xdg")), Some(Path::new("/home/u")));
+    assert_eq!(
+        dirs,
+        vec![
+            PathBuf::from("/xdg/niri-bar/themes"),
+            PathBuf::from("/home/u/.config/niri-bar/themes"),
+            PathBuf::from(THEMES_DIR),
+        ]
+    );
+
+    // XDG_CONFIG_HOME pointing at ~/.config doesn't list the same dir twice
+    let dirs = theme_search_dirs(
+        Some(Path::new("/home/u/.config")),
+        Some(Path::new("/home/u")),
+    );
+    assert_eq!(
+        dirs,
+        vec![
+            PathBuf::from("/home/u/.config/niri-bar/themes"),
+            PathBuf::from(THEMES_DIR),
+        ]
+    );
+
+    assert_eq!(
+        theme_search_dirs(None, None),
+        vec![PathBuf::from(THEMES_DIR)]
+    );
+}
+
+#[test]
+fn test_resolve_theme_file_first_existing_wins() {
+    let xdg = TempDir::new().unwrap();
+    let home = TempDir::new().unwrap();
+    let bundled = TempDir::new().unwrap();
+    let dirs = vec![
+        xdg.path().join("niri-bar/themes"),
+        home.path().join(".config/niri-bar/themes"),
+        bundled.path().to_path_buf(),
+    ];
+
+    let bundled_base = write_css(bundled.path(), BASE_THEME);
+    let bundled_wombat = write_css(bundled.path(), "wombat");
+    let home_wombat = write_css(&dirs[1], "wombat");
+    let xdg_custom = write_css(&dirs[0], "custom");
+
+    // The user's copy shadows the bundled one, per theme
+    assert_eq!(
+        resolve_theme_file(&dirs, "wombat"),
+        Some(home_wombat.clone())
+    );
+    assert_eq!(
+        resolve_theme_file(&dirs, BASE_THEME),
+        Some(bundled_base.clone())
+    );
+    assert_eq!(
+        resolve_theme_file(&dirs, "custom"),
+        Some(xdg_custom.clone())
+    );
+    assert_eq!(resolve_theme_file(&dirs, "missing"), None);
+
+    let xdg_wombat = write_css(&dirs[0], "wombat");
+    assert_eq!(
+        resolve_theme_file(&dirs, "wombat"),
+        Some(xdg_wombat.clone())
+    );
+    assert_ne!(xdg_wombat, bundled_wombat);
+
+    assert_eq!(available_themes(&dirs), vec!["custom", "wombat"]);
+}
+
+fn config_with_theme(theme: &str) -> NiriBarConfig {
+    let yaml = format!(
+        "application:\n  theme: {}\n  modules: {{}}\n  layouts: {{}}\n  monitors: []\n",
+        theme
+    );
+    ConfigManager::parse_config(yaml.as_bytes()).unwrap()
+}
+
+#[test]
+fn test_theme_watch_paths_follow_active_theme() {
+    let user = TempDir::new().unwrap();
+    let bundled = TempDir::new().unwrap();
+    let dirs = vec![user.path().to_path_buf(), bundled.path().to_path_buf()];
+
+    let base = write_css(bundled.path(), BASE_THEME);
+    let wombat = write_css(bundled.path(), "wombat");
+    write_css(bundled.path(), "dracula");
+    let custom = write_css(user.path(), "custom");
+
+    // Only base plus the active theme, never the other installed themes
+    assert_eq!(
+        theme_watch_paths(&config_with_theme("wombat"), &dirs),
+        vec![base.clone(), wombat]
+    );
+    assert_eq!(
+        theme_watch_paths(&config_with_theme("custom"), &dirs),
+        vec![base.clone(), custom]
+    );
+    // A theme that doesn't exist anywhere leaves just base
+    assert_eq!(
+        theme_watch_paths(&config_with_theme("nope"), &dirs),
+        vec![base]
+    );
+}
+
+#[test]
+fn test_bundled_themes_resolve_from_repo() {
+    let dirs = theme_search_dirs(None, None);
+    assert_eq!(
+        resolve_theme_file(&dirs, BASE_THEME),
+        Some(PathBuf::from("themes/base.css"))
+    );
+    let themes = available_themes(&dirs);
+    for theme in ["wombat", "solarized", "dracula"] {
+        assert!(themes.contains(&theme.to_string()), "missing {}", theme);
+    }
+}
@@ -0,0 +1,97 @@
+use niri_bar::config::{ModuleConfig, ModulePlacement};
+use niri_bar::ui_state::{UiStateKey, UiStateRegistry, restore_state, save_state};
+use pretty_assertions::assert_eq;
+use serde::{Deserialize, Serialize};
+
+/// Stand-in for a module's volatile state
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct ScrollState {
+    offset: f64,
+    hovered: Option<i64>,
+}
+
+#[test]
+fn test_ui_state_key_from_bar_settings() {
+    let mut settings = ModuleConfig::default();
+    // Built outside a bar: nowhere to save state
+    assert_eq!(UiStateKey::for_module("bar.module.clock", &settings), None);
+
+    // User options named like the placement don't stand in for it
+    settings
+        .additional
+        .insert("output".to_string(), "eDP-1".into());
+    settings
+        .additional
+        .insert("column".to_string(), "left".into());
+    assert_eq!(UiStateKey::for_module("bar.module.clock", &settings), None);
+    settings.placement = Some(ModulePlacement {
+        output: "eDP-1".to_string(),
+        column: "left".to_string(),
+    });
+    assert_eq!(
+        UiStateKey::for_module("bar.module.clock", &settings),
+        Some(UiStateKey::new("bar.module.clock", "eDP-1", "left"))
+    );
+}
+
+#[test]
+fn test_ui_state_save_restore_round_trip() {
+    let mut registry = UiStateRegistry::default();
+    let key = UiStateKey::new("bar.module.workspaces", "eDP-1", "left");
+    assert!(registry.is_empty());
+    assert_eq!(registry.restore::<ScrollState>(&key), None);
+
+    let state = ScrollState {
+        offset: 42.5,
+        hovered: Some(3),
+    };
+    registry.save(key.clone(), &state);
+    assert_eq!(registry.restore(&key), Some(state.clone()));
+    // Restoring doesn't consume the state; the next rebuild finds it too
+    assert_eq!(registry.restore(&key), Some(state));
+
+    // Saving again replaces
+    registry.save(key.clone(), &ScrollState::default());
+    assert_eq!(registry.restore(&key), Some(ScrollState::default()));
+    assert_eq!(registry.len(), 1);
+
+    assert!(registry.remove(&key).is_some());
+    assert_eq!(registry.restore::<ScrollState>(&key), None);
+}
+
+#[test]
+fn test_ui_state_is_scoped_to_placement() {
+    let mut registry = UiStateRegistry::default();
+    let left = UiStateKey::new("bar.module.workspaces", "eDP-1", "left");
+    let other_output = UiStateKey::new("bar.module.workspaces", "HDMI-A-1", "left");
+    let other_column = UiStateKey::new("bar.module.workspaces", "eDP-1", "right");
+    let other_module = UiStateKey::new("bar.module.viewport", "eDP-1", "left");
+
+    registry.save(
+        left.clone(),
+        &ScrollState {
+            offset: 1.0,
+            hovered: None,
+        },
+    );
+    for key in [&other_output, &other_column, &other_module] {
+        assert_eq!(registry.restore::<ScrollState>(key), None, "{:?}", key);
+    }
+    assert_eq!(registry.restore::<ScrollState>(&left).unwrap().offset, 1.0);
+
+    // State that no longer matches the module's type is dropped rather than misread
+    registry.save(left.clone(), &"not a scroll state");
+    assert_eq!(registry.restore::<ScrollState>(&left), None);
+}
+
+#[test]
+fn test_ui_state_shared_registry() {
+    let key = UiStateKey::new("bar.module.workspaces", "DP-2", "center");
+    assert_eq!(restore_state::<ScrollState>(&key), None);
+    let state = ScrollState {
+        offset: 0.0,
+        hovered: Some(7),
+    };
+    save_state(key.clone(), &state);
+    assert_eq!(restore_state(&key), Some(state));
+}
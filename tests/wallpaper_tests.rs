@@ -1,10 +1,15 @@
-use niri_bar::config::WallpaperConfig;
+use niri_bar::config::{SwwwOptions, WallpaperConfig};
 use niri_bar::niri::WorkspaceInfo;
-use niri_bar::wallpaper::{DefaultWallpaperExecutor, WallpaperCommandExecutor, WallpaperSwitcher};
+use niri_bar::wallpaper::{
+    DEFAULT_SLIDESHOW_INTERVAL_SECS, Debounce, DefaultWallpaperExecutor, Slideshow,
+    SystemWallpaperExecutor, WALLPAPER_DEBOUNCE, WallpaperCommandExecutor, WallpaperSwitcher,
+    slideshow_images,
+};
 use pretty_assertions::assert_eq;
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
-use tempfile::NamedTempFile;
+use tempfile::{NamedTempFile, TempDir};
 
 /// Mock executor for testing wallpaper switching
 struct MockWallpaperExecutor {
@@ -53,6 +58,8 @@ fn create_test_workspace(idx: i64, name: Option<&str>) -> WorkspaceInfo {
         idx,
         name: name.map(|s| s.to_string()),
         is_focused: true,
+        is_active: true,
+        output: None,
     }
 }
 
@@ -73,6 +80,8 @@ fn test_wallpaper_switcher_with_empty_config() {
         by_workspace: HashMap::new(),
         special_cmd: None,
         swww_options: None,
+        slideshow_interval_secs: None,
+        shuffle: None,
     };
 
     let switcher = WallpaperSwitcher::new_default(config);
@@ -92,6 +101,8 @@ fn test_wallpaper_switcher_with_default_wallpaper() {
         by_workspace: HashMap::new(),
         special_cmd: None,
         swww_options: None,
+        slideshow_interval_secs: None,
+        shuffle: None,
     };
 
     let switcher = WallpaperSwitcher::new_default(config);
@@ -120,6 +131,8 @@ fn test_wallpaper_switcher_with_workspace_specific_wallpaper() {
         by_workspace,
         special_cmd: None,
         swww_options: None,
+        slideshow_interval_secs: None,
+        shuffle: None,
     };
 
     let switcher = WallpaperSwitcher::new_default(config);
@@ -147,6 +160,8 @@ fn test_wallpaper_switcher_with_special_command() {
         by_workspace: HashMap::new(),
         special_cmd: Some("echo 'wallpaper switched to ${current_workspace_image}'".to_string()),
         swww_options: None,
+        slideshow_interval_secs: None,
+        shuffle: None,
     };
 
     let switcher = WallpaperSwitcher::new_default(config);
@@ -179,6 +194,8 @@ fn test_wallpaper_switcher_workspace_resolution_priority() {
         by_workspace,
         special_cmd: None,
         swww_options: None,
+        slideshow_interval_secs: None,
+        shuffle: None,
     };
 
     let switcher = WallpaperSwitcher::new_default(config);
@@ -201,6 +218,8 @@ fn test_wallpaper_switcher_tilde_expansion() {
         by_workspace: HashMap::new(),
         special_cmd: None,
         swww_options: None,
+        slideshow_interval_secs: None,
+        shuffle: None,
     };
 
     let switcher = WallpaperSwitcher::new_default(config);
@@ -218,6 +237,8 @@ fn test_wallpaper_switcher_invalid_paths() {
         by_workspace: HashMap::new(),
         special_cmd: None,
         swww_options: None,
+        slideshow_interval_secs: None,
+        shuffle: None,
     };
 
     let switcher = WallpaperSwitcher::new_default(config);
@@ -244,6 +265,8 @@ fn test_wallpaper_switcher_multiple_workspaces() {
         by_workspace,
         special_cmd: None,
         swww_options: None,
+        slideshow_interval_secs: None,
+        shuffle: None,
     };
 
     let switcher = WallpaperSwitcher::new_default(config);
@@ -273,6 +296,8 @@ fn test_wallpaper_switcher_empty_workspace_name() {
         by_workspace,
         special_cmd: None,
         swww_options: None,
+        slideshow_interval_secs: None,
+        shuffle: None,
     };
 
     let switcher = WallpaperSwitcher::new_default(config);
@@ -294,6 +319,8 @@ fn test_wallpaper_switcher_performance() {
         by_workspace: HashMap::new(),
         special_cmd: None,
         swww_options: None,
+        slideshow_interval_secs: None,
+        shuffle: None,
     };
 
     let switcher = WallpaperSwitcher::new_default(config);
@@ -326,6 +353,8 @@ fn test_wallpaper_switcher_thread_safety() {
         by_workspace: HashMap::new(),
         special_cmd: None,
         swww_options: None,
+        slideshow_interval_secs: None,
+        shuffle: None,
     };
 
     let switcher = Arc::new(Mutex::new(
@@ -370,6 +399,8 @@ fn test_wallpaper_switcher_config_persistence() {
         by_workspace: by_workspace.clone(),
         special_cmd: Some("echo test".to_string()),
         swww_options: None,
+        slideshow_interval_secs: None,
+        shuffle: None,
     };
 
     let switcher = WallpaperSwitcher::new_default(config);
@@ -396,6 +427,8 @@ fn test_wallpaper_switcher_special_command_substitution() {
         by_workspace: HashMap::new(),
         special_cmd: Some("echo 'Setting wallpaper to: ${current_workspace_image}'".to_string()),
         swww_options: None,
+        slideshow_interval_secs: None,
+        shuffle: None,
     };
 
     let switcher = WallpaperSwitcher::new_default(config);
@@ -427,6 +460,8 @@ fn test_wallpaper_switcher_swww_options() {
         by_workspace: HashMap::new(),
         special_cmd: None,
         swww_options: Some(swww_opts),
+        slideshow_interval_secs: None,
+        shuffle: None,
     };
 
     let switcher = WallpaperSwitcher::new_default(config);
@@ -463,6 +498,8 @@ mod property_tests {
                 by_workspace,
                 special_cmd,
                 swww_options: None,
+                slideshow_interval_secs: None,
+                shuffle: None,
             };
 
             let switcher = WallpaperSwitcher::new_default(config);
@@ -485,6 +522,8 @@ mod property_tests {
                 by_workspace: HashMap::new(),
                 special_cmd: None,
                 swww_options: None,
+                slideshow_interval_secs: None,
+                shuffle: None,
             };
 
             let switcher = WallpaperSwitcher::new_default(config);
@@ -506,6 +545,8 @@ fn test_wallpaper_switcher_with_mock_executor() {
         by_workspace: HashMap::new(),
         special_cmd: Some("echo 'Setting wallpaper to: ${current_workspace_image}'".to_string()),
         swww_options: None,
+        slideshow_interval_secs: None,
+        shuffle: None,
     };
 
     let mock_executor = MockWallpaperExecutor::new();
@@ -533,6 +574,8 @@ fn test_wallpaper_switcher_swww_fallback_with_mock() {
         by_workspace: HashMap::new(),
         special_cmd: None, // No special command, should fall back to swww
         swww_options: None,
+        slideshow_interval_secs: None,
+        shuffle: None,
     };
 
     let mut mock_executor = MockWallpaperExecutor::new();
@@ -563,6 +606,8 @@ fn test_wallpaper_switcher_no_providers_with_mock() {
         by_workspace: HashMap::new(),
         special_cmd: None,
         swww_options: None,
+        slideshow_interval_secs: None,
+        shuffle: None,
     };
 
     let mut mock_executor = MockWallpaperExecutor::new();
@@ -580,3 +625,432 @@ fn test_wallpaper_switcher_no_providers_with_mock() {
     let executed_commands = switcher.get_executor().get_executed_commands();
     assert_eq!(executed_commands.len(), 0);
 }
+
+fn wallpapers(default: Option<&str>, by_workspace: &[(&str, &str)]) -> WallpaperConfig {
+    WallpaperConfig {
+        default: default.map(str::to_string),
+        by_workspace: by_workspace
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        special_cmd: None,
+        swww_options: None,
+        slideshow_interval_secs: None,
+        shuffle: None,
+    }
+}
+
+#[test]
+fn test_wallpaper_switcher_per_monitor_selection() {
+    let global = wallpapers(Some("/global.png"), &[("1", "/global-1.png")]);
+    let switcher = WallpaperSwitcher::new(global, MockWallpaperExecutor::new())
+        .with_monitor_configs(vec![
+            (
+                ".*".to_string(),
+                wallpapers(None, &[("2", "/any-monitor-2.png")]),
+            ),
+            (
+                "^eDP-1$".to_string(),
+                wallpapers(Some("/laptop.png"), &[("work", "/laptop-work.png")]),
+            ),
+        ]);
+
+    let mut workspace = create_test_workspace(1, Some("work"));
+    // The most specific monitor wins, by name first
+    switcher.switch_wallpaper_for(&workspace, "eDP-1");
+    // Its default beats the global per-workspace mapping
+    workspace.name = None;
+    switcher.switch_wallpaper_for(&workspace, "eDP-1");
+    // Less specific monitor entry for another output
+    workspace.idx = 2;
+    switcher.switch_wallpaper_for(&workspace, "DP-1");
+    // Nothing set for this workspace on the monitor: fall back to the global mapping
+    workspace.idx = 1;
+    switcher.switch_wallpaper_for(&workspace, "DP-1");
+    // switch_wallpaper uses the workspace's own output
+    workspace.output = Some("eDP-1".to_string());
+    switcher.switch_wallpaper(&workspace);
+
+    assert_eq!(
+        switcher.get_executor().get_executed_commands(),
+        vec![
+            "swww img --outputs eDP-1 /laptop-work.png",
+            "swww img --outputs eDP-1 /laptop.png",
+            "swww img --outputs DP-1 /any-monitor-2.png",
+            "swww img --outputs DP-1 /global-1.png",
+            "swww img --outputs eDP-1 /laptop.png",
+        ]
+    );
+}
+
+#[test]
+fn test_wallpaper_switcher_outputs_flag() {
+    let mut config = wallpapers(Some("/bg.png"), &[]);
+    config.swww_options = Some(SwwwOptions {
+        transition_type: "none".to_string(),
+        ..SwwwOptions::default()
+    });
+    let switcher = WallpaperSwitcher::new(config, MockWallpaperExecutor::new());
+
+    // No output known: all outputs, as before
+    switcher.switch_wallpaper(&create_test_workspace(1, None));
+    switcher.switch_wallpaper_for(&create_test_workspace(1, None), "HDMI-A-1");
+
+    let commands = switcher.get_executor().get_executed_commands();
+    assert!(commands[0].starts_with("swww img --transition-type none"));
+    assert!(!commands[0].contains("--outputs"));
+    assert!(commands[1].starts_with("swww img --outputs HDMI-A-1 --transition-type none"));
+    assert!(commands[1].ends_with(" /bg.png"));
+}
+
+#[test]
+fn test_wallpaper_switcher_from_config() {
+    let yaml = r#"
+application:
+  modules: {}
+  layouts: {}
+  wallpapers:
+    default: /global.png
+    special_cmd: "global-cmd ${current_workspace_image}"
+  monitors:
+    - match: "^DP-1$"
+      wallpapers:
+        default: /dp1.png
+        special_cmd: "dp1-cmd ${current_workspace_image}"
+    - match: ".*"
+"#;
+    let config = niri_bar::config::ConfigManager::parse_config(yaml.as_bytes()).unwrap();
+    let switcher =
+        WallpaperSwitcher::from_config(&config.application, MockWallpaperExecutor::new());
+
+    let workspace = create_test_workspace(3, None);
+    switcher.switch_wallpaper_for(&workspace, "DP-1");
+    switcher.switch_wallpaper_for(&workspace, "eDP-1");
+    // from_config debounces; settle the pending switches
+    assert_eq!(
+        switcher.apply_pending_at(Instant::now() + WALLPAPER_DEBOUNCE),
+        2
+    );
+    assert_eq!(
+        switcher.get_executor().get_executed_commands(),
+        vec!["dp1-cmd /dp1.png", "global-cmd /global.png"]
+    );
+}
+
+#[test]
+fn test_wallpaper_switcher_swww_option_flags() {
+    let mut config = wallpapers(Some("/bg.png"), &[]);
+    config.swww_options = Some(SwwwOptions {
+        transition_type: "wipe".to_string(),
+        transition_duration: 1.5,
+        transition_step: 120,
+        transition_fps: 60,
+        filter: "Nearest".to_string(),
+        resize: "fit".to_string(),
+        fill_color: "1e1e2e".to_string(),
+    });
+    let switcher = WallpaperSwitcher::new(config, MockWallpaperExecutor::new());
+    switcher.switch_wallpaper_for(&create_test_workspace(1, None), "DP-1");
+
+    assert_eq!(
+        switcher.get_executor().get_executed_commands(),
+        vec![
+            "swww img --outputs DP-1 --transition-type wipe --transition-duration 1.5 \
+             --transition-step 120 --transition-fps 60 --filter Nearest --resize fit \
+             --fill-color 1e1e2e /bg.png"
+        ]
+    );
+}
+
+#[test]
+fn test_swww_options_args() {
+    // Defaults match the config defaults; instant transitions skip the duration
+    assert_eq!(
+        SwwwOptions::default().to_args(),
+        vec![
+            "--transition-type",
+            "simple",
+            "--transition-step",
+            "90",
+            "--transition-fps",
+            "30",
+            "--filter",
+            "Lanczos3",
+            "--resize",
+            "crop",
+            "--fill-color",
+            "000000",
+        ]
+    );
+    assert!(SwwwOptions::default().unknown_values().is_empty());
+
+    // Unknown values are reported but still passed through
+    let opts = SwwwOptions {
+        transition_type: "dissolve".to_string(),
+        filter: "lanczos3".to_string(),
+        resize: "cover".to_string(),
+        ..SwwwOptions::default()
+    };
+    assert_eq!(
+        opts.unknown_values(),
+        vec![
+            ("transition-type", "dissolve"),
+            ("filter", "lanczos3"),
+            ("resize", "cover"),
+        ]
+    );
+    let args = opts.to_args();
+    assert_eq!(
+        args[..4],
+        [
+            "--transition-type",
+            "dissolve",
+            "--transition-duration",
+            "1"
+        ]
+    );
+    assert!(args.windows(2).any(|w| w == ["--resize", "cover"]));
+}
+
+#[test]
+fn test_wallpaper_switcher_debounces_rapid_switches() {
+    let config = wallpapers(
+        Some("/default.png"),
+        &[("1", "/one.png"), ("2", "/two.png"), ("3", "/three.png")],
+    );
+    let switcher = WallpaperSwitcher::new(config, MockWallpaperExecutor::new())
+        .with_debounce(WALLPAPER_DEBOUNCE);
+
+    // Scrolling through workspaces: nothing runs while focus keeps moving
+    for idx in [1, 2, 3, 2, 3] {
+        switcher.switch_wallpaper_for(&create_test_workspace(idx, None), "DP-1");
+    }
+    let last_switch = Instant::now();
+    assert!(switcher.has_pending());
+    assert_eq!(switcher.apply_pending_at(last_switch), 0);
+    assert!(switcher.get_executor().get_executed_commands().is_empty());
+
+    // Only the workspace focus settled on is applied, once
+    let settled = last_switch + WALLPAPER_DEBOUNCE;
+    assert_eq!(switcher.apply_pending_at(settled), 1);
+    assert_eq!(switcher.apply_pending_at(settled + WALLPAPER_DEBOUNCE), 0);
+    assert!(!switcher.has_pending());
+    assert_eq!(
+        switcher.get_executor().get_executed_commands(),
+        vec!["swww img --outputs DP-1 /three.png"]
+    );
+}
+
+#[test]
+fn test_wallpaper_switcher_debounce_is_per_output() {
+    let config = wallpapers(
+        Some("/default.png"),
+        &[("1", "/one.png"), ("2", "/two.png")],
+    );
+    let switcher = WallpaperSwitcher::new(config, MockWallpaperExecutor::new())
+        .with_debounce(Duration::from_millis(20));
+
+    switcher.switch_wallpaper_for(&create_test_workspace(1, None), "DP-1");
+    switcher.switch_wallpaper_for(&create_test_workspace(2, None), "eDP-1");
+    std::thread::sleep(Duration::from_millis(40));
+    // A switch on another output doesn't cancel either of them
+    assert_eq!(switcher.apply_pending(), 2);
+
+    assert_eq!(
+        switcher.get_executor().get_executed_commands(),
+        vec![
+            "swww img --outputs DP-1 /one.png",
+            "swww img --outputs eDP-1 /two.png",
+        ]
+    );
+}
+
+#[test]
+fn test_debounce_restarts_on_new_value() {
+    let start = Instant::now();
+    let ms = |n| start + Duration::from_millis(n);
+    let mut debounce = Debounce::new(Duration::from_millis(100));
+
+    debounce.request(1, ms(0));
+    debounce.request(2, ms(80));
+    // 1 was superseded; 2 has only waited 20ms
+    assert_eq!(debounce.take_due(ms(100)), None);
+    // Repeating the pending value keeps its original start
+    debounce.request(2, ms(150));
+    assert_eq!(debounce.take_due(ms(180)), Some(2));
+    assert_eq!(debounce.take_due(ms(500)), None);
+
+    debounce.request(3, ms(200));
+    debounce.cancel();
+    assert!(!debounce.is_pending());
+    assert_eq!(debounce.take_due(ms(500)), None);
+}
+
+/// Slideshow directory with three images plus files that aren't images
+fn slideshow_dir() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    for name in ["b.jpg", "a.png", "c.WEBP", "notes.txt", "README"] {
+        std::fs::write(dir.path().join(name), b"").unwrap();
+    }
+    std::fs::create_dir(dir.path().join("nested.png")).unwrap();
+    dir
+}
+
+#[test]
+fn test_slideshow_images_enumeration() {
+    let dir = slideshow_dir();
+    let names: Vec<String> = slideshow_images(dir.path())
+        .iter()
+        .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(names, vec!["a.png", "b.jpg", "c.WEBP"]);
+
+    assert!(slideshow_images(&dir.path().join("missing")).is_empty());
+}
+
+#[test]
+fn test_slideshow_next_image() {
+    let images: Vec<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+
+    // In order, wrapping around
+    let mut slideshow = Slideshow::new(images.clone(), false, 0);
+    let shown: Vec<String> = (0..5)
+        .map(|_| slideshow.next_image().unwrap().to_string())
+        .collect();
+    assert_eq!(shown, vec!["a", "b", "c", "a", "b"]);
+
+    assert_eq!(Slideshow::new(Vec::new(), true, 1).next_image(), None);
+}
+
+#[test]
+fn test_slideshow_shuffle_is_seeded() {
+    let images: Vec<String> = (0..8).map(|i| format!("{}.png", i)).collect();
+    let run = |seed: u64| {
+        let mut slideshow = Slideshow::new(images.clone(), true, seed);
+        (0..24)
+            .map(|_| slideshow.next_image().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    // Same seed, same order; another seed, another order
+    assert_eq!(run(42), run(42));
+    assert_ne!(run(42), run(7));
+
+    let shown = run(42);
+    for pass in shown.chunks(images.len()) {
+        // Every pass shows each image once
+        let mut sorted = pass.to_vec();
+        sorted.sort();
+        assert_eq!(sorted, images);
+    }
+    // No image twice in a row, including across passes
+    assert!(shown.windows(2).all(|w| w[0] != w[1]));
+}
+
+#[test]
+fn test_wallpaper_switcher_slideshow_rotation() {
+    let dir = slideshow_dir();
+    let dir_path = dir.path().to_string_lossy().into_owned();
+    let mut config = wallpapers(Some("/default.png"), &[("1", &dir_path)]);
+    config.slideshow_interval_secs = Some(60);
+    let switcher = WallpaperSwitcher::new(config, MockWallpaperExecutor::new());
+    let interval = Duration::from_secs(60);
+
+    // Focusing the workspace shows the first image right away
+    switcher.switch_wallpaper_for(&create_test_workspace(1, None), "DP-1");
+    let started = Instant::now();
+    assert!(switcher.has_slideshow());
+    assert_eq!(switcher.advance_slideshows_at(started), 0);
+    assert_eq!(switcher.advance_slideshows_at(started + interval), 1);
+    // Refocusing the same workspace doesn't restart it
+    switcher.switch_wallpaper_for(&create_test_workspace(1, None), "DP-1");
+    assert_eq!(switcher.advance_slideshows_at(started + interval * 2), 1);
+
+    // Leaving the workspace stops the rotation
+    switcher.switch_wallpaper_for(&create_test_workspace(2, None), "DP-1");
+    assert!(!switcher.has_slideshow());
+    assert_eq!(switcher.advance_slideshows_at(started + interval * 10), 0);
+
+    let image = |name: &str| format!("swww img --outputs DP-1 {}/{}", dir_path, name);
+    assert_eq!(
+        switcher.get_executor().get_executed_commands(),
+        vec![
+            image("a.png"),
+            image("b.jpg"),
+            image("c.WEBP"),
+            "swww img --outputs DP-1 /default.png".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn test_wallpaper_switcher_slideshow_is_per_output() {
+    let dir = slideshow_dir();
+    let dir_path = dir.path().to_string_lossy().into_owned();
+    let config = wallpapers(Some("/default.png"), &[("1", &dir_path)]);
+    let switcher = WallpaperSwitcher::new(config, MockWallpaperExecutor::new());
+    // Default interval applies when none is configured
+    let interval = Duration::from_secs(DEFAULT_SLIDESHOW_INTERVAL_SECS);
+
+    switcher.switch_wallpaper_for(&create_test_workspace(1, None), "DP-1");
+    let started = Instant::now();
+    // Another output switching doesn't stop DP-1, which still shows workspace 1
+    switcher.switch_wallpaper_for(&create_test_workspace(3, None), "eDP-1");
+    assert!(switcher.has_slideshow());
+    assert_eq!(switcher.advance_slideshows_at(started + interval), 1);
+    // DP-1 moving to another workspace does
+    switcher.switch_wallpaper_for(&create_test_workspace(2, None), "DP-1");
+    assert!(!switcher.has_slideshow());
+    assert_eq!(switcher.advance_slideshows_at(started + interval * 2), 0);
+}
+
+#[test]
+fn test_wallpaper_switcher_swaybg_replaces_only_its_output() {
+    let mut executor = MockWallpaperExecutor::new();
+    executor.existing_paths.remove("/usr/bin/swww");
+    let switcher = WallpaperSwitcher::new(wallpapers(Some("/bg.png"), &[]), executor);
+
+    switcher.switch_wallpaper_for(&create_test_workspace(1, None), "DP-1");
+    switcher.switch_wallpaper(&create_test_workspace(1, None));
+
+    assert_eq!(
+        switcher.get_executor().get_executed_commands(),
+        vec![
+            "pkill -f '^/usr/bin/swaybg -o DP-1 '; /usr/bin/swaybg -o DP-1 -m fill -i /bg.png",
+            "pkill swaybg; /usr/bin/swaybg -m fill -i /bg.png",
+        ]
+    );
+}
+
+#[test]
+fn test_wallpaper_switcher_has_wallpapers() {
+    let empty = WallpaperSwitcher::new(WallpaperConfig::default(), MockWallpaperExecutor::new());
+    assert!(!empty.has_wallpapers());
+
+    let global = WallpaperSwitcher::new(
+        wallpapers(None, &[("1", "/one.png")]),
+        MockWallpaperExecutor::new(),
+    );
+    assert!(global.has_wallpapers());
+
+    let monitor_only =
+        WallpaperSwitcher::new(WallpaperConfig::default(), MockWallpaperExecutor::new())
+            .with_monitor_configs(vec![("DP-1".to_string(), wallpapers(Some("/dp.png"), &[]))]);
+    assert!(monitor_only.has_wallpapers());
+}
+
+#[test]
+fn test_system_wallpaper_executor_runs_commands() {
+    let dir = TempDir::new().unwrap();
+    let marker = dir.path().join("ran");
+    SystemWallpaperExecutor
+        .execute_command(&format!("touch '{}'", marker.display()))
+        .unwrap();
+
+    // The command runs in the background
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !marker.exists() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(marker.exists());
+}
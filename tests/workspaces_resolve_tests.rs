@@ -27,11 +27,13 @@ fn test_resolve_workspace_wallpaper_by_idx() {
         idx: 2,
         name: Some("dev".into()),
         is_focused: false,
+        is_active: false,
+        output: None,
     };
     let mut map: HashMap<String, String> = HashMap::new();
     map.insert("2".into(), "/tmp/idx.png".into());
     let def = Some("/tmp/default.png".into());
-    let out = niri_bar::modules::workspaces::resolve_workspace_wallpaper(&ws, &map, &def);
+    let out = niri_bar::modules::workspaces::resolve_workspace_wallpaper(&ws, &map, &[], &def);
     assert_eq!(out.as_deref(), Some("/tmp/idx.png"));
 }
 
@@ -42,11 +44,13 @@ fn test_resolve_workspace_wallpaper_by_name() {
         idx: 9,
         name: Some("video".into()),
         is_focused: false,
+        is_active: false,
+        output: None,
     };
     let mut map: HashMap<String, String> = HashMap::new();
     map.insert("video".into(), "/tmp/name.png".into());
     let def = Some("/tmp/default.png".into());
-    let out = niri_bar::modules::workspaces::resolve_workspace_wallpaper(&ws, &map, &def);
+    let out = niri_bar::modules::workspaces::resolve_workspace_wallpaper(&ws, &map, &[], &def);
     assert_eq!(out.as_deref(), Some("/tmp/name.png"));
 }
 
@@ -57,9 +61,68 @@ fn test_resolve_workspace_wallpaper_default() {
         idx: 7,
         name: None,
         is_focused: false,
+        is_active: false,
+        output: None,
     };
     let map: HashMap<String, String> = HashMap::new();
     let def = Some("/tmp/default.png".into());
-    let out = niri_bar::modules::workspaces::resolve_workspace_wallpaper(&ws, &map, &def);
+    let out = niri_bar::modules::workspaces::resolve_workspace_wallpaper(&ws, &map, &[], &def);
     assert_eq!(out.as_deref(), Some("/tmp/default.png"));
 }
+
+#[test]
+fn test_resolve_workspace_wallpaper_precedence() {
+    use niri_bar::config::ModuleConfig;
+    use niri_bar::modules::workspaces::{compile_wallpaper_patterns, resolve_workspace_wallpaper};
+
+    let ws = |idx: i64, name: Option<&str>| WorkspaceInfo {
+        id: 20 + idx,
+        idx,
+        name: name.map(Into::into),
+        is_focused: false,
+        is_active: false,
+        output: None,
+    };
+    let settings: ModuleConfig = serde_yaml::from_str(
+        r#"
+wallpapers: { "3": /tmp/idx.png, dev-rust: /tmp/name.png }
+wallpaper_patterns:
+  - { pattern: "^dev-.*", path: /tmp/dev.png }
+  - { pattern: "(", path: /tmp/broken.png }
+  - { pattern: "dev|chat", path: /tmp/later.png }
+"#,
+    )
+    .unwrap();
+    // The invalid pattern is dropped, order is kept
+    let patterns = compile_wallpaper_patterns(&settings);
+    assert_eq!(
+        patterns.iter().map(|p| p.path.as_str()).collect::<Vec<_>>(),
+        vec!["/tmp/dev.png", "/tmp/later.png"]
+    );
+    let map = settings.wallpapers.unwrap();
+    let def = Some("/tmp/default.png".to_string());
+    let resolve = |idx, name| resolve_workspace_wallpaper(&ws(idx, name), &map, &patterns, &def);
+
+    // Exact index beats an exact name, which beats a pattern
+    assert_eq!(
+        resolve(3, Some("dev-rust")).as_deref(),
+        Some("/tmp/idx.png")
+    );
+    assert_eq!(
+        resolve(4, Some("dev-rust")).as_deref(),
+        Some("/tmp/name.png")
+    );
+    // First matching pattern wins
+    assert_eq!(resolve(4, Some("dev-go")).as_deref(), Some("/tmp/dev.png"));
+    assert_eq!(resolve(4, Some("chat")).as_deref(), Some("/tmp/later.png"));
+    // No match, or no name to match, falls back to the default
+    assert_eq!(
+        resolve(4, Some("music")).as_deref(),
+        Some("/tmp/default.png")
+    );
+    assert_eq!(resolve(4, None).as_deref(), Some("/tmp/default.png"));
+    assert_eq!(
+        resolve_workspace_wallpaper(&ws(4, Some("music")), &map, &patterns, &None),
+        None
+    );
+}